@@ -140,6 +140,19 @@ impl ConnectedPoint {
         .any(|p| p == Protocol::P2pCircuit)
     }
 
+    /// Returns true if we dialed the connection but act as the listener on it, i.e. the
+    /// connection was established via a dial with [`Endpoint::Listener`] as `role_override`, as
+    /// done for hole punching.
+    pub fn is_role_overridden(&self) -> bool {
+        matches!(
+            self,
+            ConnectedPoint::Dialer {
+                role_override: Endpoint::Listener,
+                ..
+            }
+        )
+    }
+
     /// Returns the address of the remote stored in this struct.
     ///
     /// For `Dialer`, this returns `address`. For `Listener`, this returns `send_back_addr`.
@@ -210,6 +210,26 @@ where
         }
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        use TransportError::*;
+        match self {
+            Either::Left(a) => match a.dial_from_port(addr, port) {
+                Ok(connec) => Ok(EitherFuture::First(connec)),
+                Err(MultiaddrNotSupported(addr)) => Err(MultiaddrNotSupported(addr)),
+                Err(Other(err)) => Err(Other(Either::Left(err))),
+            },
+            Either::Right(b) => match b.dial_from_port(addr, port) {
+                Ok(connec) => Ok(EitherFuture::Second(connec)),
+                Err(MultiaddrNotSupported(addr)) => Err(MultiaddrNotSupported(addr)),
+                Err(Other(err)) => Err(Other(Either::Right(err))),
+            },
+        }
+    }
+
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
         match self {
             Either::Left(a) => a.address_translation(server, observed),
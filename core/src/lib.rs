@@ -49,6 +49,7 @@ mod translation;
 
 pub mod connection;
 pub mod either;
+pub mod multiaddr_ext;
 pub mod muxing;
 pub mod peer_record;
 pub mod signed_envelope;
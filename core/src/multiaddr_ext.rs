@@ -0,0 +1,496 @@
+//! Classification of [`Multiaddr`]s, e.g. by the transport or the kind of IP address they use,
+//! and helpers to manipulate their `/p2p` components.
+//!
+//! See [`MultiaddrExt`] for ready-made predicates and [`MultiaddrPattern`] for matching the
+//! protocol stack of an address.
+
+use crate::transport::global_only::{ipv4_global, ipv6_global};
+use libp2p_identity::PeerId;
+use multiaddr::{Multiaddr, Protocol};
+
+/// The kind of transport required to dial or listen on a [`Multiaddr`].
+///
+/// See [`required_transport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum TransportKind {
+    /// Plain TCP, e.g. `/ip4/127.0.0.1/tcp/4001`.
+    Tcp,
+    /// QUIC, e.g. `/ip4/127.0.0.1/udp/4001/quic-v1`.
+    Quic,
+    /// WebSocket, with or without TLS, e.g. `/ip4/127.0.0.1/tcp/4001/ws`.
+    WebSocket,
+    /// WebTransport, e.g. `/ip4/127.0.0.1/udp/4001/quic-v1/webtransport`.
+    WebTransport,
+    /// WebRTC direct, e.g. `/ip4/127.0.0.1/udp/4001/webrtc-direct`.
+    WebRtcDirect,
+    /// A circuit relay, e.g. `/ip4/127.0.0.1/tcp/4001/p2p/<relay>/p2p-circuit`.
+    Relay,
+    /// The in-memory transport, e.g. `/memory/1234`.
+    Memory,
+    /// Unix domain sockets, e.g. `/unix/tmp%2Fp2p.sock`.
+    Unix,
+}
+
+/// Returns the kind of transport required to dial or listen on the given [`Multiaddr`].
+///
+/// The protocol stack is walked from the outermost to the innermost protocol, each protocol
+/// layered on top of another one taking precedence, e.g. `/ip4/127.0.0.1/tcp/4001/ws` requires
+/// [`TransportKind::WebSocket`], not [`TransportKind::Tcp`].
+///
+/// Returns [`None`] if the address does not contain any protocol a transport is known for.
+pub fn required_transport(addr: &Multiaddr) -> Option<TransportKind> {
+    addr.iter().fold(None, |kind, protocol| {
+        let layer = match protocol {
+            Protocol::Tcp(_) => TransportKind::Tcp,
+            Protocol::QuicV1 => TransportKind::Quic,
+            Protocol::Ws(_) | Protocol::Wss(_) => TransportKind::WebSocket,
+            Protocol::WebTransport => TransportKind::WebTransport,
+            Protocol::WebRTCDirect => TransportKind::WebRtcDirect,
+            Protocol::P2pCircuit => TransportKind::Relay,
+            Protocol::Memory(_) => TransportKind::Memory,
+            Protocol::Unix(_) => TransportKind::Unix,
+            _ => return kind,
+        };
+
+        Some(layer)
+    })
+}
+
+/// Predicates over and helpers for a [`Multiaddr`].
+///
+/// The IP related predicates only consider addresses starting with an `/ip4` or `/ip6` protocol,
+/// i.e. they are `false` for `/dns` addresses.
+pub trait MultiaddrExt {
+    /// Whether the address is relayed, i.e. contains a `/p2p-circuit` protocol.
+    fn is_relayed(&self) -> bool;
+
+    /// Whether the address starts with a loopback IP address.
+    fn is_loopback(&self) -> bool;
+
+    /// Whether the address starts with a private or link-local IP address, e.g. `192.168.0.1`,
+    /// `169.254.0.1`, `fd00::1` or `fe80::1`.
+    fn is_private_ip(&self) -> bool;
+
+    /// Whether the address starts with a globally reachable IP address.
+    fn is_public_ip(&self) -> bool;
+
+    /// The kind of transport required to dial or listen on the address, see
+    /// [`required_transport`].
+    fn transport_kind(&self) -> Option<TransportKind>;
+
+    /// The [`PeerId`] of the trailing `/p2p` component, i.e. of the peer reached by dialing the
+    /// address.
+    ///
+    /// The `/p2p` component of the relay in a relayed address is not considered, e.g. this is
+    /// [`None`] for `/ip4/1.1.1.1/tcp/4001/p2p/<relay>/p2p-circuit`.
+    fn last_peer_id(&self) -> Option<PeerId>;
+
+    /// The address without its trailing `/p2p` component, if any.
+    ///
+    /// Like [`MultiaddrExt::last_peer_id`], this leaves the `/p2p` component of the relay in a
+    /// relayed address untouched, such that a `/p2p` component can be safely appended to the
+    /// result.
+    fn without_p2p(&self) -> Multiaddr;
+}
+
+impl MultiaddrExt for Multiaddr {
+    fn is_relayed(&self) -> bool {
+        self.iter().any(|p| p == Protocol::P2pCircuit)
+    }
+
+    fn is_loopback(&self) -> bool {
+        match self.iter().next() {
+            Some(Protocol::Ip4(a)) => a.is_loopback(),
+            Some(Protocol::Ip6(a)) => a.is_loopback(),
+            _ => false,
+        }
+    }
+
+    fn is_private_ip(&self) -> bool {
+        match self.iter().next() {
+            Some(Protocol::Ip4(a)) => a.is_private() || a.is_link_local(),
+            Some(Protocol::Ip6(a)) => {
+                ipv6_global::is_unique_local(a) || ipv6_global::is_unicast_link_local(a)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_public_ip(&self) -> bool {
+        match self.iter().next() {
+            Some(Protocol::Ip4(a)) => ipv4_global::is_global(a),
+            Some(Protocol::Ip6(a)) => ipv6_global::is_global(a),
+            _ => false,
+        }
+    }
+
+    fn transport_kind(&self) -> Option<TransportKind> {
+        required_transport(self)
+    }
+
+    fn last_peer_id(&self) -> Option<PeerId> {
+        match self.iter().last() {
+            Some(Protocol::P2p(peer_id)) => Some(peer_id),
+            _ => None,
+        }
+    }
+
+    fn without_p2p(&self) -> Multiaddr {
+        let mut addr = self.clone();
+        if let Some(Protocol::P2p(_)) = addr.iter().last() {
+            addr.pop();
+        }
+
+        addr
+    }
+}
+
+/// A pattern over the leading protocols of a [`Multiaddr`].
+///
+/// An address matches if its protocol stack starts with the protocols of the pattern, in order.
+/// Any protocols following them are ignored, e.g. a trailing `/p2p` or `/certhash`.
+///
+/// ```
+/// # use libp2p_core::multiaddr::{Multiaddr, Protocol};
+/// # use libp2p_core::multiaddr_ext::MultiaddrPattern;
+/// let webrtc_direct = MultiaddrPattern::new()
+///     .ip()
+///     .udp()
+///     .protocol(Protocol::WebRTCDirect);
+///
+/// let addr: Multiaddr = "/ip6/::1/udp/4001/webrtc-direct".parse().unwrap();
+/// assert!(webrtc_direct.matches(&addr));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultiaddrPattern {
+    matchers: Vec<Matcher>,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Ip,
+    Ip4,
+    Ip6,
+    Dns,
+    Tcp,
+    Udp,
+    Protocol(Protocol<'static>),
+}
+
+impl MultiaddrPattern {
+    /// Creates an empty pattern, matching any address.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches an `/ip4` or `/ip6` protocol.
+    pub fn ip(self) -> Self {
+        self.with(Matcher::Ip)
+    }
+
+    /// Matches an `/ip4` protocol.
+    pub fn ip4(self) -> Self {
+        self.with(Matcher::Ip4)
+    }
+
+    /// Matches an `/ip6` protocol.
+    pub fn ip6(self) -> Self {
+        self.with(Matcher::Ip6)
+    }
+
+    /// Matches a `/dns`, `/dns4`, `/dns6` or `/dnsaddr` protocol.
+    pub fn dns(self) -> Self {
+        self.with(Matcher::Dns)
+    }
+
+    /// Matches a `/tcp` protocol with any port.
+    pub fn tcp(self) -> Self {
+        self.with(Matcher::Tcp)
+    }
+
+    /// Matches a `/udp` protocol with any port.
+    pub fn udp(self) -> Self {
+        self.with(Matcher::Udp)
+    }
+
+    /// Matches the given protocol, ignoring its value, e.g. `Protocol::Memory(0)` matches any
+    /// `/memory` protocol.
+    pub fn protocol(self, protocol: Protocol<'static>) -> Self {
+        self.with(Matcher::Protocol(protocol))
+    }
+
+    /// Whether the given address matches this pattern.
+    pub fn matches(&self, addr: &Multiaddr) -> bool {
+        let mut protocols = addr.iter();
+
+        self.matchers
+            .iter()
+            .all(|matcher| protocols.next().is_some_and(|p| matcher.matches(&p)))
+    }
+
+    fn with(mut self, matcher: Matcher) -> Self {
+        self.matchers.push(matcher);
+        self
+    }
+}
+
+impl Matcher {
+    fn matches(&self, protocol: &Protocol<'_>) -> bool {
+        match (self, protocol) {
+            (Matcher::Ip, Protocol::Ip4(_) | Protocol::Ip6(_)) => true,
+            (Matcher::Ip4, Protocol::Ip4(_)) => true,
+            (Matcher::Ip6, Protocol::Ip6(_)) => true,
+            (
+                Matcher::Dns,
+                Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_),
+            ) => true,
+            (Matcher::Tcp, Protocol::Tcp(_)) => true,
+            (Matcher::Udp, Protocol::Udp(_)) => true,
+            (Matcher::Protocol(expected), protocol) => same_variant(expected, protocol),
+            _ => false,
+        }
+    }
+}
+
+fn same_variant<'a>(a: &Protocol<'a>, b: &Protocol<'a>) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER_ID: &str = "12D3KooWEz3ZnZJ7D4wTqVSDUdqDRLXb6Ds9PbNTx7WnNVjKw3YG";
+    const CERTHASH: &str = "uEiDikp5KVUgkLta1EjUN-IKbHk-dUBg8VzKgf5nXxLK46w";
+
+    fn addr(addr: &str) -> Multiaddr {
+        addr.replace("<peer>", PEER_ID)
+            .replace("<certhash>", CERTHASH)
+            .parse()
+            .unwrap()
+    }
+
+    fn kind(a: &str) -> Option<TransportKind> {
+        required_transport(&addr(a))
+    }
+
+    #[test]
+    fn tcp() {
+        assert_eq!(kind("/ip4/127.0.0.1/tcp/4001"), Some(TransportKind::Tcp));
+        assert_eq!(
+            kind("/dns4/example.com/tcp/4001/p2p/<peer>"),
+            Some(TransportKind::Tcp)
+        );
+    }
+
+    #[test]
+    fn websocket() {
+        assert_eq!(
+            kind("/ip4/127.0.0.1/tcp/4001/ws"),
+            Some(TransportKind::WebSocket)
+        );
+        assert_eq!(
+            kind("/dns4/example.com/tcp/443/wss"),
+            Some(TransportKind::WebSocket)
+        );
+        assert_eq!(
+            kind("/dns4/example.com/tcp/443/tls/ws"),
+            Some(TransportKind::WebSocket)
+        );
+    }
+
+    #[test]
+    fn quic() {
+        assert_eq!(
+            kind("/ip4/127.0.0.1/udp/4001/quic-v1"),
+            Some(TransportKind::Quic)
+        );
+        assert_eq!(
+            kind("/ip4/127.0.0.1/udp/4001/quic-v1/webtransport/certhash/<certhash>"),
+            Some(TransportKind::WebTransport)
+        );
+    }
+
+    #[test]
+    fn webrtc_direct() {
+        assert_eq!(
+            kind("/ip4/127.0.0.1/udp/4001/webrtc-direct/certhash/<certhash>"),
+            Some(TransportKind::WebRtcDirect)
+        );
+    }
+
+    #[test]
+    fn relay() {
+        assert_eq!(
+            kind("/ip4/127.0.0.1/tcp/4001/p2p/<peer>/p2p-circuit"),
+            Some(TransportKind::Relay)
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(kind("/ip4/127.0.0.1"), None);
+        assert_eq!(kind("/ip4/127.0.0.1/udp/4001"), None);
+    }
+
+    #[test]
+    fn is_relayed() {
+        assert!(addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>/p2p-circuit").is_relayed());
+        assert!(
+            addr("/dns4/relay.example.com/tcp/443/wss/p2p/<peer>/p2p-circuit/p2p/<peer>")
+                .is_relayed()
+        );
+        assert!(!addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>").is_relayed());
+    }
+
+    #[test]
+    fn is_loopback() {
+        assert!(addr("/ip4/127.0.0.1/tcp/4001").is_loopback());
+        assert!(addr("/ip6/::1/udp/4001/quic-v1").is_loopback());
+        assert!(!addr("/ip4/192.168.1.1/tcp/4001").is_loopback());
+        assert!(!addr("/dns4/localhost/tcp/4001").is_loopback());
+        assert!(!addr("/memory/1234").is_loopback());
+    }
+
+    #[test]
+    fn is_private_ip() {
+        for private in [
+            "/ip4/10.0.0.1/tcp/4001",
+            "/ip4/172.16.0.1/tcp/4001",
+            "/ip4/192.168.1.1/udp/4001/quic-v1",
+            "/ip4/169.254.1.1/tcp/4001",
+            "/ip6/fd00::1/tcp/4001",
+            "/ip6/fe80::1/udp/4001/webrtc-direct/certhash/<certhash>",
+        ] {
+            assert!(addr(private).is_private_ip(), "{private}");
+            assert!(!addr(private).is_public_ip(), "{private}");
+        }
+
+        assert!(!addr("/ip4/127.0.0.1/tcp/4001").is_private_ip());
+        assert!(!addr("/ip4/1.1.1.1/tcp/4001").is_private_ip());
+        assert!(!addr("/dns4/example.com/tcp/4001").is_private_ip());
+    }
+
+    #[test]
+    fn is_public_ip() {
+        for public in [
+            "/ip4/1.1.1.1/tcp/4001",
+            "/ip4/8.8.8.8/udp/4001/quic-v1/webtransport/certhash/<certhash>/p2p/<peer>",
+            "/ip6/2606:4700:4700::1111/tcp/443/wss",
+        ] {
+            assert!(addr(public).is_public_ip(), "{public}");
+        }
+
+        for non_public in [
+            "/ip4/127.0.0.1/tcp/4001",
+            "/ip4/0.0.0.0/tcp/4001",
+            "/ip4/100.64.0.1/tcp/4001",
+            "/ip4/192.0.2.1/tcp/4001",
+            "/ip4/255.255.255.255/udp/4001",
+            "/ip6/::/tcp/4001",
+            "/ip6/::1/tcp/4001",
+            "/ip6/2001:db8::1/tcp/4001",
+            "/dns4/example.com/tcp/4001",
+        ] {
+            assert!(!addr(non_public).is_public_ip(), "{non_public}");
+        }
+    }
+
+    #[test]
+    fn transport_kind() {
+        assert_eq!(
+            addr("/dns/example.com/tcp/443/tls/ws/p2p/<peer>").transport_kind(),
+            Some(TransportKind::WebSocket)
+        );
+        assert_eq!(
+            addr("/memory/1234").transport_kind(),
+            Some(TransportKind::Memory)
+        );
+    }
+
+    #[test]
+    fn last_peer_id() {
+        let peer_id = PEER_ID.parse::<PeerId>().unwrap();
+
+        assert_eq!(
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>").last_peer_id(),
+            Some(peer_id)
+        );
+        assert_eq!(
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>/p2p-circuit/p2p/<peer>").last_peer_id(),
+            Some(peer_id)
+        );
+        assert_eq!(
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>/p2p-circuit").last_peer_id(),
+            None
+        );
+        assert_eq!(addr("/ip4/1.1.1.1/tcp/4001").last_peer_id(), None);
+    }
+
+    #[test]
+    fn without_p2p() {
+        assert_eq!(
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>").without_p2p(),
+            addr("/ip4/1.1.1.1/tcp/4001")
+        );
+        assert_eq!(
+            addr("/ip4/1.1.1.1/tcp/4001").without_p2p(),
+            addr("/ip4/1.1.1.1/tcp/4001")
+        );
+        assert_eq!(
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>/p2p-circuit/p2p/<peer>").without_p2p(),
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>/p2p-circuit")
+        );
+        assert_eq!(
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>/p2p-circuit").without_p2p(),
+            addr("/ip4/1.1.1.1/tcp/4001/p2p/<peer>/p2p-circuit")
+        );
+    }
+
+    #[test]
+    fn pattern_matches_leading_protocols() {
+        let webrtc_direct = MultiaddrPattern::new()
+            .ip()
+            .udp()
+            .protocol(Protocol::WebRTCDirect);
+
+        assert!(webrtc_direct.matches(&addr("/ip4/1.1.1.1/udp/4001/webrtc-direct")));
+        assert!(webrtc_direct.matches(&addr(
+            "/ip6/::1/udp/4001/webrtc-direct/certhash/<certhash>/p2p/<peer>"
+        )));
+        assert!(!webrtc_direct.matches(&addr("/ip4/1.1.1.1/udp/4001/quic-v1")));
+        assert!(!webrtc_direct.matches(&addr("/ip4/1.1.1.1/udp/4001")));
+        assert!(!webrtc_direct.matches(&addr("/dns4/example.com/udp/4001/webrtc-direct")));
+    }
+
+    #[test]
+    fn pattern_ignores_protocol_values() {
+        let memory = MultiaddrPattern::new().protocol(Protocol::Memory(0));
+        let wss = MultiaddrPattern::new()
+            .dns()
+            .tcp()
+            .protocol(Protocol::Wss("/".into()));
+
+        assert!(memory.matches(&addr("/memory/1234")));
+        assert!(!memory.matches(&addr("/ip4/127.0.0.1/tcp/4001")));
+        assert!(wss.matches(&addr("/dnsaddr/example.com/tcp/443/wss/p2p/<peer>")));
+        assert!(!wss.matches(&addr("/dns4/example.com/tcp/443/ws")));
+    }
+
+    #[test]
+    fn pattern_distinguishes_ip_versions() {
+        let ip4_tcp = MultiaddrPattern::new().ip4().tcp();
+        let ip6_tcp = MultiaddrPattern::new().ip6().tcp();
+
+        assert!(ip4_tcp.matches(&addr("/ip4/1.1.1.1/tcp/4001")));
+        assert!(!ip4_tcp.matches(&addr("/ip6/::1/tcp/4001")));
+        assert!(ip6_tcp.matches(&addr("/ip6/::1/tcp/4001")));
+    }
+
+    #[test]
+    fn empty_pattern_matches_any_address() {
+        assert!(MultiaddrPattern::new().matches(&Multiaddr::empty()));
+        assert!(MultiaddrPattern::new().matches(&addr("/memory/1234")));
+    }
+}
@@ -53,10 +53,12 @@
 use futures::{task::Context, task::Poll, AsyncRead, AsyncWrite};
 use multiaddr::Multiaddr;
 use std::future::Future;
+use std::io;
 use std::pin::Pin;
 
 pub use self::boxed::StreamMuxerBox;
 pub use self::boxed::SubstreamBox;
+pub use self::boxed::{StreamCounter, StreamCounts};
 
 mod boxed;
 
@@ -118,6 +120,38 @@ pub enum StreamMuxerEvent {
     AddressChange(Multiaddr),
 }
 
+/// Error of [`StreamMuxer::poll_outbound`] signalling that the muxer refused to open a new outbound
+/// stream because the maximum number of streams of the connection is reached.
+///
+/// In contrast to other errors, the connection remains usable. Implementations return this error,
+/// or an error caused by it, e.g. an [`io::Error`] wrapping it, such that callers can detect it via
+/// [`StreamLimitReached::is_cause_of`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("maximum number of streams reached")]
+pub struct StreamLimitReached;
+
+impl StreamLimitReached {
+    /// Whether the given error is a [`StreamLimitReached`] or caused by one.
+    pub fn is_cause_of(error: &(dyn std::error::Error + 'static)) -> bool {
+        let mut next = Some(error);
+        while let Some(error) = next {
+            if error.is::<StreamLimitReached>() {
+                return true;
+            }
+            // `io::Error::source` skips the error wrapped by the `io::Error`.
+            next = match error
+                .downcast_ref::<io::Error>()
+                .and_then(io::Error::get_ref)
+            {
+                Some(inner) => Some(inner as &(dyn std::error::Error + 'static)),
+                None => error.source(),
+            };
+        }
+
+        false
+    }
+}
+
 /// Extension trait for [`StreamMuxer`].
 pub trait StreamMuxerExt: StreamMuxer + Sized {
     /// Convenience function for calling [`StreamMuxer::poll_inbound`] for [`StreamMuxer`]s that are `Unpin`.
@@ -1,4 +1,5 @@
 use crate::muxing::{StreamMuxer, StreamMuxerEvent};
+use crate::Endpoint;
 use futures::{AsyncRead, AsyncWrite};
 use pin_project::pin_project;
 use std::error::Error;
@@ -6,11 +7,14 @@ use std::fmt;
 use std::io;
 use std::io::{IoSlice, IoSliceMut};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 /// Abstract `StreamMuxer`.
 pub struct StreamMuxerBox {
     inner: Pin<Box<dyn StreamMuxer<Substream = SubstreamBox, Error = io::Error> + Send>>,
+    counter: StreamCounter,
 }
 
 impl fmt::Debug for StreamMuxerBox {
@@ -23,7 +27,80 @@ impl fmt::Debug for StreamMuxerBox {
 ///
 /// A [`SubstreamBox`] erases the concrete type it is given and only retains its `AsyncRead`
 /// and `AsyncWrite` capabilities.
-pub struct SubstreamBox(Pin<Box<dyn AsyncReadWrite + Send>>);
+pub struct SubstreamBox {
+    inner: Pin<Box<dyn AsyncReadWrite + Send>>,
+    /// Keeps the stream accounted for in the [`StreamCounter`] of the [`StreamMuxerBox`] that
+    /// opened it.
+    _count_guard: Option<StreamCountGuard>,
+}
+
+/// Snapshot of the streams opened via a [`StreamMuxerBox`], see [`StreamCounter::counts`].
+///
+/// Streams are counted from the moment the muxer hands them out until their [`SubstreamBox`] is
+/// dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamCounts {
+    /// The number of currently open streams that were opened by the remote.
+    pub inbound_active: usize,
+    /// The number of currently open streams that were opened by us.
+    pub outbound_active: usize,
+    /// The total number of streams opened so far, in both directions.
+    pub total_opened: usize,
+}
+
+/// Handle to the stream accounting of a [`StreamMuxerBox`], see [`StreamMuxerBox::stream_counter`].
+#[derive(Debug, Clone, Default)]
+pub struct StreamCounter(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    inbound_active: AtomicUsize,
+    outbound_active: AtomicUsize,
+    total_opened: AtomicUsize,
+}
+
+impl StreamCounter {
+    /// The current stream counts.
+    pub fn counts(&self) -> StreamCounts {
+        StreamCounts {
+            inbound_active: self.0.inbound_active.load(Ordering::Relaxed),
+            outbound_active: self.0.outbound_active.load(Ordering::Relaxed),
+            total_opened: self.0.total_opened.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records a newly opened stream, returning a guard that marks the stream as closed on drop.
+    fn track(&self, direction: Endpoint) -> StreamCountGuard {
+        self.active(direction).fetch_add(1, Ordering::Relaxed);
+        self.0.total_opened.fetch_add(1, Ordering::Relaxed);
+
+        StreamCountGuard {
+            counter: self.clone(),
+            direction,
+        }
+    }
+
+    fn active(&self, direction: Endpoint) -> &AtomicUsize {
+        match direction {
+            Endpoint::Dialer => &self.0.outbound_active,
+            Endpoint::Listener => &self.0.inbound_active,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StreamCountGuard {
+    counter: StreamCounter,
+    direction: Endpoint,
+}
+
+impl Drop for StreamCountGuard {
+    fn drop(&mut self) {
+        self.counter
+            .active(self.direction)
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
 
 #[pin_project]
 struct Wrap<T>
@@ -97,13 +174,28 @@ impl StreamMuxerBox {
 
         StreamMuxerBox {
             inner: Box::pin(wrap),
+            counter: StreamCounter::default(),
         }
     }
 
+    /// Returns a handle to the number of streams opened via this muxer.
+    pub fn stream_counter(&self) -> StreamCounter {
+        self.counter.clone()
+    }
+
+    /// The current number of streams opened via this muxer.
+    pub fn stream_counts(&self) -> StreamCounts {
+        self.counter.counts()
+    }
+
     fn project(
         self: Pin<&mut Self>,
-    ) -> Pin<&mut (dyn StreamMuxer<Substream = SubstreamBox, Error = io::Error> + Send)> {
-        self.get_mut().inner.as_mut()
+    ) -> (
+        Pin<&mut (dyn StreamMuxer<Substream = SubstreamBox, Error = io::Error> + Send)>,
+        &StreamCounter,
+    ) {
+        let this = self.get_mut();
+        (this.inner.as_mut(), &this.counter)
     }
 }
 
@@ -115,39 +207,55 @@ impl StreamMuxer for StreamMuxerBox {
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<Self::Substream, Self::Error>> {
-        self.project().poll_inbound(cx)
+        let (inner, counter) = self.project();
+        inner
+            .poll_inbound(cx)
+            .map_ok(|stream| stream.counted(counter.track(Endpoint::Listener)))
     }
 
     fn poll_outbound(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<Self::Substream, Self::Error>> {
-        self.project().poll_outbound(cx)
+        let (inner, counter) = self.project();
+        inner
+            .poll_outbound(cx)
+            .map_ok(|stream| stream.counted(counter.track(Endpoint::Dialer)))
     }
 
     #[inline]
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.project().poll_close(cx)
+        self.project().0.poll_close(cx)
     }
 
     fn poll(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
-        self.project().poll(cx)
+        self.project().0.poll(cx)
     }
 }
 
 impl SubstreamBox {
     /// Construct a new [`SubstreamBox`] from something that implements [`AsyncRead`] and [`AsyncWrite`].
     pub fn new<S: AsyncRead + AsyncWrite + Send + 'static>(stream: S) -> Self {
-        Self(Box::pin(stream))
+        Self {
+            inner: Box::pin(stream),
+            _count_guard: None,
+        }
+    }
+
+    fn counted(self, count_guard: StreamCountGuard) -> Self {
+        Self {
+            _count_guard: Some(count_guard),
+            ..self
+        }
     }
 }
 
 impl fmt::Debug for SubstreamBox {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "SubstreamBox({})", self.0.type_name())
+        write!(f, "SubstreamBox({})", self.inner.type_name())
     }
 }
 
@@ -174,7 +282,7 @@ impl AsyncRead for SubstreamBox {
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<std::io::Result<usize>> {
-        self.0.as_mut().poll_read(cx, buf)
+        self.inner.as_mut().poll_read(cx, buf)
     }
 
     fn poll_read_vectored(
@@ -182,7 +290,7 @@ impl AsyncRead for SubstreamBox {
         cx: &mut Context<'_>,
         bufs: &mut [IoSliceMut<'_>],
     ) -> Poll<std::io::Result<usize>> {
-        self.0.as_mut().poll_read_vectored(cx, bufs)
+        self.inner.as_mut().poll_read_vectored(cx, bufs)
     }
 }
 
@@ -192,7 +300,7 @@ impl AsyncWrite for SubstreamBox {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<std::io::Result<usize>> {
-        self.0.as_mut().poll_write(cx, buf)
+        self.inner.as_mut().poll_write(cx, buf)
     }
 
     fn poll_write_vectored(
@@ -200,14 +308,14 @@ impl AsyncWrite for SubstreamBox {
         cx: &mut Context<'_>,
         bufs: &[IoSlice<'_>],
     ) -> Poll<std::io::Result<usize>> {
-        self.0.as_mut().poll_write_vectored(cx, bufs)
+        self.inner.as_mut().poll_write_vectored(cx, bufs)
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
-        self.0.as_mut().poll_flush(cx)
+        self.inner.as_mut().poll_flush(cx)
     }
 
     fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
-        self.0.as_mut().poll_close(cx)
+        self.inner.as_mut().poll_close(cx)
     }
 }
@@ -141,6 +141,20 @@ pub trait Transport {
         addr: Multiaddr,
     ) -> Result<Self::Dial, TransportError<Self::Error>>;
 
+    /// As [`Transport::dial`] but binds the outgoing socket to the given local `port`.
+    ///
+    /// Transports that cannot choose the local port of a connection must fail rather than dial
+    /// from another port. By default, [`TransportError::MultiaddrNotSupported`] is returned, such
+    /// that combinators like [`OrTransport`] try the next transport.
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let _ = port;
+        Err(TransportError::MultiaddrNotSupported(addr))
+    }
+
     /// Poll for [`TransportEvent`]s.
     ///
     /// A [`TransportEvent::Incoming`] should be produced whenever a connection is received at the lowest
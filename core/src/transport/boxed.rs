@@ -60,6 +60,11 @@ trait Abstract<O> {
     fn remove_listener(&mut self, id: ListenerId) -> bool;
     fn dial(&mut self, addr: Multiaddr) -> Result<Dial<O>, TransportError<io::Error>>;
     fn dial_as_listener(&mut self, addr: Multiaddr) -> Result<Dial<O>, TransportError<io::Error>>;
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Dial<O>, TransportError<io::Error>>;
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr>;
     fn poll(
         self: Pin<&mut Self>,
@@ -100,6 +105,17 @@ where
         Ok(Box::pin(fut) as Dial<_>)
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Dial<O>, TransportError<io::Error>> {
+        let fut = Transport::dial_from_port(self, addr, port)
+            .map(|r| r.map_err(box_err))
+            .map_err(|e| e.map(box_err))?;
+        Ok(Box::pin(fut) as Dial<_>)
+    }
+
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
         Transport::address_translation(self, server, observed)
     }
@@ -154,6 +170,14 @@ impl<O> Transport for Boxed<O> {
         self.inner.dial_as_listener(addr)
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.inner.dial_from_port(addr, port)
+    }
+
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
         self.inner.address_translation(server, observed)
     }
@@ -38,7 +38,7 @@ pub struct Transport<T> {
 /// Credit for this implementation goes to the Rust standard library team.
 ///
 /// Unstable tracking issue: [#27709](https://github.com/rust-lang/rust/issues/27709)
-mod ipv4_global {
+pub(crate) mod ipv4_global {
     use std::net::Ipv4Addr;
 
     /// Returns [`true`] if this address is reserved by IANA for future use. [IETF RFC 1112]
@@ -148,7 +148,7 @@ mod ipv4_global {
 /// Credit for this implementation goes to the Rust standard library team.
 ///
 /// Unstable tracking issue: [#27709](https://github.com/rust-lang/rust/issues/27709)
-mod ipv6_global {
+pub(crate) mod ipv6_global {
     use std::net::Ipv6Addr;
 
     /// Returns `true` if the address is a unicast address with link-local scope,
@@ -178,7 +178,7 @@ mod ipv6_global {
     /// [loopback address]: Ipv6Addr::LOCALHOST
     #[must_use]
     #[inline]
-    const fn is_unicast_link_local(a: Ipv6Addr) -> bool {
+    pub(crate) const fn is_unicast_link_local(a: Ipv6Addr) -> bool {
         (a.segments()[0] & 0xffc0) == 0xfe80
     }
 
@@ -189,7 +189,7 @@ mod ipv6_global {
     /// [IETF RFC 4193]: https://tools.ietf.org/html/rfc4193
     #[must_use]
     #[inline]
-    const fn is_unique_local(a: Ipv6Addr) -> bool {
+    pub(crate) const fn is_unique_local(a: Ipv6Addr) -> bool {
         (a.segments()[0] & 0xfe00) == 0xfc00
     }
 
@@ -336,6 +336,33 @@ impl<T: crate::Transport + Unpin> crate::Transport for Transport<T> {
         }
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        match addr.iter().next() {
+            Some(Protocol::Ip4(a)) => {
+                if !ipv4_global::is_global(a) {
+                    tracing::debug!(ip=?a, "Not dialing non global IP address");
+                    return Err(TransportError::MultiaddrNotSupported(addr));
+                }
+                self.inner.dial_from_port(addr, port)
+            }
+            Some(Protocol::Ip6(a)) => {
+                if !ipv6_global::is_global(a) {
+                    tracing::debug!(ip=?a, "Not dialing non global IP address");
+                    return Err(TransportError::MultiaddrNotSupported(addr));
+                }
+                self.inner.dial_from_port(addr, port)
+            }
+            _ => {
+                tracing::debug!(address=%addr, "Not dialing unsupported Multiaddress");
+                Err(TransportError::MultiaddrNotSupported(addr))
+            }
+        }
+    }
+
     fn address_translation(&self, listen: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
         self.inner.address_translation(listen, observed)
     }
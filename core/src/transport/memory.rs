@@ -502,6 +502,20 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn dial_from_port_is_not_supported() {
+        let mut transport = MemoryTransport::default();
+        let addr: Multiaddr = "/memory/3948572039485".parse().unwrap();
+        transport
+            .listen_on(ListenerId::next(), addr.clone())
+            .unwrap();
+
+        assert!(matches!(
+            transport.dial_from_port(addr.clone(), 1234),
+            Err(TransportError::MultiaddrNotSupported(a)) if a == addr
+        ));
+    }
+
     #[test]
     fn stop_listening() {
         let rand_port = rand::random::<u64>().saturating_add(1);
@@ -350,6 +350,14 @@ where
         self.0.dial_as_listener(addr)
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.0.dial_from_port(addr, port)
+    }
+
     fn listen_on(
         &mut self,
         id: ListenerId,
@@ -433,6 +441,21 @@ where
         })
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let future = self
+            .inner
+            .dial_from_port(addr, port)
+            .map_err(|err| err.map(TransportUpgradeError::Transport))?;
+        Ok(DialUpgradeFuture {
+            future: Box::pin(future),
+            upgrade: future::Either::Left(Some(self.upgrade.clone())),
+        })
+    }
+
     fn listen_on(
         &mut self,
         id: ListenerId,
@@ -0,0 +1,249 @@
+//! Structured summaries of the swarm events of the server, logged as structured events and served
+//! as JSON via `GET /events`.
+
+use libp2p::{ping, swarm::SwarmEvent};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// The number of most recent events retained by an [`EventLog`].
+pub(crate) const EVENT_LOG_CAPACITY: usize = 256;
+
+/// Summary of a [`SwarmEvent`], serialized with its kind as `event_type`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+pub(crate) enum EventSummary {
+    NewListenAddr {
+        address: String,
+    },
+    ConnectionEstablished {
+        peer_id: String,
+        address: String,
+    },
+    ConnectionClosed {
+        peer_id: String,
+        address: String,
+        cause: Option<String>,
+    },
+    IncomingConnectionError {
+        address: String,
+        error: String,
+    },
+    Ping {
+        peer_id: String,
+        rtt_ms: Option<f64>,
+        error: Option<String>,
+    },
+}
+
+impl EventSummary {
+    /// Summarizes the given event, or returns `None` for events not worth reporting.
+    pub(crate) fn from_swarm_event(event: &SwarmEvent<ping::Event>) -> Option<Self> {
+        let summary = match event {
+            SwarmEvent::NewListenAddr { address, .. } => EventSummary::NewListenAddr {
+                address: address.to_string(),
+            },
+            SwarmEvent::ConnectionEstablished {
+                peer_id, endpoint, ..
+            } => EventSummary::ConnectionEstablished {
+                peer_id: peer_id.to_string(),
+                address: endpoint.get_remote_address().to_string(),
+            },
+            SwarmEvent::ConnectionClosed {
+                peer_id,
+                endpoint,
+                cause,
+                ..
+            } => EventSummary::ConnectionClosed {
+                peer_id: peer_id.to_string(),
+                address: endpoint.get_remote_address().to_string(),
+                cause: cause.as_ref().map(|e| e.to_string()),
+            },
+            SwarmEvent::IncomingConnectionError {
+                send_back_addr,
+                error,
+                ..
+            } => EventSummary::IncomingConnectionError {
+                address: send_back_addr.to_string(),
+                error: error.to_string(),
+            },
+            SwarmEvent::Behaviour(ping::Event { peer, result, .. }) => EventSummary::Ping {
+                peer_id: peer.to_string(),
+                rtt_ms: result
+                    .as_ref()
+                    .ok()
+                    .map(|rtt| rtt.as_micros() as f64 / 1000.0),
+                error: result.as_ref().err().map(|e| e.to_string()),
+            },
+            _ => return None,
+        };
+
+        Some(summary)
+    }
+
+    /// Emits the summary as a structured event, with its fields as fields of the event.
+    pub(crate) fn log(&self) {
+        match self {
+            EventSummary::NewListenAddr { address } => {
+                tracing::info!(event_type = "new_listen_addr", %address, "Listening");
+            }
+            EventSummary::ConnectionEstablished { peer_id, address } => {
+                tracing::info!(
+                    event_type = "connection_established",
+                    %peer_id,
+                    %address,
+                    "Connection established"
+                );
+            }
+            EventSummary::ConnectionClosed {
+                peer_id,
+                address,
+                cause,
+            } => {
+                tracing::info!(
+                    event_type = "connection_closed",
+                    %peer_id,
+                    %address,
+                    cause = cause.as_deref(),
+                    "Connection closed"
+                );
+            }
+            EventSummary::IncomingConnectionError { address, error } => {
+                tracing::warn!(
+                    event_type = "incoming_connection_error",
+                    %address,
+                    %error,
+                    "Incoming connection failed"
+                );
+            }
+            EventSummary::Ping {
+                peer_id,
+                rtt_ms: Some(rtt_ms),
+                ..
+            } => {
+                tracing::info!(event_type = "ping", %peer_id, rtt_ms, "Pinged peer");
+            }
+            EventSummary::Ping { peer_id, error, .. } => {
+                tracing::warn!(
+                    event_type = "ping",
+                    %peer_id,
+                    error = error.as_deref(),
+                    "Ping failed"
+                );
+            }
+        }
+    }
+}
+
+/// Ring buffer of the [`EVENT_LOG_CAPACITY`] most recent [`EventSummary`]s, shared between the
+/// event loop and the HTTP server.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventLog(Arc<Mutex<VecDeque<EventSummary>>>);
+
+impl EventLog {
+    /// Appends the given summary, dropping the oldest one once full.
+    pub(crate) fn push(&self, summary: EventSummary) {
+        let mut events = self.0.lock().unwrap();
+        if events.len() == EVENT_LOG_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(summary);
+    }
+
+    /// Returns the retained summaries, oldest first.
+    pub(crate) fn snapshot(&self) -> Vec<EventSummary> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::swarm::ConnectionId;
+    use libp2p::PeerId;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn ping(result: Result<Duration, ping::Failure>) -> SwarmEvent<ping::Event> {
+        SwarmEvent::Behaviour(ping::Event {
+            peer: PeerId::random(),
+            connection: ConnectionId::new_unchecked(0),
+            result,
+            max_failures_reached: false,
+        })
+    }
+
+    #[test]
+    fn summarizes_successful_ping() {
+        let event = ping(Ok(Duration::from_micros(1500)));
+        let SwarmEvent::Behaviour(ping::Event { peer, .. }) = &event else {
+            unreachable!()
+        };
+
+        let summary = EventSummary::from_swarm_event(&event).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            json!({
+                "event_type": "ping",
+                "peer_id": peer.to_string(),
+                "rtt_ms": 1.5,
+                "error": null,
+            })
+        );
+    }
+
+    #[test]
+    fn summarizes_failed_ping() {
+        let summary = EventSummary::from_swarm_event(&ping(Err(ping::Failure::Timeout))).unwrap();
+
+        let value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(value["event_type"], "ping");
+        assert_eq!(value["rtt_ms"], json!(null));
+        assert_eq!(value["error"], ping::Failure::Timeout.to_string());
+    }
+
+    #[test]
+    fn summarizes_new_listen_addr() {
+        let event = SwarmEvent::NewListenAddr {
+            listener_id: libp2p::core::transport::ListenerId::next(),
+            address: "/ip4/10.0.0.1/udp/4001/webrtc-direct".parse().unwrap(),
+        };
+
+        let summary = EventSummary::from_swarm_event(&event).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            json!({
+                "event_type": "new_listen_addr",
+                "address": "/ip4/10.0.0.1/udp/4001/webrtc-direct",
+            })
+        );
+    }
+
+    #[test]
+    fn event_log_retains_most_recent_events() {
+        let log = EventLog::default();
+        for i in 0..EVENT_LOG_CAPACITY + 2 {
+            log.push(EventSummary::NewListenAddr {
+                address: i.to_string(),
+            });
+        }
+
+        let events = log.snapshot();
+
+        assert_eq!(events.len(), EVENT_LOG_CAPACITY);
+        assert_eq!(
+            events.first(),
+            Some(&EventSummary::NewListenAddr {
+                address: "2".to_owned()
+            })
+        );
+        assert_eq!(
+            events.last(),
+            Some(&EventSummary::NewListenAddr {
+                address: (EVENT_LOG_CAPACITY + 1).to_string()
+            })
+        );
+    }
+}
@@ -1,41 +1,79 @@
 #![allow(non_upper_case_globals)]
 
+mod events;
+
 use anyhow::Result;
 use axum::extract::{Path, State};
 use axum::http::header::CONTENT_TYPE;
 use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse};
-use axum::{http::Method, routing::get, Router};
-use futures::StreamExt;
+use axum::{http::Method, routing::get, Json, Router};
+use events::{EventLog, EventSummary};
+use futures::future::BoxFuture;
+use futures::{Future, FutureExt, StreamExt};
 use libp2p::{
-    core::muxing::StreamMuxerBox,
-    core::Transport,
+    core::multiaddr_ext::MultiaddrExt,
     multiaddr::{Multiaddr, Protocol},
     ping,
-    swarm::SwarmEvent,
+    swarm::{ListenersReady, SwarmEvent},
+    webrtc, BrowserPreset,
 };
-use libp2p_webrtc as webrtc;
 use rand::thread_rng;
 use std::net::{Ipv4Addr, SocketAddr};
+use std::str::FromStr;
 use std::time::Duration;
 use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
 use tower_http::cors::{Any, CorsLayer};
 
+const LOG_FILTER: &str = "browser_webrtc_example=debug,libp2p_webrtc=info,libp2p_ping=debug";
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let _ = tracing_subscriber::fmt()
-        .with_env_filter("browser_webrtc_example=debug,libp2p_webrtc=info,libp2p_ping=debug")
-        .try_init();
+    let mut log_format = LogFormat::Text;
+    let mut http_port = DEFAULT_HTTP_PORT;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--log-format" => {
+                let format = args
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Expected `text` or `json` after `{arg}`"))?;
+                log_format = format.parse()?;
+            }
+            "--http-port" => {
+                let port = args
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Expected a port after `{arg}`"))?;
+                http_port = port.parse()?;
+            }
+            _ => anyhow::bail!("Unexpected argument `{arg}`"),
+        }
+    }
+
+    match log_format {
+        LogFormat::Text => {
+            let _ = tracing_subscriber::fmt()
+                .with_env_filter(LOG_FILTER)
+                .try_init();
+        }
+        LogFormat::Json => {
+            let _ = tracing_subscriber::fmt()
+                .json()
+                .flatten_event(true)
+                .with_env_filter(LOG_FILTER)
+                .try_init();
+        }
+    }
 
     let mut swarm = libp2p::SwarmBuilder::with_new_identity()
         .with_tokio()
-        .with_other_transport(|id_keys| {
-            Ok(webrtc::tokio::Transport::new(
-                id_keys.clone(),
-                webrtc::tokio::Certificate::generate(&mut thread_rng())?,
-            )
-            .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn))))
-        })?
+        .with_browser_reachable_defaults(BrowserPreset {
+            webrtc_certificate: webrtc::tokio::Certificate::generate(&mut thread_rng())?,
+            wss_tls_config: None,
+            enable_webtransport: false,
+        })
+        .await?
         .with_behaviour(|_| ping::Behaviour::default())?
         .with_swarm_config(|cfg| {
             cfg.with_idle_connection_timeout(
@@ -48,51 +86,171 @@ async fn main() -> anyhow::Result<()> {
         .with(Protocol::Udp(0))
         .with(Protocol::WebRTCDirect);
 
-    swarm.listen_on(address_webrtc.clone())?;
+    let listener_ids = swarm.listen_on_many([address_webrtc]).map_err(|(_, e)| e)?;
+    let mut listeners = ListenersReady::new(listener_ids);
+    let events = EventLog::default();
+
+    // Only serve the client files once all listeners are ready.
+    while !listeners.is_ready() {
+        let event = swarm.select_next_some().await;
 
-    let address = loop {
-        if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
-            if address
-                .iter()
-                .any(|e| e == Protocol::Ip4(Ipv4Addr::LOCALHOST))
-            {
+        if let SwarmEvent::NewListenAddr { address, .. } = &event {
+            if address.is_loopback() {
                 tracing::debug!(
                     "Ignoring localhost address to make sure the example works in Firefox"
                 );
                 continue;
             }
+        }
 
-            tracing::info!(%address, "Listening");
+        record(&events, &event);
+        listeners.on_swarm_event(&event);
+    }
 
-            break address;
-        }
+    let Some(address) = listeners.addresses().first().cloned() else {
+        anyhow::bail!("No listener is listening on a non-localhost address");
     };
 
-    let addr = address.with(Protocol::P2p(*swarm.local_peer_id()));
+    let addr = swarm.dialable_address(&address);
+
+    // Install the signal handlers before announcing the server, not to be terminated by an early
+    // signal.
+    let mut signal = shutdown_signal();
 
     // Serve .wasm, .js and server multiaddress over HTTP on this address.
-    tokio::spawn(serve(addr));
+    let shutdown = CancellationToken::new();
+    let server = tokio::spawn(serve(
+        addr,
+        http_port,
+        events.clone(),
+        shutdown.clone().cancelled_owned(),
+    ));
 
     loop {
         tokio::select! {
-            swarm_event = swarm.next() => {
-                tracing::trace!(?swarm_event)
+            swarm_event = swarm.select_next_some() => {
+                record(&events, &swarm_event);
             },
-            _ = tokio::signal::ctrl_c() => {
+            _ = &mut signal => {
                 break;
             }
         }
     }
 
+    let grace_period = grace_period()?;
+    tracing::info!(?grace_period, "Shutting down");
+
+    // Stop accepting HTTP requests and close all connections, giving both until the end of the
+    // grace period to complete.
+    shutdown.cancel();
+    let peers = swarm.connected_peers().copied().collect::<Vec<_>>();
+    for peer in peers {
+        let _ = swarm.disconnect_peer_id(peer);
+    }
+    let drained = tokio::time::timeout(grace_period, async {
+        while swarm.network_info().num_peers() > 0 {
+            let swarm_event = swarm.select_next_some().await;
+            record(&events, &swarm_event);
+        }
+        let _ = server.await;
+    })
+    .await;
+
+    if drained.is_err() {
+        tracing::warn!("Grace period elapsed before all connections were closed");
+    }
+
     Ok(())
 }
 
+/// The port the client files are served on unless set via `--http-port`.
+const DEFAULT_HTTP_PORT: u16 = 8080;
+
+/// Format of the log lines written to stdout, set via `--log-format`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LogFormat {
+    /// Human-readable lines, the default.
+    Text,
+    /// One JSON object per line, with the fields of an event at the top level.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => anyhow::bail!("Unknown log format `{other}`, expected `text` or `json`"),
+        }
+    }
+}
+
+/// Logs the given event as a structured event and retains it for `GET /events`.
+fn record(events: &EventLog, swarm_event: &SwarmEvent<ping::Event>) {
+    let Some(summary) = EventSummary::from_swarm_event(swarm_event) else {
+        tracing::trace!(?swarm_event);
+        return;
+    };
+
+    summary.log();
+    events.push(summary);
+}
+
+/// The environment variable overriding the default grace period of 5 seconds on shutdown.
+const GRACE_PERIOD_ENV: &str = "SHUTDOWN_GRACE_PERIOD_SECS";
+
+/// Returns the time connections and HTTP requests in progress are given to complete on shutdown.
+fn grace_period() -> Result<Duration> {
+    let secs = match std::env::var(GRACE_PERIOD_ENV) {
+        Ok(secs) => secs.parse()?,
+        Err(_) => 5,
+    };
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Installs the signal handlers and returns a future resolving once the process is asked to
+/// terminate, i.e. on SIGTERM or SIGINT on unix platforms and on ctrl-c on others.
+fn shutdown_signal() -> BoxFuture<'static, ()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sigterm = signal(SignalKind::terminate()).expect("to install SIGTERM handler");
+        let mut sigint = signal(SignalKind::interrupt()).expect("to install SIGINT handler");
+
+        async move {
+            tokio::select! {
+                _ = sigterm.recv() => {}
+                _ = sigint.recv() => {}
+            }
+        }
+        .boxed()
+    }
+
+    #[cfg(not(unix))]
+    {
+        async {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+        .boxed()
+    }
+}
+
 #[derive(rust_embed::RustEmbed)]
 #[folder = "$CARGO_MANIFEST_DIR/static"]
 struct StaticFiles;
 
-/// Serve the Multiaddr we are listening on and the host files.
-pub(crate) async fn serve(libp2p_transport: Multiaddr) {
+/// Serve the Multiaddr we are listening on, the host files and the most recent swarm events on
+/// `http_port`, or an ephemeral port if `0`, until `shutdown` resolves.
+pub(crate) async fn serve(
+    libp2p_transport: Multiaddr,
+    http_port: u16,
+    events: EventLog,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) {
     let Some(Protocol::Ip4(listen_addr)) = libp2p_transport.iter().next() else {
         panic!("Expected 1st protocol to be IP4")
     };
@@ -100,8 +258,12 @@ pub(crate) async fn serve(libp2p_transport: Multiaddr) {
     let server = Router::new()
         .route("/", get(get_index))
         .route("/index.html", get(get_index))
+        .route("/events", get(get_events))
         .route("/:path", get(get_static_file))
-        .with_state(Libp2pEndpoint(libp2p_transport))
+        .with_state(AppState {
+            libp2p_endpoint: libp2p_transport,
+            events,
+        })
         .layer(
             // allow cors
             CorsLayer::new()
@@ -109,27 +271,33 @@ pub(crate) async fn serve(libp2p_transport: Multiaddr) {
                 .allow_methods([Method::GET]),
         );
 
-    let addr = SocketAddr::new(listen_addr.into(), 8080);
+    let listener = TcpListener::bind(SocketAddr::new(listen_addr.into(), http_port))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
 
     tracing::info!(url=%format!("http://{addr}"), "Serving client files at url");
 
-    axum::serve(
-        TcpListener::bind((listen_addr, 8080)).await.unwrap(),
-        server.into_make_service(),
-    )
-    .await
-    .unwrap();
+    axum::serve(listener, server.into_make_service())
+        .with_graceful_shutdown(shutdown)
+        .await
+        .unwrap();
 }
 
 #[derive(Clone)]
-struct Libp2pEndpoint(Multiaddr);
+struct AppState {
+    libp2p_endpoint: Multiaddr,
+    events: EventLog,
+}
 
 /// Serves the index.html file for our client.
 ///
 /// Our server listens on a random UDP port for the WebRTC transport.
 /// To allow the client to connect, we replace the `__LIBP2P_ENDPOINT__` placeholder with the actual address.
 async fn get_index(
-    State(Libp2pEndpoint(libp2p_endpoint)): State<Libp2pEndpoint>,
+    State(AppState {
+        libp2p_endpoint, ..
+    }): State<AppState>,
 ) -> Result<Html<String>, StatusCode> {
     let content = StaticFiles::get("index.html")
         .ok_or(StatusCode::NOT_FOUND)?
@@ -142,6 +310,11 @@ async fn get_index(
     Ok(Html(html))
 }
 
+/// Serves the most recent swarm events, oldest first.
+async fn get_events(State(AppState { events, .. }): State<AppState>) -> Json<Vec<EventSummary>> {
+    Json(events.snapshot())
+}
+
 /// Serves the static files generated by `wasm-pack`.
 async fn get_static_file(Path(path): Path<String>) -> Result<impl IntoResponse, StatusCode> {
     tracing::debug!(file_path=%path, "Serving static file");
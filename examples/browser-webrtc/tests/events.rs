@@ -0,0 +1,127 @@
+#![cfg(unix)]
+
+use futures::StreamExt;
+use libp2p::{ping, swarm::SwarmEvent, webrtc, Multiaddr};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+#[tokio::test]
+async fn events_contain_ping_after_connecting() {
+    let server = Server::spawn();
+    let index = http_get(server.http_address, "/");
+    let endpoint: Multiaddr = index
+        .split("run(\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("index.html to contain the address of the server")
+        .parse()
+        .unwrap();
+
+    let mut client = libp2p::SwarmBuilder::with_new_identity()
+        .with_tokio()
+        .with_other_transport(|key| {
+            webrtc::tokio::Transport::new(
+                key.clone(),
+                webrtc::tokio::Certificate::generate(&mut rand::thread_rng()).unwrap(),
+            )
+        })
+        .unwrap()
+        .with_behaviour(|_| {
+            ping::Behaviour::new(ping::Config::new().with_interval(Duration::from_millis(100)))
+        })
+        .unwrap()
+        .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(Duration::from_secs(30)))
+        .build();
+    let client_peer_id = client.local_peer_id().to_string();
+    // The WebRTC transport dials via the socket of a listener.
+    client
+        .listen_on("/ip4/0.0.0.0/udp/0/webrtc-direct".parse().unwrap())
+        .unwrap();
+    client.dial(endpoint).unwrap();
+
+    let ping_entry = tokio::time::timeout(Duration::from_secs(30), async {
+        loop {
+            tokio::select! {
+                event = client.select_next_some() => {
+                    if let SwarmEvent::OutgoingConnectionError { error, .. } = event {
+                        panic!("Failed to connect to the server: {error}");
+                    }
+                }
+                _ = tokio::time::sleep(Duration::from_millis(500)) => {
+                    let events: serde_json::Value =
+                        serde_json::from_str(&http_get(server.http_address, "/events")).unwrap();
+                    let ping = events.as_array().unwrap().iter().find(|event| {
+                        event["event_type"] == "ping" && event["peer_id"] == client_peer_id
+                    });
+                    if let Some(ping) = ping {
+                        return ping.clone();
+                    }
+                }
+            }
+        }
+    })
+    .await
+    .expect("a ping entry within 30s");
+
+    assert!(ping_entry["rtt_ms"].is_number(), "{ping_entry}");
+}
+
+/// The server, running until dropped.
+struct Server {
+    process: Child,
+    http_address: SocketAddr,
+}
+
+impl Server {
+    fn spawn() -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_browser-webrtc-example"))
+            .args(["--log-format", "json", "--http-port", "0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        // Wait until the server is up, i.e. serving the client files, and keep reading its output
+        // afterwards, not to block it on a full pipe.
+        let mut output = BufReader::new(process.stdout.take().unwrap()).lines();
+        let url = output
+            .by_ref()
+            .find_map(|line| {
+                let line: serde_json::Value = serde_json::from_str(&line.unwrap()).ok()?;
+                (line["message"] == "Serving client files at url")
+                    .then(|| line["url"].as_str().unwrap().to_owned())
+            })
+            .expect("server to serve the client files");
+        std::thread::spawn(move || output.for_each(drop));
+
+        Self {
+            process,
+            http_address: url.strip_prefix("http://").unwrap().parse().unwrap(),
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Returns the body of the response to a `GET` request of `path`.
+fn http_get(address: SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+
+    body.to_owned()
+}
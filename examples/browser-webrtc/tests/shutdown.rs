@@ -0,0 +1,51 @@
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+#[test]
+fn exits_cleanly_on_sigterm() {
+    let mut server = Command::new(env!("CARGO_BIN_EXE_browser-webrtc-example"))
+        .args(["--http-port", "0"])
+        .env(
+            "SHUTDOWN_GRACE_PERIOD_SECS",
+            GRACE_PERIOD.as_secs().to_string(),
+        )
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Wait until the server is up, i.e. serving the client files. Keep its output open until it
+    // exited.
+    let mut output = BufReader::new(server.stdout.take().unwrap()).lines();
+    for line in output.by_ref() {
+        if line.unwrap().contains("Serving client files") {
+            break;
+        }
+    }
+
+    let status = Command::new("kill")
+        .args(["-TERM", &server.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Allow for some slack on top of the grace period for the process to exit.
+    let deadline = Instant::now() + GRACE_PERIOD + Duration::from_secs(1);
+    let status = loop {
+        if let Some(status) = server.try_wait().unwrap() {
+            break status;
+        }
+        if Instant::now() > deadline {
+            let _ = server.kill();
+            panic!("Server did not exit within the grace period");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    assert!(status.success());
+    drop(output);
+}
@@ -0,0 +1,312 @@
+//! Configuration of the example node, read from a TOML file with environment overrides.
+//!
+//! See `config.toml` for a sample configuration file.
+
+use libp2p::core::multiaddr::Protocol;
+use libp2p::core::Multiaddr;
+use libp2p::identity;
+use serde::Deserialize;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+/// Prefix of the environment variables overriding the fields of the configuration file, e.g.
+/// `METRICS_EXAMPLE_HTTP_ADDRESS`. Lists are separated by commas.
+const ENV_PREFIX: &str = "METRICS_EXAMPLE_";
+
+/// Validated configuration of the example node.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Config {
+    /// Address the HTTP server serving `/metrics` and `/peers` binds to.
+    pub(crate) http_address: SocketAddr,
+    /// Addresses the swarm listens on.
+    pub(crate) listen_addresses: Vec<Multiaddr>,
+    /// File holding the protobuf-encoded keypair of the node. A new identity is generated if
+    /// unset.
+    pub(crate) keypair_path: Option<PathBuf>,
+    /// Addresses dialed on startup.
+    pub(crate) remotes: Vec<Multiaddr>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            http_address: (Ipv4Addr::LOCALHOST, 0).into(),
+            listen_addresses: vec![Multiaddr::empty()
+                .with(Protocol::Ip4(Ipv4Addr::UNSPECIFIED))
+                .with(Protocol::Tcp(0))],
+            keypair_path: None,
+            remotes: Vec::new(),
+        }
+    }
+}
+
+/// The configuration file as written, before validation.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct File {
+    http_address: Option<String>,
+    listen_addresses: Option<Vec<String>>,
+    keypair_path: Option<PathBuf>,
+    remotes: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Reads the configuration file at `path`, applying overrides from the environment.
+    pub(crate) fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::new("<file>", format!("{}: {e}", path.display())))?;
+
+        Self::parse(&contents, |key| std::env::var(key).ok())
+    }
+
+    /// Parses and validates the configuration in `contents`, looking up overrides with `env`.
+    fn parse(contents: &str, env: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut file: File =
+            toml::from_str(contents).map_err(|e| ConfigError::new("<file>", e.message()))?;
+
+        let var = |field: &str| env(&format!("{ENV_PREFIX}{}", field.to_uppercase()));
+        let list = |value: String| value.split(',').map(|s| s.trim().to_owned()).collect();
+        if let Some(value) = var("http_address") {
+            file.http_address = Some(value);
+        }
+        if let Some(value) = var("listen_addresses") {
+            file.listen_addresses = Some(list(value));
+        }
+        if let Some(value) = var("keypair_path") {
+            file.keypair_path = Some(value.into());
+        }
+        if let Some(value) = var("remotes") {
+            file.remotes = Some(list(value));
+        }
+
+        Self::validate(file)
+    }
+
+    fn validate(file: File) -> Result<Self, ConfigError> {
+        let default = Config::default();
+
+        let http_address = match file.http_address {
+            Some(address) => address
+                .parse::<SocketAddr>()
+                .map_err(|e| ConfigError::new("http_address", format!("`{address}`: {e}")))?,
+            None => default.http_address,
+        };
+        let listen_addresses = match file.listen_addresses {
+            Some(addresses) => parse_multiaddrs("listen_addresses", addresses)?,
+            None => default.listen_addresses,
+        };
+        let remotes = match file.remotes {
+            Some(addresses) => parse_multiaddrs("remotes", addresses)?,
+            None => default.remotes,
+        };
+
+        if listen_addresses.is_empty() {
+            return Err(ConfigError::new(
+                "listen_addresses",
+                "at least one address is required",
+            ));
+        }
+        for (i, address) in listen_addresses.iter().enumerate() {
+            if let Some((ip, port)) = tcp_socket(address) {
+                if port == http_address.port() && overlaps(ip, http_address.ip()) {
+                    return Err(ConfigError::new(
+                        format!("listen_addresses[{i}]"),
+                        format!("TCP port {port} conflicts with `http_address` {http_address}"),
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            http_address,
+            listen_addresses,
+            keypair_path: file.keypair_path,
+            remotes,
+        })
+    }
+
+    /// Reads the keypair from [`Config::keypair_path`], or generates a new one if unset.
+    pub(crate) fn keypair(&self) -> Result<identity::Keypair, ConfigError> {
+        let Some(path) = &self.keypair_path else {
+            return Ok(identity::Keypair::generate_ed25519());
+        };
+
+        let bytes = std::fs::read(path)
+            .map_err(|e| ConfigError::new("keypair_path", format!("{}: {e}", path.display())))?;
+        identity::Keypair::from_protobuf_encoding(&bytes)
+            .map_err(|e| ConfigError::new("keypair_path", format!("{}: {e}", path.display())))
+    }
+}
+
+fn parse_multiaddrs(field: &str, addresses: Vec<String>) -> Result<Vec<Multiaddr>, ConfigError> {
+    addresses
+        .into_iter()
+        .enumerate()
+        .map(|(i, address)| {
+            address
+                .parse()
+                .map_err(|e| ConfigError::new(format!("{field}[{i}]"), format!("`{address}`: {e}")))
+        })
+        .collect()
+}
+
+/// Returns the IP address and port of a `/ip4/.../tcp/...` or `/ip6/.../tcp/...` address with a
+/// fixed port.
+fn tcp_socket(address: &Multiaddr) -> Option<(IpAddr, u16)> {
+    let mut protocols = address.iter();
+    let ip = match protocols.next()? {
+        Protocol::Ip4(ip) => IpAddr::from(ip),
+        Protocol::Ip6(ip) => IpAddr::from(ip),
+        _ => return None,
+    };
+    match protocols.next()? {
+        Protocol::Tcp(port) if port != 0 => Some((ip, port)),
+        _ => None,
+    }
+}
+
+/// Whether binding to both `a` and `b` on the same port would conflict.
+fn overlaps(a: IpAddr, b: IpAddr) -> bool {
+    a == b || a.is_unspecified() || b.is_unspecified()
+}
+
+/// An invalid configuration, naming the offending field.
+#[derive(Debug)]
+pub(crate) struct ConfigError {
+    field: String,
+    message: String,
+}
+
+impl ConfigError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid config `{}`: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(contents: &str) -> Result<Config, ConfigError> {
+        Config::parse(contents, |_| None)
+    }
+
+    #[test]
+    fn empty_file_yields_defaults() {
+        assert_eq!(parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parses_all_fields() {
+        let config = parse(
+            r#"
+            http_address = "127.0.0.1:9090"
+            listen_addresses = ["/ip4/127.0.0.1/tcp/4001", "/ip6/::1/tcp/4001"]
+            keypair_path = "key.pb"
+            remotes = ["/ip4/10.0.0.1/tcp/4001"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                http_address: "127.0.0.1:9090".parse().unwrap(),
+                listen_addresses: vec![
+                    "/ip4/127.0.0.1/tcp/4001".parse().unwrap(),
+                    "/ip6/::1/tcp/4001".parse().unwrap(),
+                ],
+                keypair_path: Some("key.pb".into()),
+                remotes: vec!["/ip4/10.0.0.1/tcp/4001".parse().unwrap()],
+            }
+        );
+    }
+
+    #[test]
+    fn sample_config_is_valid() {
+        parse(include_str!("../config.toml")).unwrap();
+    }
+
+    #[test]
+    fn environment_overrides_file() {
+        let env = HashMap::from([
+            ("METRICS_EXAMPLE_HTTP_ADDRESS", "127.0.0.1:9191"),
+            (
+                "METRICS_EXAMPLE_REMOTES",
+                "/ip4/10.0.0.1/tcp/1, /ip4/10.0.0.2/tcp/2",
+            ),
+        ]);
+
+        let config = Config::parse(r#"http_address = "127.0.0.1:9090""#, |key| {
+            env.get(key).map(|v| v.to_string())
+        })
+        .unwrap();
+
+        assert_eq!(config.http_address, "127.0.0.1:9191".parse().unwrap());
+        assert_eq!(
+            config.remotes,
+            vec![
+                "/ip4/10.0.0.1/tcp/1".parse::<Multiaddr>().unwrap(),
+                "/ip4/10.0.0.2/tcp/2".parse().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_multiaddr_with_index() {
+        let error =
+            parse(r#"listen_addresses = ["/ip4/127.0.0.1/tcp/0", "/ip4/nope"]"#).unwrap_err();
+
+        assert_eq!(error.field, "listen_addresses[1]");
+    }
+
+    #[test]
+    fn reports_invalid_http_address() {
+        let error = parse(r#"http_address = "localhost""#).unwrap_err();
+
+        assert_eq!(error.field, "http_address");
+    }
+
+    #[test]
+    fn reports_conflicting_ports() {
+        let error = parse(
+            r#"
+            http_address = "127.0.0.1:4001"
+            listen_addresses = ["/ip4/127.0.0.1/tcp/0", "/ip4/0.0.0.0/tcp/4001"]
+            "#,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.field, "listen_addresses[1]");
+    }
+
+    #[test]
+    fn allows_same_port_on_distinct_ips() {
+        parse(
+            r#"
+            http_address = "127.0.0.1:4001"
+            listen_addresses = ["/ip4/10.0.0.1/tcp/4001"]
+            "#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(parse(r#"http_adress = "127.0.0.1:9090""#).is_err());
+    }
+}
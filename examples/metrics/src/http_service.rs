@@ -18,11 +18,12 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use crate::{PeerInfo, SharedPeers};
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::get;
-use axum::Router;
+use axum::{Json, Router};
 use prometheus_client::encoding::text::encode;
 use prometheus_client::registry::Registry;
 use std::net::SocketAddr;
@@ -31,13 +32,20 @@ use tokio::net::TcpListener;
 
 const METRICS_CONTENT_TYPE: &str = "application/openmetrics-text;charset=utf-8;version=1.0.0";
 
-pub(crate) async fn metrics_server(registry: Registry) -> Result<(), std::io::Error> {
-    // Serve on localhost.
-    let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+pub(crate) async fn metrics_server(
+    addr: SocketAddr,
+    registry: Registry,
+    peers: SharedPeers,
+) -> Result<(), std::io::Error> {
     let service = MetricService::new(registry);
     let server = Router::new()
         .route("/metrics", get(respond_with_metrics))
-        .with_state(service);
+        .with_state(service)
+        .merge(
+            Router::new()
+                .route("/peers", get(respond_with_peers))
+                .with_state(peers),
+        );
     let tcp_listener = TcpListener::bind(addr).await?;
     let local_addr = tcp_listener.local_addr()?;
     tracing::info!(metrics_server=%format!("http://{}/metrics", local_addr));
@@ -62,6 +70,14 @@ async fn respond_with_metrics(state: State<MetricService>) -> impl IntoResponse
     )
 }
 
+/// Serves the currently connected peers as JSON, ordered by peer ID.
+pub(crate) async fn respond_with_peers(State(peers): State<SharedPeers>) -> Json<Vec<PeerInfo>> {
+    let mut peers = peers.read().unwrap().values().cloned().collect::<Vec<_>>();
+    peers.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+
+    Json(peers)
+}
+
 type SharedRegistry = Arc<Mutex<Registry>>;
 
 impl MetricService {
@@ -24,51 +24,114 @@ use futures::StreamExt;
 use libp2p::core::Multiaddr;
 use libp2p::metrics::{Metrics, Recorder};
 use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
-use libp2p::{identify, identity, noise, ping, tcp, yamux};
+use libp2p::{identify, identity, noise, ping, tcp, yamux, PeerId, Swarm};
 use opentelemetry::KeyValue;
 use prometheus_client::registry::Registry;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{EnvFilter, Layer};
 
+mod config;
 mod http_service;
 
+use config::Config;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     setup_tracing()?;
 
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "--config").is_some() {
+        let path = args.next().ok_or("Expected a path after `--config`")?;
+        return start_with_config(Config::load(path)?).await;
+    }
+
+    let remotes = args
+        .map(|addr| addr.parse())
+        .collect::<Result<Vec<Multiaddr>, _>>()?;
+
+    start(remotes).await
+}
+
+/// Starts a node with the default [`Config`], dialing the given `remotes`.
+async fn start(remotes: Vec<Multiaddr>) -> Result<(), Box<dyn Error>> {
+    start_with_config(Config {
+        remotes,
+        ..Config::default()
+    })
+    .await
+}
+
+/// Starts a node as described by `config`, serving its metrics over HTTP.
+async fn start_with_config(config: Config) -> Result<(), Box<dyn Error>> {
+    // Fail on an unreadable keypair before binding any socket.
+    let keypair = config.keypair()?;
+
     let mut metric_registry = Registry::default();
 
-    let mut swarm = libp2p::SwarmBuilder::with_new_identity()
+    let mut swarm = build_swarm(keypair, &mut metric_registry)?;
+    for address in config.listen_addresses {
+        swarm.listen_on(address)?;
+    }
+
+    let metrics = Metrics::new(&mut metric_registry);
+    let peers = SharedPeers::default();
+    tokio::spawn(http_service::metrics_server(
+        config.http_address,
+        metric_registry,
+        peers.clone(),
+    ));
+
+    run(config.remotes, swarm, metrics, peers).await
+}
+
+fn build_swarm(
+    keypair: identity::Keypair,
+    metric_registry: &mut Registry,
+) -> Result<Swarm<Behaviour>, Box<dyn Error>> {
+    let swarm = libp2p::SwarmBuilder::with_existing_identity(keypair)
         .with_tokio()
         .with_tcp(
             tcp::Config::default(),
             noise::Config::new,
             yamux::Config::default,
         )?
-        .with_bandwidth_metrics(&mut metric_registry)
+        .with_bandwidth_metrics(metric_registry)
         .with_behaviour(|key| Behaviour::new(key.public()))?
         .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(Duration::from_secs(u64::MAX)))
         .build();
 
-    swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
+    Ok(swarm)
+}
 
-    if let Some(addr) = std::env::args().nth(1) {
-        let remote: Multiaddr = addr.parse()?;
+/// Dials all `remotes` and drives the swarm, recording metrics and tracking the connected peers.
+async fn run(
+    remotes: Vec<Multiaddr>,
+    mut swarm: Swarm<Behaviour>,
+    metrics: Metrics,
+    peers: SharedPeers,
+) -> Result<(), Box<dyn Error>> {
+    for remote in remotes {
+        tracing::info!(address=%remote, "Dialing address");
         swarm.dial(remote)?;
-        tracing::info!(address=%addr, "Dialed address")
     }
 
-    let metrics = Metrics::new(&mut metric_registry);
-    tokio::spawn(http_service::metrics_server(metric_registry));
-
     loop {
         match swarm.select_next_some().await {
             SwarmEvent::Behaviour(BehaviourEvent::Ping(ping_event)) => {
                 tracing::info!(?ping_event);
                 metrics.record(&ping_event);
+
+                if let Ok(rtt) = ping_event.result {
+                    if let Some(peer) = peers.write().unwrap().get_mut(&ping_event.peer) {
+                        peer.rtt_millis = Some(rtt.as_secs_f64() * 1000.);
+                    }
+                }
             }
             SwarmEvent::Behaviour(BehaviourEvent::Identify(identify_event)) => {
                 tracing::info!(?identify_event);
@@ -77,6 +140,30 @@ async fn main() -> Result<(), Box<dyn Error>> {
             swarm_event => {
                 tracing::info!(?swarm_event);
                 metrics.record(&swarm_event);
+
+                match swarm_event {
+                    SwarmEvent::ConnectionEstablished {
+                        peer_id, endpoint, ..
+                    } => {
+                        peers
+                            .write()
+                            .unwrap()
+                            .entry(peer_id)
+                            .or_insert_with(|| PeerInfo {
+                                peer_id: peer_id.to_string(),
+                                address: endpoint.get_remote_address().to_string(),
+                                rtt_millis: None,
+                            });
+                    }
+                    SwarmEvent::ConnectionClosed {
+                        peer_id,
+                        num_established: 0,
+                        ..
+                    } => {
+                        peers.write().unwrap().remove(&peer_id);
+                    }
+                    _ => {}
+                }
             }
         }
     }
@@ -103,6 +190,19 @@ fn setup_tracing() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// The currently connected peers, shared with the HTTP server.
+type SharedPeers = Arc<RwLock<HashMap<PeerId, PeerInfo>>>;
+
+/// A connected peer as served on `GET /peers`.
+#[derive(Clone, Debug, Serialize)]
+struct PeerInfo {
+    peer_id: String,
+    /// The remote address of the first established connection.
+    address: String,
+    /// The round-trip time of the last successful ping.
+    rtt_millis: Option<f64>,
+}
+
 /// Our network behaviour.
 #[derive(NetworkBehaviour)]
 struct Behaviour {
@@ -121,3 +221,81 @@ impl Behaviour {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::response::IntoResponse;
+
+    #[tokio::test]
+    async fn peers_lists_connected_remotes_with_rtt() {
+        let mut remotes = Vec::new();
+        let mut remote_peer_ids = Vec::new();
+        for _ in 0..2 {
+            let (peer_id, address) = spawn_node().await;
+            remotes.push(address);
+            remote_peer_ids.push(peer_id.to_string());
+        }
+        remote_peer_ids.sort();
+
+        let mut registry = Registry::default();
+        let swarm = build_swarm(identity::Keypair::generate_ed25519(), &mut registry).unwrap();
+        let metrics = Metrics::new(&mut registry);
+        let peers = SharedPeers::default();
+        tokio::spawn({
+            let peers = peers.clone();
+            async move {
+                let _ = run(remotes, swarm, metrics, peers).await;
+            }
+        });
+
+        let listed = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let response = http_service::respond_with_peers(State(peers.clone()))
+                    .await
+                    .into_response();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let listed: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+
+                if listed.len() == 2 && listed.iter().all(|p| p["rtt_millis"].is_f64()) {
+                    break listed;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("bootstrap node to list both remotes with RTTs");
+
+        let listed_peer_ids = listed
+            .iter()
+            .map(|p| p["peer_id"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(listed_peer_ids, remote_peer_ids);
+    }
+
+    /// Starts a node listening on localhost, returning its peer ID and listen address.
+    async fn spawn_node() -> (PeerId, Multiaddr) {
+        let mut registry = Registry::default();
+        let mut swarm = build_swarm(identity::Keypair::generate_ed25519(), &mut registry).unwrap();
+        swarm
+            .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .unwrap();
+
+        let address = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+                break address;
+            }
+        };
+        let peer_id = *swarm.local_peer_id();
+
+        let metrics = Metrics::new(&mut registry);
+        tokio::spawn(async move {
+            let _ = run(Vec::new(), swarm, metrics, SharedPeers::default()).await;
+        });
+
+        (peer_id, address)
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[test]
+fn serves_metrics_on_configured_address() {
+    let dir = tempfile::tempdir().unwrap();
+    let http_port = free_port();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        format!(
+            r#"
+            http_address = "127.0.0.1:{http_port}"
+            listen_addresses = ["/ip4/127.0.0.1/tcp/0"]
+            "#
+        ),
+    )
+    .unwrap();
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_metrics-example"))
+        .arg("--config")
+        .arg(&path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let response = loop {
+        if let Some(response) = get(http_port, "/metrics") {
+            break response;
+        }
+        if let Some(status) = server.try_wait().unwrap() {
+            panic!("Server exited early with {status}");
+        }
+        if Instant::now() > deadline {
+            let _ = server.kill();
+            panic!("Server did not serve metrics on port {http_port}");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("libp2p_swarm"), "{response}");
+}
+
+#[test]
+fn reports_invalid_config_before_binding() {
+    let dir = tempfile::tempdir().unwrap();
+    let http_port = free_port();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        format!(
+            r#"
+            http_address = "127.0.0.1:{http_port}"
+            listen_addresses = ["/ip4/127.0.0.1/tcp/{http_port}"]
+            "#
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_metrics-example"))
+        .arg("--config")
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("listen_addresses[0]"), "{stderr}");
+}
+
+/// Returns a port that is currently free on localhost.
+fn free_port() -> u16 {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Sends a `GET` request for `path` to localhost, returning the raw response.
+fn get(port: u16, path: &str) -> Option<String> {
+    let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).ok()?;
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+    )
+    .ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+
+    Some(response)
+}
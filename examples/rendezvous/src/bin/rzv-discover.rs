@@ -108,6 +108,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     })) if peer != rendezvous_point => {
                         tracing::info!(%peer, "Ping is {}ms", rtt.as_millis())
                     }
+                    SwarmEvent::Behaviour(MyBehaviourEvent::Ping(ping::Event {
+                        peer,
+                        result: Err(failure),
+                        ..
+                    })) => {
+                        tracing::warn!(%peer, "Ping failed: {failure}")
+                    }
                     other => {
                         tracing::debug!("Unhandled {:?}", other);
                     }
@@ -121,6 +121,13 @@ async fn main() {
             })) if peer != rendezvous_point => {
                 tracing::info!("Ping to {} is {}ms", peer, rtt.as_millis())
             }
+            SwarmEvent::Behaviour(MyBehaviourEvent::Ping(ping::Event {
+                peer,
+                result: Err(failure),
+                ..
+            })) => {
+                tracing::warn!("Ping to {} failed: {}", peer, failure)
+            }
             other => {
                 tracing::debug!("Unhandled {:?}", other);
             }
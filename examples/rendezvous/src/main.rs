@@ -89,6 +89,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     registrations.len()
                 );
             }
+            SwarmEvent::Behaviour(MyBehaviourEvent::Ping(ping::Event {
+                peer,
+                result: Err(failure),
+                ..
+            })) => {
+                tracing::warn!("Ping to {} failed: {}", peer, failure)
+            }
             other => {
                 tracing::debug!("Unhandled {:?}", other);
             }
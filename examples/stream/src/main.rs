@@ -81,7 +81,7 @@ async fn main() -> Result<()> {
 
         match event {
             libp2p::swarm::SwarmEvent::NewListenAddr { address, .. } => {
-                let listen_address = address.with_p2p(*swarm.local_peer_id()).unwrap();
+                let listen_address = swarm.dialable_address(&address);
                 tracing::info!(%listen_address);
             }
             event => tracing::trace!(?event),
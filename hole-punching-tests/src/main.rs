@@ -294,7 +294,7 @@ impl RedisClient {
 
         tracing::debug!("Pushing {key}={value} to redis");
 
-        self.inner.rpush(key, value).await?;
+        self.inner.rpush::<_, _, ()>(key, value).await?;
 
         Ok(())
     }
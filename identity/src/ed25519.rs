@@ -85,6 +85,10 @@ impl fmt::Debug for Keypair {
     }
 }
 
+/// The secret key is zeroized when the keypair is dropped.
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Keypair {}
+
 /// Demote an Ed25519 keypair to a secret key.
 impl From<Keypair> for SecretKey {
     fn from(kp: Keypair) -> SecretKey {
@@ -179,6 +183,23 @@ impl fmt::Debug for SecretKey {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SecretKey {}
+
 impl SecretKey {
     /// Generate a new Ed25519 secret key.
     #[cfg(feature = "rand")]
@@ -237,6 +258,21 @@ mod tests {
         QuickCheck::new().tests(10).quickcheck(prop as fn() -> _);
     }
 
+    #[test]
+    #[cfg(all(feature = "rand", feature = "zeroize"))]
+    fn ed25519_secret_key_zeroized_on_drop() {
+        fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<Keypair>();
+        assert_zeroize_on_drop::<SecretKey>();
+
+        let mut sk = SecretKey::generate();
+        assert_ne!(sk.0, [0u8; 32]);
+
+        zeroize::Zeroize::zeroize(&mut sk);
+
+        assert_eq!(sk.0, [0u8; 32]);
+    }
+
     #[test]
     #[cfg(feature = "rand")]
     fn ed25519_signature() {
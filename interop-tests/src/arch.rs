@@ -1,53 +1,92 @@
+use anyhow::Result;
+
 // Native re-exports
 #[cfg(not(target_arch = "wasm32"))]
-pub(crate) use native::{build_swarm, init_logger, sleep, Instant, RedisClient};
+pub(crate) use native::{
+    build_swarm, init_logger, sleep, InMemoryRedisClient, Instant, RemoteRedisClient,
+};
 
 // Wasm re-exports
 #[cfg(target_arch = "wasm32")]
-pub(crate) use wasm::{build_swarm, init_logger, sleep, Instant, RedisClient};
+pub(crate) use wasm::{build_swarm, init_logger, sleep, Instant, RemoteRedisClient};
+
+/// The redis operations used to coordinate the dialer and the listener of a test.
+pub(crate) trait RedisClient {
+    /// Pops the first element of the list at `key`, waiting up to `timeout` seconds for one to be
+    /// pushed. A `timeout` of zero waits indefinitely.
+    ///
+    /// Like redis, returns the key followed by the element, or nothing if the timeout expired.
+    async fn blpop(&self, key: &str, timeout: u64) -> Result<Vec<String>>;
+
+    /// Appends `value` to the list at `key`.
+    async fn rpush(&self, key: &str, value: String) -> Result<()>;
+}
 
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) mod native {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     use anyhow::{bail, Context, Result};
     use futures::future::BoxFuture;
-    use futures::FutureExt;
+    use futures::{Future, FutureExt};
     use libp2p::identity::Keypair;
     use libp2p::swarm::{NetworkBehaviour, Swarm};
     use libp2p::{noise, tcp, tls, yamux};
     use libp2p_mplex as mplex;
     use libp2p_webrtc as webrtc;
     use redis::AsyncCommands;
+    use tokio::sync::mpsc;
+    use tracing_subscriber::util::SubscriberInitExt;
     use tracing_subscriber::EnvFilter;
 
+    use super::RedisClient;
     use crate::{Muxer, SecProtocol, Transport};
 
     pub(crate) type Instant = std::time::Instant;
 
+    /// Installs the logger, emitting JSON if `LOG_FORMAT=json` is set.
     pub(crate) fn init_logger() {
-        let _ = tracing_subscriber::fmt()
-            .with_env_filter(EnvFilter::from_default_env())
-            .try_init();
+        let json = std::env::var("LOG_FORMAT").is_ok_and(|format| format == "json");
+        let _ = logger(json).try_init();
+    }
+
+    /// Builds a logger writing to stdout, filtered by `RUST_LOG` and defaulting to `info`.
+    fn logger(json: bool) -> Box<dyn tracing::Subscriber + Send + Sync> {
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(std::io::stdout);
+
+        if json {
+            Box::new(builder.json().finish())
+        } else {
+            Box::new(builder.finish())
+        }
     }
 
     pub(crate) fn sleep(duration: Duration) -> BoxFuture<'static, ()> {
         tokio::time::sleep(duration).boxed()
     }
 
-    pub(crate) async fn build_swarm<B: NetworkBehaviour>(
+    pub(crate) async fn build_swarm<B: NetworkBehaviour, F>(
         ip: &str,
         transport: Transport,
         sec_protocol: Option<SecProtocol>,
         muxer: Option<Muxer>,
-        behaviour_constructor: impl FnOnce(&Keypair) -> B,
-    ) -> Result<(Swarm<B>, String)> {
+        behaviour_constructor: impl FnOnce(&Keypair) -> F,
+    ) -> Result<(Swarm<B>, String)>
+    where
+        F: Future<Output = Result<B>>,
+    {
         let (swarm, addr) = match (transport, sec_protocol, muxer) {
             (Transport::QuicV1, None, None) => (
                 libp2p::SwarmBuilder::with_new_identity()
                     .with_tokio()
                     .with_quic()
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/udp/0/quic-v1"),
@@ -60,7 +99,8 @@ pub(crate) mod native {
                         tls::Config::new,
                         mplex::MplexConfig::default,
                     )?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0"),
@@ -73,7 +113,8 @@ pub(crate) mod native {
                         tls::Config::new,
                         yamux::Config::default,
                     )?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0"),
@@ -86,7 +127,8 @@ pub(crate) mod native {
                         noise::Config::new,
                         mplex::MplexConfig::default,
                     )?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0"),
@@ -99,7 +141,8 @@ pub(crate) mod native {
                         noise::Config::new,
                         yamux::Config::default,
                     )?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0"),
@@ -109,7 +152,8 @@ pub(crate) mod native {
                     .with_tokio()
                     .with_websocket(tls::Config::new, mplex::MplexConfig::default)
                     .await?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0/ws"),
@@ -119,7 +163,8 @@ pub(crate) mod native {
                     .with_tokio()
                     .with_websocket(tls::Config::new, yamux::Config::default)
                     .await?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0/ws"),
@@ -129,7 +174,8 @@ pub(crate) mod native {
                     .with_tokio()
                     .with_websocket(noise::Config::new, mplex::MplexConfig::default)
                     .await?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0/ws"),
@@ -139,7 +185,8 @@ pub(crate) mod native {
                     .with_tokio()
                     .with_websocket(noise::Config::new, yamux::Config::default)
                     .await?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0/ws"),
@@ -153,7 +200,8 @@ pub(crate) mod native {
                             webrtc::tokio::Certificate::generate(&mut rand::thread_rng())?,
                         ))
                     })?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/udp/0/webrtc-direct"),
@@ -163,41 +211,124 @@ pub(crate) mod native {
         Ok((swarm, addr))
     }
 
-    pub(crate) struct RedisClient(redis::Client);
+    pub(crate) struct RemoteRedisClient(redis::Client);
 
-    impl RedisClient {
+    impl RemoteRedisClient {
         pub(crate) fn new(redis_addr: &str) -> Result<Self> {
             Ok(Self(
                 redis::Client::open(redis_addr).context("Could not connect to redis")?,
             ))
         }
+    }
 
-        pub(crate) async fn blpop(&self, key: &str, timeout: u64) -> Result<Vec<String>> {
+    impl RedisClient for RemoteRedisClient {
+        async fn blpop(&self, key: &str, timeout: u64) -> Result<Vec<String>> {
             let mut conn = self.0.get_async_connection().await?;
             Ok(conn.blpop(key, timeout as usize).await?)
         }
 
-        pub(crate) async fn rpush(&self, key: &str, value: String) -> Result<()> {
+        async fn rpush(&self, key: &str, value: String) -> Result<()> {
             let mut conn = self.0.get_async_connection().await?;
-            conn.rpush(key, value).await?;
+            conn.rpush::<_, _, ()>(key, value).await?;
+            Ok(())
+        }
+    }
+
+    /// Stands in for redis when running the dialer and the listener within the same process.
+    #[derive(Default)]
+    pub(crate) struct InMemoryRedisClient {
+        lists: Mutex<HashMap<String, List>>,
+    }
+
+    #[derive(Clone)]
+    struct List {
+        sender: mpsc::UnboundedSender<String>,
+        receiver: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<String>>>,
+    }
+
+    impl InMemoryRedisClient {
+        /// Returns the list at `key`, creating it if needed.
+        fn list(&self, key: &str) -> List {
+            self.lists
+                .lock()
+                .unwrap()
+                .entry(key.to_owned())
+                .or_insert_with(|| {
+                    let (sender, receiver) = mpsc::unbounded_channel();
+                    List {
+                        sender,
+                        receiver: Arc::new(tokio::sync::Mutex::new(receiver)),
+                    }
+                })
+                .clone()
+        }
+    }
+
+    impl RedisClient for InMemoryRedisClient {
+        async fn blpop(&self, key: &str, timeout: u64) -> Result<Vec<String>> {
+            let list = self.list(key);
+            let mut receiver = list.receiver.lock().await;
+
+            let value = match timeout {
+                0 => receiver.recv().await,
+                secs => tokio::time::timeout(Duration::from_secs(secs), receiver.recv())
+                    .await
+                    .unwrap_or_default(),
+            };
+
+            Ok(value
+                .map(|value| vec![key.to_owned(), value])
+                .unwrap_or_default())
+        }
+
+        async fn rpush(&self, key: &str, value: String) -> Result<()> {
+            self.list(key)
+                .sender
+                .send(value)
+                .expect("the list holds on to its receiver");
             Ok(())
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn json_logger_can_be_constructed() {
+            tracing::subscriber::with_default(logger(true), || {
+                tracing::info!(answer = 42, "Logging as JSON");
+            });
+        }
+
+        #[tokio::test]
+        async fn in_memory_redis_pops_pushed_values_in_order() {
+            let client = InMemoryRedisClient::default();
+
+            client.rpush("key", "first".to_owned()).await.unwrap();
+            client.rpush("key", "second".to_owned()).await.unwrap();
+
+            assert_eq!(client.blpop("key", 1).await.unwrap(), ["key", "first"]);
+            assert_eq!(client.blpop("key", 1).await.unwrap(), ["key", "second"]);
+            assert!(client.blpop("other", 1).await.unwrap().is_empty());
+        }
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
 pub(crate) mod wasm {
     use anyhow::{bail, Context, Result};
     use futures::future::{BoxFuture, FutureExt};
-    use libp2p::core::upgrade::Version;
+    use futures::Future;
     use libp2p::identity::Keypair;
     use libp2p::swarm::{NetworkBehaviour, Swarm};
-    use libp2p::{noise, websocket_websys, webtransport_websys, yamux, Transport as _};
+    use libp2p::{noise, yamux};
     use libp2p_mplex as mplex;
     use libp2p_webrtc_websys as webrtc_websys;
     use std::time::Duration;
 
-    use crate::{BlpopRequest, Muxer, SecProtocol, Transport};
+    use super::RedisClient;
+    use crate::{BlpopRequest, Muxer, RpushRequest, SecProtocol, Transport};
 
     pub(crate) type Instant = instant::Instant;
 
@@ -210,23 +341,23 @@ pub(crate) mod wasm {
         futures_timer::Delay::new(duration).boxed()
     }
 
-    pub(crate) async fn build_swarm<B: NetworkBehaviour>(
+    pub(crate) async fn build_swarm<B: NetworkBehaviour, F>(
         ip: &str,
         transport: Transport,
         sec_protocol: Option<SecProtocol>,
         muxer: Option<Muxer>,
-        behaviour_constructor: impl FnOnce(&Keypair) -> B,
-    ) -> Result<(Swarm<B>, String)> {
+        behaviour_constructor: impl FnOnce(&Keypair) -> F,
+    ) -> Result<(Swarm<B>, String)>
+    where
+        F: Future<Output = Result<B>>,
+    {
         Ok(match (transport, sec_protocol, muxer) {
             (Transport::Webtransport, None, None) => (
                 libp2p::SwarmBuilder::with_new_identity()
                     .with_wasm_bindgen()
-                    .with_other_transport(|local_key| {
-                        webtransport_websys::Transport::new(webtransport_websys::Config::new(
-                            &local_key,
-                        ))
-                    })?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_webtransport_websys()
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/udp/0/quic/webtransport"),
@@ -234,16 +365,10 @@ pub(crate) mod wasm {
             (Transport::Ws, Some(SecProtocol::Noise), Some(Muxer::Mplex)) => (
                 libp2p::SwarmBuilder::with_new_identity()
                     .with_wasm_bindgen()
-                    .with_other_transport(|local_key| {
-                        Ok(websocket_websys::Transport::default()
-                            .upgrade(Version::V1Lazy)
-                            .authenticate(
-                                noise::Config::new(&local_key)
-                                    .context("failed to initialise noise")?,
-                            )
-                            .multiplex(mplex::MplexConfig::new()))
-                    })?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_websocket_websys(noise::Config::new, mplex::MplexConfig::new)
+                    .context("failed to initialise noise")?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0/wss"),
@@ -251,16 +376,10 @@ pub(crate) mod wasm {
             (Transport::Ws, Some(SecProtocol::Noise), Some(Muxer::Yamux)) => (
                 libp2p::SwarmBuilder::with_new_identity()
                     .with_wasm_bindgen()
-                    .with_other_transport(|local_key| {
-                        Ok(websocket_websys::Transport::default()
-                            .upgrade(Version::V1Lazy)
-                            .authenticate(
-                                noise::Config::new(&local_key)
-                                    .context("failed to initialise noise")?,
-                            )
-                            .multiplex(yamux::Config::default()))
-                    })?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_websocket_websys(noise::Config::new, yamux::Config::default)
+                    .context("failed to initialise noise")?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/tcp/0/wss"),
@@ -271,7 +390,8 @@ pub(crate) mod wasm {
                     .with_other_transport(|local_key| {
                         webrtc_websys::Transport::new(webrtc_websys::Config::new(&local_key))
                     })?
-                    .with_behaviour(behaviour_constructor)?
+                    .with_behaviour_async(behaviour_constructor)
+                    .await?
                     .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(5)))
                     .build(),
                 format!("/ip4/{ip}/udp/0/webrtc-direct"),
@@ -280,14 +400,16 @@ pub(crate) mod wasm {
         })
     }
 
-    pub(crate) struct RedisClient(String);
+    pub(crate) struct RemoteRedisClient(String);
 
-    impl RedisClient {
+    impl RemoteRedisClient {
         pub(crate) fn new(base_url: &str) -> Result<Self> {
             Ok(Self(base_url.to_owned()))
         }
+    }
 
-        pub(crate) async fn blpop(&self, key: &str, timeout: u64) -> Result<Vec<String>> {
+    impl RedisClient for RemoteRedisClient {
+        async fn blpop(&self, key: &str, timeout: u64) -> Result<Vec<String>> {
             let res = reqwest::Client::new()
                 .post(&format!("http://{}/blpop", self.0))
                 .json(&BlpopRequest {
@@ -301,8 +423,17 @@ pub(crate) mod wasm {
             Ok(res)
         }
 
-        pub(crate) async fn rpush(&self, _: &str, _: String) -> Result<()> {
-            bail!("unimplemented")
+        async fn rpush(&self, key: &str, value: String) -> Result<()> {
+            reqwest::Client::new()
+                .post(&format!("http://{}/rpush", self.0))
+                .json(&RpushRequest {
+                    key: key.to_owned(),
+                    value,
+                })
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
         }
     }
 }
@@ -11,6 +11,9 @@ pub(crate) struct Config {
     pub(crate) is_dialer: bool,
     pub(crate) test_timeout: u64,
     pub(crate) redis_addr: String,
+    /// Whether to run both the dialer and the listener within this process, as requested via the
+    /// `--local` flag. Ignores `is_dialer` and `redis_addr`.
+    pub(crate) local: bool,
 }
 
 impl Config {
@@ -30,6 +33,7 @@ impl Config {
 
         let sec_protocol = env::var("security").ok();
         let muxer = env::var("muxer").ok();
+        let local = env::args().any(|arg| arg == "--local");
 
         Ok(Self {
             transport,
@@ -39,6 +43,7 @@ impl Config {
             is_dialer,
             test_timeout,
             redis_addr,
+            local,
         })
     }
 }
@@ -6,16 +6,27 @@ mod config;
 async fn main() -> Result<()> {
     let config = config::Config::from_env()?;
 
-    let report = interop_tests::run_test(
-        &config.transport,
-        &config.ip,
-        config.is_dialer,
-        config.test_timeout,
-        &config.redis_addr,
-        config.sec_protocol,
-        config.muxer,
-    )
-    .await?;
+    let report = if config.local {
+        interop_tests::run_test_local(
+            &config.transport,
+            &config.ip,
+            config.test_timeout,
+            config.sec_protocol,
+            config.muxer,
+        )
+        .await?
+    } else {
+        interop_tests::run_test(
+            &config.transport,
+            &config.ip,
+            config.is_dialer,
+            config.test_timeout,
+            &config.redis_addr,
+            config.sec_protocol,
+            config.muxer,
+        )
+        .await?
+    };
 
     println!("{}", serde_json::to_string(&report)?);
 
@@ -9,17 +9,15 @@ use axum::http::{header, Uri};
 use axum::response::{Html, IntoResponse, Response};
 use axum::routing::get;
 use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
-use redis::{AsyncCommands, Client};
 use thirtyfour::prelude::*;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::net::TcpListener;
 use tokio::process::Child;
 use tokio::sync::mpsc;
-use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
-use interop_tests::{BlpopRequest, Report};
+use interop_tests::{proxy, Report};
 
 mod config;
 
@@ -34,7 +32,6 @@ struct WasmPackage;
 
 #[derive(Clone)]
 struct TestState {
-    redis_client: Client,
     config: config::Config,
     results_tx: mpsc::Sender<Result<Report, String>>,
 }
@@ -49,23 +46,19 @@ async fn main() -> Result<()> {
 
     // read env variables
     let config = config::Config::from_env()?;
+    if config.local {
+        bail!("Running both roles locally is only supported by native_ping");
+    }
     let test_timeout = Duration::from_secs(config.test_timeout);
 
-    // create a redis client
-    let redis_client =
-        Client::open(config.redis_addr.as_str()).context("Could not connect to redis")?;
+    // create a redis proxy
+    let redis_proxy = proxy::router(&config.redis_addr)?;
     let (results_tx, mut results_rx) = mpsc::channel(1);
 
-    let state = TestState {
-        redis_client,
-        config,
-        results_tx,
-    };
+    let state = TestState { config, results_tx };
 
     // create a wasm-app service
     let app = Router::new()
-        // Redis proxy
-        .route("/blpop", post(redis_blpop))
         // Report tests status
         .route("/results", post(post_results))
         // Wasm ping test trigger
@@ -73,9 +66,10 @@ async fn main() -> Result<()> {
         // Wasm app static files
         .fallback(serve_wasm_pkg)
         // Middleware
-        .layer(CorsLayer::very_permissive())
         .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .with_state(state)
+        // Redis proxy
+        .merge(redis_proxy);
 
     // Run the service in background
     tokio::spawn(axum::serve(TcpListener::bind(BIND_ADDR).await?, app).into_future());
@@ -137,32 +131,6 @@ async fn open_in_browser() -> Result<(Child, WebDriver)> {
     Ok((chrome, driver))
 }
 
-/// Redis proxy handler.
-/// `blpop` is currently the only redis client method used in a ping dialer.
-async fn redis_blpop(
-    state: State<TestState>,
-    request: Json<BlpopRequest>,
-) -> Result<Json<Vec<String>>, StatusCode> {
-    let client = state.0.redis_client;
-    let mut conn = client.get_async_connection().await.map_err(|e| {
-        tracing::warn!("Failed to connect to redis: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let res = conn
-        .blpop(&request.key, request.timeout as usize)
-        .await
-        .map_err(|e| {
-            tracing::warn!(
-                key=%request.key,
-                timeout=%request.timeout,
-                "Failed to get list elem key within timeout: {e}"
-            );
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    Ok(Json(res))
-}
-
 /// Receive test results
 async fn post_results(
     state: State<TestState>,
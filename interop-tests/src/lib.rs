@@ -2,16 +2,19 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
-use futures::{FutureExt, StreamExt};
+use futures::{future, FutureExt, StreamExt};
 use libp2p::identity::Keypair;
+use libp2p::multiaddr::Protocol;
 use libp2p::swarm::SwarmEvent;
 use libp2p::{identify, ping, swarm::NetworkBehaviour, Multiaddr};
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
 mod arch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod proxy;
 
-use arch::{build_swarm, init_logger, Instant, RedisClient};
+use arch::{build_swarm, init_logger, Instant, RedisClient, RemoteRedisClient};
 
 pub async fn run_test(
     transport: &str,
@@ -24,6 +27,75 @@ pub async fn run_test(
 ) -> Result<Report> {
     init_logger();
 
+    let redis_client = RemoteRedisClient::new(redis_addr).context("Could not connect to redis")?;
+
+    run_role(
+        transport,
+        ip,
+        is_dialer,
+        test_timeout_seconds,
+        &redis_client,
+        sec_protocol,
+        muxer,
+    )
+    .await
+}
+
+/// Runs the listener and the dialer of the ping test within this process, exchanging the
+/// listener's address through an in-memory stand-in for redis.
+///
+/// Returns the dialer's report once it has pinged the listener.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run_test_local(
+    transport: &str,
+    ip: &str,
+    test_timeout_seconds: u64,
+    sec_protocol: Option<String>,
+    muxer: Option<String>,
+) -> Result<Report> {
+    init_logger();
+
+    let redis_client = arch::InMemoryRedisClient::default();
+
+    let listener = run_role(
+        transport,
+        ip,
+        false,
+        test_timeout_seconds,
+        &redis_client,
+        sec_protocol.clone(),
+        muxer.clone(),
+    );
+    let dialer = run_role(
+        transport,
+        ip,
+        true,
+        test_timeout_seconds,
+        &redis_client,
+        sec_protocol,
+        muxer,
+    );
+
+    let report = match future::select(listener.boxed_local(), dialer.boxed_local()).await {
+        future::Either::Left((Err(e), _)) => Err(e.context("Listener failed")),
+        future::Either::Left((Ok(_), _)) => bail!("Listener stopped before the dialer finished"),
+        future::Either::Right((report, _)) => report,
+    };
+
+    report
+}
+
+/// Runs either the dialer or the listener of the ping test, coordinating with the other role via
+/// `redis_client`.
+async fn run_role(
+    transport: &str,
+    ip: &str,
+    is_dialer: bool,
+    test_timeout_seconds: u64,
+    redis_client: &impl RedisClient,
+    sec_protocol: Option<String>,
+    muxer: Option<String>,
+) -> Result<Report> {
     let test_timeout = Duration::from_secs(test_timeout_seconds);
     let transport = transport.parse().context("Couldn't parse transport")?;
     let sec_protocol = sec_protocol
@@ -41,11 +113,11 @@ pub async fn run_test(
         })
         .transpose()?;
 
-    let redis_client = RedisClient::new(redis_addr).context("Could not connect to redis")?;
-
     // Build the transport from the passed ENV var.
-    let (mut swarm, local_addr) =
-        build_swarm(ip, transport, sec_protocol, muxer, build_behaviour).await?;
+    let (mut swarm, local_addr) = build_swarm(ip, transport, sec_protocol, muxer, |key| {
+        future::ready(Ok(build_behaviour(key)))
+    })
+    .await?;
 
     tracing::info!(local_peer=%swarm.local_peer_id(), "Running ping test");
 
@@ -71,9 +143,11 @@ pub async fn run_test(
                 .get(1)
                 .context("Failed to wait for listener to be ready")?;
 
+            let other_addr = parse_listener_addr(other, transport)?;
+
             let handshake_start = Instant::now();
 
-            swarm.dial(other.parse::<Multiaddr>()?)?;
+            swarm.dial(other_addr)?;
             tracing::info!(listener=%other, "Test instance, dialing multiaddress");
 
             let rtt = loop {
@@ -112,12 +186,17 @@ pub async fn run_test(
                     address,
                 }) = swarm.next().await
                 {
-                    if address.to_string().contains("127.0.0.1") {
+                    // Don't publish loopback addresses unless we explicitly listen on loopback,
+                    // e.g. when running both roles locally.
+                    if address.to_string().contains("127.0.0.1") && ip != "127.0.0.1" {
                         continue;
                     }
                     if listener_id == id {
-                        let ma = format!("{address}/p2p/{}", swarm.local_peer_id());
-                        redis_client.rpush("listenerAddr", ma.clone()).await?;
+                        let ma = match address.with_p2p(*swarm.local_peer_id()) {
+                            Ok(ma) => ma,
+                            Err(ma) => bail!("Listen address {ma} contains a foreign peer id"),
+                        };
+                        redis_client.rpush("listenerAddr", ma.to_string()).await?;
                         break;
                     }
                 }
@@ -184,6 +263,13 @@ pub struct BlpopRequest {
     pub timeout: u64,
 }
 
+/// A request to redis proxy that will push the value to the end of the list.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct RpushRequest {
+    pub key: String,
+    pub value: String,
+}
+
 /// A report generated by the test
 #[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Report {
@@ -218,6 +304,42 @@ impl FromStr for Transport {
     }
 }
 
+/// Parses the address published by the listener and checks that it can be dialed via `transport`.
+///
+/// The address must end with the listener's `/p2p` component. WebRTC-direct and WebTransport
+/// addresses must further contain the listener's certificate hashes.
+fn parse_listener_addr(addr: &str, transport: Transport) -> Result<Multiaddr> {
+    let ma = addr
+        .parse::<Multiaddr>()
+        .with_context(|| format!("Invalid listener address {addr:?}"))?;
+
+    if !matches!(ma.iter().last(), Some(Protocol::P2p(_))) {
+        bail!("Listener address {ma} does not end with a /p2p component");
+    }
+
+    let has = |f: fn(&Protocol) -> bool| ma.iter().any(|p| f(&p));
+    let tcp = has(|p| matches!(p, Protocol::Tcp(_)));
+    let ws = has(|p| matches!(p, Protocol::Ws(_) | Protocol::Wss(_)));
+    let udp = has(|p| matches!(p, Protocol::Udp(_)));
+    let quic_v1 = has(|p| matches!(p, Protocol::QuicV1));
+    let webtransport = has(|p| matches!(p, Protocol::WebTransport));
+    let webrtc_direct = has(|p| matches!(p, Protocol::WebRTCDirect));
+    let certhash = has(|p| matches!(p, Protocol::Certhash(_)));
+
+    let supported = match transport {
+        Transport::Tcp => tcp && !ws,
+        Transport::Ws => tcp && ws,
+        Transport::QuicV1 => udp && quic_v1 && !webtransport,
+        Transport::Webtransport => udp && quic_v1 && webtransport && certhash,
+        Transport::WebRtcDirect => udp && webrtc_direct && certhash,
+    };
+    if !supported {
+        bail!("Listener address {ma} is not a valid {transport:?} address");
+    }
+
+    Ok(ma)
+}
+
 /// Supported stream multiplexers by rust-libp2p.
 #[derive(Clone, Debug)]
 pub enum Muxer {
@@ -272,3 +394,84 @@ pub(crate) fn build_behaviour(key: &Keypair) -> Behaviour {
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER_ID: &str = "12D3KooWEz3ZnZJ7D4wTqVSDUdqDRLXb6Ds9PbNTx7WnNVjKw3YG";
+    const CERTHASH: &str = "uEiDikp5KVUgkLta1EjUN-IKbHk-dUBg8VzKgf5nXxLK46w";
+
+    fn is_valid(addr: &str, transport: Transport) -> bool {
+        parse_listener_addr(addr, transport).is_ok()
+    }
+
+    #[test]
+    fn tcp() {
+        let addr = format!("/ip4/10.0.0.1/tcp/4001/p2p/{PEER_ID}");
+
+        assert!(is_valid(&addr, Transport::Tcp));
+        assert!(!is_valid(&addr, Transport::Ws));
+        assert!(!is_valid(&addr, Transport::QuicV1));
+    }
+
+    #[test]
+    fn ws() {
+        let addr = format!("/ip4/10.0.0.1/tcp/4001/ws/p2p/{PEER_ID}");
+
+        assert!(is_valid(&addr, Transport::Ws));
+        assert!(!is_valid(&addr, Transport::Tcp));
+    }
+
+    #[test]
+    fn quic_v1() {
+        let addr = format!("/ip4/10.0.0.1/udp/4001/quic-v1/p2p/{PEER_ID}");
+
+        assert!(is_valid(&addr, Transport::QuicV1));
+        assert!(!is_valid(&addr, Transport::Webtransport));
+        assert!(!is_valid(&addr, Transport::Tcp));
+    }
+
+    #[test]
+    fn webtransport() {
+        let addr = format!(
+            "/ip4/10.0.0.1/udp/4001/quic-v1/webtransport/certhash/{CERTHASH}/certhash/{CERTHASH}/p2p/{PEER_ID}"
+        );
+        let without_certhash = format!("/ip4/10.0.0.1/udp/4001/quic-v1/webtransport/p2p/{PEER_ID}");
+
+        assert!(is_valid(&addr, Transport::Webtransport));
+        assert!(!is_valid(&addr, Transport::QuicV1));
+        assert!(!is_valid(&without_certhash, Transport::Webtransport));
+    }
+
+    #[test]
+    fn webrtc_direct() {
+        let addr =
+            format!("/ip4/10.0.0.1/udp/4001/webrtc-direct/certhash/{CERTHASH}/p2p/{PEER_ID}");
+        let without_certhash = format!("/ip4/10.0.0.1/udp/4001/webrtc-direct/p2p/{PEER_ID}");
+
+        assert!(is_valid(&addr, Transport::WebRtcDirect));
+        assert!(!is_valid(&addr, Transport::QuicV1));
+        assert!(!is_valid(&without_certhash, Transport::WebRtcDirect));
+    }
+
+    #[test]
+    fn missing_peer_id() {
+        let error = parse_listener_addr("/ip4/10.0.0.1/tcp/4001", Transport::Tcp).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Listener address /ip4/10.0.0.1/tcp/4001 does not end with a /p2p component"
+        );
+    }
+
+    #[test]
+    fn malformed_address() {
+        let error = parse_listener_addr("not-a-multiaddr", Transport::Tcp).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid listener address \"not-a-multiaddr\""
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! HTTP proxy giving the wasm tests access to redis.
+//!
+//! Browsers cannot talk to redis directly, so the wasm `RedisClient` sends its commands to this
+//! proxy, which forwards them to redis using the native `RedisClient`.
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{extract::State, Json, Router};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tower_http::cors::CorsLayer;
+use tower_http::trace::TraceLayer;
+
+use crate::arch::{RedisClient, RemoteRedisClient};
+use crate::{BlpopRequest, RpushRequest};
+
+/// Time granted to a redis command on top of the time it is expected to block.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Creates a router exposing the `POST /blpop` and `POST /rpush` proxy endpoints.
+///
+/// CORS is fully permissive as the wasm tests are served from a different origin.
+pub fn router(redis_addr: &str) -> Result<Router> {
+    let redis_client = Arc::new(RemoteRedisClient::new(redis_addr)?);
+
+    Ok(Router::new()
+        .route("/blpop", post(blpop))
+        .route("/rpush", post(rpush))
+        .layer(CorsLayer::very_permissive())
+        .layer(TraceLayer::new_for_http())
+        .with_state(redis_client))
+}
+
+/// Binds to `listen_addr` and serves the proxy in the background.
+///
+/// Returns the address the proxy is listening on together with the handle of the server task.
+pub async fn spawn_proxy(
+    redis_addr: &str,
+    listen_addr: SocketAddr,
+) -> Result<(SocketAddr, JoinHandle<io::Result<()>>)> {
+    let app = router(redis_addr)?;
+    let listener = TcpListener::bind(listen_addr).await?;
+    let local_addr = listener.local_addr()?;
+
+    tracing::info!(%local_addr, "Redis proxy listening");
+
+    Ok((
+        local_addr,
+        tokio::spawn(async move { axum::serve(listener, app).await }),
+    ))
+}
+
+async fn blpop(
+    State(redis_client): State<Arc<RemoteRedisClient>>,
+    Json(request): Json<BlpopRequest>,
+) -> Result<Json<Vec<String>>, ProxyError> {
+    let timeout = Duration::from_secs(request.timeout) + REQUEST_TIMEOUT;
+    let res = with_timeout(timeout, redis_client.blpop(&request.key, request.timeout))
+        .await
+        .map_err(|e| {
+            tracing::warn!(
+                key=%request.key,
+                timeout=%request.timeout,
+                "Failed to get list elem key within timeout: {e}"
+            );
+            e
+        })?;
+
+    Ok(Json(res))
+}
+
+async fn rpush(
+    State(redis_client): State<Arc<RemoteRedisClient>>,
+    Json(request): Json<RpushRequest>,
+) -> Result<(), ProxyError> {
+    with_timeout(
+        REQUEST_TIMEOUT,
+        redis_client.rpush(&request.key, request.value),
+    )
+    .await
+    .map_err(|e| {
+        tracing::warn!(key=%request.key, "Failed to push list elem: {e}");
+        e
+    })
+}
+
+async fn with_timeout<T>(
+    timeout: Duration,
+    command: impl Future<Output = Result<T>>,
+) -> Result<T, ProxyError> {
+    match tokio::time::timeout(timeout, command).await {
+        Ok(Ok(res)) => Ok(res),
+        Ok(Err(e)) => Err(ProxyError::Redis(e)),
+        Err(_) => Err(ProxyError::Timeout),
+    }
+}
+
+/// Errors returned to the proxy clients as a JSON body of the form `{"error": "..."}`.
+#[derive(Debug)]
+enum ProxyError {
+    Redis(anyhow::Error),
+    Timeout,
+}
+
+impl std::fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyError::Redis(e) => write!(f, "redis command failed: {e}"),
+            ProxyError::Timeout => write!(f, "redis command timed out"),
+        }
+    }
+}
+
+impl IntoResponse for ProxyError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ProxyError::Redis(_) => StatusCode::BAD_GATEWAY,
+            ProxyError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        };
+
+        (
+            status,
+            Json(serde_json::json!({ "error": self.to_string() })),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Mutex;
+
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpStream;
+
+    #[tokio::test]
+    async fn rpush_then_blpop() {
+        let redis_addr = spawn_mock_redis().await;
+        let (proxy_addr, _proxy) = spawn_proxy(&redis_addr, "127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!("http://{proxy_addr}/rpush"))
+            .json(&RpushRequest {
+                key: "listenerAddr".to_owned(),
+                value: "/ip4/127.0.0.1/tcp/1234".to_owned(),
+            })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = client
+            .post(format!("http://{proxy_addr}/blpop"))
+            .header("Origin", "http://localhost:8080")
+            .json(&BlpopRequest {
+                key: "listenerAddr".to_owned(),
+                timeout: 1,
+            })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()["access-control-allow-origin"],
+            "http://localhost:8080"
+        );
+        assert_eq!(
+            res.json::<Vec<String>>().await.unwrap(),
+            vec!["listenerAddr", "/ip4/127.0.0.1/tcp/1234"]
+        );
+    }
+
+    #[tokio::test]
+    async fn redis_failure_is_reported_as_json() {
+        // Nothing is listening on the discard port.
+        let (proxy_addr, _proxy) =
+            spawn_proxy("redis://127.0.0.1:9", "127.0.0.1:0".parse().unwrap())
+                .await
+                .unwrap();
+
+        let res = reqwest::Client::new()
+            .post(format!("http://{proxy_addr}/rpush"))
+            .json(&RpushRequest {
+                key: "dialerDone".to_owned(),
+                value: "true".to_owned(),
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+        let body = res.json::<serde_json::Value>().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("redis"));
+    }
+
+    /// Spawns a minimal redis server understanding `RPUSH` and `BLPOP` on non-empty lists.
+    async fn spawn_mock_redis() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let lists = Arc::new(Mutex::new(HashMap::<String, VecDeque<String>>::new()));
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(serve_mock_redis(stream, lists.clone()));
+            }
+        });
+
+        format!("redis://{addr}")
+    }
+
+    async fn serve_mock_redis(
+        stream: TcpStream,
+        lists: Arc<Mutex<HashMap<String, VecDeque<String>>>>,
+    ) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Ok(Some(header)) = lines.next_line().await {
+            let num_args = header.trim_start_matches('*').parse::<usize>().unwrap();
+            let mut args = Vec::with_capacity(num_args);
+            for _ in 0..num_args {
+                let _len = lines.next_line().await.unwrap().unwrap();
+                args.push(lines.next_line().await.unwrap().unwrap());
+            }
+
+            let reply = match args[0].to_uppercase().as_str() {
+                "RPUSH" => {
+                    let mut lists = lists.lock().unwrap();
+                    let list = lists.entry(args[1].clone()).or_default();
+                    list.extend(args[2..].iter().cloned());
+                    format!(":{}\r\n", list.len())
+                }
+                "BLPOP" => {
+                    let key = &args[1];
+                    match lists
+                        .lock()
+                        .unwrap()
+                        .get_mut(key)
+                        .and_then(|l| l.pop_front())
+                    {
+                        Some(value) => format!(
+                            "*2\r\n${}\r\n{key}\r\n${}\r\n{value}\r\n",
+                            key.len(),
+                            value.len()
+                        ),
+                        None => "*-1\r\n".to_owned(),
+                    }
+                }
+                other => format!("-ERR unknown command '{other}'\r\n"),
+            };
+
+            writer.write_all(reply.as_bytes()).await.unwrap();
+        }
+    }
+}
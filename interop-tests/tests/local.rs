@@ -0,0 +1,25 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use interop_tests::run_test_local;
+
+const TEST_TIMEOUT_SECS: u64 = 30;
+
+#[tokio::test]
+async fn tcp_noise_yamux() {
+    run_test_local(
+        "tcp",
+        "127.0.0.1",
+        TEST_TIMEOUT_SECS,
+        Some("noise".to_owned()),
+        Some("yamux".to_owned()),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn quic_v1() {
+    run_test_local("quic-v1", "127.0.0.1", TEST_TIMEOUT_SECS, None, None)
+        .await
+        .unwrap();
+}
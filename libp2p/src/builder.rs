@@ -3,6 +3,20 @@ use std::marker::PhantomData;
 mod phase;
 mod select_muxer;
 mod select_security;
+mod stack_description;
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "dns",
+    feature = "websocket",
+    feature = "webrtc"
+))]
+pub use phase::{BrowserPreset, BrowserPresetError};
+pub use stack_description::UnsupportedAddressError;
 
 /// Build a [`Swarm`](libp2p_swarm::Swarm) by combining an identity, a set of
 /// [`Transport`](libp2p_core::Transport)s and a
@@ -62,12 +76,36 @@ mod select_security;
 /// #     Ok(())
 /// # }
 /// ```
-pub struct SwarmBuilder<Provider, Phase> {
-    keypair: libp2p_identity::Keypair,
+///
+/// The builder holds the [`Keypair`](libp2p_identity::Keypair) of the
+/// [`Swarm`](libp2p_swarm::Swarm) until it is built, such that it can be accessed via
+/// [`SwarmBuilder::keypair`] in every phase. Once the behaviour is constructed,
+/// [`SwarmBuilder::drop_keypair`] drops the secret key, keeping only the
+/// [`PeerId`](libp2p_identity::PeerId).
+pub struct SwarmBuilder<Provider, Phase, Identity = libp2p_identity::Keypair> {
+    identity: Identity,
+    stack: stack_description::StackDescription,
     phantom: PhantomData<Provider>,
     phase: Phase,
 }
 
+impl<Provider, Phase> SwarmBuilder<Provider, Phase> {
+    /// The identity of the [`Swarm`](libp2p_swarm::Swarm) being built.
+    ///
+    /// Allows reusing the keypair, e.g. for signing application data, once the
+    /// [`Swarm`](libp2p_swarm::Swarm) is built.
+    pub fn keypair(&self) -> &libp2p_identity::Keypair {
+        &self.identity
+    }
+}
+
+impl<Provider, Phase, Identity: phase::LocalIdentity> SwarmBuilder<Provider, Phase, Identity> {
+    /// The [`PeerId`](libp2p_identity::PeerId) of the [`Swarm`](libp2p_swarm::Swarm) being built.
+    pub fn local_peer_id(&self) -> libp2p_identity::PeerId {
+        self.identity.local_peer_id()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::SwarmBuilder;
@@ -97,6 +135,174 @@ mod tests {
             .build();
     }
 
+    #[test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    fn tcp_socks5_proxy() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_socks5_proxy(
+                libp2p_tcp::socks5::Config::new("127.0.0.1:1080".parse().unwrap())
+                    .with_credentials("user", "password"),
+            )
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "quic"
+    ))]
+    fn tcp_quic_socket_buffer_sizes() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_socket_buffer_sizes(4 * 1024 * 1024, 4 * 1024 * 1024)
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_socket_buffer_sizes(8 * 1024 * 1024, 8 * 1024 * 1024)
+            .with_quic()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    fn tcp_low_latency() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_low_latency_tcp(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    fn tcp_handshake_timeout() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .with_swarm_config(|cfg| cfg.with_handshake_timeout(std::time::Duration::from_secs(10)))
+            .build();
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    fn keypair() {
+        let builder = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .with_swarm_config(std::convert::identity);
+        let keypair = builder.keypair().clone();
+
+        let swarm = builder.build();
+
+        assert_eq!(keypair.public().to_peer_id(), *swarm.local_peer_id());
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    fn drop_keypair() {
+        let builder = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap();
+        let peer_id = builder.keypair().public().to_peer_id();
+
+        let builder = builder
+            .drop_keypair()
+            .with_swarm_config(std::convert::identity);
+        assert_eq!(peer_id, builder.local_peer_id());
+
+        let swarm = builder.build();
+
+        assert_eq!(peer_id, *swarm.local_peer_id());
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    fn tcp_substream_upgrade_timeout() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .with_swarm_config(std::convert::identity)
+            .with_substream_upgrade_timeout(std::time::Duration::from_secs(5))
+            .build();
+    }
+
     #[test]
     #[cfg(all(
         feature = "async-std",
@@ -119,6 +325,93 @@ mod tests {
             .build();
     }
 
+    #[test]
+    #[cfg(all(
+        feature = "async-std",
+        feature = "tcp",
+        feature = "unix",
+        feature = "noise",
+        feature = "yamux",
+    ))]
+    fn async_std_tcp_unix() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_async_std()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_unix(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "unix",
+        feature = "noise",
+        feature = "yamux",
+        feature = "ping",
+    ))]
+    async fn unix_ping() {
+        use futures::StreamExt;
+        use libp2p_core::multiaddr::{Multiaddr, Protocol};
+        use libp2p_swarm::{Swarm, SwarmEvent};
+
+        fn new_swarm() -> Swarm<libp2p_ping::Behaviour> {
+            SwarmBuilder::with_new_identity()
+                .with_tokio()
+                .with_unix(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+                .unwrap()
+                .with_behaviour(|_| libp2p_ping::Behaviour::default())
+                .unwrap()
+                .with_swarm_config(|c| {
+                    c.with_idle_connection_timeout(std::time::Duration::from_secs(10))
+                })
+                .build()
+        }
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let socket = temp_dir.path().join("node.sock");
+        let address = Multiaddr::from(Protocol::Unix(socket.to_string_lossy().into_owned().into()));
+
+        let mut listener = new_swarm();
+        let listener_id = listener.listen_on(address.clone()).unwrap();
+        loop {
+            if let SwarmEvent::NewListenAddr { .. } = listener.select_next_some().await {
+                break;
+            }
+        }
+        assert!(socket.exists());
+
+        let mut dialer = new_swarm();
+        dialer.dial(address).unwrap();
+        loop {
+            tokio::select! {
+                event = dialer.select_next_some() => match event {
+                    SwarmEvent::Behaviour(libp2p_ping::Event { result: Ok(_), .. }) => break,
+                    SwarmEvent::OutgoingConnectionError { error, .. } => {
+                        panic!("failed to dial over unix socket: {error}")
+                    }
+                    _ => {}
+                },
+                _ = listener.select_next_some() => {}
+            }
+        }
+
+        assert!(listener.remove_listener(listener_id));
+        loop {
+            if let SwarmEvent::ListenerClosed { .. } = listener.select_next_some().await {
+                break;
+            }
+        }
+        assert!(!socket.exists());
+    }
+
     #[test]
     #[cfg(all(feature = "tokio", feature = "quic"))]
     fn quic() {
@@ -389,6 +682,40 @@ mod tests {
             .build();
     }
 
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "dns",
+        feature = "websocket"
+    ))]
+    async fn websocket_reuses_dns_resolver() {
+        super::phase::RESOLVERS_CONSTRUCTED.with(|n| n.set(0));
+
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_dns_config(
+                libp2p_dns::ResolverConfig::default(),
+                libp2p_dns::ResolverOpts::default(),
+            )
+            .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .await
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+
+        assert_eq!(super::phase::RESOLVERS_CONSTRUCTED.with(|n| n.get()), 1);
+    }
+
     #[tokio::test]
     #[cfg(all(feature = "tokio", feature = "quic", feature = "dns"))]
     async fn quic_dns_config() {
@@ -460,6 +787,96 @@ mod tests {
             .build();
     }
 
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "quic"
+    ))]
+    async fn dial_unsupported_address_lists_configured_transports() {
+        use futures::StreamExt;
+        use libp2p_swarm::SwarmEvent;
+
+        let mut swarm = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_quic()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+
+        let address: libp2p_core::Multiaddr =
+            "/ip4/127.0.0.1/udp/1234/webrtc-direct".parse().unwrap();
+        swarm.dial(address).unwrap();
+
+        loop {
+            if let SwarmEvent::OutgoingConnectionError { error, .. } =
+                swarm.select_next_some().await
+            {
+                let error = error.to_string();
+                assert!(
+                    error.contains(
+                        "configured transports: tcp/noise/yamux, quic-v1 \
+                        — address requires webrtc-direct"
+                    ),
+                    "{error}"
+                );
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "quic",
+        feature = "dns",
+        feature = "websocket",
+        feature = "relay"
+    ))]
+    async fn universal_transport() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_universal_transport()
+            .await
+            .unwrap()
+            .with_behaviour(|_, relay| relay)
+            .unwrap()
+            .build();
+    }
+
+    #[async_std::test]
+    #[cfg(all(
+        feature = "async-std",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "quic",
+        feature = "dns",
+        feature = "websocket",
+        feature = "relay"
+    ))]
+    async fn async_std_universal_transport() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_async_std()
+            .with_universal_transport()
+            .await
+            .unwrap()
+            .with_behaviour(|_, relay| relay)
+            .unwrap()
+            .build();
+    }
+
     /// Showcases how to provide custom transports unknown to the libp2p crate, e.g. WebRTC.
     #[test]
     #[cfg(feature = "tokio")]
@@ -483,6 +900,99 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    async fn tcp_behaviour_async() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour_async(|_| async {
+                futures_timer::Delay::new(std::time::Duration::from_millis(10)).await;
+                Ok::<_, std::convert::Infallible>(libp2p_swarm::dummy::Behaviour)
+            })
+            .await
+            .unwrap()
+            .build();
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "relay"
+    ))]
+    async fn tcp_relay_behaviour_async() {
+        #[derive(libp2p_swarm::NetworkBehaviour)]
+        #[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+        struct Behaviour {
+            dummy: libp2p_swarm::dummy::Behaviour,
+            relay: libp2p_relay::client::Behaviour,
+        }
+
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_relay_client(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .unwrap()
+            .with_behaviour_async(|_, relay| async move {
+                futures_timer::Delay::new(std::time::Duration::from_millis(10)).await;
+                Ok::<_, std::convert::Infallible>(Behaviour {
+                    dummy: libp2p_swarm::dummy::Behaviour,
+                    relay,
+                })
+            })
+            .await
+            .unwrap()
+            .build();
+    }
+
+    #[tokio::test]
+    #[cfg(all(feature = "tokio", feature = "noise", feature = "yamux"))]
+    async fn existing_transport() {
+        use futures::StreamExt;
+        use libp2p_core::{multiaddr::Protocol, transport::MemoryTransport, upgrade, Transport};
+        use libp2p_swarm::SwarmEvent;
+
+        let keypair = libp2p_identity::Keypair::generate_ed25519();
+        let transport = MemoryTransport::default()
+            .upgrade(upgrade::Version::V1)
+            .authenticate(libp2p_noise::Config::new(&keypair).unwrap())
+            .multiplex(libp2p_yamux::Config::default())
+            .boxed();
+
+        let mut swarm = SwarmBuilder::with_existing_transport(keypair.clone(), transport)
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .with_swarm_config(libp2p_swarm::Config::with_tokio_executor())
+            .build();
+        assert_eq!(keypair.public().to_peer_id(), *swarm.local_peer_id());
+
+        swarm.listen_on(Protocol::Memory(0).into()).unwrap();
+        loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+                assert!(matches!(address.iter().next(), Some(Protocol::Memory(_))));
+                break;
+            }
+        }
+    }
+
     #[tokio::test]
     #[cfg(all(
         feature = "tokio",
@@ -513,6 +1023,71 @@ mod tests {
             .build();
     }
 
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "dns",
+        feature = "websocket",
+    ))]
+    async fn tcp_wss() {
+        use futures::StreamExt;
+        use futures_rustls::rustls;
+        use libp2p_core::multiaddr::Protocol;
+        use libp2p_swarm::SwarmEvent;
+        use std::sync::Arc;
+
+        let rcgen_cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_der = rcgen_cert.serialize_der().unwrap();
+
+        let mut swarm = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_wss(
+                vec![libp2p_websocket::tls::Certificate::new(cert_der.clone())],
+                libp2p_websocket::tls::PrivateKey::new(rcgen_cert.serialize_private_key_der()),
+            )
+            .unwrap()
+            .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .await
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+
+        swarm
+            .listen_on("/ip4/127.0.0.1/tcp/0/wss".parse().unwrap())
+            .unwrap();
+        let port = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+                break address
+                    .iter()
+                    .find_map(|p| match p {
+                        Protocol::Tcp(port) => Some(port),
+                        _ => None,
+                    })
+                    .unwrap();
+            }
+        };
+        tokio::spawn(swarm.collect::<Vec<_>>());
+
+        // A plain TLS client trusting the certificate completes the handshake.
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(cert_der)).unwrap();
+        let client_config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let tcp = async_std::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        futures_rustls::TlsConnector::from(Arc::new(client_config))
+            .connect("localhost".try_into().unwrap(), tcp)
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     #[cfg(all(
         feature = "tokio",
@@ -555,6 +1130,166 @@ mod tests {
             .build();
     }
 
+    #[tokio::test]
+    #[cfg(all(feature = "tokio", feature = "quic", feature = "webrtc"))]
+    async fn shared_udp() {
+        use futures::StreamExt;
+        use libp2p_core::multiaddr::{Multiaddr, Protocol};
+        use libp2p_swarm::{Swarm, SwarmEvent};
+
+        fn new_swarm() -> Swarm<libp2p_swarm::dummy::Behaviour> {
+            SwarmBuilder::with_new_identity()
+                .with_tokio()
+                .with_shared_udp(
+                    libp2p_webrtc::tokio::Certificate::generate(&mut rand::thread_rng()).unwrap(),
+                )
+                .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+                .unwrap()
+                .build()
+        }
+
+        let port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let mut listener = new_swarm();
+        let listener_peer_id = *listener.local_peer_id();
+        listener
+            .listen_on(
+                format!("/ip4/127.0.0.1/udp/{port}/quic-v1")
+                    .parse()
+                    .unwrap(),
+            )
+            .unwrap();
+        listener
+            .listen_on(
+                format!("/ip4/127.0.0.1/udp/{port}/webrtc-direct")
+                    .parse()
+                    .unwrap(),
+            )
+            .unwrap();
+        let mut listen_addrs = Vec::<Multiaddr>::new();
+        while listen_addrs.len() < 2 {
+            if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await {
+                assert_eq!(address.iter().nth(1), Some(Protocol::Udp(port)));
+                listen_addrs.push(address);
+            }
+        }
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        for address in listen_addrs {
+            let mut dialer = new_swarm();
+            // WebRTC direct dials from the socket of a listener.
+            dialer
+                .listen_on("/ip4/127.0.0.1/udp/0/webrtc-direct".parse().unwrap())
+                .unwrap();
+            dialer.dial(address.clone()).unwrap();
+            loop {
+                match dialer.select_next_some().await {
+                    SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                        assert_eq!(peer_id, listener_peer_id);
+                        break;
+                    }
+                    SwarmEvent::OutgoingConnectionError { error, .. } => {
+                        panic!("failed to dial {address}: {error}")
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "dns",
+        feature = "websocket",
+        feature = "webrtc",
+    ))]
+    async fn browser_reachable_defaults() {
+        use futures::StreamExt;
+        use libp2p_core::Multiaddr;
+        use libp2p_swarm::{Swarm, SwarmEvent};
+
+        async fn new_swarm() -> Swarm<libp2p_swarm::dummy::Behaviour> {
+            SwarmBuilder::with_new_identity()
+                .with_tokio()
+                .with_browser_reachable_defaults(crate::BrowserPreset {
+                    webrtc_certificate: libp2p_webrtc::tokio::Certificate::generate(
+                        &mut rand::thread_rng(),
+                    )
+                    .unwrap(),
+                    wss_tls_config: None,
+                    enable_webtransport: false,
+                })
+                .await
+                .unwrap()
+                .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+                .unwrap()
+                .build()
+        }
+
+        for listen_addr in [
+            "/ip4/127.0.0.1/tcp/0/ws",
+            "/ip4/127.0.0.1/udp/0/webrtc-direct",
+        ] {
+            let mut listener = new_swarm().await;
+            listener.listen_on(listen_addr.parse().unwrap()).unwrap();
+            let address: Multiaddr = loop {
+                if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await
+                {
+                    break address;
+                }
+            };
+            tokio::spawn(listener.collect::<Vec<_>>());
+
+            let mut dialer = new_swarm().await;
+            // The WebRTC transport dials from the socket of one of its listeners.
+            dialer.listen_on(listen_addr.parse().unwrap()).unwrap();
+            dialer.dial(address).unwrap();
+            loop {
+                match dialer.select_next_some().await {
+                    SwarmEvent::ConnectionEstablished { .. } => break,
+                    SwarmEvent::OutgoingConnectionError { error, .. } => {
+                        panic!("failed to dial {listen_addr}: {error}")
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "dns",
+        feature = "websocket",
+        feature = "webrtc",
+    ))]
+    async fn browser_reachable_defaults_reject_webtransport() {
+        let result = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_browser_reachable_defaults(crate::BrowserPreset {
+                webrtc_certificate: libp2p_webrtc::tokio::Certificate::generate(
+                    &mut rand::thread_rng(),
+                )
+                .unwrap(),
+                wss_tls_config: None,
+                enable_webtransport: true,
+            })
+            .await;
+
+        assert!(result.is_err());
+    }
+
     #[test]
     #[cfg(all(feature = "tokio", feature = "tcp", feature = "tls", feature = "yamux"))]
     fn tcp_bandwidth_metrics() -> Result<(), Box<dyn std::error::Error>> {
@@ -573,6 +1308,125 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "tls",
+        feature = "yamux",
+        feature = "metrics"
+    ))]
+    async fn tcp_metrics_record_swarm_events() {
+        use futures::StreamExt;
+        use libp2p_swarm::SwarmEvent;
+
+        let mut registry = libp2p_metrics::Registry::default();
+        let new_swarm = |registry: &mut libp2p_metrics::Registry| {
+            SwarmBuilder::with_new_identity()
+                .with_tokio()
+                .with_tcp(
+                    Default::default(),
+                    libp2p_tls::Config::new,
+                    libp2p_yamux::Config::default,
+                )
+                .unwrap()
+                .with_metrics(registry)
+                .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+                .unwrap()
+                .with_swarm_config(|c| {
+                    c.with_idle_connection_timeout(std::time::Duration::from_secs(10))
+                })
+                .build()
+        };
+        let mut listener = new_swarm(&mut registry);
+        let mut dialer = new_swarm(&mut libp2p_metrics::Registry::default());
+
+        listener
+            .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .unwrap();
+        let address = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await {
+                break address;
+            }
+        };
+
+        dialer.dial(address).unwrap();
+        tokio::spawn(dialer.collect::<Vec<_>>());
+        loop {
+            if let SwarmEvent::ConnectionEstablished { .. } = listener.select_next_some().await {
+                break;
+            }
+        }
+
+        let mut encoded = String::new();
+        prometheus_client::encoding::text::encode(&mut encoded, &registry).unwrap();
+        assert!(encoded.contains("libp2p_swarm_connections_established_total{role=\"Listener\""));
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "tls",
+        feature = "yamux",
+        feature = "ping"
+    ))]
+    async fn protocol_prefix_isolates_networks() {
+        use futures::StreamExt;
+        use libp2p_swarm::{Swarm, SwarmEvent};
+
+        fn new_swarm(prefix: &str) -> Swarm<libp2p_ping::Behaviour> {
+            SwarmBuilder::with_new_identity()
+                .with_tokio()
+                .with_tcp(
+                    Default::default(),
+                    libp2p_tls::Config::new,
+                    libp2p_yamux::Config::default,
+                )
+                .unwrap()
+                .with_behaviour(|_| libp2p_ping::Behaviour::default())
+                .unwrap()
+                .with_swarm_config(|c| {
+                    c.with_idle_connection_timeout(std::time::Duration::from_secs(10))
+                })
+                .with_protocol_prefix(prefix)
+                .build()
+        }
+
+        /// Connects a new swarm with the given prefix to `listener` and returns the outcome of
+        /// the first ping.
+        async fn ping(
+            listener: &libp2p_core::Multiaddr,
+            prefix: &str,
+        ) -> Result<std::time::Duration, libp2p_ping::Failure> {
+            let mut dialer = new_swarm(prefix);
+            dialer.dial(listener.clone()).unwrap();
+
+            loop {
+                if let SwarmEvent::Behaviour(event) = dialer.select_next_some().await {
+                    return event.result;
+                }
+            }
+        }
+
+        let mut listener = new_swarm("/mynet");
+        listener
+            .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .unwrap();
+        let address = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await {
+                break address;
+            }
+        };
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        assert!(ping(&address, "/mynet").await.is_ok());
+        assert!(matches!(
+            ping(&address, "/othernet").await,
+            Err(libp2p_ping::Failure::Unsupported)
+        ));
+    }
+
     #[test]
     #[cfg(all(feature = "tokio", feature = "quic"))]
     fn quic_bandwidth_metrics() -> Result<(), Box<dyn std::error::Error>> {
@@ -601,3 +1455,52 @@ mod tests {
         Ok(())
     }
 }
+
+#[cfg(all(test, target_arch = "wasm32", feature = "wasm-bindgen"))]
+mod wasm_tests {
+    use crate::SwarmBuilder;
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    #[cfg(feature = "webtransport-websys")]
+    fn webtransport_websys() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_wasm_bindgen()
+            .with_webtransport_websys()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+
+    #[wasm_bindgen_test]
+    #[cfg(all(feature = "websocket-websys", feature = "noise", feature = "yamux"))]
+    fn websocket_websys() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_wasm_bindgen()
+            .with_websocket_websys(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+
+    #[wasm_bindgen_test]
+    #[cfg(all(
+        feature = "webtransport-websys",
+        feature = "websocket-websys",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    fn webtransport_websys_and_websocket_websys() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_wasm_bindgen()
+            .with_webtransport_websys()
+            .with_websocket_websys(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+}
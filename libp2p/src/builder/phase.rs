@@ -1,5 +1,45 @@
 #![allow(unused_imports)]
 
+/// Adds `with_noise` and `with_tls` stubs to a phase whose transport takes a security upgrade,
+/// pointing users who compiled without the `noise` and `tls` features to the missing feature
+/// instead of failing with an unhelpful "no method named `with_noise`" error.
+///
+/// The stubs are bound on `NoiseOrTlsFeatureEnabled`, which no provider implements, so calling
+/// them fails to compile with an error naming the trait.
+///
+/// Invoked under `#[cfg(not(any(feature = "noise", feature = "tls")))]`, thus the stubs vanish
+/// once either feature is enabled.
+#[allow(unused_macros)]
+macro_rules! impl_security_feature_stubs {
+    (<Provider $(, $generic:ident)*> $builder:ty) => {
+        impl<Provider $(, $generic)*> $builder {
+            /// Stub for the missing `noise` feature. Enable the `noise` or `tls` feature of
+            /// libp2p and pass e.g. `libp2p::noise::Config::new` as security upgrade instead.
+            pub fn with_noise(self) -> Self
+            where
+                Provider: super::NoiseOrTlsFeatureEnabled,
+            {
+                unreachable!("no provider implements `NoiseOrTlsFeatureEnabled`")
+            }
+
+            /// Stub for the missing `tls` feature. Enable the `noise` or `tls` feature of
+            /// libp2p and pass e.g. `libp2p::tls::Config::new` as security upgrade instead.
+            pub fn with_tls(self) -> Self
+            where
+                Provider: super::NoiseOrTlsFeatureEnabled,
+            {
+                unreachable!("no provider implements `NoiseOrTlsFeatureEnabled`")
+            }
+        }
+    };
+}
+
+/// Bound on the provider of the `with_noise` and `with_tls` stubs of the [`SwarmBuilder`] phases
+/// taking a security upgrade. Never implemented: enable the `noise` or `tls` feature of libp2p to
+/// authenticate connections.
+#[cfg(not(any(feature = "noise", feature = "tls")))]
+pub trait NoiseOrTlsFeatureEnabled {}
+
 mod bandwidth_logging;
 mod bandwidth_metrics;
 mod behaviour;
@@ -12,6 +52,7 @@ mod quic;
 mod relay;
 mod swarm;
 mod tcp;
+mod unix;
 mod websocket;
 
 use bandwidth_logging::*;
@@ -27,8 +68,24 @@ use swarm::*;
 use tcp::*;
 use websocket::*;
 
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "dns"))]
+pub(crate) use dns::RESOLVERS_CONSTRUCTED;
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "dns",
+    feature = "websocket",
+    feature = "webrtc"
+))]
+pub use tcp::{BrowserPreset, BrowserPresetError};
+
 use super::select_muxer::SelectMuxerUpgrade;
 use super::select_security::SelectSecurityUpgrade;
+use super::stack_description::StackDescription;
 use super::SwarmBuilder;
 
 use libp2p_core::{muxing::StreamMuxerBox, Transport};
@@ -137,3 +194,23 @@ where
     type D = T::Dial;
     type U = T::ListenerUpgrade;
 }
+
+/// The identity held by a [`SwarmBuilder`], either the [`Keypair`] of the
+/// [`Swarm`](libp2p_swarm::Swarm) or, once the secret key is dropped via
+/// [`SwarmBuilder::drop_keypair`], only its [`PeerId`](libp2p_identity::PeerId).
+#[allow(unreachable_pub)]
+pub trait LocalIdentity {
+    fn local_peer_id(&self) -> libp2p_identity::PeerId;
+}
+
+impl LocalIdentity for Keypair {
+    fn local_peer_id(&self) -> libp2p_identity::PeerId {
+        self.public().to_peer_id()
+    }
+}
+
+impl LocalIdentity for libp2p_identity::PeerId {
+    fn local_peer_id(&self) -> libp2p_identity::PeerId {
+        *self
+    }
+}
@@ -29,7 +29,8 @@ impl<T: AuthenticatedMultiplexedTransport, Provider, R>
                     relay_behaviour: self.phase.relay_behaviour,
                     transport,
                 },
-                keypair: self.keypair,
+                identity: self.identity,
+                stack: self.stack,
                 phantom: PhantomData,
             },
             sinks,
@@ -42,7 +43,8 @@ impl<T: AuthenticatedMultiplexedTransport, Provider, R>
                 relay_behaviour: self.phase.relay_behaviour,
                 transport: self.phase.transport,
             },
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
         }
     }
@@ -60,6 +62,15 @@ impl<Provider, T: AuthenticatedMultiplexedTransport, R>
         self.without_bandwidth_logging()
             .with_bandwidth_metrics(registry)
     }
+
+    pub fn with_metrics(
+        self,
+        registry: &mut libp2p_metrics::Registry,
+    ) -> SwarmBuilder<Provider, BehaviourPhase<T, R>> {
+        self.without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_metrics(registry)
+    }
 }
 #[cfg(feature = "relay")]
 impl<Provider, T: AuthenticatedMultiplexedTransport>
@@ -73,6 +84,20 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
             .without_bandwidth_metrics()
             .with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair, libp2p_relay::client::Behaviour) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
 impl<Provider, T: AuthenticatedMultiplexedTransport>
     SwarmBuilder<Provider, BandwidthLoggingPhase<T, NoRelayBehaviour>>
@@ -85,4 +110,18 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
             .without_bandwidth_metrics()
             .with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
@@ -24,8 +24,10 @@ impl<T: AuthenticatedMultiplexedTransport, Provider, R>
                 relay_behaviour: self.phase.relay_behaviour,
                 transport: libp2p_metrics::BandwidthTransport::new(self.phase.transport, registry)
                     .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn))),
+                metrics: None,
             },
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
         }
     }
@@ -37,14 +39,26 @@ impl<T, Provider, R> SwarmBuilder<Provider, BandwidthMetricsPhase<T, R>> {
             phase: BehaviourPhase {
                 relay_behaviour: self.phase.relay_behaviour,
                 transport: self.phase.transport,
+                #[cfg(feature = "metrics")]
+                metrics: None,
             },
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
         }
     }
 }
 
 // Shortcuts
+#[cfg(feature = "metrics")]
+impl<T, Provider, R> SwarmBuilder<Provider, BandwidthMetricsPhase<T, R>> {
+    pub fn with_metrics(
+        self,
+        registry: &mut libp2p_metrics::Registry,
+    ) -> SwarmBuilder<Provider, BehaviourPhase<T, R>> {
+        self.without_bandwidth_metrics().with_metrics(registry)
+    }
+}
 #[cfg(feature = "relay")]
 impl<Provider, T: AuthenticatedMultiplexedTransport>
     SwarmBuilder<Provider, BandwidthMetricsPhase<T, libp2p_relay::client::Behaviour>>
@@ -55,6 +69,19 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
     ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, R::Error> {
         self.without_bandwidth_metrics().with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair, libp2p_relay::client::Behaviour) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_bandwidth_metrics()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
 
 impl<Provider, T: AuthenticatedMultiplexedTransport>
@@ -66,4 +93,17 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
     ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, R::Error> {
         self.without_bandwidth_metrics().with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_bandwidth_metrics()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
@@ -2,11 +2,29 @@ use super::*;
 use crate::SwarmBuilder;
 use libp2p_swarm::NetworkBehaviour;
 use std::convert::Infallible;
+use std::future::Future;
 use std::marker::PhantomData;
 
 pub struct BehaviourPhase<T, R> {
     pub(crate) relay_behaviour: R,
     pub(crate) transport: T,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<libp2p_metrics::Metrics>,
+}
+
+#[cfg(feature = "metrics")]
+impl<T, Provider, R> SwarmBuilder<Provider, BehaviourPhase<T, R>> {
+    /// Registers the Prometheus metrics of the [`Swarm`](libp2p_swarm::Swarm) and its
+    /// connections with `registry`.
+    ///
+    /// The built [`Swarm`](libp2p_swarm::Swarm) records each of its events, see
+    /// [`Swarm::add_event_observer`](libp2p_swarm::Swarm::add_event_observer). Events of
+    /// individual protocols, e.g. ping, still need to be recorded via
+    /// [`Recorder`](libp2p_metrics::Recorder).
+    pub fn with_metrics(mut self, registry: &mut libp2p_metrics::Registry) -> Self {
+        self.phase.metrics = Some(libp2p_metrics::Metrics::new(registry));
+        self
+    }
 }
 
 #[cfg(feature = "relay")]
@@ -17,11 +35,39 @@ impl<T, Provider> SwarmBuilder<Provider, BehaviourPhase<T, libp2p_relay::client:
     ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, R::Error> {
         Ok(SwarmBuilder {
             phase: SwarmPhase {
-                behaviour: constructor(&self.keypair, self.phase.relay_behaviour)
+                behaviour: constructor(&self.identity, self.phase.relay_behaviour)
                     .try_into_behaviour()?,
                 transport: self.phase.transport,
+                #[cfg(feature = "metrics")]
+                metrics: self.phase.metrics,
+            },
+            identity: self.identity,
+            stack: self.stack,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Like [`SwarmBuilder::with_behaviour`], though constructing the behaviour asynchronously,
+    /// e.g. to load state from disk.
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair, libp2p_relay::client::Behaviour) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: NetworkBehaviour,
+        F: Future<Output = Result<B, E>>,
+    {
+        let behaviour = constructor(&self.identity, self.phase.relay_behaviour).await?;
+
+        Ok(SwarmBuilder {
+            phase: SwarmPhase {
+                behaviour,
+                transport: self.phase.transport,
+                #[cfg(feature = "metrics")]
+                metrics: self.phase.metrics,
             },
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
         })
     }
@@ -37,10 +83,41 @@ impl<T, Provider> SwarmBuilder<Provider, BehaviourPhase<T, NoRelayBehaviour>> {
 
         Ok(SwarmBuilder {
             phase: SwarmPhase {
-                behaviour: constructor(&self.keypair).try_into_behaviour()?,
+                behaviour: constructor(&self.identity).try_into_behaviour()?,
+                transport: self.phase.transport,
+                #[cfg(feature = "metrics")]
+                metrics: self.phase.metrics,
+            },
+            identity: self.identity,
+            stack: self.stack,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Like [`SwarmBuilder::with_behaviour`], though constructing the behaviour asynchronously,
+    /// e.g. to load state from disk.
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: NetworkBehaviour,
+        F: Future<Output = Result<B, E>>,
+    {
+        // Discard `NoRelayBehaviour`.
+        let _ = self.phase.relay_behaviour;
+
+        let behaviour = constructor(&self.identity).await?;
+
+        Ok(SwarmBuilder {
+            phase: SwarmPhase {
+                behaviour,
                 transport: self.phase.transport,
+                #[cfg(feature = "metrics")]
+                metrics: self.phase.metrics,
             },
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
         })
     }
@@ -1,6 +1,7 @@
 #[allow(unused_imports)]
 use super::*;
 
+use crate::builder::stack_description::DescribedTransport;
 use crate::SwarmBuilder;
 use libp2p_core::Transport;
 use libp2p_swarm::Swarm;
@@ -9,23 +10,108 @@ pub struct BuildPhase<T, B> {
     pub(crate) behaviour: B,
     pub(crate) transport: T,
     pub(crate) swarm_config: libp2p_swarm::Config,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<libp2p_metrics::Metrics>,
 }
 
 const CONNECTION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
-impl<Provider, T: AuthenticatedMultiplexedTransport, B: libp2p_swarm::NetworkBehaviour>
-    SwarmBuilder<Provider, BuildPhase<T, B>>
+impl<Provider, T, B, Identity> SwarmBuilder<Provider, BuildPhase<T, B>, Identity> {
+    /// How long the negotiation and upgrade of a substream may take before it is aborted.
+    ///
+    /// See [`libp2p_swarm::Config::with_substream_upgrade_timeout`].
+    pub fn with_substream_upgrade_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.phase.swarm_config = self
+            .phase
+            .swarm_config
+            .with_substream_upgrade_timeout(timeout);
+        self
+    }
+
+    /// Prefix of the names of all protocols negotiated on connections, e.g. `/mynet`.
+    ///
+    /// Only peers configured with the same prefix can negotiate protocols with each other.
+    ///
+    /// See [`libp2p_swarm::Config::with_protocol_prefix`].
+    pub fn with_protocol_prefix(mut self, prefix: &str) -> Self {
+        self.phase.swarm_config = self.phase.swarm_config.with_protocol_prefix(prefix);
+        self
+    }
+
+    /// Consult the given [`libp2p_swarm::ResourceManager`] before accepting each inbound
+    /// connection.
+    ///
+    /// See [`libp2p_swarm::Config::with_resource_manager`].
+    pub fn with_resource_manager(
+        mut self,
+        manager: impl libp2p_swarm::ResourceManager + 'static,
+    ) -> Self {
+        self.phase.swarm_config = self.phase.swarm_config.with_resource_manager(manager);
+        self
+    }
+
+    /// Accept at most `per_second` inbound connections per second, refusing the ones beyond.
+    ///
+    /// Refused connections are reported to behaviours via
+    /// [`FromSwarm::ListenFailure`](libp2p_swarm::FromSwarm::ListenFailure).
+    ///
+    /// See [`libp2p_swarm::Config::with_inbound_connection_rate_limit`].
+    pub fn with_inbound_connection_rate_limit(mut self, per_second: u32) -> Self {
+        self.phase.swarm_config = self
+            .phase
+            .swarm_config
+            .with_inbound_connection_rate_limit(per_second);
+        self
+    }
+}
+
+impl<Provider, T, B> SwarmBuilder<Provider, BuildPhase<T, B>> {
+    /// Drops the [`Keypair`](libp2p_identity::Keypair) of the
+    /// [`Swarm`](libp2p_swarm::Swarm) being built, zeroizing its secret key if the `zeroize`
+    /// feature is enabled, and keeps only its [`PeerId`](libp2p_identity::PeerId).
+    ///
+    /// [`SwarmBuilder::keypair`] is no longer available afterwards.
+    pub fn drop_keypair(self) -> SwarmBuilder<Provider, BuildPhase<T, B>, libp2p_identity::PeerId> {
+        SwarmBuilder {
+            identity: self.identity.public().to_peer_id(),
+            stack: self.stack,
+            phantom: std::marker::PhantomData,
+            phase: self.phase,
+        }
+    }
+}
+
+impl<
+        Provider,
+        T: AuthenticatedMultiplexedTransport,
+        B: libp2p_swarm::NetworkBehaviour,
+        Identity: LocalIdentity,
+    > SwarmBuilder<Provider, BuildPhase<T, B>, Identity>
 {
     pub fn build(self) -> Swarm<B> {
-        Swarm::new(
-            libp2p_core::transport::timeout::TransportTimeout::new(
-                self.phase.transport,
-                CONNECTION_TIMEOUT,
+        #[allow(unused_mut)]
+        let mut swarm = Swarm::new(
+            DescribedTransport::new(
+                libp2p_core::transport::timeout::TransportTimeout::new(
+                    self.phase.transport,
+                    CONNECTION_TIMEOUT,
+                )
+                .boxed(),
+                self.stack,
             )
             .boxed(),
             self.phase.behaviour,
-            self.keypair.public().to_peer_id(),
+            self.identity.local_peer_id(),
             self.phase.swarm_config,
-        )
+        );
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.phase.metrics {
+            use libp2p_metrics::Recorder;
+
+            swarm.add_event_observer(move |event| metrics.record(event));
+        }
+
+        swarm
     }
 }
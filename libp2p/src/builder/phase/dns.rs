@@ -6,6 +6,31 @@ pub struct DnsPhase<T> {
     pub(crate) transport: T,
 }
 
+/// The resolver of the DNS transport configured in the [`DnsPhase`].
+///
+/// Reused by the websocket transport instead of constructing a second resolver.
+#[cfg(all(not(target_arch = "wasm32"), feature = "dns", feature = "websocket"))]
+#[derive(Clone)]
+pub(crate) enum Resolver {
+    #[cfg(feature = "async-std")]
+    AsyncStd(libp2p_dns::async_std::Resolver),
+    #[cfg(feature = "tokio")]
+    Tokio(libp2p_dns::tokio::Resolver),
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "dns"))]
+thread_local! {
+    /// The number of DNS resolvers constructed by the builder on the current thread.
+    pub(crate) static RESOLVERS_CONSTRUCTED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Records the construction of a DNS resolver, counted on the current thread in tests.
+#[cfg(all(not(target_arch = "wasm32"), feature = "dns"))]
+pub(crate) fn resolver_constructed() {
+    #[cfg(test)]
+    RESOLVERS_CONSTRUCTED.with(|n| n.set(n.get() + 1));
+}
+
 #[cfg(all(not(target_arch = "wasm32"), feature = "async-std", feature = "dns"))]
 impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::AsyncStd, DnsPhase<T>> {
     // TODO: Remove `async`
@@ -18,11 +43,19 @@ impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::AsyncSt
         >,
         std::io::Error,
     > {
+        let transport = libp2p_dns::async_std::Transport::system2(self.phase.transport)?;
+        resolver_constructed();
+
         Ok(SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: WebsocketPhase {
-                transport: libp2p_dns::async_std::Transport::system2(self.phase.transport)?,
+                #[cfg(feature = "websocket")]
+                resolver: Some(Resolver::AsyncStd(transport.resolver().clone())),
+                transport,
+                #[cfg(feature = "websocket")]
+                tls_config: None,
             },
         })
     }
@@ -30,6 +63,26 @@ impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::AsyncSt
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "tokio", feature = "dns"))]
 impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::Tokio, DnsPhase<T>> {
+    /// Resolves DNS names in the addresses to dial using the system's DNS configuration.
+    ///
+    /// A websocket transport added afterwards reuses this resolver. DNS can only be configured
+    /// once:
+    ///
+    /// ```compile_fail
+    /// # use libp2p::SwarmBuilder;
+    /// # fn build() -> Result<(), Box<dyn std::error::Error>> {
+    /// SwarmBuilder::with_new_identity()
+    ///     .with_tokio()
+    ///     .with_tcp(
+    ///         Default::default(),
+    ///         libp2p_noise::Config::new,
+    ///         libp2p_yamux::Config::default,
+    ///     )?
+    ///     .with_dns()?
+    ///     .with_dns()?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn with_dns(
         self,
     ) -> Result<
@@ -39,11 +92,19 @@ impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::Tokio,
         >,
         std::io::Error,
     > {
+        let transport = libp2p_dns::tokio::Transport::system(self.phase.transport)?;
+        resolver_constructed();
+
         Ok(SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: WebsocketPhase {
-                transport: libp2p_dns::tokio::Transport::system(self.phase.transport)?,
+                #[cfg(feature = "websocket")]
+                resolver: Some(Resolver::Tokio(transport.resolver().clone())),
+                transport,
+                #[cfg(feature = "websocket")]
+                tls_config: None,
             },
         })
     }
@@ -59,15 +120,19 @@ impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::AsyncSt
         super::provider::AsyncStd,
         WebsocketPhase<impl AuthenticatedMultiplexedTransport>,
     > {
+        let transport = libp2p_dns::async_std::Transport::custom2(self.phase.transport, cfg, opts);
+        resolver_constructed();
+
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: WebsocketPhase {
-                transport: libp2p_dns::async_std::Transport::custom2(
-                    self.phase.transport,
-                    cfg,
-                    opts,
-                ),
+                #[cfg(feature = "websocket")]
+                resolver: Some(Resolver::AsyncStd(transport.resolver().clone())),
+                transport,
+                #[cfg(feature = "websocket")]
+                tls_config: None,
             },
         }
     }
@@ -81,11 +146,19 @@ impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::Tokio,
         opts: libp2p_dns::ResolverOpts,
     ) -> SwarmBuilder<super::provider::Tokio, WebsocketPhase<impl AuthenticatedMultiplexedTransport>>
     {
+        let transport = libp2p_dns::tokio::Transport::custom(self.phase.transport, cfg, opts);
+        resolver_constructed();
+
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: WebsocketPhase {
-                transport: libp2p_dns::tokio::Transport::custom(self.phase.transport, cfg, opts),
+                #[cfg(feature = "websocket")]
+                resolver: Some(Resolver::Tokio(transport.resolver().clone())),
+                transport,
+                #[cfg(feature = "websocket")]
+                tls_config: None,
             },
         }
     }
@@ -94,10 +167,15 @@ impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::Tokio,
 impl<Provider, T> SwarmBuilder<Provider, DnsPhase<T>> {
     pub(crate) fn without_dns(self) -> SwarmBuilder<Provider, WebsocketPhase<T>> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: WebsocketPhase {
                 transport: self.phase.transport,
+                #[cfg(all(not(target_arch = "wasm32"), feature = "websocket"))]
+                tls_config: None,
+                #[cfg(all(not(target_arch = "wasm32"), feature = "dns", feature = "websocket"))]
+                resolver: None,
             },
         }
     }
@@ -114,4 +192,19 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, DnsP
             .without_relay()
             .with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_dns()
+            .without_websocket()
+            .without_relay()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
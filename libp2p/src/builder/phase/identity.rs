@@ -1,5 +1,7 @@
 use super::*;
 use crate::SwarmBuilder;
+use libp2p_core::transport::Boxed;
+use libp2p_identity::PeerId;
 use std::marker::PhantomData;
 
 pub struct IdentityPhase {}
@@ -13,9 +15,35 @@ impl SwarmBuilder<NoProviderSpecified, IdentityPhase> {
         keypair: libp2p_identity::Keypair,
     ) -> SwarmBuilder<NoProviderSpecified, ProviderPhase> {
         SwarmBuilder {
-            keypair,
+            identity: keypair,
+            stack: StackDescription::default(),
             phantom: PhantomData,
             phase: ProviderPhase {},
         }
     }
+
+    /// Skips the provider and transport phases, using the given, fully upgraded transport.
+    ///
+    /// As no provider is selected, the [`libp2p_swarm::Config`] and thus the executor has to be
+    /// passed explicitly via `with_swarm_config` before building the
+    /// [`Swarm`](libp2p_swarm::Swarm).
+    pub fn with_existing_transport(
+        keypair: libp2p_identity::Keypair,
+        transport: Boxed<(PeerId, StreamMuxerBox)>,
+    ) -> SwarmBuilder<
+        NoProviderSpecified,
+        BehaviourPhase<Boxed<(PeerId, StreamMuxerBox)>, NoRelayBehaviour>,
+    > {
+        SwarmBuilder {
+            identity: keypair,
+            stack: StackDescription::default().with("other"),
+            phantom: PhantomData,
+            phase: BehaviourPhase {
+                relay_behaviour: NoRelayBehaviour,
+                transport,
+                #[cfg(feature = "metrics")]
+                metrics: None,
+            },
+        }
+    }
 }
@@ -4,7 +4,14 @@ use std::sync::Arc;
 
 use libp2p_core::upgrade::{InboundConnectionUpgrade, OutboundConnectionUpgrade};
 use libp2p_core::Transport;
-#[cfg(feature = "relay")]
+#[cfg(any(
+    feature = "relay",
+    all(
+        target_arch = "wasm32",
+        feature = "wasm-bindgen",
+        feature = "websocket-websys"
+    )
+))]
 use libp2p_core::{Negotiated, UpgradeInfo};
 #[cfg(feature = "relay")]
 use libp2p_identity::PeerId;
@@ -46,20 +53,22 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
                     .phase
                     .transport
                     .or_transport(
-                        constructor(&self.keypair)
+                        constructor(&self.identity)
                             .try_into_transport()?
                             .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn))),
                     )
                     .map(|either, _| either.into_inner()),
             },
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack.with("other"),
             phantom: PhantomData,
         })
     }
 
     pub(crate) fn without_any_other_transports(self) -> SwarmBuilder<Provider, DnsPhase<T>> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: DnsPhase {
                 transport: self.phase.transport,
@@ -68,6 +77,167 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
     }
 }
 
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "wasm-bindgen",
+    feature = "webtransport-websys"
+))]
+impl<T: AuthenticatedMultiplexedTransport>
+    SwarmBuilder<super::provider::WasmBindgen, OtherTransportPhase<T>>
+{
+    /// Adds a WebTransport based transport, see [`libp2p_webtransport_websys`].
+    ///
+    /// WebTransport connections are secured and multiplexed by the transport itself, thus no
+    /// security or multiplexer upgrade is needed.
+    pub fn with_webtransport_websys(
+        self,
+    ) -> SwarmBuilder<
+        super::provider::WasmBindgen,
+        OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+    > {
+        let webtransport = libp2p_webtransport_websys::Transport::new(
+            libp2p_webtransport_websys::Config::new(&self.identity),
+        )
+        .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)));
+
+        SwarmBuilder {
+            phase: OtherTransportPhase {
+                transport: self
+                    .phase
+                    .transport
+                    .or_transport(webtransport)
+                    .map(|either, _| either.into_inner()),
+            },
+            identity: self.identity,
+            stack: self.stack.with("webtransport"),
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "wasm-bindgen",
+    feature = "websocket-websys"
+))]
+impl<T: AuthenticatedMultiplexedTransport>
+    SwarmBuilder<super::provider::WasmBindgen, OtherTransportPhase<T>>
+{
+    /// Adds a websocket based transport, see [`libp2p_websocket_websys`].
+    ///
+    /// Like [`SwarmBuilder::with_tcp`], the connections are upgraded with the given security
+    /// and multiplexer protocols, e.g. `(libp2p_noise::Config::new, libp2p_yamux::Config::default)`.
+    pub fn with_websocket_websys<
+        SecUpgrade,
+        SecStream,
+        SecError,
+        MuxUpgrade,
+        MuxStream,
+        MuxError,
+    >(
+        self,
+        security_upgrade: SecUpgrade,
+        multiplexer_upgrade: MuxUpgrade,
+    ) -> Result<
+        SwarmBuilder<
+            super::provider::WasmBindgen,
+            OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+        >,
+        SecUpgrade::Error,
+    >
+    where
+        SecStream: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static,
+        SecError: std::error::Error + Send + Sync + 'static,
+        SecUpgrade: IntoSecurityUpgrade<libp2p_websocket_websys::Connection>,
+        SecUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + OutboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + Clone + Send + 'static,
+        <SecUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>>>::Future: Send,
+        <SecUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>>>::Future: Send,
+        <<<SecUpgrade as IntoSecurityUpgrade<libp2p_websocket_websys::Connection>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+        <<SecUpgrade as IntoSecurityUpgrade<libp2p_websocket_websys::Connection>>::Upgrade as UpgradeInfo>::Info: Send,
+
+        MuxStream: libp2p_core::muxing::StreamMuxer + Send + 'static,
+        MuxStream::Substream: Send + 'static,
+        MuxStream::Error: Send + Sync + 'static,
+        MuxUpgrade: IntoMultiplexerUpgrade<SecStream>,
+        MuxUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + OutboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + Clone + Send + 'static,
+        <MuxUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+        <MuxUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+        MuxError: std::error::Error + Send + Sync + 'static,
+        <<<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+        <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
+    {
+        let security_upgrade = security_upgrade.into_security_upgrade(&self.identity)?;
+        let multiplexer_upgrade = multiplexer_upgrade.into_multiplexer_upgrade();
+        let stack = self
+            .stack
+            .with_upgraded("websocket", &security_upgrade, &multiplexer_upgrade);
+        let websocket = libp2p_websocket_websys::Transport::default()
+            .upgrade(libp2p_core::upgrade::Version::V1Lazy)
+            .authenticate(security_upgrade)
+            .multiplex(multiplexer_upgrade)
+            .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)));
+
+        Ok(SwarmBuilder {
+            phase: OtherTransportPhase {
+                transport: self
+                    .phase
+                    .transport
+                    .or_transport(websocket)
+                    .map(|either, _| either.into_inner()),
+            },
+            identity: self.identity,
+            stack,
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "tokio", feature = "webrtc"))]
+impl<T: AuthenticatedMultiplexedTransport>
+    SwarmBuilder<super::provider::Tokio, OtherTransportPhase<T>>
+{
+    /// Adds a WebRTC direct transport, see [`libp2p_webrtc`], which browsers can connect to
+    /// without a TLS certificate signed by a certificate authority.
+    ///
+    /// WebRTC connections are secured and multiplexed by the transport itself, thus no security
+    /// or multiplexer upgrade is needed. The hash of `certificate` is part of the
+    /// `/webrtc-direct/certhash/<hash>` addresses the node listens on, thus reuse the certificate
+    /// across restarts to keep these addresses stable.
+    pub fn with_webrtc(
+        self,
+        certificate: libp2p_webrtc::tokio::Certificate,
+    ) -> SwarmBuilder<
+        super::provider::Tokio,
+        OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+    > {
+        let webrtc = libp2p_webrtc::tokio::Transport::new(self.identity.clone(), certificate);
+        self.with_webrtc_transport(webrtc)
+    }
+
+    pub(crate) fn with_webrtc_transport(
+        self,
+        webrtc: libp2p_webrtc::tokio::Transport,
+    ) -> SwarmBuilder<
+        super::provider::Tokio,
+        OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+    > {
+        let webrtc = webrtc.map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)));
+
+        SwarmBuilder {
+            phase: OtherTransportPhase {
+                transport: self
+                    .phase
+                    .transport
+                    .or_transport(webrtc)
+                    .map(|either, _| either.into_inner()),
+            },
+            identity: self.identity,
+            stack: self.stack.with("webrtc-direct"),
+            phantom: PhantomData,
+        }
+    }
+}
+
 // Shortcuts
 #[cfg(all(not(target_arch = "wasm32"), feature = "async-std", feature = "dns"))]
 impl<T: AuthenticatedMultiplexedTransport>
@@ -214,6 +384,19 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
             .without_bandwidth_logging()
             .with_bandwidth_metrics(registry)
     }
+
+    pub fn with_metrics(
+        self,
+        registry: &mut libp2p_metrics::Registry,
+    ) -> SwarmBuilder<Provider, BehaviourPhase<T, NoRelayBehaviour>> {
+        self.without_any_other_transports()
+            .without_dns()
+            .without_websocket()
+            .without_relay()
+            .without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_metrics(registry)
+    }
 }
 impl<Provider, T: AuthenticatedMultiplexedTransport>
     SwarmBuilder<Provider, OtherTransportPhase<T>>
@@ -229,6 +412,23 @@ impl<Provider, T: AuthenticatedMultiplexedTransport>
             .without_bandwidth_logging()
             .with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_any_other_transports()
+            .without_dns()
+            .without_websocket()
+            .without_relay()
+            .without_bandwidth_logging()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
 
 pub trait TryIntoTransport<T>: private::Sealed<Self::Error> {
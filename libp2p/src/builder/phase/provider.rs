@@ -9,27 +9,30 @@ impl SwarmBuilder<NoProviderSpecified, ProviderPhase> {
     #[cfg(all(not(target_arch = "wasm32"), feature = "async-std"))]
     pub fn with_async_std(self) -> SwarmBuilder<AsyncStd, TcpPhase> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: std::marker::PhantomData,
-            phase: TcpPhase {},
+            phase: TcpPhase::default(),
         }
     }
 
     #[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
     pub fn with_tokio(self) -> SwarmBuilder<Tokio, TcpPhase> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: std::marker::PhantomData,
-            phase: TcpPhase {},
+            phase: TcpPhase::default(),
         }
     }
 
     #[cfg(feature = "wasm-bindgen")]
     pub fn with_wasm_bindgen(self) -> SwarmBuilder<WasmBindgen, TcpPhase> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: std::marker::PhantomData,
-            phase: TcpPhase {},
+            phase: TcpPhase::default(),
         }
     }
 }
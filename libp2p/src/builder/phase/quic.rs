@@ -12,6 +12,8 @@ use std::{marker::PhantomData, sync::Arc};
 
 pub struct QuicPhase<T> {
     pub(crate) transport: T,
+    #[cfg(all(not(target_arch = "wasm32"), feature = "quic"))]
+    pub(crate) socket_buffer_sizes: Option<(usize, usize)>,
 }
 
 macro_rules! impl_quic_builder {
@@ -27,6 +29,13 @@ macro_rules! impl_quic_builder {
                 self.with_quic_config(std::convert::identity)
             }
 
+            /// Adds QUIC with the [`libp2p_quic::Config`] returned by `constructor`.
+            ///
+            /// To share the UDP port of QUIC with WebRTC-direct, enable
+            /// [`libp2p_quic::Config::reuse_port`] here, add a `libp2p_webrtc::tokio::Transport`
+            /// with `with_reuse_port(true)` via
+            /// [`with_other_transport`](SwarmBuilder::with_other_transport) and listen on both
+            /// `/udp/<port>/quic-v1` and `/udp/<port>/webrtc-direct` with the same port.
             pub fn with_quic_config(
                 self,
                 constructor: impl FnOnce(libp2p_quic::Config) -> libp2p_quic::Config,
@@ -40,16 +49,23 @@ macro_rules! impl_quic_builder {
                             .phase
                             .transport
                             .or_transport(
-                                libp2p_quic::$quic::Transport::new(constructor(
-                                    libp2p_quic::Config::new(&self.keypair),
-                                ))
+                                libp2p_quic::$quic::Transport::new(constructor({
+                                    let config = libp2p_quic::Config::new(&self.identity);
+                                    match self.phase.socket_buffer_sizes {
+                                        Some((send, recv)) => {
+                                            config.with_socket_buffer_sizes(send, recv)
+                                        }
+                                        None => config,
+                                    }
+                                }))
                                 .map(|(peer_id, muxer), _| {
                                     (peer_id, libp2p_core::muxing::StreamMuxerBox::new(muxer))
                                 }),
                             )
                             .map(|either, _| either.into_inner()),
                     },
-                    keypair: self.keypair,
+                    identity: self.identity,
+                    stack: self.stack.with("quic-v1"),
                     phantom: PhantomData,
                 }
             }
@@ -60,10 +76,75 @@ macro_rules! impl_quic_builder {
 impl_quic_builder!("async-std", AsyncStd, async_std);
 impl_quic_builder!("tokio", super::provider::Tokio, tokio);
 
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "quic",
+    feature = "webrtc"
+))]
+impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::Tokio, QuicPhase<T>> {
+    /// Adds QUIC and a WebRTC direct transport sharing the UDP port of their listeners, e.g. to
+    /// forward a single port through a NAT for both.
+    ///
+    /// Listen on `/udp/<port>/quic-v1` and `/udp/<port>/webrtc-direct` with the same port,
+    /// which must not be `0`. On Linux, incoming datagrams are routed to the listener of their
+    /// protocol, see [`libp2p_webrtc::tokio::Transport::with_reuse_port`].
+    ///
+    /// See [`SwarmBuilder::with_webrtc`] for the role of `webrtc_certificate`.
+    ///
+    /// ``` rust
+    /// # use libp2p::webrtc::tokio::Certificate;
+    /// # use libp2p::SwarmBuilder;
+    /// # use std::error::Error;
+    /// # async fn build_swarm(webrtc_certificate: Certificate) -> Result<(), Box<dyn Error>> {
+    /// let mut swarm = SwarmBuilder::with_new_identity()
+    ///     .with_tokio()
+    ///     .with_shared_udp(webrtc_certificate)
+    ///     .with_behaviour(|_| libp2p::swarm::dummy::Behaviour)?
+    ///     .build();
+    ///
+    /// swarm.listen_on("/ip4/0.0.0.0/udp/4001/quic-v1".parse()?)?;
+    /// swarm.listen_on("/ip4/0.0.0.0/udp/4001/webrtc-direct".parse()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_shared_udp(
+        self,
+        webrtc_certificate: libp2p_webrtc::tokio::Certificate,
+    ) -> SwarmBuilder<
+        super::provider::Tokio,
+        OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+    > {
+        let builder = self.with_quic_config(|mut config| {
+            config.reuse_port = true;
+            config
+        });
+        let webrtc =
+            libp2p_webrtc::tokio::Transport::new(builder.identity.clone(), webrtc_certificate)
+                .with_reuse_port(true);
+
+        builder.with_webrtc_transport(webrtc)
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "quic"))]
+impl<Provider, T> SwarmBuilder<Provider, QuicPhase<T>> {
+    /// Sets the sizes of the send and receive buffers of the UDP sockets of the QUIC transport
+    /// added next, see [`libp2p_quic::Config::with_socket_buffer_sizes`].
+    ///
+    /// The [`libp2p_quic::Config`] passed to the constructor of
+    /// [`with_quic_config`](SwarmBuilder::with_quic_config) already has the sizes set.
+    pub fn with_socket_buffer_sizes(mut self, send: usize, recv: usize) -> Self {
+        self.phase.socket_buffer_sizes = Some((send, recv));
+        self
+    }
+}
+
 impl<Provider, T> SwarmBuilder<Provider, QuicPhase<T>> {
     pub(crate) fn without_quic(self) -> SwarmBuilder<Provider, OtherTransportPhase<T>> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: OtherTransportPhase {
                 transport: self.phase.transport,
@@ -147,6 +228,23 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Quic
             .without_relay()
             .with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_quic()
+            .without_any_other_transports()
+            .without_dns()
+            .without_websocket()
+            .without_relay()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
 #[cfg(all(not(target_arch = "wasm32"), feature = "async-std", feature = "dns"))]
 impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<super::provider::AsyncStd, QuicPhase<T>> {
@@ -275,6 +373,23 @@ impl_quic_phase_with_websocket!(
     super::provider::Tokio,
     rw_stream_sink::RwStreamSink<libp2p_websocket::BytesConnection<libp2p_tcp::tokio::TcpStream>>
 );
+#[cfg(all(not(target_arch = "wasm32"), feature = "websocket"))]
+impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, QuicPhase<T>> {
+    /// See [`SwarmBuilder::with_wss`].
+    pub fn with_wss(
+        self,
+        tls_cert: impl IntoIterator<Item = libp2p_websocket::tls::Certificate>,
+        tls_key: libp2p_websocket::tls::PrivateKey,
+    ) -> Result<
+        SwarmBuilder<Provider, WebsocketPhase<impl AuthenticatedMultiplexedTransport>>,
+        libp2p_websocket::tls::Error,
+    > {
+        self.without_quic()
+            .without_any_other_transports()
+            .without_dns()
+            .with_wss(tls_cert, tls_key)
+    }
+}
 impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, QuicPhase<T>> {
     #[allow(deprecated)]
     #[deprecated(note = "Use `with_bandwidth_metrics` instead.")]
@@ -313,4 +428,18 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Quic
             .without_bandwidth_logging()
             .with_bandwidth_metrics(registry)
     }
+
+    pub fn with_metrics(
+        self,
+        registry: &mut libp2p_metrics::Registry,
+    ) -> SwarmBuilder<Provider, BehaviourPhase<T, NoRelayBehaviour>> {
+        self.without_quic()
+            .without_any_other_transports()
+            .without_dns()
+            .without_websocket()
+            .without_relay()
+            .without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_metrics(registry)
+    }
 }
@@ -78,11 +78,16 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Rela
     <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
     {
         let (relay_transport, relay_behaviour) =
-            libp2p_relay::client::new(self.keypair.public().to_peer_id());
+            libp2p_relay::client::new(self.identity.public().to_peer_id());
+        let security_upgrade = security_upgrade.into_security_upgrade(&self.identity)?;
+        let multiplexer_upgrade = multiplexer_upgrade.into_multiplexer_upgrade();
+        let stack =
+            self.stack
+                .with_upgraded("p2p-circuit", &security_upgrade, &multiplexer_upgrade);
         let relay_transport = relay_transport
             .upgrade(libp2p_core::upgrade::Version::V1Lazy)
-            .authenticate(security_upgrade.into_security_upgrade(&self.keypair)?)
-            .multiplex(multiplexer_upgrade.into_multiplexer_upgrade())
+            .authenticate(security_upgrade)
+            .multiplex(multiplexer_upgrade)
             .map(|(p, c), _| (p, StreamMuxerBox::new(c)));
 
         Ok(SwarmBuilder {
@@ -92,12 +97,18 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Rela
                     .or_transport(self.phase.transport)
                     .map(|either, _| either.into_inner()),
             },
-            keypair: self.keypair,
+            identity: self.identity,
+            stack,
             phantom: PhantomData,
         })
     }
 }
 
+#[cfg(all(feature = "relay", not(feature = "noise"), not(feature = "tls")))]
+impl_security_feature_stubs!(
+    <Provider, T> SwarmBuilder<Provider, RelayPhase<T>>
+);
+
 pub struct NoRelayBehaviour;
 
 impl<Provider, T> SwarmBuilder<Provider, RelayPhase<T>> {
@@ -105,7 +116,8 @@ impl<Provider, T> SwarmBuilder<Provider, RelayPhase<T>> {
         self,
     ) -> SwarmBuilder<Provider, BandwidthLoggingPhase<T, NoRelayBehaviour>> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: BandwidthLoggingPhase {
                 transport: self.phase.transport,
@@ -129,6 +141,16 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Rela
             .without_bandwidth_logging()
             .with_bandwidth_metrics(registry)
     }
+
+    pub fn with_metrics(
+        self,
+        registry: &mut libp2p_metrics::Registry,
+    ) -> SwarmBuilder<Provider, BehaviourPhase<T, NoRelayBehaviour>> {
+        self.without_relay()
+            .without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_metrics(registry)
+    }
 }
 impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, RelayPhase<T>> {
     pub fn with_behaviour<B, R: TryIntoBehaviour<B>>(
@@ -140,4 +162,19 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Rela
             .without_bandwidth_metrics()
             .with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_relay()
+            .without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
@@ -5,23 +5,66 @@ use super::*;
 pub struct SwarmPhase<T, B> {
     pub(crate) behaviour: B,
     pub(crate) transport: T,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<libp2p_metrics::Metrics>,
+}
+
+impl<T, B, Identity> SwarmBuilder<NoProviderSpecified, SwarmPhase<T, B>, Identity> {
+    /// Uses the given [`libp2p_swarm::Config`], including its executor, for the
+    /// [`Swarm`](libp2p_swarm::Swarm) of a builder without a provider.
+    pub fn with_swarm_config(
+        self,
+        config: libp2p_swarm::Config,
+    ) -> SwarmBuilder<NoProviderSpecified, BuildPhase<T, B>, Identity> {
+        SwarmBuilder {
+            phase: BuildPhase {
+                behaviour: self.phase.behaviour,
+                transport: self.phase.transport,
+                swarm_config: config,
+                #[cfg(feature = "metrics")]
+                metrics: self.phase.metrics,
+            },
+            identity: self.identity,
+            stack: self.stack,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<Provider, T, B> SwarmBuilder<Provider, SwarmPhase<T, B>> {
+    /// Drops the [`Keypair`](libp2p_identity::Keypair) of the
+    /// [`Swarm`](libp2p_swarm::Swarm) being built, zeroizing its secret key if the `zeroize`
+    /// feature is enabled, and keeps only its [`PeerId`](libp2p_identity::PeerId).
+    ///
+    /// [`SwarmBuilder::keypair`] is no longer available afterwards.
+    pub fn drop_keypair(self) -> SwarmBuilder<Provider, SwarmPhase<T, B>, libp2p_identity::PeerId> {
+        SwarmBuilder {
+            identity: self.identity.public().to_peer_id(),
+            stack: self.stack,
+            phantom: std::marker::PhantomData,
+            phase: self.phase,
+        }
+    }
 }
 
 macro_rules! impl_with_swarm_config {
     ($providerKebabCase:literal, $providerPascalCase:ty, $config:expr) => {
         #[cfg(feature = $providerKebabCase)]
-        impl<T, B> SwarmBuilder<$providerPascalCase, SwarmPhase<T, B>> {
+        impl<T, B, Identity> SwarmBuilder<$providerPascalCase, SwarmPhase<T, B>, Identity> {
             pub fn with_swarm_config(
                 self,
                 constructor: impl FnOnce(libp2p_swarm::Config) -> libp2p_swarm::Config,
-            ) -> SwarmBuilder<$providerPascalCase, BuildPhase<T, B>> {
+            ) -> SwarmBuilder<$providerPascalCase, BuildPhase<T, B>, Identity> {
                 SwarmBuilder {
                     phase: BuildPhase {
                         behaviour: self.phase.behaviour,
                         transport: self.phase.transport,
                         swarm_config: constructor($config),
+                        #[cfg(feature = "metrics")]
+                        metrics: self.phase.metrics,
                     },
-                    keypair: self.keypair,
+                    identity: self.identity,
+                    stack: self.stack,
                     phantom: std::marker::PhantomData,
                 }
             }
@@ -31,6 +74,7 @@ macro_rules! impl_with_swarm_config {
             where
                 B: libp2p_swarm::NetworkBehaviour,
                 T: AuthenticatedMultiplexedTransport,
+                Identity: LocalIdentity,
             {
                 self.with_swarm_config(std::convert::identity).build()
             }
@@ -1,22 +1,47 @@
 use super::*;
 use crate::SwarmBuilder;
+#[cfg(any(
+    all(
+        not(target_arch = "wasm32"),
+        any(feature = "tcp", feature = "websocket")
+    ),
+    all(
+        target_arch = "wasm32",
+        feature = "wasm-bindgen",
+        feature = "websocket-websys"
+    )
+))]
+use libp2p_core::muxing::StreamMuxer;
 #[cfg(all(
     not(target_arch = "wasm32"),
     any(feature = "tcp", feature = "websocket")
 ))]
-use libp2p_core::muxing::{StreamMuxer, StreamMuxerBox};
+use libp2p_core::muxing::StreamMuxerBox;
 #[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
 use libp2p_core::Transport;
-#[cfg(all(
-    not(target_arch = "wasm32"),
-    any(feature = "tcp", feature = "websocket")
+#[cfg(any(
+    all(
+        not(target_arch = "wasm32"),
+        any(feature = "tcp", feature = "websocket")
+    ),
+    all(
+        target_arch = "wasm32",
+        feature = "wasm-bindgen",
+        feature = "websocket-websys"
+    )
 ))]
 use libp2p_core::{
     upgrade::InboundConnectionUpgrade, upgrade::OutboundConnectionUpgrade, Negotiated, UpgradeInfo,
 };
 use std::marker::PhantomData;
 
-pub struct TcpPhase {}
+#[derive(Default)]
+pub struct TcpPhase {
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tcp"))]
+    socks5_proxy: Option<libp2p_tcp::socks5::Config>,
+    #[cfg(all(not(target_arch = "wasm32"), feature = "tcp"))]
+    socket_buffer_sizes: Option<(usize, usize)>,
+}
 
 macro_rules! impl_tcp_builder {
     ($providerKebabCase:literal, $providerPascalCase:ty, $path:ident) => {
@@ -77,20 +102,81 @@ macro_rules! impl_tcp_builder {
                 <<<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
                 <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
             {
+                let tcp_config = match self.phase.socket_buffer_sizes {
+                    Some((send, recv)) => tcp_config.socket_buffer_sizes(send, recv),
+                    None => tcp_config,
+                };
+                let transport = libp2p_tcp::$path::Transport::new(tcp_config);
+                let transport = match self.phase.socks5_proxy {
+                    Some(proxy) => either::Either::Left(libp2p_tcp::socks5::Transport::new(transport, proxy)),
+                    None => either::Either::Right(transport),
+                };
+
+                let security_upgrade = security_upgrade.into_security_upgrade(&self.identity)?;
+                let multiplexer_upgrade = multiplexer_upgrade.into_multiplexer_upgrade();
+                let stack = self.stack.with_upgraded("tcp", &security_upgrade, &multiplexer_upgrade);
+
                 Ok(SwarmBuilder {
                     phase: QuicPhase {
-                        transport: libp2p_tcp::$path::Transport::new(tcp_config)
+                        transport: transport
+                            .map(|stream, _| stream.into_inner())
+                            .map_err(either::Either::into_inner)
                             .upgrade(libp2p_core::upgrade::Version::V1Lazy)
-                            .authenticate(
-                                security_upgrade.into_security_upgrade(&self.keypair)?,
-                            )
-                            .multiplex(multiplexer_upgrade.into_multiplexer_upgrade())
+                            .authenticate(security_upgrade)
+                            .multiplex(multiplexer_upgrade)
                             .map(|(p, c), _| (p, StreamMuxerBox::new(c))),
+                        #[cfg(all(not(target_arch = "wasm32"), feature = "quic"))]
+                        socket_buffer_sizes: None,
                     },
-                    keypair: self.keypair,
+                    identity: self.identity,
+                    stack,
                     phantom: PhantomData,
                 })
             }
+
+            /// Adds a TCP based transport tuned for low latency.
+            ///
+            /// This is a shortcut for [`SwarmBuilder::with_tcp`] with a [`libp2p_tcp::Config`]
+            /// that
+            ///
+            ///   * sets `TCP_NODELAY`, i.e. disables Nagle's algorithm. Small writes are sent
+            ///     immediately instead of being coalesced, at the cost of more packets and
+            ///     syscalls on chatty connections.
+            ///   * enables port reuse, see [`libp2p_tcp::Config::port_reuse`]. Outgoing
+            ///     connections share the port of a listening socket, which limits them to a
+            ///     single connection per remote address and port and lets other sockets with
+            ///     port reuse enabled bind the same port.
+            pub fn with_low_latency_tcp<SecUpgrade, SecStream, SecError, MuxUpgrade, MuxStream, MuxError>(
+                self,
+                security_upgrade: SecUpgrade,
+                multiplexer_upgrade: MuxUpgrade,
+            ) -> Result<
+                SwarmBuilder<$providerPascalCase, QuicPhase<impl AuthenticatedMultiplexedTransport>>,
+            SecUpgrade::Error,
+            >
+            where
+                SecStream: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static,
+                SecError: std::error::Error + Send + Sync + 'static,
+                SecUpgrade: IntoSecurityUpgrade<libp2p_tcp::$path::TcpStream>,
+                SecUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<libp2p_tcp::$path::TcpStream>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + OutboundConnectionUpgrade<Negotiated<libp2p_tcp::$path::TcpStream>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + Clone + Send + 'static,
+                <SecUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<libp2p_tcp::$path::TcpStream>>>::Future: Send,
+                <SecUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<libp2p_tcp::$path::TcpStream>>>::Future: Send,
+                <<<SecUpgrade as IntoSecurityUpgrade<libp2p_tcp::$path::TcpStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+                <<SecUpgrade as IntoSecurityUpgrade<libp2p_tcp::$path::TcpStream>>::Upgrade as UpgradeInfo>::Info: Send,
+
+                MuxStream: StreamMuxer + Send + 'static,
+                MuxStream::Substream: Send + 'static,
+                MuxStream::Error: Send + Sync + 'static,
+                MuxUpgrade: IntoMultiplexerUpgrade<SecStream>,
+                MuxUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + OutboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + Clone + Send + 'static,
+                <MuxUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+                <MuxUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+                MuxError: std::error::Error + Send + Sync + 'static,
+                <<<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+                <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
+            {
+                self.with_tcp(low_latency_tcp_config(), security_upgrade, multiplexer_upgrade)
+            }
         }
     };
 }
@@ -98,15 +184,73 @@ macro_rules! impl_tcp_builder {
 impl_tcp_builder!("async-std", super::provider::AsyncStd, async_io);
 impl_tcp_builder!("tokio", super::provider::Tokio, tokio);
 
+/// The [`libp2p_tcp::Config`] used by [`SwarmBuilder::with_low_latency_tcp`].
+#[cfg(all(not(target_arch = "wasm32"), feature = "tcp"))]
+pub(crate) fn low_latency_tcp_config() -> libp2p_tcp::Config {
+    libp2p_tcp::Config::default().nodelay(true).port_reuse(true)
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "tcp"))]
+impl<Provider> SwarmBuilder<Provider, TcpPhase> {
+    /// Dials all connections of the TCP based transport added next through the given SOCKS5
+    /// proxy, while listening as usual.
+    ///
+    /// Pass a [`libp2p_tcp::socks5::Config`] instead of the proxy address in case the proxy
+    /// requires authentication.
+    ///
+    /// ``` rust
+    /// # use libp2p::SwarmBuilder;
+    /// # use std::error::Error;
+    /// # async fn build_swarm() -> Result<(), Box<dyn Error>> {
+    /// let swarm = SwarmBuilder::with_new_identity()
+    ///     .with_tokio()
+    ///     .with_socks5_proxy("127.0.0.1:1080".parse::<std::net::SocketAddr>()?)
+    ///     .with_tcp(
+    ///         Default::default(),
+    ///         (libp2p_tls::Config::new, libp2p_noise::Config::new),
+    ///         libp2p_yamux::Config::default,
+    ///     )?
+    /// # ;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_socks5_proxy(mut self, proxy: impl Into<libp2p_tcp::socks5::Config>) -> Self {
+        self.phase.socks5_proxy = Some(proxy.into());
+        self
+    }
+
+    /// Sets the sizes of the send and receive buffers of the sockets of the TCP based transport
+    /// added next, see [`libp2p_tcp::Config::socket_buffer_sizes`].
+    ///
+    /// Overrides the sizes of the [`libp2p_tcp::Config`] passed to [`SwarmBuilder::with_tcp`].
+    pub fn with_socket_buffer_sizes(mut self, send: usize, recv: usize) -> Self {
+        self.phase.socket_buffer_sizes = Some((send, recv));
+        self
+    }
+}
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tcp",
+    not(feature = "noise"),
+    not(feature = "tls")
+))]
+impl_security_feature_stubs!(
+    <Provider> SwarmBuilder<Provider, TcpPhase>
+);
+
 impl<Provider> SwarmBuilder<Provider, TcpPhase> {
     pub(crate) fn without_tcp(
         self,
     ) -> SwarmBuilder<Provider, QuicPhase<impl AuthenticatedMultiplexedTransport>> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: QuicPhase {
                 transport: libp2p_core::transport::dummy::DummyTransport::new(),
+                #[cfg(all(not(target_arch = "wasm32"), feature = "quic"))]
+                socket_buffer_sizes: None,
             },
         }
     }
@@ -159,6 +303,24 @@ impl SwarmBuilder<super::provider::Tokio, TcpPhase> {
         self.without_tcp().with_quic_config(constructor)
     }
 }
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "quic",
+    feature = "webrtc"
+))]
+impl SwarmBuilder<super::provider::Tokio, TcpPhase> {
+    /// See [`SwarmBuilder::with_shared_udp`].
+    pub fn with_shared_udp(
+        self,
+        webrtc_certificate: libp2p_webrtc::tokio::Certificate,
+    ) -> SwarmBuilder<
+        super::provider::Tokio,
+        OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+    > {
+        self.without_tcp().with_shared_udp(webrtc_certificate)
+    }
+}
 impl<Provider> SwarmBuilder<Provider, TcpPhase> {
     pub fn with_other_transport<
         Muxer: libp2p_core::muxing::StreamMuxer + Send + 'static,
@@ -183,6 +345,73 @@ impl<Provider> SwarmBuilder<Provider, TcpPhase> {
             .with_other_transport(constructor)
     }
 }
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "wasm-bindgen",
+    feature = "webtransport-websys"
+))]
+impl SwarmBuilder<super::provider::WasmBindgen, TcpPhase> {
+    /// See [`SwarmBuilder::with_webtransport_websys`].
+    pub fn with_webtransport_websys(
+        self,
+    ) -> SwarmBuilder<
+        super::provider::WasmBindgen,
+        OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+    > {
+        self.without_tcp().without_quic().with_webtransport_websys()
+    }
+}
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "wasm-bindgen",
+    feature = "websocket-websys"
+))]
+impl SwarmBuilder<super::provider::WasmBindgen, TcpPhase> {
+    /// See [`SwarmBuilder::with_websocket_websys`].
+    pub fn with_websocket_websys<
+        SecUpgrade,
+        SecStream,
+        SecError,
+        MuxUpgrade,
+        MuxStream,
+        MuxError,
+    >(
+        self,
+        security_upgrade: SecUpgrade,
+        multiplexer_upgrade: MuxUpgrade,
+    ) -> Result<
+        SwarmBuilder<
+            super::provider::WasmBindgen,
+            OtherTransportPhase<impl AuthenticatedMultiplexedTransport>,
+        >,
+        SecUpgrade::Error,
+    >
+    where
+        SecStream: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static,
+        SecError: std::error::Error + Send + Sync + 'static,
+        SecUpgrade: IntoSecurityUpgrade<libp2p_websocket_websys::Connection>,
+        SecUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + OutboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + Clone + Send + 'static,
+        <SecUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>>>::Future: Send,
+        <SecUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<libp2p_websocket_websys::Connection>>>::Future: Send,
+        <<<SecUpgrade as IntoSecurityUpgrade<libp2p_websocket_websys::Connection>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+        <<SecUpgrade as IntoSecurityUpgrade<libp2p_websocket_websys::Connection>>::Upgrade as UpgradeInfo>::Info: Send,
+
+        MuxStream: StreamMuxer + Send + 'static,
+        MuxStream::Substream: Send + 'static,
+        MuxStream::Error: Send + Sync + 'static,
+        MuxUpgrade: IntoMultiplexerUpgrade<SecStream>,
+        MuxUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + OutboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + Clone + Send + 'static,
+        <MuxUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+        <MuxUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+        MuxError: std::error::Error + Send + Sync + 'static,
+        <<<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+        <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
+    {
+        self.without_tcp()
+            .without_quic()
+            .with_websocket_websys(security_upgrade, multiplexer_upgrade)
+    }
+}
 macro_rules! impl_tcp_phase_with_websocket {
     ($providerKebabCase:literal, $providerPascalCase:ty, $websocketStream:ty) => {
         #[cfg(all(feature = $providerKebabCase, not(target_arch = "wasm32"), feature = "websocket"))]
@@ -249,3 +478,360 @@ impl_tcp_phase_with_websocket!(
     super::provider::Tokio,
     rw_stream_sink::RwStreamSink<libp2p_websocket::BytesConnection<libp2p_tcp::tokio::TcpStream>>
 );
+#[cfg(all(not(target_arch = "wasm32"), feature = "websocket"))]
+impl<Provider> SwarmBuilder<Provider, TcpPhase> {
+    /// See [`SwarmBuilder::with_wss`].
+    pub fn with_wss(
+        self,
+        tls_cert: impl IntoIterator<Item = libp2p_websocket::tls::Certificate>,
+        tls_key: libp2p_websocket::tls::PrivateKey,
+    ) -> Result<
+        SwarmBuilder<Provider, WebsocketPhase<impl AuthenticatedMultiplexedTransport>>,
+        libp2p_websocket::tls::Error,
+    > {
+        self.without_tcp()
+            .without_quic()
+            .without_any_other_transports()
+            .without_dns()
+            .with_wss(tls_cert, tls_key)
+    }
+}
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "async-std",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "quic",
+    feature = "dns",
+    feature = "websocket",
+    feature = "relay"
+))]
+impl SwarmBuilder<super::provider::AsyncStd, TcpPhase> {
+    /// Adds the universal transport stack: TCP, QUIC, WebSocket and a relay client, with DNS
+    /// resolution, securing and multiplexing connections via Noise and Yamux where needed.
+    ///
+    /// This is a shortcut for
+    ///
+    /// ``` rust,ignore
+    /// builder
+    ///     .with_tcp(Default::default(), libp2p_noise::Config::new, libp2p_yamux::Config::default)?
+    ///     .with_quic()
+    ///     .with_dns()
+    ///     .await?
+    ///     .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+    ///     .await?
+    ///     .with_relay_client(libp2p_noise::Config::new, libp2p_yamux::Config::default)?
+    /// ```
+    pub async fn with_universal_transport(
+        self,
+    ) -> Result<
+        SwarmBuilder<
+            super::provider::AsyncStd,
+            BehaviourPhase<impl AuthenticatedMultiplexedTransport, libp2p_relay::client::Behaviour>,
+        >,
+        UniversalTransportError,
+    > {
+        Ok(self
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .map_err(UniversalTransportErrorInner::Noise)?
+            .with_quic()
+            .with_dns()
+            .await
+            .map_err(UniversalTransportErrorInner::Dns)?
+            .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .await
+            .map_err(UniversalTransportErrorInner::Websocket)?
+            .with_relay_client(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .map_err(UniversalTransportErrorInner::Noise)?
+            .without_bandwidth_logging()
+            .without_bandwidth_metrics())
+    }
+}
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "quic",
+    feature = "dns",
+    feature = "websocket",
+    feature = "relay"
+))]
+impl SwarmBuilder<super::provider::Tokio, TcpPhase> {
+    /// Adds the universal transport stack: TCP, QUIC, WebSocket and a relay client, with DNS
+    /// resolution, securing and multiplexing connections via Noise and Yamux where needed.
+    ///
+    /// This is a shortcut for
+    ///
+    /// ``` rust,ignore
+    /// builder
+    ///     .with_tcp(Default::default(), libp2p_noise::Config::new, libp2p_yamux::Config::default)?
+    ///     .with_quic()
+    ///     .with_dns()?
+    ///     .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+    ///     .await?
+    ///     .with_relay_client(libp2p_noise::Config::new, libp2p_yamux::Config::default)?
+    /// ```
+    ///
+    /// ``` rust
+    /// # use libp2p::SwarmBuilder;
+    /// # use std::error::Error;
+    /// # async fn build_swarm() -> Result<(), Box<dyn Error>> {
+    /// let swarm = SwarmBuilder::with_new_identity()
+    ///     .with_tokio()
+    ///     .with_universal_transport()
+    ///     .await?
+    ///     .with_behaviour(|_key, relay| relay)?
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_universal_transport(
+        self,
+    ) -> Result<
+        SwarmBuilder<
+            super::provider::Tokio,
+            BehaviourPhase<impl AuthenticatedMultiplexedTransport, libp2p_relay::client::Behaviour>,
+        >,
+        UniversalTransportError,
+    > {
+        Ok(self
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .map_err(UniversalTransportErrorInner::Noise)?
+            .with_quic()
+            .with_dns()
+            .map_err(UniversalTransportErrorInner::Dns)?
+            .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .await
+            .map_err(UniversalTransportErrorInner::Websocket)?
+            .with_relay_client(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .map_err(UniversalTransportErrorInner::Noise)?
+            .without_bandwidth_logging()
+            .without_bandwidth_metrics())
+    }
+}
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "dns",
+    feature = "websocket",
+    feature = "webrtc"
+))]
+impl SwarmBuilder<super::provider::Tokio, TcpPhase> {
+    /// Adds the transports browsers can connect to: WebSocket, secured via TLS if a TLS
+    /// configuration is given, and WebRTC direct, alongside TCP for non-browser peers, with DNS
+    /// resolution.
+    ///
+    /// TCP and WebSocket connections are secured via Noise and multiplexed via Yamux, which is
+    /// what browser implementations of libp2p support. This is a shortcut for
+    ///
+    /// ``` rust,ignore
+    /// builder
+    ///     .with_tcp(Default::default(), libp2p_noise::Config::new, libp2p_yamux::Config::default)?
+    ///     .with_other_transport(|key| /* libp2p_webrtc::tokio::Transport */)?
+    ///     .with_dns()?
+    ///     .with_wss(/* certificate and key */)?
+    ///     .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+    ///     .await?
+    /// ```
+    ///
+    /// returning the same phase, thus e.g. a relay client can still be added afterwards.
+    ///
+    /// Once built, listen on
+    ///
+    /// - `/ip4/0.0.0.0/tcp/<port>/wss` for secure WebSocket connections, or
+    ///   `/ip4/0.0.0.0/tcp/<port>/ws` without a TLS configuration. Browsers only allow secure
+    ///   WebSockets on HTTPS pages, which in turn requires the certificate to be valid for the
+    ///   domain browsers dial, i.e. `/dns4/<domain>/tcp/<port>/wss`.
+    /// - `/ip4/0.0.0.0/udp/<port>/webrtc-direct` for WebRTC direct connections. The listen
+    ///   addresses reported via [`SwarmEvent::NewListenAddr`](libp2p_swarm::SwarmEvent::NewListenAddr)
+    ///   carry the `/certhash` browsers need to dial the node.
+    /// - `/ip4/0.0.0.0/tcp/<port>` for TCP connections of non-browser peers.
+    ///
+    /// Only a single WebSocket listener per port is possible, thus use different ports for the
+    /// TCP and WebSocket listeners.
+    ///
+    /// ``` rust
+    /// # use libp2p::webrtc::tokio::Certificate;
+    /// # use libp2p::{BrowserPreset, SwarmBuilder};
+    /// # use std::error::Error;
+    /// # async fn build_swarm(webrtc_certificate: Certificate) -> Result<(), Box<dyn Error>> {
+    /// let mut swarm = SwarmBuilder::with_new_identity()
+    ///     .with_tokio()
+    ///     .with_browser_reachable_defaults(BrowserPreset {
+    ///         webrtc_certificate,
+    ///         wss_tls_config: None,
+    ///         enable_webtransport: false,
+    ///     })
+    ///     .await?
+    ///     .with_behaviour(|_| libp2p::swarm::dummy::Behaviour)?
+    ///     .build();
+    ///
+    /// swarm.listen_on("/ip4/0.0.0.0/tcp/0/ws".parse()?)?;
+    /// swarm.listen_on("/ip4/0.0.0.0/udp/0/webrtc-direct".parse()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_browser_reachable_defaults(
+        self,
+        preset: BrowserPreset,
+    ) -> Result<
+        SwarmBuilder<super::provider::Tokio, RelayPhase<impl AuthenticatedMultiplexedTransport>>,
+        BrowserPresetError,
+    > {
+        let BrowserPreset {
+            webrtc_certificate,
+            wss_tls_config,
+            enable_webtransport,
+        } = preset;
+
+        if enable_webtransport {
+            return Err(BrowserPresetErrorInner::WebTransportUnsupported.into());
+        }
+
+        let mut builder = self
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .map_err(BrowserPresetErrorInner::Noise)?
+            .without_quic()
+            .with_webrtc(webrtc_certificate)
+            .with_dns()
+            .map_err(BrowserPresetErrorInner::Dns)?;
+        builder.phase.tls_config = wss_tls_config;
+
+        Ok(builder
+            .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .await
+            .map_err(BrowserPresetErrorInner::Websocket)?)
+    }
+}
+
+/// The configuration of [`SwarmBuilder::with_browser_reachable_defaults`].
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "dns",
+    feature = "websocket",
+    feature = "webrtc"
+))]
+pub struct BrowserPreset {
+    /// The certificate of the WebRTC direct transport, see
+    /// [`libp2p_webrtc::tokio::Certificate::generate`].
+    ///
+    /// Its hash is part of the WebRTC direct addresses of the node.
+    pub webrtc_certificate: libp2p_webrtc::tokio::Certificate,
+    /// The TLS configuration of secure WebSocket (`/wss`) listeners, see
+    /// [`libp2p_websocket::tls::Config::new`].
+    ///
+    /// Without one, only plain WebSocket (`/ws`) connections are possible.
+    pub wss_tls_config: Option<libp2p_websocket::tls::Config>,
+    /// Whether to add a WebTransport server transport.
+    ///
+    /// Not supported yet, as no WebTransport server transport exists for non-browser targets.
+    /// Setting it results in an error.
+    pub enable_webtransport: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "dns",
+    feature = "websocket",
+    feature = "webrtc"
+))]
+pub struct BrowserPresetError(#[from] BrowserPresetErrorInner);
+
+#[derive(Debug, thiserror::Error)]
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "dns",
+    feature = "websocket",
+    feature = "webrtc"
+))]
+enum BrowserPresetErrorInner {
+    #[error("Noise")]
+    Noise(#[source] libp2p_noise::Error),
+    #[error("Dns")]
+    Dns(#[source] std::io::Error),
+    #[error("Websocket")]
+    Websocket(#[source] WebsocketError<libp2p_noise::Error>),
+    #[error("WebTransport server transport is not supported")]
+    WebTransportUnsupported,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "quic",
+    feature = "dns",
+    feature = "websocket",
+    feature = "relay"
+))]
+pub struct UniversalTransportError(#[from] UniversalTransportErrorInner);
+
+#[derive(Debug, thiserror::Error)]
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "quic",
+    feature = "dns",
+    feature = "websocket",
+    feature = "relay"
+))]
+enum UniversalTransportErrorInner {
+    #[error("Noise")]
+    Noise(#[source] libp2p_noise::Error),
+    #[error("Dns")]
+    Dns(#[source] std::io::Error),
+    #[error("Websocket")]
+    Websocket(#[source] WebsocketError<libp2p_noise::Error>),
+}
+
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "tcp"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_latency_tcp_config_sets_nodelay_and_port_reuse() {
+        let config = format!("{:?}", low_latency_tcp_config());
+
+        assert!(config.contains("nodelay: Some(true)"), "{config}");
+        assert!(config.contains("enable_port_reuse: true"), "{config}");
+    }
+}
@@ -0,0 +1,150 @@
+use super::*;
+use crate::SwarmBuilder;
+#[cfg(all(not(target_arch = "wasm32"), feature = "unix"))]
+use libp2p_core::{
+    muxing::StreamMuxer,
+    upgrade::{InboundConnectionUpgrade, OutboundConnectionUpgrade},
+    Negotiated, UpgradeInfo,
+};
+use std::marker::PhantomData;
+
+macro_rules! impl_unix_builder {
+    ($providerKebabCase:literal, $providerPascalCase:ty, $config:ident, $unixStream:ty) => {
+        #[cfg(all(
+            not(target_arch = "wasm32"),
+            feature = "unix",
+            feature = $providerKebabCase,
+        ))]
+        impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<$providerPascalCase, QuicPhase<T>> {
+            /// Adds a Unix domain socket based transport, upgraded like the TCP based transport
+            /// of [`SwarmBuilder::with_tcp`].
+            ///
+            /// Listens on and dials addresses of the form `/unix/%2Ftmp%2Fnode.sock`, i.e. with
+            /// an absolute, percent-encoded path. See [`libp2p_uds`] for how socket files are
+            /// cleaned up.
+            ///
+            /// ``` rust
+            /// # use libp2p::SwarmBuilder;
+            /// # use std::error::Error;
+            /// # async fn build_swarm() -> Result<(), Box<dyn Error>> {
+            /// let swarm = SwarmBuilder::with_new_identity()
+            ///     .with_tokio()
+            ///     .with_unix(
+            ///         (libp2p_tls::Config::new, libp2p_noise::Config::new),
+            ///         libp2p_yamux::Config::default,
+            ///     )?
+            /// # ;
+            /// # Ok(())
+            /// # }
+            /// ```
+            pub fn with_unix<SecUpgrade, SecStream, SecError, MuxUpgrade, MuxStream, MuxError>(
+                self,
+                security_upgrade: SecUpgrade,
+                multiplexer_upgrade: MuxUpgrade,
+            ) -> Result<
+                SwarmBuilder<$providerPascalCase, QuicPhase<impl AuthenticatedMultiplexedTransport>>,
+            SecUpgrade::Error,
+            >
+            where
+                SecStream: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static,
+                SecError: std::error::Error + Send + Sync + 'static,
+                SecUpgrade: IntoSecurityUpgrade<$unixStream>,
+                SecUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<$unixStream>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + OutboundConnectionUpgrade<Negotiated<$unixStream>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + Clone + Send + 'static,
+                <SecUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<$unixStream>>>::Future: Send,
+                <SecUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<$unixStream>>>::Future: Send,
+                <<<SecUpgrade as IntoSecurityUpgrade<$unixStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+                <<SecUpgrade as IntoSecurityUpgrade<$unixStream>>::Upgrade as UpgradeInfo>::Info: Send,
+
+                MuxStream: StreamMuxer + Send + 'static,
+                MuxStream::Substream: Send + 'static,
+                MuxStream::Error: Send + Sync + 'static,
+                MuxUpgrade: IntoMultiplexerUpgrade<SecStream>,
+                MuxUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + OutboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + Clone + Send + 'static,
+                <MuxUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+                <MuxUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+                MuxError: std::error::Error + Send + Sync + 'static,
+                <<<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+                <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
+            {
+                let security_upgrade = security_upgrade.into_security_upgrade(&self.identity)?;
+                let multiplexer_upgrade = multiplexer_upgrade.into_multiplexer_upgrade();
+                let stack = self.stack.with_upgraded("unix", &security_upgrade, &multiplexer_upgrade);
+
+                Ok(SwarmBuilder {
+                    phase: QuicPhase {
+                        transport: self
+                            .phase
+                            .transport
+                            .or_transport(
+                                libp2p_uds::$config::new()
+                                    .upgrade(libp2p_core::upgrade::Version::V1Lazy)
+                                    .authenticate(security_upgrade)
+                                    .multiplex(multiplexer_upgrade)
+                                    .map(|(p, c), _| (p, StreamMuxerBox::new(c))),
+                            )
+                            .map(|either, _| either.into_inner()),
+                        #[cfg(all(not(target_arch = "wasm32"), feature = "quic"))]
+                        socket_buffer_sizes: self.phase.socket_buffer_sizes,
+                    },
+                    identity: self.identity,
+                    stack,
+                    phantom: PhantomData,
+                })
+            }
+        }
+
+        #[cfg(all(
+            not(target_arch = "wasm32"),
+            feature = "unix",
+            feature = $providerKebabCase,
+        ))]
+        impl SwarmBuilder<$providerPascalCase, TcpPhase> {
+            /// See [`SwarmBuilder::with_unix`].
+            pub fn with_unix<SecUpgrade, SecStream, SecError, MuxUpgrade, MuxStream, MuxError>(
+                self,
+                security_upgrade: SecUpgrade,
+                multiplexer_upgrade: MuxUpgrade,
+            ) -> Result<
+                SwarmBuilder<$providerPascalCase, QuicPhase<impl AuthenticatedMultiplexedTransport>>,
+            SecUpgrade::Error,
+            >
+            where
+                SecStream: futures::AsyncRead + futures::AsyncWrite + Unpin + Send + 'static,
+                SecError: std::error::Error + Send + Sync + 'static,
+                SecUpgrade: IntoSecurityUpgrade<$unixStream>,
+                SecUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<$unixStream>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + OutboundConnectionUpgrade<Negotiated<$unixStream>, Output = (libp2p_identity::PeerId, SecStream), Error = SecError> + Clone + Send + 'static,
+                <SecUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<$unixStream>>>::Future: Send,
+                <SecUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<$unixStream>>>::Future: Send,
+                <<<SecUpgrade as IntoSecurityUpgrade<$unixStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+                <<SecUpgrade as IntoSecurityUpgrade<$unixStream>>::Upgrade as UpgradeInfo>::Info: Send,
+
+                MuxStream: StreamMuxer + Send + 'static,
+                MuxStream::Substream: Send + 'static,
+                MuxStream::Error: Send + Sync + 'static,
+                MuxUpgrade: IntoMultiplexerUpgrade<SecStream>,
+                MuxUpgrade::Upgrade: InboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + OutboundConnectionUpgrade<Negotiated<SecStream>, Output = MuxStream, Error = MuxError> + Clone + Send + 'static,
+                <MuxUpgrade::Upgrade as InboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+                <MuxUpgrade::Upgrade as OutboundConnectionUpgrade<Negotiated<SecStream>>>::Future: Send,
+                MuxError: std::error::Error + Send + Sync + 'static,
+                <<<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+                <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
+            {
+                self.without_tcp()
+                    .with_unix(security_upgrade, multiplexer_upgrade)
+            }
+        }
+    };
+}
+
+impl_unix_builder!(
+    "async-std",
+    super::provider::AsyncStd,
+    UdsConfig,
+    libp2p_uds::UnixStream
+);
+impl_unix_builder!(
+    "tokio",
+    super::provider::Tokio,
+    TokioUdsConfig,
+    libp2p_uds::TokioUnixStream
+);
@@ -19,8 +19,57 @@ use std::marker::PhantomData;
 
 pub struct WebsocketPhase<T> {
     pub(crate) transport: T,
+    #[cfg(all(not(target_arch = "wasm32"), feature = "websocket"))]
+    pub(crate) tls_config: Option<libp2p_websocket::tls::Config>,
+    /// The resolver configured in the [`DnsPhase`], if any.
+    #[cfg(all(not(target_arch = "wasm32"), feature = "dns", feature = "websocket"))]
+    pub(crate) resolver: Option<Resolver>,
 }
 
+#[cfg(all(not(target_arch = "wasm32"), feature = "websocket"))]
+impl<Provider, T> SwarmBuilder<Provider, WebsocketPhase<T>> {
+    /// Enables secure websockets (`/wss`) at the transport layer, using the given server
+    /// certificate chain and private key.
+    ///
+    /// Incoming connections on `/wss` addresses complete a standard TLS handshake with the
+    /// given certificate before the libp2p security and multiplexer upgrades are negotiated.
+    /// This is needed for browsers that only allow secure websockets, e.g. on HTTPS pages.
+    /// The certificate is unrelated to the libp2p identity of the node.
+    ///
+    /// ``` rust
+    /// # use libp2p::SwarmBuilder;
+    /// # use libp2p::websocket::tls::{Certificate, PrivateKey};
+    /// # use std::error::Error;
+    /// # async fn build_swarm(cert: Certificate, key: PrivateKey) -> Result<(), Box<dyn Error>> {
+    /// let swarm = SwarmBuilder::with_new_identity()
+    ///     .with_tokio()
+    ///     .with_wss(vec![cert], key)?
+    ///     .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+    ///     .await?
+    /// # ;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_wss(
+        mut self,
+        tls_cert: impl IntoIterator<Item = libp2p_websocket::tls::Certificate>,
+        tls_key: libp2p_websocket::tls::PrivateKey,
+    ) -> Result<Self, libp2p_websocket::tls::Error> {
+        self.phase.tls_config = Some(libp2p_websocket::tls::Config::new(tls_key, tls_cert)?);
+        Ok(self)
+    }
+}
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "websocket",
+    not(feature = "noise"),
+    not(feature = "tls")
+))]
+impl_security_feature_stubs!(
+    <Provider, T> SwarmBuilder<Provider, WebsocketPhase<T>>
+);
+
 macro_rules! impl_websocket_builder {
     ($providerKebabCase:literal, $providerPascalCase:ty, $dnsTcp:expr, $websocketStream:ty) => {
         /// Adds a websocket client transport.
@@ -89,18 +138,25 @@ macro_rules! impl_websocket_builder {
                 <<MuxUpgrade as IntoMultiplexerUpgrade<SecStream>>::Upgrade as UpgradeInfo>::Info: Send,
 
             {
-                let security_upgrade = security_upgrade.into_security_upgrade(&self.keypair)
+                let security_upgrade = security_upgrade.into_security_upgrade(&self.identity)
                     .map_err(WebsocketErrorInner::SecurityUpgrade)?;
-                let websocket_transport = libp2p_websocket::WsConfig::new(
-                    $dnsTcp.await.map_err(WebsocketErrorInner::Dns)?,
-                )
+                let mut websocket_transport = libp2p_websocket::WsConfig::new(
+                    $dnsTcp(self.phase.resolver).await.map_err(WebsocketErrorInner::Dns)?,
+                );
+                if let Some(tls_config) = self.phase.tls_config {
+                    websocket_transport.set_tls_config(tls_config);
+                }
+                let multiplexer_upgrade = multiplexer_upgrade.into_multiplexer_upgrade();
+                let stack = self.stack.with_upgraded("websocket", &security_upgrade, &multiplexer_upgrade);
+                let websocket_transport = websocket_transport
                     .upgrade(libp2p_core::upgrade::Version::V1Lazy)
                     .authenticate(security_upgrade)
-                    .multiplex(multiplexer_upgrade.into_multiplexer_upgrade())
+                    .multiplex(multiplexer_upgrade)
                     .map(|(p, c), _| (p, StreamMuxerBox::new(c)));
 
                 Ok(SwarmBuilder {
-                    keypair: self.keypair,
+                    identity: self.identity,
+                    stack,
                     phantom: PhantomData,
                     phase: RelayPhase {
                         transport: websocket_transport
@@ -116,9 +172,7 @@ macro_rules! impl_websocket_builder {
 impl_websocket_builder!(
     "async-std",
     super::provider::AsyncStd,
-    libp2p_dns::async_std::Transport::system(libp2p_tcp::async_io::Transport::new(
-        libp2p_tcp::Config::default(),
-    )),
+    async_std_dns_tcp,
     rw_stream_sink::RwStreamSink<
         libp2p_websocket::BytesConnection<libp2p_tcp::async_io::TcpStream>,
     >
@@ -126,18 +180,56 @@ impl_websocket_builder!(
 impl_websocket_builder!(
     "tokio",
     super::provider::Tokio,
-    // Note this is an unnecessary await for Tokio Websocket (i.e. tokio dns) in order to be consistent
-    // with above AsyncStd construction.
-    futures::future::ready(libp2p_dns::tokio::Transport::system(
-        libp2p_tcp::tokio::Transport::new(libp2p_tcp::Config::default())
-    )),
+    tokio_dns_tcp,
     rw_stream_sink::RwStreamSink<libp2p_websocket::BytesConnection<libp2p_tcp::tokio::TcpStream>>
 );
 
+/// Creates the TCP transport underlying the websocket transport, resolving DNS names via the
+/// resolver configured in the [`DnsPhase`] or the system's DNS configuration otherwise.
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "async-std",
+    feature = "websocket"
+))]
+async fn async_std_dns_tcp(
+    resolver: Option<Resolver>,
+) -> Result<libp2p_dns::async_std::Transport<libp2p_tcp::async_io::Transport>, std::io::Error> {
+    let tcp = libp2p_tcp::async_io::Transport::new(libp2p_tcp::Config::default());
+
+    match resolver {
+        Some(Resolver::AsyncStd(resolver)) => Ok(libp2p_dns::async_std::Transport::with_resolver(
+            tcp, resolver,
+        )),
+        _ => {
+            resolver_constructed();
+            libp2p_dns::async_std::Transport::system(tcp).await
+        }
+    }
+}
+
+/// The `tokio` counterpart of `async_std_dns_tcp`.
+#[cfg(all(not(target_arch = "wasm32"), feature = "tokio", feature = "websocket"))]
+async fn tokio_dns_tcp(
+    resolver: Option<Resolver>,
+) -> Result<libp2p_dns::tokio::Transport<libp2p_tcp::tokio::Transport>, std::io::Error> {
+    let tcp = libp2p_tcp::tokio::Transport::new(libp2p_tcp::Config::default());
+
+    match resolver {
+        Some(Resolver::Tokio(resolver)) => {
+            Ok(libp2p_dns::tokio::Transport::with_resolver(tcp, resolver))
+        }
+        _ => {
+            resolver_constructed();
+            libp2p_dns::tokio::Transport::system(tcp)
+        }
+    }
+}
+
 impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, WebsocketPhase<T>> {
     pub(crate) fn without_websocket(self) -> SwarmBuilder<Provider, RelayPhase<T>> {
         SwarmBuilder {
-            keypair: self.keypair,
+            identity: self.identity,
+            stack: self.stack,
             phantom: PhantomData,
             phase: RelayPhase {
                 transport: self.phase.transport,
@@ -200,6 +292,17 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Webs
             .without_bandwidth_logging()
             .with_bandwidth_metrics(registry)
     }
+
+    pub fn with_metrics(
+        self,
+        registry: &mut libp2p_metrics::Registry,
+    ) -> SwarmBuilder<Provider, BehaviourPhase<T, NoRelayBehaviour>> {
+        self.without_websocket()
+            .without_relay()
+            .without_bandwidth_logging()
+            .without_bandwidth_metrics()
+            .with_metrics(registry)
+    }
 }
 impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, WebsocketPhase<T>> {
     pub fn with_behaviour<B, R: TryIntoBehaviour<B>>(
@@ -211,6 +314,21 @@ impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, Webs
             .without_bandwidth_logging()
             .with_behaviour(constructor)
     }
+
+    pub async fn with_behaviour_async<B, E, F>(
+        self,
+        constructor: impl FnOnce(&libp2p_identity::Keypair) -> F,
+    ) -> Result<SwarmBuilder<Provider, SwarmPhase<T, B>>, E>
+    where
+        B: libp2p_swarm::NetworkBehaviour,
+        F: std::future::Future<Output = Result<B, E>>,
+    {
+        self.without_websocket()
+            .without_relay()
+            .without_bandwidth_logging()
+            .with_behaviour_async(constructor)
+            .await
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -0,0 +1,196 @@
+use libp2p_core::multiaddr_ext::{required_transport, TransportKind};
+use libp2p_core::transport::{ListenerId, TransportError, TransportEvent};
+use libp2p_core::upgrade::UpgradeInfo;
+use libp2p_core::{Multiaddr, Transport};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::{error, fmt, io};
+
+/// The transports composed by the [`SwarmBuilder`](super::SwarmBuilder), in the order they were
+/// added, e.g. `tcp/noise/yamux` and `quic-v1`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StackDescription(Vec<String>);
+
+impl StackDescription {
+    /// Adds a transport that secures and multiplexes its connections itself, e.g. QUIC.
+    pub(crate) fn with(mut self, transport: &str) -> Self {
+        self.0.push(transport.to_owned());
+        self
+    }
+
+    /// Adds a transport upgraded with the given security and multiplexer upgrades.
+    ///
+    /// Alternative upgrades are separated by `|`, e.g. `tcp/tls|noise/yamux`.
+    #[allow(dead_code)] // Unused without any transport needing upgrades.
+    pub(crate) fn with_upgraded(
+        self,
+        transport: &str,
+        security_upgrade: &impl UpgradeInfo,
+        multiplexer_upgrade: &impl UpgradeInfo,
+    ) -> Self {
+        self.with(&format!(
+            "{transport}/{}/{}",
+            protocol_names(security_upgrade),
+            protocol_names(multiplexer_upgrade)
+        ))
+    }
+}
+
+/// The names of the protocols of an upgrade, without their version, e.g. `yamux` for
+/// `/yamux/1.0.0`.
+#[allow(dead_code)]
+fn protocol_names(upgrade: &impl UpgradeInfo) -> String {
+    upgrade
+        .protocol_info()
+        .into_iter()
+        .map(|info| {
+            info.as_ref()
+                .trim_start_matches('/')
+                .split('/')
+                .next()
+                .unwrap_or_default()
+                .to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Wraps the transport built by the [`SwarmBuilder`](super::SwarmBuilder), reporting dials of
+/// addresses none of the composed transports supports as an [`UnsupportedAddressError`].
+pub(crate) struct DescribedTransport<T> {
+    inner: T,
+    description: StackDescription,
+}
+
+impl<T> DescribedTransport<T> {
+    pub(crate) fn new(inner: T, description: StackDescription) -> Self {
+        Self { inner, description }
+    }
+
+    fn map_dial_error(&self, error: TransportError<io::Error>) -> TransportError<io::Error> {
+        match error {
+            TransportError::MultiaddrNotSupported(address) => {
+                TransportError::Other(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    UnsupportedAddressError {
+                        address,
+                        configured_transports: self.description.0.clone(),
+                    },
+                ))
+            }
+            e => e,
+        }
+    }
+}
+
+impl<T> Transport for DescribedTransport<T>
+where
+    T: Transport<Error = io::Error> + Unpin,
+{
+    type Output = T::Output;
+    type Error = io::Error;
+    type ListenerUpgrade = T::ListenerUpgrade;
+    type Dial = T::Dial;
+
+    fn listen_on(
+        &mut self,
+        id: ListenerId,
+        addr: Multiaddr,
+    ) -> Result<(), TransportError<Self::Error>> {
+        self.inner.listen_on(id, addr)
+    }
+
+    fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.inner.remove_listener(id)
+    }
+
+    fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.inner.dial(addr).map_err(|e| self.map_dial_error(e))
+    }
+
+    fn dial_as_listener(
+        &mut self,
+        addr: Multiaddr,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.inner
+            .dial_as_listener(addr)
+            .map_err(|e| self.map_dial_error(e))
+    }
+
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.inner
+            .dial_from_port(addr, port)
+            .map_err(|e| self.map_dial_error(e))
+    }
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+        Pin::new(&mut self.inner).poll(cx)
+    }
+
+    fn address_translation(&self, listen: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.inner.address_translation(listen, observed)
+    }
+}
+
+/// The address of a dial is not supported by any transport of a
+/// [`Swarm`](libp2p_swarm::Swarm) built with the [`SwarmBuilder`](super::SwarmBuilder).
+///
+/// Reported in place of [`TransportError::MultiaddrNotSupported`], as [`TransportError::Other`]
+/// wrapping an [`io::Error`] of kind [`io::ErrorKind::Unsupported`].
+#[derive(Debug, Clone)]
+pub struct UnsupportedAddressError {
+    address: Multiaddr,
+    configured_transports: Vec<String>,
+}
+
+impl UnsupportedAddressError {
+    /// The address that was dialed.
+    pub fn address(&self) -> &Multiaddr {
+        &self.address
+    }
+
+    /// The transports of the [`Swarm`](libp2p_swarm::Swarm), e.g. `tcp/noise/yamux`.
+    pub fn configured_transports(&self) -> &[String] {
+        &self.configured_transports
+    }
+
+    /// The kind of transport the address requires, see [`required_transport`].
+    pub fn required_transport(&self) -> Option<TransportKind> {
+        required_transport(&self.address)
+    }
+}
+
+impl fmt::Display for UnsupportedAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let configured = if self.configured_transports.is_empty() {
+            "none".to_owned()
+        } else {
+            self.configured_transports.join(", ")
+        };
+        let required = match self.required_transport() {
+            Some(TransportKind::Tcp) => "tcp",
+            Some(TransportKind::Quic) => "quic-v1",
+            Some(TransportKind::WebSocket) => "websocket",
+            Some(TransportKind::WebTransport) => "webtransport",
+            Some(TransportKind::WebRtcDirect) => "webrtc-direct",
+            Some(TransportKind::Relay) => "p2p-circuit",
+            Some(TransportKind::Memory) => "memory",
+            Some(TransportKind::Unix) => "unix",
+            _ => "an unknown transport",
+        };
+
+        write!(
+            f,
+            "configured transports: {configured} — address requires {required}"
+        )
+    }
+}
+
+impl error::Error for UnsupportedAddressError {}
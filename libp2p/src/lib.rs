@@ -126,6 +126,11 @@ pub use libp2p_uds as uds;
 #[cfg(not(target_arch = "wasm32"))]
 #[doc(inline)]
 pub use libp2p_upnp as upnp;
+#[cfg(feature = "webrtc")]
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "webrtc")))]
+#[doc(inline)]
+pub use libp2p_webrtc as webrtc;
 #[cfg(feature = "websocket")]
 #[cfg(not(target_arch = "wasm32"))]
 #[doc(inline)]
@@ -149,8 +154,20 @@ pub mod bandwidth;
 #[cfg(doc)]
 pub mod tutorials;
 
-pub use self::builder::SwarmBuilder;
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "tokio",
+    feature = "tcp",
+    feature = "noise",
+    feature = "yamux",
+    feature = "dns",
+    feature = "websocket",
+    feature = "webrtc"
+))]
+pub use self::builder::{BrowserPreset, BrowserPresetError};
+pub use self::builder::{SwarmBuilder, UnsupportedAddressError};
 pub use self::core::{
+    multiaddr_ext::{required_transport, TransportKind},
     transport::TransportError,
     upgrade::{InboundUpgrade, OutboundUpgrade},
     Transport,
@@ -0,0 +1,24 @@
+use libp2p::core::{muxing::StreamMuxerBox, transport::dummy::DummyTransport};
+use libp2p::PeerId;
+
+async fn build() {
+    let _ = libp2p::SwarmBuilder::with_new_identity()
+        .with_tokio()
+        .with_other_transport(|_| DummyTransport::<(PeerId, StreamMuxerBox)>::new())
+        .unwrap()
+        .with_dns()
+        .unwrap()
+        .with_websocket(
+            |keypair: &libp2p::identity::Keypair| {
+                Ok::<_, std::convert::Infallible>(libp2p::plaintext::Config::new(keypair))
+            },
+            libp2p::yamux::Config::default,
+        )
+        .await
+        .unwrap()
+        .with_noise();
+}
+
+fn main() {
+    let _ = build();
+}
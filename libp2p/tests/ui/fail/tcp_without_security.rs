@@ -0,0 +1,5 @@
+fn main() {
+    let _ = libp2p::SwarmBuilder::with_new_identity()
+        .with_tokio()
+        .with_noise();
+}
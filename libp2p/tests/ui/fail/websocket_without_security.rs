@@ -0,0 +1,12 @@
+use libp2p::core::{muxing::StreamMuxerBox, transport::dummy::DummyTransport};
+use libp2p::PeerId;
+
+fn main() {
+    let _ = libp2p::SwarmBuilder::with_new_identity()
+        .with_tokio()
+        .with_other_transport(|_| DummyTransport::<(PeerId, StreamMuxerBox)>::new())
+        .unwrap()
+        .with_dns()
+        .unwrap()
+        .with_tls();
+}
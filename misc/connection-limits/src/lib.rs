@@ -22,8 +22,8 @@ use libp2p_core::{ConnectedPoint, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use libp2p_swarm::{
     behaviour::{ConnectionEstablished, DialFailure, ListenFailure},
-    dummy, ConnectionClosed, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler,
-    THandlerInEvent, THandlerOutEvent, ToSwarm,
+    dummy, ConnectionClosed, ConnectionDenied, ConnectionId, FromSwarm, LimitKind,
+    NetworkBehaviour, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
 };
 use std::collections::{HashMap, HashSet};
 use std::fmt;
@@ -87,12 +87,21 @@ impl Behaviour {
     }
 }
 
-fn check_limit(limit: Option<u32>, current: usize, kind: Kind) -> Result<(), ConnectionDenied> {
+fn check_limit(
+    limit: Option<u32>,
+    current: usize,
+    kind: LimitKind,
+) -> Result<(), ConnectionDenied> {
     let limit = limit.unwrap_or(u32::MAX);
     let current = current as u32;
 
     if current >= limit {
-        return Err(ConnectionDenied::new(Exceeded { limit, kind }));
+        return Err(ConnectionDenied::limit_reached(
+            Exceeded { limit, kind },
+            kind,
+            current,
+            limit,
+        ));
     }
 
     Ok(())
@@ -102,13 +111,17 @@ fn check_limit(limit: Option<u32>, current: usize, kind: Kind) -> Result<(), Con
 #[derive(Debug, Clone, Copy)]
 pub struct Exceeded {
     limit: u32,
-    kind: Kind,
+    kind: LimitKind,
 }
 
 impl Exceeded {
     pub fn limit(&self) -> u32 {
         self.limit
     }
+
+    pub fn kind(&self) -> LimitKind {
+        self.kind
+    }
 }
 
 impl fmt::Display for Exceeded {
@@ -121,29 +134,6 @@ impl fmt::Display for Exceeded {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Kind {
-    PendingIncoming,
-    PendingOutgoing,
-    EstablishedIncoming,
-    EstablishedOutgoing,
-    EstablishedPerPeer,
-    EstablishedTotal,
-}
-
-impl fmt::Display for Kind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Kind::PendingIncoming => write!(f, "pending incoming connections"),
-            Kind::PendingOutgoing => write!(f, "pending outgoing connections"),
-            Kind::EstablishedIncoming => write!(f, "established incoming connections"),
-            Kind::EstablishedOutgoing => write!(f, "established outgoing connections"),
-            Kind::EstablishedPerPeer => write!(f, "established connections per peer"),
-            Kind::EstablishedTotal => write!(f, "established connections"),
-        }
-    }
-}
-
 impl std::error::Error for Exceeded {}
 
 /// The configurable connection limits.
@@ -214,7 +204,7 @@ impl NetworkBehaviour for Behaviour {
         check_limit(
             self.limits.max_pending_incoming,
             self.pending_inbound_connections.len(),
-            Kind::PendingIncoming,
+            LimitKind::PendingIncoming,
         )?;
 
         self.pending_inbound_connections.insert(connection_id);
@@ -234,7 +224,7 @@ impl NetworkBehaviour for Behaviour {
         check_limit(
             self.limits.max_established_incoming,
             self.established_inbound_connections.len(),
-            Kind::EstablishedIncoming,
+            LimitKind::EstablishedIncoming,
         )?;
         check_limit(
             self.limits.max_established_per_peer,
@@ -242,13 +232,13 @@ impl NetworkBehaviour for Behaviour {
                 .get(&peer)
                 .map(|connections| connections.len())
                 .unwrap_or(0),
-            Kind::EstablishedPerPeer,
+            LimitKind::EstablishedPerPeer,
         )?;
         check_limit(
             self.limits.max_established_total,
             self.established_inbound_connections.len()
                 + self.established_outbound_connections.len(),
-            Kind::EstablishedTotal,
+            LimitKind::EstablishedTotal,
         )?;
 
         Ok(dummy::ConnectionHandler)
@@ -264,7 +254,7 @@ impl NetworkBehaviour for Behaviour {
         check_limit(
             self.limits.max_pending_outgoing,
             self.pending_outbound_connections.len(),
-            Kind::PendingOutgoing,
+            LimitKind::PendingOutgoing,
         )?;
 
         self.pending_outbound_connections.insert(connection_id);
@@ -284,7 +274,7 @@ impl NetworkBehaviour for Behaviour {
         check_limit(
             self.limits.max_established_outgoing,
             self.established_outbound_connections.len(),
-            Kind::EstablishedOutgoing,
+            LimitKind::EstablishedOutgoing,
         )?;
         check_limit(
             self.limits.max_established_per_peer,
@@ -292,13 +282,13 @@ impl NetworkBehaviour for Behaviour {
                 .get(&peer)
                 .map(|connections| connections.len())
                 .unwrap_or(0),
-            Kind::EstablishedPerPeer,
+            LimitKind::EstablishedPerPeer,
         )?;
         check_limit(
             self.limits.max_established_total,
             self.established_inbound_connections.len()
                 + self.established_outbound_connections.len(),
-            Kind::EstablishedTotal,
+            LimitKind::EstablishedTotal,
         )?;
 
         Ok(dummy::ConnectionHandler)
@@ -366,8 +356,8 @@ impl NetworkBehaviour for Behaviour {
 mod tests {
     use super::*;
     use libp2p_swarm::{
-        behaviour::toggle::Toggle, dial_opts::DialOpts, dial_opts::PeerCondition, DialError,
-        ListenError, Swarm, SwarmEvent,
+        behaviour::toggle::Toggle, dial_opts::DialOpts, dial_opts::PeerCondition,
+        ConnectionLimitReached, DialError, ListenError, Swarm, SwarmEvent,
     };
     use libp2p_swarm_test::SwarmExt;
     use quickcheck::*;
@@ -477,6 +467,38 @@ mod tests {
         quickcheck(prop as fn(_));
     }
 
+    #[test]
+    fn reached_limit_is_reported_to_behaviours() {
+        let mut swarm1 = Swarm::new_ephemeral(|_| {
+            Behaviour::new(ConnectionLimits::default().with_max_pending_incoming(Some(0)))
+        });
+        let mut swarm2 = Swarm::new_ephemeral(|_| Behaviour::new(ConnectionLimits::default()));
+
+        async_std::task::block_on(async {
+            let (listen_addr, _) = swarm1.listen().await;
+            swarm2.dial(listen_addr).unwrap();
+            async_std::task::spawn(swarm2.loop_on_next());
+
+            let connection_id = swarm1
+                .wait(|event| match event {
+                    SwarmEvent::IncomingConnectionError {
+                        connection_id,
+                        error: ListenError::Denied { .. },
+                        ..
+                    } => Some(connection_id),
+                    _ => None,
+                })
+                .await;
+
+            let reached = &swarm1.behaviour().limit_recorder.reached;
+            assert_eq!(reached.len(), 1);
+            assert_eq!(reached[0].connection_id, connection_id);
+            assert_eq!(reached[0].kind, LimitKind::PendingIncoming);
+            assert_eq!(reached[0].current, 0);
+            assert_eq!(reached[0].limit, 0);
+        });
+    }
+
     /// Another sibling [`NetworkBehaviour`] implementation might deny established connections in
     /// [`handle_established_outbound_connection`] or [`handle_established_inbound_connection`].
     /// [`Behaviour`] must not increase the established counters in
@@ -527,6 +549,7 @@ mod tests {
     struct Behaviour {
         limits: super::Behaviour,
         connection_denier: Toggle<ConnectionDenier>,
+        limit_recorder: LimitRecorder,
     }
 
     impl Behaviour {
@@ -534,16 +557,71 @@ mod tests {
             Self {
                 limits: super::Behaviour::new(limits),
                 connection_denier: None.into(),
+                limit_recorder: LimitRecorder::default(),
             }
         }
         fn new_with_connection_denier(limits: ConnectionLimits) -> Self {
             Self {
                 limits: super::Behaviour::new(limits),
                 connection_denier: Some(ConnectionDenier {}).into(),
+                limit_recorder: LimitRecorder::default(),
             }
         }
     }
 
+    /// Records each [`FromSwarm::ConnectionLimitReached`].
+    #[derive(Default)]
+    struct LimitRecorder {
+        reached: Vec<ConnectionLimitReached>,
+    }
+
+    impl NetworkBehaviour for LimitRecorder {
+        type ConnectionHandler = dummy::ConnectionHandler;
+        type ToSwarm = Void;
+
+        fn handle_established_inbound_connection(
+            &mut self,
+            _connection_id: ConnectionId,
+            _peer: PeerId,
+            _local_addr: &Multiaddr,
+            _remote_addr: &Multiaddr,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn handle_established_outbound_connection(
+            &mut self,
+            _connection_id: ConnectionId,
+            _peer: PeerId,
+            _addr: &Multiaddr,
+            _role_override: Endpoint,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn on_swarm_event(&mut self, event: FromSwarm) {
+            if let FromSwarm::ConnectionLimitReached(reached) = event {
+                self.reached.push(reached);
+            }
+        }
+
+        fn on_connection_handler_event(
+            &mut self,
+            _peer_id: PeerId,
+            _connection_id: ConnectionId,
+            event: THandlerOutEvent<Self>,
+        ) {
+            void::unreachable(event)
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+            Poll::Pending
+        }
+    }
+
     struct ConnectionDenier {}
 
     impl NetworkBehaviour for ConnectionDenier {
@@ -107,6 +107,20 @@ where
             })))
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let metrics = ConnectionMetrics::from_family_and_addr(&self.metrics, &addr);
+        Ok(self
+            .transport
+            .dial_from_port(addr.clone(), port)?
+            .map_ok(Box::new(|(peer_id, stream_muxer)| {
+                (peer_id, Muxer::new(stream_muxer, metrics))
+            })))
+    }
+
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
         self.transport.address_translation(server, observed)
     }
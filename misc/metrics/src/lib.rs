@@ -140,6 +140,16 @@ impl Recorder<libp2p_relay::Event> for Metrics {
     }
 }
 
+/// Records the state of the [`Swarm`](libp2p_swarm::Swarm) not reported via its events, i.e. the
+/// number of pending commands of its behaviour. Record it periodically, e.g. after each event.
+impl<TBehaviour: libp2p_swarm::NetworkBehaviour> Recorder<libp2p_swarm::Swarm<TBehaviour>>
+    for Metrics
+{
+    fn record(&self, swarm: &libp2p_swarm::Swarm<TBehaviour>) {
+        self.swarm.record(swarm)
+    }
+}
+
 impl<TBvEv> Recorder<libp2p_swarm::SwarmEvent<TBvEv>> for Metrics {
     fn record(&self, event: &libp2p_swarm::SwarmEvent<TBvEv>) {
         self.swarm.record(event);
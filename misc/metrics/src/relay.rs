@@ -52,6 +52,7 @@ enum EventType {
     ReservationReqAccepted,
     ReservationReqAcceptFailed,
     ReservationReqDenied,
+    ReservationReqRateLimited,
     ReservationReqDenyFailed,
     ReservationTimedOut,
     CircuitReqDenied,
@@ -71,6 +72,9 @@ impl From<&libp2p_relay::Event> for EventType {
                 EventType::ReservationReqAcceptFailed
             }
             libp2p_relay::Event::ReservationReqDenied { .. } => EventType::ReservationReqDenied,
+            libp2p_relay::Event::ReservationReqRateLimited { .. } => {
+                EventType::ReservationReqRateLimited
+            }
             #[allow(deprecated)]
             libp2p_relay::Event::ReservationReqDenyFailed { .. } => {
                 EventType::ReservationReqDenyFailed
@@ -23,10 +23,11 @@ use std::sync::{Arc, Mutex};
 
 use crate::protocol_stack;
 use instant::Instant;
-use libp2p_swarm::{ConnectionId, DialError, SwarmEvent};
+use libp2p_swarm::{ConnectionId, DialError, NetworkBehaviour, Swarm, SwarmEvent};
 use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue};
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
 use prometheus_client::registry::{Registry, Unit};
 
@@ -51,6 +52,8 @@ pub(crate) struct Metrics {
     dial_attempt: Counter,
     outgoing_connection_error: Family<OutgoingConnectionErrorLabels, Counter>,
 
+    pending_behaviour_commands: Gauge,
+
     connections: Arc<Mutex<HashMap<ConnectionId, Instant>>>,
 }
 
@@ -135,6 +138,13 @@ impl Metrics {
             outgoing_connection_error.clone(),
         );
 
+        let pending_behaviour_commands = Gauge::default();
+        sub_registry.register(
+            "pending_behaviour_commands",
+            "Number of commands of the behaviour being processed",
+            pending_behaviour_commands.clone(),
+        );
+
         let connections_established = Family::default();
         sub_registry.register(
             "connections_established",
@@ -178,6 +188,7 @@ impl Metrics {
             listener_error,
             dial_attempt,
             outgoing_connection_error,
+            pending_behaviour_commands,
             connections_establishment_duration,
             connections_duration,
             connections: Default::default(),
@@ -185,6 +196,13 @@ impl Metrics {
     }
 }
 
+impl<TBehaviour: NetworkBehaviour> super::Recorder<Swarm<TBehaviour>> for Metrics {
+    fn record(&self, swarm: &Swarm<TBehaviour>) {
+        self.pending_behaviour_commands
+            .set(swarm.pending_behaviour_commands() as i64);
+    }
+}
+
 impl<TBvEv> super::Recorder<SwarmEvent<TBvEv>> for Metrics {
     fn record(&self, event: &SwarmEvent<TBvEv>) {
         match event {
@@ -271,6 +289,14 @@ impl<TBvEv> super::Recorder<SwarmEvent<TBvEv>> for Metrics {
                                 ) => {
                                     record(OutgoingConnectionError::TransportMultiaddrNotSupported)
                                 }
+                                // Dials of addresses none of the transports of a `Swarm` built
+                                // via `libp2p::SwarmBuilder` supports are reported as
+                                // `io::ErrorKind::Unsupported`, see `UnsupportedAddressError`.
+                                libp2p_core::transport::TransportError::Other(error)
+                                    if error.kind() == std::io::ErrorKind::Unsupported =>
+                                {
+                                    record(OutgoingConnectionError::TransportMultiaddrNotSupported)
+                                }
                                 libp2p_core::transport::TransportError::Other(_) => {
                                     record(OutgoingConnectionError::TransportOther)
                                 }
@@ -283,8 +309,11 @@ impl<TBvEv> super::Recorder<SwarmEvent<TBvEv>> for Metrics {
                         record(OutgoingConnectionError::DialPeerConditionFalse)
                     }
                     DialError::Aborted => record(OutgoingConnectionError::Aborted),
+                    DialError::Timeout => record(OutgoingConnectionError::Timeout),
                     DialError::WrongPeerId { .. } => record(OutgoingConnectionError::WrongPeerId),
                     DialError::Denied { .. } => record(OutgoingConnectionError::Denied),
+                    DialError::Banned { .. } => record(OutgoingConnectionError::Banned),
+                    _ => record(OutgoingConnectionError::Other),
                 };
             }
             SwarmEvent::NewListenAddr { address, .. } => {
@@ -408,10 +437,13 @@ enum OutgoingConnectionError {
     NoAddresses,
     DialPeerConditionFalse,
     Aborted,
+    Timeout,
     WrongPeerId,
     TransportMultiaddrNotSupported,
     TransportOther,
     Denied,
+    Banned,
+    Other,
 }
 
 #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
@@ -427,7 +459,13 @@ enum IncomingConnectionError {
     TransportErrorMultiaddrNotSupported,
     TransportErrorOther,
     Aborted,
+    Timeout,
     Denied,
+    ListenerPaused,
+    Banned,
+    InsufficientResources,
+    RateLimited,
+    Other,
 }
 
 impl From<&libp2p_swarm::ListenError> for IncomingConnectionError {
@@ -442,7 +480,15 @@ impl From<&libp2p_swarm::ListenError> for IncomingConnectionError {
                 libp2p_core::transport::TransportError::Other(_),
             ) => IncomingConnectionError::TransportErrorOther,
             libp2p_swarm::ListenError::Aborted => IncomingConnectionError::Aborted,
+            libp2p_swarm::ListenError::Timeout => IncomingConnectionError::Timeout,
             libp2p_swarm::ListenError::Denied { .. } => IncomingConnectionError::Denied,
+            libp2p_swarm::ListenError::ListenerPaused => IncomingConnectionError::ListenerPaused,
+            libp2p_swarm::ListenError::Banned { .. } => IncomingConnectionError::Banned,
+            libp2p_swarm::ListenError::InsufficientResources => {
+                IncomingConnectionError::InsufficientResources
+            }
+            libp2p_swarm::ListenError::RateLimited => IncomingConnectionError::RateLimited,
+            _ => IncomingConnectionError::Other,
         }
     }
 }
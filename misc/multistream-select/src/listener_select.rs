@@ -33,6 +33,10 @@ use std::{
     task::{Context, Poll},
 };
 
+/// The default number of protocols proposed by the dialer that a listener rejects before aborting
+/// the negotiation, see [`ListenerSelectFuture::with_max_rejected_protocols`].
+const DEFAULT_MAX_REJECTED_PROTOCOLS: usize = 1000;
+
 /// Returns a `Future` that negotiates a protocol on the given I/O stream
 /// for a peer acting as the _listener_ (or _responder_).
 ///
@@ -65,6 +69,8 @@ where
             io: MessageIO::new(inner),
         },
         last_sent_na: false,
+        num_rejected: 0,
+        max_rejected: DEFAULT_MAX_REJECTED_PROTOCOLS,
     }
 }
 
@@ -83,6 +89,23 @@ pub struct ListenerSelectFuture<R, N> {
     /// considered failed, but not with a protocol violation or I/O
     /// error.
     last_sent_na: bool,
+    /// The number of protocols proposed by the dialer that have been rejected.
+    ///
+    /// Bounded by `max_rejected` so that a remote cannot keep the negotiation going forever.
+    num_rejected: usize,
+    /// The number of protocols proposed by the dialer that may be rejected.
+    max_rejected: usize,
+}
+
+impl<R, N> ListenerSelectFuture<R, N> {
+    /// Sets the number of protocols proposed by the dialer that are rejected before the
+    /// negotiation is aborted with [`ProtocolError::TooManyProtocols`].
+    ///
+    /// Defaults to 1000.
+    pub fn with_max_rejected_protocols(mut self, max: usize) -> Self {
+        self.max_rejected = max;
+        self
+    }
 }
 
 enum State<R, N> {
@@ -229,8 +252,14 @@ where
                             let message = if protocol.is_some() {
                                 tracing::debug!(protocol=%p, "Listener: confirming protocol");
                                 Message::Protocol(p.clone())
+                            } else if *this.num_rejected == *this.max_rejected {
+                                tracing::debug!(
+                                    "Listener: too many protocols proposed, aborting negotiation"
+                                );
+                                return Poll::Ready(Err(ProtocolError::TooManyProtocols.into()));
                             } else {
                                 tracing::debug!(protocol=%p.as_ref(), "Listener: rejecting protocol");
+                                *this.num_rejected += 1;
                                 Message::NotAvailable
                             };
 
@@ -305,3 +334,62 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_ringbuf::Endpoint;
+
+    #[async_std::test]
+    async fn too_many_proposed_protocols_abort_negotiation() {
+        let (dialer_io, listener_io) = Endpoint::pair(64 * 1024, 64 * 1024);
+
+        let dialer = async move {
+            let mut io = MessageIO::new(dialer_io);
+            io.send(Message::Header(HeaderLine::V1)).await.unwrap();
+            for i in 0..=DEFAULT_MAX_REJECTED_PROTOCOLS {
+                let protocol = Protocol::try_from(format!("/unsupported/{i}").as_str()).unwrap();
+                io.feed(Message::Protocol(protocol)).await.unwrap();
+            }
+            io.flush().await.unwrap();
+            io
+        };
+        let listener = listener_select_proto(listener_io, vec!["/supported"]);
+
+        let (result, _io) = futures::join!(listener, dialer);
+
+        assert!(matches!(
+            result,
+            Err(NegotiationError::ProtocolError(
+                ProtocolError::TooManyProtocols
+            ))
+        ));
+    }
+
+    #[async_std::test]
+    async fn configured_max_rejected_protocols_aborts_negotiation() {
+        let (dialer_io, listener_io) = Endpoint::pair(64 * 1024, 64 * 1024);
+
+        let dialer = async move {
+            let mut io = MessageIO::new(dialer_io);
+            io.send(Message::Header(HeaderLine::V1)).await.unwrap();
+            for protocol in ["/unsupported/1", "/unsupported/2", "/supported"] {
+                let protocol = Protocol::try_from(protocol).unwrap();
+                io.feed(Message::Protocol(protocol)).await.unwrap();
+            }
+            io.flush().await.unwrap();
+            io
+        };
+        let listener =
+            listener_select_proto(listener_io, vec!["/supported"]).with_max_rejected_protocols(1);
+
+        let (result, _io) = futures::join!(listener, dialer);
+
+        assert!(matches!(
+            result,
+            Err(NegotiationError::ProtocolError(
+                ProtocolError::TooManyProtocols
+            ))
+        ));
+    }
+}
@@ -419,7 +419,7 @@ pub enum ProtocolError {
     /// A protocol (name) is invalid.
     InvalidProtocol,
 
-    /// Too many protocols have been returned by the remote.
+    /// Too many protocols have been returned or proposed by the remote.
     TooManyProtocols,
 }
 
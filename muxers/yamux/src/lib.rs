@@ -24,17 +24,16 @@
 
 use either::Either;
 use futures::{prelude::*, ready};
-use libp2p_core::muxing::{StreamMuxer, StreamMuxerEvent};
+use libp2p_core::muxing::{StreamLimitReached, StreamMuxer, StreamMuxerEvent};
 use libp2p_core::upgrade::{InboundConnectionUpgrade, OutboundConnectionUpgrade, UpgradeInfo};
 use std::collections::VecDeque;
 use std::io::{IoSlice, IoSliceMut};
 use std::task::Waker;
 use std::{
-    io, iter,
+    fmt, io, iter,
     pin::Pin,
     task::{Context, Poll},
 };
-use thiserror::Error;
 
 /// A Yamux connection.
 #[derive(Debug)]
@@ -422,19 +421,42 @@ impl Default for Config013 {
 }
 
 /// The Yamux [`StreamMuxer`] error type.
-#[derive(Debug, Error)]
-#[error(transparent)]
+///
+/// Caused by [`StreamLimitReached`] if the maximum number of streams is reached.
+#[derive(Debug)]
 pub struct Error(Either<yamux012::ConnectionError, yamux013::ConnectionError>);
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Either::Left(e) => e.fmt(f),
+            Either::Right(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.0 {
+            Either::Left(yamux012::ConnectionError::TooManyStreams)
+            | Either::Right(yamux013::ConnectionError::TooManyStreams) => Some(&StreamLimitReached),
+            Either::Left(e) => e.source(),
+            Either::Right(e) => e.source(),
+        }
+    }
+}
+
 impl From<Error> for io::Error {
     fn from(err: Error) -> Self {
         match err.0 {
             Either::Left(err) => match err {
                 yamux012::ConnectionError::Io(e) => e,
+                yamux012::ConnectionError::TooManyStreams => io::Error::other(StreamLimitReached),
                 e => io::Error::new(io::ErrorKind::Other, e),
             },
             Either::Right(err) => match err {
                 yamux013::ConnectionError::Io(e) => e,
+                yamux013::ConnectionError::TooManyStreams => io::Error::other(StreamLimitReached),
                 e => io::Error::new(io::ErrorKind::Other, e),
             },
         }
@@ -458,4 +480,23 @@ mod test {
         cfg.set_max_num_streams(42);
         assert!(matches!(cfg, Config(Either::Left(Config012 { .. }))));
     }
+
+    #[test]
+    fn too_many_streams_is_caused_by_stream_limit_reached() {
+        let error = Error(Either::Right(yamux013::ConnectionError::TooManyStreams));
+        assert!(StreamLimitReached::is_cause_of(&error));
+
+        let error = io::Error::from(Error(Either::Left(
+            yamux012::ConnectionError::TooManyStreams,
+        )));
+        assert!(StreamLimitReached::is_cause_of(&error));
+
+        let error = io::Error::from(Error(Either::Right(
+            yamux013::ConnectionError::TooManyStreams,
+        )));
+        assert!(StreamLimitReached::is_cause_of(&error));
+
+        let error = io::Error::from(Error(Either::Right(yamux013::ConnectionError::Closed)));
+        assert!(!StreamLimitReached::is_cause_of(&error));
+    }
 }
@@ -97,6 +97,8 @@ async fn test_dial_back() {
                 concurrent_dial_errors,
                 established_in: _,
                 connection_id: _,
+                label: _,
+                role_overridden: _,
             } => {
                 assert_eq!(peer_id, client_id);
                 assert_eq!(num_established, NonZeroU32::new(2).unwrap());
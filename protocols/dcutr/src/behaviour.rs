@@ -24,9 +24,10 @@ use crate::{handler, protocol};
 use either::Either;
 use libp2p_core::connection::ConnectedPoint;
 use libp2p_core::multiaddr::Protocol;
+use libp2p_core::multiaddr_ext::MultiaddrExt;
 use libp2p_core::{Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
-use libp2p_swarm::behaviour::{ConnectionClosed, DialFailure, FromSwarm};
+use libp2p_swarm::behaviour::{ConnectionClosed, DialFailure, FromSwarm, UpgradeToDirect};
 use libp2p_swarm::dial_opts::{self, DialOpts};
 use libp2p_swarm::{
     dummy, ConnectionDenied, ConnectionHandler, ConnectionId, NewExternalAddrCandidate, THandler,
@@ -161,6 +162,33 @@ impl Behaviour {
             }
         }
     }
+
+    fn on_upgrade_to_direct(
+        &mut self,
+        UpgradeToDirect {
+            peer_id,
+            connection_id,
+            endpoint,
+        }: UpgradeToDirect,
+    ) {
+        // Only the listener of the relayed connection initiates the hole punch.
+        if !endpoint.is_listener() {
+            tracing::debug!(
+                peer=%peer_id,
+                connection=%connection_id,
+                "Not upgrading outbound relayed connection, the remote initiates the hole punch"
+            );
+            return;
+        }
+
+        self.outgoing_direct_connection_attempts
+            .remove(&(connection_id, peer_id));
+        self.queued_events.push_back(ToSwarm::NotifyHandler {
+            handler: NotifyHandler::One(connection_id),
+            peer_id,
+            event: Either::Left(handler::relayed::Command::Connect),
+        });
+    }
 }
 
 impl NetworkBehaviour for Behaviour {
@@ -174,7 +202,7 @@ impl NetworkBehaviour for Behaviour {
         local_addr: &Multiaddr,
         remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
-        if is_relayed(local_addr) {
+        if local_addr.is_relayed() {
             let connected_point = ConnectedPoint::Listener {
                 local_addr: local_addr.clone(),
                 send_back_addr: remote_addr.clone(),
@@ -207,7 +235,7 @@ impl NetworkBehaviour for Behaviour {
         addr: &Multiaddr,
         role_override: Endpoint,
     ) -> Result<THandler<Self>, ConnectionDenied> {
-        if is_relayed(addr) {
+        if addr.is_relayed() {
             return Ok(Either::Left(handler::relayed::Handler::new(
                 ConnectedPoint::Dialer {
                     address: addr.clone(),
@@ -330,6 +358,9 @@ impl NetworkBehaviour for Behaviour {
                 self.on_connection_closed(connection_closed)
             }
             FromSwarm::DialFailure(dial_failure) => self.on_dial_failure(dial_failure),
+            FromSwarm::UpgradeToDirect(upgrade_to_direct) => {
+                self.on_upgrade_to_direct(upgrade_to_direct)
+            }
             FromSwarm::NewExternalAddrCandidate(NewExternalAddrCandidate { addr }) => {
                 self.address_candidates.add(addr.clone());
             }
@@ -358,7 +389,7 @@ impl Candidates {
     }
 
     fn add(&mut self, mut address: Multiaddr) {
-        if is_relayed(&address) {
+        if address.is_relayed() {
             return;
         }
 
@@ -373,7 +404,3 @@ impl Candidates {
         self.inner.iter().map(|(a, _)| a)
     }
 }
-
-fn is_relayed(addr: &Multiaddr) -> bool {
-    addr.iter().any(|p| p == Protocol::P2pCircuit)
-}
@@ -26,7 +26,7 @@ use libp2p_identity::PublicKey;
 use libp2p_swarm::behaviour::{ConnectionClosed, ConnectionEstablished, DialFailure, FromSwarm};
 use libp2p_swarm::{
     ConnectionDenied, DialError, ExternalAddresses, ListenAddresses, NetworkBehaviour,
-    NotifyHandler, PeerAddresses, StreamUpgradeError, THandlerInEvent, ToSwarm,
+    NotifyHandler, PeerAddresses, StreamProtocol, StreamUpgradeError, THandlerInEvent, ToSwarm,
 };
 use libp2p_swarm::{ConnectionId, THandler, THandlerOutEvent};
 
@@ -346,6 +346,10 @@ impl NetworkBehaviour for Behaviour {
         Ok(self.discovered_peers.get(&peer))
     }
 
+    fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        vec![crate::PROTOCOL_NAME, crate::PUSH_PROTOCOL_NAME]
+    }
+
     fn on_swarm_event(&mut self, event: FromSwarm) {
         let listen_addr_changed = self.listen_addresses.on_swarm_event(&event);
         let external_addr_changed = self.external_addresses.on_swarm_event(&event);
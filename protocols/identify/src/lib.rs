@@ -38,12 +38,17 @@
 //! The [`Behaviour`] struct implements a [`NetworkBehaviour`](libp2p_swarm::NetworkBehaviour)
 //! that negotiates and executes the protocol on every established connection, emitting
 //! [`Event`]s.
+//!
+//! Nodes only interested in the addresses remotes observe for them can use the much smaller
+//! protocol of the [`observed_addr`] module instead.
 
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 pub use self::behaviour::{Behaviour, Config, Event};
 pub use self::protocol::{Info, UpgradeError, PROTOCOL_NAME, PUSH_PROTOCOL_NAME};
 
+pub mod observed_addr;
+
 mod behaviour;
 mod handler;
 mod protocol;
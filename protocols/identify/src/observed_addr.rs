@@ -0,0 +1,124 @@
+//! Minimal protocol to learn the address a remote observes for the local node.
+//!
+//! Unlike the full identify protocol, no information about the nodes other than the observed
+//! address is exchanged, e.g. for NAT detection on constrained devices.
+//!
+//! On every connection we dialed, the [`Behaviour`] requests the address the remote observes for
+//! us via [`PROTOCOL_NAME`]. The remote answers with the observed address, encoded as
+//! [`Multiaddr`] bytes prefixed with their length as unsigned varint. Each observation is reported
+//! via [`ToSwarm::NewExternalAddrCandidate`] and as [`Event::Observed`].
+
+pub use self::protocol::{UpgradeError, PROTOCOL_NAME};
+
+mod handler;
+mod protocol;
+
+use handler::Handler;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, StreamUpgradeError, THandler,
+    THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+
+/// Network behaviour requesting the address remotes observe for the local node on outbound
+/// connections, and answering such requests of remotes.
+#[derive(Default)]
+pub struct Behaviour {
+    /// Pending events to be emitted when polled.
+    events: VecDeque<ToSwarm<Event, void::Void>>,
+}
+
+/// Event emitted by the observed address [`Behaviour`].
+#[derive(Debug)]
+pub enum Event {
+    /// A remote sent us the address it observes for us.
+    Observed {
+        /// The peer that observed the address.
+        peer_id: PeerId,
+        /// The connection the address was observed on.
+        connection_id: ConnectionId,
+        /// The address as observed by the remote, before any address translation.
+        observed_addr: Multiaddr,
+    },
+    /// We sent a remote the address we observe for it.
+    Sent { peer_id: PeerId },
+    /// Requesting or answering an observed address failed.
+    Error {
+        peer_id: PeerId,
+        error: StreamUpgradeError<UpgradeError>,
+    },
+}
+
+impl Behaviour {
+    /// Creates a new observed address [`Behaviour`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(remote_addr.clone(), false))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        addr: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(addr.clone(), true))
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        match event {
+            handler::Event::Observed(observed_addr) => {
+                self.events
+                    .push_back(ToSwarm::NewExternalAddrCandidate(observed_addr.clone()));
+                self.events
+                    .push_back(ToSwarm::GenerateEvent(Event::Observed {
+                        peer_id,
+                        connection_id,
+                        observed_addr,
+                    }));
+            }
+            handler::Event::Sent => {
+                self.events
+                    .push_back(ToSwarm::GenerateEvent(Event::Sent { peer_id }));
+            }
+            handler::Event::Error(error) => {
+                self.events
+                    .push_back(ToSwarm::GenerateEvent(Event::Error { peer_id, error }));
+            }
+        }
+    }
+
+    #[tracing::instrument(level = "trace", name = "NetworkBehaviour::poll", skip(self))]
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(event);
+        }
+
+        Poll::Pending
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+}
@@ -0,0 +1,150 @@
+use super::protocol::{self, UpgradeError, PROTOCOL_NAME};
+use futures::prelude::*;
+use futures_bounded::Timeout;
+use libp2p_core::upgrade::ReadyUpgrade;
+use libp2p_core::Multiaddr;
+use libp2p_swarm::handler::{
+    ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
+};
+use libp2p_swarm::{
+    ConnectionHandler, ConnectionHandlerEvent, StreamProtocol, StreamUpgradeError,
+    SubstreamProtocol,
+};
+use std::collections::VecDeque;
+use std::{task::Context, task::Poll, time::Duration};
+
+const STREAM_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_CONCURRENT_STREAMS_PER_CONNECTION: usize = 2;
+
+/// Protocol handler requesting the address the remote observes for us once, if we dialed the
+/// connection, and answering the requests of the remote.
+pub struct Handler {
+    /// Address we observe for the remote.
+    observed_addr: Multiaddr,
+
+    /// Whether we still have to request the address the remote observes for us.
+    request_pending: bool,
+
+    active_streams: futures_bounded::FuturesSet<Result<Success, UpgradeError>>,
+
+    /// Pending events to yield.
+    events: VecDeque<Event>,
+}
+
+/// Event produced by the [`Handler`].
+#[derive(Debug)]
+pub enum Event {
+    /// The remote sent us the address it observes for us.
+    Observed(Multiaddr),
+    /// We sent the remote the address we observe for it.
+    Sent,
+    /// Failed to request our observed address, or to answer the request of the remote.
+    Error(StreamUpgradeError<UpgradeError>),
+}
+
+enum Success {
+    Sent,
+    Received(Multiaddr),
+}
+
+impl Handler {
+    pub(crate) fn new(observed_addr: Multiaddr, request: bool) -> Self {
+        Self {
+            observed_addr,
+            request_pending: request,
+            active_streams: futures_bounded::FuturesSet::new(
+                STREAM_TIMEOUT,
+                MAX_CONCURRENT_STREAMS_PER_CONNECTION,
+            ),
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = void::Void;
+    type ToBehaviour = Event;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
+        SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL_NAME), ())
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        self.request_pending || !self.active_streams.is_empty()
+    }
+
+    #[tracing::instrument(level = "trace", name = "ConnectionHandler::poll", skip(self, cx))]
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, (), Event>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event));
+        }
+
+        if std::mem::take(&mut self.request_pending) {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL_NAME), ()),
+            });
+        }
+
+        let event = match self.active_streams.poll_unpin(cx) {
+            Poll::Ready(Ok(Ok(Success::Received(observed)))) => Event::Observed(observed),
+            Poll::Ready(Ok(Ok(Success::Sent))) => Event::Sent,
+            Poll::Ready(Ok(Err(e))) => Event::Error(StreamUpgradeError::Apply(e)),
+            Poll::Ready(Err(Timeout { .. })) => Event::Error(StreamUpgradeError::Timeout),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event))
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol: stream,
+                ..
+            }) => {
+                let pushed = self.active_streams.try_push(
+                    protocol::send(stream, self.observed_addr.clone()).map_ok(|()| Success::Sent),
+                );
+                if pushed.is_err() {
+                    tracing::warn!("Dropping inbound stream because we are at capacity");
+                }
+            }
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol: stream,
+                ..
+            }) => {
+                let pushed = self
+                    .active_streams
+                    .try_push(protocol::recv(stream).map_ok(Success::Received));
+                if pushed.is_err() {
+                    tracing::warn!("Dropping outbound stream because we are at capacity");
+                }
+            }
+            ConnectionEvent::DialUpgradeError(DialUpgradeError { error, .. }) => {
+                self.events.push_back(Event::Error(
+                    error.map_upgrade_err(|e| void::unreachable(e)),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use futures::prelude::*;
+use libp2p_core::{multiaddr, Multiaddr};
+use libp2p_swarm::StreamProtocol;
+use std::io;
+use thiserror::Error;
+
+/// The maximum length of an encoded observed address.
+pub(crate) const MAX_ADDRESS_SIZE_BYTES: usize = 1024;
+
+pub const PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/libp2p/observed-address/1.0.0");
+
+/// Sends the address we observe for the remote, prefixed with its length as unsigned varint.
+pub(crate) async fn send<S>(mut stream: S, observed: Multiaddr) -> Result<(), UpgradeError>
+where
+    S: AsyncWrite + Unpin,
+{
+    let bytes = observed.to_vec();
+    let mut length = unsigned_varint::encode::usize_buffer();
+
+    stream
+        .write_all(unsigned_varint::encode::usize(bytes.len(), &mut length))
+        .await?;
+    stream.write_all(&bytes).await?;
+    stream.close().await?;
+
+    Ok(())
+}
+
+/// Receives the address the remote observes for us.
+pub(crate) async fn recv<S>(mut stream: S) -> Result<Multiaddr, UpgradeError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // Even though we won't write to the stream anymore we don't close it here.
+    // The reason for this is that the `close` call on some transport's require the
+    // remote's ACK, but it could be that the remote already dropped the stream
+    // after sending us the address.
+
+    let length = unsigned_varint::aio::read_usize(&mut stream).await?;
+    if length > MAX_ADDRESS_SIZE_BYTES {
+        return Err(UpgradeError::TooLarge(length));
+    }
+
+    let mut bytes = vec![0; length];
+    stream.read_exact(&mut bytes).await?;
+
+    Ok(Multiaddr::try_from(bytes)?)
+}
+
+#[derive(Debug, Error)]
+pub enum UpgradeError {
+    #[error("I/O interaction failed")]
+    Io(#[from] io::Error),
+    #[error("Failed decoding length prefix")]
+    Length(#[from] unsigned_varint::io::ReadError),
+    #[error("Observed address of {0} bytes exceeds the limit of {MAX_ADDRESS_SIZE_BYTES} bytes")]
+    TooLarge(usize),
+    #[error("Failed decoding multiaddr")]
+    Multiaddr(#[from] multiaddr::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::Cursor;
+
+    #[test]
+    fn roundtrip() {
+        let observed: Multiaddr = "/ip4/203.0.113.7/tcp/4001".parse().unwrap();
+        let mut stream = Cursor::new(Vec::new());
+
+        futures::executor::block_on(send(&mut stream, observed.clone())).unwrap();
+        stream.set_position(0);
+        let received = futures::executor::block_on(recv(&mut stream)).unwrap();
+
+        assert_eq!(received, observed);
+    }
+
+    #[test]
+    fn rejects_too_large_address() {
+        let mut length = unsigned_varint::encode::usize_buffer();
+        let mut stream = Cursor::new(
+            unsigned_varint::encode::usize(MAX_ADDRESS_SIZE_BYTES + 1, &mut length).to_vec(),
+        );
+
+        let error = futures::executor::block_on(recv(&mut stream)).unwrap_err();
+
+        assert!(matches!(error, UpgradeError::TooLarge(_)));
+    }
+}
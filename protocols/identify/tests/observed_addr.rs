@@ -0,0 +1,107 @@
+use futures::future::Either;
+use libp2p_core::muxing::StreamMuxerBox;
+use libp2p_core::transport::Boxed;
+use libp2p_core::upgrade::Version;
+use libp2p_core::{Multiaddr, Transport};
+use libp2p_identify::observed_addr;
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_swarm::{Config, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
+
+#[async_std::test]
+async fn reports_source_address_over_tcp() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    assert_observes_source_address(tcp_transport, "/ip4/127.0.0.1/tcp/0").await;
+}
+
+#[async_std::test]
+async fn reports_source_address_over_quic() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    assert_observes_source_address(quic_transport, "/ip4/127.0.0.1/udp/0/quic-v1").await;
+}
+
+/// Connects two swarms over the given transport, asserting that the address the dialer is
+/// reported to be observed as is the address the listener accepted the connection from.
+async fn assert_observes_source_address(
+    transport: fn(&Keypair) -> Boxed<(PeerId, StreamMuxerBox)>,
+    listen_addr: &str,
+) {
+    let mut listener = new_swarm(transport);
+    let mut dialer = new_swarm(transport);
+    let listener_peer_id = *listener.local_peer_id();
+
+    listener.listen_on(listen_addr.parse().unwrap()).unwrap();
+    let listen_addr = listener
+        .wait(|e| match e {
+            SwarmEvent::NewListenAddr { address, .. } => Some(address),
+            _ => None,
+        })
+        .await;
+    dialer.dial(listen_addr).unwrap();
+
+    let mut source_addr = None;
+    let mut observed = None;
+    let mut candidate = None;
+    while source_addr.is_none() || observed.is_none() || candidate.is_none() {
+        match futures::future::select(dialer.next_swarm_event(), listener.next_swarm_event()).await
+        {
+            Either::Left((
+                SwarmEvent::Behaviour(observed_addr::Event::Observed {
+                    peer_id,
+                    observed_addr,
+                    ..
+                }),
+                _,
+            )) => {
+                assert_eq!(peer_id, listener_peer_id);
+                observed = Some(observed_addr);
+            }
+            Either::Left((SwarmEvent::NewExternalAddrCandidate { address }, _)) => {
+                candidate = Some(address);
+            }
+            Either::Right((SwarmEvent::ConnectionEstablished { endpoint, .. }, _)) => {
+                source_addr = Some(endpoint.get_remote_address().clone());
+            }
+            _ => {}
+        }
+    }
+
+    let source_addr: Multiaddr = source_addr.unwrap();
+    assert_eq!(observed.unwrap(), source_addr);
+    assert_eq!(candidate.unwrap(), source_addr);
+}
+
+fn new_swarm(
+    transport: fn(&Keypair) -> Boxed<(PeerId, StreamMuxerBox)>,
+) -> Swarm<observed_addr::Behaviour> {
+    let identity = Keypair::generate_ed25519();
+
+    Swarm::new(
+        transport(&identity),
+        observed_addr::Behaviour::new(),
+        identity.public().to_peer_id(),
+        Config::with_async_std_executor().with_idle_connection_timeout(Duration::from_secs(5)),
+    )
+}
+
+fn tcp_transport(identity: &Keypair) -> Boxed<(PeerId, StreamMuxerBox)> {
+    libp2p_tcp::async_io::Transport::default()
+        .upgrade(Version::V1)
+        .authenticate(libp2p_plaintext::Config::new(identity))
+        .multiplex(libp2p_yamux::Config::default())
+        .boxed()
+}
+
+fn quic_transport(identity: &Keypair) -> Boxed<(PeerId, StreamMuxerBox)> {
+    libp2p_quic::async_std::Transport::new(libp2p_quic::Config::new(identity))
+        .map(|(peer_id, connection), _| (peer_id, StreamMuxerBox::new(connection)))
+        .boxed()
+}
@@ -2054,22 +2054,6 @@ where
         let Some(peer_id) = peer_id else { return };
 
         match error {
-            DialError::LocalPeerId { .. }
-            | DialError::WrongPeerId { .. }
-            | DialError::Aborted
-            | DialError::Denied { .. }
-            | DialError::Transport(_)
-            | DialError::NoAddresses => {
-                if let DialError::Transport(addresses) = error {
-                    for (addr, _) in addresses {
-                        self.address_failed(peer_id, addr)
-                    }
-                }
-
-                for query in self.queries.iter_mut() {
-                    query.on_failure(&peer_id);
-                }
-            }
             DialError::DialPeerConditionFalse(
                 dial_opts::PeerCondition::Disconnected
                 | dial_opts::PeerCondition::NotDialing
@@ -2081,6 +2065,17 @@ where
             DialError::DialPeerConditionFalse(dial_opts::PeerCondition::Always) => {
                 unreachable!("DialPeerCondition::Always can not trigger DialPeerConditionFalse.");
             }
+            _ => {
+                if let DialError::Transport(addresses) = error {
+                    for (addr, _) in addresses {
+                        self.address_failed(peer_id, addr)
+                    }
+                }
+
+                for query in self.queries.iter_mut() {
+                    query.on_failure(&peer_id);
+                }
+            }
         }
     }
 
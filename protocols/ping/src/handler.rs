@@ -21,19 +21,20 @@
 use crate::{protocol, PROTOCOL_NAME};
 use futures::future::{BoxFuture, Either};
 use futures::prelude::*;
-use futures_timer::Delay;
 use libp2p_core::upgrade::ReadyUpgrade;
 use libp2p_swarm::handler::{
     ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
 };
 use libp2p_swarm::{
-    ConnectionHandler, ConnectionHandlerEvent, Stream, StreamProtocol, StreamUpgradeError,
-    SubstreamProtocol,
+    Clock, ConnectionHandler, ConnectionHandlerEvent, Stream, StreamProtocol, StreamUpgradeError,
+    SubstreamProtocol, SystemClock, Timer,
 };
 use std::collections::VecDeque;
 use std::{
     error::Error,
     fmt, io,
+    num::NonZeroU32,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -46,6 +47,10 @@ pub struct Config {
     timeout: Duration,
     /// The duration between outbound pings.
     interval: Duration,
+    /// The number of consecutive outbound ping failures after which the connection is closed.
+    max_failures: Option<NonZeroU32>,
+    /// Source of time of the ping interval and timeout.
+    clock: Arc<dyn Clock>,
 }
 
 impl Config {
@@ -63,6 +68,8 @@ impl Config {
         Self {
             timeout: Duration::from_secs(20),
             interval: Duration::from_secs(15),
+            max_failures: None,
+            clock: Arc::new(SystemClock),
         }
     }
 
@@ -77,6 +84,29 @@ impl Config {
         self.interval = d;
         self
     }
+
+    /// Closes the connection once the given number of consecutive outbound pings failed.
+    ///
+    /// The last failure is reported with
+    /// [`Event::max_failures_reached`](crate::Event::max_failures_reached) set, right before the
+    /// connection is closed. Note that the first failure on a connection is counted, but only
+    /// reported if it is the last.
+    ///
+    /// By default, connections are never closed due to ping failures.
+    pub fn with_max_failures(mut self, n: NonZeroU32) -> Self {
+        self.max_failures = Some(n);
+        self
+    }
+
+    /// Sets the [`Clock`] driving the ping interval and timeout, e.g. the one of the
+    /// [`Swarm`](libp2p_swarm::Swarm) set via
+    /// [`Config::with_clock`](libp2p_swarm::Config::with_clock).
+    ///
+    /// Defaults to the [`SystemClock`].
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
 }
 
 impl Default for Config {
@@ -125,13 +155,30 @@ impl Error for Failure {
     }
 }
 
+/// The result of an outbound ping, reported by the [`Handler`] to the behaviour.
+#[derive(Debug)]
+pub struct Outcome {
+    pub(crate) result: Result<Duration, Failure>,
+    /// Whether the configured maximum of consecutive failures was reached.
+    pub(crate) max_failures_reached: bool,
+}
+
+impl From<Result<Duration, Failure>> for Outcome {
+    fn from(result: Result<Duration, Failure>) -> Self {
+        Self {
+            result,
+            max_failures_reached: false,
+        }
+    }
+}
+
 /// Protocol handler that handles pinging the remote at a regular period
 /// and answering ping queries.
 pub struct Handler {
     /// Configuration options.
     config: Config,
     /// The timer used for the delay to the next ping.
-    interval: Delay,
+    interval: Timer,
     /// Outbound ping failures that are pending to be processed by `poll()`.
     pending_errors: VecDeque<Failure>,
     /// The number of consecutive ping failures that occurred.
@@ -165,8 +212,8 @@ impl Handler {
     /// Builds a new [`Handler`] with the given configuration.
     pub fn new(config: Config) -> Self {
         Handler {
+            interval: config.clock.timer(Duration::ZERO),
             config,
-            interval: Delay::new(Duration::new(0, 0)),
             pending_errors: VecDeque::with_capacity(2),
             failures: 0,
             outbound: None,
@@ -208,7 +255,7 @@ impl Handler {
 
 impl ConnectionHandler for Handler {
     type FromBehaviour = Void;
-    type ToBehaviour = Result<Duration, Failure>;
+    type ToBehaviour = Outcome;
     type InboundProtocol = ReadyUpgrade<StreamProtocol>;
     type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
     type OutboundOpenInfo = ();
@@ -224,17 +271,16 @@ impl ConnectionHandler for Handler {
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
-    ) -> Poll<ConnectionHandlerEvent<ReadyUpgrade<StreamProtocol>, (), Result<Duration, Failure>>>
-    {
+    ) -> Poll<ConnectionHandlerEvent<ReadyUpgrade<StreamProtocol>, (), Outcome>> {
         match self.state {
             State::Inactive { reported: true } => {
                 return Poll::Pending; // nothing to do on this connection
             }
             State::Inactive { reported: false } => {
                 self.state = State::Inactive { reported: true };
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Err(
-                    Failure::Unsupported,
-                )));
+                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
+                    Err(Failure::Unsupported).into(),
+                ));
             }
             State::Active => {}
         }
@@ -263,13 +309,21 @@ impl ConnectionHandler for Handler {
 
                 self.failures += 1;
 
+                let max_failures_reached = self
+                    .config
+                    .max_failures
+                    .is_some_and(|max| self.failures >= max.get());
+
                 // Note: For backward-compatibility the first failure is always "free"
                 // and silent. This allows peers who use a new substream
                 // for each ping to have successful ping exchanges with peers
                 // that use a single substream, since every successful ping
                 // resets `failures` to `0`.
-                if self.failures > 1 {
-                    return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Err(error)));
+                if self.failures > 1 || max_failures_reached {
+                    return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Outcome {
+                        result: Err(error),
+                        max_failures_reached,
+                    }));
                 }
             }
 
@@ -283,12 +337,14 @@ impl ConnectionHandler for Handler {
                     Poll::Ready(Ok((stream, rtt))) => {
                         tracing::debug!(?rtt, "ping succeeded");
                         self.failures = 0;
-                        self.interval.reset(self.config.interval);
+                        self.interval = self.config.clock.timer(self.config.interval);
                         self.outbound = Some(OutboundState::Idle(stream));
-                        return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Ok(rtt)));
+                        return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
+                            Ok(rtt).into(),
+                        ));
                     }
                     Poll::Ready(Err(e)) => {
-                        self.interval.reset(self.config.interval);
+                        self.interval = self.config.clock.timer(self.config.interval);
                         self.pending_errors.push_front(e);
                     }
                 },
@@ -299,7 +355,7 @@ impl ConnectionHandler for Handler {
                     }
                     Poll::Ready(()) => {
                         self.outbound = Some(OutboundState::Ping(
-                            send_ping(stream, self.config.timeout).boxed(),
+                            send_ping(stream, self.config.clock.timer(self.config.timeout)).boxed(),
                         ));
                     }
                 },
@@ -346,7 +402,7 @@ impl ConnectionHandler for Handler {
             }) => {
                 stream.ignore_for_keep_alive();
                 self.outbound = Some(OutboundState::Ping(
-                    send_ping(stream, self.config.timeout).boxed(),
+                    send_ping(stream, self.config.clock.timer(self.config.timeout)).boxed(),
                 ));
             }
             ConnectionEvent::DialUpgradeError(dial_upgrade_error) => {
@@ -371,11 +427,11 @@ enum OutboundState {
 }
 
 /// A wrapper around [`protocol::send_ping`] that enforces a time out.
-async fn send_ping(stream: Stream, timeout: Duration) -> Result<(Stream, Duration), Failure> {
+async fn send_ping(stream: Stream, timeout: Timer) -> Result<(Stream, Duration), Failure> {
     let ping = protocol::send_ping(stream);
     futures::pin_mut!(ping);
 
-    match future::select(ping, Delay::new(timeout)).await {
+    match future::select(ping, timeout).await {
         Either::Left((Ok((stream, rtt)), _)) => Ok((stream, rtt)),
         Either::Left((Err(e), _)) => Err(Failure::other(e)),
         Either::Right(((), _)) => Err(Failure::Timeout),
@@ -42,6 +42,9 @@
 //! - [`Swarm::close_connection`](libp2p_swarm::Swarm::close_connection) to close a specific connection
 //! - [`Swarm::disconnect_peer_id`](libp2p_swarm::Swarm::disconnect_peer_id) to close all connections to a peer
 //!
+//! Alternatively, [`Config::with_max_failures`] closes a connection once the given number of
+//! consecutive pings on it failed.
+//!
 //! [`Swarm`]: libp2p_swarm::Swarm
 //! [`Transport`]: libp2p_core::Transport
 
@@ -54,8 +57,8 @@ use handler::Handler;
 use libp2p_core::{Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use libp2p_swarm::{
-    behaviour::FromSwarm, ConnectionDenied, ConnectionId, NetworkBehaviour, THandler,
-    THandlerInEvent, THandlerOutEvent, ToSwarm,
+    behaviour::FromSwarm, CloseConnection, ConnectionDenied, ConnectionId, NetworkBehaviour,
+    StreamProtocol, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
 };
 use std::time::Duration;
 use std::{
@@ -75,6 +78,8 @@ pub struct Behaviour {
     config: Config,
     /// Queue of events to yield to the swarm.
     events: VecDeque<Event>,
+    /// Connections to close as their maximum of consecutive ping failures was reached.
+    to_close: VecDeque<(PeerId, ConnectionId)>,
 }
 
 /// Event generated by the `Ping` network behaviour.
@@ -86,6 +91,9 @@ pub struct Event {
     pub connection: ConnectionId,
     /// The result of an inbound or outbound ping.
     pub result: Result<Duration, Failure>,
+    /// Whether this failure is the last of [`Config::with_max_failures`] consecutive failures,
+    /// thus the connection is being closed.
+    pub max_failures_reached: bool,
 }
 
 impl Behaviour {
@@ -94,6 +102,7 @@ impl Behaviour {
         Self {
             config,
             events: VecDeque::new(),
+            to_close: VecDeque::new(),
         }
     }
 }
@@ -128,26 +137,42 @@ impl NetworkBehaviour for Behaviour {
         Ok(Handler::new(self.config.clone()))
     }
 
+    fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        vec![PROTOCOL_NAME]
+    }
+
     fn on_connection_handler_event(
         &mut self,
         peer: PeerId,
         connection: ConnectionId,
-        result: THandlerOutEvent<Self>,
+        outcome: THandlerOutEvent<Self>,
     ) {
+        if outcome.max_failures_reached {
+            self.to_close.push_front((peer, connection));
+        }
+
         self.events.push_front(Event {
             peer,
             connection,
-            result,
+            result: outcome.result,
+            max_failures_reached: outcome.max_failures_reached,
         })
     }
 
     #[tracing::instrument(level = "trace", name = "NetworkBehaviour::poll", skip(self))]
     fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
         if let Some(e) = self.events.pop_back() {
-            Poll::Ready(ToSwarm::GenerateEvent(e))
-        } else {
-            Poll::Pending
+            return Poll::Ready(ToSwarm::GenerateEvent(e));
+        }
+
+        if let Some((peer_id, connection)) = self.to_close.pop_back() {
+            return Poll::Ready(ToSwarm::CloseConnection {
+                peer_id,
+                connection: CloseConnection::One(connection),
+            });
         }
+
+        Poll::Pending
     }
 
     fn on_swarm_event(&mut self, _event: FromSwarm) {}
@@ -20,12 +20,17 @@
 
 //! Integration tests for the `Ping` network behaviour.
 
+use futures::StreamExt;
 use libp2p_ping as ping;
 use libp2p_swarm::dummy;
+use libp2p_swarm::testing::{connect, TestRuntime, TestSwarm};
 use libp2p_swarm::{Swarm, SwarmEvent};
 use libp2p_swarm_test::SwarmExt;
 use quickcheck::*;
-use std::{num::NonZeroU8, time::Duration};
+use std::{
+    num::{NonZeroU32, NonZeroU8},
+    time::Duration,
+};
 
 #[test]
 fn ping_pong() {
@@ -93,3 +98,61 @@ fn unsupported_doesnt_fail() {
 
     result.expect("node with ping should not fail connection due to unsupported protocol");
 }
+
+#[test]
+fn closes_connection_after_max_failures() {
+    let runtime = TestRuntime::new();
+    let cfg = ping::Config::new()
+        .with_interval(Duration::from_millis(10))
+        .with_timeout(Duration::from_millis(50))
+        .with_max_failures(NonZeroU32::new(3).unwrap())
+        .with_clock(runtime.clock());
+
+    // Accepts inbound pings but never answers them.
+    let mut silent = TestSwarm::new(&runtime, |_| libp2p_stream::Behaviour::new());
+    let mut incoming = silent
+        .behaviour()
+        .new_control()
+        .accept(ping::PROTOCOL_NAME)
+        .unwrap();
+    runtime.spawn(async move {
+        let mut streams = Vec::new();
+        while let Some((_, stream)) = incoming.next().await {
+            streams.push(stream);
+        }
+    });
+    let mut swarm = TestSwarm::new(&runtime, |_| ping::Behaviour::new(cfg));
+
+    connect(&mut swarm, &mut silent);
+    silent.run_in_background();
+
+    let mut failures = Vec::new();
+    swarm
+        .wait_for_event(
+            |event| match event {
+                SwarmEvent::Behaviour(ping::Event {
+                    result: Err(failure),
+                    max_failures_reached,
+                    ..
+                }) => {
+                    failures.push((failure, max_failures_reached));
+                    None
+                }
+                SwarmEvent::ConnectionClosed { .. } => Some(()),
+                _ => None,
+            },
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+    // The first of the three failures is not reported.
+    assert!(matches!(
+        failures.as_slice(),
+        [
+            (ping::Failure::Timeout, false),
+            (ping::Failure::Timeout, true)
+        ]
+    ));
+    // Three pings each timing out after 50ms, separated by intervals of 10ms.
+    assert_eq!(runtime.clock().elapsed(), Duration::from_millis(170));
+}
@@ -23,12 +23,12 @@
 pub(crate) mod handler;
 pub(crate) mod rate_limiter;
 use crate::behaviour::handler::Handler;
-use crate::multiaddr_ext::MultiaddrExt;
 use crate::proto;
 use crate::protocol::{inbound_hop, outbound_stop};
 use either::Either;
 use instant::Instant;
 use libp2p_core::multiaddr::Protocol;
+use libp2p_core::multiaddr_ext::MultiaddrExt;
 use libp2p_core::{ConnectedPoint, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use libp2p_swarm::behaviour::{ConnectionClosed, FromSwarm};
@@ -37,6 +37,7 @@ use libp2p_swarm::{
     THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
 };
 use std::collections::{hash_map, HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
 use std::num::NonZeroU32;
 use std::ops::Add;
 use std::task::{Context, Poll};
@@ -50,6 +51,9 @@ use std::time::Duration;
 pub struct Config {
     pub max_reservations: usize,
     pub max_reservations_per_peer: usize,
+    /// The maximum number of connections with an active reservation originating from the same
+    /// IP address, regardless of the peer ID used.
+    pub max_reservations_per_ip: usize,
     pub reservation_duration: Duration,
     pub reservation_rate_limiters: Vec<Box<dyn rate_limiter::RateLimiter>>,
 
@@ -99,6 +103,7 @@ impl std::fmt::Debug for Config {
         f.debug_struct("Config")
             .field("max_reservations", &self.max_reservations)
             .field("max_reservations_per_peer", &self.max_reservations_per_peer)
+            .field("max_reservations_per_ip", &self.max_reservations_per_ip)
             .field("reservation_duration", &self.reservation_duration)
             .field(
                 "reservation_rate_limiters",
@@ -147,6 +152,7 @@ impl Default for Config {
         Config {
             max_reservations: 128,
             max_reservations_per_peer: 4,
+            max_reservations_per_ip: 32,
             reservation_duration: Duration::from_secs(60 * 60),
             reservation_rate_limiters,
 
@@ -178,6 +184,11 @@ pub enum Event {
     },
     /// An inbound reservation request has been denied.
     ReservationReqDenied { src_peer_id: PeerId },
+    /// An inbound reservation request has been denied because its source exceeded
+    /// [`Config::max_reservations_per_ip`] or one of the [`Config::reservation_rate_limiters`].
+    ///
+    /// Once the denial has been sent, it is reported as [`Event::ReservationReqDenied`] as well.
+    ReservationReqRateLimited { src_peer_id: PeerId },
     /// Denying an inbound reservation request has failed.
     #[deprecated(
         note = "Will be removed in favor of logging them internally, see <https://github.com/libp2p/rust-libp2p/issues/4757> for details."
@@ -241,6 +252,8 @@ pub struct Behaviour {
     local_peer_id: PeerId,
 
     reservations: HashMap<PeerId, HashSet<ConnectionId>>,
+    /// The connections with an active reservation, by remote IP address.
+    reservations_by_ip: HashMap<IpAddr, HashSet<ConnectionId>>,
     circuits: CircuitsTracker,
 
     /// Queue of actions to return when polled.
@@ -255,6 +268,7 @@ impl Behaviour {
             config,
             local_peer_id,
             reservations: Default::default(),
+            reservations_by_ip: Default::default(),
             circuits: Default::default(),
             queued_actions: Default::default(),
             external_addresses: Default::default(),
@@ -275,6 +289,7 @@ impl Behaviour {
                 peer.remove();
             }
         }
+        self.remove_reservation_ip(connection_id);
 
         for circuit in self
             .circuits
@@ -291,6 +306,89 @@ impl Behaviour {
                 }));
         }
     }
+
+    /// Checks an inbound reservation request against the configured limits, tracking the
+    /// reservation if it is within all of them.
+    fn try_reserve(
+        &mut self,
+        peer_id: PeerId,
+        connection: ConnectionId,
+        endpoint: &ConnectedPoint,
+        renewed: bool,
+        now: Instant,
+    ) -> Result<(), ReservationDenial> {
+        let remote_addr = endpoint.get_remote_address();
+        let remote_ip = rate_limiter::multiaddr_to_ip(remote_addr);
+
+        // Deny if it is a new reservation and exceeds `max_reservations_per_peer`.
+        if (!renewed
+            && self
+                .reservations
+                .get(&peer_id)
+                .map(|cs| cs.len())
+                .unwrap_or(0)
+                > self.config.max_reservations_per_peer)
+            // Deny if it exceeds `max_reservations`.
+            || self
+                .reservations
+                .values()
+                .map(|cs| cs.len())
+                .sum::<usize>()
+                >= self.config.max_reservations
+        {
+            return Err(ReservationDenial::ResourceLimit);
+        }
+
+        // Deny if it is a new reservation and exceeds `max_reservations_per_ip`.
+        if !renewed
+            && remote_ip
+                .and_then(|ip| self.reservations_by_ip.get(&ip))
+                .map(|cs| cs.len())
+                .unwrap_or(0)
+                >= self.config.max_reservations_per_ip
+        {
+            return Err(ReservationDenial::RateLimit);
+        }
+
+        // Deny if it exceeds the allowed rate of reservations.
+        if !self
+            .config
+            .reservation_rate_limiters
+            .iter_mut()
+            .all(|limiter| limiter.try_next(peer_id, remote_addr, now))
+        {
+            return Err(ReservationDenial::RateLimit);
+        }
+
+        self.reservations
+            .entry(peer_id)
+            .or_default()
+            .insert(connection);
+        if let Some(ip) = remote_ip {
+            self.reservations_by_ip
+                .entry(ip)
+                .or_default()
+                .insert(connection);
+        }
+
+        Ok(())
+    }
+
+    fn remove_reservation_ip(&mut self, connection: ConnectionId) {
+        self.reservations_by_ip.retain(|_, cs| {
+            cs.remove(&connection);
+            !cs.is_empty()
+        });
+    }
+}
+
+/// The reason an inbound reservation request is denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReservationDenial {
+    /// The relay is at capacity, either in total or for the requesting peer.
+    ResourceLimit,
+    /// The requesting peer or its IP address exceeded a per-IP limit or a rate limit.
+    RateLimit,
 }
 
 impl NetworkBehaviour for Behaviour {
@@ -380,63 +478,44 @@ impl NetworkBehaviour for Behaviour {
                      denies all inbound substreams."
                 );
 
-                let action = if
-                // Deny if it is a new reservation and exceeds `max_reservations_per_peer`.
-                (!renewed
-                    && self
-                        .reservations
-                        .get(&event_source)
-                        .map(|cs| cs.len())
-                        .unwrap_or(0)
-                        > self.config.max_reservations_per_peer)
-                    // Deny if it exceeds `max_reservations`.
-                    || self
-                        .reservations
-                        .values()
-                        .map(|cs| cs.len())
-                        .sum::<usize>()
-                        >= self.config.max_reservations
-                    // Deny if it exceeds the allowed rate of reservations.
-                    || !self
-                        .config
-                        .reservation_rate_limiters
-                        .iter_mut()
-                        .all(|limiter| {
-                            limiter.try_next(event_source, endpoint.get_remote_address(), now)
-                        }) {
-                    ToSwarm::NotifyHandler {
-                        handler: NotifyHandler::One(connection),
-                        peer_id: event_source,
-                        event: Either::Left(handler::In::DenyReservationReq {
-                            inbound_reservation_req,
-                            status: proto::Status::RESOURCE_LIMIT_EXCEEDED,
-                        }),
-                    }
-                } else {
-                    // Accept reservation.
-                    self.reservations
-                        .entry(event_source)
-                        .or_default()
-                        .insert(connection);
-
-                    ToSwarm::NotifyHandler {
-                        handler: NotifyHandler::One(connection),
-                        peer_id: event_source,
-                        event: Either::Left(handler::In::AcceptReservationReq {
-                            inbound_reservation_req,
-                            addrs: self
-                                .external_addresses
-                                .iter()
-                                .cloned()
-                                // Add local peer ID in case it isn't present yet.
-                                .filter_map(|a| match a.iter().last()? {
-                                    Protocol::P2p(_) => Some(a),
-                                    _ => Some(a.with(Protocol::P2p(self.local_peer_id))),
-                                })
-                                .collect(),
-                        }),
-                    }
-                };
+                let action =
+                    match self.try_reserve(event_source, connection, &endpoint, renewed, now) {
+                        Err(denial) => {
+                            if denial == ReservationDenial::RateLimit {
+                                self.queued_actions.push_back(ToSwarm::GenerateEvent(
+                                    Event::ReservationReqRateLimited {
+                                        src_peer_id: event_source,
+                                    },
+                                ));
+                            }
+
+                            ToSwarm::NotifyHandler {
+                                handler: NotifyHandler::One(connection),
+                                peer_id: event_source,
+                                event: Either::Left(handler::In::DenyReservationReq {
+                                    inbound_reservation_req,
+                                    status: proto::Status::RESOURCE_LIMIT_EXCEEDED,
+                                }),
+                            }
+                        }
+                        Ok(()) => ToSwarm::NotifyHandler {
+                            handler: NotifyHandler::One(connection),
+                            peer_id: event_source,
+                            event: Either::Left(handler::In::AcceptReservationReq {
+                                inbound_reservation_req,
+                                addrs: self
+                                    .external_addresses
+                                    .iter()
+                                    .cloned()
+                                    // Add local peer ID in case it isn't present yet.
+                                    .filter_map(|a| match a.iter().last()? {
+                                        Protocol::P2p(_) => Some(a),
+                                        _ => Some(a.with(Protocol::P2p(self.local_peer_id))),
+                                    })
+                                    .collect(),
+                            }),
+                        },
+                    };
 
                 self.queued_actions.push_back(action);
             }
@@ -496,6 +575,7 @@ impl NetworkBehaviour for Behaviour {
                         );
                     }
                 }
+                self.remove_reservation_ip(connection);
 
                 self.queued_actions
                     .push_back(ToSwarm::GenerateEvent(Event::ReservationTimedOut {
@@ -792,3 +872,114 @@ impl Add<u64> for CircuitId {
         CircuitId(self.0 + rhs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn behaviour(config: Config) -> Behaviour {
+        Behaviour::new(PeerId::random(), config)
+    }
+
+    fn endpoint_from(ip: &str) -> ConnectedPoint {
+        ConnectedPoint::Listener {
+            local_addr: "/ip4/10.0.0.1/tcp/4001".parse().unwrap(),
+            send_back_addr: format!("/ip4/{ip}/tcp/50000").parse().unwrap(),
+        }
+    }
+
+    fn reserve_as_new_peer(behaviour: &mut Behaviour, ip: &str) -> Result<(), ReservationDenial> {
+        behaviour.try_reserve(
+            PeerId::random(),
+            ConnectionId::new_unchecked(rand::random()),
+            &endpoint_from(ip),
+            false,
+            Instant::now(),
+        )
+    }
+
+    #[test]
+    fn max_reservations_per_ip_denies_further_peers_from_same_ip() {
+        let mut behaviour = behaviour(Config {
+            max_reservations_per_ip: 3,
+            ..Default::default()
+        });
+
+        for _ in 0..3 {
+            assert_eq!(reserve_as_new_peer(&mut behaviour, "1.2.3.4"), Ok(()));
+        }
+        for _ in 0..3 {
+            assert_eq!(
+                reserve_as_new_peer(&mut behaviour, "1.2.3.4"),
+                Err(ReservationDenial::RateLimit)
+            );
+        }
+
+        assert_eq!(reserve_as_new_peer(&mut behaviour, "5.6.7.8"), Ok(()));
+    }
+
+    #[test]
+    fn closed_reservation_frees_ip_slot() {
+        let mut behaviour = behaviour(Config {
+            max_reservations_per_ip: 1,
+            ..Default::default()
+        });
+        let peer_id = PeerId::random();
+        let connection_id = ConnectionId::new_unchecked(0);
+        let endpoint = endpoint_from("1.2.3.4");
+
+        behaviour
+            .try_reserve(peer_id, connection_id, &endpoint, false, Instant::now())
+            .unwrap();
+        assert_eq!(
+            reserve_as_new_peer(&mut behaviour, "1.2.3.4"),
+            Err(ReservationDenial::RateLimit)
+        );
+
+        behaviour.on_connection_closed(ConnectionClosed {
+            peer_id,
+            connection_id,
+            endpoint: &endpoint,
+            remaining_established: 0,
+        });
+
+        assert_eq!(reserve_as_new_peer(&mut behaviour, "1.2.3.4"), Ok(()));
+    }
+
+    #[test]
+    fn reservation_rate_per_ip_denies_further_peers_from_same_ip() {
+        let mut behaviour = behaviour(
+            Config {
+                reservation_rate_limiters: Vec::new(),
+                ..Default::default()
+            }
+            .reservation_rate_per_ip(NonZeroU32::new(2).unwrap(), Duration::from_secs(60)),
+        );
+
+        for _ in 0..2 {
+            assert_eq!(reserve_as_new_peer(&mut behaviour, "1.2.3.4"), Ok(()));
+        }
+        for _ in 0..3 {
+            assert_eq!(
+                reserve_as_new_peer(&mut behaviour, "1.2.3.4"),
+                Err(ReservationDenial::RateLimit)
+            );
+        }
+
+        assert_eq!(reserve_as_new_peer(&mut behaviour, "5.6.7.8"), Ok(()));
+    }
+
+    #[test]
+    fn max_reservations_is_a_resource_limit() {
+        let mut behaviour = behaviour(Config {
+            max_reservations: 1,
+            ..Default::default()
+        });
+
+        assert_eq!(reserve_as_new_peer(&mut behaviour, "1.2.3.4"), Ok(()));
+        assert_eq!(
+            reserve_as_new_peer(&mut behaviour, "5.6.7.8"),
+            Err(ReservationDenial::ResourceLimit)
+        );
+    }
+}
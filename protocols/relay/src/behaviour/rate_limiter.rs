@@ -57,7 +57,7 @@ impl<T: FnMut(PeerId, &Multiaddr, Instant) -> bool + Send> RateLimiter for T {
     }
 }
 
-fn multiaddr_to_ip(addr: &Multiaddr) -> Option<IpAddr> {
+pub(crate) fn multiaddr_to_ip(addr: &Multiaddr) -> Option<IpAddr> {
     addr.iter().find_map(|p| match p {
         Protocol::Ip4(addr) => Some(addr.into()),
         Protocol::Ip6(addr) => Some(addr.into()),
@@ -23,7 +23,6 @@
 pub(crate) mod handler;
 pub(crate) mod transport;
 
-use crate::multiaddr_ext::MultiaddrExt;
 use crate::priv_client::handler::Handler;
 use crate::protocol::{self, inbound_stop};
 use bytes::Bytes;
@@ -34,6 +33,7 @@ use futures::io::{AsyncRead, AsyncWrite};
 use futures::ready;
 use futures::stream::StreamExt;
 use libp2p_core::multiaddr::Protocol;
+use libp2p_core::multiaddr_ext::MultiaddrExt;
 use libp2p_core::{Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use libp2p_swarm::behaviour::{ConnectionClosed, ConnectionEstablished, FromSwarm};
@@ -19,7 +19,6 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::multiaddr_ext::MultiaddrExt;
 use crate::priv_client::Connection;
 use crate::protocol::outbound_hop;
 use crate::protocol::outbound_hop::{ConnectError, ReserveError};
@@ -32,6 +31,7 @@ use futures::sink::SinkExt;
 use futures::stream::SelectAll;
 use futures::stream::{Stream, StreamExt};
 use libp2p_core::multiaddr::{Multiaddr, Protocol};
+use libp2p_core::multiaddr_ext::MultiaddrExt;
 use libp2p_core::transport::{ListenerId, TransportError, TransportEvent};
 use libp2p_identity::PeerId;
 use std::collections::VecDeque;
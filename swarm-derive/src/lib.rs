@@ -27,6 +27,7 @@ use crate::syn_ext::RequireStrLit;
 use heck::ToUpperCamelCase;
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::ext::IdentExt;
 use syn::punctuated::Punctuated;
 use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Meta, Token};
 
@@ -60,6 +61,8 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
     let BehaviourAttributes {
         prelude_path,
         user_specified_out_event,
+        event_accessors,
+        poll_order,
     } = parse_attributes(ast)?;
 
     let multiaddr = quote! { #prelude_path::Multiaddr };
@@ -76,6 +79,28 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
     let t_handler_out_event = quote! { #prelude_path::THandlerOutEvent };
     let endpoint = quote! { #prelude_path::Endpoint };
     let connection_denied = quote! { #prelude_path::ConnectionDenied };
+    let listen_denied = quote! { #prelude_path::ListenDenied };
+    let listener_id = quote! { #prelude_path::ListenerId };
+    let stream_protocol = quote! { #prelude_path::StreamProtocol };
+    let connection_payload = quote! { #prelude_path::ConnectionPayload };
+    let poll_rotation = quote! { #prelude_path::PollRotation };
+
+    // The payloads of the fields are combined into a single payload, which is only created if any
+    // field returned one, and split up again once the connection is established.
+    let combine_payloads = quote! {
+        if payloads.iter().any(Option::is_some) {
+            Some(::std::boxed::Box::new(payloads) as #connection_payload)
+        } else {
+            None
+        }
+    };
+    let split_payloads = quote! {
+        let mut payloads = payload
+            .and_then(|payload| payload.downcast::<::std::vec::Vec<Option<#connection_payload>>>().ok())
+            .map(|payloads| *payloads)
+            .unwrap_or_default()
+            .into_iter();
+    };
 
     // Build the generics.
     let impl_generics = {
@@ -157,6 +182,69 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
                         .as_ref()
                         .map(|where_clause| quote! {#where_clause, #(#additional_debug),*});
 
+                    let accessors = event_accessors.then(|| {
+                        let methods = data_struct.fields.iter().zip(fields.clone()).map(
+                            |(field, (variant, ty))| {
+                                let field_name = field
+                                    .ident
+                                    .as_ref()
+                                    .expect("Fields of NetworkBehaviour implementation to be named.")
+                                    .unraw();
+                                let method = syn::Ident::new(
+                                    &format!("as_{field_name}"),
+                                    field_name.span(),
+                                );
+                                let doc = format!(
+                                    "Returns the event of the `{field_name}` behaviour, if this is one."
+                                );
+                                quote! {
+                                    #[doc = #doc]
+                                    #[allow(unreachable_patterns)]
+                                    #visibility fn #method(&self) -> ::core::option::Option<&<#ty as #trait_to_impl>::ToSwarm> {
+                                        match self {
+                                            #enum_name::#variant(event) => ::core::option::Option::Some(event),
+                                            _ => ::core::option::Option::None,
+                                        }
+                                    }
+                                }
+                            },
+                        );
+
+                        let into_methods = data_struct.fields.iter().zip(fields.clone()).map(
+                            |(field, (variant, ty))| {
+                                let field_name = field
+                                    .ident
+                                    .as_ref()
+                                    .expect("Fields of NetworkBehaviour implementation to be named.")
+                                    .unraw();
+                                let method = syn::Ident::new(
+                                    &format!("into_{field_name}"),
+                                    field_name.span(),
+                                );
+                                let doc = format!(
+                                    "Converts into the event of the `{field_name}` behaviour, returning `self` if this is not one."
+                                );
+                                quote! {
+                                    #[doc = #doc]
+                                    #[allow(unreachable_patterns)]
+                                    #visibility fn #method(self) -> ::core::result::Result<<#ty as #trait_to_impl>::ToSwarm, Self> {
+                                        match self {
+                                            #enum_name::#variant(event) => ::core::result::Result::Ok(event),
+                                            event => ::core::result::Result::Err(event),
+                                        }
+                                    }
+                                }
+                            },
+                        );
+
+                        quote! {
+                            impl #impl_generics #enum_name #ty_generics #where_clause {
+                                #(#methods)*
+                                #(#into_methods)*
+                            }
+                        }
+                    });
+
                     let match_variants = fields.map(|(variant, _ty)| variant);
                     let msg = format!("`NetworkBehaviour::ToSwarm` produced by {name}.");
 
@@ -177,6 +265,8 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
                                 }
                             }
                         }
+
+                        #accessors
                     })
                 };
                 let from_clauses = vec![];
@@ -285,6 +375,68 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
                 }
             });
 
+    // The content of `handle_pending_inbound_connection_with_payload`, collecting the payload of
+    // each field in declaration order.
+    let handle_pending_inbound_connection_with_payload_stmts =
+        data_struct
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(field_n, field)| {
+                match field.ident {
+                    Some(ref i) => quote! {
+                        payloads.push(#trait_to_impl::handle_pending_inbound_connection_with_payload(&mut self.#i, connection_id, local_addr, remote_addr)?);
+                    },
+                    None => quote! {
+                        payloads.push(#trait_to_impl::handle_pending_inbound_connection_with_payload(&mut self.#field_n, connection_id, local_addr, remote_addr)?);
+                    }
+                }
+            });
+
+    // The content of `handle_pending_listen`.
+    let handle_pending_listen_stmts =
+        data_struct
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(field_n, field)| match field.ident {
+                Some(ref i) => quote! {
+                    #trait_to_impl::handle_pending_listen(&mut self.#i, listener_id, addr)?;
+                },
+                None => quote! {
+                    #trait_to_impl::handle_pending_listen(&mut self.#field_n, listener_id, addr)?;
+                },
+            });
+
+    // The content of `sort_addresses`.
+    let sort_addresses_stmts = data_struct
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(field_n, field)| match field.ident {
+            Some(ref i) => quote! {
+                #trait_to_impl::sort_addresses(&self.#i, peer, addrs);
+            },
+            None => quote! {
+                #trait_to_impl::sort_addresses(&self.#field_n, peer, addrs);
+            },
+        });
+
+    // The content of `supported_protocols`.
+    let supported_protocols_stmts =
+        data_struct
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(field_n, field)| match field.ident {
+                Some(ref i) => quote! {
+                    protocols.extend(#trait_to_impl::supported_protocols(&self.#i));
+                },
+                None => quote! {
+                    protocols.extend(#trait_to_impl::supported_protocols(&self.#field_n));
+                },
+            });
+
     // The content of `handle_established_inbound_connection`.
     let handle_established_inbound_connection = {
         let mut out_handler = None;
@@ -308,6 +460,30 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
         out_handler.unwrap_or(quote! {()}) // TODO: See test `empty`.
     };
 
+    // The content of `handle_established_inbound_connection_with_payload`, handing each field the
+    // payload it returned from `handle_pending_inbound_connection_with_payload`.
+    let handle_established_inbound_connection_with_payload = {
+        let mut out_handler = None;
+
+        for (field_n, field) in data_struct.fields.iter().enumerate() {
+            let field_name = match field.ident {
+                Some(ref i) => quote! { self.#i },
+                None => quote! { self.#field_n },
+            };
+
+            let builder = quote! {
+                #field_name.handle_established_inbound_connection_with_payload(connection_id, peer, local_addr, remote_addr, payloads.next().flatten())?
+            };
+
+            match out_handler {
+                Some(h) => out_handler = Some(quote! { #connection_handler::select(#h, #builder) }),
+                ref mut h @ None => *h = Some(builder),
+            }
+        }
+
+        out_handler.unwrap_or(quote! {()}) // TODO: See test `empty`.
+    };
+
     // The content of `handle_pending_outbound_connection`.
     let handle_pending_outbound_connection = {
         let extend_stmts =
@@ -335,6 +511,36 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
         }
     };
 
+    // The content of `handle_pending_outbound_connection_with_payload`.
+    let handle_pending_outbound_connection_with_payload = {
+        let extend_stmts =
+            data_struct
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(field_n, field)| {
+                    let field_name = match field.ident {
+                        Some(ref i) => quote! { self.#i },
+                        None => quote! { self.#field_n },
+                    };
+
+                    quote! {
+                        let (field_addresses, payload) = #trait_to_impl::handle_pending_outbound_connection_with_payload(&mut #field_name, connection_id, maybe_peer, addresses, effective_role)?;
+                        combined_addresses.extend(field_addresses);
+                        payloads.push(payload);
+                    }
+                });
+
+        quote! {
+            let mut combined_addresses = vec![];
+            let mut payloads = ::std::vec::Vec::<Option<#connection_payload>>::new();
+
+            #(#extend_stmts)*
+
+            Ok((combined_addresses, #combine_payloads))
+        }
+    };
+
     // The content of `handle_established_outbound_connection`.
     let handle_established_outbound_connection = {
         let mut out_handler = None;
@@ -358,10 +564,32 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
         out_handler.unwrap_or(quote! {()}) // TODO: See test `empty`.
     };
 
-    // List of statements to put in `poll()`.
-    //
-    // We poll each child one by one and wrap around the output.
-    let poll_stmts = data_struct
+    // The content of `handle_established_outbound_connection_with_payload`.
+    let handle_established_outbound_connection_with_payload = {
+        let mut out_handler = None;
+
+        for (field_n, field) in data_struct.fields.iter().enumerate() {
+            let field_name = match field.ident {
+                Some(ref i) => quote! { self.#i },
+                None => quote! { self.#field_n },
+            };
+
+            let builder = quote! {
+                #field_name.handle_established_outbound_connection_with_payload(connection_id, peer, addr, role_override, payloads.next().flatten())?
+            };
+
+            match out_handler {
+                Some(h) => out_handler = Some(quote! { #connection_handler::select(#h, #builder) }),
+                ref mut h @ None => *h = Some(builder),
+            }
+        }
+
+        out_handler.unwrap_or(quote! {()}) // TODO: See test `empty`.
+    };
+
+    // List of match arms polling the child with the given index in `poll()`, wrapping around its
+    // output.
+    let poll_arms = data_struct
         .fields
         .iter()
         .enumerate()
@@ -396,13 +624,58 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
             let map_in_event = quote! { |event| #wrapped_event };
 
             quote! {
-                match #trait_to_impl::poll(&mut self.#field, cx) {
-                    std::task::Poll::Ready(e) => return std::task::Poll::Ready(e.map_out(#map_out_event).map_in(#map_in_event)),
-                    std::task::Poll::Pending => {},
-                }
+                #field_n => #trait_to_impl::poll_with_rotation(&mut self.#field, cx, rotation.member(#field_n)).map(|e| e.map_out(#map_out_event).map_in(#map_in_event)),
             }
         });
 
+    // The content of `poll_with_rotation()`.
+    //
+    // We poll the children one by one, starting with the first one for `PollOrder::Sequential`
+    // and with the one following the child polled first on the previous call of this instance, as
+    // tracked by `rotation`, for `PollOrder::RoundRobin`, until one of them returns an event.
+    let poll_stmts = if data_struct.fields.is_empty() {
+        quote! { std::task::Poll::Pending }
+    } else {
+        let num_fields = data_struct.fields.len();
+        let field_names = data_struct.fields.iter().map(|field| {
+            field
+                .ident
+                .as_ref()
+                .expect("Fields of NetworkBehaviour implementation to be named.")
+                .unraw()
+                .to_string()
+        });
+        let start = match poll_order {
+            _ if num_fields == 1 => quote! { 0 },
+            PollOrder::Sequential => quote! { 0 },
+            PollOrder::RoundRobin => quote! { rotation.start(#num_fields) },
+        };
+
+        quote! {
+            const FIELDS: [&str; #num_fields] = [#(#field_names),*];
+
+            let start: usize = #start;
+            for offset in 0..#num_fields {
+                let mut index = start + offset;
+                if index >= #num_fields {
+                    index -= #num_fields;
+                }
+                let poll: std::task::Poll<#network_behaviour_action<Self::ToSwarm, #t_handler_in_event<Self>>> = match index {
+                    #(#poll_arms)*
+                    _ => unreachable!("index is less than the number of fields"),
+                };
+
+                if let std::task::Poll::Ready(event) = poll {
+                    rotation.on_ready(index, FIELDS[index], offset + 1 < #num_fields);
+                    return std::task::Poll::Ready(event);
+                }
+            }
+
+            rotation.on_pending();
+            std::task::Poll::Pending
+        }
+    };
+
     let out_event_reference = if out_event_definition.is_some() {
         quote! { #out_event_name #ty_generics }
     } else {
@@ -431,6 +704,17 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
                 Ok(())
             }
 
+            #[allow(clippy::needless_question_mark)]
+            fn handle_pending_listen(
+                &mut self,
+                listener_id: #listener_id,
+                addr: &#multiaddr,
+            ) -> Result<(), #listen_denied> {
+                #(#handle_pending_listen_stmts)*
+
+                Ok(())
+            }
+
             #[allow(clippy::needless_question_mark)]
             fn handle_established_inbound_connection(
                 &mut self,
@@ -453,6 +737,67 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
                 #handle_pending_outbound_connection
             }
 
+            #[allow(clippy::needless_question_mark)]
+            fn handle_pending_inbound_connection_with_payload(
+                &mut self,
+                connection_id: #connection_id,
+                local_addr: &#multiaddr,
+                remote_addr: &#multiaddr,
+            ) -> Result<Option<#connection_payload>, #connection_denied> {
+                let mut payloads = ::std::vec::Vec::<Option<#connection_payload>>::new();
+                #(#handle_pending_inbound_connection_with_payload_stmts)*
+
+                Ok(#combine_payloads)
+            }
+
+            #[allow(clippy::needless_question_mark)]
+            fn handle_established_inbound_connection_with_payload(
+                &mut self,
+                connection_id: #connection_id,
+                peer: #peer_id,
+                local_addr: &#multiaddr,
+                remote_addr: &#multiaddr,
+                payload: Option<#connection_payload>,
+            ) -> Result<#t_handler<Self>, #connection_denied> {
+                #split_payloads
+
+                Ok(#handle_established_inbound_connection_with_payload)
+            }
+
+            fn handle_pending_outbound_connection_with_payload(
+                &mut self,
+                connection_id: #connection_id,
+                maybe_peer: Option<#peer_id>,
+                addresses: &[#multiaddr],
+                effective_role: #endpoint,
+            ) -> Result<(::std::vec::Vec<#multiaddr>, Option<#connection_payload>), #connection_denied> {
+                #handle_pending_outbound_connection_with_payload
+            }
+
+            #[allow(clippy::needless_question_mark)]
+            fn handle_established_outbound_connection_with_payload(
+                &mut self,
+                connection_id: #connection_id,
+                peer: #peer_id,
+                addr: &#multiaddr,
+                role_override: #endpoint,
+                payload: Option<#connection_payload>,
+            ) -> Result<#t_handler<Self>, #connection_denied> {
+                #split_payloads
+
+                Ok(#handle_established_outbound_connection_with_payload)
+            }
+
+            fn sort_addresses(&self, peer: &#peer_id, addrs: &mut ::std::vec::Vec<#multiaddr>) {
+                #(#sort_addresses_stmts)*
+            }
+
+            fn supported_protocols(&self) -> ::std::vec::Vec<#stream_protocol> {
+                let mut protocols = ::std::vec::Vec::new();
+                #(#supported_protocols_stmts)*
+                protocols
+            }
+
             #[allow(clippy::needless_question_mark)]
             fn handle_established_outbound_connection(
                 &mut self,
@@ -476,8 +821,12 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
             }
 
             fn poll(&mut self, cx: &mut std::task::Context) -> std::task::Poll<#network_behaviour_action<Self::ToSwarm, #t_handler_in_event<Self>>> {
-                #(#poll_stmts)*
-                std::task::Poll::Pending
+                #trait_to_impl::poll_with_rotation(self, cx, &mut #poll_rotation::default())
+            }
+
+            #[allow(unused_variables)]
+            fn poll_with_rotation(&mut self, cx: &mut std::task::Context, rotation: &mut #poll_rotation) -> std::task::Poll<#network_behaviour_action<Self::ToSwarm, #t_handler_in_event<Self>>> {
+                #poll_stmts
             }
 
             fn on_swarm_event(&mut self, event: #from_swarm) {
@@ -492,6 +841,16 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
 struct BehaviourAttributes {
     prelude_path: syn::Path,
     user_specified_out_event: Option<syn::Type>,
+    event_accessors: bool,
+    poll_order: PollOrder,
+}
+
+/// The order in which the generated `poll()` polls the children.
+enum PollOrder {
+    /// Start with the child following the one polled first on the previous call.
+    RoundRobin,
+    /// Always start with the first child, in field order.
+    Sequential,
 }
 
 /// Parses the `value` of a key=value pair in the `#[behaviour]` attribute into the requested type.
@@ -499,7 +858,10 @@ fn parse_attributes(ast: &DeriveInput) -> syn::Result<BehaviourAttributes> {
     let mut attributes = BehaviourAttributes {
         prelude_path: syn::parse_quote! { ::libp2p::swarm::derive_prelude },
         user_specified_out_event: None,
+        event_accessors: false,
+        poll_order: PollOrder::RoundRobin,
     };
+    let mut event_accessors_path = None;
 
     for attr in ast
         .attrs
@@ -524,8 +886,41 @@ fn parse_attributes(ast: &DeriveInput) -> syn::Result<BehaviourAttributes> {
 
                 continue;
             }
+
+            if meta.path().is_ident("event_accessors") {
+                let path = meta.require_path_only()?;
+
+                attributes.event_accessors = true;
+                event_accessors_path = Some(path.clone());
+
+                continue;
+            }
+
+            if meta.path().is_ident("poll_order") {
+                let value = &meta.require_name_value()?.value;
+
+                attributes.poll_order = match value.require_str_lit()?.as_str() {
+                    "round_robin" => PollOrder::RoundRobin,
+                    "sequential" => PollOrder::Sequential,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "expected `\"round_robin\"` or `\"sequential\"`",
+                        ))
+                    }
+                };
+
+                continue;
+            }
         }
     }
 
+    if let (Some(path), Some(_)) = (event_accessors_path, &attributes.user_specified_out_event) {
+        return Err(syn::Error::new_spanned(
+            path,
+            "`event_accessors` is only supported for the generated `ToSwarm` event",
+        ));
+    }
+
     Ok(attributes)
 }
@@ -65,10 +65,13 @@ pub trait SwarmExt {
     /// Because we don't have access to the other [`Swarm`], we can't guarantee that it makes progress.
     async fn dial_and_wait(&mut self, addr: Multiaddr) -> PeerId;
 
-    /// Wait for specified condition to return `Some`.
+    /// Polls the [`Swarm`] until the predicate returns `Some` for an event, returning its value.
+    ///
+    /// Panics if the [`Swarm`] does not emit an event within 10 seconds, see
+    /// [`SwarmExt::next_swarm_event`].
     async fn wait<E, P>(&mut self, predicate: P) -> E
     where
-        P: Fn(SwarmEvent<<Self::NB as NetworkBehaviour>::ToSwarm>) -> Option<E>,
+        P: FnMut(SwarmEvent<<Self::NB as NetworkBehaviour>::ToSwarm>) -> Option<E>,
         P: Send;
 
     /// Listens for incoming connections, polling the [`Swarm`] until the transport is ready to accept connections.
@@ -287,9 +290,9 @@ where
         .await
     }
 
-    async fn wait<E, P>(&mut self, predicate: P) -> E
+    async fn wait<E, P>(&mut self, mut predicate: P) -> E
     where
-        P: Fn(SwarmEvent<<B as NetworkBehaviour>::ToSwarm>) -> Option<E>,
+        P: FnMut(SwarmEvent<<B as NetworkBehaviour>::ToSwarm>) -> Option<E>,
         P: Send,
     {
         loop {
@@ -422,3 +425,44 @@ where
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p_swarm::dummy;
+
+    #[async_std::test]
+    async fn wait_returns_value_extracted_from_event() {
+        let mut swarm = Swarm::new_ephemeral(|_| dummy::Behaviour);
+        swarm.listen_on(Protocol::Memory(0).into()).unwrap();
+
+        let address = swarm
+            .wait(|event| match event {
+                SwarmEvent::NewListenAddr { address, .. } => Some(address),
+                _ => None,
+            })
+            .await;
+
+        assert!(matches!(address.iter().next(), Some(Protocol::Memory(_))));
+        assert_eq!(swarm.listeners().collect::<Vec<_>>(), vec![&address]);
+    }
+
+    #[async_std::test]
+    async fn wait_accepts_stateful_predicate() {
+        let mut swarm = Swarm::new_ephemeral(|_| dummy::Behaviour);
+        swarm.listen_on(Protocol::Memory(0).into()).unwrap();
+        swarm.listen_on(Protocol::Memory(0).into()).unwrap();
+
+        let mut addresses = Vec::new();
+        swarm
+            .wait(|event| {
+                if let SwarmEvent::NewListenAddr { address, .. } = event {
+                    addresses.push(address);
+                }
+                (addresses.len() == 2).then_some(())
+            })
+            .await;
+
+        assert_eq!(addresses.len(), 2);
+    }
+}
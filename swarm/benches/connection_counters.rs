@@ -0,0 +1,258 @@
+//! Benchmarks of writing to a stream with and without the byte counters of
+//! `Config::with_connection_counters`, over the memory transport.
+//!
+//! Run against a saved baseline (`cargo bench -- --save-baseline <name>`) to compare changes.
+
+use async_std::task;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt, StreamExt};
+use libp2p_core::transport::MemoryTransport;
+use libp2p_core::upgrade::{ReadyUpgrade, Version};
+use libp2p_core::{multiaddr::Protocol, Endpoint, Multiaddr, Transport};
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_swarm::handler::{ConnectionEvent, FullyNegotiatedInbound, FullyNegotiatedOutbound};
+use libp2p_swarm::{
+    Config, ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, StreamProtocol, SubstreamProtocol, Swarm, SwarmEvent,
+    THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::io;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/payload");
+
+/// The number of bytes written per iteration, in chunks of [`CHUNK`] bytes.
+const PAYLOAD: usize = 1024 * 1024;
+const CHUNK: usize = 1024;
+
+fn stream_transfer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stream_transfer");
+    group.throughput(Throughput::Bytes(PAYLOAD as u64));
+
+    for (name, count_bytes) in [("uncounted", false), ("counted", true)] {
+        let (mut dialer, listener) = task::block_on(connected_swarms(count_bytes));
+        task::spawn(listener.loop_on_next());
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                dialer.behaviour_mut().to_send += 1;
+                task::block_on(dialer.next_behaviour_event())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// Creates two connected swarms, counting the bytes transferred over their connection if enabled.
+async fn connected_swarms(count_bytes: bool) -> (Swarm<Sender>, Swarm<Sender>) {
+    let mut dialer = new_swarm(count_bytes);
+    let mut listener = new_swarm(count_bytes);
+    listen_on_memory(&mut listener).await;
+
+    dialer.connect(&mut listener).await;
+    dialer.behaviour_mut().peer = Some(*listener.local_peer_id());
+
+    (dialer, listener)
+}
+
+fn new_swarm(count_bytes: bool) -> Swarm<Sender> {
+    let identity = Keypair::generate_ed25519();
+    let peer_id = identity.public().to_peer_id();
+
+    let transport = MemoryTransport::default()
+        .upgrade(Version::V1)
+        .authenticate(libp2p_plaintext::Config::new(&identity))
+        .multiplex(libp2p_yamux::Config::default())
+        .boxed();
+
+    Swarm::new(
+        transport,
+        Sender::default(),
+        peer_id,
+        Config::with_executor(|f| {
+            task::spawn(f);
+        })
+        .with_connection_counters(count_bytes)
+        .with_idle_connection_timeout(Duration::from_secs(60)),
+    )
+}
+
+/// Listens on a new memory address and adds it as external address.
+async fn listen_on_memory(swarm: &mut Swarm<Sender>) {
+    swarm.listen_on(Protocol::Memory(0).into()).unwrap();
+    let address = swarm
+        .wait(|event| match event {
+            SwarmEvent::NewListenAddr { address, .. } => Some(address),
+            _ => None,
+        })
+        .await;
+    swarm.add_external_address(address);
+}
+
+/// Sends [`PAYLOAD`] bytes over a new stream to `peer` for each requested transfer and reports
+/// each completed transfer.
+#[derive(Default)]
+struct Sender {
+    peer: Option<PeerId>,
+    /// The number of transfers still to be started.
+    to_send: usize,
+    /// The number of completed transfers not yet reported.
+    completed: usize,
+}
+
+impl NetworkBehaviour for Sender {
+    type ConnectionHandler = Handler;
+    type ToSwarm = ();
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::default())
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        _: THandlerOutEvent<Self>,
+    ) {
+        self.completed += 1;
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if self.completed > 0 {
+            self.completed -= 1;
+            return Poll::Ready(ToSwarm::GenerateEvent(()));
+        }
+
+        match self.peer {
+            Some(peer_id) if self.to_send > 0 => {
+                self.to_send -= 1;
+
+                Poll::Ready(ToSwarm::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::Any,
+                    event: (),
+                })
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
+
+/// Writes [`PAYLOAD`] bytes to a new outbound stream per event of the behaviour and reads all
+/// inbound streams to their end.
+#[derive(Default)]
+struct Handler {
+    /// The number of outbound streams still to be requested.
+    requested: usize,
+    transfers: FuturesUnordered<BoxFuture<'static, io::Result<()>>>,
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = ();
+    type ToBehaviour = ();
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ())
+    }
+
+    fn on_behaviour_event(&mut self, _: Self::FromBehaviour) {
+        self.requested += 1;
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if self.requested > 0 {
+            self.requested -= 1;
+
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ()),
+            });
+        }
+
+        if let Poll::Ready(Some(result)) = self.transfers.poll_next_unpin(cx) {
+            result.unwrap();
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(()));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol: mut stream,
+                ..
+            }) => self.transfers.push(
+                async move {
+                    let mut payload = Vec::with_capacity(PAYLOAD);
+                    stream.read_to_end(&mut payload).await?;
+
+                    Ok(())
+                }
+                .boxed(),
+            ),
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol: mut stream,
+                ..
+            }) => self.transfers.push(
+                async move {
+                    let chunk = [0; CHUNK];
+                    for _ in 0..PAYLOAD / CHUNK {
+                        stream.write_all(&chunk).await?;
+                    }
+                    stream.close().await?;
+
+                    Ok(())
+                }
+                .boxed(),
+            ),
+            _ => {}
+        }
+    }
+}
+
+criterion_group!(connection_counters, stream_transfer);
+criterion_main!(connection_counters);
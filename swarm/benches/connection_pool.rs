@@ -0,0 +1,231 @@
+//! Benchmarks of the connection pool with many connections to a single peer over the memory
+//! transport: delivering events via `NotifyHandler::Any` and closing all connections.
+//!
+//! Run against a saved baseline (`cargo bench -- --save-baseline <name>`) to compare changes.
+
+use async_std::task;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use futures::future::poll_fn;
+use futures::StreamExt;
+use libp2p_core::upgrade::DeniedUpgrade;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::handler::ConnectionEvent;
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, SubstreamProtocol, Swarm, SwarmEvent, THandler,
+    THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use void::Void;
+
+/// The numbers of connections to the remote peer to benchmark with.
+const CONNECTIONS: [usize; 3] = [10, 100, 1_000];
+
+/// The number of events delivered per iteration of the `notify_handler_any` benchmark.
+const EVENTS: usize = 1_000;
+
+fn notify_handler_any(c: &mut Criterion) {
+    let mut group = c.benchmark_group("notify_handler_any");
+
+    for connections in CONNECTIONS {
+        let (mut dialer, listener) = task::block_on(connected_swarms(connections));
+        task::spawn(listener.loop_on_next());
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(connections),
+            &connections,
+            |b, _| {
+                b.iter(|| {
+                    dialer.behaviour_mut().to_notify += EVENTS;
+                    task::block_on(poll_fn(|cx| {
+                        while let Poll::Ready(Some(_)) = dialer.poll_next_unpin(cx) {}
+
+                        if dialer.behaviour().to_notify == 0 && dialer.behaviour().pending == 0 {
+                            return Poll::Ready(());
+                        }
+
+                        Poll::Pending
+                    }))
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn connection_close(c: &mut Criterion) {
+    let mut group = c.benchmark_group("connection_close");
+    group.sample_size(10);
+
+    for connections in CONNECTIONS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(connections),
+            &connections,
+            |b, &connections| {
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+
+                    for _ in 0..iters {
+                        let (mut dialer, listener) = task::block_on(connected_swarms(connections));
+                        let peer = *listener.local_peer_id();
+                        task::spawn(listener.loop_on_next());
+
+                        let start = Instant::now();
+                        dialer.disconnect_peer_id(peer).unwrap();
+                        task::block_on(async {
+                            let mut closed = 0;
+                            while closed < connections {
+                                if let SwarmEvent::ConnectionClosed { .. } =
+                                    dialer.select_next_some().await
+                                {
+                                    closed += 1;
+                                }
+                            }
+                        });
+                        elapsed += start.elapsed();
+                    }
+
+                    elapsed
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Creates two swarms with the given number of connections between them.
+async fn connected_swarms(connections: usize) -> (Swarm<Notifier>, Swarm<Notifier>) {
+    let mut dialer = Swarm::new_ephemeral(|_| Notifier::default());
+    let mut listener = Swarm::new_ephemeral(|_| Notifier::default());
+    listener.listen().with_memory_addr_external().await;
+
+    for _ in 0..connections {
+        dialer.connect(&mut listener).await;
+    }
+    dialer.behaviour_mut().peer = Some(*listener.local_peer_id());
+
+    (dialer, listener)
+}
+
+/// Sends events to any connection of `peer` and counts the events reported back by the handlers.
+#[derive(Default)]
+struct Notifier {
+    peer: Option<PeerId>,
+    /// The number of events still to be sent.
+    to_notify: usize,
+    /// The number of events sent but not reported back yet.
+    pending: usize,
+}
+
+impl NetworkBehaviour for Notifier {
+    type ConnectionHandler = EchoHandler;
+    type ToSwarm = Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(EchoHandler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(EchoHandler::default())
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        _: THandlerOutEvent<Self>,
+    ) {
+        self.pending -= 1;
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.peer {
+            Some(peer_id) if self.to_notify > 0 => {
+                self.to_notify -= 1;
+                self.pending += 1;
+
+                Poll::Ready(ToSwarm::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::Any,
+                    event: (),
+                })
+            }
+            _ => Poll::Pending,
+        }
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+}
+
+/// Reports every event received from the behaviour back to it.
+#[derive(Default)]
+struct EchoHandler {
+    /// The number of events received but not reported back yet.
+    received: usize,
+}
+
+impl ConnectionHandler for EchoHandler {
+    type FromBehaviour = ();
+    type ToBehaviour = ();
+    type InboundProtocol = DeniedUpgrade;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = Void;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(DeniedUpgrade, ())
+    }
+
+    fn on_behaviour_event(&mut self, _: Self::FromBehaviour) {
+        self.received += 1;
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if self.received == 0 {
+            return Poll::Pending;
+        }
+        self.received -= 1;
+
+        Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(()))
+    }
+
+    fn on_connection_event(
+        &mut self,
+        _: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+    }
+}
+
+criterion_group!(connection_pool, notify_handler_any, connection_close);
+criterion_main!(connection_pool);
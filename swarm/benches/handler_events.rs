@@ -0,0 +1,203 @@
+//! Benchmarks of the rate at which events are exchanged between a `NetworkBehaviour` and the
+//! `ConnectionHandler` of a single connection over the memory transport.
+//!
+//! Run against a saved baseline (`cargo bench -- --save-baseline <name>`) to compare changes.
+
+use async_std::task;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use libp2p_core::transport::MemoryTransport;
+use libp2p_core::upgrade::{DeniedUpgrade, Version};
+use libp2p_core::{Endpoint, Multiaddr, Transport};
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_swarm::handler::ConnectionEvent;
+use libp2p_swarm::{
+    Config, ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, SubstreamProtocol, Swarm, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use void::Void;
+
+/// The number of events sent to the handler and back per iteration.
+const EVENTS: u64 = 10_000;
+
+fn echo(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handler_events");
+    group.throughput(Throughput::Elements(EVENTS));
+
+    let mut swarm = task::block_on(connected_swarm());
+
+    group.bench_function("echo", |b| {
+        b.iter(|| {
+            swarm.behaviour_mut().to_send = EVENTS;
+            task::block_on(swarm.next_behaviour_event())
+        })
+    });
+
+    group.finish();
+}
+
+/// Creates a [`Swarm`] connected to a second one, which is driven in the background.
+async fn connected_swarm() -> Swarm<Echo> {
+    let mut dialer = new_swarm();
+    let mut listener = new_swarm();
+    listener.listen().with_memory_addr_external().await;
+
+    dialer.connect(&mut listener).await;
+    task::spawn(listener.loop_on_next());
+
+    dialer
+}
+
+fn new_swarm() -> Swarm<Echo> {
+    let identity = Keypair::generate_ed25519();
+    let peer_id = identity.public().to_peer_id();
+
+    let transport = MemoryTransport::default()
+        .upgrade(Version::V1)
+        .authenticate(libp2p_plaintext::Config::new(&identity))
+        .multiplex(libp2p_yamux::Config::default())
+        .boxed();
+
+    Swarm::new(
+        transport,
+        Echo::default(),
+        peer_id,
+        Config::with_executor(|f| {
+            task::spawn(f);
+        })
+        .with_idle_connection_timeout(Duration::from_secs(60)),
+    )
+}
+
+/// Sends `to_send` events to the handler of the first established connection and reports once all
+/// of them were sent back.
+#[derive(Default)]
+struct Echo {
+    connection: Option<(PeerId, ConnectionId)>,
+    to_send: u64,
+    /// The number of events sent to the handler and not yet sent back.
+    in_flight: u64,
+    completed: bool,
+}
+
+impl NetworkBehaviour for Echo {
+    type ConnectionHandler = EchoHandler;
+    type ToSwarm = ();
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(EchoHandler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(EchoHandler::default())
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ConnectionEstablished(e) = event {
+            self.connection.get_or_insert((e.peer_id, e.connection_id));
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        _: THandlerOutEvent<Self>,
+    ) {
+        self.in_flight -= 1;
+        if self.in_flight == 0 && self.to_send == 0 {
+            self.completed = true;
+        }
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if std::mem::take(&mut self.completed) {
+            return Poll::Ready(ToSwarm::GenerateEvent(()));
+        }
+
+        match self.connection {
+            Some((peer_id, connection)) if self.to_send > 0 => {
+                self.to_send -= 1;
+                self.in_flight += 1;
+
+                Poll::Ready(ToSwarm::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::One(connection),
+                    event: (),
+                })
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
+
+/// Sends each event of the behaviour back to it.
+#[derive(Default)]
+struct EchoHandler {
+    /// The number of received events not yet sent back.
+    received: usize,
+}
+
+impl ConnectionHandler for EchoHandler {
+    type FromBehaviour = ();
+    type ToBehaviour = ();
+    type InboundProtocol = DeniedUpgrade;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = Void;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(DeniedUpgrade, ())
+    }
+
+    fn on_behaviour_event(&mut self, _: Self::FromBehaviour) {
+        self.received += 1;
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if self.received > 0 {
+            self.received -= 1;
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(()));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_connection_event(
+        &mut self,
+        _: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+    }
+}
+
+criterion_group!(handler_events, echo);
+criterion_main!(handler_events);
@@ -18,26 +18,36 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+pub mod address_book;
 mod either;
 mod external_addresses;
+#[cfg(any(test, feature = "testing"))]
+mod from_fn;
 mod listen_addresses;
 mod peer_addresses;
+mod poll_rotation;
 pub mod toggle;
 
 pub use external_addresses::ExternalAddresses;
+#[cfg(any(test, feature = "testing"))]
+pub use from_fn::{from_fn, FromFn};
 pub use listen_addresses::ListenAddresses;
 pub use peer_addresses::PeerAddresses;
+pub use poll_rotation::PollRotation;
 
 use crate::connection::ConnectionId;
 use crate::dial_opts::DialOpts;
 use crate::listen_opts::ListenOpts;
 use crate::{
-    ConnectionDenied, ConnectionHandler, DialError, ListenError, THandler, THandlerInEvent,
-    THandlerOutEvent,
+    ConnectionDenied, ConnectionHandler, DialError, ListenDenied, ListenError, ListenProtocol,
+    StreamProtocol, THandler, THandlerInEvent, THandlerOutEvent,
+};
+use libp2p_core::{
+    transport::{ListenerId, TransportError},
+    ConnectedPoint, Endpoint, Multiaddr,
 };
-use libp2p_core::{transport::ListenerId, ConnectedPoint, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
-use std::{task::Context, task::Poll};
+use std::{any::Any, fmt, io, task::Context, task::Poll};
 
 /// A [`NetworkBehaviour`] defines the behaviour of the local node on the network.
 ///
@@ -119,6 +129,43 @@ use std::{task::Context, task::Poll};
 ///   }
 /// }
 /// ```
+///
+/// When the derive macro generates the event `enum`, `#[behaviour(event_accessors)]` additionally
+/// generates an `as_<field>` accessor for each `struct` member as well as an `into_<field>` method
+/// converting the event `enum` into the event type of the member, returning the `enum` otherwise.
+///
+/// ``` rust
+/// # use libp2p_identify as identify;
+/// # use libp2p_ping as ping;
+/// # use libp2p_swarm_derive::NetworkBehaviour;
+/// #[derive(NetworkBehaviour)]
+/// #[behaviour(event_accessors)]
+/// # #[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+/// struct MyBehaviour {
+///   identify: identify::Behaviour,
+///   ping: ping::Behaviour,
+/// }
+///
+/// fn on_event(event: MyBehaviourEvent) {
+///   if let Some(ping::Event { peer, .. }) = event.as_ping() {
+///     println!("Pinged {peer}");
+///   }
+///
+///   if let Ok(identify::Event::Received { peer_id, .. }) = event.into_identify() {
+///     println!("Identified {peer_id}");
+///   }
+/// }
+/// ```
+///
+/// The generated [`NetworkBehaviour::poll_with_rotation`] polls the `struct` members in turn until
+/// one of them returns an event. By default, each call starts with the member following the one
+/// polled first on the previous call, such that a member that always has events to report cannot
+/// starve the ones declared after it. The member to start with is tracked per instance in the
+/// [`PollRotation`] kept by the [`Swarm`](crate::Swarm). The generated
+/// [`NetworkBehaviour::poll`] lacks this state and thus always starts with the first member.
+/// `#[behaviour(poll_order = "sequential")]` always starts with the first member, in declaration
+/// order, like previous versions did. In debug builds, a warning is logged if a member returns
+/// 1000 events in a row before all other members were polled.
 pub trait NetworkBehaviour: 'static {
     /// Handler for all the protocols the network behaviour supports.
     type ConnectionHandler: ConnectionHandler;
@@ -178,6 +225,25 @@ pub trait NetworkBehaviour: 'static {
         Ok(vec![])
     }
 
+    /// Callback that is invoked with the addresses about to be dialed for a known peer.
+    ///
+    /// The addresses include those passed to [`DialOpts`] and those returned from
+    /// [`NetworkBehaviour::handle_pending_outbound_connection`]. Reordering them lets a behaviour
+    /// move addresses that are likely to succeed, e.g. local ones, to the front.
+    ///
+    /// The order is still subject to the [`AddressOrder`](crate::dial_opts::AddressOrder) of the
+    /// dial, which is applied afterwards.
+    fn sort_addresses(&self, _peer: &PeerId, _addrs: &mut Vec<Multiaddr>) {}
+
+    /// Returns the protocols the behaviour supports on inbound streams, independent of any
+    /// connection, e.g. to list the protocols the local node speaks at startup.
+    ///
+    /// Aggregated over all behaviours by [`Swarm::supported_protocols`](crate::Swarm::supported_protocols).
+    /// Returns none by default.
+    fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        Vec::new()
+    }
+
     /// Callback that is invoked for every established outbound connection.
     ///
     /// This is invoked once we have successfully dialed a peer.
@@ -192,6 +258,95 @@ pub trait NetworkBehaviour: 'static {
         role_override: Endpoint,
     ) -> Result<THandler<Self>, ConnectionDenied>;
 
+    /// As [`NetworkBehaviour::handle_pending_inbound_connection`], but may return a
+    /// [`ConnectionPayload`] the [`Swarm`](crate::Swarm) hands back to
+    /// [`NetworkBehaviour::handle_established_inbound_connection_with_payload`] of the same
+    /// connection, e.g. the result of an expensive lookup that would otherwise be repeated.
+    ///
+    /// The payload is dropped if the connection fails before being established. Defaults to
+    /// calling [`NetworkBehaviour::handle_pending_inbound_connection`] without a payload.
+    fn handle_pending_inbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<Option<ConnectionPayload>, ConnectionDenied> {
+        self.handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
+
+        Ok(None)
+    }
+
+    /// As [`NetworkBehaviour::handle_established_inbound_connection`], additionally passing the
+    /// payload returned from [`NetworkBehaviour::handle_pending_inbound_connection_with_payload`]
+    /// for this connection, if any.
+    ///
+    /// Defaults to calling [`NetworkBehaviour::handle_established_inbound_connection`], dropping
+    /// the payload.
+    fn handle_established_inbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+        _payload: Option<ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.handle_established_inbound_connection(connection_id, peer, local_addr, remote_addr)
+    }
+
+    /// As [`NetworkBehaviour::handle_pending_outbound_connection`], but may additionally return a
+    /// [`ConnectionPayload`] the [`Swarm`](crate::Swarm) hands back to
+    /// [`NetworkBehaviour::handle_established_outbound_connection_with_payload`] of the same
+    /// connection.
+    ///
+    /// The payload is dropped if the connection fails before being established. Defaults to
+    /// calling [`NetworkBehaviour::handle_pending_outbound_connection`] without a payload.
+    fn handle_pending_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        maybe_peer: Option<PeerId>,
+        addresses: &[Multiaddr],
+        effective_role: Endpoint,
+    ) -> Result<(Vec<Multiaddr>, Option<ConnectionPayload>), ConnectionDenied> {
+        let addresses = self.handle_pending_outbound_connection(
+            connection_id,
+            maybe_peer,
+            addresses,
+            effective_role,
+        )?;
+
+        Ok((addresses, None))
+    }
+
+    /// As [`NetworkBehaviour::handle_established_outbound_connection`], additionally passing the
+    /// payload returned from [`NetworkBehaviour::handle_pending_outbound_connection_with_payload`]
+    /// for this connection, if any.
+    ///
+    /// Defaults to calling [`NetworkBehaviour::handle_established_outbound_connection`], dropping
+    /// the payload.
+    fn handle_established_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+        _payload: Option<ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.handle_established_outbound_connection(connection_id, peer, addr, role_override)
+    }
+
+    /// Callback that is invoked for every address a listener is about to report.
+    ///
+    /// Any error returned from this function suppresses the address: it is neither reported via
+    /// [`FromSwarm::NewListenAddr`] nor via [`SwarmEvent::NewListenAddr`](crate::SwarmEvent::NewListenAddr),
+    /// nor listed by [`Swarm::listeners`](crate::Swarm::listeners). The listener itself is kept.
+    fn handle_pending_listen(
+        &mut self,
+        _listener_id: ListenerId,
+        _addr: &Multiaddr,
+    ) -> Result<(), ListenDenied> {
+        Ok(())
+    }
+
     /// Informs the behaviour about an event from the [`Swarm`](crate::Swarm).
     fn on_swarm_event(&mut self, event: FromSwarm);
 
@@ -213,8 +368,31 @@ pub trait NetworkBehaviour: 'static {
     /// order to wake it up at a later point in time.
     fn poll(&mut self, cx: &mut Context<'_>)
         -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>>;
+
+    /// Polls for things that swarm should do, like [`NetworkBehaviour::poll`], given the
+    /// [`PollRotation`] of this behaviour.
+    ///
+    /// The [`Swarm`](crate::Swarm) calls this instead of [`NetworkBehaviour::poll`]. It is
+    /// implemented by `#[derive(NetworkBehaviour)]` to poll the `struct` members round-robin.
+    /// Behaviours wrapping another behaviour should forward it. Defaults to
+    /// [`NetworkBehaviour::poll`].
+    fn poll_with_rotation(
+        &mut self,
+        cx: &mut Context<'_>,
+        rotation: &mut PollRotation,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        let _ = rotation;
+        self.poll(cx)
+    }
 }
 
+/// Data a [`NetworkBehaviour`] attaches to a pending connection, handed back once the connection
+/// is established.
+///
+/// See [`NetworkBehaviour::handle_pending_inbound_connection_with_payload`] and
+/// [`NetworkBehaviour::handle_pending_outbound_connection_with_payload`].
+pub type ConnectionPayload = Box<dyn Any + Send>;
+
 /// A command issued from a [`NetworkBehaviour`] for the [`Swarm`].
 ///
 /// [`Swarm`]: super::Swarm
@@ -240,6 +418,14 @@ pub enum ToSwarm<TOutEvent, TInEvent> {
     /// Instructs the [`Swarm`](crate::Swarm) to remove the listener.
     RemoveListener { id: ListenerId },
 
+    /// Instructs the [`Swarm`](crate::Swarm) to refuse new inbound connections on the listener,
+    /// see [`Swarm::pause_listener`](crate::Swarm::pause_listener).
+    PauseListener { id: ListenerId },
+
+    /// Instructs the [`Swarm`](crate::Swarm) to accept new inbound connections on a paused
+    /// listener again, see [`Swarm::resume_listener`](crate::Swarm::resume_listener).
+    ResumeListener { id: ListenerId },
+
     /// Instructs the `Swarm` to send an event to the handler dedicated to a
     /// connection with a peer.
     ///
@@ -278,6 +464,12 @@ pub enum ToSwarm<TOutEvent, TInEvent> {
     /// - A protocol such as identify obtained it from a remote.
     /// - The user provided it based on configuration.
     /// - We made an educated guess based on one of our listen addresses.
+    ///
+    /// If a TTL is configured via [`Config::with_candidate_ttl`](crate::Config::with_candidate_ttl),
+    /// a candidate that is neither confirmed via [`ToSwarm::ExternalAddrConfirmed`] nor reported
+    /// again within the TTL is dropped and reported via [`FromSwarm::ExternalAddrCandidateExpired`].
+    /// Unlike [`ExternalAddresses`], which only tracks confirmed addresses, this allows behaviours
+    /// to conclude that candidates keep failing to be confirmed.
     NewExternalAddrCandidate(Multiaddr),
 
     /// Indicates to the [`Swarm`](crate::Swarm) that the provided address is confirmed to be externally reachable.
@@ -308,6 +500,21 @@ pub enum ToSwarm<TOutEvent, TInEvent> {
 
     /// Reports external address of a remote peer to the [`Swarm`](crate::Swarm) and through that to other [`NetworkBehaviour`]s.
     NewExternalAddrOfPeer { peer_id: PeerId, address: Multiaddr },
+
+    /// Instructs the [`Swarm`](crate::Swarm) to attempt to upgrade a relayed connection to a direct connection.
+    ///
+    /// The request is shared with all [`NetworkBehaviour`]s via [`FromSwarm::UpgradeToDirect`],
+    /// upon which a behaviour implementing the upgrade, e.g. DCUtR, attempts to establish a
+    /// direct connection to the peer. Once a direct connection to the peer is established while
+    /// the relayed connection is still open, the [`Swarm`](crate::Swarm) reports
+    /// [`FromSwarm::DirectConnectionUpgraded`].
+    ///
+    /// The relayed connection is not closed by the [`Swarm`](crate::Swarm).
+    /// The request is ignored if the connection is not an established relayed connection.
+    UpgradeToDirect {
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+    },
 }
 
 impl<TOutEvent, TInEventOld> ToSwarm<TOutEvent, TInEventOld> {
@@ -321,6 +528,8 @@ impl<TOutEvent, TInEventOld> ToSwarm<TOutEvent, TInEventOld> {
             ToSwarm::Dial { opts } => ToSwarm::Dial { opts },
             ToSwarm::ListenOn { opts } => ToSwarm::ListenOn { opts },
             ToSwarm::RemoveListener { id } => ToSwarm::RemoveListener { id },
+            ToSwarm::PauseListener { id } => ToSwarm::PauseListener { id },
+            ToSwarm::ResumeListener { id } => ToSwarm::ResumeListener { id },
             ToSwarm::NotifyHandler {
                 peer_id,
                 handler,
@@ -347,6 +556,13 @@ impl<TOutEvent, TInEventOld> ToSwarm<TOutEvent, TInEventOld> {
                 address: addr,
                 peer_id,
             },
+            ToSwarm::UpgradeToDirect {
+                peer_id,
+                connection_id,
+            } => ToSwarm::UpgradeToDirect {
+                peer_id,
+                connection_id,
+            },
         }
     }
 }
@@ -359,6 +575,8 @@ impl<TOutEvent, THandlerIn> ToSwarm<TOutEvent, THandlerIn> {
             ToSwarm::Dial { opts } => ToSwarm::Dial { opts },
             ToSwarm::ListenOn { opts } => ToSwarm::ListenOn { opts },
             ToSwarm::RemoveListener { id } => ToSwarm::RemoveListener { id },
+            ToSwarm::PauseListener { id } => ToSwarm::PauseListener { id },
+            ToSwarm::ResumeListener { id } => ToSwarm::ResumeListener { id },
             ToSwarm::NotifyHandler {
                 peer_id,
                 handler,
@@ -385,6 +603,13 @@ impl<TOutEvent, THandlerIn> ToSwarm<TOutEvent, THandlerIn> {
                 address: addr,
                 peer_id,
             },
+            ToSwarm::UpgradeToDirect {
+                peer_id,
+                connection_id,
+            } => ToSwarm::UpgradeToDirect {
+                peer_id,
+                connection_id,
+            },
         }
     }
 }
@@ -395,6 +620,9 @@ pub enum NotifyHandler {
     /// Notify a particular connection handler.
     One(ConnectionId),
     /// Notify an arbitrary connection handler.
+    ///
+    /// Connections with a higher priority are preferred, i.e. direct over relayed connections by
+    /// default, see [`Swarm::set_connection_priority`](crate::Swarm::set_connection_priority).
     Any,
 }
 
@@ -421,12 +649,44 @@ pub enum FromSwarm<'a> {
     /// [`FromSwarm::ConnectionEstablished`] with the same peer ID, connection ID
     /// and endpoint.
     ConnectionClosed(ConnectionClosed<'a>),
+    /// Informs the behaviour whether a closed connection shut down gracefully.
+    ///
+    /// This event always directly follows the [`FromSwarm::ConnectionClosed`] of the same
+    /// connection.
+    ConnectionCloseCompleted(ConnectionCloseCompleted),
+    /// Informs the behaviour that a connection closed without any substream having been
+    /// negotiated on it, after at least one negotiation failed, e.g. because the peers do not
+    /// share a protocol.
+    ///
+    /// This event is emitted regardless of whether the connection was reported via
+    /// [`FromSwarm::ConnectionEstablished`] and precedes its [`FromSwarm::ConnectionClosed`], if any.
+    NegotiationFailed(NegotiationFailed<'a>),
     /// Informs the behaviour that the [`ConnectedPoint`] of an existing
     /// connection has changed.
     AddressChange(AddressChange<'a>),
+    /// Informs the behaviour that an upgrade of a relayed connection to a direct connection was
+    /// requested via [`ToSwarm::UpgradeToDirect`].
+    UpgradeToDirect(UpgradeToDirect<'a>),
+    /// Informs the behaviour that a direct connection was established to a peer whose relayed
+    /// connection was requested to be upgraded via [`ToSwarm::UpgradeToDirect`].
+    DirectConnectionUpgraded(DirectConnectionUpgraded<'a>),
     /// Informs the behaviour that the dial to a known
     /// or unknown node failed.
     DialFailure(DialFailure<'a>),
+    /// Informs the behaviour that dialing one of the addresses of a pending outbound connection
+    /// failed, while other addresses may still be tried.
+    ///
+    /// Only reported if enabled via
+    /// [`Config::with_dial_attempt_failure_events`](crate::Config::with_dial_attempt_failure_events).
+    /// It precedes the [`FromSwarm::ConnectionEstablished`] or [`FromSwarm::DialFailure`] of the
+    /// same connection.
+    DialAttemptFailed(DialAttemptFailed<'a>),
+    /// Informs the behaviour that a connection was refused because a limit on the number of
+    /// connections was reached, see [`ConnectionDenied::limit_reached`](crate::ConnectionDenied::limit_reached).
+    ///
+    /// This event precedes the [`FromSwarm::DialFailure`] or [`FromSwarm::ListenFailure`] of the
+    /// refused connection.
+    ConnectionLimitReached(ConnectionLimitReached),
     /// Informs the behaviour that an error
     /// happened on an incoming connection during its initial handshake.
     ///
@@ -445,8 +705,16 @@ pub enum FromSwarm<'a> {
     ListenerError(ListenerError<'a>),
     /// Informs the behaviour that a listener closed.
     ListenerClosed(ListenerClosed<'a>),
+    /// Informs the behaviour that the set of [`ListenProtocol`]s of all listen addresses changed.
+    ///
+    /// This event follows the [`FromSwarm::NewListenAddr`] or [`FromSwarm::ExpiredListenAddr`]
+    /// that caused the change.
+    ListenProtocolsChanged(ListenProtocolsChanged<'a>),
     /// Informs the behaviour that we have discovered a new candidate for an external address for us.
     NewExternalAddrCandidate(NewExternalAddrCandidate<'a>),
+    /// Informs the behaviour that a candidate for an external address was not confirmed within
+    /// the TTL configured via [`Config::with_candidate_ttl`](crate::Config::with_candidate_ttl).
+    ExternalAddrCandidateExpired(ExternalAddrCandidateExpired<'a>),
     /// Informs the behaviour that an external address of the local node was confirmed.
     ExternalAddrConfirmed(ExternalAddrConfirmed<'a>),
     /// Informs the behaviour that an external address of the local node expired, i.e. is no-longer confirmed.
@@ -478,6 +746,69 @@ pub struct ConnectionClosed<'a> {
     pub remaining_established: usize,
 }
 
+/// [`FromSwarm`] variant that informs the behaviour that a connection was refused because a limit
+/// on the number of connections was reached.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionLimitReached {
+    pub connection_id: ConnectionId,
+    /// The kind of connections the limit applies to.
+    pub kind: LimitKind,
+    /// The number of connections of this kind at the time the connection was refused.
+    pub current: u32,
+    pub limit: u32,
+}
+
+/// The kind of connections a limit on the number of connections applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LimitKind {
+    PendingIncoming,
+    PendingOutgoing,
+    EstablishedIncoming,
+    EstablishedOutgoing,
+    EstablishedPerPeer,
+    EstablishedTotal,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKind::PendingIncoming => write!(f, "pending incoming connections"),
+            LimitKind::PendingOutgoing => write!(f, "pending outgoing connections"),
+            LimitKind::EstablishedIncoming => write!(f, "established incoming connections"),
+            LimitKind::EstablishedOutgoing => write!(f, "established outgoing connections"),
+            LimitKind::EstablishedPerPeer => write!(f, "established connections per peer"),
+            LimitKind::EstablishedTotal => write!(f, "established connections"),
+        }
+    }
+}
+
+/// [`FromSwarm`] variant that informs the behaviour whether a closed connection shut down
+/// gracefully.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionCloseCompleted {
+    pub peer_id: PeerId,
+    pub connection_id: ConnectionId,
+    /// `true` if the connection was actively closed, e.g. via [`ToSwarm::CloseConnection`], and
+    /// both [`ConnectionHandler::poll_close`](crate::ConnectionHandler::poll_close) and the
+    /// closing of the muxer ran to completion, i.e. all buffered data was flushed.
+    ///
+    /// `false` if the connection was dropped abruptly, e.g. because the remote closed it or
+    /// the transport failed.
+    pub graceful: bool,
+}
+
+/// [`FromSwarm`] variant that informs the behaviour that no protocol could be negotiated on a
+/// closed connection.
+#[derive(Debug, Clone, Copy)]
+pub struct NegotiationFailed<'a> {
+    pub peer_id: PeerId,
+    pub connection_id: ConnectionId,
+    /// The protocols offered on the outbound and supported on the inbound substreams that failed
+    /// to negotiate, without duplicates.
+    pub protocols_tried: &'a [String],
+}
+
 /// [`FromSwarm`] variant that informs the behaviour that the [`ConnectedPoint`] of an existing
 /// connection has changed.
 #[derive(Debug, Clone, Copy)]
@@ -488,6 +819,30 @@ pub struct AddressChange<'a> {
     pub new: &'a ConnectedPoint,
 }
 
+/// [`FromSwarm`] variant that informs the behaviour that an upgrade of a relayed connection to a
+/// direct connection was requested.
+#[derive(Debug, Clone, Copy)]
+pub struct UpgradeToDirect<'a> {
+    pub peer_id: PeerId,
+    /// The relayed connection to upgrade.
+    pub connection_id: ConnectionId,
+    pub endpoint: &'a ConnectedPoint,
+}
+
+/// [`FromSwarm`] variant that informs the behaviour that a direct connection was established to a
+/// peer whose relayed connection was requested to be upgraded.
+///
+/// Both connections remain open, the direct one is reported separately via
+/// [`FromSwarm::ConnectionEstablished`].
+#[derive(Debug, Clone, Copy)]
+pub struct DirectConnectionUpgraded<'a> {
+    pub peer_id: PeerId,
+    pub relayed_connection_id: ConnectionId,
+    pub relayed_endpoint: &'a ConnectedPoint,
+    pub direct_connection_id: ConnectionId,
+    pub direct_endpoint: &'a ConnectedPoint,
+}
+
 /// [`FromSwarm`] variant that informs the behaviour that the dial to a known
 /// or unknown node failed.
 #[derive(Debug, Clone, Copy)]
@@ -497,6 +852,18 @@ pub struct DialFailure<'a> {
     pub connection_id: ConnectionId,
 }
 
+/// [`FromSwarm`] variant that informs the behaviour that dialing one of the addresses of a
+/// pending outbound connection failed.
+#[derive(Debug, Clone, Copy)]
+pub struct DialAttemptFailed<'a> {
+    pub peer_id: Option<PeerId>,
+    pub connection_id: ConnectionId,
+    pub address: &'a Multiaddr,
+    pub error: &'a TransportError<io::Error>,
+    /// Number of addresses of the connection still being dialed or yet to be dialed.
+    pub remaining_addresses: usize,
+}
+
 /// [`FromSwarm`] variant that informs the behaviour that an error
 /// happened on an incoming connection during its initial handshake.
 ///
@@ -547,12 +914,27 @@ pub struct ListenerClosed<'a> {
     pub reason: Result<(), &'a std::io::Error>,
 }
 
+/// [`FromSwarm`] variant that informs the behaviour that the set of [`ListenProtocol`]s of all
+/// listen addresses changed.
+#[derive(Debug, Clone, Copy)]
+pub struct ListenProtocolsChanged<'a> {
+    /// The protocols the local node is listening on, sorted and without duplicates.
+    pub protocols: &'a [ListenProtocol],
+}
+
 /// [`FromSwarm`] variant that informs the behaviour about a new candidate for an external address for us.
 #[derive(Debug, Clone, Copy)]
 pub struct NewExternalAddrCandidate<'a> {
     pub addr: &'a Multiaddr,
 }
 
+/// [`FromSwarm`] variant that informs the behaviour that a candidate for an external address
+/// expired without being confirmed.
+#[derive(Debug, Clone, Copy)]
+pub struct ExternalAddrCandidateExpired<'a> {
+    pub addr: &'a Multiaddr,
+}
+
 /// [`FromSwarm`] variant that informs the behaviour that an external address was confirmed.
 #[derive(Debug, Clone, Copy)]
 pub struct ExternalAddrConfirmed<'a> {
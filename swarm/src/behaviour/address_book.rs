@@ -0,0 +1,172 @@
+//! A [`NetworkBehaviour`] supplying known addresses of peers dialed by [`PeerId`] only.
+
+use crate::behaviour::{FromSwarm, NetworkBehaviour, ToSwarm};
+use crate::connection::ConnectionId;
+use crate::{dummy, ConnectionDenied, THandler, THandlerInEvent, THandlerOutEvent};
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use std::collections::HashMap;
+use std::task::{Context, Poll};
+use void::Void;
+
+/// [`NetworkBehaviour`] storing addresses of peers, e.g. persisted before a restart, and returning
+/// them from [`NetworkBehaviour::handle_pending_outbound_connection`].
+///
+/// Dialing a peer via [`DialOpts::peer_id`](crate::dial_opts::DialOpts::peer_id) without
+/// addresses thus dials the stored addresses of it. Addresses are only added and removed
+/// explicitly, i.e. they are not learned from or removed upon connections.
+///
+/// ```
+/// # use libp2p_identity::PeerId;
+/// # use libp2p_swarm::behaviour::address_book::AddressBook;
+/// let peer = PeerId::random();
+/// let address_book = AddressBook::from_iter([(peer, vec!["/memory/1234".parse().unwrap()])]);
+/// ```
+#[derive(Debug, Default)]
+pub struct AddressBook {
+    addresses: HashMap<PeerId, Vec<Multiaddr>>,
+}
+
+impl AddressBook {
+    /// Creates an empty [`AddressBook`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an address of the given peer.
+    ///
+    /// Returns `false` if the address was already stored.
+    pub fn add_address(&mut self, peer: PeerId, address: Multiaddr) -> bool {
+        let addresses = self.addresses.entry(peer).or_default();
+        if addresses.contains(&address) {
+            return false;
+        }
+        addresses.push(address);
+
+        true
+    }
+
+    /// Removes an address of the given peer.
+    ///
+    /// Returns `false` if the address was not stored.
+    pub fn remove_address(&mut self, peer: &PeerId, address: &Multiaddr) -> bool {
+        let Some(addresses) = self.addresses.get_mut(peer) else {
+            return false;
+        };
+        let Some(index) = addresses.iter().position(|a| a == address) else {
+            return false;
+        };
+        addresses.remove(index);
+        if addresses.is_empty() {
+            self.addresses.remove(peer);
+        }
+
+        true
+    }
+
+    /// Returns the stored addresses of the given peer, in the order they were added.
+    pub fn addresses_of_peer(&self, peer: &PeerId) -> &[Multiaddr] {
+        self.addresses
+            .get(peer)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl FromIterator<(PeerId, Vec<Multiaddr>)> for AddressBook {
+    fn from_iter<T: IntoIterator<Item = (PeerId, Vec<Multiaddr>)>>(iter: T) -> Self {
+        let mut address_book = Self::new();
+        for (peer, addresses) in iter {
+            for address in addresses {
+                address_book.add_address(peer, address);
+            }
+        }
+
+        address_book
+    }
+}
+
+impl NetworkBehaviour for AddressBook {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = Void;
+
+    fn handle_pending_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        maybe_peer: Option<PeerId>,
+        _: &[Multiaddr],
+        _: Endpoint,
+    ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
+        let Some(peer) = maybe_peer else {
+            return Ok(vec![]);
+        };
+
+        Ok(self.addresses_of_peer(&peer).to_vec())
+    }
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_remove_addresses() {
+        let peer = PeerId::random();
+        let a: Multiaddr = "/memory/1".parse().unwrap();
+        let b: Multiaddr = "/memory/2".parse().unwrap();
+        let mut address_book = AddressBook::from_iter([(peer, vec![a.clone()])]);
+
+        assert!(!address_book.add_address(peer, a.clone()));
+        assert!(address_book.add_address(peer, b.clone()));
+        assert_eq!(
+            address_book.addresses_of_peer(&peer),
+            [a.clone(), b.clone()]
+        );
+
+        assert!(address_book.remove_address(&peer, &a));
+        assert!(!address_book.remove_address(&peer, &a));
+        assert_eq!(
+            address_book.addresses_of_peer(&peer),
+            std::slice::from_ref(&b)
+        );
+
+        assert!(address_book.remove_address(&peer, &b));
+        assert!(address_book.addresses_of_peer(&peer).is_empty());
+        assert!(address_book.addresses.is_empty());
+    }
+}
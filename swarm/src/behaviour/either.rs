@@ -18,11 +18,13 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::behaviour::{self, NetworkBehaviour, ToSwarm};
+use crate::behaviour::{self, NetworkBehaviour, PollRotation, ToSwarm};
 use crate::connection::ConnectionId;
-use crate::{ConnectionDenied, THandler, THandlerInEvent, THandlerOutEvent};
+use crate::{
+    ConnectionDenied, ListenDenied, StreamProtocol, THandler, THandlerInEvent, THandlerOutEvent,
+};
 use either::Either;
-use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_core::{transport::ListenerId, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use std::{task::Context, task::Poll};
 
@@ -122,6 +124,134 @@ where
         Ok(handler)
     }
 
+    fn handle_pending_inbound_connection_with_payload(
+        &mut self,
+        id: ConnectionId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<Option<behaviour::ConnectionPayload>, ConnectionDenied> {
+        match self {
+            Either::Left(a) => {
+                a.handle_pending_inbound_connection_with_payload(id, local_addr, remote_addr)
+            }
+            Either::Right(b) => {
+                b.handle_pending_inbound_connection_with_payload(id, local_addr, remote_addr)
+            }
+        }
+    }
+
+    fn handle_established_inbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+        payload: Option<behaviour::ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        let handler = match self {
+            Either::Left(inner) => {
+                Either::Left(inner.handle_established_inbound_connection_with_payload(
+                    connection_id,
+                    peer,
+                    local_addr,
+                    remote_addr,
+                    payload,
+                )?)
+            }
+            Either::Right(inner) => {
+                Either::Right(inner.handle_established_inbound_connection_with_payload(
+                    connection_id,
+                    peer,
+                    local_addr,
+                    remote_addr,
+                    payload,
+                )?)
+            }
+        };
+
+        Ok(handler)
+    }
+
+    fn handle_pending_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        maybe_peer: Option<PeerId>,
+        addresses: &[Multiaddr],
+        effective_role: Endpoint,
+    ) -> Result<(Vec<Multiaddr>, Option<behaviour::ConnectionPayload>), ConnectionDenied> {
+        match self {
+            Either::Left(inner) => inner.handle_pending_outbound_connection_with_payload(
+                connection_id,
+                maybe_peer,
+                addresses,
+                effective_role,
+            ),
+            Either::Right(inner) => inner.handle_pending_outbound_connection_with_payload(
+                connection_id,
+                maybe_peer,
+                addresses,
+                effective_role,
+            ),
+        }
+    }
+
+    fn handle_established_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+        payload: Option<behaviour::ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        let handler = match self {
+            Either::Left(inner) => {
+                Either::Left(inner.handle_established_outbound_connection_with_payload(
+                    connection_id,
+                    peer,
+                    addr,
+                    role_override,
+                    payload,
+                )?)
+            }
+            Either::Right(inner) => {
+                Either::Right(inner.handle_established_outbound_connection_with_payload(
+                    connection_id,
+                    peer,
+                    addr,
+                    role_override,
+                    payload,
+                )?)
+            }
+        };
+
+        Ok(handler)
+    }
+
+    fn sort_addresses(&self, peer: &PeerId, addrs: &mut Vec<Multiaddr>) {
+        match self {
+            Either::Left(a) => a.sort_addresses(peer, addrs),
+            Either::Right(b) => b.sort_addresses(peer, addrs),
+        }
+    }
+
+    fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        match self {
+            Either::Left(a) => a.supported_protocols(),
+            Either::Right(b) => b.supported_protocols(),
+        }
+    }
+
+    fn handle_pending_listen(
+        &mut self,
+        listener_id: ListenerId,
+        addr: &Multiaddr,
+    ) -> Result<(), ListenDenied> {
+        match self {
+            Either::Left(a) => a.handle_pending_listen(listener_id, addr),
+            Either::Right(b) => b.handle_pending_listen(listener_id, addr),
+        }
+    }
+
     fn on_swarm_event(&mut self, event: behaviour::FromSwarm) {
         match self {
             Either::Left(b) => b.on_swarm_event(event),
@@ -161,4 +291,25 @@ where
 
         Poll::Ready(event)
     }
+
+    fn poll_with_rotation(
+        &mut self,
+        cx: &mut Context<'_>,
+        rotation: &mut PollRotation,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        let event = match self {
+            Either::Left(behaviour) => {
+                futures::ready!(behaviour.poll_with_rotation(cx, rotation.member(0)))
+                    .map_out(Either::Left)
+                    .map_in(Either::Left)
+            }
+            Either::Right(behaviour) => {
+                futures::ready!(behaviour.poll_with_rotation(cx, rotation.member(1)))
+                    .map_out(Either::Right)
+                    .map_in(Either::Right)
+            }
+        };
+
+        Poll::Ready(event)
+    }
 }
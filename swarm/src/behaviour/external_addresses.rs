@@ -1,5 +1,7 @@
 use crate::behaviour::{ExternalAddrConfirmed, ExternalAddrExpired, FromSwarm};
 use libp2p_core::Multiaddr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// The maximum number of local external addresses. When reached any
 /// further externally reported addresses are ignored. The behaviour always
@@ -8,11 +10,36 @@ const MAX_LOCAL_EXTERNAL_ADDRS: usize = 20;
 
 /// Utility struct for tracking the external addresses of a [`Swarm`](crate::Swarm).
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExternalAddresses {
     addresses: Vec<Multiaddr>,
 }
 
 impl ExternalAddresses {
+    /// Creates a new [`ExternalAddresses`] from previously saved addresses, e.g. obtained via
+    /// [`ExternalAddresses::snapshot`] before a restart.
+    ///
+    /// The addresses are expected to be ordered from most to least recently confirmed.
+    pub fn from_saved(addresses: impl IntoIterator<Item = Multiaddr>) -> Self {
+        let mut saved = Self::default();
+
+        for address in addresses {
+            if saved.addresses.len() == MAX_LOCAL_EXTERNAL_ADDRS {
+                break;
+            }
+            if !saved.addresses.contains(&address) {
+                saved.addresses.push(address);
+            }
+        }
+
+        saved
+    }
+
+    /// Returns all external addresses, ordered from most to least recently confirmed.
+    pub fn snapshot(&self) -> Vec<Multiaddr> {
+        self.addresses.clone()
+    }
+
     /// Returns an [`Iterator`] over all external addresses.
     pub fn iter(&self) -> impl ExactSizeIterator<Item = &Multiaddr> {
         self.addresses.iter()
@@ -157,6 +184,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_saved_restores_snapshot_without_duplicates() {
+        let mut addresses = ExternalAddresses::default();
+        addresses.on_swarm_event(&new_external_addr1());
+        addresses.on_swarm_event(&new_external_addr2());
+
+        let mut saved = addresses.snapshot();
+        saved.push((*MEMORY_ADDR_1000).clone());
+
+        assert_eq!(
+            ExternalAddresses::from_saved(saved).as_slice(),
+            addresses.as_slice()
+        );
+    }
+
+    #[test]
+    fn from_saved_keeps_at_most_max_addresses() {
+        let saved = (0..2 * MAX_LOCAL_EXTERNAL_ADDRS as u64)
+            .map(|port| Multiaddr::empty().with(Protocol::Memory(port)))
+            .collect::<Vec<_>>();
+
+        let addresses = ExternalAddresses::from_saved(saved.clone());
+
+        assert_eq!(addresses.as_slice(), &saved[..MAX_LOCAL_EXTERNAL_ADDRS]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let mut addresses = ExternalAddresses::default();
+        addresses.on_swarm_event(&new_external_addr1());
+        addresses.on_swarm_event(&new_external_addr2());
+
+        let json = serde_json::to_string(&addresses).unwrap();
+        let restored = serde_json::from_str::<ExternalAddresses>(&json).unwrap();
+
+        assert_eq!(restored.as_slice(), addresses.as_slice());
+    }
+
     fn new_external_addr1() -> FromSwarm<'static> {
         FromSwarm::ExternalAddrConfirmed(ExternalAddrConfirmed {
             addr: &MEMORY_ADDR_1000,
@@ -0,0 +1,110 @@
+use crate::behaviour::{FromSwarm, NetworkBehaviour, ToSwarm};
+use crate::connection::ConnectionId;
+use crate::{dummy, ConnectionDenied, THandler, THandlerInEvent, THandlerOutEvent};
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use std::task::{Context, Poll};
+use void::Void;
+
+/// Creates a [`NetworkBehaviour`] whose [`NetworkBehaviour::poll`] calls the given closure, for
+/// prototyping and tests. Enabled via the `testing` feature.
+///
+/// The closure returns the next command for the [`Swarm`](crate::Swarm), if any. If it returns
+/// `None`, it is only called again once the [`Swarm`](crate::Swarm) is woken up, e.g. via the
+/// waker of the given [`Context`].
+/// Connections are handled by the [`dummy::ConnectionHandler`], thus no protocols are supported.
+///
+/// ```
+/// # use libp2p_swarm::{behaviour, ToSwarm};
+/// let mut events = vec!["hello", "world"];
+/// let behaviour = behaviour::from_fn(move |_| events.pop().map(ToSwarm::GenerateEvent));
+/// ```
+pub fn from_fn<F, E>(f: F) -> FromFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Option<ToSwarm<E, Void>>,
+{
+    FromFn(f)
+}
+
+/// [`NetworkBehaviour`] created via [`from_fn`].
+pub struct FromFn<F>(F);
+
+impl<F, E> NetworkBehaviour for FromFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Option<ToSwarm<E, Void>> + 'static,
+    E: Send + 'static,
+{
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = E;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match (self.0)(cx) {
+            Some(event) => Poll::Ready(event),
+            None => Poll::Pending,
+        }
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestRuntime, TestSwarm};
+    use crate::SwarmEvent;
+    use std::time::Duration;
+
+    #[test]
+    fn generated_event_is_reported_by_swarm() {
+        let runtime = TestRuntime::new();
+        let mut events = vec![2u32, 1];
+        let mut swarm = TestSwarm::new(&runtime, |_| {
+            from_fn(move |_| events.pop().map(ToSwarm::GenerateEvent))
+        });
+
+        for expected in [1, 2] {
+            let event = swarm
+                .wait_for_event(
+                    |event| match event {
+                        SwarmEvent::Behaviour(e) => Some(e),
+                        _ => None,
+                    },
+                    Duration::from_secs(1),
+                )
+                .unwrap();
+            assert_eq!(event, expected);
+        }
+    }
+}
@@ -1,14 +1,30 @@
 use crate::behaviour::{ExpiredListenAddr, FromSwarm, NewListenAddr};
 use libp2p_core::Multiaddr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// Utility struct for tracking the addresses a [`Swarm`](crate::Swarm) is listening on.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ListenAddresses {
     addresses: HashSet<Multiaddr>,
 }
 
 impl ListenAddresses {
+    /// Creates a new [`ListenAddresses`] from previously saved addresses, e.g. obtained via
+    /// [`ListenAddresses::snapshot`] before a restart.
+    pub fn from_saved(addresses: impl IntoIterator<Item = Multiaddr>) -> Self {
+        Self {
+            addresses: addresses.into_iter().collect(),
+        }
+    }
+
+    /// Returns all listen addresses.
+    pub fn snapshot(&self) -> Vec<Multiaddr> {
+        self.addresses.iter().cloned().collect()
+    }
+
     /// Returns an [`Iterator`] over all listen addresses.
     pub fn iter(&self) -> impl ExactSizeIterator<Item = &Multiaddr> {
         self.addresses.iter()
@@ -59,6 +75,28 @@ mod tests {
         assert!(!changed)
     }
 
+    #[test]
+    fn from_saved_restores_snapshot() {
+        let mut addresses = ListenAddresses::default();
+        addresses.on_swarm_event(&new_listen_addr());
+
+        let restored = ListenAddresses::from_saved(addresses.snapshot());
+
+        assert_eq!(restored.snapshot(), vec![(*MEMORY_ADDR).clone()]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let mut addresses = ListenAddresses::default();
+        addresses.on_swarm_event(&new_listen_addr());
+
+        let json = serde_json::to_string(&addresses).unwrap();
+        let restored = serde_json::from_str::<ListenAddresses>(&json).unwrap();
+
+        assert_eq!(restored.snapshot(), addresses.snapshot());
+    }
+
     fn new_listen_addr() -> FromSwarm<'static> {
         FromSwarm::NewListenAddr(NewListenAddr {
             listener_id: ListenerId::next(),
@@ -0,0 +1,77 @@
+/// The number of consecutive events of one member, returned before all other members were polled,
+/// after which a warning is logged in debug builds.
+const STARVATION_THRESHOLD: usize = 1000;
+
+/// State of the polling of a `#[derive(NetworkBehaviour)]` `struct`, see
+/// [`NetworkBehaviour::poll_with_rotation`](crate::NetworkBehaviour::poll_with_rotation).
+///
+/// The [`Swarm`](crate::Swarm) keeps one for its behaviour, which in turn holds the state of the
+/// `struct` members, such that each instance of a `struct` is polled round-robin independently of
+/// other instances.
+#[derive(Debug, Default)]
+pub struct PollRotation {
+    /// The member to poll first on the next call.
+    next_start: usize,
+    /// The member that returned an event before all other members were polled, and how many
+    /// times in a row it did.
+    streak: Option<(usize, usize)>,
+    /// The state of the members, used if they are derived behaviours themselves.
+    members: Vec<PollRotation>,
+}
+
+impl PollRotation {
+    /// Returns the member to poll first out of `num_members`, advancing to the next one for the
+    /// following call.
+    #[doc(hidden)]
+    pub fn start(&mut self, num_members: usize) -> usize {
+        let start = self.next_start % num_members;
+        self.next_start = (start + 1) % num_members;
+
+        start
+    }
+
+    /// Returns the state of the member with the given index.
+    #[doc(hidden)]
+    pub fn member(&mut self, index: usize) -> &mut PollRotation {
+        if self.members.len() <= index {
+            self.members.resize_with(index + 1, Default::default);
+        }
+
+        &mut self.members[index]
+    }
+
+    /// Records that the member with the given index and name returned an event, `starving` the
+    /// members that were not polled.
+    ///
+    /// In debug builds, logs a warning once a member returned
+    /// [`STARVATION_THRESHOLD`] events in a row while starving others.
+    #[doc(hidden)]
+    pub fn on_ready(&mut self, index: usize, name: &str, starving: bool) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        if !starving {
+            self.streak = None;
+            return;
+        }
+
+        let streak = match self.streak {
+            Some((previous, streak)) if previous == index => streak + 1,
+            _ => 1,
+        };
+        self.streak = Some((index, streak));
+
+        if streak == STARVATION_THRESHOLD {
+            tracing::warn!(
+                behaviour = name,
+                "Behaviour returned an event {STARVATION_THRESHOLD} times in a row before all other behaviours were polled"
+            );
+        }
+    }
+
+    /// Records that none of the members returned an event.
+    #[doc(hidden)]
+    pub fn on_pending(&mut self) {
+        self.streak = None;
+    }
+}
@@ -18,7 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::behaviour::FromSwarm;
+use crate::behaviour::{ConnectionPayload, FromSwarm, PollRotation};
 use crate::connection::ConnectionId;
 use crate::handler::{
     AddressChange, ConnectionEvent, ConnectionHandler, ConnectionHandlerEvent, DialUpgradeError,
@@ -26,11 +26,12 @@ use crate::handler::{
 };
 use crate::upgrade::SendWrapper;
 use crate::{
-    ConnectionDenied, NetworkBehaviour, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+    ConnectionDenied, ListenDenied, NetworkBehaviour, StreamProtocol, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
 };
 use either::Either;
 use futures::future;
-use libp2p_core::{upgrade::DeniedUpgrade, Endpoint, Multiaddr};
+use libp2p_core::{transport::ListenerId, upgrade::DeniedUpgrade, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use std::{task::Context, task::Poll};
 
@@ -157,6 +158,113 @@ where
         })
     }
 
+    fn handle_pending_inbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<Option<ConnectionPayload>, ConnectionDenied> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok(None);
+        };
+
+        inner.handle_pending_inbound_connection_with_payload(connection_id, local_addr, remote_addr)
+    }
+
+    fn handle_established_inbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+        payload: Option<ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok(ToggleConnectionHandler { inner: None });
+        };
+
+        let handler = inner.handle_established_inbound_connection_with_payload(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+            payload,
+        )?;
+
+        Ok(ToggleConnectionHandler {
+            inner: Some(handler),
+        })
+    }
+
+    fn handle_pending_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        maybe_peer: Option<PeerId>,
+        addresses: &[Multiaddr],
+        effective_role: Endpoint,
+    ) -> Result<(Vec<Multiaddr>, Option<ConnectionPayload>), ConnectionDenied> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok((vec![], None));
+        };
+
+        inner.handle_pending_outbound_connection_with_payload(
+            connection_id,
+            maybe_peer,
+            addresses,
+            effective_role,
+        )
+    }
+
+    fn handle_established_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+        payload: Option<ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok(ToggleConnectionHandler { inner: None });
+        };
+
+        let handler = inner.handle_established_outbound_connection_with_payload(
+            connection_id,
+            peer,
+            addr,
+            role_override,
+            payload,
+        )?;
+
+        Ok(ToggleConnectionHandler {
+            inner: Some(handler),
+        })
+    }
+
+    fn sort_addresses(&self, peer: &PeerId, addrs: &mut Vec<Multiaddr>) {
+        if let Some(behaviour) = &self.inner {
+            behaviour.sort_addresses(peer, addrs);
+        }
+    }
+
+    fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        self.inner
+            .as_ref()
+            .map(|behaviour| behaviour.supported_protocols())
+            .unwrap_or_default()
+    }
+
+    fn handle_pending_listen(
+        &mut self,
+        listener_id: ListenerId,
+        addr: &Multiaddr,
+    ) -> Result<(), ListenDenied> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok(());
+        };
+
+        inner.handle_pending_listen(listener_id, addr)
+    }
+
     fn on_swarm_event(&mut self, event: FromSwarm) {
         if let Some(behaviour) = &mut self.inner {
             behaviour.on_swarm_event(event);
@@ -184,6 +292,18 @@ where
             Poll::Pending
         }
     }
+
+    fn poll_with_rotation(
+        &mut self,
+        cx: &mut Context<'_>,
+        rotation: &mut PollRotation,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(inner) = self.inner.as_mut() {
+            inner.poll_with_rotation(cx, rotation)
+        } else {
+            Poll::Pending
+        }
+    }
 }
 
 /// Implementation of [`ConnectionHandler`] that can be in the disabled state.
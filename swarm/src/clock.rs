@@ -0,0 +1,63 @@
+//! Provides the source of time of the [`Swarm`](crate::Swarm)'s timers.
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use futures_timer::Delay;
+use instant::Instant;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Source of time of the timers of the [`Swarm`](crate::Swarm), i.e. the idle timeout and the
+/// upgrade timeouts of its connections, see [`Config::with_clock`](crate::Config::with_clock).
+///
+/// [`NetworkBehaviour`](crate::NetworkBehaviour)s and
+/// [`ConnectionHandler`](crate::ConnectionHandler)s can opt into using the same [`Clock`] for
+/// their own timers, e.g. to have them advance instantly in tests.
+pub trait Clock: fmt::Debug + Send + Sync + 'static {
+    /// Returns the current point in time.
+    fn now(&self) -> Instant;
+
+    /// Returns a [`Timer`] firing once the given duration passed.
+    fn timer(&self, duration: Duration) -> Timer;
+}
+
+/// The [`Clock`] of the operating system, used by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn timer(&self, duration: Duration) -> Timer {
+        Timer::new(Delay::new(duration))
+    }
+}
+
+/// A timer created by a [`Clock`], resolving once it fired.
+pub struct Timer(BoxFuture<'static, ()>);
+
+impl Timer {
+    /// Creates a [`Timer`] from a future resolving once the timer fired.
+    pub fn new(future: impl Future<Output = ()> + Send + 'static) -> Self {
+        Self(future.boxed())
+    }
+}
+
+impl Future for Timer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.poll_unpin(cx)
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timer").finish_non_exhaustive()
+    }
+}
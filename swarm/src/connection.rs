@@ -29,12 +29,15 @@ pub(crate) use error::{
 };
 pub use supported_protocols::SupportedProtocols;
 
+use crate::clock::{Clock, SystemClock, Timer};
 use crate::handler::{
     AddressChange, ConnectionEvent, ConnectionHandler, DialUpgradeError, FullyNegotiatedInbound,
     FullyNegotiatedOutbound, ListenUpgradeError, ProtocolSupport, ProtocolsAdded, ProtocolsChange,
     UpgradeInfoSend,
 };
-use crate::stream::ActiveStreamCounter;
+use crate::stream::{
+    ActiveStreamCounter, InboundStreamLimitGuard, StreamTraffic, StreamTrafficRecorder,
+};
 use crate::upgrade::{InboundUpgradeSend, OutboundUpgradeSend};
 use crate::{
     ConnectionHandlerEvent, Stream, StreamProtocol, StreamUpgradeError, SubstreamProtocol,
@@ -43,19 +46,22 @@ use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use futures::{stream, FutureExt};
-use futures_timer::Delay;
 use instant::Instant;
 use libp2p_core::connection::ConnectedPoint;
 use libp2p_core::multiaddr::Multiaddr;
-use libp2p_core::muxing::{StreamMuxerBox, StreamMuxerEvent, StreamMuxerExt, SubstreamBox};
+use libp2p_core::muxing::{
+    StreamCounter, StreamCounts, StreamLimitReached, StreamMuxerBox, StreamMuxerEvent,
+    StreamMuxerExt, SubstreamBox,
+};
 use libp2p_core::upgrade;
 use libp2p_core::upgrade::{NegotiationError, ProtocolError};
 use libp2p_core::Endpoint;
 use libp2p_identity::PeerId;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::future::Future;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::task::Waker;
 use std::time::Duration;
 use std::{fmt, io, mem, pin::Pin, task::Context, task::Poll};
@@ -98,6 +104,53 @@ pub(crate) struct Connected {
     pub(crate) peer_id: PeerId,
 }
 
+/// Settings of a [`Connection`], shared by all connections of a [`Pool`](pool::Pool).
+#[derive(Clone)]
+pub(crate) struct ConnectionConfig {
+    /// The substream upgrade protocol override, if any.
+    pub(crate) substream_upgrade_protocol_override: Option<upgrade::Version>,
+    /// The maximum number of inbound streams concurrently negotiating on a
+    /// connection. New inbound streams exceeding the limit are dropped and thus
+    /// reset.
+    ///
+    /// Note: This only enforces a limit on the number of concurrently
+    /// negotiating inbound streams. The total number of inbound streams on a
+    /// connection is the sum of negotiating and negotiated streams. A limit on
+    /// the total number of streams can be enforced at the [`StreamMuxerBox`] level.
+    pub(crate) max_negotiating_inbound_streams: usize,
+    /// How long a connection is kept alive once it starts idling.
+    pub(crate) idle_timeout: Duration,
+    /// Upgrade timeout overriding the one of each [`SubstreamProtocol`], if any.
+    pub(crate) substream_upgrade_timeout: Option<Duration>,
+    /// Prefix of all protocol names negotiated on the connection, if any.
+    pub(crate) protocol_prefix: Option<Arc<str>>,
+    /// The number of protocols proposed by the remote that are rejected before the negotiation
+    /// of an inbound stream is aborted, if it overrides the default of `multistream-select`.
+    pub(crate) max_negotiated_protocols: Option<usize>,
+    /// Whether to count the bytes transferred over the streams of the connection.
+    pub(crate) count_bytes: bool,
+    /// Whether to track when the connection last read successfully.
+    pub(crate) track_reads: bool,
+    /// Source of time of the idle and upgrade timeouts.
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            substream_upgrade_protocol_override: None,
+            max_negotiating_inbound_streams: 128,
+            idle_timeout: Duration::ZERO,
+            substream_upgrade_timeout: None,
+            protocol_prefix: None,
+            max_negotiated_protocols: None,
+            count_bytes: false,
+            track_reads: false,
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
 /// Event generated by a [`Connection`].
 #[derive(Debug, Clone)]
 pub(crate) enum Event<T> {
@@ -105,6 +158,14 @@ pub(crate) enum Event<T> {
     Handler(T),
     /// Address of the remote has changed.
     AddressChange(Multiaddr),
+    /// The muxer refused to open a new outbound stream because its stream limit is reached.
+    ///
+    /// Carries the stream counts at the time of the refusal.
+    StreamLimitReached(StreamCounts),
+    /// The first inbound or outbound substream of the connection has been fully negotiated.
+    ///
+    /// Emitted at most once per connection.
+    FirstSubstreamNegotiated,
 }
 
 /// A multiplexed connection to a peer with an associated [`ConnectionHandler`].
@@ -134,17 +195,8 @@ where
     >,
     /// The currently planned connection & handler shutdown.
     shutdown: Shutdown,
-    /// The substream upgrade protocol override, if any.
-    substream_upgrade_protocol_override: Option<upgrade::Version>,
-    /// The maximum number of inbound streams concurrently negotiating on a
-    /// connection. New inbound streams exceeding the limit are dropped and thus
-    /// reset.
-    ///
-    /// Note: This only enforces a limit on the number of concurrently
-    /// negotiating inbound streams. The total number of inbound streams on a
-    /// connection is the sum of negotiating and negotiated streams. A limit on
-    /// the total number of streams can be enforced at the [`StreamMuxerBox`] level.
-    max_negotiating_inbound_streams: usize,
+    /// The settings of this connection.
+    config: ConnectionConfig,
     /// Contains all upgrades that are waiting for a new outbound substream.
     ///
     /// The upgrade timeout is already ticking here so this may fail in case the remote is not quick
@@ -155,8 +207,17 @@ where
 
     local_supported_protocols: HashSet<StreamProtocol>,
     remote_supported_protocols: HashSet<StreamProtocol>,
-    idle_timeout: Duration,
     stream_counter: ActiveStreamCounter,
+    /// Traffic accounting of the streams opened on this connection.
+    stream_traffic: Arc<StreamTraffic>,
+    /// The number of open inbound streams of each protocol limited via
+    /// [`SubstreamProtocol::with_max_inbound_streams`].
+    inbound_streams_per_protocol: HashMap<StreamProtocol, Arc<AtomicUsize>>,
+    /// Whether a substream has been fully negotiated on this connection.
+    substream_negotiated: bool,
+    /// The protocols of the substreams that failed to negotiate, as long as no substream has been
+    /// negotiated.
+    failed_protocols: Vec<String>,
 }
 
 impl<THandler> fmt::Debug for Connection<THandler>
@@ -182,9 +243,7 @@ where
     pub(crate) fn new(
         muxer: StreamMuxerBox,
         mut handler: THandler,
-        substream_upgrade_protocol_override: Option<upgrade::Version>,
-        max_negotiating_inbound_streams: usize,
-        idle_timeout: Duration,
+        config: &ConnectionConfig,
     ) -> Self {
         let initial_protocols = gather_supported_protocols(&handler);
         if !initial_protocols.is_empty() {
@@ -198,16 +257,38 @@ where
             negotiating_in: Default::default(),
             negotiating_out: Default::default(),
             shutdown: Shutdown::None,
-            substream_upgrade_protocol_override,
-            max_negotiating_inbound_streams,
+            config: config.clone(),
             requested_substreams: Default::default(),
             local_supported_protocols: initial_protocols,
             remote_supported_protocols: Default::default(),
-            idle_timeout,
             stream_counter: ActiveStreamCounter::default(),
+            stream_traffic: Arc::new(StreamTraffic::new(config.count_bytes, config.track_reads)),
+            inbound_streams_per_protocol: Default::default(),
+            substream_negotiated: false,
+            failed_protocols: Vec::new(),
         }
     }
 
+    /// Returns the protocols tried on the substreams that failed to negotiate if no substream has
+    /// been negotiated on this connection, but at least one failed to.
+    pub(crate) fn negotiation_failure(&self) -> Option<Vec<String>> {
+        if self.substream_negotiated || self.failed_protocols.is_empty() {
+            return None;
+        }
+
+        Some(self.failed_protocols.clone())
+    }
+
+    /// Returns the per-direction stream accounting of this connection, maintained by its muxer.
+    pub(crate) fn stream_counter(&self) -> StreamCounter {
+        self.muxing.stream_counter()
+    }
+
+    /// Returns the traffic accounting of the streams of this connection.
+    pub(crate) fn stream_traffic(&self) -> Arc<StreamTraffic> {
+        self.stream_traffic.clone()
+    }
+
     /// Notifies the connection handler of an event.
     pub(crate) fn on_behaviour_event(&mut self, event: THandler::FromBehaviour) {
         self.handler.on_behaviour_event(event);
@@ -246,12 +327,14 @@ where
             negotiating_out,
             negotiating_in,
             shutdown,
-            max_negotiating_inbound_streams,
-            substream_upgrade_protocol_override,
+            config,
             local_supported_protocols: supported_protocols,
             remote_supported_protocols,
-            idle_timeout,
             stream_counter,
+            stream_traffic,
+            inbound_streams_per_protocol,
+            substream_negotiated,
+            failed_protocols,
             ..
         } = self.get_mut();
 
@@ -274,10 +357,14 @@ where
             match handler.poll(cx) {
                 Poll::Pending => {}
                 Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest { protocol }) => {
-                    let timeout = *protocol.timeout();
-                    let (upgrade, user_data) = protocol.into_upgrade();
+                    let timeout = config
+                        .substream_upgrade_timeout
+                        .unwrap_or(*protocol.timeout());
 
-                    requested_substreams.push(SubstreamRequested::new(user_data, timeout, upgrade));
+                    requested_substreams.push(SubstreamRequested::new(
+                        protocol,
+                        config.clock.timer(timeout),
+                    ));
                     continue; // Poll handler until exhausted.
                 }
                 Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event)) => {
@@ -313,13 +400,21 @@ where
             // In case the [`ConnectionHandler`] can not make any more progress, poll the negotiating outbound streams.
             match negotiating_out.poll_next_unpin(cx) {
                 Poll::Pending | Poll::Ready(None) => {}
-                Poll::Ready(Some((info, Ok(protocol)))) => {
+                Poll::Ready(Some((info, Ok(protocol), _))) => {
                     handler.on_connection_event(ConnectionEvent::FullyNegotiatedOutbound(
                         FullyNegotiatedOutbound { protocol, info },
                     ));
+
+                    if !std::mem::replace(substream_negotiated, true) {
+                        return Poll::Ready(Ok(Event::FirstSubstreamNegotiated));
+                    }
                     continue;
                 }
-                Poll::Ready(Some((info, Err(error)))) => {
+                Poll::Ready(Some((info, Err(error), protocols))) => {
+                    if matches!(error, StreamUpgradeError::NegotiationFailed) {
+                        record_failed_protocols(failed_protocols, *substream_negotiated, protocols);
+                    }
+
                     handler.on_connection_event(ConnectionEvent::DialUpgradeError(
                         DialUpgradeError { info, error },
                     ));
@@ -331,27 +426,32 @@ where
             // make any more progress, poll the negotiating inbound streams.
             match negotiating_in.poll_next_unpin(cx) {
                 Poll::Pending | Poll::Ready(None) => {}
-                Poll::Ready(Some((info, Ok(protocol)))) => {
+                Poll::Ready(Some((info, Ok(protocol), _))) => {
                     handler.on_connection_event(ConnectionEvent::FullyNegotiatedInbound(
                         FullyNegotiatedInbound { protocol, info },
                     ));
+
+                    if !std::mem::replace(substream_negotiated, true) {
+                        return Poll::Ready(Ok(Event::FirstSubstreamNegotiated));
+                    }
                     continue;
                 }
-                Poll::Ready(Some((info, Err(StreamUpgradeError::Apply(error))))) => {
+                Poll::Ready(Some((info, Err(StreamUpgradeError::Apply(error)), _))) => {
                     handler.on_connection_event(ConnectionEvent::ListenUpgradeError(
                         ListenUpgradeError { info, error },
                     ));
                     continue;
                 }
-                Poll::Ready(Some((_, Err(StreamUpgradeError::Io(e))))) => {
+                Poll::Ready(Some((_, Err(StreamUpgradeError::Io(e)), _))) => {
                     tracing::debug!("failed to upgrade inbound stream: {e}");
                     continue;
                 }
-                Poll::Ready(Some((_, Err(StreamUpgradeError::NegotiationFailed)))) => {
+                Poll::Ready(Some((_, Err(StreamUpgradeError::NegotiationFailed), protocols))) => {
                     tracing::debug!("no protocol could be agreed upon for inbound stream");
+                    record_failed_protocols(failed_protocols, *substream_negotiated, protocols);
                     continue;
                 }
-                Poll::Ready(Some((_, Err(StreamUpgradeError::Timeout)))) => {
+                Poll::Ready(Some((_, Err(StreamUpgradeError::Timeout), _))) => {
                     tracing::debug!("inbound stream upgrade timed out");
                     continue;
                 }
@@ -364,9 +464,12 @@ where
                 && requested_substreams.is_empty()
                 && stream_counter.has_no_active_streams()
             {
-                if let Some(new_timeout) =
-                    compute_new_shutdown(handler.connection_keep_alive(), shutdown, *idle_timeout)
-                {
+                if let Some(new_timeout) = compute_new_shutdown(
+                    handler.connection_keep_alive(),
+                    shutdown,
+                    config.idle_timeout,
+                    config.clock.as_ref(),
+                ) {
                     *shutdown = new_timeout;
                 }
 
@@ -395,35 +498,72 @@ where
             }
 
             if let Some(requested_substream) = requested_substreams.iter_mut().next() {
-                match muxing.poll_outbound_unpin(cx)? {
+                match muxing.poll_outbound_unpin(cx) {
                     Poll::Pending => {}
-                    Poll::Ready(substream) => {
-                        let (user_data, timeout, upgrade) = requested_substream.extract();
+                    Poll::Ready(Ok(substream)) => {
+                        let (protocol, timeout) = requested_substream.extract();
 
                         negotiating_out.push(StreamUpgrade::new_outbound(
                             substream,
-                            user_data,
+                            protocol,
                             timeout,
-                            upgrade,
-                            *substream_upgrade_protocol_override,
+                            config,
                             stream_counter.clone(),
+                            stream_traffic.recorder(),
+                            !*substream_negotiated,
                         ));
 
                         continue; // Go back to the top, handler can potentially make progress again.
                     }
+                    Poll::Ready(Err(error)) if StreamLimitReached::is_cause_of(&error) => {
+                        // The connection remains usable, thus only fail the request and report the
+                        // refusal.
+                        let (protocol, _) = requested_substream.extract();
+                        let (_, info) = protocol.into_upgrade();
+                        let counts = muxing.stream_counts();
+
+                        tracing::debug!("muxer refused new outbound stream: {error}");
+                        handler.on_connection_event(ConnectionEvent::DialUpgradeError(
+                            DialUpgradeError {
+                                info,
+                                error: StreamUpgradeError::Io(error),
+                            },
+                        ));
+
+                        return Poll::Ready(Ok(Event::StreamLimitReached(counts)));
+                    }
+                    Poll::Ready(Err(error)) => {
+                        return Poll::Ready(Err(ConnectionError::IO(error)));
+                    }
                 }
             }
 
-            if negotiating_in.len() < *max_negotiating_inbound_streams {
+            if negotiating_in.len() < config.max_negotiating_inbound_streams {
                 match muxing.poll_inbound_unpin(cx)? {
                     Poll::Pending => {}
                     Poll::Ready(substream) => {
                         let protocol = handler.listen_protocol();
+                        let limits = protocol
+                            .max_inbound_streams()
+                            .iter()
+                            .map(|(protocol, max)| {
+                                let active = inbound_streams_per_protocol
+                                    .entry(protocol.clone())
+                                    .or_default()
+                                    .clone();
+
+                                (protocol.clone(), *max, active)
+                            })
+                            .collect();
 
                         negotiating_in.push(StreamUpgrade::new_inbound(
                             substream,
                             protocol,
+                            config,
                             stream_counter.clone(),
+                            stream_traffic.recorder(),
+                            limits,
+                            !*substream_negotiated,
                         ));
 
                         continue; // Go back to the top, handler can potentially make progress again.
@@ -467,15 +607,16 @@ fn compute_new_shutdown(
     handler_keep_alive: bool,
     current_shutdown: &Shutdown,
     idle_timeout: Duration,
+    clock: &dyn Clock,
 ) -> Option<Shutdown> {
     match (current_shutdown, handler_keep_alive) {
         (_, false) if idle_timeout == Duration::ZERO => Some(Shutdown::Asap),
         (Shutdown::Later(_), false) => None, // Do nothing, i.e. let the shutdown timer continue to tick.
         (_, false) => {
-            let now = Instant::now();
+            let now = clock.now();
             let safe_keep_alive = checked_add_fraction(now, idle_timeout);
 
-            Some(Shutdown::Later(Delay::new(safe_keep_alive)))
+            Some(Shutdown::Later(clock.timer(safe_keep_alive)))
         }
         (_, true) => Some(Shutdown::None),
     }
@@ -514,25 +655,60 @@ impl<'a> IncomingInfo<'a> {
     }
 }
 
+/// Records the protocols of a substream that failed to negotiate, unless a substream has already
+/// been negotiated on the connection.
+fn record_failed_protocols(
+    failed_protocols: &mut Vec<String>,
+    substream_negotiated: bool,
+    protocols: Vec<String>,
+) {
+    if substream_negotiated {
+        return;
+    }
+
+    for protocol in protocols {
+        if !failed_protocols.contains(&protocol) {
+            failed_protocols.push(protocol);
+        }
+    }
+}
+
 struct StreamUpgrade<UserData, TOk, TErr> {
     user_data: Option<UserData>,
-    timeout: Delay,
+    timeout: Timer,
     upgrade: BoxFuture<'static, Result<TOk, StreamUpgradeError<TErr>>>,
+    /// The names of the protocols offered for the substream, only recorded if requested.
+    protocols: Vec<String>,
+}
+
+/// Returns the names of the protocols of the given upgrade if `record` is `true`.
+fn protocol_names<Upgrade: UpgradeInfoSend>(upgrade: &Upgrade, record: bool) -> Vec<String> {
+    if !record {
+        return Vec::new();
+    }
+
+    upgrade
+        .protocol_info()
+        .map(|p| p.as_ref().to_owned())
+        .collect()
 }
 
 impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
     fn new_outbound<Upgrade>(
         substream: SubstreamBox,
-        user_data: UserData,
-        timeout: Delay,
-        upgrade: Upgrade,
-        version_override: Option<upgrade::Version>,
+        protocol: SubstreamProtocol<Upgrade, UserData>,
+        timeout: Timer,
+        config: &ConnectionConfig,
         counter: ActiveStreamCounter,
+        traffic: StreamTrafficRecorder,
+        record_protocols: bool,
     ) -> Self
     where
         Upgrade: OutboundUpgradeSend<Output = TOk, Error = TErr>,
     {
-        let effective_version = match version_override {
+        let preferred_protocols = protocol.preferred_protocols().to_vec();
+        let (upgrade, user_data) = protocol.into_upgrade();
+        let effective_version = match config.substream_upgrade_protocol_override {
             Some(version_override) if version_override != upgrade::Version::default() => {
                 tracing::debug!(
                     "Substream upgrade protocol override: {:?} -> {:?}",
@@ -544,22 +720,36 @@ impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
             }
             _ => upgrade::Version::default(),
         };
-        let protocols = upgrade.protocol_info();
+        let protocol_names = protocol_names(&upgrade, record_protocols);
+        let mut protocols = upgrade.protocol_info().collect::<Vec<_>>();
+        // Stable, thus the protocols that are not preferred keep their order.
+        protocols.sort_by_key(|info| {
+            preferred_protocols
+                .iter()
+                .position(|p| p.as_ref() == info.as_ref())
+                .unwrap_or(usize::MAX)
+        });
+        let protocol_prefix = config.protocol_prefix.clone();
+        let protocols = protocols
+            .into_iter()
+            .map(move |info| PrefixedProtocol::new(info, protocol_prefix.as_deref()));
 
         Self {
             user_data: Some(user_data),
             timeout,
+            protocols: protocol_names,
             upgrade: Box::pin(async move {
-                let (info, stream) = multistream_select::dialer_select_proto(
-                    substream,
-                    protocols,
-                    effective_version,
-                )
-                .await
-                .map_err(to_stream_upgrade_error)?;
+                let (PrefixedProtocol { info, .. }, stream) =
+                    multistream_select::dialer_select_proto(
+                        substream,
+                        protocols,
+                        effective_version,
+                    )
+                    .await
+                    .map_err(to_stream_upgrade_error)?;
 
                 let output = upgrade
-                    .upgrade_outbound(Stream::new(stream, counter), info)
+                    .upgrade_outbound(Stream::new(stream, counter, traffic, None), info)
                     .await
                     .map_err(StreamUpgradeError::Apply)?;
 
@@ -573,26 +763,56 @@ impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
     fn new_inbound<Upgrade>(
         substream: SubstreamBox,
         protocol: SubstreamProtocol<Upgrade, UserData>,
+        config: &ConnectionConfig,
         counter: ActiveStreamCounter,
+        traffic: StreamTrafficRecorder,
+        limits: Vec<(StreamProtocol, usize, Arc<AtomicUsize>)>,
+        record_protocols: bool,
     ) -> Self
     where
         Upgrade: InboundUpgradeSend<Output = TOk, Error = TErr>,
     {
-        let timeout = *protocol.timeout();
+        let timeout = config
+            .substream_upgrade_timeout
+            .unwrap_or(*protocol.timeout());
         let (upgrade, open_info) = protocol.into_upgrade();
-        let protocols = upgrade.protocol_info();
+        let protocol_names = protocol_names(&upgrade, record_protocols);
+        let protocol_prefix = config.protocol_prefix.clone();
+        let protocols = upgrade
+            .protocol_info()
+            .map(move |info| PrefixedProtocol::new(info, protocol_prefix.as_deref()));
+        let max_negotiated_protocols = config.max_negotiated_protocols;
 
         Self {
             user_data: Some(open_info),
-            timeout: Delay::new(timeout),
+            timeout: config.clock.timer(timeout),
+            protocols: protocol_names,
             upgrade: Box::pin(async move {
-                let (info, stream) =
-                    multistream_select::listener_select_proto(substream, protocols)
-                        .await
-                        .map_err(to_stream_upgrade_error)?;
+                let mut negotiation =
+                    multistream_select::listener_select_proto(substream, protocols);
+                if let Some(max) = max_negotiated_protocols {
+                    negotiation = negotiation.with_max_rejected_protocols(max);
+                }
+                let (PrefixedProtocol { info, .. }, stream) =
+                    negotiation.await.map_err(to_stream_upgrade_error)?;
+
+                // Dropping the stream on exceeding the limit of its protocol resets it.
+                let limit_guard = match limits
+                    .into_iter()
+                    .find(|(protocol, ..)| protocol.as_ref() == info.as_ref())
+                {
+                    Some((protocol, max, active)) => Some(
+                        InboundStreamLimitGuard::try_new(active, max).ok_or_else(|| {
+                            StreamUpgradeError::Io(io::Error::other(format!(
+                                "limit of {max} concurrent inbound {protocol} streams reached"
+                            )))
+                        })?,
+                    ),
+                    None => None,
+                };
 
                 let output = upgrade
-                    .upgrade_inbound(Stream::new(stream, counter), info)
+                    .upgrade_inbound(Stream::new(stream, counter, traffic, limit_guard), info)
                     .await
                     .map_err(StreamUpgradeError::Apply)?;
 
@@ -602,6 +822,29 @@ impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
     }
 }
 
+/// A protocol offered during negotiation, with the protocol prefix of the connection, if any,
+/// prepended to its name.
+#[derive(Clone)]
+struct PrefixedProtocol<Info> {
+    info: Info,
+    /// The prefixed name, [`None`] without a prefix.
+    name: Option<String>,
+}
+
+impl<Info: AsRef<str>> PrefixedProtocol<Info> {
+    fn new(info: Info, prefix: Option<&str>) -> Self {
+        let name = prefix.map(|prefix| format!("{prefix}{}", info.as_ref()));
+
+        Self { info, name }
+    }
+}
+
+impl<Info: AsRef<str>> AsRef<str> for PrefixedProtocol<Info> {
+    fn as_ref(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.info.as_ref())
+    }
+}
+
 fn to_stream_upgrade_error<T>(e: NegotiationError) -> StreamUpgradeError<T> {
     match e {
         NegotiationError::Failed => StreamUpgradeError::NegotiationFailed,
@@ -615,7 +858,8 @@ fn to_stream_upgrade_error<T>(e: NegotiationError) -> StreamUpgradeError<T> {
 impl<UserData, TOk, TErr> Unpin for StreamUpgrade<UserData, TOk, TErr> {}
 
 impl<UserData, TOk, TErr> Future for StreamUpgrade<UserData, TOk, TErr> {
-    type Output = (UserData, Result<TOk, StreamUpgradeError<TErr>>);
+    /// The user data, the outcome of the upgrade and the recorded protocol names.
+    type Output = (UserData, Result<TOk, StreamUpgradeError<TErr>>, Vec<String>);
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         match self.timeout.poll_unpin(cx) {
@@ -625,6 +869,7 @@ impl<UserData, TOk, TErr> Future for StreamUpgrade<UserData, TOk, TErr> {
                         .take()
                         .expect("Future not to be polled again once ready."),
                     Err(StreamUpgradeError::Timeout),
+                    mem::take(&mut self.protocols),
                 ))
             }
 
@@ -637,15 +882,14 @@ impl<UserData, TOk, TErr> Future for StreamUpgrade<UserData, TOk, TErr> {
             .take()
             .expect("Future not to be polled again once ready.");
 
-        Poll::Ready((user_data, result))
+        Poll::Ready((user_data, result, mem::take(&mut self.protocols)))
     }
 }
 
 enum SubstreamRequested<UserData, Upgrade> {
     Waiting {
-        user_data: UserData,
-        timeout: Delay,
-        upgrade: Upgrade,
+        protocol: SubstreamProtocol<Upgrade, UserData>,
+        timeout: Timer,
         /// A waker to notify our [`FuturesUnordered`] that we have extracted the data.
         ///
         /// This will ensure that we will get polled again in the next iteration which allows us to
@@ -656,28 +900,26 @@ enum SubstreamRequested<UserData, Upgrade> {
 }
 
 impl<UserData, Upgrade> SubstreamRequested<UserData, Upgrade> {
-    fn new(user_data: UserData, timeout: Duration, upgrade: Upgrade) -> Self {
+    fn new(protocol: SubstreamProtocol<Upgrade, UserData>, timeout: Timer) -> Self {
         Self::Waiting {
-            user_data,
-            timeout: Delay::new(timeout),
-            upgrade,
+            protocol,
+            timeout,
             extracted_waker: None,
         }
     }
 
-    fn extract(&mut self) -> (UserData, Delay, Upgrade) {
+    fn extract(&mut self) -> (SubstreamProtocol<Upgrade, UserData>, Timer) {
         match mem::replace(self, Self::Done) {
             SubstreamRequested::Waiting {
-                user_data,
+                protocol,
                 timeout,
-                upgrade,
                 extracted_waker: waker,
             } => {
                 if let Some(waker) = waker {
                     waker.wake();
                 }
 
-                (user_data, timeout, upgrade)
+                (protocol, timeout)
             }
             SubstreamRequested::Done => panic!("cannot extract twice"),
         }
@@ -694,16 +936,14 @@ impl<UserData, Upgrade> Future for SubstreamRequested<UserData, Upgrade> {
 
         match mem::replace(this, Self::Done) {
             SubstreamRequested::Waiting {
-                user_data,
-                upgrade,
+                protocol,
                 mut timeout,
                 ..
             } => match timeout.poll_unpin(cx) {
-                Poll::Ready(()) => Poll::Ready(Err(user_data)),
+                Poll::Ready(()) => Poll::Ready(Err(protocol.into_upgrade().1)),
                 Poll::Pending => {
                     *this = Self::Waiting {
-                        user_data,
-                        upgrade,
+                        protocol,
                         timeout,
                         extracted_waker: Some(cx.waker().clone()),
                     };
@@ -730,14 +970,16 @@ enum Shutdown {
     None,
     /// A shut down is planned as soon as possible.
     Asap,
-    /// A shut down is planned for when a `Delay` has elapsed.
-    Later(Delay),
+    /// A shut down is planned for when a [`Timer`] has fired.
+    Later(Timer),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::SystemClock;
     use crate::dummy;
+    use crate::testing::VirtualClock;
     use futures::future;
     use futures::AsyncRead;
     use futures::AsyncWrite;
@@ -764,9 +1006,10 @@ mod tests {
                     counter: alive_substream_counter.clone(),
                 }),
                 MockConnectionHandler::new(Duration::from_secs(10)),
-                None,
-                max_negotiating_inbound_streams,
-                Duration::ZERO,
+                &ConnectionConfig {
+                    max_negotiating_inbound_streams,
+                    ..Default::default()
+                },
             );
 
             let result = connection.poll_noop_waker();
@@ -785,18 +1028,49 @@ mod tests {
     #[test]
     fn outbound_stream_timeout_starts_on_request() {
         let upgrade_timeout = Duration::from_secs(1);
+        let clock = VirtualClock::new();
         let mut connection = Connection::new(
             StreamMuxerBox::new(PendingStreamMuxer),
             MockConnectionHandler::new(upgrade_timeout),
-            None,
-            2,
-            Duration::ZERO,
+            &ConnectionConfig {
+                max_negotiating_inbound_streams: 2,
+                clock: Arc::new(clock.clone()),
+                ..Default::default()
+            },
         );
 
         connection.handler.open_new_outbound();
         let _ = connection.poll_noop_waker();
 
-        std::thread::sleep(upgrade_timeout + Duration::from_secs(1));
+        clock.advance(upgrade_timeout);
+
+        let _ = connection.poll_noop_waker();
+
+        assert!(matches!(
+            connection.handler.error.unwrap(),
+            StreamUpgradeError::Timeout
+        ))
+    }
+
+    #[test]
+    fn substream_upgrade_timeout_overrides_protocol_timeout() {
+        let upgrade_timeout = Duration::from_millis(100);
+        let clock = VirtualClock::new();
+        let mut connection = Connection::new(
+            StreamMuxerBox::new(PendingStreamMuxer),
+            MockConnectionHandler::new(Duration::from_secs(60)),
+            &ConnectionConfig {
+                max_negotiating_inbound_streams: 2,
+                substream_upgrade_timeout: Some(upgrade_timeout),
+                clock: Arc::new(clock.clone()),
+                ..Default::default()
+            },
+        );
+
+        connection.handler.open_new_outbound();
+        let _ = connection.poll_noop_waker();
+
+        clock.advance(upgrade_timeout);
 
         let _ = connection.poll_noop_waker();
 
@@ -811,9 +1085,10 @@ mod tests {
         let mut connection = Connection::new(
             StreamMuxerBox::new(PendingStreamMuxer),
             ConfigurableProtocolConnectionHandler::default(),
-            None,
-            0,
-            Duration::ZERO,
+            &ConnectionConfig {
+                max_negotiating_inbound_streams: 0,
+                ..Default::default()
+            },
         );
 
         // First, start listening on a single protocol.
@@ -850,9 +1125,10 @@ mod tests {
         let mut connection = Connection::new(
             StreamMuxerBox::new(PendingStreamMuxer),
             ConfigurableProtocolConnectionHandler::default(),
-            None,
-            0,
-            Duration::ZERO,
+            &ConnectionConfig {
+                max_negotiating_inbound_streams: 0,
+                ..Default::default()
+            },
         );
 
         // First, remote supports a single protocol.
@@ -896,21 +1172,25 @@ mod tests {
         assert_eq!(connection.handler.remote_removed, vec![vec!["/bar"]]);
     }
 
-    #[tokio::test]
-    async fn idle_timeout_with_keep_alive_no() {
+    #[test]
+    fn idle_timeout_with_keep_alive_no() {
         let idle_timeout = Duration::from_millis(100);
+        let clock = VirtualClock::new();
 
         let mut connection = Connection::new(
             StreamMuxerBox::new(PendingStreamMuxer),
             dummy::ConnectionHandler,
-            None,
-            0,
-            idle_timeout,
+            &ConnectionConfig {
+                max_negotiating_inbound_streams: 0,
+                idle_timeout,
+                clock: Arc::new(clock.clone()),
+                ..Default::default()
+            },
         );
 
         assert!(connection.poll_noop_waker().is_pending());
 
-        tokio::time::sleep(idle_timeout).await;
+        clock.advance(idle_timeout);
 
         assert!(matches!(
             connection.poll_noop_waker(),
@@ -945,9 +1225,9 @@ mod tests {
                     Shutdown::None => Shutdown::None,
                     Shutdown::Asap => Shutdown::Asap,
                     Shutdown::Later(_) => Shutdown::Later(
-                        // compute_new_shutdown does not touch the delay. Delay does not
-                        // implement Clone. Thus use a placeholder delay.
-                        Delay::new(Duration::from_secs(1)),
+                        // compute_new_shutdown does not touch the timer. Timer does not
+                        // implement Clone. Thus use a placeholder timer.
+                        SystemClock.timer(Duration::from_secs(1)),
                     ),
                 };
 
@@ -960,7 +1240,9 @@ mod tests {
                 let shutdown = match g.gen_range(1u8..4) {
                     1 => Shutdown::None,
                     2 => Shutdown::Asap,
-                    3 => Shutdown::Later(Delay::new(Duration::from_secs(u32::arbitrary(g) as u64))),
+                    3 => Shutdown::Later(
+                        SystemClock.timer(Duration::from_secs(u32::arbitrary(g) as u64)),
+                    ),
                     _ => unreachable!(),
                 };
 
@@ -973,7 +1255,12 @@ mod tests {
             current_shutdown: ArbitraryShutdown,
             idle_timeout: Duration,
         ) {
-            compute_new_shutdown(handler_keep_alive, &current_shutdown.0, idle_timeout);
+            compute_new_shutdown(
+                handler_keep_alive,
+                &current_shutdown.0,
+                idle_timeout,
+                &SystemClock,
+            );
         }
 
         QuickCheck::new().quickcheck(prop as fn(_, _, _));
@@ -73,6 +73,7 @@ pub(crate) type PendingInboundConnectionError = PendingConnectionError<Transport
 
 /// Errors that can occur in the context of a pending `Connection`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum PendingConnectionError<TTransErr> {
     /// An error occurred while negotiating the transport protocol(s) on a connection.
     Transport(TTransErr),
@@ -80,6 +81,12 @@ pub enum PendingConnectionError<TTransErr> {
     /// Pending connection attempt has been aborted.
     Aborted,
 
+    /// The connection was not established within the configured handshake timeout or, for
+    /// outbound connections, the timeout of the dial.
+    ///
+    /// See [`Config::with_handshake_timeout`](crate::Config::with_handshake_timeout).
+    Timeout,
+
     /// The peer identity obtained on the connection did not
     /// match the one that was expected.
     WrongPeerId {
@@ -96,6 +103,7 @@ impl<T> PendingConnectionError<T> {
         match self {
             PendingConnectionError::Transport(t) => PendingConnectionError::Transport(f(t)),
             PendingConnectionError::Aborted => PendingConnectionError::Aborted,
+            PendingConnectionError::Timeout => PendingConnectionError::Timeout,
             PendingConnectionError::WrongPeerId { obtained, endpoint } => {
                 PendingConnectionError::WrongPeerId { obtained, endpoint }
             }
@@ -113,6 +121,9 @@ where
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             PendingConnectionError::Aborted => write!(f, "Pending connection: Aborted."),
+            PendingConnectionError::Timeout => {
+                write!(f, "Pending connection: Handshake timed out.")
+            }
             PendingConnectionError::Transport(err) => {
                 write!(
                     f,
@@ -142,6 +153,7 @@ where
             PendingConnectionError::WrongPeerId { .. } => None,
             PendingConnectionError::LocalPeerId { .. } => None,
             PendingConnectionError::Aborted => None,
+            PendingConnectionError::Timeout => None,
         }
     }
 }
@@ -18,7 +18,9 @@
 // LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
-use crate::connection::{Connection, ConnectionId, PendingPoint};
+use crate::clock::{Clock, SystemClock};
+use crate::connection::{Connection, ConnectionConfig, ConnectionId, PendingPoint};
+use crate::stream::{ConnectionByteCounters, StreamTraffic};
 use crate::{
     connection::{
         Connected, ConnectionError, IncomingInfo, PendingConnectionError,
@@ -27,7 +29,7 @@ use crate::{
     transport::TransportError,
     ConnectedPoint, ConnectionHandler, Executor, Multiaddr, PeerId,
 };
-use concurrent_dial::ConcurrentDial;
+use concurrent_dial::{AttemptFailureSink, ConcurrentDial};
 use fnv::FnvHashMap;
 use futures::prelude::*;
 use futures::stream::SelectAll;
@@ -39,10 +41,13 @@ use futures::{
 };
 use instant::{Duration, Instant};
 use libp2p_core::connection::Endpoint;
-use libp2p_core::muxing::{StreamMuxerBox, StreamMuxerExt};
+use libp2p_core::multiaddr_ext::{MultiaddrExt, TransportKind};
+use libp2p_core::muxing::{StreamCounter, StreamCounts, StreamMuxerBox, StreamMuxerExt};
+use smallvec::SmallVec;
+use std::sync::Arc;
 use std::task::Waker;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     fmt,
     num::{NonZeroU8, NonZeroUsize},
     pin::Pin,
@@ -90,11 +95,11 @@ where
     /// The connection counter(s).
     counters: ConnectionCounters,
 
-    /// The managed connections of each peer that are currently considered established.
-    established: FnvHashMap<
-        PeerId,
-        FnvHashMap<ConnectionId, EstablishedConnection<THandler::FromBehaviour>>,
-    >,
+    /// The managed connections that are currently considered established.
+    established: FnvHashMap<ConnectionId, EstablishedConnection<THandler::FromBehaviour>>,
+
+    /// The IDs of the established connections of each peer, in the order they were established.
+    established_by_peer: FnvHashMap<PeerId, SmallVec<[ConnectionId; 10]>>,
 
     /// The pending connections that are currently being negotiated.
     pending: HashMap<ConnectionId, PendingConnection>,
@@ -105,13 +110,12 @@ where
     /// Number of addresses concurrently dialed for a single outbound connection attempt.
     dial_concurrency_factor: NonZeroU8,
 
-    /// The configured override for substream protocol upgrades, if any.
-    substream_upgrade_protocol_override: Option<libp2p_core::upgrade::Version>,
+    /// Whether to report each failed address of an outbound connection attempt via
+    /// [`PoolEvent::DialAttemptFailed`].
+    report_dial_attempt_failures: bool,
 
-    /// The maximum number of inbound streams concurrently negotiating on a connection.
-    ///
-    /// See [`Connection::max_negotiating_inbound_streams`].
-    max_negotiating_inbound_streams: usize,
+    /// The settings of the established connections.
+    connection_config: ConnectionConfig,
 
     /// How many [`task::EstablishedConnectionEvent`]s can be buffered before the connection is back-pressured.
     per_connection_event_buffer_size: usize,
@@ -134,18 +138,34 @@ where
     established_connection_events:
         SelectAll<mpsc::Receiver<task::EstablishedConnectionEvent<THandler::ToBehaviour>>>,
 
+    /// Events of a batch received from an established connection, not yet returned.
+    handler_events: VecDeque<(PeerId, ConnectionId, THandler::ToBehaviour)>,
+
     /// Receivers for [`NewConnection`] objects that are dropped.
     new_connection_dropped_listeners: FuturesUnordered<oneshot::Receiver<StreamMuxerBox>>,
 
-    /// How long a connection should be kept alive once it starts idling.
-    idle_connection_timeout: Duration,
+    /// How long a pending connection may take to be established, if bounded.
+    handshake_timeout: Option<Duration>,
+
+    /// How long a connection may go without a successful read before [`NotifyHandler::Any`](crate::NotifyHandler::Any)
+    /// prefers other connections of the peer, if enabled.
+    notify_any_read_window: Option<Duration>,
 }
 
 #[derive(Debug)]
 pub(crate) struct EstablishedConnection<TInEvent> {
+    peer_id: PeerId,
     endpoint: ConnectedPoint,
     /// Channel endpoint to send commands to the task.
     sender: mpsc::Sender<task::Command<TInEvent>>,
+    /// Per-direction stream accounting, maintained by the muxer of the connection.
+    stream_counter: StreamCounter,
+    /// Traffic accounting of the streams, maintained by the connection task.
+    stream_traffic: Arc<StreamTraffic>,
+    /// Connections with a higher priority are preferred for [`NotifyHandler::Any`](crate::NotifyHandler::Any).
+    priority: u8,
+    /// The label given when dialing or by the listener, see [`Pool::connection_label`].
+    label: Option<String>,
 }
 
 impl<TInEvent> EstablishedConnection<TInEvent> {
@@ -201,6 +221,11 @@ struct PendingConnection {
     abort_notifier: Option<oneshot::Sender<Void>>,
     /// The moment we became aware of this possible connection, useful for timing metrics.
     accepted_at: Instant,
+    /// The transports of the addresses being dialed, or of the remote address of an incoming
+    /// connection.
+    transports: BTreeSet<TransportKind>,
+    /// The label given when dialing or by the listener, see [`Pool::connection_label`].
+    label: Option<String>,
 }
 
 impl PendingConnection {
@@ -216,6 +241,16 @@ impl PendingConnection {
     }
 }
 
+/// The priority a connection is established with: relayed connections have a priority of `0`, all
+/// other connections a priority of `1`.
+fn default_priority(endpoint: &ConnectedPoint) -> u8 {
+    if endpoint.is_relayed() {
+        0
+    } else {
+        1
+    }
+}
+
 impl<THandler: ConnectionHandler> fmt::Debug for Pool<THandler> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         f.debug_struct("Pool")
@@ -239,6 +274,8 @@ pub(crate) enum PoolEvent<ToBehaviour> {
         concurrent_dial_errors: Option<Vec<(Multiaddr, TransportError<std::io::Error>)>>,
         /// How long it took to establish this connection.
         established_in: std::time::Duration,
+        /// The label given when dialing or by the listener.
+        label: Option<String>,
     },
 
     /// An established connection was closed.
@@ -261,6 +298,8 @@ pub(crate) enum PoolEvent<ToBehaviour> {
         error: Option<ConnectionError>,
         /// The remaining established connections to the same peer.
         remaining_established_connection_ids: Vec<ConnectionId>,
+        /// The protocols tried if no substream could be negotiated on the connection.
+        negotiation_failure: Option<Vec<String>>,
     },
 
     /// An outbound connection attempt failed.
@@ -273,6 +312,22 @@ pub(crate) enum PoolEvent<ToBehaviour> {
         peer: Option<PeerId>,
     },
 
+    /// Dialing one of the addresses of an outbound connection attempt failed.
+    ///
+    /// Only reported if enabled via [`PoolConfig::with_dial_attempt_failure_events`].
+    DialAttemptFailed {
+        /// The ID of the pending connection.
+        id: ConnectionId,
+        /// The (expected) peer of the pending connection.
+        peer: Option<PeerId>,
+        /// The address that failed.
+        address: Multiaddr,
+        /// The error that occurred.
+        error: TransportError<std::io::Error>,
+        /// Number of addresses still being dialed or yet to be dialed.
+        remaining_addresses: usize,
+    },
+
     /// An inbound connection attempt failed.
     PendingInboundConnectionError {
         /// The ID of the failed connection.
@@ -293,6 +348,16 @@ pub(crate) enum PoolEvent<ToBehaviour> {
         event: ToBehaviour,
     },
 
+    /// The muxer of a connection refused to open a new outbound stream.
+    StreamLimitReached {
+        id: ConnectionId,
+        peer_id: PeerId,
+        counts: StreamCounts,
+    },
+
+    /// The first substream of a connection has been fully negotiated.
+    FirstSubstreamNegotiated { id: ConnectionId, peer_id: PeerId },
+
     /// The connection to a node has changed its address.
     AddressChange {
         id: ConnectionId,
@@ -319,18 +384,31 @@ where
             local_id,
             counters: ConnectionCounters::new(),
             established: Default::default(),
+            established_by_peer: Default::default(),
             pending: Default::default(),
             task_command_buffer_size: config.task_command_buffer_size,
             dial_concurrency_factor: config.dial_concurrency_factor,
-            substream_upgrade_protocol_override: config.substream_upgrade_protocol_override,
-            max_negotiating_inbound_streams: config.max_negotiating_inbound_streams,
+            report_dial_attempt_failures: config.report_dial_attempt_failures,
+            connection_config: ConnectionConfig {
+                substream_upgrade_protocol_override: config.substream_upgrade_protocol_override,
+                max_negotiating_inbound_streams: config.max_negotiating_inbound_streams,
+                idle_timeout: config.idle_connection_timeout,
+                substream_upgrade_timeout: config.substream_upgrade_timeout,
+                protocol_prefix: config.protocol_prefix,
+                max_negotiated_protocols: config.max_negotiated_protocols,
+                count_bytes: config.connection_counters,
+                track_reads: config.notify_any_read_window.is_some(),
+                clock: config.clock,
+            },
             per_connection_event_buffer_size: config.per_connection_event_buffer_size,
-            idle_connection_timeout: config.idle_connection_timeout,
+            handshake_timeout: config.handshake_timeout,
+            notify_any_read_window: config.notify_any_read_window,
             executor,
             pending_connection_events_tx,
             pending_connection_events_rx,
             no_established_connections_waker: None,
             established_connection_events: Default::default(),
+            handler_events: VecDeque::new(),
             new_connection_dropped_listeners: Default::default(),
         }
     }
@@ -345,22 +423,55 @@ where
         &mut self,
         id: ConnectionId,
     ) -> Option<&mut EstablishedConnection<THandler::FromBehaviour>> {
+        self.established.get_mut(&id)
+    }
+
+    /// Returns the peer and endpoint of an established connection.
+    pub(crate) fn established_endpoint(
+        &self,
+        id: ConnectionId,
+    ) -> Option<(PeerId, &ConnectedPoint)> {
+        self.established
+            .get(&id)
+            .map(|connection| (connection.peer_id, &connection.endpoint))
+    }
+
+    /// Returns the label of a pending or established connection, if it has one.
+    pub(crate) fn connection_label(&self, id: ConnectionId) -> Option<&str> {
+        match self.established.get(&id) {
+            Some(connection) => connection.label.as_deref(),
+            None => self.pending.get(&id)?.label.as_deref(),
+        }
+    }
+
+    /// Returns the current stream accounting of an established connection.
+    pub(crate) fn stream_counts(&self, id: ConnectionId) -> Option<StreamCounts> {
+        self.established
+            .get(&id)
+            .map(|connection| connection.stream_counter.counts())
+    }
+
+    /// Returns the byte counters of an established connection, if enabled.
+    pub(crate) fn connection_byte_counters(
+        &self,
+        id: ConnectionId,
+    ) -> Option<ConnectionByteCounters> {
         self.established
-            .values_mut()
-            .find_map(|connections| connections.get_mut(&id))
+            .get(&id)
+            .and_then(|connection| connection.stream_traffic.connection_counters())
     }
 
     /// Returns true if we are connected to the given peer.
     ///
     /// This will return true only after a `NodeReached` event has been produced by `poll()`.
     pub(crate) fn is_connected(&self, id: PeerId) -> bool {
-        self.established.contains_key(&id)
+        self.established_by_peer.contains_key(&id)
     }
 
     /// Returns the number of connected peers, i.e. those with at least one
     /// established connection in the pool.
     pub(crate) fn num_peers(&self) -> usize {
-        self.established.len()
+        self.established_by_peer.len()
     }
 
     /// (Forcefully) close all connections to the given peer.
@@ -369,8 +480,8 @@ where
     /// closed asap and no more events from these connections are emitted
     /// by the pool effective immediately.
     pub(crate) fn disconnect(&mut self, peer: PeerId) {
-        if let Some(conns) = self.established.get_mut(&peer) {
-            for (_, conn) in conns.iter_mut() {
+        for id in self.established_by_peer.get(&peer).into_iter().flatten() {
+            if let Some(conn) = self.established.get_mut(id) {
                 conn.start_close();
             }
         }
@@ -384,15 +495,105 @@ where
         }
     }
 
+    /// Returns the IDs of all established connections to the given peer, ordered by descending
+    /// priority, with stale connections last if a read window is configured.
+    pub(crate) fn established_connections_of_peer_by_priority(
+        &self,
+        peer: &PeerId,
+    ) -> SmallVec<[ConnectionId; 10]> {
+        let Some(ids) = self.established_by_peer.get(peer) else {
+            return SmallVec::new();
+        };
+        let mut ids = ids.clone();
+        // Stable, thus connections of the same priority keep the order they were established in.
+        // Stale connections come last, so they are only used if no other connection is ready.
+        ids.sort_by_key(|id| {
+            let connection = &self.established[id];
+            let is_stale = self
+                .notify_any_read_window
+                .is_some_and(|window| connection.stream_traffic.is_stale(window));
+
+            (is_stale, std::cmp::Reverse(connection.priority))
+        });
+
+        ids
+    }
+
+    /// Returns the priority of an established connection.
+    pub(crate) fn connection_priority(&self, id: ConnectionId) -> Option<u8> {
+        self.established.get(&id).map(|conn| conn.priority)
+    }
+
+    /// Sets the priority of an established connection, returning `false` if there is none with
+    /// the given ID.
+    pub(crate) fn set_connection_priority(&mut self, id: ConnectionId, priority: u8) -> bool {
+        match self.get_established(id) {
+            Some(conn) => {
+                conn.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of addresses concurrently dialed for a single outbound connection attempt.
+    pub(crate) fn dial_concurrency_factor(&self) -> NonZeroU8 {
+        self.dial_concurrency_factor
+    }
+
+    /// Sets the number of addresses concurrently dialed for outbound connection attempts started
+    /// from now on.
+    pub(crate) fn set_dial_concurrency_factor(&mut self, factor: NonZeroU8) {
+        self.dial_concurrency_factor = factor;
+    }
+
     /// Returns an iterator over all established connections of `peer`.
     pub(crate) fn iter_established_connections_of_peer(
         &mut self,
         peer: &PeerId,
     ) -> impl Iterator<Item = ConnectionId> + '_ {
-        match self.established.get(peer) {
-            Some(conns) => either::Either::Left(conns.iter().map(|(id, _)| *id)),
-            None => either::Either::Right(std::iter::empty()),
+        self.established_by_peer
+            .get(peer)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// Returns an iterator over the peer, ID and endpoint of all established connections.
+    pub(crate) fn iter_established(
+        &self,
+    ) -> impl Iterator<Item = (PeerId, ConnectionId, &ConnectedPoint)> + '_ {
+        self.established
+            .iter()
+            .map(|(id, conn)| (conn.peer_id, *id, &conn.endpoint))
+    }
+
+    /// Counts the established connections by the transport of their remote address and their
+    /// direction.
+    pub(crate) fn established_by_transport(&self) -> BTreeMap<TransportKind, DirectionCounts> {
+        let mut counts = BTreeMap::<TransportKind, DirectionCounts>::new();
+        for connection in self.established.values() {
+            let endpoint = &connection.endpoint;
+            if let Some(kind) = endpoint.get_remote_address().transport_kind() {
+                counts.entry(kind).or_default().inc(endpoint.is_dialer());
+            }
+        }
+
+        counts
+    }
+
+    /// Counts the pending connections by the transports they are established over and their
+    /// direction.
+    pub(crate) fn pending_by_transport(&self) -> BTreeMap<TransportKind, DirectionCounts> {
+        let mut counts = BTreeMap::<TransportKind, DirectionCounts>::new();
+        for connection in self.pending.values() {
+            let is_dialer = matches!(connection.endpoint, PendingPoint::Dialer { .. });
+            for kind in &connection.transports {
+                counts.entry(*kind).or_default().inc(is_dialer);
+            }
         }
+
+        counts
     }
 
     /// Checks whether we are currently dialing the given peer.
@@ -405,39 +606,66 @@ where
     /// Returns an iterator over all connected peers, i.e. those that have
     /// at least one established connection in the pool.
     pub(crate) fn iter_connected(&self) -> impl Iterator<Item = &PeerId> {
-        self.established.keys()
+        self.established_by_peer.keys()
     }
 
     /// Adds a pending outgoing connection to the pool in the form of a `Future`
     /// that establishes and negotiates the connection.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn add_outgoing(
         &mut self,
-        dials: Vec<
-            BoxFuture<
-                'static,
-                (
-                    Multiaddr,
-                    Result<(PeerId, StreamMuxerBox), TransportError<std::io::Error>>,
-                ),
+        dial_groups: Vec<
+            Vec<
+                BoxFuture<
+                    'static,
+                    (
+                        Multiaddr,
+                        Result<(PeerId, StreamMuxerBox), TransportError<std::io::Error>>,
+                    ),
+                >,
             >,
         >,
         peer: Option<PeerId>,
         role_override: Endpoint,
         dial_concurrency_factor_override: Option<NonZeroU8>,
+        delay: Option<Duration>,
+        timeout: Option<Duration>,
         connection_id: ConnectionId,
+        transports: BTreeSet<TransportKind>,
+        label: Option<String>,
     ) {
         let concurrency_factor =
             dial_concurrency_factor_override.unwrap_or(self.dial_concurrency_factor);
-        let span = tracing::debug_span!(parent: tracing::Span::none(), "new_outgoing_connection", %concurrency_factor, num_dials=%dials.len(), id = %connection_id);
+        let num_dials = dial_groups.iter().map(Vec::len).sum::<usize>();
+        let span = tracing::debug_span!(parent: tracing::Span::none(), "new_outgoing_connection", %concurrency_factor, %num_dials, id = %connection_id);
         span.follows_from(tracing::Span::current());
 
         let (abort_notifier, abort_receiver) = oneshot::channel();
 
+        let attempt_failures = self.report_dial_attempt_failures.then(|| {
+            Box::pin(
+                self.pending_connection_events_tx
+                    .clone()
+                    .with(move |failure| {
+                        future::ready(Ok(task::PendingConnectionEvent::DialAttemptFailed {
+                            id: connection_id,
+                            failure,
+                        }))
+                    }),
+            ) as AttemptFailureSink
+        });
+
         self.executor.spawn(
             task::new_for_pending_outgoing_connection(
                 connection_id,
-                ConcurrentDial::new(dials, concurrency_factor),
+                ConcurrentDial::new(dial_groups, concurrency_factor, attempt_failures),
                 abort_receiver,
+                delay,
+                // The earlier of the dial and handshake timeout aborts the dial.
+                match (timeout, self.handshake_timeout) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                },
                 self.pending_connection_events_tx.clone(),
             )
             .instrument(span),
@@ -453,6 +681,8 @@ where
                 endpoint,
                 abort_notifier: Some(abort_notifier),
                 accepted_at: Instant::now(),
+                transports,
+                label,
             },
         );
     }
@@ -464,10 +694,12 @@ where
         future: TFut,
         info: IncomingInfo<'_>,
         connection_id: ConnectionId,
+        label: Option<String>,
     ) where
         TFut: Future<Output = Result<(PeerId, StreamMuxerBox), std::io::Error>> + Send + 'static,
     {
         let endpoint = info.create_connected_point();
+        let transports = info.send_back_addr.transport_kind().into_iter().collect();
 
         let (abort_notifier, abort_receiver) = oneshot::channel();
 
@@ -479,6 +711,7 @@ where
                 connection_id,
                 future,
                 abort_receiver,
+                self.handshake_timeout,
                 self.pending_connection_events_tx.clone(),
             )
             .instrument(span),
@@ -492,6 +725,8 @@ where
                 endpoint: endpoint.into(),
                 abort_notifier: Some(abort_notifier),
                 accepted_at: Instant::now(),
+                transports,
+                label,
             },
         );
     }
@@ -503,35 +738,38 @@ where
         endpoint: &ConnectedPoint,
         connection: NewConnection,
         handler: THandler,
+        label: Option<String>,
     ) {
         let connection = connection.extract();
-        let conns = self.established.entry(obtained_peer_id).or_default();
         self.counters.inc_established(endpoint);
 
         let (command_sender, command_receiver) = mpsc::channel(self.task_command_buffer_size);
         let (event_sender, event_receiver) = mpsc::channel(self.per_connection_event_buffer_size);
 
-        conns.insert(
+        let connection = Connection::new(connection, handler, &self.connection_config);
+
+        self.established.insert(
             id,
             EstablishedConnection {
+                peer_id: obtained_peer_id,
                 endpoint: endpoint.clone(),
                 sender: command_sender,
+                stream_counter: connection.stream_counter(),
+                stream_traffic: connection.stream_traffic(),
+                priority: default_priority(endpoint),
+                label,
             },
         );
+        self.established_by_peer
+            .entry(obtained_peer_id)
+            .or_default()
+            .push(id);
         self.established_connection_events.push(event_receiver);
         if let Some(waker) = self.no_established_connections_waker.take() {
             waker.wake();
         }
 
-        let connection = Connection::new(
-            connection,
-            handler,
-            self.substream_upgrade_protocol_override,
-            self.max_negotiating_inbound_streams,
-            self.idle_connection_timeout,
-        );
-
-        let span = tracing::debug_span!(parent: tracing::Span::none(), "new_established_connection", remote_addr = %endpoint.get_remote_address(), %id, peer = %obtained_peer_id);
+        let span = tracing::debug_span!(parent: tracing::Span::none(), "new_established_connection", remote_addr = %endpoint.get_remote_address(), %id, peer = %obtained_peer_id, direction = if endpoint.is_dialer() { "dialer" } else { "listener" });
         span.follows_from(tracing::Span::current());
 
         self.executor.spawn(
@@ -553,6 +791,11 @@ where
         THandler: ConnectionHandler + 'static,
         <THandler as ConnectionHandler>::OutboundOpenInfo: Send,
     {
+        // Return the remaining events of the last batch first, preserving their order.
+        if let Some((peer_id, id, event)) = self.handler_events.pop_front() {
+            return Poll::Ready(PoolEvent::ConnectionEvent { peer_id, id, event });
+        }
+
         // Poll for events of established connections.
         //
         // Note that established connections are polled before pending connections, thus
@@ -563,9 +806,35 @@ where
                 self.no_established_connections_waker = Some(cx.waker().clone());
             }
 
-            Poll::Ready(Some(task::EstablishedConnectionEvent::Notify { id, peer_id, event })) => {
+            Poll::Ready(Some(task::EstablishedConnectionEvent::Notify {
+                id,
+                peer_id,
+                events,
+            })) => {
+                let mut events = events.into_iter();
+                let event = events.next().expect("batches to be non-empty");
+                self.handler_events
+                    .extend(events.map(|event| (peer_id, id, event)));
+
                 return Poll::Ready(PoolEvent::ConnectionEvent { peer_id, id, event });
             }
+            Poll::Ready(Some(task::EstablishedConnectionEvent::StreamLimitReached {
+                id,
+                peer_id,
+                counts,
+            })) => {
+                return Poll::Ready(PoolEvent::StreamLimitReached {
+                    id,
+                    peer_id,
+                    counts,
+                });
+            }
+            Poll::Ready(Some(task::EstablishedConnectionEvent::FirstSubstreamNegotiated {
+                id,
+                peer_id,
+            })) => {
+                return Poll::Ready(PoolEvent::FirstSubstreamNegotiated { id, peer_id });
+            }
             Poll::Ready(Some(task::EstablishedConnectionEvent::AddressChange {
                 id,
                 peer_id,
@@ -573,8 +842,6 @@ where
             })) => {
                 let connection = self
                     .established
-                    .get_mut(&peer_id)
-                    .expect("Receive `AddressChange` event for established peer.")
                     .get_mut(&id)
                     .expect("Receive `AddressChange` event from established connection");
                 let mut new_endpoint = connection.endpoint.clone();
@@ -589,24 +856,34 @@ where
                     old_endpoint,
                 });
             }
-            Poll::Ready(Some(task::EstablishedConnectionEvent::Closed { id, peer_id, error })) => {
-                let connections = self
+            Poll::Ready(Some(task::EstablishedConnectionEvent::Closed {
+                id,
+                peer_id,
+                error,
+                negotiation_failure,
+            })) => {
+                let EstablishedConnection { endpoint, .. } = self
                     .established
-                    .get_mut(&peer_id)
+                    .remove(&id)
                     .expect("`Closed` event for established connection");
-                let EstablishedConnection { endpoint, .. } =
-                    connections.remove(&id).expect("Connection to be present");
                 self.counters.dec_established(&endpoint);
-                let remaining_established_connection_ids: Vec<ConnectionId> =
-                    connections.keys().cloned().collect();
+                let connections = self
+                    .established_by_peer
+                    .get_mut(&peer_id)
+                    .expect("Established connection to be indexed by its peer");
+                if let Some(index) = connections.iter().position(|c| *c == id) {
+                    connections.remove(index);
+                }
+                let remaining_established_connection_ids = connections.to_vec();
                 if remaining_established_connection_ids.is_empty() {
-                    self.established.remove(&peer_id);
+                    self.established_by_peer.remove(&peer_id);
                 }
                 return Poll::Ready(PoolEvent::ConnectionClosed {
                     id,
                     connected: Connected { endpoint, peer_id },
                     error,
                     remaining_established_connection_ids,
+                    negotiation_failure,
                 });
             }
         }
@@ -641,6 +918,8 @@ where
                         endpoint,
                         abort_notifier: _,
                         accepted_at,
+                        transports: _,
+                        label,
                     } = self
                         .pending
                         .remove(&id)
@@ -744,14 +1023,29 @@ where
                         connection,
                         concurrent_dial_errors,
                         established_in,
+                        label,
                     });
                 }
+                task::PendingConnectionEvent::DialAttemptFailed { id, failure } => {
+                    // The connection may have been aborted in the meantime.
+                    if let Some(PendingConnection { peer_id, .. }) = self.pending.get(&id) {
+                        return Poll::Ready(PoolEvent::DialAttemptFailed {
+                            id,
+                            peer: *peer_id,
+                            address: failure.address,
+                            error: failure.error,
+                            remaining_addresses: failure.remaining_addresses,
+                        });
+                    }
+                }
                 task::PendingConnectionEvent::PendingFailed { id, error } => {
                     if let Some(PendingConnection {
                         peer_id,
                         endpoint,
                         abort_notifier: _,
                         accepted_at: _, // Ignoring the time it took for the connection to fail.
+                        transports: _,
+                        label: _,
                     }) = self.pending.remove(&id)
                     {
                         self.counters.dec_pending(&endpoint);
@@ -838,6 +1132,39 @@ impl Drop for NewConnection {
     }
 }
 
+/// The number of connections of a transport in each direction, see
+/// [`NetworkInfo::connections_by_transport`](crate::NetworkInfo::connections_by_transport).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirectionCounts {
+    incoming: u32,
+    outgoing: u32,
+}
+
+impl DirectionCounts {
+    /// The number of incoming connections.
+    pub fn num_incoming(&self) -> u32 {
+        self.incoming
+    }
+
+    /// The number of outgoing connections.
+    pub fn num_outgoing(&self) -> u32 {
+        self.outgoing
+    }
+
+    /// The total number of connections, both incoming and outgoing.
+    pub fn num_connections(&self) -> u32 {
+        self.incoming + self.outgoing
+    }
+
+    fn inc(&mut self, is_dialer: bool) {
+        if is_dialer {
+            self.outgoing += 1;
+        } else {
+            self.incoming += 1;
+        }
+    }
+}
+
 /// Network connection information.
 #[derive(Debug, Clone)]
 pub struct ConnectionCounters {
@@ -959,14 +1286,31 @@ pub(crate) struct PoolConfig {
     pub(crate) per_connection_event_buffer_size: usize,
     /// Number of addresses concurrently dialed for a single outbound connection attempt.
     pub(crate) dial_concurrency_factor: NonZeroU8,
+    /// Whether to report each failed address of an outbound connection attempt.
+    pub(crate) report_dial_attempt_failures: bool,
     /// How long a connection should be kept alive once it is idling.
     pub(crate) idle_connection_timeout: Duration,
+    /// How long a pending connection may take to be established, if bounded.
+    pub(crate) handshake_timeout: Option<Duration>,
+    /// Upgrade timeout overriding the one of each [`SubstreamProtocol`](crate::SubstreamProtocol), if any.
+    pub(crate) substream_upgrade_timeout: Option<Duration>,
+    /// Prefix of all protocol names negotiated on connections, if any.
+    pub(crate) protocol_prefix: Option<Arc<str>>,
+    /// See [`ConnectionConfig::max_negotiated_protocols`].
+    pub(crate) max_negotiated_protocols: Option<usize>,
+    /// Whether to count the bytes transferred over the streams of each connection.
+    pub(crate) connection_counters: bool,
+    /// How long a connection may go without a successful read before
+    /// [`NotifyHandler::Any`](crate::NotifyHandler::Any) prefers other connections, if enabled.
+    pub(crate) notify_any_read_window: Option<Duration>,
+    /// Source of time of the timers of the connections.
+    pub(crate) clock: Arc<dyn Clock>,
     /// The configured override for substream protocol upgrades, if any.
     substream_upgrade_protocol_override: Option<libp2p_core::upgrade::Version>,
 
     /// The maximum number of inbound streams concurrently negotiating on a connection.
     ///
-    /// See [`Connection::max_negotiating_inbound_streams`].
+    /// See [`ConnectionConfig::max_negotiating_inbound_streams`].
     max_negotiating_inbound_streams: usize,
 }
 
@@ -977,7 +1321,15 @@ impl PoolConfig {
             task_command_buffer_size: 32,
             per_connection_event_buffer_size: 7,
             dial_concurrency_factor: NonZeroU8::new(8).expect("8 > 0"),
+            report_dial_attempt_failures: false,
             idle_connection_timeout: Duration::ZERO,
+            handshake_timeout: None,
+            substream_upgrade_timeout: None,
+            protocol_prefix: None,
+            max_negotiated_protocols: None,
+            connection_counters: false,
+            notify_any_read_window: None,
+            clock: Arc::new(SystemClock),
             substream_upgrade_protocol_override: None,
             max_negotiating_inbound_streams: 128,
         }
@@ -1012,6 +1364,13 @@ impl PoolConfig {
         self
     }
 
+    /// Whether to report each failed address of an outbound connection attempt via
+    /// [`PoolEvent::DialAttemptFailed`], before the outcome of the attempt.
+    pub(crate) fn with_dial_attempt_failure_events(mut self, enabled: bool) -> Self {
+        self.report_dial_attempt_failures = enabled;
+        self
+    }
+
     /// Configures an override for the substream upgrade protocol to use.
     pub(crate) fn with_substream_upgrade_protocol_override(
         mut self,
@@ -1023,7 +1382,7 @@ impl PoolConfig {
 
     /// The maximum number of inbound streams concurrently negotiating on a connection.
     ///
-    /// See [`Connection::max_negotiating_inbound_streams`].
+    /// See [`ConnectionConfig::max_negotiating_inbound_streams`].
     pub(crate) fn with_max_negotiating_inbound_streams(mut self, v: usize) -> Self {
         self.max_negotiating_inbound_streams = v;
         self
@@ -20,13 +20,17 @@
 
 use crate::{transport::TransportError, Multiaddr};
 use futures::{
+    channel::mpsc,
     future::{BoxFuture, Future},
     ready,
     stream::{FuturesUnordered, StreamExt},
+    Sink,
 };
 use libp2p_core::muxing::StreamMuxerBox;
 use libp2p_identity::PeerId;
 use std::{
+    collections::VecDeque,
+    iter,
     num::NonZeroU8,
     pin::Pin,
     task::{Context, Poll},
@@ -40,31 +44,107 @@ type Dial = BoxFuture<
     ),
 >;
 
+/// A failed dial of a [`ConcurrentDial`], reported while other dials may still be in progress.
+pub(crate) struct AttemptFailure {
+    pub(crate) address: Multiaddr,
+    /// A copy of the error, which is also part of the outcome of the [`ConcurrentDial`].
+    ///
+    /// [`std::io::Error`] is not [`Clone`], thus the copy retains only its kind and message.
+    pub(crate) error: TransportError<std::io::Error>,
+    /// Number of addresses still being dialed or yet to be dialed.
+    pub(crate) remaining_addresses: usize,
+}
+
+pub(crate) type AttemptFailureSink =
+    Pin<Box<dyn Sink<AttemptFailure, Error = mpsc::SendError> + Send>>;
+
 pub(crate) struct ConcurrentDial {
     dials: FuturesUnordered<Dial>,
     pending_dials: Box<dyn Iterator<Item = Dial> + Send>,
+    /// Groups of dials that are only started once all dials of the previous groups failed.
+    pending_groups: VecDeque<Vec<Dial>>,
+    concurrency_factor: NonZeroU8,
     errors: Vec<(Multiaddr, TransportError<std::io::Error>)>,
+    /// Number of dials that neither failed nor succeeded yet.
+    remaining: usize,
+    /// Where to report each failed dial to, if anywhere.
+    attempt_failures: Option<AttemptFailureSink>,
+    /// Failed dials not yet sent to [`ConcurrentDial::attempt_failures`].
+    pending_attempt_failures: VecDeque<AttemptFailure>,
 }
 
 impl Unpin for ConcurrentDial {}
 
 impl ConcurrentDial {
-    pub(crate) fn new(pending_dials: Vec<Dial>, concurrency_factor: NonZeroU8) -> Self {
-        let mut pending_dials = pending_dials.into_iter();
+    pub(crate) fn new(
+        dial_groups: Vec<Vec<Dial>>,
+        concurrency_factor: NonZeroU8,
+        attempt_failures: Option<AttemptFailureSink>,
+    ) -> Self {
+        let remaining = dial_groups.iter().map(Vec::len).sum();
+        let mut dial = Self {
+            dials: FuturesUnordered::new(),
+            pending_dials: Box::new(iter::empty()),
+            pending_groups: dial_groups
+                .into_iter()
+                .filter(|group| !group.is_empty())
+                .collect(),
+            concurrency_factor,
+            errors: Default::default(),
+            remaining,
+            attempt_failures,
+            pending_attempt_failures: Default::default(),
+        };
+        dial.start_next_group();
+
+        dial
+    }
 
-        let dials = FuturesUnordered::new();
+    /// Starts dialing the next group, returning `false` if there is none left.
+    fn start_next_group(&mut self) -> bool {
+        let Some(group) = self.pending_groups.pop_front() else {
+            return false;
+        };
+
+        let mut pending_dials = group.into_iter();
         for dial in pending_dials.by_ref() {
-            dials.push(dial);
-            if dials.len() == concurrency_factor.get() as usize {
+            self.dials.push(dial);
+            if self.dials.len() == self.concurrency_factor.get() as usize {
                 break;
             }
         }
+        self.pending_dials = Box::new(pending_dials);
 
-        Self {
-            dials,
-            errors: Default::default(),
-            pending_dials: Box::new(pending_dials),
+        true
+    }
+
+    /// Sends all pending failed dials to [`ConcurrentDial::attempt_failures`].
+    fn poll_report_attempt_failures(&mut self, cx: &mut Context) -> Poll<()> {
+        let Some(sink) = self.attempt_failures.as_mut() else {
+            return Poll::Ready(());
+        };
+        let result = loop {
+            if self.pending_attempt_failures.is_empty() {
+                break ready!(sink.as_mut().poll_flush(cx));
+            }
+            if let Err(e) = ready!(sink.as_mut().poll_ready(cx)) {
+                break Err(e);
+            }
+            let failure = self
+                .pending_attempt_failures
+                .pop_front()
+                .expect("not empty");
+            if let Err(e) = sink.as_mut().start_send(failure) {
+                break Err(e);
+            }
+        };
+        if result.is_err() {
+            // The receiver is gone, no one is interested in failed dials anymore.
+            self.attempt_failures = None;
+            self.pending_attempt_failures.clear();
         }
+
+        Poll::Ready(())
     }
 }
 
@@ -83,21 +163,48 @@ impl Future for ConcurrentDial {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         loop {
+            // Report failed dials before the outcome of the whole dial.
+            ready!(self.poll_report_attempt_failures(cx));
+
             match ready!(self.dials.poll_next_unpin(cx)) {
                 Some((addr, Ok(output))) => {
                     let errors = std::mem::take(&mut self.errors);
                     return Poll::Ready(Ok((addr, output, errors)));
                 }
                 Some((addr, Err(e))) => {
+                    self.remaining -= 1;
+                    if self.attempt_failures.is_some() {
+                        let failure = AttemptFailure {
+                            address: addr.clone(),
+                            error: copy_transport_error(&e),
+                            remaining_addresses: self.remaining,
+                        };
+                        self.pending_attempt_failures.push_back(failure);
+                    }
                     self.errors.push((addr, e));
                     if let Some(dial) = self.pending_dials.next() {
                         self.dials.push(dial)
                     }
                 }
                 None => {
+                    if self.start_next_group() {
+                        continue;
+                    }
+
                     return Poll::Ready(Err(std::mem::take(&mut self.errors)));
                 }
             }
         }
     }
 }
+
+fn copy_transport_error(error: &TransportError<std::io::Error>) -> TransportError<std::io::Error> {
+    match error {
+        TransportError::MultiaddrNotSupported(addr) => {
+            TransportError::MultiaddrNotSupported(addr.clone())
+        }
+        TransportError::Other(e) => {
+            TransportError::Other(std::io::Error::new(e.kind(), e.to_string()))
+        }
+    }
+}
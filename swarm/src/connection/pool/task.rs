@@ -21,24 +21,31 @@
 
 //! Async functions driving pending and established connections in the form of a task.
 
-use super::concurrent_dial::ConcurrentDial;
+use super::concurrent_dial::{AttemptFailure, ConcurrentDial};
 use crate::{
     connection::{
         self, ConnectionError, ConnectionId, PendingInboundConnectionError,
         PendingOutboundConnectionError,
     },
     transport::TransportError,
-    ConnectionHandler, Multiaddr, PeerId,
+    ConnectionHandler, Multiaddr, PeerId, StreamCounts,
 };
 use futures::{
     channel::{mpsc, oneshot},
     future::{poll_fn, Either, Future},
     SinkExt, StreamExt,
 };
+use futures_timer::Delay;
 use libp2p_core::muxing::StreamMuxerBox;
 use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
 use void::Void;
 
+/// The maximum number of events of a connection handler delivered to the [`Pool`](super::Pool)
+/// at once.
+const MAX_HANDLER_EVENT_BATCH: usize = 32;
+
 /// Commands that can be sent to a task driving an established connection.
 #[derive(Debug)]
 pub(crate) enum Command<T> {
@@ -58,6 +65,11 @@ pub(crate) enum PendingConnectionEvent {
         /// of dial attempts that failed before the one successful dial.
         outgoing: Option<(Multiaddr, Vec<(Multiaddr, TransportError<std::io::Error>)>)>,
     },
+    /// Dialing one of the addresses of a pending outbound connection failed.
+    DialAttemptFailed {
+        id: ConnectionId,
+        failure: AttemptFailure,
+    },
     /// A pending connection failed.
     PendingFailed {
         id: ConnectionId,
@@ -73,11 +85,21 @@ pub(crate) enum EstablishedConnectionEvent<ToBehaviour> {
         peer_id: PeerId,
         new_address: Multiaddr,
     },
-    /// Notify the manager of an event from the connection.
+    /// The muxer refused to open a new outbound stream.
+    StreamLimitReached {
+        id: ConnectionId,
+        peer_id: PeerId,
+        counts: StreamCounts,
+    },
+    /// The first substream of the connection has been fully negotiated.
+    FirstSubstreamNegotiated { id: ConnectionId, peer_id: PeerId },
+    /// Notify the manager of events from the connection, in the order they were emitted.
+    ///
+    /// Never empty.
     Notify {
         id: ConnectionId,
         peer_id: PeerId,
-        event: ToBehaviour,
+        events: Vec<ToBehaviour>,
     },
     /// A connection closed, possibly due to an error.
     ///
@@ -87,6 +109,8 @@ pub(crate) enum EstablishedConnectionEvent<ToBehaviour> {
         id: ConnectionId,
         peer_id: PeerId,
         error: Option<ConnectionError>,
+        /// The protocols tried if no substream could be negotiated on the connection.
+        negotiation_failure: Option<Vec<String>>,
     },
 }
 
@@ -94,9 +118,19 @@ pub(crate) async fn new_for_pending_outgoing_connection(
     connection_id: ConnectionId,
     dial: ConcurrentDial,
     abort_receiver: oneshot::Receiver<Void>,
+    delay: Option<Duration>,
+    handshake_timeout: Option<Duration>,
     mut events: mpsc::Sender<PendingConnectionEvent>,
 ) {
-    match futures::future::select(abort_receiver, Box::pin(dial)).await {
+    let delayed_dial = async move {
+        if let Some(delay) = delay {
+            Delay::new(delay).await;
+        }
+
+        with_deadline(dial, handshake_timeout).await
+    };
+
+    match futures::future::select(abort_receiver, Box::pin(delayed_dial)).await {
         Either::Left((Err(oneshot::Canceled), _)) => {
             let _ = events
                 .send(PendingConnectionEvent::PendingFailed {
@@ -106,7 +140,15 @@ pub(crate) async fn new_for_pending_outgoing_connection(
                 .await;
         }
         Either::Left((Ok(v), _)) => void::unreachable(v),
-        Either::Right((Ok((address, output, errors)), _)) => {
+        Either::Right((None, _)) => {
+            let _ = events
+                .send(PendingConnectionEvent::PendingFailed {
+                    id: connection_id,
+                    error: Either::Left(PendingOutboundConnectionError::Timeout),
+                })
+                .await;
+        }
+        Either::Right((Some(Ok((address, output, errors))), _)) => {
             let _ = events
                 .send(PendingConnectionEvent::ConnectionEstablished {
                     id: connection_id,
@@ -115,7 +157,7 @@ pub(crate) async fn new_for_pending_outgoing_connection(
                 })
                 .await;
         }
-        Either::Right((Err(e), _)) => {
+        Either::Right((Some(Err(e)), _)) => {
             let _ = events
                 .send(PendingConnectionEvent::PendingFailed {
                     id: connection_id,
@@ -130,11 +172,17 @@ pub(crate) async fn new_for_pending_incoming_connection<TFut>(
     connection_id: ConnectionId,
     future: TFut,
     abort_receiver: oneshot::Receiver<Void>,
+    handshake_timeout: Option<Duration>,
     mut events: mpsc::Sender<PendingConnectionEvent>,
 ) where
     TFut: Future<Output = Result<(PeerId, StreamMuxerBox), std::io::Error>> + Send + 'static,
 {
-    match futures::future::select(abort_receiver, Box::pin(future)).await {
+    match futures::future::select(
+        abort_receiver,
+        Box::pin(with_deadline(future, handshake_timeout)),
+    )
+    .await
+    {
         Either::Left((Err(oneshot::Canceled), _)) => {
             let _ = events
                 .send(PendingConnectionEvent::PendingFailed {
@@ -144,7 +192,15 @@ pub(crate) async fn new_for_pending_incoming_connection<TFut>(
                 .await;
         }
         Either::Left((Ok(v), _)) => void::unreachable(v),
-        Either::Right((Ok(output), _)) => {
+        Either::Right((None, _)) => {
+            let _ = events
+                .send(PendingConnectionEvent::PendingFailed {
+                    id: connection_id,
+                    error: Either::Right(PendingInboundConnectionError::Timeout),
+                })
+                .await;
+        }
+        Either::Right((Some(Ok(output)), _)) => {
             let _ = events
                 .send(PendingConnectionEvent::ConnectionEstablished {
                     id: connection_id,
@@ -153,7 +209,7 @@ pub(crate) async fn new_for_pending_incoming_connection<TFut>(
                 })
                 .await;
         }
-        Either::Right((Err(e), _)) => {
+        Either::Right((Some(Err(e)), _)) => {
             let _ = events
                 .send(PendingConnectionEvent::PendingFailed {
                     id: connection_id,
@@ -166,6 +222,18 @@ pub(crate) async fn new_for_pending_incoming_connection<TFut>(
     }
 }
 
+/// Resolves to [`None`] if the given future does not complete within the timeout, if any.
+async fn with_deadline<F: Future>(future: F, timeout: Option<Duration>) -> Option<F::Output> {
+    let Some(timeout) = timeout else {
+        return Some(future.await);
+    };
+
+    match futures::future::select(Box::pin(future), Delay::new(timeout)).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(((), _)) => None,
+    }
+}
+
 pub(crate) async fn new_for_established_connection<THandler>(
     connection_id: ConnectionId,
     peer_id: PeerId,
@@ -175,91 +243,155 @@ pub(crate) async fn new_for_established_connection<THandler>(
 ) where
     THandler: ConnectionHandler,
 {
+    // An event of the connection polled while collecting a batch of handler events.
+    let mut deferred = None;
+
     loop {
-        match futures::future::select(
-            command_receiver.next(),
-            poll_fn(|cx| Pin::new(&mut connection).poll(cx)),
-        )
-        .await
-        {
-            Either::Left((Some(command), _)) => match command {
-                Command::NotifyHandler(event) => connection.on_behaviour_event(event),
-                Command::Close => {
-                    command_receiver.close();
-                    let (remaining_events, closing_muxer) = connection.close();
+        let event = match deferred.take() {
+            Some(event) => event,
+            None => match futures::future::select(
+                command_receiver.next(),
+                poll_fn(|cx| Pin::new(&mut connection).poll(cx)),
+            )
+            .await
+            {
+                Either::Left((Some(command), _)) => {
+                    let mut next = Some(command);
+                    while let Some(command) = next.take() {
+                        match command {
+                            Command::NotifyHandler(event) => {
+                                connection.on_behaviour_event(event);
+                                // Deliver all events queued in the meantime in one go, instead of
+                                // polling the connection in between each of them.
+                                next = command_receiver.try_next().ok().flatten();
+                            }
+                            Command::Close => {
+                                command_receiver.close();
+                                let negotiation_failure = connection.negotiation_failure();
+                                let (remaining_events, closing_muxer) = connection.close();
 
-                    let _ = events
-                        .send_all(&mut remaining_events.map(|event| {
-                            Ok(EstablishedConnectionEvent::Notify {
-                                id: connection_id,
-                                event,
-                                peer_id,
-                            })
-                        }))
-                        .await;
+                                send_remaining_events(
+                                    &mut events,
+                                    connection_id,
+                                    peer_id,
+                                    remaining_events,
+                                )
+                                .await;
 
-                    let error = closing_muxer.await.err().map(ConnectionError::IO);
+                                let error = closing_muxer.await.err().map(ConnectionError::IO);
 
-                    let _ = events
-                        .send(EstablishedConnectionEvent::Closed {
-                            id: connection_id,
-                            peer_id,
-                            error,
-                        })
-                        .await;
-                    return;
+                                let _ = events
+                                    .send(EstablishedConnectionEvent::Closed {
+                                        id: connection_id,
+                                        peer_id,
+                                        error,
+                                        negotiation_failure,
+                                    })
+                                    .await;
+                                return;
+                            }
+                        }
+                    }
+                    continue;
                 }
-            },
 
-            // The manager has disappeared; abort.
-            Either::Left((None, _)) => return,
+                // The manager has disappeared; abort.
+                Either::Left((None, _)) => return,
 
-            Either::Right((event, _)) => {
-                match event {
-                    Ok(connection::Event::Handler(event)) => {
-                        let _ = events
-                            .send(EstablishedConnectionEvent::Notify {
-                                id: connection_id,
-                                peer_id,
-                                event,
-                            })
-                            .await;
-                    }
-                    Ok(connection::Event::AddressChange(new_address)) => {
-                        let _ = events
-                            .send(EstablishedConnectionEvent::AddressChange {
-                                id: connection_id,
-                                peer_id,
-                                new_address,
-                            })
-                            .await;
-                    }
-                    Err(error) => {
-                        command_receiver.close();
-                        let (remaining_events, _closing_muxer) = connection.close();
+                Either::Right((event, _)) => event,
+            },
+        };
 
-                        let _ = events
-                            .send_all(&mut remaining_events.map(|event| {
-                                Ok(EstablishedConnectionEvent::Notify {
-                                    id: connection_id,
-                                    event,
-                                    peer_id,
-                                })
-                            }))
-                            .await;
+        match event {
+            Ok(connection::Event::Handler(event)) => {
+                let mut batch = vec![event];
 
-                        // Terminate the task with the error, dropping the connection.
-                        let _ = events
-                            .send(EstablishedConnectionEvent::Closed {
-                                id: connection_id,
-                                peer_id,
-                                error: Some(error),
-                            })
-                            .await;
-                        return;
+                // Collect the events the handler emits without waiting and deliver them in one
+                // go, waking up the `Swarm` once per batch instead of once per event.
+                while batch.len() < MAX_HANDLER_EVENT_BATCH {
+                    match poll_fn(|cx| Poll::Ready(Pin::new(&mut connection).poll(cx))).await {
+                        Poll::Ready(Ok(connection::Event::Handler(event))) => batch.push(event),
+                        Poll::Ready(event) => {
+                            deferred = Some(event);
+                            break;
+                        }
+                        Poll::Pending => break,
                     }
                 }
+
+                let _ = events
+                    .send(EstablishedConnectionEvent::Notify {
+                        id: connection_id,
+                        peer_id,
+                        events: batch,
+                    })
+                    .await;
+            }
+            Ok(connection::Event::AddressChange(new_address)) => {
+                let _ = events
+                    .send(EstablishedConnectionEvent::AddressChange {
+                        id: connection_id,
+                        peer_id,
+                        new_address,
+                    })
+                    .await;
+            }
+            Ok(connection::Event::StreamLimitReached(counts)) => {
+                let _ = events
+                    .send(EstablishedConnectionEvent::StreamLimitReached {
+                        id: connection_id,
+                        peer_id,
+                        counts,
+                    })
+                    .await;
+            }
+            Ok(connection::Event::FirstSubstreamNegotiated) => {
+                let _ = events
+                    .send(EstablishedConnectionEvent::FirstSubstreamNegotiated {
+                        id: connection_id,
+                        peer_id,
+                    })
+                    .await;
+            }
+            Err(error) => {
+                command_receiver.close();
+                let negotiation_failure = connection.negotiation_failure();
+                let (remaining_events, _closing_muxer) = connection.close();
+
+                send_remaining_events(&mut events, connection_id, peer_id, remaining_events).await;
+
+                // Terminate the task with the error, dropping the connection.
+                let _ = events
+                    .send(EstablishedConnectionEvent::Closed {
+                        id: connection_id,
+                        peer_id,
+                        error: Some(error),
+                        negotiation_failure,
+                    })
+                    .await;
+                return;
             }
         }
     }
 }
+
+/// Delivers the events a closing connection handler emitted, if any.
+async fn send_remaining_events<ToBehaviour>(
+    events: &mut mpsc::Sender<EstablishedConnectionEvent<ToBehaviour>>,
+    connection_id: ConnectionId,
+    peer_id: PeerId,
+    remaining_events: impl futures::Stream<Item = ToBehaviour>,
+) {
+    let remaining_events = remaining_events.collect::<Vec<_>>().await;
+    if remaining_events.is_empty() {
+        return;
+    }
+
+    let _ = events
+        .send(EstablishedConnectionEvent::Notify {
+            id: connection_id,
+            peer_id,
+            events: remaining_events,
+        })
+        .await;
+}
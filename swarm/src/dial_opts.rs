@@ -22,9 +22,11 @@
 use crate::ConnectionId;
 use libp2p_core::connection::Endpoint;
 use libp2p_core::multiaddr::Protocol;
+use libp2p_core::multiaddr_ext::MultiaddrExt;
 use libp2p_core::Multiaddr;
 use libp2p_identity::PeerId;
 use std::num::NonZeroU8;
+use std::time::Duration;
 
 /// Options to configure a dial to a known or unknown peer.
 ///
@@ -44,6 +46,12 @@ pub struct DialOpts {
     extend_addresses_through_behaviour: bool,
     role_override: Endpoint,
     dial_concurrency_factor_override: Option<NonZeroU8>,
+    address_order: AddressOrder,
+    relay_only: bool,
+    delay: Option<Duration>,
+    timeout: Option<Duration>,
+    label: Option<String>,
+    source_port: Option<u16>,
     connection_id: ConnectionId,
 }
 
@@ -65,6 +73,12 @@ impl DialOpts {
             condition: Default::default(),
             role_override: Endpoint::Dialer,
             dial_concurrency_factor_override: Default::default(),
+            address_order: Default::default(),
+            relay_only: false,
+            delay: None,
+            timeout: None,
+            label: None,
+            source_port: None,
         }
     }
 
@@ -124,6 +138,30 @@ impl DialOpts {
     pub(crate) fn role_override(&self) -> Endpoint {
         self.role_override
     }
+
+    pub(crate) fn address_order(&self) -> AddressOrder {
+        self.address_order
+    }
+
+    pub(crate) fn relay_only(&self) -> bool {
+        self.relay_only
+    }
+
+    pub(crate) fn delay(&self) -> Option<Duration> {
+        self.delay
+    }
+
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub(crate) fn label(&self) -> Option<String> {
+        self.label.clone()
+    }
+
+    pub(crate) fn source_port(&self) -> Option<u16> {
+        self.source_port
+    }
 }
 
 impl From<Multiaddr> for DialOpts {
@@ -144,6 +182,12 @@ pub struct WithPeerId {
     condition: PeerCondition,
     role_override: Endpoint,
     dial_concurrency_factor_override: Option<NonZeroU8>,
+    address_order: AddressOrder,
+    relay_only: bool,
+    delay: Option<Duration>,
+    timeout: Option<Duration>,
+    label: Option<String>,
+    source_port: Option<u16>,
 }
 
 impl WithPeerId {
@@ -160,6 +204,65 @@ impl WithPeerId {
         self
     }
 
+    /// Specify the [`AddressOrder`] in which the addresses of the peer are dialed.
+    pub fn address_order(mut self, order: AddressOrder) -> Self {
+        self.address_order = order;
+        self
+    }
+
+    /// Only dial relayed addresses, i.e. addresses containing a `/p2p-circuit` component.
+    ///
+    /// Direct addresses are discarded, thus the local node never reveals its IP address to the
+    /// peer. Fails with [`DialError::NoAddresses`](crate::DialError::NoAddresses) if no relayed
+    /// address is available.
+    pub fn relay_only(mut self) -> Self {
+        self.relay_only = true;
+        self
+    }
+
+    /// Hold the dial for the given duration before starting it.
+    ///
+    /// Staggering dials avoids bursts, e.g. when redialing many peers at once after a network
+    /// outage. The held dial counts as a pending connection and can be aborted, e.g. via
+    /// [`Swarm::disconnect_peer_id`](crate::Swarm::disconnect_peer_id).
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Abort the dial, i.e. the attempts to all addresses including their upgrades, if no
+    /// connection is established within the given duration.
+    ///
+    /// The dial fails with [`DialError::Timeout`](crate::DialError::Timeout). The timeout applies
+    /// in addition to [`Config::with_handshake_timeout`](crate::Config::with_handshake_timeout),
+    /// the earlier of the two aborts the dial. It starts once a held dial, see `with_delay`, is
+    /// started.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Label the connection, e.g. with `"bootstrap"`, to tell it apart in
+    /// [`SwarmEvent::ConnectionEstablished`](crate::SwarmEvent::ConnectionEstablished) and via
+    /// [`Swarm::connection_label`](crate::Swarm::connection_label).
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Bind the socket of the dial to the given local port, e.g. to reuse the port of a listener
+    /// for NAT traversal.
+    ///
+    /// Only honoured by transports that support port reuse, e.g. TCP with port reuse enabled and
+    /// QUIC with a listener bound to the port, see
+    /// [`Transport::dial_from_port`](libp2p_core::Transport::dial_from_port). Dialing an address
+    /// of any other transport fails, as does every dial with an overridden role, see
+    /// `override_role`.
+    pub fn source_port(mut self, port: u16) -> Self {
+        self.source_port = Some(port);
+        self
+    }
+
     /// Specify a set of addresses to be used to dial the known peer.
     pub fn addresses(self, addresses: Vec<Multiaddr>) -> WithPeerIdWithAddresses {
         WithPeerIdWithAddresses {
@@ -169,6 +272,12 @@ impl WithPeerId {
             extend_addresses_through_behaviour: false,
             role_override: self.role_override,
             dial_concurrency_factor_override: self.dial_concurrency_factor_override,
+            address_order: self.address_order,
+            relay_only: self.relay_only,
+            delay: self.delay,
+            timeout: self.timeout,
+            label: self.label,
+            source_port: self.source_port,
         }
     }
 
@@ -192,6 +301,12 @@ impl WithPeerId {
             extend_addresses_through_behaviour: true,
             role_override: self.role_override,
             dial_concurrency_factor_override: self.dial_concurrency_factor_override,
+            address_order: self.address_order,
+            relay_only: self.relay_only,
+            delay: self.delay,
+            timeout: self.timeout,
+            label: self.label,
+            source_port: self.source_port,
             connection_id: ConnectionId::next(),
         }
     }
@@ -205,6 +320,12 @@ pub struct WithPeerIdWithAddresses {
     extend_addresses_through_behaviour: bool,
     role_override: Endpoint,
     dial_concurrency_factor_override: Option<NonZeroU8>,
+    address_order: AddressOrder,
+    relay_only: bool,
+    delay: Option<Duration>,
+    timeout: Option<Duration>,
+    label: Option<String>,
+    source_port: Option<u16>,
 }
 
 impl WithPeerIdWithAddresses {
@@ -239,6 +360,56 @@ impl WithPeerIdWithAddresses {
         self
     }
 
+    /// Specify the [`AddressOrder`] in which the addresses of the peer are dialed.
+    ///
+    /// See [`WithPeerId::address_order`].
+    pub fn address_order(mut self, order: AddressOrder) -> Self {
+        self.address_order = order;
+        self
+    }
+
+    /// Only dial relayed addresses, i.e. addresses containing a `/p2p-circuit` component.
+    ///
+    /// Direct addresses are discarded, thus the local node never reveals its IP address to the
+    /// peer. Fails with [`DialError::NoAddresses`](crate::DialError::NoAddresses) if no relayed
+    /// address is available.
+    pub fn relay_only(mut self) -> Self {
+        self.relay_only = true;
+        self
+    }
+
+    /// Hold the dial for the given duration before starting it.
+    ///
+    /// See [`WithPeerId::with_delay`].
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Abort the dial if no connection is established within the given duration.
+    ///
+    /// See [`WithPeerId::timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Label the connection.
+    ///
+    /// See [`WithPeerId::label`].
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Bind the socket of the dial to the given local port.
+    ///
+    /// See [`WithPeerId::source_port`].
+    pub fn source_port(mut self, port: u16) -> Self {
+        self.source_port = Some(port);
+        self
+    }
+
     /// Build the final [`DialOpts`].
     pub fn build(self) -> DialOpts {
         DialOpts {
@@ -248,6 +419,12 @@ impl WithPeerIdWithAddresses {
             extend_addresses_through_behaviour: self.extend_addresses_through_behaviour,
             role_override: self.role_override,
             dial_concurrency_factor_override: self.dial_concurrency_factor_override,
+            address_order: self.address_order,
+            relay_only: self.relay_only,
+            delay: self.delay,
+            timeout: self.timeout,
+            label: self.label,
+            source_port: self.source_port,
             connection_id: ConnectionId::next(),
         }
     }
@@ -262,6 +439,10 @@ impl WithoutPeerId {
         WithoutPeerIdWithAddress {
             address,
             role_override: Endpoint::Dialer,
+            delay: None,
+            timeout: None,
+            label: None,
+            source_port: None,
         }
     }
 }
@@ -270,6 +451,10 @@ impl WithoutPeerId {
 pub struct WithoutPeerIdWithAddress {
     address: Multiaddr,
     role_override: Endpoint,
+    delay: Option<Duration>,
+    timeout: Option<Duration>,
+    label: Option<String>,
+    source_port: Option<u16>,
 }
 
 impl WithoutPeerIdWithAddress {
@@ -283,6 +468,39 @@ impl WithoutPeerIdWithAddress {
         self.role_override = Endpoint::Listener;
         self
     }
+
+    /// Hold the dial for the given duration before starting it.
+    ///
+    /// See [`WithPeerId::with_delay`].
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Abort the dial if no connection is established within the given duration.
+    ///
+    /// See [`WithPeerId::timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Label the connection.
+    ///
+    /// See [`WithPeerId::label`].
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Bind the socket of the dial to the given local port.
+    ///
+    /// See [`WithPeerId::source_port`].
+    pub fn source_port(mut self, port: u16) -> Self {
+        self.source_port = Some(port);
+        self
+    }
+
     /// Build the final [`DialOpts`].
     pub fn build(self) -> DialOpts {
         DialOpts {
@@ -292,6 +510,12 @@ impl WithoutPeerIdWithAddress {
             extend_addresses_through_behaviour: false,
             role_override: self.role_override,
             dial_concurrency_factor_override: None,
+            address_order: AddressOrder::AsProvided,
+            relay_only: false,
+            delay: self.delay,
+            timeout: self.timeout,
+            label: self.label,
+            source_port: self.source_port,
             connection_id: ConnectionId::next(),
         }
     }
@@ -327,3 +551,42 @@ pub enum PeerCondition {
     /// configured connection limits.
     Always,
 }
+
+/// The order in which the addresses of a peer are dialed.
+///
+/// ```
+/// # use libp2p_swarm::dial_opts::{AddressOrder, DialOpts};
+/// # use libp2p_identity::PeerId;
+/// #
+/// DialOpts::peer_id(PeerId::random())
+///    .addresses(vec![
+///        "/ip4/127.0.0.1/tcp/1234".parse().unwrap(),
+///        "/ip4/127.0.0.1/tcp/4321/p2p/12D3KooWEz3ZnZJ7D4wTqVSDUdqDRLXb6Ds9PbNTx7WnNVjKw3YG/p2p-circuit".parse().unwrap(),
+///    ])
+///    .address_order(AddressOrder::DirectFirst)
+///    .build();
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum AddressOrder {
+    /// All addresses are dialed in the order they are provided, only subject to the dial
+    /// concurrency factor.
+    #[default]
+    AsProvided,
+    /// Direct addresses are dialed first. Relayed addresses, i.e. addresses containing a
+    /// `/p2p-circuit` component, are only dialed once all direct addresses failed.
+    DirectFirst,
+}
+
+impl AddressOrder {
+    /// Splits the addresses into groups that are dialed one after the other.
+    pub(crate) fn group(self, addresses: Vec<Multiaddr>) -> Vec<Vec<Multiaddr>> {
+        match self {
+            AddressOrder::AsProvided => vec![addresses],
+            AddressOrder::DirectFirst => {
+                let (relayed, direct) = addresses.into_iter().partition(|a| a.is_relayed());
+
+                vec![direct, relayed]
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use futures::FutureExt;
+use futures_timer::Delay;
+use instant::Instant;
+use libp2p_core::Multiaddr;
+use std::collections::HashMap;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Candidates for external addresses that have not been confirmed yet, see
+/// [`Config::with_candidate_ttl`](crate::Config::with_candidate_ttl).
+///
+/// A candidate expires once the TTL passed since it was last reported, unless it was confirmed in
+/// the meantime.
+#[derive(Debug)]
+pub(crate) struct ExternalAddrCandidates {
+    /// [`None`] if candidates never expire.
+    ttl: Option<Duration>,
+    expires_at: HashMap<Multiaddr, Instant>,
+    /// Fires at the earliest expiry of all candidates, together with that expiry.
+    timer: Option<(Instant, Delay)>,
+}
+
+impl ExternalAddrCandidates {
+    pub(crate) fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            ttl,
+            expires_at: HashMap::new(),
+            timer: None,
+        }
+    }
+
+    /// Tracks a reported candidate, restarting its TTL if it was reported before.
+    pub(crate) fn on_new_candidate(&mut self, addr: Multiaddr, now: Instant) {
+        if let Some(ttl) = self.ttl {
+            self.expires_at.insert(addr, now + ttl);
+        }
+    }
+
+    /// Stops tracking a candidate as it was confirmed.
+    pub(crate) fn on_confirmed(&mut self, addr: &Multiaddr) {
+        self.expires_at.remove(addr);
+    }
+
+    /// Removes and returns a candidate whose TTL has passed at `now`.
+    pub(crate) fn pop_expired(&mut self, now: Instant) -> Option<Multiaddr> {
+        let addr = self
+            .expires_at
+            .iter()
+            .find(|(_, expires_at)| **expires_at <= now)
+            .map(|(addr, _)| addr.clone())?;
+        self.expires_at.remove(&addr);
+
+        Some(addr)
+    }
+
+    pub(crate) fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Multiaddr> {
+        loop {
+            if let Some(addr) = self.pop_expired(Instant::now()) {
+                return Poll::Ready(addr);
+            }
+
+            let Some(next) = self.expires_at.values().min().copied() else {
+                self.timer = None;
+                return Poll::Pending;
+            };
+
+            match &mut self.timer {
+                Some((at, timer)) if *at == next => {
+                    if timer.poll_unpin(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    self.timer = None;
+                }
+                _ => {
+                    let delay = next.saturating_duration_since(Instant::now());
+                    self.timer = Some((next, Delay::new(delay)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(10);
+
+    fn addr(port: u16) -> Multiaddr {
+        format!("/ip4/1.2.3.4/tcp/{port}").parse().unwrap()
+    }
+
+    #[test]
+    fn unconfirmed_candidate_expires_after_ttl() {
+        let mut candidates = ExternalAddrCandidates::new(Some(TTL));
+        let start = Instant::now();
+
+        candidates.on_new_candidate(addr(1), start);
+
+        assert_eq!(candidates.pop_expired(start + TTL / 2), None);
+        assert_eq!(candidates.pop_expired(start + TTL), Some(addr(1)));
+        assert_eq!(candidates.pop_expired(start + TTL * 2), None);
+    }
+
+    #[test]
+    fn confirmation_prevents_expiry() {
+        let mut candidates = ExternalAddrCandidates::new(Some(TTL));
+        let start = Instant::now();
+
+        candidates.on_new_candidate(addr(1), start);
+        candidates.on_new_candidate(addr(2), start);
+        candidates.on_confirmed(&addr(1));
+
+        assert_eq!(candidates.pop_expired(start + TTL), Some(addr(2)));
+        assert_eq!(candidates.pop_expired(start + TTL), None);
+    }
+
+    #[test]
+    fn reporting_again_restarts_ttl() {
+        let mut candidates = ExternalAddrCandidates::new(Some(TTL));
+        let start = Instant::now();
+
+        candidates.on_new_candidate(addr(1), start);
+        candidates.on_new_candidate(addr(1), start + TTL / 2);
+
+        assert_eq!(candidates.pop_expired(start + TTL), None);
+        assert_eq!(candidates.pop_expired(start + TTL + TTL / 2), Some(addr(1)));
+    }
+
+    #[test]
+    fn candidates_never_expire_without_ttl() {
+        let mut candidates = ExternalAddrCandidates::new(None);
+        let start = Instant::now();
+
+        candidates.on_new_candidate(addr(1), start);
+
+        assert_eq!(
+            candidates.pop_expired(start + Duration::from_secs(3600)),
+            None
+        );
+    }
+}
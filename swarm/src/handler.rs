@@ -39,6 +39,7 @@
 //! >           [`NetworkBehaviour`](crate::behaviour::NetworkBehaviour) trait.
 
 pub mod either;
+pub mod logging;
 mod map_in;
 mod map_out;
 pub mod multi;
@@ -60,7 +61,7 @@ use once_cell::sync::Lazy;
 use smallvec::SmallVec;
 use std::collections::hash_map::RandomState;
 use std::collections::hash_set::{Difference, Intersection};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::iter::Peekable;
 use std::{error, fmt, io, task::Context, task::Poll, time::Duration};
 
@@ -463,11 +464,13 @@ pub struct ListenUpgradeError<IOI, IP: InboundUpgradeSend> {
 ///
 /// The inbound substream protocol(s) are defined by [`ConnectionHandler::listen_protocol`]
 /// and the outbound substream protocol(s) by [`ConnectionHandlerEvent::OutboundSubstreamRequest`].
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SubstreamProtocol<TUpgrade, TInfo> {
     upgrade: TUpgrade,
     info: TInfo,
     timeout: Duration,
+    max_inbound_streams: HashMap<StreamProtocol, usize>,
+    preferred_protocols: Vec<StreamProtocol>,
 }
 
 impl<TUpgrade, TInfo> SubstreamProtocol<TUpgrade, TInfo> {
@@ -480,6 +483,8 @@ impl<TUpgrade, TInfo> SubstreamProtocol<TUpgrade, TInfo> {
             upgrade,
             info,
             timeout: Duration::from_secs(10),
+            max_inbound_streams: HashMap::new(),
+            preferred_protocols: Vec::new(),
         }
     }
 
@@ -492,6 +497,8 @@ impl<TUpgrade, TInfo> SubstreamProtocol<TUpgrade, TInfo> {
             upgrade: f(self.upgrade),
             info: self.info,
             timeout: self.timeout,
+            max_inbound_streams: self.max_inbound_streams,
+            preferred_protocols: self.preferred_protocols,
         }
     }
 
@@ -504,6 +511,8 @@ impl<TUpgrade, TInfo> SubstreamProtocol<TUpgrade, TInfo> {
             upgrade: self.upgrade,
             info: f(self.info),
             timeout: self.timeout,
+            max_inbound_streams: self.max_inbound_streams,
+            preferred_protocols: self.preferred_protocols,
         }
     }
 
@@ -513,6 +522,41 @@ impl<TUpgrade, TInfo> SubstreamProtocol<TUpgrade, TInfo> {
         self
     }
 
+    /// Limits the number of concurrent inbound streams of the given protocol on a connection.
+    ///
+    /// Inbound streams negotiating the protocol once `max` of them are open are reset right
+    /// away, without being reported to the [`ConnectionHandler`].
+    /// Only applies to the inbound protocols of [`ConnectionHandler::listen_protocol`].
+    pub fn with_max_inbound_streams(mut self, protocol: StreamProtocol, max: usize) -> Self {
+        self.max_inbound_streams.insert(protocol, max);
+        self
+    }
+
+    /// Proposes the given protocols of the upgrade first, in the given order, when negotiating an
+    /// outbound stream.
+    ///
+    /// The remaining protocols of the upgrade are proposed afterwards, in the order of
+    /// [`UpgradeInfo::protocol_info`](libp2p_core::upgrade::UpgradeInfo::protocol_info).
+    /// Protocols not supported by the upgrade are ignored. Allows e.g. to prefer `/myproto/2.0`
+    /// over `/myproto/1.0` for a single outbound stream.
+    /// Only applies to the outbound protocols of [`ConnectionHandlerEvent::OutboundSubstreamRequest`].
+    pub fn with_preferred_protocols(
+        mut self,
+        protocols: impl IntoIterator<Item = StreamProtocol>,
+    ) -> Self {
+        self.preferred_protocols = protocols.into_iter().collect();
+        self
+    }
+
+    /// Adds all of the given limits, see [`SubstreamProtocol::with_max_inbound_streams`].
+    pub(crate) fn with_all_max_inbound_streams(
+        mut self,
+        limits: impl IntoIterator<Item = (StreamProtocol, usize)>,
+    ) -> Self {
+        self.max_inbound_streams.extend(limits);
+        self
+    }
+
     /// Borrows the contained protocol upgrade.
     pub fn upgrade(&self) -> &TUpgrade {
         &self.upgrade
@@ -528,6 +572,16 @@ impl<TUpgrade, TInfo> SubstreamProtocol<TUpgrade, TInfo> {
         &self.timeout
     }
 
+    /// Borrows the limits on concurrent inbound streams per protocol.
+    pub fn max_inbound_streams(&self) -> &HashMap<StreamProtocol, usize> {
+        &self.max_inbound_streams
+    }
+
+    /// Borrows the protocols to propose first on outbound streams.
+    pub fn preferred_protocols(&self) -> &[StreamProtocol] {
+        &self.preferred_protocols
+    }
+
     /// Converts the substream protocol configuration into the contained upgrade.
     pub fn into_upgrade(self) -> (TUpgrade, TInfo) {
         (self.upgrade, self.info)
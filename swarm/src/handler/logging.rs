@@ -0,0 +1,146 @@
+//! A [`ConnectionHandler`] wrapper logging the stream events of the handler it wraps.
+
+use crate::handler::{
+    ConnectionEvent, ConnectionHandler, ConnectionHandlerEvent, DialUpgradeError,
+    StreamUpgradeError, SubstreamProtocol, UpgradeInfoSend,
+};
+use std::task::{Context, Poll};
+
+/// Wrapper around a [`ConnectionHandler`] that emits a `tracing` event for every stream
+/// requested, negotiated or failed by the inner handler, as well as when the connection closes.
+///
+/// Events are emitted at the `debug` level, failures at the `warn` level, under the target of this
+/// module. They are part of the span of the connection, which carries the remote peer and
+/// address. The closing of individual streams is not visible to a [`ConnectionHandler`], thus not
+/// logged.
+///
+/// Meant for debugging custom protocols, e.g. by wrapping the handler returned by a
+/// [`NetworkBehaviour`](crate::NetworkBehaviour) via [`LoggingHandler::new`].
+#[derive(Debug)]
+pub struct LoggingHandler<THandler> {
+    inner: THandler,
+    closing: bool,
+}
+
+impl<THandler> LoggingHandler<THandler> {
+    /// Wraps the given handler.
+    pub fn new(inner: THandler) -> Self {
+        Self {
+            inner,
+            closing: false,
+        }
+    }
+
+    /// Returns a reference to the inner handler.
+    pub fn inner(&self) -> &THandler {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner handler.
+    pub fn inner_mut(&mut self) -> &mut THandler {
+        &mut self.inner
+    }
+
+    /// Returns the inner handler.
+    pub fn into_inner(self) -> THandler {
+        self.inner
+    }
+}
+
+impl<THandler> ConnectionHandler for LoggingHandler<THandler>
+where
+    THandler: ConnectionHandler,
+{
+    type FromBehaviour = THandler::FromBehaviour;
+    type ToBehaviour = THandler::ToBehaviour;
+    type InboundProtocol = THandler::InboundProtocol;
+    type OutboundProtocol = THandler::OutboundProtocol;
+    type InboundOpenInfo = THandler::InboundOpenInfo;
+    type OutboundOpenInfo = THandler::OutboundOpenInfo;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        self.inner.listen_protocol()
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        self.inner.on_behaviour_event(event);
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        self.inner.connection_keep_alive()
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        let event = self.inner.poll(cx);
+
+        if let Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest { protocol }) = &event {
+            tracing::debug!(
+                protocols = ?protocol_names(protocol.upgrade()),
+                "Requesting outbound stream"
+            );
+        }
+
+        event
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::ToBehaviour>> {
+        if !std::mem::replace(&mut self.closing, true) {
+            tracing::debug!("Closing connection");
+        }
+
+        self.inner.poll_close(cx)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match &event {
+            ConnectionEvent::FullyNegotiatedInbound(_) => {
+                tracing::debug!("Negotiated inbound stream");
+            }
+            ConnectionEvent::FullyNegotiatedOutbound(_) => {
+                tracing::debug!("Negotiated outbound stream");
+            }
+            ConnectionEvent::DialUpgradeError(DialUpgradeError { error, .. }) => match error {
+                StreamUpgradeError::Timeout => {
+                    tracing::warn!("Outbound stream timed out");
+                }
+                StreamUpgradeError::NegotiationFailed => {
+                    tracing::warn!("Outbound stream failed to negotiate a protocol");
+                }
+                StreamUpgradeError::Apply(_) => {
+                    tracing::warn!("Outbound stream failed to apply the upgrade");
+                }
+                StreamUpgradeError::Io(error) => {
+                    tracing::warn!(%error, "Outbound stream failed");
+                }
+            },
+            ConnectionEvent::ListenUpgradeError(_) => {
+                tracing::warn!("Inbound stream failed to apply the upgrade");
+            }
+            ConnectionEvent::AddressChange(_)
+            | ConnectionEvent::LocalProtocolsChange(_)
+            | ConnectionEvent::RemoteProtocolsChange(_) => {}
+        }
+
+        self.inner.on_connection_event(event);
+    }
+}
+
+fn protocol_names(upgrade: &impl UpgradeInfoSend) -> Vec<String> {
+    upgrade
+        .protocol_info()
+        .map(|p| p.as_ref().to_owned())
+        .collect()
+}
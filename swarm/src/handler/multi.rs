@@ -117,25 +117,34 @@ where
     type OutboundOpenInfo = (K, <H as ConnectionHandler>::OutboundOpenInfo);
 
     fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
-        let (upgrade, info, timeout) = self
+        let (upgrade, info, timeout, max_inbound_streams) = self
             .handlers
             .iter()
             .map(|(key, handler)| {
                 let proto = handler.listen_protocol();
                 let timeout = *proto.timeout();
+                let max_inbound_streams = proto.max_inbound_streams().clone();
                 let (upgrade, info) = proto.into_upgrade();
-                (key.clone(), (upgrade, info, timeout))
+                (key.clone(), (upgrade, info, timeout, max_inbound_streams))
             })
             .fold(
-                (Upgrade::new(), Info::new(), Duration::from_secs(0)),
-                |(mut upg, mut inf, mut timeout), (k, (u, i, t))| {
+                (
+                    Upgrade::new(),
+                    Info::new(),
+                    Duration::from_secs(0),
+                    HashMap::new(),
+                ),
+                |(mut upg, mut inf, mut timeout, mut max_streams), (k, (u, i, t, m))| {
                     upg.upgrades.push((k.clone(), u));
                     inf.infos.push((k, i));
                     timeout = cmp::max(timeout, t);
-                    (upg, inf, timeout)
+                    max_streams.extend(m);
+                    (upg, inf, timeout, max_streams)
                 },
             );
-        SubstreamProtocol::new(upgrade, info).with_timeout(timeout)
+        SubstreamProtocol::new(upgrade, info)
+            .with_timeout(timeout)
+            .with_all_max_inbound_streams(max_inbound_streams)
     }
 
     fn on_connection_event(
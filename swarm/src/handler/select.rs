@@ -194,10 +194,14 @@ where
         let proto1 = self.proto1.listen_protocol();
         let proto2 = self.proto2.listen_protocol();
         let timeout = *std::cmp::max(proto1.timeout(), proto2.timeout());
+        let mut max_inbound_streams = proto1.max_inbound_streams().clone();
+        max_inbound_streams.extend(proto2.max_inbound_streams().clone());
         let (u1, i1) = proto1.into_upgrade();
         let (u2, i2) = proto2.into_upgrade();
         let choice = SelectUpgrade::new(SendWrapper(u1), SendWrapper(u2));
-        SubstreamProtocol::new(choice, (i1, i2)).with_timeout(timeout)
+        SubstreamProtocol::new(choice, (i1, i2))
+            .with_timeout(timeout)
+            .with_all_max_inbound_streams(max_inbound_streams)
     }
 
     fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
@@ -0,0 +1,72 @@
+use instant::Instant;
+
+/// Limits the rate at which inbound connections are accepted, see
+/// [`Config::with_inbound_connection_rate_limit`](crate::Config::with_inbound_connection_rate_limit).
+///
+/// A token bucket holding up to one second worth of connections, i.e. bursts of up to
+/// `per_second` connections are accepted at once.
+#[derive(Debug)]
+pub(crate) struct InboundRateLimit {
+    per_second: u32,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl InboundRateLimit {
+    pub(crate) fn new(per_second: u32, now: Instant) -> Self {
+        Self {
+            per_second,
+            tokens: f64::from(per_second),
+            last_refill: now,
+        }
+    }
+
+    /// Returns whether an inbound connection may be accepted at `now`, consuming one token if so.
+    pub(crate) fn try_accept(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * f64::from(self.per_second))
+            .min(f64::from(self.per_second));
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn refuses_bursts_beyond_rate() {
+        let start = Instant::now();
+        let mut limit = InboundRateLimit::new(3, start);
+
+        assert!((0..3).all(|_| limit.try_accept(start)));
+        assert!(!limit.try_accept(start));
+
+        // One token is refilled every third of a second.
+        assert!(!limit.try_accept(start + Duration::from_millis(300)));
+        assert!(limit.try_accept(start + Duration::from_millis(400)));
+        assert!(!limit.try_accept(start + Duration::from_millis(400)));
+
+        // At most one second worth of tokens is refilled.
+        let later = start + Duration::from_secs(10);
+        assert!((0..3).all(|_| limit.try_accept(later)));
+        assert!(!limit.try_accept(later));
+    }
+
+    #[test]
+    fn zero_rate_refuses_all() {
+        let start = Instant::now();
+        let mut limit = InboundRateLimit::new(0, start);
+
+        assert!(!limit.try_accept(start));
+        assert!(!limit.try_accept(start + Duration::from_secs(10)));
+    }
+}
@@ -55,8 +55,11 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+mod clock;
 mod connection;
 mod executor;
+mod external_addr_candidates;
+mod inbound_rate_limit;
 mod stream;
 mod stream_protocol;
 #[cfg(test)]
@@ -68,31 +71,46 @@ pub mod dial_opts;
 pub mod dummy;
 pub mod handler;
 mod listen_opts;
+mod listen_protocol;
+mod listeners_ready;
+mod peer_metadata;
+mod resource_manager;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 /// Bundles all symbols required for the [`libp2p_swarm_derive::NetworkBehaviour`] macro.
 #[doc(hidden)]
 pub mod derive_prelude {
     pub use crate::behaviour::AddressChange;
+    pub use crate::behaviour::ConnectionCloseCompleted;
     pub use crate::behaviour::ConnectionClosed;
     pub use crate::behaviour::ConnectionEstablished;
+    pub use crate::behaviour::ConnectionPayload;
+    pub use crate::behaviour::DialAttemptFailed;
     pub use crate::behaviour::DialFailure;
     pub use crate::behaviour::ExpiredListenAddr;
+    pub use crate::behaviour::ExternalAddrCandidateExpired;
     pub use crate::behaviour::ExternalAddrConfirmed;
     pub use crate::behaviour::ExternalAddrExpired;
     pub use crate::behaviour::FromSwarm;
     pub use crate::behaviour::ListenFailure;
+    pub use crate::behaviour::ListenProtocolsChanged;
     pub use crate::behaviour::ListenerClosed;
     pub use crate::behaviour::ListenerError;
+    pub use crate::behaviour::NegotiationFailed;
     pub use crate::behaviour::NewExternalAddrCandidate;
     pub use crate::behaviour::NewExternalAddrOfPeer;
     pub use crate::behaviour::NewListenAddr;
     pub use crate::behaviour::NewListener;
+    pub use crate::behaviour::PollRotation;
     pub use crate::connection::ConnectionId;
     pub use crate::ConnectionDenied;
     pub use crate::ConnectionHandler;
     pub use crate::ConnectionHandlerSelect;
     pub use crate::DialError;
+    pub use crate::ListenDenied;
     pub use crate::NetworkBehaviour;
+    pub use crate::StreamProtocol;
     pub use crate::THandler;
     pub use crate::THandlerInEvent;
     pub use crate::THandlerOutEvent;
@@ -107,43 +125,57 @@ pub mod derive_prelude {
 }
 
 pub use behaviour::{
-    AddressChange, CloseConnection, ConnectionClosed, DialFailure, ExpiredListenAddr,
-    ExternalAddrExpired, ExternalAddresses, FromSwarm, ListenAddresses, ListenFailure,
-    ListenerClosed, ListenerError, NetworkBehaviour, NewExternalAddrCandidate,
-    NewExternalAddrOfPeer, NewListenAddr, NotifyHandler, PeerAddresses, ToSwarm,
+    AddressChange, CloseConnection, ConnectionCloseCompleted, ConnectionClosed,
+    ConnectionLimitReached, ConnectionPayload, DialAttemptFailed, DialFailure,
+    DirectConnectionUpgraded, ExpiredListenAddr, ExternalAddrCandidateExpired, ExternalAddrExpired,
+    ExternalAddresses, FromSwarm, LimitKind, ListenAddresses, ListenFailure,
+    ListenProtocolsChanged, ListenerClosed, ListenerError, NegotiationFailed, NetworkBehaviour,
+    NewExternalAddrCandidate, NewExternalAddrOfPeer, NewListenAddr, NotifyHandler, PeerAddresses,
+    PollRotation, ToSwarm, UpgradeToDirect,
 };
-pub use connection::pool::ConnectionCounters;
+pub use clock::{Clock, SystemClock, Timer};
+pub use connection::pool::{ConnectionCounters, DirectionCounts};
 pub use connection::{ConnectionError, ConnectionId, SupportedProtocols};
 pub use executor::Executor;
 pub use handler::{
     ConnectionHandler, ConnectionHandlerEvent, ConnectionHandlerSelect, OneShotHandler,
     OneShotHandlerConfig, StreamUpgradeError, SubstreamProtocol,
 };
+pub use libp2p_core::muxing::StreamCounts;
 #[cfg(feature = "macros")]
 pub use libp2p_swarm_derive::NetworkBehaviour;
 pub use listen_opts::ListenOpts;
-pub use stream::Stream;
+pub use listen_protocol::{listen_protocol, ListenProtocol};
+pub use listeners_ready::ListenersReady;
+pub use peer_metadata::PeerMetadata;
+pub use resource_manager::ResourceManager;
+pub use stream::{ConnectionByteCounters, Stream};
 pub use stream_protocol::{InvalidProtocol, StreamProtocol};
 
 use crate::behaviour::ExternalAddrConfirmed;
-use crate::handler::UpgradeInfoSend;
 use connection::pool::{EstablishedConnection, Pool, PoolConfig, PoolEvent};
 use connection::IncomingInfo;
 use connection::{
     PendingConnectionError, PendingInboundConnectionError, PendingOutboundConnectionError,
 };
 use dial_opts::{DialOpts, PeerCondition};
+use external_addr_candidates::ExternalAddrCandidates;
 use futures::{prelude::*, stream::FusedStream};
+use inbound_rate_limit::InboundRateLimit;
+use instant::Instant;
 use libp2p_core::{
     connection::ConnectedPoint,
-    muxing::StreamMuxerBox,
+    multiaddr_ext::{MultiaddrExt, TransportKind},
+    muxing::{StreamMuxer, StreamMuxerBox},
     transport::{self, ListenerId, TransportError, TransportEvent},
+    upgrade::{InboundConnectionUpgrade, Negotiated, OutboundConnectionUpgrade, UpgradeInfo},
     Endpoint, Multiaddr, Transport,
 };
 use libp2p_identity::PeerId;
 use smallvec::SmallVec;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::num::{NonZeroU32, NonZeroU8, NonZeroUsize};
+use std::sync::Arc;
 use std::time::Duration;
 use std::{
     error, fmt, io,
@@ -189,6 +221,12 @@ pub enum SwarmEvent<TBehaviourOutEvent> {
         concurrent_dial_errors: Option<Vec<(Multiaddr, TransportError<io::Error>)>>,
         /// How long it took to establish this connection
         established_in: std::time::Duration,
+        /// The label given via [`DialOpts`] when dialing, or via [`Swarm::set_listener_label`]
+        /// for inbound connections.
+        label: Option<String>,
+        /// Whether we dialed the connection but act as the listener on it, as done for hole
+        /// punching. Shorthand for [`ConnectedPoint::is_role_overridden`] of `endpoint`.
+        role_overridden: bool,
     },
     /// A connection with the given peer has been closed,
     /// possibly as a result of an error.
@@ -245,6 +283,27 @@ pub enum SwarmEvent<TBehaviourOutEvent> {
         /// Error that has been encountered.
         error: DialError,
     },
+    /// Dialing one of the addresses of an outbound connection failed, while other addresses may
+    /// still be tried.
+    ///
+    /// Only reported if enabled via [`Config::with_dial_attempt_failure_events`]. Precedes the
+    /// [`ConnectionEstablished`](SwarmEvent::ConnectionEstablished) or
+    /// [`OutgoingConnectionError`](SwarmEvent::OutgoingConnectionError) of the same connection,
+    /// which again contains the error.
+    DialAttemptFailed {
+        /// Identifier of the connection.
+        connection_id: ConnectionId,
+        /// If known, [`PeerId`] of the peer we try to reach.
+        peer_id: Option<PeerId>,
+        /// The address that failed.
+        address: Multiaddr,
+        /// Error that has been encountered.
+        ///
+        /// [`io::Error`]s are copies, retaining only their kind and message.
+        error: TransportError<io::Error>,
+        /// Number of addresses still being dialed or yet to be dialed.
+        remaining_addresses: usize,
+    },
     /// One of our listeners has reported a new local listening address.
     NewListenAddr {
         /// The listener that is listening on the new address.
@@ -300,6 +359,21 @@ pub enum SwarmEvent<TBehaviourOutEvent> {
     ExternalAddrExpired { address: Multiaddr },
     /// We have discovered a new address of a peer.
     NewExternalAddrOfPeer { peer_id: PeerId, address: Multiaddr },
+    /// The muxer of a connection refused to open a new outbound stream because its maximum number
+    /// of streams is reached, i.e. it returned a
+    /// [`StreamLimitReached`](libp2p_core::muxing::StreamLimitReached) error.
+    ///
+    /// The affected outbound stream request fails with a [`StreamUpgradeError::Io`] reported to
+    /// the [`ConnectionHandler`], while the connection remains open. Any other error of the muxer
+    /// closes the connection.
+    ConnectionStreamLimitReached {
+        /// Identity of the peer the connection is to.
+        peer_id: PeerId,
+        /// Identifier of the connection.
+        connection_id: ConnectionId,
+        /// The streams of the connection at the time the muxer refused the new stream.
+        stream_counts: StreamCounts,
+    },
 }
 
 impl<TBehaviourOutEvent> SwarmEvent<TBehaviourOutEvent> {
@@ -334,22 +408,95 @@ where
     /// handlers.
     behaviour: TBehaviour,
 
-    /// List of protocols that the behaviour says it supports.
-    supported_protocols: SmallVec<[Vec<u8>; 16]>,
+    /// The state of the round-robin polling of the behaviour, see
+    /// [`NetworkBehaviour::poll_with_rotation`].
+    behaviour_poll_rotation: PollRotation,
+
+    /// Payloads of pending connections, returned by the `handle_pending_*_with_payload` callbacks
+    /// of the [`NetworkBehaviour`] and handed to the `handle_established_*_with_payload` ones.
+    pending_connection_payloads: HashMap<ConnectionId, ConnectionPayload>,
 
     confirmed_external_addr: HashSet<Multiaddr>,
 
+    /// Candidates for external addresses not confirmed yet, see [`Config::with_candidate_ttl`].
+    external_addr_candidates: ExternalAddrCandidates,
+
     /// Multiaddresses that our listeners are listening on,
     listened_addrs: HashMap<ListenerId, SmallVec<[Multiaddr; 1]>>,
 
+    /// Listeners refusing new inbound connections, see [`Swarm::pause_listener`].
+    paused_listeners: HashSet<ListenerId>,
+
+    /// Labels of the inbound connections of listeners, see [`Swarm::set_listener_label`].
+    listener_labels: HashMap<ListenerId, String>,
+
+    /// The span of each listener, under which its events are handled.
+    listener_spans: HashMap<ListenerId, tracing::Span>,
+
+    /// The address each listener was requested to listen on.
+    listen_requests: HashMap<ListenerId, Multiaddr>,
+
+    /// Peers banned via [`Swarm::ban_peer`] and when their ban expires.
+    banned_peers: HashMap<PeerId, Instant>,
+
+    /// Consulted before accepting inbound connections, see [`Config::with_resource_manager`].
+    resource_manager: Option<Box<dyn ResourceManager>>,
+
+    /// Limits the rate of accepted inbound connections, see
+    /// [`Config::with_inbound_connection_rate_limit`].
+    inbound_rate_limit: Option<InboundRateLimit>,
+
+    /// Consulted before the transport when translating candidates for external addresses, see
+    /// [`Config::with_address_translator`].
+    address_translator: Option<AddressTranslator>,
+
+    /// The [`ListenProtocol`]s of all [`Swarm::listened_addrs`], sorted and without duplicates.
+    listen_protocols: Vec<ListenProtocol>,
+
+    /// Application-defined metadata of remote peers.
+    peer_metadata: PeerMetadata,
+
+    /// Whether [`FromSwarm::ConnectionEstablished`] is only reported once the first substream of a
+    /// connection has been negotiated.
+    defer_connection_established: bool,
+
+    /// Established connections not yet reported via [`FromSwarm::ConnectionEstablished`].
+    deferred_connections: HashMap<ConnectionId, DeferredConnection<THandlerOutEvent<TBehaviour>>>,
+
     /// Pending event to be delivered to connection handlers
     /// (or dropped if the peer disconnected) before the `behaviour`
     /// can be polled again.
     pending_handler_event: Option<(PeerId, PendingNotifyHandler, THandlerInEvent<TBehaviour>)>,
 
     pending_swarm_events: VecDeque<SwarmEvent<TBehaviour::ToSwarm>>,
+
+    /// Observers of all events returned from the [`Swarm`], see [`Swarm::add_event_observer`].
+    event_observers: Vec<EventObserver<TBehaviour::ToSwarm>>,
+
+    /// Whether the last poll of the [`Swarm`] made progress, see [`Swarm::did_work`].
+    did_work: bool,
+
+    /// The maximum number of [`Swarm::pending_behaviour_commands`] before the behaviour is no
+    /// longer polled, see [`Config::with_max_pending_behaviour_commands`].
+    max_pending_behaviour_commands: Option<usize>,
+
+    /// Pending outgoing connections dialed via [`ToSwarm::Dial`].
+    pending_behaviour_dials: HashSet<ConnectionId>,
+
+    /// Relayed connections to upgrade to a direct connection by peer, see
+    /// [`ToSwarm::UpgradeToDirect`].
+    direct_upgrades: HashMap<PeerId, (ConnectionId, ConnectedPoint)>,
+
+    /// Since when the behaviour is no longer polled because of too many
+    /// [`Swarm::pending_behaviour_commands`], and whether this was logged already.
+    saturated_since: Option<(Instant, bool)>,
 }
 
+/// How long the behaviour has to be held back by too many pending commands before it is logged.
+const SATURATION_LOG_THRESHOLD: Duration = Duration::from_secs(1);
+
+type EventObserver<TOutEvent> = Box<dyn FnMut(&SwarmEvent<TOutEvent>) + Send>;
+
 impl<TBehaviour> Unpin for Swarm<TBehaviour> where TBehaviour: NetworkBehaviour {}
 
 impl<TBehaviour> Swarm<TBehaviour>
@@ -366,17 +513,58 @@ where
     ) -> Self {
         tracing::info!(%local_peer_id);
 
-        Swarm {
+        let mut swarm = Swarm {
             local_peer_id,
             transport,
             pool: Pool::new(local_peer_id, config.pool_config),
             behaviour,
-            supported_protocols: Default::default(),
+            behaviour_poll_rotation: Default::default(),
+            pending_connection_payloads: Default::default(),
             confirmed_external_addr: Default::default(),
+            external_addr_candidates: ExternalAddrCandidates::new(config.candidate_ttl),
             listened_addrs: HashMap::new(),
+            paused_listeners: HashSet::new(),
+            listener_labels: HashMap::new(),
+            listener_spans: HashMap::new(),
+            listen_requests: HashMap::new(),
+            banned_peers: HashMap::new(),
+            resource_manager: config.resource_manager,
+            inbound_rate_limit: config
+                .inbound_connection_rate_limit
+                .map(|per_second| InboundRateLimit::new(per_second, Instant::now())),
+            address_translator: config.address_translator,
+            listen_protocols: Vec::new(),
+            peer_metadata: PeerMetadata::new(config.peer_metadata_grace_period),
+            defer_connection_established: config.defer_connection_established,
+            deferred_connections: HashMap::new(),
             pending_handler_event: None,
             pending_swarm_events: VecDeque::default(),
+            event_observers: Vec::new(),
+            did_work: false,
+            max_pending_behaviour_commands: config.max_pending_behaviour_commands,
+            pending_behaviour_dials: HashSet::new(),
+            direct_upgrades: HashMap::new(),
+            saturated_since: None,
+        };
+
+        for address in config.external_addresses {
+            if !swarm.confirmed_external_addr.contains(&address) {
+                swarm.add_external_address(address);
+            }
         }
+
+        swarm
+    }
+
+    /// Returns the protocols the [`NetworkBehaviour`] supports on inbound streams, sorted and
+    /// without duplicates, e.g. to log which protocols the local node speaks.
+    ///
+    /// See [`NetworkBehaviour::supported_protocols`].
+    pub fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        let mut protocols = self.behaviour.supported_protocols();
+        protocols.sort_unstable_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        protocols.dedup();
+        protocols
     }
 
     /// Returns information about the connections underlying the [`Swarm`].
@@ -386,29 +574,313 @@ where
         NetworkInfo {
             num_peers,
             connection_counters,
+            connections_by_transport: self.pool.established_by_transport(),
+            pending_by_transport: self.pool.pending_by_transport(),
         }
     }
 
+    /// Returns the application-defined [`PeerMetadata`] of remote peers.
+    pub fn peer_metadata_mut(&mut self) -> &mut PeerMetadata {
+        self.peer_metadata.remove_expired(Instant::now());
+        &mut self.peer_metadata
+    }
+
     /// Starts listening on the given address.
     /// Returns an error if the address is not supported.
     ///
     /// Listeners report their new listening addresses as [`SwarmEvent::NewListenAddr`].
     /// Depending on the underlying transport, one listener may have multiple listening addresses.
+    ///
+    /// If a listener was already started for the exact same address, or already listens on it,
+    /// no new listener is started and the ID of the existing listener is returned instead.
+    /// Addresses with a port of 0 always start a new listener, as each is bound to a different
+    /// port.
     pub fn listen_on(&mut self, addr: Multiaddr) -> Result<ListenerId, TransportError<io::Error>> {
+        if let Some(id) = self.existing_listener(&addr) {
+            tracing::debug!(listener=?id, address=%addr, "Already listening on address");
+            return Ok(id);
+        }
+
         let opts = ListenOpts::new(addr);
         let id = opts.listener_id();
         self.add_listener(opts)?;
         Ok(id)
     }
 
+    /// Starts listening on all of the given addresses, in order.
+    ///
+    /// If listening on any of the addresses fails, the listeners started for the preceding
+    /// addresses are removed again and the index of the failing address is returned together with
+    /// the error.
+    ///
+    /// Use [`ListenersReady`] to wait until the returned listeners are ready.
+    pub fn listen_on_many(
+        &mut self,
+        addrs: impl IntoIterator<Item = Multiaddr>,
+    ) -> Result<Vec<ListenerId>, (usize, TransportError<io::Error>)> {
+        let mut listener_ids = Vec::new();
+        let mut started = Vec::new();
+
+        for (i, addr) in addrs.into_iter().enumerate() {
+            let existing = self.existing_listener(&addr);
+
+            match self.listen_on(addr) {
+                Ok(id) => {
+                    listener_ids.push(id);
+                    if existing.is_none() {
+                        started.push(id);
+                    }
+                }
+                Err(e) => {
+                    for id in started {
+                        self.remove_listener(id);
+                    }
+                    return Err((i, e));
+                }
+            }
+        }
+
+        Ok(listener_ids)
+    }
+
+    /// Starts listening on every local network interface with an IP address of the same family as
+    /// the one of `port_template`, which must start with `/ip4/0.0.0.0` or `/ip6/::`.
+    ///
+    /// Unlike listening on the unspecified address directly, this results in one listener per
+    /// interface, each reporting the actual IP address of the interface as listen address. The
+    /// remaining components of `port_template` are kept as is for each listener.
+    ///
+    /// If listening on any of the interfaces fails, the listeners started for the preceding
+    /// interfaces are removed again. Use [`ListenersReady`] to wait until the returned listeners are
+    /// ready.
+    #[cfg(not(any(target_os = "emscripten", target_os = "wasi", target_os = "unknown")))]
+    pub fn listen_on_all_interfaces(
+        &mut self,
+        port_template: Multiaddr,
+    ) -> Result<Vec<ListenerId>, TransportError<io::Error>> {
+        use libp2p_core::multiaddr::Protocol;
+
+        let mut iter = port_template.iter();
+        let is_ipv4 = match iter.next() {
+            Some(Protocol::Ip4(ip)) if ip.is_unspecified() => Some(true),
+            Some(Protocol::Ip6(ip)) if ip.is_unspecified() => Some(false),
+            _ => None,
+        };
+        let rest = iter.collect::<Multiaddr>();
+        let Some(is_ipv4) = is_ipv4 else {
+            return Err(TransportError::MultiaddrNotSupported(port_template));
+        };
+
+        let addrs = if_addrs::get_if_addrs()
+            .map_err(TransportError::Other)?
+            .into_iter()
+            .map(|interface| interface.ip())
+            .filter(|ip| ip.is_ipv4() == is_ipv4)
+            .map(|ip| rest.iter().fold(Multiaddr::from(ip), Multiaddr::with))
+            .collect::<Vec<_>>();
+
+        self.listen_on_many(addrs).map_err(|(_, e)| e)
+    }
+
     /// Remove some listener.
     ///
     /// Returns `true` if there was a listener with this ID, `false`
     /// otherwise.
     pub fn remove_listener(&mut self, listener_id: ListenerId) -> bool {
+        self.listen_requests.remove(&listener_id);
         self.transport.remove_listener(listener_id)
     }
 
+    /// Adopts a connection established outside of the [`Transport`] of the [`Swarm`], e.g. a stdio
+    /// pipe or a socket handed over by a supervisor.
+    ///
+    /// The [`Swarm`] negotiates and applies the `security` upgrade on the raw `stream`, e.g.
+    /// `libp2p_noise::Config`, followed by the `muxer` upgrade, e.g. `libp2p_yamux::Config`, the
+    /// way a [`Transport`] built with [`transport::upgrade::Builder`] does. `endpoint` is the role
+    /// of the local node in these upgrades, thus the remote has to take the opposite role.
+    ///
+    /// The connection is handled like one accepted by a listener on `local_addr` from
+    /// `send_back_addr`, whatever the `endpoint`: it counts towards
+    /// [`Config::with_inbound_connection_rate_limit`], may be refused by the [`ResourceManager`]
+    /// or denied by the [`NetworkBehaviour`] and is reported via
+    /// [`SwarmEvent::IncomingConnection`], followed by [`SwarmEvent::ConnectionEstablished`] or
+    /// [`SwarmEvent::IncomingConnectionError`].
+    pub fn adopt_connection<S, TSec, TSecStream, TSecError, TMux, TMuxer, TMuxError>(
+        &mut self,
+        stream: S,
+        endpoint: Endpoint,
+        security: TSec,
+        muxer: TMux,
+        local_addr: Multiaddr,
+        send_back_addr: Multiaddr,
+    ) -> ConnectionId
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        TSec: InboundConnectionUpgrade<
+                Negotiated<S>,
+                Output = (PeerId, TSecStream),
+                Error = TSecError,
+            > + OutboundConnectionUpgrade<
+                Negotiated<S>,
+                Output = (PeerId, TSecStream),
+                Error = TSecError,
+            > + Send
+            + 'static,
+        <TSec as InboundConnectionUpgrade<Negotiated<S>>>::Future: Send,
+        <TSec as OutboundConnectionUpgrade<Negotiated<S>>>::Future: Send,
+        <TSec as UpgradeInfo>::Info: Send,
+        <<TSec as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+        TSecStream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        TSecError: error::Error + Send + Sync + 'static,
+        TMux: InboundConnectionUpgrade<Negotiated<TSecStream>, Output = TMuxer, Error = TMuxError>
+            + OutboundConnectionUpgrade<Negotiated<TSecStream>, Output = TMuxer, Error = TMuxError>
+            + Send
+            + 'static,
+        <TMux as InboundConnectionUpgrade<Negotiated<TSecStream>>>::Future: Send,
+        <TMux as OutboundConnectionUpgrade<Negotiated<TSecStream>>>::Future: Send,
+        <TMux as UpgradeInfo>::Info: Send,
+        <<TMux as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+        TMuxer: StreamMuxer + Send + 'static,
+        TMuxer::Substream: Send + 'static,
+        TMuxer::Error: Send + Sync + 'static,
+        TMuxError: error::Error + Send + Sync + 'static,
+    {
+        let upgrade = async move {
+            let (peer_id, stream) = apply_upgrade(stream, security, endpoint).await?;
+            let muxer = apply_upgrade(stream, muxer, endpoint).await?;
+
+            Ok((peer_id, StreamMuxerBox::new(muxer)))
+        };
+
+        let connection_id = ConnectionId::next();
+        self.accept_inbound_connection(connection_id, upgrade, local_addr, send_back_addr, None);
+
+        connection_id
+    }
+
+    /// Returns the listener started for or listening on exactly the given address, unless the
+    /// address has a port of 0.
+    fn existing_listener(&self, addr: &Multiaddr) -> Option<ListenerId> {
+        use libp2p_core::multiaddr::Protocol;
+
+        if addr
+            .iter()
+            .any(|p| matches!(p, Protocol::Tcp(0) | Protocol::Udp(0) | Protocol::Memory(0)))
+        {
+            return None;
+        }
+
+        self.listen_requests
+            .iter()
+            .find(|(_, requested)| *requested == addr)
+            .map(|(id, _)| *id)
+            .or_else(|| {
+                self.listened_addrs
+                    .iter()
+                    .find(|(_, addrs)| addrs.contains(addr))
+                    .map(|(id, _)| *id)
+            })
+    }
+
+    /// Pause a listener, refusing new inbound connections on it until it is resumed via
+    /// [`Swarm::resume_listener`].
+    ///
+    /// Unlike [`Swarm::remove_listener`], the listener keeps its addresses and sockets. Inbound
+    /// connections are dropped as soon as they are accepted and reported via
+    /// [`SwarmEvent::IncomingConnectionError`] with [`ListenError::ListenerPaused`].
+    ///
+    /// Returns `false` if the listener was paused already.
+    pub fn pause_listener(&mut self, listener_id: ListenerId) -> bool {
+        self.paused_listeners.insert(listener_id)
+    }
+
+    /// Resume a listener paused via [`Swarm::pause_listener`].
+    ///
+    /// Returns `false` if the listener was not paused.
+    pub fn resume_listener(&mut self, listener_id: ListenerId) -> bool {
+        self.paused_listeners.remove(&listener_id)
+    }
+
+    /// Label the inbound connections accepted by a listener from now on, e.g. to tell them apart
+    /// from the connections of other listeners.
+    ///
+    /// Returns the previous label of the listener, if any. See [`Swarm::connection_label`].
+    pub fn set_listener_label(
+        &mut self,
+        listener_id: ListenerId,
+        label: impl Into<String>,
+    ) -> Option<String> {
+        self.listener_labels.insert(listener_id, label.into())
+    }
+
+    /// Returns the label of a pending or established connection, given when dialing via
+    /// [`DialOpts`] or by its listener via [`Swarm::set_listener_label`].
+    pub fn connection_label(&self, connection_id: ConnectionId) -> Option<&str> {
+        self.pool.connection_label(connection_id)
+    }
+
+    /// Returns whether the last poll of the [`Swarm`] made progress.
+    ///
+    /// This is `false` if the last poll returned [`Poll::Pending`] without any of the behaviour,
+    /// the connections or the transport having made progress, i.e. the swarm is idle until woken
+    /// up again. It is `true` if the last poll returned an event, or handled work internally
+    /// without producing an event.
+    ///
+    /// Allows event loops embedding the [`Swarm`] to tell an idle swarm apart from one that is
+    /// busy, e.g. to detect busy-looping.
+    pub fn did_work(&self) -> bool {
+        self.did_work
+    }
+
+    /// Returns the number of commands of the behaviour still being processed, i.e. the pending
+    /// outgoing connections dialed via [`ToSwarm::Dial`] and the event of a
+    /// [`ToSwarm::NotifyHandler`] waiting for the connection handler to accept it.
+    ///
+    /// See [`Config::with_max_pending_behaviour_commands`].
+    pub fn pending_behaviour_commands(&self) -> usize {
+        self.pending_behaviour_dials.len() + usize::from(self.pending_handler_event.is_some())
+    }
+
+    /// Informs the behaviour if the given connection was denied because of a limit on the number
+    /// of connections.
+    fn report_limit_reached(&mut self, connection_id: ConnectionId, cause: &ConnectionDenied) {
+        if let Some((kind, current, limit)) = cause.limit {
+            self.behaviour
+                .on_swarm_event(FromSwarm::ConnectionLimitReached(ConnectionLimitReached {
+                    connection_id,
+                    kind,
+                    current,
+                    limit,
+                }));
+        }
+    }
+
+    /// Returns whether the behaviour must not be polled for new commands, because of too many
+    /// [`Swarm::pending_behaviour_commands`].
+    fn is_saturated(&mut self) -> bool {
+        let saturated = self
+            .max_pending_behaviour_commands
+            .is_some_and(|max| self.pending_behaviour_commands() >= max);
+        if !saturated {
+            self.saturated_since = None;
+            return false;
+        }
+
+        let (since, logged) = self
+            .saturated_since
+            .get_or_insert_with(|| (Instant::now(), false));
+        if !*logged && since.elapsed() >= SATURATION_LOG_THRESHOLD {
+            tracing::debug!(
+                pending=%self.pending_behaviour_dials.len(),
+                "Behaviour not polled for {:?} because of too many pending commands",
+                since.elapsed()
+            );
+            *logged = true;
+        }
+
+        true
+    }
+
     /// Dial a known or unknown peer.
     ///
     /// See also [`DialOpts`].
@@ -443,6 +915,19 @@ where
         let condition = dial_opts.peer_condition();
         let connection_id = dial_opts.connection_id();
 
+        if let Some(until) = peer_id.and_then(|p| self.banned_until(p)) {
+            let e = DialError::Banned { until };
+
+            self.behaviour
+                .on_swarm_event(FromSwarm::DialFailure(DialFailure {
+                    peer_id,
+                    error: &e,
+                    connection_id,
+                }));
+
+            return Err(e);
+        }
+
         let should_dial = match (condition, peer_id) {
             (_, None) => true,
             (PeerCondition::Always, _) => true,
@@ -466,16 +951,18 @@ where
             return Err(e);
         }
 
-        let addresses = {
+        let (addresses, payload) = {
             let mut addresses_from_opts = dial_opts.get_addresses();
 
-            match self.behaviour.handle_pending_outbound_connection(
-                connection_id,
-                peer_id,
-                addresses_from_opts.as_slice(),
-                dial_opts.role_override(),
-            ) {
-                Ok(addresses) => {
+            let payload = match self
+                .behaviour
+                .handle_pending_outbound_connection_with_payload(
+                    connection_id,
+                    peer_id,
+                    addresses_from_opts.as_slice(),
+                    dial_opts.role_override(),
+                ) {
+                Ok((addresses, payload)) => {
                     if dial_opts.extend_addresses_through_behaviour() {
                         addresses_from_opts.extend(addresses)
                     } else {
@@ -489,8 +976,11 @@ where
                             )
                         }
                     }
+
+                    payload
                 }
                 Err(cause) => {
+                    self.report_limit_reached(connection_id, &cause);
                     let error = DialError::Denied { cause };
 
                     self.behaviour
@@ -502,7 +992,7 @@ where
 
                     return Err(error);
                 }
-            }
+            };
 
             let mut unique_addresses = HashSet::new();
             addresses_from_opts.retain(|addr| {
@@ -510,6 +1000,17 @@ where
                     && unique_addresses.insert(addr.clone())
             });
 
+            if dial_opts.relay_only() {
+                let num_addresses = addresses_from_opts.len();
+                addresses_from_opts.retain(|a| a.is_relayed());
+
+                tracing::debug!(
+                    connection=%connection_id,
+                    discarded_addresses_count=%(num_addresses - addresses_from_opts.len()),
+                    "discarding direct addresses because `DialOpts::relay_only` is set for connection"
+                );
+            }
+
             if addresses_from_opts.is_empty() {
                 let error = DialError::NoAddresses;
                 self.behaviour
@@ -521,52 +1022,98 @@ where
                 return Err(error);
             };
 
-            addresses_from_opts
+            if let Some(peer_id) = peer_id {
+                self.behaviour
+                    .sort_addresses(&peer_id, &mut addresses_from_opts);
+            }
+
+            (addresses_from_opts, payload)
         };
 
-        let dials = addresses
+        let transports = addresses
+            .iter()
+            .filter_map(|a| a.transport_kind())
+            .collect();
+        let dial_groups = dial_opts
+            .address_order()
+            .group(addresses)
             .into_iter()
-            .map(|a| match peer_id.map_or(Ok(a.clone()), |p| a.with_p2p(p)) {
-                Ok(address) => {
-                    let (dial, span) = match dial_opts.role_override() {
-                        Endpoint::Dialer => (
-                            self.transport.dial(address.clone()),
-                            tracing::debug_span!(parent: tracing::Span::none(), "Transport::dial", %address),
-                        ),
-                        Endpoint::Listener => (
-                            self.transport.dial_as_listener(address.clone()),
-                            tracing::debug_span!(parent: tracing::Span::none(), "Transport::dial_as_listener", %address),
-                        ),
-                    };
-                    span.follows_from(tracing::Span::current());
-
-                    match dial {
-                        Ok(fut) => fut
-                            .map(|r| (address, r.map_err(TransportError::Other)))
-                            .instrument(span)
-                            .boxed(),
-                        Err(err) => futures::future::ready((address, Err(err))).boxed(),
-                    }
-                }
-                Err(address) => futures::future::ready((
-                    address.clone(),
-                    Err(TransportError::MultiaddrNotSupported(address)),
-                ))
-                .boxed(),
+            .map(|addresses| {
+                addresses
+                    .into_iter()
+                    .map(|a| match peer_id.map_or(Ok(a.clone()), |p| a.with_p2p(p)) {
+                        Ok(address) => {
+                            let (dial, span) = match (dial_opts.role_override(), dial_opts.source_port()) {
+                                (Endpoint::Dialer, None) => (
+                                    self.transport.dial(address.clone()),
+                                    tracing::debug_span!(parent: tracing::Span::none(), "Transport::dial", %address),
+                                ),
+                                (Endpoint::Dialer, Some(port)) => (
+                                    self.transport.dial_from_port(address.clone(), port),
+                                    tracing::debug_span!(parent: tracing::Span::none(), "Transport::dial_from_port", %address, %port),
+                                ),
+                                (Endpoint::Listener, None) => (
+                                    self.transport.dial_as_listener(address.clone()),
+                                    tracing::debug_span!(parent: tracing::Span::none(), "Transport::dial_as_listener", %address),
+                                ),
+                                (Endpoint::Listener, Some(port)) => (
+                                    Err(TransportError::Other(io::Error::new(
+                                        io::ErrorKind::Unsupported,
+                                        format!("cannot dial from port {port} with an overridden role"),
+                                    ))),
+                                    tracing::Span::none(),
+                                ),
+                            };
+                            span.follows_from(tracing::Span::current());
+
+                            match dial {
+                                Ok(fut) => fut
+                                    .map(|r| (address, r.map_err(TransportError::Other)))
+                                    .instrument(span)
+                                    .boxed(),
+                                Err(err) => futures::future::ready((address, Err(err))).boxed(),
+                            }
+                        }
+                        Err(address) => futures::future::ready((
+                            address.clone(),
+                            Err(TransportError::MultiaddrNotSupported(address)),
+                        ))
+                        .boxed(),
+                    })
+                    .collect()
             })
             .collect();
 
+        if let Some(payload) = payload {
+            self.pending_connection_payloads
+                .insert(connection_id, payload);
+        }
         self.pool.add_outgoing(
-            dials,
+            dial_groups,
             peer_id,
             dial_opts.role_override(),
             dial_opts.dial_concurrency_override(),
+            dial_opts.delay(),
+            dial_opts.timeout(),
             connection_id,
+            transports,
+            dial_opts.label(),
         );
 
         Ok(())
     }
 
+    /// Calls `observer` with every event before it is returned from the [`Swarm`], e.g. to record
+    /// metrics without handling each event manually.
+    ///
+    /// Observers are called in the order they were added.
+    pub fn add_event_observer(
+        &mut self,
+        observer: impl FnMut(&SwarmEvent<TBehaviour::ToSwarm>) + Send + 'static,
+    ) {
+        self.event_observers.push(Box::new(observer));
+    }
+
     /// Returns an iterator that produces the list of addresses we're listening on.
     pub fn listeners(&self) -> impl Iterator<Item = &Multiaddr> {
         self.listened_addrs.values().flatten()
@@ -577,6 +1124,17 @@ where
         &self.local_peer_id
     }
 
+    /// Returns the given address with the local [`PeerId`] appended as a `/p2p` component,
+    /// making it dialable by remote peers.
+    ///
+    /// A trailing `/p2p` component already present in the address is replaced, while the `/p2p`
+    /// component of the relay in a relayed address is kept.
+    pub fn dialable_address(&self, listen_addr: &Multiaddr) -> Multiaddr {
+        listen_addr
+            .without_p2p()
+            .with(libp2p_core::multiaddr::Protocol::P2p(self.local_peer_id))
+    }
+
     /// List all **confirmed** external address for the local node.
     pub fn external_addresses(&self) -> impl Iterator<Item = &Multiaddr> {
         self.confirmed_external_addr.iter()
@@ -596,6 +1154,11 @@ where
             return Err(e);
         }
 
+        let span = tracing::debug_span!(parent: tracing::Span::none(), "listener", id = ?listener_id, address = %addr);
+        span.follows_from(tracing::Span::current());
+        self.listener_spans.insert(listener_id, span);
+        self.listen_requests.insert(listener_id, addr.clone());
+
         self.behaviour
             .on_swarm_event(FromSwarm::NewListener(behaviour::NewListener {
                 listener_id,
@@ -613,6 +1176,7 @@ where
             .on_swarm_event(FromSwarm::ExternalAddrConfirmed(ExternalAddrConfirmed {
                 addr: &a,
             }));
+        self.external_addr_candidates.on_confirmed(&a);
         self.confirmed_external_addr.insert(a);
     }
 
@@ -672,6 +1236,102 @@ where
         false
     }
 
+    /// Returns the inbound and outbound streams currently open on an established connection.
+    ///
+    /// Returns `None` if there is no established connection with the given ID.
+    pub fn connection_stream_counts(&self, connection_id: ConnectionId) -> Option<StreamCounts> {
+        self.pool.stream_counts(connection_id)
+    }
+
+    /// Returns a handle to the number of bytes transferred over the streams of an established
+    /// connection, e.g. to be handed to a [`NetworkBehaviour`] enforcing fair use.
+    ///
+    /// Returns `None` if there is no established connection with the given ID or if counting is
+    /// disabled, see [`Config::with_connection_counters`].
+    pub fn connection_byte_counters(
+        &self,
+        connection_id: ConnectionId,
+    ) -> Option<ConnectionByteCounters> {
+        self.pool.connection_byte_counters(connection_id)
+    }
+
+    /// Returns the priority of an established connection.
+    ///
+    /// Returns `None` if there is no established connection with the given ID.
+    pub fn connection_priority(&self, connection_id: ConnectionId) -> Option<u8> {
+        self.pool.connection_priority(connection_id)
+    }
+
+    /// Sets the priority of an established connection, returning `false` if there is no
+    /// established connection with the given ID.
+    ///
+    /// Events sent via [`NotifyHandler::Any`] are delivered to the connection with the highest
+    /// priority that is ready to receive them. Connections are established with a priority of
+    /// `1`, except for relayed connections, which start with a priority of `0`.
+    pub fn set_connection_priority(&mut self, connection_id: ConnectionId, priority: u8) -> bool {
+        self.pool.set_connection_priority(connection_id, priority)
+    }
+
+    /// Returns the number of addresses of a peer dialed concurrently, see
+    /// [`Config::with_dial_concurrency_factor`].
+    pub fn dial_concurrency_factor(&self) -> NonZeroU8 {
+        self.pool.dial_concurrency_factor()
+    }
+
+    /// Sets the number of addresses of a peer dialed concurrently, e.g. to throttle dialing while
+    /// bandwidth is scarce.
+    ///
+    /// Only applies to dials started from now on. Dials already in progress and dials with an
+    /// override via [`DialOpts`] are unaffected.
+    pub fn set_dial_concurrency_factor(&mut self, factor: NonZeroU8) {
+        self.pool.set_dial_concurrency_factor(factor);
+    }
+
+    /// Ban a peer for the given duration.
+    ///
+    /// All established connections to the peer are closed. Dials of the peer and connections to
+    /// it that are established while the ban lasts, including pending ones, are denied with
+    /// [`DialError::Banned`] and [`ListenError::Banned`] respectively.
+    ///
+    /// Banning a banned peer again replaces the expiry of its ban.
+    pub fn ban_peer(&mut self, peer_id: PeerId, duration: Duration) {
+        let until = Instant::now() + duration;
+        tracing::debug!(peer=%peer_id, ?duration, "Banning peer");
+
+        self.banned_peers.insert(peer_id, until);
+        for connection_id in self
+            .pool
+            .iter_established_connections_of_peer(&peer_id)
+            .collect::<Vec<_>>()
+        {
+            self.close_connection(connection_id);
+        }
+    }
+
+    /// Lift the ban of a peer banned via [`Swarm::ban_peer`] before it expires.
+    ///
+    /// Returns `false` if the peer was not banned.
+    pub fn unban_peer(&mut self, peer_id: PeerId) -> bool {
+        self.banned_peers
+            .remove(&peer_id)
+            .is_some_and(|until| until > Instant::now())
+    }
+
+    /// Checks whether a peer is banned, see [`Swarm::ban_peer`].
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        self.banned_peers
+            .get(peer_id)
+            .is_some_and(|until| *until > Instant::now())
+    }
+
+    /// Returns when the ban of a peer expires, removing expired bans.
+    fn banned_until(&mut self, peer_id: PeerId) -> Option<Instant> {
+        let now = Instant::now();
+        self.banned_peers.retain(|_, until| *until > now);
+
+        self.banned_peers.get(&peer_id).copied()
+    }
+
     /// Checks whether there is an established connection to a peer.
     pub fn is_connected(&self, peer_id: &PeerId) -> bool {
         self.pool.is_connected(*peer_id)
@@ -692,7 +1352,86 @@ where
         &mut self.behaviour
     }
 
+    /// Returns a stream of the events emitted by the [`NetworkBehaviour`], i.e. the payloads of
+    /// [`SwarmEvent::Behaviour`].
+    ///
+    /// Polling the stream drives the [`Swarm`] as usual, all other [`SwarmEvent`]s are discarded.
+    pub fn behaviour_events(&mut self) -> impl futures::Stream<Item = TBehaviour::ToSwarm> + '_ {
+        self.filter_map(|event| future::ready(event.try_into_behaviour_event().ok()))
+    }
+
+    /// Re-reports all established connections to the [`NetworkBehaviour`].
+    ///
+    /// Allows a behaviour that was replaced or reset via [`Swarm::behaviour_mut`] to learn about
+    /// the current connections. For every established connection,
+    /// [`NetworkBehaviour::handle_established_inbound_connection`] or
+    /// [`NetworkBehaviour::handle_established_outbound_connection`] is called, followed by
+    /// [`FromSwarm::ConnectionEstablished`].
+    ///
+    /// The connection handlers returned by the behaviour are discarded, the connections keep
+    /// running their existing handlers. Connections denied by the behaviour are closed.
+    /// Connections whose [`FromSwarm::ConnectionEstablished`] is deferred until their first
+    /// substream (see
+    /// [`Config::with_defer_connection_established_until_first_substream`]) are skipped, as they are
+    /// reported once the substream is negotiated.
+    pub fn reset_behaviour_state(&mut self) {
+        let connections = self
+            .pool
+            .iter_established()
+            .filter(|(_, id, _)| !self.deferred_connections.contains_key(id))
+            .map(|(peer_id, id, endpoint)| (peer_id, id, endpoint.clone()))
+            .collect::<Vec<_>>();
+
+        let mut num_established = HashMap::<PeerId, usize>::new();
+        for (peer_id, connection_id, endpoint) in connections {
+            let handler = match &endpoint {
+                ConnectedPoint::Dialer {
+                    address,
+                    role_override,
+                } => self.behaviour.handle_established_outbound_connection(
+                    connection_id,
+                    peer_id,
+                    address,
+                    *role_override,
+                ),
+                ConnectedPoint::Listener {
+                    local_addr,
+                    send_back_addr,
+                } => self.behaviour.handle_established_inbound_connection(
+                    connection_id,
+                    peer_id,
+                    local_addr,
+                    send_back_addr,
+                ),
+            };
+            if let Err(cause) = handler {
+                tracing::debug!(
+                    peer=%peer_id,
+                    connection=%connection_id,
+                    "Behaviour denied established connection: {cause}"
+                );
+                self.close_connection(connection_id);
+                continue;
+            }
+
+            let other_established = num_established.entry(peer_id).or_default();
+            self.behaviour
+                .on_swarm_event(FromSwarm::ConnectionEstablished(
+                    behaviour::ConnectionEstablished {
+                        peer_id,
+                        connection_id,
+                        endpoint: &endpoint,
+                        failed_addresses: &[],
+                        other_established: *other_established,
+                    },
+                ));
+            *other_established += 1;
+        }
+    }
+
     fn handle_pool_event(&mut self, event: PoolEvent<THandlerOutEvent<TBehaviour>>) {
+        self.peer_metadata.remove_expired(Instant::now());
+
         match event {
             PoolEvent::ConnectionEstablished {
                 peer_id,
@@ -701,20 +1440,73 @@ where
                 connection,
                 concurrent_dial_errors,
                 established_in,
+                label,
             } => {
+                self.pending_behaviour_dials.remove(&id);
+                let payload = self.pending_connection_payloads.remove(&id);
+                if let Some(until) = self.banned_until(peer_id) {
+                    tracing::debug!(peer=%peer_id, "Denying connection to banned peer");
+
+                    match endpoint {
+                        ConnectedPoint::Dialer { .. } => {
+                            let dial_error = DialError::Banned { until };
+                            self.behaviour
+                                .on_swarm_event(FromSwarm::DialFailure(DialFailure {
+                                    connection_id: id,
+                                    error: &dial_error,
+                                    peer_id: Some(peer_id),
+                                }));
+                            self.pending_swarm_events.push_back(
+                                SwarmEvent::OutgoingConnectionError {
+                                    peer_id: Some(peer_id),
+                                    connection_id: id,
+                                    error: dial_error,
+                                },
+                            );
+                        }
+                        ConnectedPoint::Listener {
+                            local_addr,
+                            send_back_addr,
+                        } => {
+                            let listen_error = ListenError::Banned { until };
+                            self.behaviour.on_swarm_event(FromSwarm::ListenFailure(
+                                ListenFailure {
+                                    local_addr: &local_addr,
+                                    send_back_addr: &send_back_addr,
+                                    error: &listen_error,
+                                    connection_id: id,
+                                },
+                            ));
+                            self.pending_swarm_events.push_back(
+                                SwarmEvent::IncomingConnectionError {
+                                    connection_id: id,
+                                    local_addr,
+                                    send_back_addr,
+                                    error: listen_error,
+                                },
+                            );
+                        }
+                    }
+                    return;
+                }
+
                 let handler = match endpoint.clone() {
                     ConnectedPoint::Dialer {
                         address,
                         role_override,
                     } => {
-                        match self.behaviour.handle_established_outbound_connection(
-                            id,
-                            peer_id,
-                            &address,
-                            role_override,
-                        ) {
+                        match self
+                            .behaviour
+                            .handle_established_outbound_connection_with_payload(
+                                id,
+                                peer_id,
+                                &address,
+                                role_override,
+                                payload,
+                            ) {
                             Ok(handler) => handler,
                             Err(cause) => {
+                                self.report_limit_reached(id, &cause);
                                 let dial_error = DialError::Denied { cause };
                                 self.behaviour.on_swarm_event(FromSwarm::DialFailure(
                                     DialFailure {
@@ -739,14 +1531,18 @@ where
                         local_addr,
                         send_back_addr,
                     } => {
-                        match self.behaviour.handle_established_inbound_connection(
-                            id,
-                            peer_id,
-                            &local_addr,
-                            &send_back_addr,
-                        ) {
+                        match self
+                            .behaviour
+                            .handle_established_inbound_connection_with_payload(
+                                id,
+                                peer_id,
+                                &local_addr,
+                                &send_back_addr,
+                                payload,
+                            ) {
                             Ok(handler) => handler,
                             Err(cause) => {
+                                self.report_limit_reached(id, &cause);
                                 let listen_error = ListenError::Denied { cause };
                                 self.behaviour.on_swarm_event(FromSwarm::ListenFailure(
                                     ListenFailure {
@@ -771,12 +1567,6 @@ where
                     }
                 };
 
-                let supported_protocols = handler
-                    .listen_protocol()
-                    .upgrade()
-                    .protocol_info()
-                    .map(|p| p.as_ref().as_bytes().to_vec())
-                    .collect();
                 let other_established_connection_ids = self
                     .pool
                     .iter_established_connections_of_peer(&peer_id)
@@ -786,8 +1576,15 @@ where
                 )
                 .expect("n + 1 is always non-zero; qed");
 
-                self.pool
-                    .spawn_connection(id, peer_id, &endpoint, connection, handler);
+                self.pool.spawn_connection(
+                    id,
+                    peer_id,
+                    &endpoint,
+                    connection,
+                    handler,
+                    label.clone(),
+                );
+                self.peer_metadata.on_connection_established(peer_id);
 
                 tracing::debug!(
                     peer=%peer_id,
@@ -804,17 +1601,29 @@ where
                             .collect::<Vec<Multiaddr>>()
                     })
                     .unwrap_or_default();
-                self.behaviour
-                    .on_swarm_event(FromSwarm::ConnectionEstablished(
-                        behaviour::ConnectionEstablished {
-                            peer_id,
-                            connection_id: id,
-                            endpoint: &endpoint,
-                            failed_addresses: &failed_addresses,
-                            other_established: other_established_connection_ids.len(),
+                if self.defer_connection_established {
+                    self.deferred_connections.insert(
+                        id,
+                        DeferredConnection {
+                            endpoint: endpoint.clone(),
+                            failed_addresses,
+                            handler_events: Vec::new(),
                         },
-                    ));
-                self.supported_protocols = supported_protocols;
+                    );
+                } else {
+                    self.behaviour
+                        .on_swarm_event(FromSwarm::ConnectionEstablished(
+                            behaviour::ConnectionEstablished {
+                                peer_id,
+                                connection_id: id,
+                                endpoint: &endpoint,
+                                failed_addresses: &failed_addresses,
+                                other_established: other_established_connection_ids.len(),
+                            },
+                        ));
+                    self.report_direct_upgrade(peer_id, id, &endpoint);
+                }
+                let role_overridden = endpoint.is_role_overridden();
                 self.pending_swarm_events
                     .push_back(SwarmEvent::ConnectionEstablished {
                         peer_id,
@@ -823,6 +1632,8 @@ where
                         endpoint,
                         concurrent_dial_errors,
                         established_in,
+                        label,
+                        role_overridden,
                     });
             }
             PoolEvent::PendingOutboundConnectionError {
@@ -830,6 +1641,8 @@ where
                 error,
                 peer,
             } => {
+                self.pending_behaviour_dials.remove(&connection_id);
+                self.pending_connection_payloads.remove(&connection_id);
                 let error = error.into();
 
                 self.behaviour
@@ -852,13 +1665,47 @@ where
                         error,
                     });
             }
-            PoolEvent::PendingInboundConnectionError {
-                id,
-                send_back_addr,
-                local_addr,
+            PoolEvent::DialAttemptFailed {
+                id: connection_id,
+                peer,
+                address,
                 error,
+                remaining_addresses,
             } => {
-                let error = error.into();
+                tracing::debug!(
+                    peer=?peer,
+                    %address,
+                    %remaining_addresses,
+                    "Dial attempt failed with {:?}",
+                    error
+                );
+
+                self.behaviour
+                    .on_swarm_event(FromSwarm::DialAttemptFailed(DialAttemptFailed {
+                        peer_id: peer,
+                        connection_id,
+                        address: &address,
+                        error: &error,
+                        remaining_addresses,
+                    }));
+
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::DialAttemptFailed {
+                        connection_id,
+                        peer_id: peer,
+                        address,
+                        error,
+                        remaining_addresses,
+                    });
+            }
+            PoolEvent::PendingInboundConnectionError {
+                id,
+                send_back_addr,
+                local_addr,
+                error,
+            } => {
+                self.pending_connection_payloads.remove(&id);
+                let error = error.into();
 
                 tracing::debug!("Incoming connection failed: {:?}", error);
                 self.behaviour
@@ -881,7 +1728,7 @@ where
                 connected,
                 error,
                 remaining_established_connection_ids,
-                ..
+                negotiation_failure,
             } => {
                 if let Some(error) = error.as_ref() {
                     tracing::debug!(
@@ -899,16 +1746,62 @@ where
                 }
                 let peer_id = connected.peer_id;
                 let endpoint = connected.endpoint;
+                if self
+                    .direct_upgrades
+                    .get(&peer_id)
+                    .is_some_and(|(relayed, _)| *relayed == id)
+                {
+                    self.direct_upgrades.remove(&peer_id);
+                }
                 let num_established =
                     u32::try_from(remaining_established_connection_ids.len()).unwrap();
 
+                self.peer_metadata.on_connection_closed(
+                    peer_id,
+                    num_established as usize,
+                    Instant::now(),
+                );
+
+                // The behaviour already holds the connection's handler, so a connection closed
+                // before its first substream is still reported as established before its closing.
+                if let Some(deferred) = self.deferred_connections.remove(&id) {
+                    self.report_deferred_connection(peer_id, id, deferred);
+                }
+
+                if let Some(protocols_tried) = negotiation_failure {
+                    tracing::debug!(
+                        peer=%peer_id,
+                        ?protocols_tried,
+                        "Connection closed without negotiating any protocol"
+                    );
+                    self.behaviour.on_swarm_event(FromSwarm::NegotiationFailed(
+                        NegotiationFailed {
+                            peer_id,
+                            connection_id: id,
+                            protocols_tried: &protocols_tried,
+                        },
+                    ));
+                }
+
+                let remaining_established = remaining_established_connection_ids
+                    .iter()
+                    .filter(|c| !self.deferred_connections.contains_key(c))
+                    .count();
                 self.behaviour
                     .on_swarm_event(FromSwarm::ConnectionClosed(ConnectionClosed {
                         peer_id,
                         connection_id: id,
                         endpoint: &endpoint,
-                        remaining_established: num_established as usize,
+                        remaining_established,
                     }));
+                self.behaviour
+                    .on_swarm_event(FromSwarm::ConnectionCloseCompleted(
+                        ConnectionCloseCompleted {
+                            peer_id,
+                            connection_id: id,
+                            graceful: error.is_none(),
+                        },
+                    ));
                 self.pending_swarm_events
                     .push_back(SwarmEvent::ConnectionClosed {
                         peer_id,
@@ -919,9 +1812,36 @@ where
                     });
             }
             PoolEvent::ConnectionEvent { peer_id, id, event } => {
+                if let Some(deferred) = self.deferred_connections.get_mut(&id) {
+                    deferred.handler_events.push(event);
+                    return;
+                }
+
                 self.behaviour
                     .on_connection_handler_event(peer_id, id, event);
             }
+            PoolEvent::StreamLimitReached {
+                id,
+                peer_id,
+                counts,
+            } => {
+                tracing::debug!(peer=%peer_id, connection=%id, ?counts, "Muxer refused new outbound stream");
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::ConnectionStreamLimitReached {
+                        peer_id,
+                        connection_id: id,
+                        stream_counts: counts,
+                    });
+            }
+            PoolEvent::FirstSubstreamNegotiated { id, peer_id } => {
+                let Some(deferred) = self.deferred_connections.remove(&id) else {
+                    return;
+                };
+
+                let endpoint = deferred.endpoint.clone();
+                self.report_deferred_connection(peer_id, id, deferred);
+                self.report_direct_upgrade(peer_id, id, &endpoint);
+            }
             PoolEvent::AddressChange {
                 peer_id,
                 id,
@@ -946,63 +1866,78 @@ where
             io::Error,
         >,
     ) {
+        let (TransportEvent::Incoming { listener_id, .. }
+        | TransportEvent::NewAddress { listener_id, .. }
+        | TransportEvent::AddressExpired { listener_id, .. }
+        | TransportEvent::ListenerClosed { listener_id, .. }
+        | TransportEvent::ListenerError { listener_id, .. }) = &event;
+        let _span = self
+            .listener_spans
+            .get(listener_id)
+            .cloned()
+            .map(tracing::Span::entered);
+
         match event {
             TransportEvent::Incoming {
-                listener_id: _,
+                listener_id,
                 upgrade,
                 local_addr,
                 send_back_addr,
             } => {
                 let connection_id = ConnectionId::next();
 
-                match self.behaviour.handle_pending_inbound_connection(
-                    connection_id,
-                    &local_addr,
-                    &send_back_addr,
-                ) {
-                    Ok(()) => {}
-                    Err(cause) => {
-                        let listen_error = ListenError::Denied { cause };
-
-                        self.behaviour
-                            .on_swarm_event(FromSwarm::ListenFailure(ListenFailure {
-                                local_addr: &local_addr,
-                                send_back_addr: &send_back_addr,
-                                error: &listen_error,
-                                connection_id,
-                            }));
-
-                        self.pending_swarm_events
-                            .push_back(SwarmEvent::IncomingConnectionError {
-                                connection_id,
-                                local_addr,
-                                send_back_addr,
-                                error: listen_error,
-                            });
-                        return;
-                    }
+                if self.paused_listeners.contains(&listener_id) {
+                    tracing::debug!(
+                        listener=?listener_id,
+                        address=%send_back_addr,
+                        "Refusing inbound connection on paused listener"
+                    );
+                    drop(upgrade);
+
+                    let listen_error = ListenError::ListenerPaused;
+                    self.behaviour
+                        .on_swarm_event(FromSwarm::ListenFailure(ListenFailure {
+                            local_addr: &local_addr,
+                            send_back_addr: &send_back_addr,
+                            error: &listen_error,
+                            connection_id,
+                        }));
+                    self.pending_swarm_events
+                        .push_back(SwarmEvent::IncomingConnectionError {
+                            connection_id,
+                            local_addr,
+                            send_back_addr,
+                            error: listen_error,
+                        });
+                    return;
                 }
 
-                self.pool.add_incoming(
-                    upgrade,
-                    IncomingInfo {
-                        local_addr: &local_addr,
-                        send_back_addr: &send_back_addr,
-                    },
+                let label = self.listener_labels.get(&listener_id).cloned();
+                self.accept_inbound_connection(
                     connection_id,
+                    upgrade,
+                    local_addr,
+                    send_back_addr,
+                    label,
                 );
-
-                self.pending_swarm_events
-                    .push_back(SwarmEvent::IncomingConnection {
-                        connection_id,
-                        local_addr,
-                        send_back_addr,
-                    })
             }
             TransportEvent::NewAddress {
                 listener_id,
                 listen_addr,
             } => {
+                if let Err(cause) = self
+                    .behaviour
+                    .handle_pending_listen(listener_id, &listen_addr)
+                {
+                    tracing::debug!(
+                        listener=?listener_id,
+                        address=%listen_addr,
+                        %cause,
+                        "Listener address denied by behaviour"
+                    );
+                    return;
+                }
+
                 tracing::debug!(
                     listener=?listener_id,
                     address=%listen_addr,
@@ -1017,6 +1952,7 @@ where
                         listener_id,
                         addr: &listen_addr,
                     }));
+                self.update_listen_protocols();
                 self.pending_swarm_events
                     .push_back(SwarmEvent::NewListenAddr {
                         listener_id,
@@ -1027,19 +1963,28 @@ where
                 listener_id,
                 listen_addr,
             } => {
+                // Addresses denied via `handle_pending_listen` were never reported, thus their
+                // expiry is not reported either.
+                let Some(addrs) = self
+                    .listened_addrs
+                    .get_mut(&listener_id)
+                    .filter(|addrs| addrs.contains(&listen_addr))
+                else {
+                    return;
+                };
+                addrs.retain(|a| a != &listen_addr);
+
                 tracing::debug!(
                     listener=?listener_id,
                     address=%listen_addr,
                     "Expired listener address"
                 );
-                if let Some(addrs) = self.listened_addrs.get_mut(&listener_id) {
-                    addrs.retain(|a| a != &listen_addr);
-                }
                 self.behaviour
                     .on_swarm_event(FromSwarm::ExpiredListenAddr(ExpiredListenAddr {
                         listener_id,
                         addr: &listen_addr,
                     }));
+                self.update_listen_protocols();
                 self.pending_swarm_events
                     .push_back(SwarmEvent::ExpiredListenAddr {
                         listener_id,
@@ -1056,11 +2001,16 @@ where
                     "Listener closed"
                 );
                 let addrs = self.listened_addrs.remove(&listener_id).unwrap_or_default();
+                self.paused_listeners.remove(&listener_id);
+                self.listener_labels.remove(&listener_id);
+                self.listener_spans.remove(&listener_id);
+                self.listen_requests.remove(&listener_id);
                 for addr in addrs.iter() {
                     self.behaviour.on_swarm_event(FromSwarm::ExpiredListenAddr(
                         ExpiredListenAddr { listener_id, addr },
                     ));
                 }
+                self.update_listen_protocols();
                 self.behaviour
                     .on_swarm_event(FromSwarm::ListenerClosed(ListenerClosed {
                         listener_id,
@@ -1085,6 +2035,148 @@ where
         }
     }
 
+    /// Accepts an inbound connection that passed the checks of its listener, if the resource
+    /// manager and the [`NetworkBehaviour`] allow it.
+    fn accept_inbound_connection<TFut>(
+        &mut self,
+        connection_id: ConnectionId,
+        upgrade: TFut,
+        local_addr: Multiaddr,
+        send_back_addr: Multiaddr,
+        label: Option<String>,
+    ) where
+        TFut: Future<Output = Result<(PeerId, StreamMuxerBox), io::Error>> + Send + 'static,
+    {
+        if let Some(limit) = &mut self.inbound_rate_limit {
+            if !limit.try_accept(Instant::now()) {
+                tracing::debug!(
+                    address=%send_back_addr,
+                    "Refusing inbound connection beyond the rate limit"
+                );
+                drop(upgrade);
+
+                let listen_error = ListenError::RateLimited;
+                self.behaviour
+                    .on_swarm_event(FromSwarm::ListenFailure(ListenFailure {
+                        local_addr: &local_addr,
+                        send_back_addr: &send_back_addr,
+                        error: &listen_error,
+                        connection_id,
+                    }));
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::IncomingConnectionError {
+                        connection_id,
+                        local_addr,
+                        send_back_addr,
+                        error: listen_error,
+                    });
+                return;
+            }
+        }
+
+        if let Some(manager) = &self.resource_manager {
+            if !manager.can_accept_inbound() {
+                tracing::debug!(
+                    address=%send_back_addr,
+                    "Refusing inbound connection due to insufficient resources"
+                );
+                drop(upgrade);
+
+                let listen_error = ListenError::InsufficientResources;
+                self.behaviour
+                    .on_swarm_event(FromSwarm::ListenFailure(ListenFailure {
+                        local_addr: &local_addr,
+                        send_back_addr: &send_back_addr,
+                        error: &listen_error,
+                        connection_id,
+                    }));
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::IncomingConnectionError {
+                        connection_id,
+                        local_addr,
+                        send_back_addr,
+                        error: listen_error,
+                    });
+                return;
+            }
+        }
+
+        match self
+            .behaviour
+            .handle_pending_inbound_connection_with_payload(
+                connection_id,
+                &local_addr,
+                &send_back_addr,
+            ) {
+            Ok(None) => {}
+            Ok(Some(payload)) => {
+                self.pending_connection_payloads
+                    .insert(connection_id, payload);
+            }
+            Err(cause) => {
+                self.report_limit_reached(connection_id, &cause);
+                let listen_error = ListenError::Denied { cause };
+
+                self.behaviour
+                    .on_swarm_event(FromSwarm::ListenFailure(ListenFailure {
+                        local_addr: &local_addr,
+                        send_back_addr: &send_back_addr,
+                        error: &listen_error,
+                        connection_id,
+                    }));
+
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::IncomingConnectionError {
+                        connection_id,
+                        local_addr,
+                        send_back_addr,
+                        error: listen_error,
+                    });
+                return;
+            }
+        }
+
+        self.pool.add_incoming(
+            upgrade,
+            IncomingInfo {
+                local_addr: &local_addr,
+                send_back_addr: &send_back_addr,
+            },
+            connection_id,
+            label,
+        );
+
+        self.pending_swarm_events
+            .push_back(SwarmEvent::IncomingConnection {
+                connection_id,
+                local_addr,
+                send_back_addr,
+            });
+    }
+
+    /// Recomputes the [`ListenProtocol`]s of all listen addresses, informing the behaviour if
+    /// they changed.
+    fn update_listen_protocols(&mut self) {
+        let mut protocols = self
+            .listened_addrs
+            .values()
+            .flatten()
+            .filter_map(listen_protocol)
+            .collect::<Vec<_>>();
+        protocols.sort_unstable();
+        protocols.dedup();
+
+        if protocols == self.listen_protocols {
+            return;
+        }
+
+        self.listen_protocols = protocols;
+        self.behaviour
+            .on_swarm_event(FromSwarm::ListenProtocolsChanged(ListenProtocolsChanged {
+                protocols: &self.listen_protocols,
+            }));
+    }
+
     fn handle_behaviour_event(
         &mut self,
         event: ToSwarm<TBehaviour::ToSwarm, THandlerInEvent<TBehaviour>>,
@@ -1098,6 +2190,7 @@ where
                 let peer_id = opts.get_peer_id();
                 let connection_id = opts.connection_id();
                 if let Ok(()) = self.dial(opts) {
+                    self.pending_behaviour_dials.insert(connection_id);
                     self.pending_swarm_events.push_back(SwarmEvent::Dialing {
                         peer_id,
                         connection_id,
@@ -1111,6 +2204,12 @@ where
             ToSwarm::RemoveListener { id } => {
                 self.remove_listener(id);
             }
+            ToSwarm::PauseListener { id } => {
+                self.pause_listener(id);
+            }
+            ToSwarm::ResumeListener { id } => {
+                self.resume_listener(id);
+            }
             ToSwarm::NotifyHandler {
                 peer_id,
                 handler,
@@ -1119,13 +2218,10 @@ where
                 assert!(self.pending_handler_event.is_none());
                 let handler = match handler {
                     NotifyHandler::One(connection) => PendingNotifyHandler::One(connection),
-                    NotifyHandler::Any => {
-                        let ids = self
-                            .pool
-                            .iter_established_connections_of_peer(&peer_id)
-                            .collect();
-                        PendingNotifyHandler::Any(ids)
-                    }
+                    NotifyHandler::Any => PendingNotifyHandler::Any(
+                        self.pool
+                            .established_connections_of_peer_by_priority(&peer_id),
+                    ),
                 };
 
                 self.pending_handler_event = Some((peer_id, handler, event));
@@ -1138,7 +2234,12 @@ where
                         .listened_addrs
                         .values()
                         .flatten()
-                        .filter_map(|server| self.transport.address_translation(server, &addr))
+                        .filter_map(|server| {
+                            self.address_translator
+                                .as_ref()
+                                .and_then(|translate| translate(server, &addr))
+                                .or_else(|| self.transport.address_translation(server, &addr))
+                        })
                         .collect();
 
                     // remove duplicates
@@ -1149,20 +2250,10 @@ where
 
                 // If address translation yielded nothing, broacast the original candidate address.
                 if translated_addresses.is_empty() {
-                    self.behaviour
-                        .on_swarm_event(FromSwarm::NewExternalAddrCandidate(
-                            NewExternalAddrCandidate { addr: &addr },
-                        ));
-                    self.pending_swarm_events
-                        .push_back(SwarmEvent::NewExternalAddrCandidate { address: addr });
+                    self.report_external_addr_candidate(addr);
                 } else {
                     for addr in translated_addresses {
-                        self.behaviour
-                            .on_swarm_event(FromSwarm::NewExternalAddrCandidate(
-                                NewExternalAddrCandidate { addr: &addr },
-                            ));
-                        self.pending_swarm_events
-                            .push_back(SwarmEvent::NewExternalAddrCandidate { address: addr });
+                        self.report_external_addr_candidate(addr);
                     }
                 }
             }
@@ -1198,7 +2289,108 @@ where
                 self.pending_swarm_events
                     .push_back(SwarmEvent::NewExternalAddrOfPeer { peer_id, address });
             }
+            ToSwarm::UpgradeToDirect {
+                peer_id,
+                connection_id,
+            } => {
+                let endpoint = match self.pool.established_endpoint(connection_id) {
+                    Some((peer, endpoint)) if peer == peer_id && endpoint.is_relayed() => {
+                        endpoint.clone()
+                    }
+                    _ => {
+                        tracing::debug!(
+                            peer=%peer_id,
+                            connection=%connection_id,
+                            "Ignoring upgrade to direct connection of unknown or direct connection"
+                        );
+                        return;
+                    }
+                };
+                self.behaviour
+                    .on_swarm_event(FromSwarm::UpgradeToDirect(UpgradeToDirect {
+                        peer_id,
+                        connection_id,
+                        endpoint: &endpoint,
+                    }));
+                self.direct_upgrades
+                    .insert(peer_id, (connection_id, endpoint));
+            }
+        }
+    }
+
+    /// Reports a connection whose [`FromSwarm::ConnectionEstablished`] was deferred, followed by
+    /// the events its handler produced in the meantime.
+    fn report_deferred_connection(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        DeferredConnection {
+            endpoint,
+            failed_addresses,
+            handler_events,
+        }: DeferredConnection<THandlerOutEvent<TBehaviour>>,
+    ) {
+        let other_established = self
+            .pool
+            .iter_established_connections_of_peer(&peer_id)
+            .filter(|c| *c != connection_id && !self.deferred_connections.contains_key(c))
+            .count();
+        self.behaviour
+            .on_swarm_event(FromSwarm::ConnectionEstablished(
+                behaviour::ConnectionEstablished {
+                    peer_id,
+                    connection_id,
+                    endpoint: &endpoint,
+                    failed_addresses: &failed_addresses,
+                    other_established,
+                },
+            ));
+        for event in handler_events {
+            self.behaviour
+                .on_connection_handler_event(peer_id, connection_id, event);
+        }
+    }
+
+    /// Reports a direct connection to a peer whose relayed connection is to be upgraded as
+    /// [`FromSwarm::DirectConnectionUpgraded`], see [`ToSwarm::UpgradeToDirect`].
+    fn report_direct_upgrade(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        endpoint: &ConnectedPoint,
+    ) {
+        if endpoint.is_relayed() {
+            return;
+        }
+        let Some((relayed_connection_id, relayed_endpoint)) = self.direct_upgrades.remove(&peer_id)
+        else {
+            return;
+        };
+
+        self.behaviour
+            .on_swarm_event(FromSwarm::DirectConnectionUpgraded(
+                DirectConnectionUpgraded {
+                    peer_id,
+                    relayed_connection_id,
+                    relayed_endpoint: &relayed_endpoint,
+                    direct_connection_id: connection_id,
+                    direct_endpoint: endpoint,
+                },
+            ));
+    }
+
+    fn report_external_addr_candidate(&mut self, addr: Multiaddr) {
+        if !self.confirmed_external_addr.contains(&addr) {
+            self.external_addr_candidates
+                .on_new_candidate(addr.clone(), Instant::now());
         }
+
+        self.behaviour
+            .on_swarm_event(FromSwarm::NewExternalAddrCandidate(
+                NewExternalAddrCandidate { addr: &addr },
+            ));
+        self.pending_swarm_events
+            .push_back(SwarmEvent::NewExternalAddrCandidate { address: addr });
     }
 
     /// Internal function used by everything event-related.
@@ -1222,8 +2414,17 @@ where
         // (1) is polled before (2) to prioritize local work over work coming from a remote.
         //
         // (2) is polled before (3) to prioritize existing connections over upgrading new incoming connections.
+        this.did_work = false;
+        let mut first_iteration = true;
         loop {
+            // The loop only ever continues once one of the components made progress.
+            if !first_iteration {
+                this.did_work = true;
+            }
+            first_iteration = false;
+
             if let Some(swarm_event) = this.pending_swarm_events.pop_front() {
+                this.did_work = true;
                 return Poll::Ready(swarm_event);
             }
 
@@ -1252,8 +2453,14 @@ where
                         }
                     }
                 },
+                // Too many commands of the [`NetworkBehaviour`] are still being processed. Hold it
+                // back until they are, i.e. until the pool reports their progress.
+                None if this.is_saturated() => {}
                 // No pending event. Allow the [`NetworkBehaviour`] to make progress.
-                None => match this.behaviour.poll(cx) {
+                None => match this
+                    .behaviour
+                    .poll_with_rotation(cx, &mut this.behaviour_poll_rotation)
+                {
                     Poll::Pending => {}
                     Poll::Ready(behaviour_event) => {
                         this.handle_behaviour_event(behaviour_event);
@@ -1281,6 +2488,14 @@ where
                 }
             }
 
+            if let Poll::Ready(addr) = this.external_addr_candidates.poll(cx) {
+                this.behaviour
+                    .on_swarm_event(FromSwarm::ExternalAddrCandidateExpired(
+                        ExternalAddrCandidateExpired { addr: &addr },
+                    ));
+                continue;
+            }
+
             return Poll::Pending;
         }
     }
@@ -1386,7 +2601,13 @@ where
     type Item = SwarmEvent<TBehaviourOutEvent<TBehaviour>>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.as_mut().poll_next_event(cx).map(Some)
+        let event = futures::ready!(self.as_mut().poll_next_event(cx));
+
+        for observer in &mut self.event_observers {
+            observer(&event);
+        }
+
+        Poll::Ready(Some(event))
     }
 }
 
@@ -1402,14 +2623,44 @@ where
 
 pub struct Config {
     pool_config: PoolConfig,
+    external_addresses: Vec<Multiaddr>,
+    peer_metadata_grace_period: Duration,
+    defer_connection_established: bool,
+    candidate_ttl: Option<Duration>,
+    resource_manager: Option<Box<dyn ResourceManager>>,
+    inbound_connection_rate_limit: Option<u32>,
+    address_translator: Option<AddressTranslator>,
+    max_pending_behaviour_commands: Option<usize>,
 }
 
+/// Translates an observed address into an external address candidate, given a listen address.
+type AddressTranslator = Box<dyn Fn(&Multiaddr, &Multiaddr) -> Option<Multiaddr> + Send>;
+
 impl Config {
     /// Creates a new [`Config`] from the given executor. The [`Swarm`] is obtained via
     /// [`Swarm::new`].
     pub fn with_executor(executor: impl Executor + Send + 'static) -> Self {
+        Self::new(Some(Box::new(executor)))
+    }
+
+    /// Creates a new [`Config`] without executor, polling the connections as part of the
+    /// [`Swarm`] itself.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn without_executor() -> Self {
+        Self::new(None)
+    }
+
+    fn new(executor: Option<Box<dyn Executor + Send>>) -> Self {
         Self {
-            pool_config: PoolConfig::new(Some(Box::new(executor))),
+            pool_config: PoolConfig::new(executor),
+            external_addresses: Vec::new(),
+            peer_metadata_grace_period: Duration::from_secs(60),
+            defer_connection_established: false,
+            candidate_ttl: None,
+            resource_manager: None,
+            inbound_connection_rate_limit: None,
+            address_translator: None,
+            max_pending_behaviour_commands: None,
         }
     }
 
@@ -1450,6 +2701,11 @@ impl Config {
     /// the [`Swarm`] has to wait. An individual buffer with this number of
     /// events exists for each individual connection.
     ///
+    /// Once the buffer of a connection is full, the [`Swarm`] holds on to the
+    /// next [`ToSwarm::NotifyHandler`] event and stops polling the
+    /// [`NetworkBehaviour`] until the connection accepts it, thus bounding the
+    /// memory used for a connection that is slow to accept events.
+    ///
     /// The ideal value depends on the executor used, the CPU speed, and the
     /// volume of events. If this value is too low, then the [`Swarm`] will
     /// be sleeping more often than necessary. Increasing this value increases
@@ -1462,7 +2718,9 @@ impl Config {
     /// Configures the size of the buffer for events sent by a [`ConnectionHandler`] to the
     /// [`NetworkBehaviour`].
     ///
-    /// Each connection has its own buffer.
+    /// Each connection has its own buffer. The size counts batches of up to 32 events each,
+    /// collected from the [`ConnectionHandler`] without waiting. A buffer of size `n` thus holds
+    /// up to `32 * (n + 1)` events in the worst case.
     ///
     /// The ideal value depends on the executor used, the CPU speed and the volume of events.
     /// If this value is too low, then the [`ConnectionHandler`]s will be sleeping more often
@@ -1481,6 +2739,16 @@ impl Config {
         self
     }
 
+    /// Whether to report each address of an outbound connection attempt that failed to dial as
+    /// [`SwarmEvent::DialAttemptFailed`] and [`FromSwarm::DialAttemptFailed`], instead of only
+    /// the outcome of the whole attempt.
+    ///
+    /// Defaults to `false`.
+    pub fn with_dial_attempt_failure_events(mut self, enabled: bool) -> Self {
+        self.pool_config = self.pool_config.with_dial_attempt_failure_events(enabled);
+        self
+    }
+
     /// Configures an override for the substream upgrade protocol to use.
     ///
     /// The subtream upgrade protocol is the multistream-select protocol
@@ -1520,30 +2788,247 @@ impl Config {
         self.pool_config.idle_connection_timeout = timeout;
         self
     }
-}
 
-/// Possible errors when trying to establish or upgrade an outbound connection.
-#[derive(Debug)]
-pub enum DialError {
-    /// The peer identity obtained on the connection matches the local peer.
-    LocalPeerId {
-        endpoint: ConnectedPoint,
-    },
-    /// No addresses have been provided by [`NetworkBehaviour::handle_pending_outbound_connection`] and [`DialOpts`].
-    NoAddresses,
-    /// The provided [`dial_opts::PeerCondition`] evaluated to false and thus
-    /// the dial was aborted.
-    DialPeerConditionFalse(dial_opts::PeerCondition),
-    /// Pending connection attempt has been aborted.
-    Aborted,
-    /// The peer identity obtained on the connection did not match the one that was expected.
-    WrongPeerId {
-        obtained: PeerId,
-        endpoint: ConnectedPoint,
-    },
-    Denied {
-        cause: ConnectionDenied,
-    },
+    /// How long a pending connection may take to be established, i.e. to complete the security
+    /// and muxer handshakes, before it is aborted.
+    ///
+    /// Pending connections that exceed the timeout are reported via
+    /// [`DialError::Timeout`] and [`ListenError::Timeout`] respectively. For inbound connections
+    /// the timeout starts once the transport accepted the connection. For outbound connections it
+    /// starts once the dial is started, thus it also covers the transport-level connection setup.
+    ///
+    /// Defaults to no timeout.
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_config.handshake_timeout = Some(timeout);
+        self
+    }
+
+    /// How long the negotiation and upgrade of a substream may take before it is aborted,
+    /// overriding the timeout of each individual [`SubstreamProtocol`].
+    ///
+    /// For outbound substreams the timeout starts once the [`ConnectionHandler`] requested the
+    /// substream. Upgrades exceeding the timeout are reported to the [`ConnectionHandler`] as
+    /// [`StreamUpgradeError::Timeout`].
+    ///
+    /// Defaults to the timeout of each [`SubstreamProtocol`], see
+    /// [`SubstreamProtocol::with_timeout`].
+    pub fn with_substream_upgrade_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_config.substream_upgrade_timeout = Some(timeout);
+        self
+    }
+
+    /// Prepends `prefix` to the name of every protocol negotiated on a connection, e.g. turning
+    /// `/ipfs/ping/1.0.0` into `/mynet/ipfs/ping/1.0.0` for a prefix of `/mynet`.
+    ///
+    /// Only peers using the same prefix can negotiate any protocol with each other, isolating the
+    /// protocols of a private network from those of other networks. Note that this is no security
+    /// measure, see the `pnet` transport upgrade for that.
+    ///
+    /// The prefix is only applied on the wire. [`ConnectionHandler`]s and
+    /// [`NetworkBehaviour`]s keep using the unprefixed protocol names.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` does not start with a `/` or ends with one.
+    pub fn with_protocol_prefix(mut self, prefix: &str) -> Self {
+        assert!(
+            prefix.starts_with('/') && !prefix.ends_with('/'),
+            "Protocol prefix must start with and not end with a `/`: {prefix}"
+        );
+
+        self.pool_config.protocol_prefix = Some(Arc::from(prefix));
+        self
+    }
+
+    /// The number of protocols a remote may propose, and have rejected, when negotiating an
+    /// inbound stream. The negotiation is aborted once the remote proposes another protocol that
+    /// is not supported, such that a remote cannot keep the negotiation going forever.
+    ///
+    /// Defaults to 1000.
+    pub fn with_max_negotiated_protocols(mut self, max: usize) -> Self {
+        self.pool_config.max_negotiated_protocols = Some(max);
+        self
+    }
+
+    /// Whether to count the bytes read from and written to the streams of each connection,
+    /// queryable via [`Swarm::connection_byte_counters`].
+    ///
+    /// Counting costs one atomic addition per read and write. Disabled by default.
+    pub fn with_connection_counters(mut self, enabled: bool) -> Self {
+        self.pool_config.connection_counters = enabled;
+        self
+    }
+
+    /// Makes [`NotifyHandler::Any`] avoid connections that have not read anything from any of
+    /// their streams within the given window, e.g. half-dead relayed connections, as long as the
+    /// peer has a fresher connection.
+    ///
+    /// Connections count as fresh for the window after being established. Stale connections are
+    /// still used if no other connection is ready to receive the event. Tracking the reads costs
+    /// one atomic operation per read. Disabled by default.
+    pub fn with_notify_any_read_window(mut self, window: Duration) -> Self {
+        self.pool_config.notify_any_read_window = Some(window);
+        self
+    }
+
+    /// Confirmed external addresses the [`Swarm`] starts with, e.g. the ones saved via
+    /// [`ExternalAddresses::snapshot`] before a restart.
+    ///
+    /// Each address is reported to the [`NetworkBehaviour`] via
+    /// [`FromSwarm::ExternalAddrConfirmed`] once, right after the [`Swarm`] is created.
+    pub fn with_external_addresses(mut self, addresses: Vec<Multiaddr>) -> Self {
+        self.external_addresses = addresses;
+        self
+    }
+
+    /// How long the [`PeerMetadata`] of a peer is retained after the last connection to it closed.
+    ///
+    /// Defaults to 60 seconds.
+    pub fn with_peer_metadata_grace_period(mut self, grace_period: Duration) -> Self {
+        self.peer_metadata_grace_period = grace_period;
+        self
+    }
+
+    /// How long a candidate reported via [`ToSwarm::NewExternalAddrCandidate`] is retained unless
+    /// it is confirmed or reported again.
+    ///
+    /// Expired candidates are reported via [`FromSwarm::ExternalAddrCandidateExpired`].
+    /// Confirming a candidate, e.g. via [`ToSwarm::ExternalAddrConfirmed`], stops its TTL and
+    /// reporting it again restarts it.
+    ///
+    /// By default, candidates never expire.
+    pub fn with_candidate_ttl(mut self, ttl: Duration) -> Self {
+        self.candidate_ttl = Some(ttl);
+        self
+    }
+
+    /// Whether to delay [`FromSwarm::ConnectionEstablished`] until the first inbound or outbound
+    /// substream of the connection has been fully negotiated.
+    ///
+    /// Useful for behaviours that only care about usable connections and would otherwise react to
+    /// connections that fail before any protocol is negotiated. Events of the connection's handler
+    /// are held back until the connection is reported. Connections closed before their first
+    /// substream is negotiated are reported, together with their held back handler events, right
+    /// before their [`FromSwarm::ConnectionClosed`], as the behaviour already created their
+    /// handler. The `other_established` and `remaining_established` counts only include
+    /// connections that have been reported.
+    /// [`SwarmEvent::ConnectionEstablished`] is not delayed.
+    ///
+    /// Defaults to `false`.
+    pub fn with_defer_connection_established_until_first_substream(mut self, defer: bool) -> Self {
+        self.defer_connection_established = defer;
+        self
+    }
+
+    /// Consult the given [`ResourceManager`] before accepting each inbound connection.
+    ///
+    /// Inbound connections are refused with [`ListenError::InsufficientResources`] while
+    /// [`ResourceManager::can_accept_inbound`] returns `false`.
+    pub fn with_resource_manager(mut self, manager: impl ResourceManager + 'static) -> Self {
+        self.resource_manager = Some(Box::new(manager));
+        self
+    }
+
+    /// Accept at most `per_second` inbound connections per second, across all listeners and
+    /// connections adopted via [`Swarm::adopt_connection`].
+    ///
+    /// Bursts of up to `per_second` connections are accepted at once. Inbound connections beyond
+    /// the rate are refused with [`ListenError::RateLimited`] before any handshake, so that a flood
+    /// of new connections can't exhaust the capacity for handshakes.
+    ///
+    /// By default, there is no limit.
+    pub fn with_inbound_connection_rate_limit(mut self, per_second: u32) -> Self {
+        self.inbound_connection_rate_limit = Some(per_second);
+        self
+    }
+
+    /// Translate candidates for external addresses with the given function before falling back to
+    /// [`Transport::address_translation`].
+    ///
+    /// For each [`ToSwarm::NewExternalAddrCandidate`], the function is called with every listen
+    /// address and the observed candidate. A returned address takes the place of the transport's
+    /// translation, e.g. to apply a static NAT mapping whose external port differs from the port
+    /// listened on. Returning [`None`] falls back to the transport.
+    ///
+    /// By default, only the transport translates candidates.
+    pub fn with_address_translator(
+        mut self,
+        translator: impl Fn(&Multiaddr, &Multiaddr) -> Option<Multiaddr> + Send + 'static,
+    ) -> Self {
+        self.address_translator = Some(Box::new(translator));
+        self
+    }
+
+    /// The maximum number of commands of the [`NetworkBehaviour`] being processed at once, see
+    /// [`Swarm::pending_behaviour_commands`].
+    ///
+    /// Once reached, the behaviour is no longer polled for new commands until some of them are
+    /// processed, e.g. until dials via [`ToSwarm::Dial`] succeed or fail. This bounds the memory
+    /// used by a behaviour issuing many commands at once. Commands are processed in the order they
+    /// were issued in.
+    ///
+    /// By default, there is no limit.
+    pub fn with_max_pending_behaviour_commands(mut self, max: usize) -> Self {
+        self.max_pending_behaviour_commands = Some(max);
+        self
+    }
+
+    /// Sets the [`Clock`] driving the idle and upgrade timeouts of the connections.
+    ///
+    /// Defaults to the [`SystemClock`]. Replace it e.g. with the `VirtualClock` of the `testing`
+    /// module to advance time instantly in tests.
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.pool_config.clock = Arc::new(clock);
+        self
+    }
+}
+
+/// An established connection whose [`FromSwarm::ConnectionEstablished`] is deferred until its first
+/// substream is negotiated.
+struct DeferredConnection<THandlerEvent> {
+    endpoint: ConnectedPoint,
+    failed_addresses: Vec<Multiaddr>,
+    /// Events of the connection's handler, delivered once the connection is reported.
+    handler_events: Vec<THandlerEvent>,
+}
+
+/// Possible errors when trying to establish or upgrade an outbound connection.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DialError {
+    /// The peer identity obtained on the connection matches the local peer.
+    LocalPeerId {
+        endpoint: ConnectedPoint,
+    },
+    /// No addresses have been provided by [`NetworkBehaviour::handle_pending_outbound_connection`] and [`DialOpts`].
+    ///
+    /// Also returned if none of the addresses is a relayed address when dialing with
+    /// [`relay_only`](dial_opts::WithPeerIdWithAddresses::relay_only).
+    NoAddresses,
+    /// The provided [`dial_opts::PeerCondition`] evaluated to false and thus
+    /// the dial was aborted.
+    DialPeerConditionFalse(dial_opts::PeerCondition),
+    /// Pending connection attempt has been aborted.
+    Aborted,
+    /// The connection was not established within the configured handshake timeout or the
+    /// timeout of the dial.
+    ///
+    /// See [`Config::with_handshake_timeout`] and [`dial_opts::WithPeerId::timeout`].
+    Timeout,
+    /// The peer identity obtained on the connection did not match the one that was expected.
+    WrongPeerId {
+        obtained: PeerId,
+        endpoint: ConnectedPoint,
+    },
+    Denied {
+        cause: ConnectionDenied,
+    },
+    /// The peer is banned until the given instant.
+    ///
+    /// See [`Swarm::ban_peer`].
+    Banned {
+        until: Instant,
+    },
     /// An error occurred while negotiating the transport protocol(s) on a connection.
     Transport(Vec<(Multiaddr, TransportError<io::Error>)>),
 }
@@ -1552,6 +3037,7 @@ impl From<PendingOutboundConnectionError> for DialError {
     fn from(error: PendingOutboundConnectionError) -> Self {
         match error {
             PendingConnectionError::Aborted => DialError::Aborted,
+            PendingConnectionError::Timeout => DialError::Timeout,
             PendingConnectionError::WrongPeerId { obtained, endpoint } => {
                 DialError::WrongPeerId { obtained, endpoint }
             }
@@ -1577,6 +3063,10 @@ impl fmt::Display for DialError {
                 f,
                 "Dial error: Pending connection attempt has been aborted."
             ),
+            DialError::Timeout => write!(
+                f,
+                "Dial error: Pending connection attempt exceeded the handshake or dial timeout."
+            ),
             DialError::WrongPeerId { obtained, endpoint } => write!(
                 f,
                 "Dial error: Unexpected peer ID {obtained} at {endpoint:?}."
@@ -1596,6 +3086,9 @@ impl fmt::Display for DialError {
             DialError::Denied { .. } => {
                 write!(f, "Dial error")
             }
+            DialError::Banned { until } => {
+                write!(f, "Dial error: Peer is banned until {until:?}.")
+            }
         }
     }
 }
@@ -1617,18 +3110,25 @@ impl error::Error for DialError {
             DialError::NoAddresses => None,
             DialError::DialPeerConditionFalse(_) => None,
             DialError::Aborted => None,
+            DialError::Timeout => None,
             DialError::WrongPeerId { .. } => None,
             DialError::Transport(_) => None,
             DialError::Denied { cause } => Some(cause),
+            DialError::Banned { .. } => None,
         }
     }
 }
 
 /// Possible errors when upgrading an inbound connection.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ListenError {
     /// Pending connection attempt has been aborted.
     Aborted,
+    /// The connection was not established within the configured handshake timeout.
+    ///
+    /// See [`Config::with_handshake_timeout`].
+    Timeout,
     /// The peer identity obtained on the connection did not match the one that was expected.
     WrongPeerId {
         obtained: PeerId,
@@ -1643,6 +3143,25 @@ pub enum ListenError {
     },
     /// An error occurred while negotiating the transport protocol(s) on a connection.
     Transport(TransportError<io::Error>),
+    /// The connection was refused because its listener is paused.
+    ///
+    /// See [`Swarm::pause_listener`].
+    ListenerPaused,
+    /// The remote peer is banned until the given instant.
+    ///
+    /// See [`Swarm::ban_peer`].
+    Banned {
+        until: Instant,
+    },
+    /// The connection was refused because the [`ResourceManager`] reported insufficient
+    /// resources.
+    ///
+    /// See [`Config::with_resource_manager`].
+    InsufficientResources,
+    /// The connection was refused because inbound connections arrived faster than allowed.
+    ///
+    /// See [`Config::with_inbound_connection_rate_limit`].
+    RateLimited,
 }
 
 impl From<PendingInboundConnectionError> for ListenError {
@@ -1650,6 +3169,7 @@ impl From<PendingInboundConnectionError> for ListenError {
         match error {
             PendingInboundConnectionError::Transport(inner) => ListenError::Transport(inner),
             PendingInboundConnectionError::Aborted => ListenError::Aborted,
+            PendingInboundConnectionError::Timeout => ListenError::Timeout,
             PendingInboundConnectionError::WrongPeerId { obtained, endpoint } => {
                 ListenError::WrongPeerId { obtained, endpoint }
             }
@@ -1667,6 +3187,10 @@ impl fmt::Display for ListenError {
                 f,
                 "Listen error: Pending connection attempt has been aborted."
             ),
+            ListenError::Timeout => write!(
+                f,
+                "Listen error: Pending connection attempt exceeded the handshake timeout."
+            ),
             ListenError::WrongPeerId { obtained, endpoint } => write!(
                 f,
                 "Listen error: Unexpected peer ID {obtained} at {endpoint:?}."
@@ -1680,6 +3204,18 @@ impl fmt::Display for ListenError {
             ListenError::LocalPeerId { endpoint } => {
                 write!(f, "Listen error: Local peer ID at {endpoint:?}.")
             }
+            ListenError::ListenerPaused => {
+                write!(f, "Listen error: Listener is paused.")
+            }
+            ListenError::Banned { until } => {
+                write!(f, "Listen error: Peer is banned until {until:?}.")
+            }
+            ListenError::InsufficientResources => {
+                write!(f, "Listen error: Insufficient resources.")
+            }
+            ListenError::RateLimited => {
+                write!(f, "Listen error: Inbound connection rate limit exceeded.")
+            }
         }
     }
 }
@@ -1690,8 +3226,13 @@ impl error::Error for ListenError {
             ListenError::WrongPeerId { .. } => None,
             ListenError::Transport(err) => Some(err),
             ListenError::Aborted => None,
+            ListenError::Timeout => None,
             ListenError::Denied { cause } => Some(cause),
             ListenError::LocalPeerId { .. } => None,
+            ListenError::ListenerPaused => None,
+            ListenError::Banned { .. } => None,
+            ListenError::InsufficientResources => None,
+            ListenError::RateLimited => None,
         }
     }
 }
@@ -1702,12 +3243,31 @@ impl error::Error for ListenError {
 #[derive(Debug)]
 pub struct ConnectionDenied {
     inner: Box<dyn error::Error + Send + Sync + 'static>,
+    /// The kind, current number and limit of connections, if denied because of a limit.
+    limit: Option<(LimitKind, u32, u32)>,
 }
 
 impl ConnectionDenied {
     pub fn new(cause: impl Into<Box<dyn error::Error + Send + Sync + 'static>>) -> Self {
         Self {
             inner: cause.into(),
+            limit: None,
+        }
+    }
+
+    /// Denies a connection because the limit on the number of connections of the given kind was
+    /// reached, with `current` connections of that kind.
+    ///
+    /// All behaviours are informed via [`FromSwarm::ConnectionLimitReached`].
+    pub fn limit_reached(
+        cause: impl Into<Box<dyn error::Error + Send + Sync + 'static>>,
+        kind: LimitKind,
+        current: u32,
+        limit: u32,
+    ) -> Self {
+        Self {
+            inner: cause.into(),
+            limit: Some((kind, current, limit)),
         }
     }
 
@@ -1719,7 +3279,10 @@ impl ConnectionDenied {
         let inner = self
             .inner
             .downcast::<E>()
-            .map_err(|inner| ConnectionDenied { inner })?;
+            .map_err(|inner| ConnectionDenied {
+                inner,
+                limit: self.limit,
+            })?;
 
         Ok(*inner)
     }
@@ -1745,6 +3308,55 @@ impl error::Error for ConnectionDenied {
     }
 }
 
+/// A listen address was denied.
+///
+/// See [`NetworkBehaviour::handle_pending_listen`].
+#[derive(Debug)]
+pub struct ListenDenied {
+    inner: Box<dyn error::Error + Send + Sync + 'static>,
+}
+
+impl ListenDenied {
+    pub fn new(cause: impl Into<Box<dyn error::Error + Send + Sync + 'static>>) -> Self {
+        Self {
+            inner: cause.into(),
+        }
+    }
+
+    /// Attempt to downcast to a particular reason for why the listen address was denied.
+    pub fn downcast<E>(self) -> Result<E, Self>
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        let inner = self
+            .inner
+            .downcast::<E>()
+            .map_err(|inner| ListenDenied { inner })?;
+
+        Ok(*inner)
+    }
+
+    /// Attempt to downcast to a particular reason for why the listen address was denied.
+    pub fn downcast_ref<E>(&self) -> Option<&E>
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        self.inner.downcast_ref::<E>()
+    }
+}
+
+impl fmt::Display for ListenDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "listen address denied")
+    }
+}
+
+impl error::Error for ListenDenied {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.inner.as_ref())
+    }
+}
+
 /// Information about the connections obtained by [`Swarm::network_info()`].
 #[derive(Clone, Debug)]
 pub struct NetworkInfo {
@@ -1752,6 +3364,10 @@ pub struct NetworkInfo {
     num_peers: usize,
     /// Counters of ongoing network connections.
     connection_counters: ConnectionCounters,
+    /// Established connections by transport and direction.
+    connections_by_transport: BTreeMap<TransportKind, DirectionCounts>,
+    /// Pending connections by transport and direction.
+    pending_by_transport: BTreeMap<TransportKind, DirectionCounts>,
 }
 
 impl NetworkInfo {
@@ -1765,12 +3381,73 @@ impl NetworkInfo {
     pub fn connection_counters(&self) -> &ConnectionCounters {
         &self.connection_counters
     }
+
+    /// The number of established connections in each direction, by the [`TransportKind`] of the
+    /// remote address of each connection.
+    ///
+    /// Relayed connections are counted as [`TransportKind::Relay`], regardless of the transport
+    /// used to reach the relay. Connections whose remote address does not name a known transport
+    /// are not counted.
+    pub fn connections_by_transport(&self) -> &BTreeMap<TransportKind, DirectionCounts> {
+        &self.connections_by_transport
+    }
+
+    /// The number of pending connections in each direction, by [`TransportKind`].
+    ///
+    /// An outgoing connection dialing addresses of several transports counts towards each of
+    /// them.
+    pub fn pending_by_transport(&self) -> &BTreeMap<TransportKind, DirectionCounts> {
+        &self.pending_by_transport
+    }
+}
+
+/// Negotiates and applies `upgrade` on a connection adopted via [`Swarm::adopt_connection`], as
+/// dialer or listener depending on `endpoint`.
+async fn apply_upgrade<C, U, TOutput, TError>(
+    connection: C,
+    upgrade: U,
+    endpoint: Endpoint,
+) -> Result<TOutput, io::Error>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: InboundConnectionUpgrade<Negotiated<C>, Output = TOutput, Error = TError>
+        + OutboundConnectionUpgrade<Negotiated<C>, Output = TOutput, Error = TError>,
+    TError: error::Error + Send + Sync + 'static,
+{
+    let protocols = upgrade.protocol_info();
+    match endpoint {
+        Endpoint::Dialer => {
+            let (info, connection) = multistream_select::dialer_select_proto(
+                connection,
+                protocols,
+                multistream_select::Version::V1,
+            )
+            .await
+            .map_err(io::Error::other)?;
+            upgrade
+                .upgrade_outbound(connection, info)
+                .await
+                .map_err(io::Error::other)
+        }
+        Endpoint::Listener => {
+            let (info, connection) =
+                multistream_select::listener_select_proto(connection, protocols)
+                    .await
+                    .map_err(io::Error::other)?;
+            upgrade
+                .upgrade_inbound(connection, info)
+                .await
+                .map_err(io::Error::other)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test::{CallTraceBehaviour, MockBehaviour};
+    use dial_opts::{AddressOrder, WithPeerIdWithAddresses};
+    use futures::future::BoxFuture;
     use libp2p_core::multiaddr::multiaddr;
     use libp2p_core::transport::memory::MemoryTransportError;
     use libp2p_core::{multiaddr, upgrade};
@@ -1778,6 +3455,7 @@ mod tests {
     use libp2p_plaintext as plaintext;
     use libp2p_yamux as yamux;
     use quickcheck::*;
+    use std::sync::{Arc, Mutex};
 
     // Test execution state.
     // Connection => Disconnecting => Connecting.
@@ -1909,431 +3587,3153 @@ mod tests {
         .await
     }
 
-    /// Establishes multiple connections between two peers,
-    /// after which one peer disconnects the other
-    /// using [`ToSwarm::CloseConnection`] returned by a [`NetworkBehaviour`].
-    ///
-    /// The test expects both behaviours to be notified via calls to [`NetworkBehaviour::on_swarm_event`]
-    /// with pairs of [`FromSwarm::ConnectionEstablished`] / [`FromSwarm::ConnectionClosed`]
+    /// Polls both swarms until they are connected via `num_connections` connections or, if
+    /// `num_connections` is zero, disconnected.
+    async fn drive_until_connected(
+        swarm1: &mut Swarm<CallTraceBehaviour<MockBehaviour<dummy::ConnectionHandler, ()>>>,
+        swarm2: &mut Swarm<CallTraceBehaviour<MockBehaviour<dummy::ConnectionHandler, ()>>>,
+        num_connections: usize,
+    ) {
+        future::poll_fn(|cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut *swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut *swarm2), cx);
+            let done = if num_connections == 0 {
+                swarms_disconnected(swarm1, swarm2)
+            } else {
+                swarms_connected(swarm1, swarm2, num_connections)
+            };
+            if done {
+                return Poll::Ready(());
+            }
+            if poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await
+    }
+
     #[tokio::test]
-    async fn test_behaviour_disconnect_all() {
+    async fn banned_peer_is_disconnected_and_denied_until_unbanned() {
         let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
         let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
 
         let addr1: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
         let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
-
         swarm1.listen_on(addr1.clone()).unwrap();
         swarm2.listen_on(addr2.clone()).unwrap();
+        let swarm2_id = *swarm2.local_peer_id();
+        let swarm2_addr = addr2.clone().with(multiaddr::Protocol::P2p(swarm2_id));
+
+        swarm1.dial(addr2.clone()).unwrap();
+        drive_until_connected(&mut swarm1, &mut swarm2, 1).await;
+
+        swarm1.ban_peer(swarm2_id, Duration::from_secs(60));
+        assert!(swarm1.is_banned(&swarm2_id));
+        drive_until_connected(&mut swarm1, &mut swarm2, 0).await;
+
+        // Dials of the banned peer fail right away.
+        let error = swarm1.dial(swarm2_addr.clone()).unwrap_err();
+        assert!(matches!(error, DialError::Banned { .. }));
+        assert_eq!(
+            swarm1.behaviour().on_dial_failure.last(),
+            Some(&Some(swarm2_id))
+        );
 
-        let swarm1_id = *swarm1.local_peer_id();
-
-        let mut reconnected = false;
-        let num_connections = 10;
-
-        for _ in 0..num_connections {
-            swarm1.dial(addr2.clone()).unwrap();
-        }
-        let mut state = State::Connecting;
-
-        future::poll_fn(move |cx| loop {
-            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
-            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
-            match state {
-                State::Connecting => {
-                    if swarms_connected(&swarm1, &swarm2, num_connections) {
-                        if reconnected {
-                            return Poll::Ready(());
-                        }
-                        swarm2
-                            .behaviour
-                            .inner()
-                            .next_action
-                            .replace(ToSwarm::CloseConnection {
-                                peer_id: swarm1_id,
-                                connection: CloseConnection::All,
-                            });
-                        state = State::Disconnecting;
-                        continue;
-                    }
-                }
-                State::Disconnecting => {
-                    if swarms_disconnected(&swarm1, &swarm2) {
-                        reconnected = true;
-                        for _ in 0..num_connections {
-                            swarm2.dial(addr1.clone()).unwrap();
-                        }
-                        state = State::Connecting;
-                        continue;
+        // Connections turning out to be with the banned peer are denied once established.
+        swarm1.dial(addr2).unwrap();
+        swarm2.dial(addr1).unwrap();
+        let mut outbound_denied = false;
+        let mut inbound_denied = false;
+        future::poll_fn(|cx| {
+            let _ = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm1), cx) {
+                match event {
+                    SwarmEvent::OutgoingConnectionError {
+                        error: DialError::Banned { .. },
+                        ..
+                    } => outbound_denied = true,
+                    SwarmEvent::IncomingConnectionError {
+                        error: ListenError::Banned { .. },
+                        ..
+                    } => inbound_denied = true,
+                    SwarmEvent::ConnectionEstablished { .. } => {
+                        panic!("Unexpected connection with banned peer")
                     }
+                    _ => {}
                 }
             }
-
-            if poll1.is_pending() && poll2.is_pending() {
-                return Poll::Pending;
+            if outbound_denied && inbound_denied {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
             }
         })
-        .await
+        .await;
+        assert_eq!(swarm1.behaviour().num_connections_to_peer(swarm2_id), 0);
+
+        assert!(swarm1.unban_peer(swarm2_id));
+        assert!(!swarm1.is_banned(&swarm2_id));
+        swarm1.dial(swarm2_addr).unwrap();
+        drive_until_connected(&mut swarm1, &mut swarm2, 1).await;
     }
 
-    /// Establishes multiple connections between two peers,
-    /// after which one peer closes a single connection
-    /// using [`ToSwarm::CloseConnection`] returned by a [`NetworkBehaviour`].
-    ///
-    /// The test expects both behaviours to be notified via calls to [`NetworkBehaviour::on_swarm_event`]
-    /// with pairs of [`FromSwarm::ConnectionEstablished`] / [`FromSwarm::ConnectionClosed`]
     #[tokio::test]
-    async fn test_behaviour_disconnect_one() {
+    async fn peer_ban_expires() {
         let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
         let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
 
-        let addr1: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
         let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
-
-        swarm1.listen_on(addr1).unwrap();
         swarm2.listen_on(addr2.clone()).unwrap();
+        let swarm2_id = *swarm2.local_peer_id();
+        let swarm2_addr = addr2.with(multiaddr::Protocol::P2p(swarm2_id));
 
-        let swarm1_id = *swarm1.local_peer_id();
+        swarm1.ban_peer(swarm2_id, Duration::from_millis(100));
+        assert!(matches!(
+            swarm1.dial(swarm2_addr.clone()),
+            Err(DialError::Banned { .. })
+        ));
 
-        let num_connections = 10;
+        futures_timer::Delay::new(Duration::from_millis(200)).await;
 
-        for _ in 0..num_connections {
-            swarm1.dial(addr2.clone()).unwrap();
-        }
-        let mut state = State::Connecting;
-        let mut disconnected_conn_id = None;
+        assert!(!swarm1.is_banned(&swarm2_id));
+        swarm1.dial(swarm2_addr).unwrap();
+        drive_until_connected(&mut swarm1, &mut swarm2, 1).await;
+    }
 
-        future::poll_fn(move |cx| loop {
-            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
-            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
-            match state {
-                State::Connecting => {
-                    if swarms_connected(&swarm1, &swarm2, num_connections) {
-                        disconnected_conn_id = {
-                            let conn_id =
-                                swarm2.behaviour.on_connection_established[num_connections / 2].1;
-                            swarm2.behaviour.inner().next_action.replace(
-                                ToSwarm::CloseConnection {
-                                    peer_id: swarm1_id,
-                                    connection: CloseConnection::One(conn_id),
-                                },
-                            );
-                            Some(conn_id)
-                        };
-                        state = State::Disconnecting;
+    #[tokio::test]
+    async fn paused_listener_refuses_inbound_connections() {
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        let listener_id = swarm2.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+                break address;
+            }
+        };
+
+        assert!(swarm2.pause_listener(listener_id));
+        swarm1.dial(addr.clone()).unwrap();
+
+        let mut refused = false;
+        let mut dial_failed = false;
+        future::poll_fn(|cx| {
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm2), cx) {
+                match event {
+                    SwarmEvent::IncomingConnectionError {
+                        error: ListenError::ListenerPaused,
+                        ..
+                    } => refused = true,
+                    SwarmEvent::ConnectionEstablished { .. } => {
+                        panic!("Unexpected connection on paused listener")
                     }
+                    _ => {}
                 }
-                State::Disconnecting => {
-                    for s in &[&swarm1, &swarm2] {
-                        assert!(s
-                            .behaviour
-                            .on_connection_closed
-                            .iter()
-                            .all(|(.., remaining_conns)| *remaining_conns > 0));
-                        assert_eq!(s.behaviour.on_connection_established.len(), num_connections);
-                        s.behaviour.assert_connected(num_connections, 1);
-                    }
-                    if [&swarm1, &swarm2]
-                        .iter()
-                        .all(|s| s.behaviour.on_connection_closed.len() == 1)
-                    {
-                        let conn_id = swarm2.behaviour.on_connection_closed[0].1;
-                        assert_eq!(Some(conn_id), disconnected_conn_id);
-                        return Poll::Ready(());
-                    }
+            }
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm1), cx) {
+                if let SwarmEvent::OutgoingConnectionError { .. } = event {
+                    dial_failed = true;
                 }
             }
+            if refused && dial_failed {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+        assert!(swarm2
+            .behaviour()
+            .handle_pending_inbound_connection
+            .is_empty());
 
+        assert!(swarm2.resume_listener(listener_id));
+        swarm1.dial(addr).unwrap();
+
+        future::poll_fn(|cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+            if swarms_connected(&swarm1, &swarm2, 1) {
+                return Poll::Ready(());
+            }
             if poll1.is_pending() && poll2.is_pending() {
                 return Poll::Pending;
             }
         })
-        .await
+        .await;
     }
 
-    #[test]
-    fn concurrent_dialing() {
-        #[derive(Clone, Debug)]
-        struct DialConcurrencyFactor(NonZeroU8);
+    #[tokio::test]
+    async fn inbound_connections_beyond_rate_limit_are_refused() {
+        const RATE: u32 = 2;
+        const NUM_DIALS: u32 = 6;
 
-        impl Arbitrary for DialConcurrencyFactor {
-            fn arbitrary(g: &mut Gen) -> Self {
-                Self(NonZeroU8::new(g.gen_range(1..11)).unwrap())
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 =
+            new_test_swarm(Config::with_tokio_executor().with_inbound_connection_rate_limit(RATE));
+
+        swarm2.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+                break address;
             }
+        };
+
+        for _ in 0..NUM_DIALS {
+            swarm1.dial(addr.clone()).unwrap();
         }
 
-        fn prop(concurrency_factor: DialConcurrencyFactor) {
-            tokio::runtime::Runtime::new().unwrap().block_on(async {
-                let mut swarm = new_test_swarm(
-                    Config::with_tokio_executor()
-                        .with_dial_concurrency_factor(concurrency_factor.0),
-                );
+        let mut accepted = 0;
+        let mut refused = 0;
+        future::poll_fn(|cx| {
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm2), cx) {
+                match event {
+                    SwarmEvent::IncomingConnection { .. } => accepted += 1,
+                    SwarmEvent::IncomingConnectionError {
+                        error: ListenError::RateLimited,
+                        ..
+                    } => refused += 1,
+                    _ => {}
+                }
+            }
+            while Swarm::poll_next_event(Pin::new(&mut swarm1), cx).is_ready() {}
+            if accepted + refused == NUM_DIALS {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
 
-                // Listen on `concurrency_factor + 1` addresses.
-                //
-                // `+ 2` to ensure a subset of addresses is dialed by network_2.
-                let num_listen_addrs = concurrency_factor.0.get() + 2;
-                let mut listen_addresses = Vec::new();
-                let mut transports = Vec::new();
-                for _ in 0..num_listen_addrs {
-                    let mut transport = transport::MemoryTransport::default().boxed();
-                    transport
-                        .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
-                        .unwrap();
+        // The burst is accepted, later connections are refused until the rate allows new ones.
+        assert!(accepted >= RATE, "{accepted} connections accepted");
+        assert!(
+            refused >= NUM_DIALS - RATE - 1,
+            "{refused} connections refused"
+        );
+        assert_eq!(
+            swarm2.behaviour().handle_pending_inbound_connection.len() as u32,
+            accepted
+        );
+    }
 
-                    match transport.select_next_some().await {
-                        TransportEvent::NewAddress { listen_addr, .. } => {
-                            listen_addresses.push(listen_addr);
-                        }
-                        _ => panic!("Expected `NewListenAddr` event."),
-                    }
+    #[tokio::test]
+    async fn resource_manager_refuses_inbound_connections() {
+        use std::sync::atomic::{AtomicBool, Ordering};
 
-                    transports.push(transport);
-                }
+        let can_accept = Arc::new(AtomicBool::new(false));
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor().with_resource_manager({
+            let can_accept = can_accept.clone();
+            move || can_accept.load(Ordering::SeqCst)
+        }));
+
+        swarm2.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+                break address;
+            }
+        };
 
-                // Have swarm dial each listener and wait for each listener to receive the incoming
-                // connections.
-                swarm
-                    .dial(
-                        DialOpts::peer_id(PeerId::random())
-                            .addresses(listen_addresses)
-                            .build(),
-                    )
-                    .unwrap();
-                for mut transport in transports.into_iter() {
-                    match futures::future::select(transport.select_next_some(), swarm.next()).await
-                    {
-                        future::Either::Left((TransportEvent::Incoming { .. }, _)) => {}
-                        future::Either::Left(_) => {
-                            panic!("Unexpected transport event.")
-                        }
-                        future::Either::Right((e, _)) => {
-                            panic!("Expect swarm to not emit any event {e:?}")
-                        }
+        swarm1.dial(addr.clone()).unwrap();
+
+        let mut refused = false;
+        let mut dial_failed = false;
+        future::poll_fn(|cx| {
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm2), cx) {
+                match event {
+                    SwarmEvent::IncomingConnectionError {
+                        error: ListenError::InsufficientResources,
+                        ..
+                    } => refused = true,
+                    SwarmEvent::ConnectionEstablished { .. } => {
+                        panic!("Unexpected connection despite insufficient resources")
                     }
+                    _ => {}
                 }
-
-                match swarm.next().await.unwrap() {
-                    SwarmEvent::OutgoingConnectionError { .. } => {}
-                    e => panic!("Unexpected swarm event {e:?}"),
+            }
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm1), cx) {
+                if let SwarmEvent::OutgoingConnectionError { .. } = event {
+                    dial_failed = true;
                 }
-            })
-        }
+            }
+            if refused && dial_failed {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+        assert!(swarm2
+            .behaviour()
+            .handle_pending_inbound_connection
+            .is_empty());
 
-        QuickCheck::new().tests(10).quickcheck(prop as fn(_) -> _);
+        can_accept.store(true, Ordering::SeqCst);
+        swarm1.dial(addr).unwrap();
+
+        future::poll_fn(|cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+            if swarms_connected(&swarm1, &swarm2, 1) {
+                return Poll::Ready(());
+            }
+            if poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await;
     }
 
     #[tokio::test]
-    async fn invalid_peer_id() {
-        // Checks whether dialing an address containing the wrong peer id raises an error
-        // for the expected peer id instead of the obtained peer id.
-
+    async fn labeled_connections_carry_label() {
         let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
         let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
 
-        swarm1.listen_on("/memory/0".parse().unwrap()).unwrap();
+        let listener_id = swarm2.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        assert_eq!(swarm2.set_listener_label(listener_id, "public"), None);
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+                break address;
+            }
+        };
 
-        let address = future::poll_fn(|cx| match swarm1.poll_next_unpin(cx) {
-            Poll::Ready(Some(SwarmEvent::NewListenAddr { address, .. })) => Poll::Ready(address),
-            Poll::Pending => Poll::Pending,
-            _ => panic!("Was expecting the listen address to be reported"),
+        let opts = DialOpts::unknown_peer_id()
+            .address(addr)
+            .label("bootstrap")
+            .build();
+        let dial_id = opts.connection_id();
+        swarm1.dial(opts).unwrap();
+        assert_eq!(swarm1.connection_label(dial_id), Some("bootstrap"));
+
+        let mut established1 = None;
+        let mut established2 = None;
+        future::poll_fn(|cx| {
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm1), cx) {
+                if let SwarmEvent::ConnectionEstablished {
+                    connection_id,
+                    label,
+                    ..
+                } = event
+                {
+                    established1 = Some((connection_id, label));
+                }
+            }
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm2), cx) {
+                if let SwarmEvent::ConnectionEstablished {
+                    connection_id,
+                    label,
+                    ..
+                } = event
+                {
+                    established2 = Some((connection_id, label));
+                }
+            }
+            if established1.is_some() && established2.is_some() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
         })
         .await;
 
-        let other_id = PeerId::random();
-        let other_addr = address.with(multiaddr::Protocol::P2p(other_id));
+        let (id1, label1) = established1.unwrap();
+        let (id2, label2) = established2.unwrap();
+        assert_eq!(id1, dial_id);
+        assert_eq!(label1.as_deref(), Some("bootstrap"));
+        assert_eq!(label2.as_deref(), Some("public"));
+        assert_eq!(swarm1.connection_label(id1), Some("bootstrap"));
+        assert_eq!(swarm2.connection_label(id2), Some("public"));
+    }
 
-        swarm2.dial(other_addr.clone()).unwrap();
+    #[tokio::test]
+    async fn did_work_is_false_when_idle() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        swarm.listen_on(multiaddr![Memory(0u64)]).unwrap();
 
-        let (peer_id, error) = future::poll_fn(|cx| {
-            if let Poll::Ready(Some(SwarmEvent::IncomingConnection { .. })) =
-                swarm1.poll_next_unpin(cx)
-            {}
+        let event = future::poll_fn(|cx| Swarm::poll_next_event(Pin::new(&mut swarm), cx)).await;
+        assert!(matches!(event, SwarmEvent::NewListenAddr { .. }));
+        assert!(swarm.did_work());
 
-            match swarm2.poll_next_unpin(cx) {
-                Poll::Ready(Some(SwarmEvent::OutgoingConnectionError {
-                    peer_id, error, ..
-                })) => Poll::Ready((peer_id, error)),
-                Poll::Ready(x) => panic!("unexpected {x:?}"),
-                Poll::Pending => Poll::Pending,
-            }
-        })
-        .await;
-        assert_eq!(peer_id.unwrap(), other_id);
-        match error {
-            DialError::WrongPeerId { obtained, endpoint } => {
-                assert_eq!(obtained, *swarm1.local_peer_id());
-                assert_eq!(
-                    endpoint,
-                    ConnectedPoint::Dialer {
-                        address: other_addr,
-                        role_override: Endpoint::Dialer,
-                    }
-                );
-            }
-            x => panic!("wrong error {x:?}"),
-        }
+        // Drain any remaining work, after which the swarm is idle.
+        while future::poll_fn(|cx| Poll::Ready(swarm.poll_next_unpin(cx)))
+            .await
+            .is_ready()
+        {}
+        let poll = future::poll_fn(|cx| Poll::Ready(swarm.poll_next_unpin(cx))).await;
+
+        assert!(poll.is_pending());
+        assert!(!swarm.did_work());
     }
 
     #[tokio::test]
-    async fn dial_self() {
-        // Check whether dialing ourselves correctly fails.
-        //
-        // Dialing the same address we're listening should result in three events:
-        //
-        // - The incoming connection notification (before we know the incoming peer ID).
-        // - The connection error for the dialing endpoint (once we've determined that it's our own ID).
-        // - The connection error for the listening endpoint (once we've determined that it's our own ID).
-        //
-        // The last two can happen in any order.
+    async fn role_override_is_reported_to_behaviour_and_swarm_event() {
+        let id_keys = identity::Keypair::generate_ed25519();
+        let transport = SimultaneousOpenTransport(
+            transport::MemoryTransport::default()
+                .upgrade(upgrade::Version::V1)
+                .authenticate(plaintext::Config::new(&id_keys))
+                .multiplex(yamux::Config::default())
+                .boxed(),
+        )
+        .boxed();
+        let mut swarm1 = Swarm::new(
+            transport,
+            CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(dummy::ConnectionHandler)),
+            id_keys.public().to_peer_id(),
+            Config::with_tokio_executor(),
+        );
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
 
-        let mut swarm = new_test_swarm(Config::with_tokio_executor());
-        swarm.listen_on("/memory/0".parse().unwrap()).unwrap();
+        swarm2.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+                break address;
+            }
+        };
 
-        let local_address = future::poll_fn(|cx| match swarm.poll_next_unpin(cx) {
-            Poll::Ready(Some(SwarmEvent::NewListenAddr { address, .. })) => Poll::Ready(address),
-            Poll::Pending => Poll::Pending,
-            _ => panic!("Was expecting the listen address to be reported"),
+        let opts = DialOpts::peer_id(*swarm2.local_peer_id())
+            .addresses(vec![addr.clone()])
+            .override_role()
+            .build();
+        let dial_id = opts.connection_id();
+        swarm1.dial(opts).unwrap();
+
+        let mut established1 = None;
+        let mut established2 = None;
+        future::poll_fn(|cx| {
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm1), cx) {
+                if let SwarmEvent::ConnectionEstablished {
+                    endpoint,
+                    role_overridden,
+                    ..
+                } = event
+                {
+                    established1 = Some((endpoint, role_overridden));
+                }
+            }
+            while let Poll::Ready(event) = Swarm::poll_next_event(Pin::new(&mut swarm2), cx) {
+                if let SwarmEvent::ConnectionEstablished {
+                    role_overridden, ..
+                } = event
+                {
+                    established2 = Some(role_overridden);
+                }
+            }
+            if established1.is_some() && established2.is_some() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
         })
         .await;
 
-        swarm.listened_addrs.clear(); // This is a hack to actually execute the dial to ourselves which would otherwise be filtered.
+        let (endpoint, role_overridden) = established1.unwrap();
+        assert_eq!(
+            endpoint,
+            ConnectedPoint::Dialer {
+                address: addr.with(multiaddr::Protocol::P2p(*swarm2.local_peer_id())),
+                role_override: Endpoint::Listener,
+            }
+        );
+        assert!(role_overridden);
+        assert!(!established2.unwrap());
+
+        let (_, connection_id, endpoint, _) = &swarm1.behaviour().on_connection_established[0];
+        assert_eq!(*connection_id, dial_id);
+        assert!(endpoint.is_role_overridden());
+        let (_, _, endpoint, _) = &swarm2.behaviour().on_connection_established[0];
+        assert!(!endpoint.is_role_overridden());
+    }
 
-        swarm.dial(local_address.clone()).unwrap();
+    #[tokio::test]
+    async fn reset_behaviour_state_reports_established_connections() {
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
 
-        let mut got_dial_err = false;
-        let mut got_inc_err = false;
-        future::poll_fn(|cx| -> Poll<Result<(), io::Error>> {
-            loop {
-                match swarm.poll_next_unpin(cx) {
-                    Poll::Ready(Some(SwarmEvent::OutgoingConnectionError {
-                        peer_id,
-                        error: DialError::LocalPeerId { .. },
-                        ..
-                    })) => {
-                        assert_eq!(&peer_id.unwrap(), swarm.local_peer_id());
-                        assert!(!got_dial_err);
-                        got_dial_err = true;
-                        if got_inc_err {
-                            return Poll::Ready(Ok(()));
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+        let swarm2_id = *swarm2.local_peer_id();
+
+        let num_connections = 2;
+        for _ in 0..num_connections {
+            swarm1.dial(addr2.clone()).unwrap();
+        }
+
+        future::poll_fn(|cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+            if swarms_connected(&swarm1, &swarm2, num_connections) {
+                return Poll::Ready(());
+            }
+            if poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await;
+
+        swarm1.behaviour_mut().reset();
+        assert_eq!(swarm1.behaviour().num_connections_to_peer(swarm2_id), 0);
+
+        swarm1.reset_behaviour_state();
+
+        let behaviour = swarm1.behaviour();
+        assert_eq!(
+            behaviour.handle_established_outbound_connection.len(),
+            num_connections
+        );
+        assert_eq!(
+            behaviour.num_connections_to_peer(swarm2_id),
+            num_connections
+        );
+        let mut other_established = behaviour
+            .on_connection_established
+            .iter()
+            .map(|(.., other_established)| *other_established)
+            .collect::<Vec<_>>();
+        other_established.sort_unstable();
+        assert_eq!(other_established, vec![0, 1]);
+        assert!(swarm1.is_connected(&swarm2_id));
+    }
+
+    /// Establishes a connection between two peers, after which one peer disconnects the other.
+    ///
+    /// The test expects the disconnecting peer to report a graceful close, while the remote peer
+    /// sees the connection being dropped.
+    #[tokio::test]
+    async fn connection_close_completed_reports_graceful_close() {
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr2.clone()).unwrap();
+
+        let swarm1_id = *swarm1.local_peer_id();
+        let swarm2_id = *swarm2.local_peer_id();
+
+        swarm1.dial(addr2).unwrap();
+        let mut state = State::Connecting;
+
+        future::poll_fn(|cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+            match state {
+                State::Connecting => {
+                    if swarms_connected(&swarm1, &swarm2, 1) {
+                        swarm2
+                            .disconnect_peer_id(swarm1_id)
+                            .expect("Error disconnecting");
+                        state = State::Disconnecting;
+                    }
+                }
+                State::Disconnecting => {
+                    if swarms_disconnected(&swarm1, &swarm2) {
+                        return Poll::Ready(());
+                    }
+                }
+            }
+
+            if poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await;
+
+        let closed = |behaviour: &CallTraceBehaviour<_>| {
+            behaviour
+                .on_connection_close_completed
+                .iter()
+                .map(|(peer_id, _, graceful)| (*peer_id, *graceful))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(closed(swarm2.behaviour()), vec![(swarm1_id, true)]);
+        assert_eq!(closed(swarm1.behaviour()), vec![(swarm2_id, false)]);
+    }
+
+    /// Establishes multiple connections between two peers,
+    /// after which one peer disconnects the other
+    /// using [`ToSwarm::CloseConnection`] returned by a [`NetworkBehaviour`].
+    ///
+    /// The test expects both behaviours to be notified via calls to [`NetworkBehaviour::on_swarm_event`]
+    /// with pairs of [`FromSwarm::ConnectionEstablished`] / [`FromSwarm::ConnectionClosed`]
+    #[tokio::test]
+    async fn test_behaviour_disconnect_all() {
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        let addr1: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+
+        swarm1.listen_on(addr1.clone()).unwrap();
+        swarm2.listen_on(addr2.clone()).unwrap();
+
+        let swarm1_id = *swarm1.local_peer_id();
+
+        let mut reconnected = false;
+        let num_connections = 10;
+
+        for _ in 0..num_connections {
+            swarm1.dial(addr2.clone()).unwrap();
+        }
+        let mut state = State::Connecting;
+
+        future::poll_fn(move |cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+            match state {
+                State::Connecting => {
+                    if swarms_connected(&swarm1, &swarm2, num_connections) {
+                        if reconnected {
+                            return Poll::Ready(());
                         }
+                        swarm2
+                            .behaviour
+                            .inner()
+                            .next_action
+                            .replace(ToSwarm::CloseConnection {
+                                peer_id: swarm1_id,
+                                connection: CloseConnection::All,
+                            });
+                        state = State::Disconnecting;
+                        continue;
                     }
-                    Poll::Ready(Some(SwarmEvent::IncomingConnectionError {
-                        local_addr, ..
-                    })) => {
-                        assert!(!got_inc_err);
-                        assert_eq!(local_addr, local_address);
-                        got_inc_err = true;
-                        if got_dial_err {
-                            return Poll::Ready(Ok(()));
+                }
+                State::Disconnecting => {
+                    if swarms_disconnected(&swarm1, &swarm2) {
+                        reconnected = true;
+                        for _ in 0..num_connections {
+                            swarm2.dial(addr1.clone()).unwrap();
                         }
+                        state = State::Connecting;
+                        continue;
                     }
-                    Poll::Ready(Some(SwarmEvent::IncomingConnection { local_addr, .. })) => {
-                        assert_eq!(local_addr, local_address);
+                }
+            }
+
+            if poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await
+    }
+
+    /// Establishes multiple connections between two peers,
+    /// after which one peer closes a single connection
+    /// using [`ToSwarm::CloseConnection`] returned by a [`NetworkBehaviour`].
+    ///
+    /// The test expects both behaviours to be notified via calls to [`NetworkBehaviour::on_swarm_event`]
+    /// with pairs of [`FromSwarm::ConnectionEstablished`] / [`FromSwarm::ConnectionClosed`]
+    #[tokio::test]
+    async fn test_behaviour_disconnect_one() {
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        let addr1: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+
+        swarm1.listen_on(addr1).unwrap();
+        swarm2.listen_on(addr2.clone()).unwrap();
+
+        let swarm1_id = *swarm1.local_peer_id();
+
+        let num_connections = 10;
+
+        for _ in 0..num_connections {
+            swarm1.dial(addr2.clone()).unwrap();
+        }
+        let mut state = State::Connecting;
+        let mut disconnected_conn_id = None;
+
+        future::poll_fn(move |cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+            match state {
+                State::Connecting => {
+                    if swarms_connected(&swarm1, &swarm2, num_connections) {
+                        disconnected_conn_id = {
+                            let conn_id =
+                                swarm2.behaviour.on_connection_established[num_connections / 2].1;
+                            swarm2.behaviour.inner().next_action.replace(
+                                ToSwarm::CloseConnection {
+                                    peer_id: swarm1_id,
+                                    connection: CloseConnection::One(conn_id),
+                                },
+                            );
+                            Some(conn_id)
+                        };
+                        state = State::Disconnecting;
                     }
-                    Poll::Ready(ev) => {
-                        panic!("Unexpected event: {ev:?}")
+                }
+                State::Disconnecting => {
+                    for s in &[&swarm1, &swarm2] {
+                        assert!(s
+                            .behaviour
+                            .on_connection_closed
+                            .iter()
+                            .all(|(.., remaining_conns)| *remaining_conns > 0));
+                        assert_eq!(s.behaviour.on_connection_established.len(), num_connections);
+                        s.behaviour.assert_connected(num_connections, 1);
                     }
-                    Poll::Pending => break Poll::Pending,
+                    if [&swarm1, &swarm2]
+                        .iter()
+                        .all(|s| s.behaviour.on_connection_closed.len() == 1)
+                    {
+                        let conn_id = swarm2.behaviour.on_connection_closed[0].1;
+                        assert_eq!(Some(conn_id), disconnected_conn_id);
+                        return Poll::Ready(());
+                    }
+                }
+            }
+
+            if poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn concurrent_dialing() {
+        #[derive(Clone, Debug)]
+        struct DialConcurrencyFactor(NonZeroU8);
+
+        impl Arbitrary for DialConcurrencyFactor {
+            fn arbitrary(g: &mut Gen) -> Self {
+                Self(NonZeroU8::new(g.gen_range(1..11)).unwrap())
+            }
+        }
+
+        fn prop(concurrency_factor: DialConcurrencyFactor) {
+            tokio::runtime::Runtime::new().unwrap().block_on(async {
+                let mut swarm = new_test_swarm(
+                    Config::with_tokio_executor()
+                        .with_dial_concurrency_factor(concurrency_factor.0),
+                );
+
+                // Listen on `concurrency_factor + 1` addresses.
+                //
+                // `+ 2` to ensure a subset of addresses is dialed by network_2.
+                let num_listen_addrs = concurrency_factor.0.get() + 2;
+                let mut listen_addresses = Vec::new();
+                let mut transports = Vec::new();
+                for _ in 0..num_listen_addrs {
+                    let mut transport = transport::MemoryTransport::default().boxed();
+                    transport
+                        .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
+                        .unwrap();
+
+                    match transport.select_next_some().await {
+                        TransportEvent::NewAddress { listen_addr, .. } => {
+                            listen_addresses.push(listen_addr);
+                        }
+                        _ => panic!("Expected `NewListenAddr` event."),
+                    }
+
+                    transports.push(transport);
+                }
+
+                // Have swarm dial each listener and wait for each listener to receive the incoming
+                // connections.
+                swarm
+                    .dial(
+                        DialOpts::peer_id(PeerId::random())
+                            .addresses(listen_addresses)
+                            .build(),
+                    )
+                    .unwrap();
+                for mut transport in transports.into_iter() {
+                    match futures::future::select(transport.select_next_some(), swarm.next()).await
+                    {
+                        future::Either::Left((TransportEvent::Incoming { .. }, _)) => {}
+                        future::Either::Left(_) => {
+                            panic!("Unexpected transport event.")
+                        }
+                        future::Either::Right((e, _)) => {
+                            panic!("Expect swarm to not emit any event {e:?}")
+                        }
+                    }
+                }
+
+                match swarm.next().await.unwrap() {
+                    SwarmEvent::OutgoingConnectionError { .. } => {}
+                    e => panic!("Unexpected swarm event {e:?}"),
+                }
+            })
+        }
+
+        QuickCheck::new().tests(10).quickcheck(prop as fn(_) -> _);
+    }
+
+    #[tokio::test]
+    async fn lowering_dial_concurrency_factor_reduces_concurrent_dials() {
+        let mut swarm = new_test_swarm(
+            Config::with_tokio_executor().with_dial_concurrency_factor(NonZeroU8::new(3).unwrap()),
+        );
+        swarm.set_dial_concurrency_factor(NonZeroU8::new(1).unwrap());
+        assert_eq!(swarm.dial_concurrency_factor().get(), 1);
+
+        let mut listen_addresses = Vec::new();
+        let mut transports = Vec::new();
+        for _ in 0..3 {
+            let mut transport = transport::MemoryTransport::default().boxed();
+            transport
+                .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
+                .unwrap();
+
+            match transport.select_next_some().await {
+                TransportEvent::NewAddress { listen_addr, .. } => {
+                    listen_addresses.push(listen_addr);
+                }
+                _ => panic!("Expected `NewListenAddr` event."),
+            }
+
+            transports.push(transport);
+        }
+
+        swarm
+            .dial(
+                DialOpts::peer_id(PeerId::random())
+                    .addresses(listen_addresses)
+                    .build(),
+            )
+            .unwrap();
+
+        // The listeners hold on to, but never upgrade, the incoming connections, thus each dial
+        // stays pending.
+        let mut incoming = Vec::new();
+        let mut transport_events = futures::stream::select_all(transports.iter_mut());
+        let _ = tokio::time::timeout(Duration::from_millis(500), async {
+            loop {
+                if let future::Either::Left((TransportEvent::Incoming { upgrade, .. }, _)) =
+                    future::select(transport_events.select_next_some(), swarm.next()).await
+                {
+                    incoming.push(upgrade);
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(incoming.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn invalid_peer_id() {
+        // Checks whether dialing an address containing the wrong peer id raises an error
+        // for the expected peer id instead of the obtained peer id.
+
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        swarm1.listen_on("/memory/0".parse().unwrap()).unwrap();
+
+        let address = future::poll_fn(|cx| match swarm1.poll_next_unpin(cx) {
+            Poll::Ready(Some(SwarmEvent::NewListenAddr { address, .. })) => Poll::Ready(address),
+            Poll::Pending => Poll::Pending,
+            _ => panic!("Was expecting the listen address to be reported"),
+        })
+        .await;
+
+        let other_id = PeerId::random();
+        let other_addr = address.with(multiaddr::Protocol::P2p(other_id));
+
+        swarm2.dial(other_addr.clone()).unwrap();
+
+        let (peer_id, error) = future::poll_fn(|cx| {
+            if let Poll::Ready(Some(SwarmEvent::IncomingConnection { .. })) =
+                swarm1.poll_next_unpin(cx)
+            {}
+
+            match swarm2.poll_next_unpin(cx) {
+                Poll::Ready(Some(SwarmEvent::OutgoingConnectionError {
+                    peer_id, error, ..
+                })) => Poll::Ready((peer_id, error)),
+                Poll::Ready(x) => panic!("unexpected {x:?}"),
+                Poll::Pending => Poll::Pending,
+            }
+        })
+        .await;
+        assert_eq!(peer_id.unwrap(), other_id);
+        match error {
+            DialError::WrongPeerId { obtained, endpoint } => {
+                assert_eq!(obtained, *swarm1.local_peer_id());
+                assert_eq!(
+                    endpoint,
+                    ConnectedPoint::Dialer {
+                        address: other_addr,
+                        role_override: Endpoint::Dialer,
+                    }
+                );
+            }
+            x => panic!("wrong error {x:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn dial_self() {
+        // Check whether dialing ourselves correctly fails.
+        //
+        // Dialing the same address we're listening should result in three events:
+        //
+        // - The incoming connection notification (before we know the incoming peer ID).
+        // - The connection error for the dialing endpoint (once we've determined that it's our own ID).
+        // - The connection error for the listening endpoint (once we've determined that it's our own ID).
+        //
+        // The last two can happen in any order.
+
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        swarm.listen_on("/memory/0".parse().unwrap()).unwrap();
+
+        let local_address = future::poll_fn(|cx| match swarm.poll_next_unpin(cx) {
+            Poll::Ready(Some(SwarmEvent::NewListenAddr { address, .. })) => Poll::Ready(address),
+            Poll::Pending => Poll::Pending,
+            _ => panic!("Was expecting the listen address to be reported"),
+        })
+        .await;
+
+        swarm.listened_addrs.clear(); // This is a hack to actually execute the dial to ourselves which would otherwise be filtered.
+
+        swarm.dial(local_address.clone()).unwrap();
+
+        let mut got_dial_err = false;
+        let mut got_inc_err = false;
+        future::poll_fn(|cx| -> Poll<Result<(), io::Error>> {
+            loop {
+                match swarm.poll_next_unpin(cx) {
+                    Poll::Ready(Some(SwarmEvent::OutgoingConnectionError {
+                        peer_id,
+                        error: DialError::LocalPeerId { .. },
+                        ..
+                    })) => {
+                        assert_eq!(&peer_id.unwrap(), swarm.local_peer_id());
+                        assert!(!got_dial_err);
+                        got_dial_err = true;
+                        if got_inc_err {
+                            return Poll::Ready(Ok(()));
+                        }
+                    }
+                    Poll::Ready(Some(SwarmEvent::IncomingConnectionError {
+                        local_addr, ..
+                    })) => {
+                        assert!(!got_inc_err);
+                        assert_eq!(local_addr, local_address);
+                        got_inc_err = true;
+                        if got_dial_err {
+                            return Poll::Ready(Ok(()));
+                        }
+                    }
+                    Poll::Ready(Some(SwarmEvent::IncomingConnection { local_addr, .. })) => {
+                        assert_eq!(local_addr, local_address);
+                    }
+                    Poll::Ready(ev) => {
+                        panic!("Unexpected event: {ev:?}")
+                    }
+                    Poll::Pending => break Poll::Pending,
+                }
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn dial_self_by_id() {
+        // Trying to dial self by passing the same `PeerId` shouldn't even be possible in the first
+        // place.
+        let swarm = new_test_swarm(Config::with_tokio_executor());
+        let peer_id = *swarm.local_peer_id();
+        assert!(!swarm.is_connected(&peer_id));
+    }
+
+    #[tokio::test]
+    async fn multiple_addresses_err() {
+        // Tries dialing multiple addresses, and makes sure there's one dialing error per address.
+
+        let target = PeerId::random();
+
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+
+        let addresses = HashSet::from([
+            multiaddr![Ip4([0, 0, 0, 0]), Tcp(rand::random::<u16>())],
+            multiaddr![Ip4([0, 0, 0, 0]), Tcp(rand::random::<u16>())],
+            multiaddr![Ip4([0, 0, 0, 0]), Tcp(rand::random::<u16>())],
+            multiaddr![Udp(rand::random::<u16>())],
+            multiaddr![Udp(rand::random::<u16>())],
+            multiaddr![Udp(rand::random::<u16>())],
+            multiaddr![Udp(rand::random::<u16>())],
+            multiaddr![Udp(rand::random::<u16>())],
+        ]);
+
+        swarm
+            .dial(
+                DialOpts::peer_id(target)
+                    .addresses(addresses.iter().cloned().collect())
+                    .build(),
+            )
+            .unwrap();
+
+        match swarm.next().await.unwrap() {
+            SwarmEvent::OutgoingConnectionError {
+                peer_id,
+                // multiaddr,
+                error: DialError::Transport(errors),
+                ..
+            } => {
+                assert_eq!(target, peer_id.unwrap());
+
+                let failed_addresses = errors.into_iter().map(|(addr, _)| addr).collect::<Vec<_>>();
+                let expected_addresses = addresses
+                    .into_iter()
+                    .map(|addr| addr.with(multiaddr::Protocol::P2p(target)))
+                    .collect::<Vec<_>>();
+
+                assert_eq!(expected_addresses, failed_addresses);
+            }
+            e => panic!("Unexpected event: {e:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn dial_attempt_failures_are_reported_before_connection_established() {
+        let mut swarm1 = new_test_swarm(
+            Config::with_tokio_executor()
+                .with_dial_attempt_failure_events(true)
+                .with_dial_concurrency_factor(NonZeroU8::new(1).unwrap()),
+        );
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+        let peer2 = *swarm2.local_peer_id();
+
+        let good_addr: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(good_addr.clone()).unwrap();
+        let bad_addrs: Vec<Multiaddr> = (0..2)
+            .map(|_| multiaddr::Protocol::Memory(rand::random::<u64>()).into())
+            .collect();
+
+        let dial_opts = DialOpts::peer_id(peer2)
+            .addresses(vec![bad_addrs[0].clone(), bad_addrs[1].clone(), good_addr])
+            .build();
+        let dial_connection_id = dial_opts.connection_id();
+        swarm1.dial(dial_opts).unwrap();
+        tokio::spawn(async move {
+            loop {
+                swarm2.select_next_some().await;
+            }
+        });
+
+        let mut failed_addresses = Vec::new();
+        loop {
+            match swarm1.select_next_some().await {
+                SwarmEvent::DialAttemptFailed {
+                    connection_id,
+                    peer_id,
+                    address,
+                    remaining_addresses,
+                    ..
+                } => {
+                    assert_eq!(connection_id, dial_connection_id);
+                    assert_eq!(peer_id, Some(peer2));
+                    assert_eq!(remaining_addresses, 2 - failed_addresses.len());
+                    failed_addresses.push(address);
+                }
+                SwarmEvent::ConnectionEstablished {
+                    connection_id,
+                    concurrent_dial_errors,
+                    ..
+                } => {
+                    assert_eq!(connection_id, dial_connection_id);
+                    assert_eq!(concurrent_dial_errors.unwrap().len(), 2);
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let expected_addresses = bad_addrs
+            .into_iter()
+            .map(|addr| addr.with(multiaddr::Protocol::P2p(peer2)))
+            .collect::<Vec<_>>();
+        assert_eq!(failed_addresses, expected_addresses);
+        assert_eq!(
+            swarm1.behaviour().on_dial_attempt_failed,
+            expected_addresses
+                .into_iter()
+                .map(|addr| (Some(peer2), dial_connection_id, addr))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn aborting_pending_connection_surfaces_error() {
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .try_init();
+
+        let mut dialer = new_test_swarm(Config::with_tokio_executor());
+        let mut listener = new_test_swarm(Config::with_tokio_executor());
+
+        let listener_peer_id = *listener.local_peer_id();
+        listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let listener_address = match listener.next().await.unwrap() {
+            SwarmEvent::NewListenAddr { address, .. } => address,
+            e => panic!("Unexpected network event: {e:?}"),
+        };
+
+        dialer
+            .dial(
+                DialOpts::peer_id(listener_peer_id)
+                    .addresses(vec![listener_address])
+                    .build(),
+            )
+            .unwrap();
+
+        dialer
+            .disconnect_peer_id(listener_peer_id)
+            .expect_err("Expect peer to not yet be connected.");
+
+        match dialer.next().await.unwrap() {
+            SwarmEvent::OutgoingConnectionError {
+                error: DialError::Aborted,
+                ..
+            } => {}
+            e => panic!("Unexpected swarm event {e:?}."),
+        }
+    }
+
+    #[tokio::test]
+    async fn direct_first_dials_relayed_addresses_after_direct_ones_failed() {
+        let log = dial_with_black_holed_direct_addresses(|opts| {
+            opts.address_order(AddressOrder::DirectFirst)
+        })
+        .await;
+
+        assert_eq!(
+            log,
+            vec![
+                "direct started",
+                "direct started",
+                "direct failed",
+                "direct failed",
+                "relayed started",
+                "relayed failed",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn as_provided_dials_all_addresses_concurrently() {
+        let log = dial_with_black_holed_direct_addresses(|opts| opts).await;
+
+        let position = |entry| log.iter().position(|e| *e == entry).unwrap();
+        assert!(position("relayed started") < position("direct failed"));
+    }
+
+    #[tokio::test]
+    async fn relay_only_skips_direct_addresses() {
+        let log = dial_with_black_holed_direct_addresses(|opts| opts.relay_only()).await;
+
+        assert_eq!(log, vec!["relayed started", "relayed failed"]);
+    }
+
+    #[tokio::test]
+    async fn relay_only_fails_without_relayed_addresses() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+
+        let error = swarm
+            .dial(
+                DialOpts::peer_id(PeerId::random())
+                    .addresses(vec![
+                        multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16)],
+                        multiaddr![Ip4([10, 0, 0, 1]), Udp(1u16), QuicV1],
+                    ])
+                    .relay_only()
+                    .build(),
+            )
+            .unwrap_err();
+
+        assert!(matches!(error, DialError::NoAddresses));
+        assert_eq!(swarm.behaviour().on_dial_failure.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn behaviour_sorts_addresses_before_dialing() {
+        let mut swarm = Swarm::new(
+            transport::dummy::DummyTransport::new().boxed(),
+            IpFirst,
+            PeerId::random(),
+            Config::with_tokio_executor().with_dial_concurrency_factor(NonZeroU8::new(1).unwrap()),
+        );
+        let peer_id = PeerId::random();
+
+        swarm
+            .dial(
+                DialOpts::peer_id(peer_id)
+                    .addresses(vec![
+                        multiaddr![Dns("example.com"), Tcp(1u16)],
+                        multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16)],
+                        multiaddr![Dns4("example.com"), Tcp(2u16)],
+                        multiaddr![Ip4([10, 0, 0, 2]), Tcp(1u16)],
+                    ])
+                    .build(),
+            )
+            .unwrap();
+
+        let dialed = match swarm.next().await.unwrap() {
+            SwarmEvent::OutgoingConnectionError {
+                error: DialError::Transport(errors),
+                ..
+            } => errors.into_iter().map(|(addr, _)| addr).collect::<Vec<_>>(),
+            e => panic!("Unexpected swarm event {e:?}."),
+        };
+
+        assert_eq!(
+            dialed,
+            vec![
+                multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16), P2p(peer_id)],
+                multiaddr![Ip4([10, 0, 0, 2]), Tcp(1u16), P2p(peer_id)],
+                multiaddr![Dns("example.com"), Tcp(1u16), P2p(peer_id)],
+                multiaddr![Dns4("example.com"), Tcp(2u16), P2p(peer_id)],
+            ]
+        );
+    }
+
+    /// Behaviour dialing `/ip4` addresses before all others, e.g. `/dns` ones.
+    struct IpFirst;
+
+    impl NetworkBehaviour for IpFirst {
+        type ConnectionHandler = dummy::ConnectionHandler;
+        type ToSwarm = void::Void;
+
+        fn handle_established_inbound_connection(
+            &mut self,
+            _: ConnectionId,
+            _: PeerId,
+            _: &Multiaddr,
+            _: &Multiaddr,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn handle_established_outbound_connection(
+            &mut self,
+            _: ConnectionId,
+            _: PeerId,
+            _: &Multiaddr,
+            _: Endpoint,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn sort_addresses(&self, _: &PeerId, addrs: &mut Vec<Multiaddr>) {
+            // A stable sort keeps the relative order of the addresses within each class.
+            addrs.sort_by_key(|a| !matches!(a.iter().next(), Some(multiaddr::Protocol::Ip4(_))));
+        }
+
+        fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+        fn on_connection_handler_event(
+            &mut self,
+            _: PeerId,
+            _: ConnectionId,
+            event: THandlerOutEvent<Self>,
+        ) {
+            void::unreachable(event)
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+            Poll::Pending
+        }
+    }
+
+    /// Dials a peer through two direct and one relayed address, returning the order in which the
+    /// dials were started and failed.
+    async fn dial_with_black_holed_direct_addresses(
+        configure: impl FnOnce(WithPeerIdWithAddresses) -> WithPeerIdWithAddresses,
+    ) -> Vec<&'static str> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut swarm = Swarm::new(
+            RecordingTransport { log: log.clone() }.boxed(),
+            dummy::Behaviour,
+            PeerId::random(),
+            Config::with_tokio_executor(),
+        );
+        let relay = PeerId::random();
+
+        swarm
+            .dial(
+                configure(DialOpts::peer_id(PeerId::random()).addresses(vec![
+                    multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16)],
+                    multiaddr![Ip4([10, 0, 0, 3]), Tcp(1u16), P2p(relay), P2pCircuit],
+                    multiaddr![Ip4([10, 0, 0, 2]), Tcp(1u16)],
+                ]))
+                .build(),
+            )
+            .unwrap();
+
+        let num_errors = match swarm.next().await.unwrap() {
+            SwarmEvent::OutgoingConnectionError {
+                error: DialError::Transport(errors),
+                ..
+            } => errors.len(),
+            e => panic!("Unexpected swarm event {e:?}."),
+        };
+
+        let log = log.lock().unwrap().clone();
+        assert_eq!(
+            num_errors,
+            log.iter().filter(|e| e.ends_with("started")).count()
+        );
+        log
+    }
+
+    #[tokio::test]
+    async fn delayed_dial_does_not_start_before_delay() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut swarm = Swarm::new(
+            RecordingTransport { log: log.clone() }.boxed(),
+            dummy::Behaviour,
+            PeerId::random(),
+            Config::with_tokio_executor(),
+        );
+        let delay = Duration::from_millis(500);
+
+        let start = std::time::Instant::now();
+        swarm
+            .dial(
+                DialOpts::peer_id(PeerId::random())
+                    .addresses(vec![multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16)]])
+                    .with_delay(delay)
+                    .build(),
+            )
+            .unwrap();
+
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), swarm.next())
+                .await
+                .is_err(),
+            "Expect no event while the dial is held."
+        );
+        assert!(log.lock().unwrap().is_empty());
+
+        match swarm.next().await.unwrap() {
+            SwarmEvent::OutgoingConnectionError {
+                error: DialError::Transport(_),
+                ..
+            } => {}
+            e => panic!("Unexpected swarm event {e:?}."),
+        }
+        assert!(start.elapsed() >= delay);
+        assert_eq!(
+            log.lock().unwrap().as_slice(),
+            ["direct started", "direct failed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn source_port_fails_dial_with_overridden_role() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut swarm = Swarm::new(
+            RecordingTransport { log: log.clone() }.boxed(),
+            dummy::Behaviour,
+            PeerId::random(),
+            Config::with_tokio_executor(),
+        );
+
+        swarm
+            .dial(
+                DialOpts::peer_id(PeerId::random())
+                    .addresses(vec![multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16)]])
+                    .override_role()
+                    .source_port(4001)
+                    .build(),
+            )
+            .unwrap();
+
+        match swarm.next().await.unwrap() {
+            SwarmEvent::OutgoingConnectionError {
+                error: DialError::Transport(errors),
+                ..
+            } => {
+                assert!(matches!(
+                    errors.as_slice(),
+                    [(_, TransportError::Other(e))] if e.kind() == io::ErrorKind::Unsupported
+                ));
+            }
+            e => panic!("Unexpected swarm event {e:?}."),
+        }
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn delayed_dial_can_be_aborted() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut swarm = Swarm::new(
+            RecordingTransport { log: log.clone() }.boxed(),
+            dummy::Behaviour,
+            PeerId::random(),
+            Config::with_tokio_executor(),
+        );
+        let peer_id = PeerId::random();
+
+        swarm
+            .dial(
+                DialOpts::peer_id(peer_id)
+                    .addresses(vec![multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16)]])
+                    .with_delay(Duration::from_secs(60))
+                    .build(),
+            )
+            .unwrap();
+        swarm.disconnect_peer_id(peer_id).unwrap_err();
+
+        match swarm.next().await.unwrap() {
+            SwarmEvent::OutgoingConnectionError {
+                error: DialError::Aborted,
+                ..
+            } => {}
+            e => panic!("Unexpected swarm event {e:?}."),
+        }
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    /// Transport failing all dials, black-holing direct addresses for a while before timing out.
+    ///
+    /// Records when each dial is started, i.e. first polled, and when it failed.
+    struct RecordingTransport {
+        log: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Transport for RecordingTransport {
+        type Output = (PeerId, StreamMuxerBox);
+        type Error = io::Error;
+        type ListenerUpgrade = future::Pending<Result<Self::Output, Self::Error>>;
+        type Dial = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+        fn listen_on(
+            &mut self,
+            _: transport::ListenerId,
+            addr: Multiaddr,
+        ) -> Result<(), TransportError<Self::Error>> {
+            Err(TransportError::MultiaddrNotSupported(addr))
+        }
+
+        fn remove_listener(&mut self, _: transport::ListenerId) -> bool {
+            false
+        }
+
+        fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+            let log = self.log.clone();
+            let relayed = addr.iter().any(|p| p == multiaddr::Protocol::P2pCircuit);
+
+            Ok(async move {
+                if relayed {
+                    log.lock().unwrap().push("relayed started");
+                    log.lock().unwrap().push("relayed failed");
+                } else {
+                    log.lock().unwrap().push("direct started");
+                    futures_timer::Delay::new(Duration::from_millis(50)).await;
+                    log.lock().unwrap().push("direct failed");
+                }
+
+                Err(io::ErrorKind::TimedOut.into())
+            }
+            .boxed())
+        }
+
+        fn dial_as_listener(
+            &mut self,
+            addr: Multiaddr,
+        ) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.dial(addr)
+        }
+
+        fn poll(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<transport::TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+            Poll::Pending
+        }
+
+        fn address_translation(&self, _: &Multiaddr, _: &Multiaddr) -> Option<Multiaddr> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_behaviour_commands_are_bounded() {
+        const NUM_DIALS: usize = 20;
+        const MAX_PENDING: usize = 4;
+        let mut swarm = Swarm::new(
+            RecordingTransport {
+                log: Default::default(),
+            }
+            .boxed(),
+            DialFlood {
+                remaining: NUM_DIALS,
+                issued: Vec::new(),
+            },
+            PeerId::random(),
+            Config::with_tokio_executor().with_max_pending_behaviour_commands(MAX_PENDING),
+        );
+
+        let mut dialing = Vec::new();
+        let mut failed = 0;
+        let mut max_pending = 0;
+        while failed < NUM_DIALS {
+            match swarm.select_next_some().await {
+                SwarmEvent::Dialing { connection_id, .. } => dialing.push(connection_id),
+                SwarmEvent::OutgoingConnectionError { .. } => failed += 1,
+                _ => {}
+            }
+            max_pending = max_pending.max(swarm.pending_behaviour_commands());
+        }
+
+        assert_eq!(max_pending, MAX_PENDING);
+        assert_eq!(swarm.pending_behaviour_commands(), 0);
+        assert_eq!(dialing, swarm.behaviour().issued);
+    }
+
+    /// Behaviour dialing unreachable addresses as fast as it is polled.
+    struct DialFlood {
+        remaining: usize,
+        issued: Vec<ConnectionId>,
+    }
+
+    impl NetworkBehaviour for DialFlood {
+        type ConnectionHandler = dummy::ConnectionHandler;
+        type ToSwarm = void::Void;
+
+        fn handle_established_inbound_connection(
+            &mut self,
+            _: ConnectionId,
+            _: PeerId,
+            _: &Multiaddr,
+            _: &Multiaddr,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn handle_established_outbound_connection(
+            &mut self,
+            _: ConnectionId,
+            _: PeerId,
+            _: &Multiaddr,
+            _: Endpoint,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+        fn on_connection_handler_event(
+            &mut self,
+            _: PeerId,
+            _: ConnectionId,
+            event: THandlerOutEvent<Self>,
+        ) {
+            void::unreachable(event)
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+            if self.remaining == 0 {
+                return Poll::Pending;
+            }
+            self.remaining -= 1;
+
+            let opts = DialOpts::unknown_peer_id()
+                .address(multiaddr![Ip4([10, 0, 0, 1]), Tcp(1u16)])
+                .build();
+            self.issued.push(opts.connection_id());
+
+            Poll::Ready(ToSwarm::Dial { opts })
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_connection_is_aborted_after_handshake_timeout() {
+        let handshake_timeout = Duration::from_millis(100);
+        let mut dialer = new_pending_authentication_swarm(handshake_timeout);
+        let mut listener = new_pending_authentication_swarm(handshake_timeout);
+
+        listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let listener_address = match listener.next().await.unwrap() {
+            SwarmEvent::NewListenAddr { address, .. } => address,
+            e => panic!("Unexpected network event: {e:?}"),
+        };
+
+        let started = std::time::Instant::now();
+        dialer.dial(listener_address).unwrap();
+
+        let (dial_error, listen_error) = futures::join!(
+            async {
+                loop {
+                    if let SwarmEvent::OutgoingConnectionError { error, .. } =
+                        dialer.select_next_some().await
+                    {
+                        break error;
+                    }
+                }
+            },
+            async {
+                loop {
+                    if let SwarmEvent::IncomingConnectionError { error, .. } =
+                        listener.select_next_some().await
+                    {
+                        break error;
+                    }
+                }
+            }
+        );
+
+        assert!(started.elapsed() >= handshake_timeout);
+        assert!(matches!(dial_error, DialError::Timeout));
+        assert!(matches!(listen_error, ListenError::Timeout));
+        assert_eq!(dialer.behaviour().on_dial_failure.len(), 1);
+        assert_eq!(dialer.network_info().connection_counters().num_pending(), 0);
+        assert_eq!(
+            listener.network_info().connection_counters().num_pending(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn network_info_counts_peers_and_pending_dials() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        let mut remote1 = new_test_swarm(Config::with_tokio_executor());
+        let mut remote2 = new_test_swarm(Config::with_tokio_executor());
+
+        let addr1: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        let addr2: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        remote1.listen_on(addr1.clone()).unwrap();
+        remote2.listen_on(addr2.clone()).unwrap();
+
+        swarm.dial(addr1).unwrap();
+        swarm.dial(addr2).unwrap();
+
+        future::poll_fn(|cx| loop {
+            let poll = Swarm::poll_next_event(Pin::new(&mut swarm), cx);
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut remote1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut remote2), cx);
+
+            if swarms_connected(&swarm, &remote1, 1) && swarms_connected(&swarm, &remote2, 1) {
+                return Poll::Ready(());
+            }
+            if poll.is_pending() && poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await;
+
+        swarm
+            .dial(
+                DialOpts::unknown_peer_id()
+                    .address(multiaddr::Protocol::Memory(rand::random::<u64>()).into())
+                    .with_delay(Duration::from_secs(60))
+                    .build(),
+            )
+            .unwrap();
+
+        let info = swarm.network_info();
+        assert_eq!(info.num_peers(), 2);
+        assert_eq!(info.connection_counters().num_established(), 2);
+        assert_eq!(info.connection_counters().num_established_outgoing(), 2);
+        assert_eq!(info.connection_counters().num_pending_outgoing(), 1);
+        assert_eq!(info.connection_counters().num_pending_incoming(), 0);
+    }
+
+    #[tokio::test]
+    async fn peer_metadata_is_removed_after_disconnect() {
+        let mut swarm1 = new_test_swarm(
+            Config::with_tokio_executor().with_peer_metadata_grace_period(Duration::ZERO),
+        );
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+        let swarm2_id = *swarm2.local_peer_id();
+
+        let addr: Multiaddr = multiaddr::Protocol::Memory(rand::random::<u64>()).into();
+        swarm2.listen_on(addr.clone()).unwrap();
+        swarm1.dial(addr).unwrap();
+
+        let mut state = State::Connecting;
+        future::poll_fn(|cx| loop {
+            let poll1 = Swarm::poll_next_event(Pin::new(&mut swarm1), cx);
+            let poll2 = Swarm::poll_next_event(Pin::new(&mut swarm2), cx);
+
+            match state {
+                State::Connecting => {
+                    if swarms_connected(&swarm1, &swarm2, 1) {
+                        swarm1.peer_metadata_mut().insert(swarm2_id, "nickname");
+                        swarm1.disconnect_peer_id(swarm2_id).unwrap();
+                        state = State::Disconnecting;
+                    }
+                }
+                State::Disconnecting => {
+                    if swarms_disconnected(&swarm1, &swarm2) {
+                        return Poll::Ready(());
+                    }
+                    assert_eq!(
+                        swarm1.peer_metadata_mut().get::<&str>(&swarm2_id),
+                        Some(&"nickname")
+                    );
+                }
+            }
+
+            if poll1.is_pending() && poll2.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await;
+
+        assert_eq!(swarm1.peer_metadata_mut().get::<&str>(&swarm2_id), None);
+    }
+
+    #[tokio::test]
+    async fn dial_timeout_fires_before_transport_timeout() {
+        let transport_timeout = Duration::from_secs(10);
+        let dial_timeout = Duration::from_millis(100);
+        let mut dialer = Swarm::new(
+            transport::timeout::TransportTimeout::new(
+                transport::MemoryTransport::default()
+                    .upgrade(upgrade::Version::V1)
+                    .authenticate(PendingAuthentication)
+                    .multiplex(yamux::Config::default()),
+                transport_timeout,
+            )
+            .map(|(p, m), _| (p, StreamMuxerBox::new(m)))
+            .boxed(),
+            CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(dummy::ConnectionHandler)),
+            PeerId::random(),
+            Config::with_tokio_executor(),
+        );
+        let mut listener = new_pending_authentication_swarm(transport_timeout);
+
+        listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let listener_address = match listener.next().await.unwrap() {
+            SwarmEvent::NewListenAddr { address, .. } => address,
+            e => panic!("Unexpected network event: {e:?}"),
+        };
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        let started = std::time::Instant::now();
+        dialer
+            .dial(
+                DialOpts::unknown_peer_id()
+                    .address(listener_address)
+                    .timeout(dial_timeout)
+                    .build(),
+            )
+            .unwrap();
+
+        let error = loop {
+            if let SwarmEvent::OutgoingConnectionError { error, .. } =
+                dialer.select_next_some().await
+            {
+                break error;
+            }
+        };
+
+        assert!(matches!(error, DialError::Timeout));
+        assert!(started.elapsed() >= dial_timeout);
+        assert!(started.elapsed() < transport_timeout);
+        assert_eq!(dialer.behaviour().on_dial_failure.len(), 1);
+    }
+
+    fn new_pending_authentication_swarm(
+        handshake_timeout: Duration,
+    ) -> Swarm<CallTraceBehaviour<MockBehaviour<dummy::ConnectionHandler, ()>>> {
+        let transport = transport::MemoryTransport::default()
+            .upgrade(upgrade::Version::V1)
+            .authenticate(PendingAuthentication)
+            .multiplex(yamux::Config::default())
+            .boxed();
+        let behaviour = CallTraceBehaviour::new(MockBehaviour::new(dummy::ConnectionHandler));
+
+        Swarm::new(
+            transport,
+            behaviour,
+            PeerId::random(),
+            Config::with_tokio_executor().with_handshake_timeout(handshake_timeout),
+        )
+    }
+
+    /// Authentication upgrade whose handshake never completes.
+    #[derive(Clone)]
+    struct PendingAuthentication;
+
+    impl upgrade::UpgradeInfo for PendingAuthentication {
+        type Info = &'static str;
+        type InfoIter = std::iter::Once<Self::Info>;
+
+        fn protocol_info(&self) -> Self::InfoIter {
+            std::iter::once("/pending-authentication")
+        }
+    }
+
+    impl<C> upgrade::InboundConnectionUpgrade<C> for PendingAuthentication {
+        type Output = (PeerId, C);
+        type Error = io::Error;
+        type Future = future::Pending<Result<Self::Output, Self::Error>>;
+
+        fn upgrade_inbound(self, _: C, _: Self::Info) -> Self::Future {
+            future::pending()
+        }
+    }
+
+    impl<C> upgrade::OutboundConnectionUpgrade<C> for PendingAuthentication {
+        type Output = (PeerId, C);
+        type Error = io::Error;
+        type Future = future::Pending<Result<Self::Output, Self::Error>>;
+
+        fn upgrade_outbound(self, _: C, _: Self::Info) -> Self::Future {
+            future::pending()
+        }
+    }
+
+    #[tokio::test]
+    async fn event_observers_see_every_returned_event() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let log = observed.clone();
+        swarm.add_event_observer(move |event| log.lock().unwrap().push(format!("{event:?}")));
+
+        swarm.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let event = swarm.next().await.unwrap();
+
+        assert!(matches!(event, SwarmEvent::NewListenAddr { .. }));
+        assert_eq!(*observed.lock().unwrap(), [format!("{event:?}")]);
+    }
+
+    #[tokio::test]
+    async fn listen_on_many_waits_for_all_listeners() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+
+        let listener_ids = swarm
+            .listen_on_many([multiaddr![Memory(0u64)], multiaddr![Memory(0u64)]])
+            .unwrap();
+        assert_eq!(listener_ids.len(), 2);
+
+        let (addresses, _) = ListenersReady::new(listener_ids).wait(&mut swarm).await;
+
+        assert_eq!(addresses.len(), 2);
+        assert_eq!(
+            swarm.listeners().cloned().collect::<HashSet<_>>(),
+            addresses.into_iter().collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn duplicate_listen_on_returns_existing_listener() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        let address = multiaddr![Memory(rand::random::<u64>().saturating_add(1))];
+
+        let first = swarm.listen_on(address.clone()).unwrap();
+        assert_eq!(swarm.listen_on(address.clone()).unwrap(), first);
+        assert_eq!(swarm.behaviour().on_new_listener, vec![first]);
+
+        ListenersReady::new([first]).wait(&mut swarm).await;
+        assert_eq!(swarm.listen_on(address.clone()).unwrap(), first);
+        assert_eq!(swarm.listeners().collect::<Vec<_>>(), vec![&address]);
+
+        // Each `/memory/0` listener is bound to a different port.
+        let ephemeral = swarm
+            .listen_on_many([multiaddr![Memory(0u64)], multiaddr![Memory(0u64)]])
+            .unwrap();
+        assert_ne!(ephemeral[0], ephemeral[1]);
+    }
+
+    #[tokio::test]
+    async fn listen_protocols_changed_only_on_change() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+
+        let listener_ids = swarm
+            .listen_on_many([multiaddr![Memory(0u64)], multiaddr![Memory(0u64)]])
+            .unwrap();
+        ListenersReady::new(listener_ids.clone())
+            .wait(&mut swarm)
+            .await;
+
+        // Both listen addresses are `/memory` addresses, thus only the first one changes the set.
+        assert_eq!(
+            swarm.behaviour().on_listen_protocols_changed,
+            vec![vec![ListenProtocol::Memory]]
+        );
+
+        swarm.remove_listener(listener_ids[0]);
+        swarm.remove_listener(listener_ids[1]);
+        let mut closed = 0;
+        while closed < 2 {
+            if let SwarmEvent::ListenerClosed { .. } = swarm.select_next_some().await {
+                closed += 1;
+            }
+        }
+
+        assert_eq!(
+            swarm.behaviour().on_listen_protocols_changed,
+            vec![vec![ListenProtocol::Memory], vec![]]
+        );
+    }
+
+    #[tokio::test]
+    async fn listen_on_many_reports_failing_index() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        let unsupported = multiaddr![Ip4([127, 0, 0, 1]), Tcp(1234u16)];
+
+        let (index, error) = swarm
+            .listen_on_many([
+                multiaddr![Memory(0u64)],
+                unsupported.clone(),
+                multiaddr![Memory(0u64)],
+            ])
+            .unwrap_err();
+
+        assert_eq!(index, 1);
+        assert!(matches!(error, TransportError::MultiaddrNotSupported(a) if a == unsupported));
+
+        // The listener started before the failing address is removed again.
+        loop {
+            if let SwarmEvent::ListenerClosed { .. } = swarm.select_next_some().await {
+                break;
+            }
+        }
+        assert_eq!(swarm.listeners().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn listen_on_all_interfaces_includes_loopback() {
+        let id_keys = identity::Keypair::generate_ed25519();
+        let transport = libp2p_tcp::tokio::Transport::default()
+            .upgrade(upgrade::Version::V1)
+            .authenticate(plaintext::Config::new(&id_keys))
+            .multiplex(yamux::Config::default())
+            .boxed();
+        let mut swarm = Swarm::new(
+            transport,
+            dummy::Behaviour,
+            id_keys.public().to_peer_id(),
+            Config::with_tokio_executor(),
+        );
+
+        let listener_ids = swarm
+            .listen_on_all_interfaces(multiaddr![Ip4([0, 0, 0, 0]), Tcp(0u16)])
+            .unwrap();
+        let (addresses, _) = ListenersReady::new(listener_ids).wait(&mut swarm).await;
+
+        assert!(addresses
+            .iter()
+            .any(|a| a.iter().next() == Some(multiaddr::Protocol::Ip4([127, 0, 0, 1].into()))));
+        assert!(!addresses
+            .iter()
+            .any(|a| a.iter().next() == Some(multiaddr::Protocol::Ip4([0, 0, 0, 0].into()))));
+    }
+
+    #[test]
+    fn listen_on_all_interfaces_requires_unspecified_ip() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        let specified = multiaddr![Ip4([127, 0, 0, 1]), Tcp(0u16)];
+
+        let error = swarm
+            .listen_on_all_interfaces(specified.clone())
+            .unwrap_err();
+
+        assert!(matches!(error, TransportError::MultiaddrNotSupported(a) if a == specified));
+    }
+
+    #[tokio::test]
+    async fn seeded_external_addresses_are_confirmed_once() {
+        let addr1 = multiaddr![Ip4([1, 2, 3, 4]), Tcp(1234u16)];
+        let addr2 = multiaddr![Ip4([1, 2, 3, 4]), Udp(1234u16), QuicV1];
+
+        let mut swarm =
+            new_test_swarm(Config::with_tokio_executor().with_external_addresses(vec![
+                addr1.clone(),
+                addr2.clone(),
+                addr1.clone(),
+            ]));
+
+        assert_eq!(
+            swarm.behaviour().on_external_addr_confirmed,
+            vec![addr1.clone(), addr2.clone()]
+        );
+        assert_eq!(
+            swarm.external_addresses().cloned().collect::<HashSet<_>>(),
+            HashSet::from([addr1, addr2])
+        );
+
+        // Polling the swarm does not confirm the addresses again.
+        swarm.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        swarm.next().await.unwrap();
+        assert_eq!(swarm.behaviour().on_external_addr_confirmed.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn behaviour_external_address_commands_are_reported_as_swarm_events() {
+        let addr = multiaddr![Ip4([1, 2, 3, 4]), Tcp(1234u16)];
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+
+        swarm.behaviour_mut().inner().next_action =
+            Some(ToSwarm::ExternalAddrConfirmed(addr.clone()));
+        match swarm.next().await.unwrap() {
+            SwarmEvent::ExternalAddrConfirmed { address } => assert_eq!(address, addr),
+            e => panic!("Unexpected swarm event: {e:?}"),
+        }
+        assert_eq!(
+            swarm.behaviour().on_external_addr_confirmed,
+            vec![addr.clone()]
+        );
+        assert_eq!(swarm.external_addresses().collect::<Vec<_>>(), vec![&addr]);
+
+        swarm.behaviour_mut().inner().next_action =
+            Some(ToSwarm::ExternalAddrExpired(addr.clone()));
+        match swarm.next().await.unwrap() {
+            SwarmEvent::ExternalAddrExpired { address } => assert_eq!(address, addr),
+            e => panic!("Unexpected swarm event: {e:?}"),
+        }
+        assert_eq!(swarm.behaviour().on_expired_external_addr, vec![addr]);
+        assert_eq!(swarm.external_addresses().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn address_translator_replaces_transport_translation() {
+        use libp2p_core::multiaddr::Protocol;
+
+        let nat_port = 14001u16;
+        let mut swarm = new_test_swarm(Config::with_tokio_executor().with_address_translator(
+            move |_listen, observed| match observed.iter().collect::<Vec<_>>()[..] {
+                [Protocol::Ip4(ip), Protocol::Tcp(4001)] => {
+                    Some(multiaddr![Ip4(ip), Tcp(nat_port)])
+                }
+                _ => None,
+            },
+        ));
+        swarm.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        swarm.next().await.unwrap();
+
+        for observed in [
+            multiaddr![Ip4([1, 2, 3, 4]), Tcp(4001u16)],
+            multiaddr![Ip4([1, 2, 3, 4]), Tcp(5001u16)],
+        ] {
+            swarm.behaviour_mut().inner().next_action =
+                Some(ToSwarm::NewExternalAddrCandidate(observed));
+            loop {
+                if let SwarmEvent::NewExternalAddrCandidate { .. } = swarm.next().await.unwrap() {
+                    break;
+                }
+            }
+        }
+
+        // Addresses the translator ignores are reported as observed.
+        assert_eq!(
+            swarm.behaviour().on_new_external_addr,
+            vec![
+                multiaddr![Ip4([1, 2, 3, 4]), Tcp(nat_port)],
+                multiaddr![Ip4([1, 2, 3, 4]), Tcp(5001u16)],
+            ]
+        );
+    }
+
+    #[test]
+    fn dialable_address_appends_local_peer_id() {
+        let swarm = new_test_swarm(Config::with_tokio_executor());
+        let local_peer_id = *swarm.local_peer_id();
+        let address = multiaddr![Ip4([127, 0, 0, 1]), Tcp(1234u16)];
+
+        assert_eq!(
+            swarm.dialable_address(&address),
+            address.with(multiaddr::Protocol::P2p(local_peer_id))
+        );
+    }
+
+    #[test]
+    fn dialable_address_does_not_duplicate_peer_id() {
+        let swarm = new_test_swarm(Config::with_tokio_executor());
+        let local_peer_id = *swarm.local_peer_id();
+        let address = multiaddr![Ip4([127, 0, 0, 1]), Tcp(1234u16)]
+            .with(multiaddr::Protocol::P2p(local_peer_id));
+
+        assert_eq!(swarm.dialable_address(&address), address);
+    }
+
+    #[test]
+    fn dialable_address_keeps_relay_peer_id() {
+        let swarm = new_test_swarm(Config::with_tokio_executor());
+        let local_peer_id = *swarm.local_peer_id();
+        let address = multiaddr![
+            Ip4([127, 0, 0, 1]),
+            Tcp(1234u16),
+            P2p(PeerId::random()),
+            P2pCircuit
+        ];
+        let dialable = address
+            .clone()
+            .with(multiaddr::Protocol::P2p(local_peer_id));
+
+        assert_eq!(swarm.dialable_address(&address), dialable);
+        assert_eq!(swarm.dialable_address(&dialable), dialable);
+    }
+
+    #[tokio::test]
+    async fn stream_counts_and_limit_event_at_yamux_cap() {
+        const MAX_STREAMS: usize = 4;
+
+        fn new_stream_swarm(
+            yamux_config: yamux::Config,
+            handler: StreamHoldingHandler,
+        ) -> Swarm<MockBehaviour<StreamHoldingHandler, ()>> {
+            let id_keys = identity::Keypair::generate_ed25519();
+            let transport = transport::MemoryTransport::default()
+                .upgrade(upgrade::Version::V1)
+                .authenticate(plaintext::Config::new(&id_keys))
+                .multiplex(yamux_config)
+                .boxed();
+
+            Swarm::new(
+                transport,
+                MockBehaviour::new(handler),
+                id_keys.public().to_peer_id(),
+                Config::with_tokio_executor(),
+            )
+        }
+
+        let mut capped_yamux = yamux::Config::default();
+        capped_yamux.set_max_num_streams(MAX_STREAMS);
+
+        let mut dialer =
+            new_stream_swarm(capped_yamux, StreamHoldingHandler::opening(MAX_STREAMS + 1));
+        let mut listener =
+            new_stream_swarm(yamux::Config::default(), StreamHoldingHandler::opening(0));
+
+        listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let listener_address = match listener.next().await.unwrap() {
+            SwarmEvent::NewListenAddr { address, .. } => address,
+            e => panic!("Unexpected network event: {e:?}"),
+        };
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        dialer.dial(listener_address).unwrap();
+
+        let counts = loop {
+            match dialer.next().await.unwrap() {
+                SwarmEvent::ConnectionEstablished { connection_id, .. } => {
+                    assert!(dialer.connection_stream_counts(connection_id).is_some());
+                }
+                SwarmEvent::ConnectionStreamLimitReached { stream_counts, .. } => {
+                    break stream_counts
+                }
+                SwarmEvent::Dialing { .. } => {}
+                e => panic!("Unexpected swarm event {e:?}"),
+            }
+        };
+
+        assert_eq!(counts.outbound_active, MAX_STREAMS);
+        assert_eq!(counts.inbound_active, 0);
+        assert_eq!(counts.total_opened, MAX_STREAMS);
+        assert!(dialer
+            .connection_stream_counts(ConnectionId::next())
+            .is_none());
+    }
+
+    /// Opens a number of outbound streams and holds on to all negotiated streams.
+    struct StreamHoldingHandler {
+        to_open: usize,
+        streams: Vec<Stream>,
+    }
+
+    impl StreamHoldingHandler {
+        const PROTOCOL: StreamProtocol = StreamProtocol::new("/test/hold/1.0.0");
+
+        fn opening(to_open: usize) -> Self {
+            Self {
+                to_open,
+                streams: Vec::new(),
+            }
+        }
+    }
+
+    impl Clone for StreamHoldingHandler {
+        fn clone(&self) -> Self {
+            Self::opening(self.to_open)
+        }
+    }
+
+    impl ConnectionHandler for StreamHoldingHandler {
+        type FromBehaviour = void::Void;
+        type ToBehaviour = void::Void;
+        type InboundProtocol = upgrade::ReadyUpgrade<StreamProtocol>;
+        type OutboundProtocol = upgrade::ReadyUpgrade<StreamProtocol>;
+        type InboundOpenInfo = ();
+        type OutboundOpenInfo = ();
+
+        fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
+            SubstreamProtocol::new(upgrade::ReadyUpgrade::new(Self::PROTOCOL), ())
+        }
+
+        fn connection_keep_alive(&self) -> bool {
+            true
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, (), Self::ToBehaviour>> {
+            if self.to_open > 0 {
+                self.to_open -= 1;
+                return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                    protocol: SubstreamProtocol::new(
+                        upgrade::ReadyUpgrade::new(Self::PROTOCOL),
+                        (),
+                    ),
+                });
+            }
+
+            Poll::Pending
+        }
+
+        fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+            void::unreachable(event)
+        }
+
+        fn on_connection_event(
+            &mut self,
+            event: handler::ConnectionEvent<Self::InboundProtocol, Self::OutboundProtocol, (), ()>,
+        ) {
+            match event {
+                handler::ConnectionEvent::FullyNegotiatedInbound(
+                    handler::FullyNegotiatedInbound { protocol, .. },
+                ) => self.streams.push(protocol),
+                handler::ConnectionEvent::FullyNegotiatedOutbound(
+                    handler::FullyNegotiatedOutbound { protocol, .. },
+                ) => self.streams.push(protocol),
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn connection_established_is_deferred_until_first_substream() {
+        /// Returns whether the dialing behaviour was informed about the connection by the time
+        /// [`SwarmEvent::ConnectionEstablished`] is emitted and the number of handler events it
+        /// received by the time it was informed.
+        async fn connection_established_order(defer: bool) -> (bool, usize) {
+            let new_swarm = || {
+                let id_keys = identity::Keypair::generate_ed25519();
+                let transport = transport::MemoryTransport::default()
+                    .upgrade(upgrade::Version::V1)
+                    .authenticate(plaintext::Config::new(&id_keys))
+                    .multiplex(yamux::Config::default())
+                    .boxed();
+
+                Swarm::new(
+                    transport,
+                    CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(
+                        NotifyThenOpenHandler::default(),
+                    )),
+                    id_keys.public().to_peer_id(),
+                    Config::with_tokio_executor()
+                        .with_defer_connection_established_until_first_substream(defer),
+                )
+            };
+            let mut dialer = new_swarm();
+            let mut listener = new_swarm();
+
+            listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+            let listener_address = match listener.next().await.unwrap() {
+                SwarmEvent::NewListenAddr { address, .. } => address,
+                e => panic!("Unexpected network event: {e:?}"),
+            };
+            tokio::spawn(listener.collect::<Vec<_>>());
+
+            dialer.dial(listener_address).unwrap();
+
+            let mut reported_with_swarm_event = None;
+            future::poll_fn(|cx| loop {
+                let poll = dialer.poll_next_unpin(cx);
+                if let Poll::Ready(Some(SwarmEvent::ConnectionEstablished { .. })) = poll {
+                    reported_with_swarm_event =
+                        Some(!dialer.behaviour().on_connection_established.is_empty());
+                }
+
+                if !dialer.behaviour().on_connection_established.is_empty() {
+                    if let Some(reported_with_swarm_event) = reported_with_swarm_event {
+                        return Poll::Ready((
+                            reported_with_swarm_event,
+                            dialer.behaviour().on_connection_handler_event.len(),
+                        ));
+                    }
+                }
+
+                if poll.is_pending() {
+                    return Poll::Pending;
+                }
+            })
+            .await
+        }
+
+        // Reported right away, before the handler had a chance to produce any event.
+        assert_eq!(connection_established_order(false).await, (true, 0));
+        // Reported after the stream opened by the handler is negotiated, directly followed by the
+        // event the handler produced before.
+        assert_eq!(connection_established_order(true).await, (false, 1));
+    }
+
+    #[tokio::test]
+    async fn deferred_connection_closed_before_first_substream_is_reported() {
+        let new_swarm = || {
+            let id_keys = identity::Keypair::generate_ed25519();
+            let transport = transport::MemoryTransport::default()
+                .upgrade(upgrade::Version::V1)
+                .authenticate(plaintext::Config::new(&id_keys))
+                .multiplex(yamux::Config::default())
+                .boxed();
+
+            Swarm::new(
+                transport,
+                CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(dummy::ConnectionHandler)),
+                id_keys.public().to_peer_id(),
+                Config::with_tokio_executor()
+                    .with_defer_connection_established_until_first_substream(true),
+            )
+        };
+        let mut dialer = new_swarm();
+        let mut listener = new_swarm();
+        let listener_id = *listener.local_peer_id();
+
+        listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let listener_address = match listener.next().await.unwrap() {
+            SwarmEvent::NewListenAddr { address, .. } => address,
+            e => panic!("Unexpected network event: {e:?}"),
+        };
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        dialer.dial(listener_address).unwrap();
+        let connection_id = loop {
+            if let SwarmEvent::ConnectionEstablished { connection_id, .. } =
+                dialer.select_next_some().await
+            {
+                break connection_id;
+            }
+        };
+        assert!(dialer.behaviour().on_connection_established.is_empty());
+
+        // The connection may already be closing on its own, as the handler doesn't keep it alive.
+        dialer.close_connection(connection_id);
+        loop {
+            if let SwarmEvent::ConnectionClosed { .. } = dialer.select_next_some().await {
+                break;
+            }
+        }
+
+        let behaviour = dialer.behaviour();
+        assert_eq!(behaviour.handle_established_outbound_connection.len(), 1);
+        assert_eq!(behaviour.on_connection_established.len(), 1);
+        assert_eq!(behaviour.on_connection_closed.len(), 1);
+        assert_eq!(behaviour.on_connection_close_completed.len(), 1);
+        assert_eq!(behaviour.num_connections_to_peer(listener_id), 0);
+    }
+
+    /// Notifies the behaviour once and then opens a single outbound stream.
+    #[derive(Default, Clone)]
+    struct NotifyThenOpenHandler {
+        notified: bool,
+        opened: bool,
+    }
+
+    impl ConnectionHandler for NotifyThenOpenHandler {
+        type FromBehaviour = void::Void;
+        type ToBehaviour = ();
+        type InboundProtocol = upgrade::ReadyUpgrade<StreamProtocol>;
+        type OutboundProtocol = upgrade::ReadyUpgrade<StreamProtocol>;
+        type InboundOpenInfo = ();
+        type OutboundOpenInfo = ();
+
+        fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
+            SubstreamProtocol::new(
+                upgrade::ReadyUpgrade::new(StreamHoldingHandler::PROTOCOL),
+                (),
+            )
+        }
+
+        fn connection_keep_alive(&self) -> bool {
+            true
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, (), Self::ToBehaviour>> {
+            if !std::mem::replace(&mut self.notified, true) {
+                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(()));
+            }
+            if !std::mem::replace(&mut self.opened, true) {
+                return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                    protocol: SubstreamProtocol::new(
+                        upgrade::ReadyUpgrade::new(StreamHoldingHandler::PROTOCOL),
+                        (),
+                    ),
+                });
+            }
+
+            Poll::Pending
+        }
+
+        fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+            void::unreachable(event)
+        }
+
+        fn on_connection_event(
+            &mut self,
+            _: handler::ConnectionEvent<Self::InboundProtocol, Self::OutboundProtocol, (), ()>,
+        ) {
+        }
+    }
+
+    #[tokio::test]
+    async fn flooding_slow_connection_applies_backpressure() {
+        const BUFFER_SIZE: usize = 4;
+        const NUM_EVENTS: usize = 100;
+
+        // Holds back the task of the established connection, i.e. the second task spawned after
+        // the one of the pending connection, until the gate is opened.
+        let (open_gate, gate) = futures::channel::oneshot::channel::<()>();
+        let gate = Mutex::new(Some(gate));
+        let num_spawned = std::sync::atomic::AtomicUsize::new(0);
+        let executor = move |task: Pin<Box<dyn Future<Output = ()> + Send>>| {
+            if num_spawned.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 1 {
+                let gate = gate.lock().unwrap().take().unwrap();
+                tokio::spawn(async move {
+                    let _ = gate.await;
+                    task.await
+                });
+            } else {
+                tokio::spawn(task);
+            }
+        };
+
+        let id_keys = identity::Keypair::generate_ed25519();
+        let transport = transport::MemoryTransport::default()
+            .upgrade(upgrade::Version::V1)
+            .authenticate(plaintext::Config::new(&id_keys))
+            .multiplex(yamux::Config::default())
+            .boxed();
+        let mut swarm1 = Swarm::new(
+            transport,
+            NotifyFlood {
+                peer: None,
+                remaining: NUM_EVENTS,
+                echoed: 0,
+            },
+            id_keys.public().to_peer_id(),
+            Config::with_executor(executor)
+                .with_notify_handler_buffer_size(NonZeroUsize::new(BUFFER_SIZE).unwrap()),
+        );
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        swarm2.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+                break address;
+            }
+        };
+        swarm1.dial(addr).unwrap();
+        future::poll_fn(|cx| {
+            while swarm2.poll_next_unpin(cx).is_ready() {}
+            while let Poll::Ready(event) = swarm1.poll_next_unpin(cx) {
+                if let Some(SwarmEvent::ConnectionEstablished { .. }) = event {
+                    return Poll::Ready(());
                 }
             }
+            Poll::Pending
         })
-        .await
-        .unwrap();
-    }
+        .await;
 
-    #[tokio::test]
-    async fn dial_self_by_id() {
-        // Trying to dial self by passing the same `PeerId` shouldn't even be possible in the first
-        // place.
-        let swarm = new_test_swarm(Config::with_tokio_executor());
-        let peer_id = *swarm.local_peer_id();
-        assert!(!swarm.is_connected(&peer_id));
+        // The connection does not accept any events, thus the behaviour is held back once its
+        // buffer is full.
+        future::poll_fn(|cx| {
+            while swarm1.poll_next_unpin(cx).is_ready() {}
+            Poll::Ready(())
+        })
+        .await;
+        let sent = NUM_EVENTS - swarm1.behaviour().remaining;
+        assert!(sent <= BUFFER_SIZE + 2, "{sent} events sent");
+        assert_eq!(swarm1.pending_behaviour_commands(), 1);
+
+        open_gate.send(()).unwrap();
+        future::poll_fn(|cx| {
+            while swarm1.poll_next_unpin(cx).is_ready() {}
+            while swarm2.poll_next_unpin(cx).is_ready() {}
+            if swarm1.behaviour().echoed == NUM_EVENTS {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
     }
 
     #[tokio::test]
-    async fn multiple_addresses_err() {
-        // Tries dialing multiple addresses, and makes sure there's one dialing error per address.
+    async fn notify_any_prefers_direct_over_relayed_connection() {
+        let new_swarm = || {
+            let id_keys = identity::Keypair::generate_ed25519();
+            let transport = FakeRelayTransport(transport::MemoryTransport::default())
+                .upgrade(upgrade::Version::V1)
+                .authenticate(plaintext::Config::new(&id_keys))
+                .multiplex(yamux::Config::default())
+                .boxed();
+
+            Swarm::new(
+                transport,
+                CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(EchoHandler::default())),
+                id_keys.public().to_peer_id(),
+                Config::with_tokio_executor(),
+            )
+        };
+        let mut dialer = new_swarm();
+        let mut listener = new_swarm();
+        let listener_id = *listener.local_peer_id();
 
-        let target = PeerId::random();
+        listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let listener_address = match listener.next().await.unwrap() {
+            SwarmEvent::NewListenAddr { address, .. } => address,
+            e => panic!("Unexpected network event: {e:?}"),
+        };
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        let mut connect = |address: Multiaddr| {
+            dialer
+                .dial(
+                    DialOpts::peer_id(listener_id)
+                        .addresses(vec![address])
+                        .condition(PeerCondition::Always)
+                        .build(),
+                )
+                .unwrap();
+        };
+        connect(
+            listener_address
+                .clone()
+                .with(multiaddr::Protocol::P2pCircuit),
+        );
+        connect(listener_address);
 
-        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+        let mut direct = None;
+        let mut relayed = None;
+        while direct.is_none() || relayed.is_none() {
+            if let SwarmEvent::ConnectionEstablished {
+                connection_id,
+                endpoint,
+                ..
+            } = dialer.select_next_some().await
+            {
+                if endpoint.is_relayed() {
+                    relayed = Some(connection_id);
+                } else {
+                    direct = Some(connection_id);
+                }
+            }
+        }
+        let direct = direct.unwrap();
+        let relayed = relayed.unwrap();
+        assert_eq!(dialer.connection_priority(direct), Some(1));
+        assert_eq!(dialer.connection_priority(relayed), Some(0));
 
-        let addresses = HashSet::from([
-            multiaddr![Ip4([0, 0, 0, 0]), Tcp(rand::random::<u16>())],
-            multiaddr![Ip4([0, 0, 0, 0]), Tcp(rand::random::<u16>())],
-            multiaddr![Ip4([0, 0, 0, 0]), Tcp(rand::random::<u16>())],
-            multiaddr![Udp(rand::random::<u16>())],
-            multiaddr![Udp(rand::random::<u16>())],
-            multiaddr![Udp(rand::random::<u16>())],
-            multiaddr![Udp(rand::random::<u16>())],
-            multiaddr![Udp(rand::random::<u16>())],
-        ]);
+        assert_eq!(notify_any(&mut dialer, listener_id).await, direct);
 
-        swarm
+        assert!(dialer.set_connection_priority(relayed, 2));
+        assert_eq!(notify_any(&mut dialer, listener_id).await, relayed);
+    }
+
+    /// Establishes a relayed connection from a new dialer to a new listener and requests its
+    /// upgrade to a direct connection, returning the dialer, the listener's peer id and direct
+    /// address and the relayed connection.
+    async fn request_upgrade_to_direct() -> (
+        Swarm<CallTraceBehaviour<MockBehaviour<EchoHandler, ()>>>,
+        PeerId,
+        Multiaddr,
+        ConnectionId,
+    ) {
+        let new_swarm = || {
+            let id_keys = identity::Keypair::generate_ed25519();
+            let transport = FakeRelayTransport(transport::MemoryTransport::default())
+                .upgrade(upgrade::Version::V1)
+                .authenticate(plaintext::Config::new(&id_keys))
+                .multiplex(yamux::Config::default())
+                .boxed();
+
+            Swarm::new(
+                transport,
+                CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(EchoHandler::default())),
+                id_keys.public().to_peer_id(),
+                Config::with_tokio_executor(),
+            )
+        };
+        let mut dialer = new_swarm();
+        let mut listener = new_swarm();
+        let listener_id = *listener.local_peer_id();
+
+        listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let listener_address = match listener.next().await.unwrap() {
+            SwarmEvent::NewListenAddr { address, .. } => address,
+            e => panic!("Unexpected network event: {e:?}"),
+        };
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        dialer
             .dial(
-                DialOpts::peer_id(target)
-                    .addresses(addresses.iter().cloned().collect())
+                DialOpts::peer_id(listener_id)
+                    .addresses(vec![listener_address
+                        .clone()
+                        .with(multiaddr::Protocol::P2pCircuit)])
                     .build(),
             )
             .unwrap();
+        let relayed = loop {
+            if let SwarmEvent::ConnectionEstablished { connection_id, .. } =
+                dialer.select_next_some().await
+            {
+                break connection_id;
+            }
+        };
 
-        match swarm.next().await.unwrap() {
-            SwarmEvent::OutgoingConnectionError {
-                peer_id,
-                // multiaddr,
-                error: DialError::Transport(errors),
-                ..
-            } => {
-                assert_eq!(target, peer_id.unwrap());
+        dialer.behaviour_mut().inner().next_action = Some(ToSwarm::UpgradeToDirect {
+            peer_id: listener_id,
+            connection_id: relayed,
+        });
+        future::poll_fn(|cx| {
+            while dialer.poll_next_unpin(cx).is_ready() {}
+            if dialer.behaviour().on_upgrade_to_direct.is_empty() {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+        assert_eq!(
+            dialer.behaviour().on_upgrade_to_direct,
+            vec![(listener_id, relayed)]
+        );
 
-                let failed_addresses = errors.into_iter().map(|(addr, _)| addr).collect::<Vec<_>>();
-                let expected_addresses = addresses
-                    .into_iter()
-                    .map(|addr| addr.with(multiaddr::Protocol::P2p(target)))
-                    .collect::<Vec<_>>();
+        (dialer, listener_id, listener_address, relayed)
+    }
 
-                assert_eq!(expected_addresses, failed_addresses);
+    /// Dials `address` of `peer` directly and returns the established connection.
+    async fn dial_direct(
+        dialer: &mut Swarm<CallTraceBehaviour<MockBehaviour<EchoHandler, ()>>>,
+        peer: PeerId,
+        address: Multiaddr,
+    ) -> ConnectionId {
+        dialer
+            .dial(
+                DialOpts::peer_id(peer)
+                    .addresses(vec![address])
+                    .condition(PeerCondition::Always)
+                    .build(),
+            )
+            .unwrap();
+        loop {
+            if let SwarmEvent::ConnectionEstablished { connection_id, .. } =
+                dialer.select_next_some().await
+            {
+                break connection_id;
             }
-            e => panic!("Unexpected event: {e:?}"),
         }
     }
 
     #[tokio::test]
-    async fn aborting_pending_connection_surfaces_error() {
-        let _ = tracing_subscriber::fmt()
-            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-            .try_init();
+    async fn upgrade_to_direct_reports_direct_connection() {
+        let (mut dialer, listener_id, listener_address, relayed) =
+            request_upgrade_to_direct().await;
+        assert!(dialer.behaviour().on_direct_connection_upgraded.is_empty());
 
-        let mut dialer = new_test_swarm(Config::with_tokio_executor());
-        let mut listener = new_test_swarm(Config::with_tokio_executor());
+        // Dial the direct connection, as a DCUtR-like behaviour would in response.
+        let direct = dial_direct(&mut dialer, listener_id, listener_address).await;
+
+        assert_eq!(
+            dialer.behaviour().on_direct_connection_upgraded,
+            vec![(listener_id, relayed, direct)]
+        );
+    }
+
+    #[tokio::test]
+    async fn upgrade_to_direct_forgotten_once_relayed_connection_closed() {
+        let (mut dialer, listener_id, listener_address, relayed) =
+            request_upgrade_to_direct().await;
+
+        assert!(dialer.close_connection(relayed));
+        loop {
+            if let SwarmEvent::ConnectionClosed { connection_id, .. } =
+                dialer.select_next_some().await
+            {
+                assert_eq!(connection_id, relayed);
+                break;
+            }
+        }
+
+        // An unrelated direct connection, established after the relayed one closed.
+        dial_direct(&mut dialer, listener_id, listener_address).await;
+
+        assert!(dialer.behaviour().on_direct_connection_upgraded.is_empty());
+    }
+
+    #[tokio::test]
+    async fn notify_any_avoids_stale_connection() {
+        const READ_WINDOW: Duration = Duration::from_millis(100);
+
+        let new_swarm = |config: Config| {
+            let id_keys = identity::Keypair::generate_ed25519();
+            let transport = transport::MemoryTransport::default()
+                .upgrade(upgrade::Version::V1)
+                .authenticate(plaintext::Config::new(&id_keys))
+                .multiplex(yamux::Config::default())
+                .boxed();
+
+            Swarm::new(
+                transport,
+                CallTraceBehaviour::new(MockBehaviour::<_, ()>::new(EchoHandler::default())),
+                id_keys.public().to_peer_id(),
+                config,
+            )
+        };
+        let mut dialer =
+            new_swarm(Config::with_tokio_executor().with_notify_any_read_window(READ_WINDOW));
+        let mut listener = new_swarm(Config::with_tokio_executor());
+        let listener_id = *listener.local_peer_id();
 
-        let listener_peer_id = *listener.local_peer_id();
         listener.listen_on(multiaddr![Memory(0u64)]).unwrap();
         let listener_address = match listener.next().await.unwrap() {
             SwarmEvent::NewListenAddr { address, .. } => address,
             e => panic!("Unexpected network event: {e:?}"),
         };
+        tokio::spawn(listener.collect::<Vec<_>>());
+
+        fn connect(
+            dialer: &mut Swarm<CallTraceBehaviour<MockBehaviour<EchoHandler, ()>>>,
+            peer_id: PeerId,
+            address: &Multiaddr,
+        ) {
+            dialer
+                .dial(
+                    DialOpts::peer_id(peer_id)
+                        .addresses(vec![address.clone()])
+                        .condition(PeerCondition::Always)
+                        .build(),
+                )
+                .unwrap();
+        }
+        connect(&mut dialer, listener_id, &listener_address);
+        let stale = loop {
+            if let SwarmEvent::ConnectionEstablished { connection_id, .. } =
+                dialer.select_next_some().await
+            {
+                break connection_id;
+            }
+        };
+        // Nothing is ever read on the connections, thus the first one turns stale.
+        futures_timer::Delay::new(READ_WINDOW * 2).await;
+        assert_eq!(notify_any(&mut dialer, listener_id).await, stale);
+
+        connect(&mut dialer, listener_id, &listener_address);
+        let fresh = loop {
+            if let SwarmEvent::ConnectionEstablished { connection_id, .. } =
+                dialer.select_next_some().await
+            {
+                break connection_id;
+            }
+        };
+        assert_eq!(
+            dialer.connection_priority(stale),
+            dialer.connection_priority(fresh)
+        );
 
-        dialer
-            .dial(
-                DialOpts::peer_id(listener_peer_id)
-                    .addresses(vec![listener_address])
-                    .build(),
-            )
-            .unwrap();
+        assert_eq!(notify_any(&mut dialer, listener_id).await, fresh);
+    }
 
-        dialer
-            .disconnect_peer_id(listener_peer_id)
-            .expect_err("Expect peer to not yet be connected.");
+    /// Sends an event to any connection to the given peer, returning the connection that received
+    /// it.
+    async fn notify_any(
+        swarm: &mut Swarm<CallTraceBehaviour<MockBehaviour<EchoHandler, ()>>>,
+        peer_id: PeerId,
+    ) -> ConnectionId {
+        swarm.behaviour_mut().inner().next_action = Some(ToSwarm::NotifyHandler {
+            peer_id,
+            handler: NotifyHandler::Any,
+            event: (),
+        });
+        future::poll_fn(|cx| loop {
+            let poll = swarm.poll_next_unpin(cx);
+            if let Some((_, connection_id, ())) =
+                swarm.behaviour_mut().on_connection_handler_event.pop()
+            {
+                return Poll::Ready(connection_id);
+            }
+            if poll.is_pending() {
+                return Poll::Pending;
+            }
+        })
+        .await
+    }
 
-        match dialer.next().await.unwrap() {
-            SwarmEvent::OutgoingConnectionError {
-                error: DialError::Aborted,
-                ..
-            } => {}
-            e => panic!("Unexpected swarm event {e:?}."),
+    /// Dials and listens on memory addresses, ignoring anything from a `/p2p-circuit` on, so
+    /// that connections appear relayed.
+    struct FakeRelayTransport(transport::MemoryTransport);
+
+    impl FakeRelayTransport {
+        fn strip_circuit(address: Multiaddr) -> Multiaddr {
+            address
+                .iter()
+                .take_while(|p| *p != multiaddr::Protocol::P2pCircuit)
+                .collect()
+        }
+    }
+
+    impl Transport for FakeRelayTransport {
+        type Output = <transport::MemoryTransport as Transport>::Output;
+        type Error = <transport::MemoryTransport as Transport>::Error;
+        type ListenerUpgrade = <transport::MemoryTransport as Transport>::ListenerUpgrade;
+        type Dial = <transport::MemoryTransport as Transport>::Dial;
+
+        fn listen_on(
+            &mut self,
+            id: ListenerId,
+            addr: Multiaddr,
+        ) -> Result<(), TransportError<Self::Error>> {
+            self.0.listen_on(id, addr)
+        }
+
+        fn remove_listener(&mut self, id: ListenerId) -> bool {
+            self.0.remove_listener(id)
+        }
+
+        fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.0.dial(Self::strip_circuit(addr))
+        }
+
+        fn dial_as_listener(
+            &mut self,
+            addr: Multiaddr,
+        ) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.0.dial_as_listener(Self::strip_circuit(addr))
+        }
+
+        fn address_translation(
+            &self,
+            listen: &Multiaddr,
+            observed: &Multiaddr,
+        ) -> Option<Multiaddr> {
+            self.0.address_translation(listen, observed)
+        }
+
+        fn poll(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<transport::TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+            Pin::new(&mut self.0).poll(cx)
+        }
+    }
+
+    /// Upgrades connections dialed with an overridden role as the dialer.
+    ///
+    /// In a hole punch, the remote dials us at the same time and acts as the dialer of the
+    /// connection, which the memory transport can't simulate.
+    struct SimultaneousOpenTransport(transport::Boxed<(PeerId, StreamMuxerBox)>);
+
+    impl Transport for SimultaneousOpenTransport {
+        type Output = (PeerId, StreamMuxerBox);
+        type Error = io::Error;
+        type ListenerUpgrade =
+            <transport::Boxed<(PeerId, StreamMuxerBox)> as Transport>::ListenerUpgrade;
+        type Dial = <transport::Boxed<(PeerId, StreamMuxerBox)> as Transport>::Dial;
+
+        fn listen_on(
+            &mut self,
+            id: ListenerId,
+            addr: Multiaddr,
+        ) -> Result<(), TransportError<Self::Error>> {
+            self.0.listen_on(id, addr)
+        }
+
+        fn remove_listener(&mut self, id: ListenerId) -> bool {
+            self.0.remove_listener(id)
+        }
+
+        fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.0.dial(addr)
+        }
+
+        fn dial_as_listener(
+            &mut self,
+            addr: Multiaddr,
+        ) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.0.dial(addr)
+        }
+
+        fn address_translation(
+            &self,
+            listen: &Multiaddr,
+            observed: &Multiaddr,
+        ) -> Option<Multiaddr> {
+            self.0.address_translation(listen, observed)
+        }
+
+        fn poll(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<transport::TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+            Pin::new(&mut self.0).poll(cx)
+        }
+    }
+
+    /// Notifies the [`EchoHandler`] of the first connection to a peer of events as fast as it is
+    /// polled, counting the events reported back.
+    struct NotifyFlood {
+        peer: Option<PeerId>,
+        remaining: usize,
+        echoed: usize,
+    }
+
+    impl NetworkBehaviour for NotifyFlood {
+        type ConnectionHandler = EchoHandler;
+        type ToSwarm = ();
+
+        fn handle_established_inbound_connection(
+            &mut self,
+            _: ConnectionId,
+            _: PeerId,
+            _: &Multiaddr,
+            _: &Multiaddr,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(EchoHandler::default())
         }
+
+        fn handle_established_outbound_connection(
+            &mut self,
+            _: ConnectionId,
+            _: PeerId,
+            _: &Multiaddr,
+            _: Endpoint,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(EchoHandler::default())
+        }
+
+        fn on_swarm_event(&mut self, event: FromSwarm) {
+            if let FromSwarm::ConnectionEstablished(e) = event {
+                self.peer.get_or_insert(e.peer_id);
+            }
+        }
+
+        fn on_connection_handler_event(
+            &mut self,
+            _: PeerId,
+            _: ConnectionId,
+            (): THandlerOutEvent<Self>,
+        ) {
+            self.echoed += 1;
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+            match self.peer {
+                Some(peer_id) if self.remaining > 0 => {
+                    self.remaining -= 1;
+
+                    Poll::Ready(ToSwarm::NotifyHandler {
+                        peer_id,
+                        handler: NotifyHandler::Any,
+                        event: (),
+                    })
+                }
+                _ => Poll::Pending,
+            }
+        }
+    }
+
+    /// Reports each event from the behaviour back to it.
+    #[derive(Default, Clone)]
+    struct EchoHandler {
+        pending: usize,
+    }
+
+    impl ConnectionHandler for EchoHandler {
+        type FromBehaviour = ();
+        type ToBehaviour = ();
+        type InboundProtocol = upgrade::DeniedUpgrade;
+        type OutboundProtocol = upgrade::DeniedUpgrade;
+        type InboundOpenInfo = ();
+        type OutboundOpenInfo = void::Void;
+
+        fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
+            SubstreamProtocol::new(upgrade::DeniedUpgrade, ())
+        }
+
+        fn connection_keep_alive(&self) -> bool {
+            true
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, void::Void, Self::ToBehaviour>>
+        {
+            if self.pending > 0 {
+                self.pending -= 1;
+                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(()));
+            }
+
+            Poll::Pending
+        }
+
+        fn on_behaviour_event(&mut self, (): Self::FromBehaviour) {
+            self.pending += 1;
+        }
+
+        fn on_connection_event(
+            &mut self,
+            _: handler::ConnectionEvent<
+                Self::InboundProtocol,
+                Self::OutboundProtocol,
+                (),
+                void::Void,
+            >,
+        ) {
+        }
+    }
+
+    /// Records every span created while it is the default subscriber, together with the names of
+    /// its parent and of the spans it follows from.
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<RecordedSpan>>>);
+
+    #[derive(Debug)]
+    struct RecordedSpan {
+        id: tracing::span::Id,
+        name: &'static str,
+        fields: HashMap<&'static str, String>,
+        parent: Option<&'static str>,
+        follows_from: Vec<&'static str>,
+    }
+
+    impl SpanRecorder {
+        fn spans(&self, name: &str) -> Vec<RecordedSpan> {
+            let mut spans = self.0.lock().unwrap();
+            let (matching, rest) = spans.drain(..).partition(|s| s.name == name);
+            *spans = rest;
+            matching
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanRecorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            struct Fields<'a>(&'a mut HashMap<&'static str, String>);
+
+            impl tracing::field::Visit for Fields<'_> {
+                fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                    self.0.insert(field.name(), value.to_owned());
+                }
+
+                fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn fmt::Debug) {
+                    self.0.insert(field.name(), format!("{value:?}"));
+                }
+            }
+
+            let mut fields = HashMap::new();
+            attrs.record(&mut Fields(&mut fields));
+
+            self.0.lock().unwrap().push(RecordedSpan {
+                id: id.clone(),
+                name: attrs.metadata().name(),
+                fields,
+                parent: ctx.span(id).and_then(|s| s.parent()).map(|p| p.name()),
+                follows_from: Vec::new(),
+            });
+        }
+
+        fn on_follows_from(
+            &self,
+            span: &tracing::span::Id,
+            follows: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let Some(follows) = ctx.span(follows) else {
+                return;
+            };
+            // Span IDs are reused once a span is closed, the latest record is the live one.
+            if let Some(recorded) = self
+                .0
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .rev()
+                .find(|s| &s.id == span)
+            {
+                recorded.follows_from.push(follows.name());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn connection_lifecycle_is_traced_in_spans() {
+        use tracing_subscriber::layer::SubscriberExt as _;
+
+        // The current-thread runtime polls the connection tasks on this thread as well.
+        let recorder = SpanRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        swarm2.listen_on(multiaddr![Memory(0u64)]).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+                break address;
+            }
+        };
+
+        swarm1.dial(addr).unwrap();
+        drive_until_connected(&mut swarm1, &mut swarm2, 1).await;
+        swarm1.disconnect_peer_id(*swarm2.local_peer_id()).unwrap();
+        drive_until_connected(&mut swarm1, &mut swarm2, 0).await;
+
+        let listeners = recorder.spans("listener");
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0].fields["address"], "/memory/0");
+
+        let dials = recorder.spans("Transport::dial");
+        assert_eq!(dials.len(), 1);
+        assert!(dials[0].fields["address"].starts_with("/memory/"));
+
+        let incoming = recorder.spans("new_incoming_connection");
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].follows_from, vec!["listener"]);
+
+        let established = recorder.spans("new_established_connection");
+        let directions = established
+            .iter()
+            .map(|s| (s.fields["peer"].as_str(), s.fields["direction"].as_str()))
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            directions,
+            HashSet::from([
+                (swarm2.local_peer_id().to_string().as_str(), "dialer"),
+                (swarm1.local_peer_id().to_string().as_str(), "listener"),
+            ])
+        );
+
+        let polls = recorder.spans("Connection::poll");
+        assert!(!polls.is_empty());
+        assert!(polls
+            .iter()
+            .all(|s| s.parent == Some("new_established_connection")));
     }
 
     #[test]
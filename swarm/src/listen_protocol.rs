@@ -0,0 +1,131 @@
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::Multiaddr;
+
+/// The transport protocol a listen address accepts connections on, as reported via
+/// [`FromSwarm::ListenProtocolsChanged`](crate::FromSwarm::ListenProtocolsChanged).
+///
+/// See [`listen_protocol`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ListenProtocol {
+    /// `/tcp`
+    Tcp,
+    /// `/ws`
+    Ws,
+    /// `/wss` or `/tls/ws`
+    Wss,
+    /// `/quic`
+    Quic,
+    /// `/quic-v1`
+    QuicV1,
+    /// `/webtransport`
+    WebTransport,
+    /// `/webrtc-direct`
+    WebRtcDirect,
+    /// `/p2p-circuit`
+    P2pCircuit,
+    /// `/memory`
+    Memory,
+}
+
+/// Returns the [`ListenProtocol`] of a listen address.
+///
+/// The outermost transport protocol takes precedence, e.g. `/ip4/127.0.0.1/tcp/4001/ws` is
+/// [`ListenProtocol::Ws`] and a relayed address is always [`ListenProtocol::P2pCircuit`].
+///
+/// Returns [`None`] if the address does not contain any known transport protocol.
+pub fn listen_protocol(addr: &Multiaddr) -> Option<ListenProtocol> {
+    let mut protocol = None;
+    let mut tls = false;
+
+    for p in addr.iter() {
+        protocol = Some(match p {
+            Protocol::Tcp(_) => ListenProtocol::Tcp,
+            Protocol::Tls => {
+                tls = true;
+                continue;
+            }
+            Protocol::Ws(_) if tls => ListenProtocol::Wss,
+            Protocol::Ws(_) => ListenProtocol::Ws,
+            Protocol::Wss(_) => ListenProtocol::Wss,
+            Protocol::Quic => ListenProtocol::Quic,
+            Protocol::QuicV1 => ListenProtocol::QuicV1,
+            Protocol::WebTransport => ListenProtocol::WebTransport,
+            Protocol::WebRTCDirect => ListenProtocol::WebRtcDirect,
+            Protocol::P2pCircuit => ListenProtocol::P2pCircuit,
+            Protocol::Memory(_) => ListenProtocol::Memory,
+            _ => continue,
+        });
+    }
+
+    protocol
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(addr: &str) -> Option<ListenProtocol> {
+        listen_protocol(&addr.parse().unwrap())
+    }
+
+    #[test]
+    fn tcp() {
+        assert_eq!(parse("/ip4/127.0.0.1/tcp/4001"), Some(ListenProtocol::Tcp));
+        assert_eq!(parse("/ip6/::1/tcp/4001"), Some(ListenProtocol::Tcp));
+    }
+
+    #[test]
+    fn ws() {
+        assert_eq!(
+            parse("/ip4/127.0.0.1/tcp/4001/ws"),
+            Some(ListenProtocol::Ws)
+        );
+    }
+
+    #[test]
+    fn wss() {
+        assert_eq!(
+            parse("/ip4/127.0.0.1/tcp/4001/wss"),
+            Some(ListenProtocol::Wss)
+        );
+        assert_eq!(
+            parse("/ip4/127.0.0.1/tcp/4001/tls/ws"),
+            Some(ListenProtocol::Wss)
+        );
+    }
+
+    #[test]
+    fn quic_v1() {
+        assert_eq!(
+            parse("/ip4/127.0.0.1/udp/4001/quic-v1"),
+            Some(ListenProtocol::QuicV1)
+        );
+        assert_eq!(
+            parse("/ip4/127.0.0.1/udp/4001/quic-v1/webtransport"),
+            Some(ListenProtocol::WebTransport)
+        );
+    }
+
+    #[test]
+    fn webrtc_direct() {
+        assert_eq!(
+            parse("/ip4/127.0.0.1/udp/4001/webrtc-direct/certhash/uEiDikp5KVUgkLta1EjUN-IKbHk-dUBg8VzKgf5nXxLK46w"),
+            Some(ListenProtocol::WebRtcDirect)
+        );
+    }
+
+    #[test]
+    fn p2p_circuit() {
+        assert_eq!(
+            parse("/ip4/127.0.0.1/tcp/4001/p2p/12D3KooWEz3ZnZJ7D4wTqVSDUdqDRLXb6Ds9PbNTx7WnNVjKw3YG/p2p-circuit"),
+            Some(ListenProtocol::P2pCircuit)
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(parse("/ip4/127.0.0.1"), None);
+        assert_eq!(parse("/ip4/127.0.0.1/udp/4001"), None);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{NetworkBehaviour, Swarm, SwarmEvent};
+use futures::StreamExt;
+use libp2p_core::transport::ListenerId;
+use libp2p_core::Multiaddr;
+use std::collections::HashSet;
+
+/// Tracks a set of listeners until each of them either reported a listen address or was closed.
+///
+/// Typically used together with [`Swarm::listen_on_many`] to wait until all listeners are ready
+/// before advertising the local node.
+///
+/// ```
+/// # use libp2p_core::Multiaddr;
+/// # use libp2p_swarm::{ListenersReady, NetworkBehaviour, Swarm};
+/// # async fn listen<B: NetworkBehaviour>(swarm: &mut Swarm<B>) {
+/// let listener_ids = swarm
+///     .listen_on_many([
+///         "/ip4/0.0.0.0/tcp/0".parse::<Multiaddr>().unwrap(),
+///         "/ip4/0.0.0.0/udp/0/quic-v1".parse::<Multiaddr>().unwrap(),
+///     ])
+///     .map_err(|(index, error)| format!("Failed to listen on address {index}: {error}"))
+///     .unwrap();
+/// let (addresses, other_events) = ListenersReady::new(listener_ids).wait(swarm).await;
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ListenersReady {
+    listeners: HashSet<ListenerId>,
+    pending: HashSet<ListenerId>,
+    failed: Vec<ListenerId>,
+    addresses: Vec<Multiaddr>,
+}
+
+impl ListenersReady {
+    /// Creates a new [`ListenersReady`] waiting for the given listeners.
+    pub fn new(listener_ids: impl IntoIterator<Item = ListenerId>) -> Self {
+        let listeners: HashSet<_> = listener_ids.into_iter().collect();
+
+        Self {
+            pending: listeners.clone(),
+            listeners,
+            failed: Vec::new(),
+            addresses: Vec::new(),
+        }
+    }
+
+    /// Updates the state of the tracked listeners with an event emitted by the [`Swarm`].
+    ///
+    /// Addresses reported by a tracked listener are collected even after it became ready. A
+    /// [`SwarmEvent::ListenerError`] does not close the listener, thus a listener only fails once
+    /// it is closed. Events of other listeners and events unrelated to listeners are ignored.
+    pub fn on_swarm_event<TBehaviourOutEvent>(&mut self, event: &SwarmEvent<TBehaviourOutEvent>) {
+        match event {
+            SwarmEvent::NewListenAddr {
+                listener_id,
+                address,
+            } if self.listeners.contains(listener_id) => {
+                self.pending.remove(listener_id);
+                self.addresses.push(address.clone());
+            }
+            SwarmEvent::ListenerClosed { listener_id, .. }
+                if self.pending.contains(listener_id) =>
+            {
+                self.pending.remove(listener_id);
+                self.failed.push(*listener_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether all listeners either reported a listen address or were closed.
+    pub fn is_ready(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// The addresses reported by the listeners so far.
+    pub fn addresses(&self) -> &[Multiaddr] {
+        &self.addresses
+    }
+
+    /// The listeners that were closed before reporting a listen address.
+    pub fn failed(&self) -> &[ListenerId] {
+        &self.failed
+    }
+
+    /// Drives the [`Swarm`] until all listeners are ready, returning the reported addresses.
+    ///
+    /// All events emitted by the [`Swarm`] in the meantime, including the ones of the tracked
+    /// listeners, are returned as well, in the order they were emitted, for the caller to handle.
+    pub async fn wait<TBehaviour>(
+        mut self,
+        swarm: &mut Swarm<TBehaviour>,
+    ) -> (Vec<Multiaddr>, Vec<SwarmEvent<TBehaviour::ToSwarm>>)
+    where
+        TBehaviour: NetworkBehaviour,
+    {
+        let mut events = Vec::new();
+        while !self.is_ready() {
+            let event = swarm.select_next_some().await;
+            self.on_swarm_event(&event);
+            events.push(event);
+        }
+
+        (self.addresses, events)
+    }
+}
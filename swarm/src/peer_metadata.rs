@@ -0,0 +1,224 @@
+use instant::Instant;
+use libp2p_identity::PeerId;
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Application-defined metadata associated with remote peers, obtained via
+/// [`Swarm::peer_metadata_mut`](crate::Swarm::peer_metadata_mut).
+///
+/// Each peer can have at most one value per type. Values of a peer are removed once the
+/// configured grace period (see
+/// [`Config::with_peer_metadata_grace_period`](crate::Config::with_peer_metadata_grace_period))
+/// has passed after the last connection to that peer closed. Reconnecting within the grace period
+/// retains the values. Values inserted for a peer we are not connected to expire after the grace
+/// period unless a connection is established in the meantime.
+///
+/// The metadata is not exposed to the [`NetworkBehaviour`](crate::NetworkBehaviour).
+#[derive(Debug)]
+pub struct PeerMetadata {
+    grace_period: Duration,
+    connected: HashSet<PeerId>,
+    peers: HashMap<PeerId, Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    values: HashMap<TypeId, Box<dyn Any + Send>>,
+    /// When the values are removed, [`None`] while connected to the peer.
+    expires_at: Option<Instant>,
+}
+
+impl PeerMetadata {
+    pub(crate) fn new(grace_period: Duration) -> Self {
+        Self {
+            grace_period,
+            connected: HashSet::new(),
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Associates `value` with the given peer, returning the previous value of the same type.
+    pub fn insert<T>(&mut self, peer: PeerId, value: T) -> Option<T>
+    where
+        T: Send + 'static,
+    {
+        let expires_at = if self.connected.contains(&peer) {
+            None
+        } else {
+            Some(Instant::now() + self.grace_period)
+        };
+
+        self.peers
+            .entry(peer)
+            .or_insert_with(|| Entry {
+                values: HashMap::new(),
+                expires_at,
+            })
+            .values
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .map(|previous| {
+                *previous
+                    .downcast()
+                    .expect("values to be keyed by their type")
+            })
+    }
+
+    /// Returns the value of type `T` associated with the given peer.
+    pub fn get<T>(&self, peer: &PeerId) -> Option<&T>
+    where
+        T: 'static,
+    {
+        self.peers
+            .get(peer)?
+            .values
+            .get(&TypeId::of::<T>())?
+            .downcast_ref()
+    }
+
+    /// Returns a mutable reference to the value of type `T` associated with the given peer.
+    pub fn get_mut<T>(&mut self, peer: &PeerId) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        self.peers
+            .get_mut(peer)?
+            .values
+            .get_mut(&TypeId::of::<T>())?
+            .downcast_mut()
+    }
+
+    /// Removes the value of type `T` associated with the given peer.
+    pub fn remove<T>(&mut self, peer: &PeerId) -> Option<T>
+    where
+        T: 'static,
+    {
+        let entry = self.peers.get_mut(peer)?;
+        let value = entry.values.remove(&TypeId::of::<T>())?;
+
+        if entry.values.is_empty() {
+            self.peers.remove(peer);
+        }
+
+        Some(*value.downcast().expect("values to be keyed by their type"))
+    }
+
+    /// Removes all values associated with the given peer.
+    pub fn clear(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    pub(crate) fn on_connection_established(&mut self, peer: PeerId) {
+        self.connected.insert(peer);
+
+        if let Some(entry) = self.peers.get_mut(&peer) {
+            entry.expires_at = None;
+        }
+    }
+
+    pub(crate) fn on_connection_closed(
+        &mut self,
+        peer: PeerId,
+        remaining_established: usize,
+        now: Instant,
+    ) {
+        if remaining_established > 0 {
+            return;
+        }
+
+        self.connected.remove(&peer);
+
+        if let Some(entry) = self.peers.get_mut(&peer) {
+            entry.expires_at = Some(now + self.grace_period);
+        }
+    }
+
+    /// Removes the values of all peers whose grace period has passed.
+    pub(crate) fn remove_expired(&mut self, now: Instant) {
+        self.peers
+            .retain(|_, entry| entry.expires_at.map_or(true, |expires_at| expires_at > now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+    #[derive(Debug, PartialEq)]
+    struct Nickname(&'static str);
+
+    #[derive(Debug, PartialEq)]
+    struct Score(i32);
+
+    #[test]
+    fn values_are_keyed_by_peer_and_type() {
+        let mut metadata = PeerMetadata::new(GRACE_PERIOD);
+        let peer = PeerId::random();
+
+        assert_eq!(metadata.insert(peer, Nickname("alice")), None);
+        assert_eq!(metadata.insert(peer, Score(1)), None);
+        assert_eq!(metadata.insert(peer, Score(2)), Some(Score(1)));
+
+        assert_eq!(metadata.get::<Nickname>(&peer), Some(&Nickname("alice")));
+        assert_eq!(metadata.get::<Score>(&peer), Some(&Score(2)));
+        assert_eq!(metadata.get::<Score>(&PeerId::random()), None);
+        assert_eq!(metadata.get::<u8>(&peer), None);
+
+        metadata.get_mut::<Score>(&peer).unwrap().0 += 1;
+        assert_eq!(metadata.remove::<Score>(&peer), Some(Score(3)));
+        assert_eq!(metadata.get::<Score>(&peer), None);
+        assert_eq!(metadata.get::<Nickname>(&peer), Some(&Nickname("alice")));
+    }
+
+    #[test]
+    fn values_are_removed_after_grace_period() {
+        let mut metadata = PeerMetadata::new(GRACE_PERIOD);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        metadata.on_connection_established(peer);
+        metadata.on_connection_established(peer);
+        metadata.insert(peer, Score(1));
+
+        metadata.on_connection_closed(peer, 1, now);
+        metadata.remove_expired(now + 2 * GRACE_PERIOD);
+        assert_eq!(metadata.get::<Score>(&peer), Some(&Score(1)));
+
+        metadata.on_connection_closed(peer, 0, now);
+        metadata.remove_expired(now + GRACE_PERIOD / 2);
+        assert_eq!(metadata.get::<Score>(&peer), Some(&Score(1)));
+
+        metadata.remove_expired(now + GRACE_PERIOD);
+        assert_eq!(metadata.get::<Score>(&peer), None);
+    }
+
+    #[test]
+    fn values_are_retained_across_reconnects_within_grace_period() {
+        let mut metadata = PeerMetadata::new(GRACE_PERIOD);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        metadata.on_connection_established(peer);
+        metadata.insert(peer, Score(1));
+        metadata.on_connection_closed(peer, 0, now);
+
+        metadata.remove_expired(now + GRACE_PERIOD / 2);
+        metadata.on_connection_established(peer);
+        metadata.remove_expired(now + 2 * GRACE_PERIOD);
+
+        assert_eq!(metadata.get::<Score>(&peer), Some(&Score(1)));
+    }
+
+    #[test]
+    fn values_of_unconnected_peers_expire() {
+        let mut metadata = PeerMetadata::new(GRACE_PERIOD);
+        let peer = PeerId::random();
+
+        metadata.insert(peer, Score(1));
+        metadata.remove_expired(Instant::now() + 2 * GRACE_PERIOD);
+
+        assert_eq!(metadata.get::<Score>(&peer), None);
+    }
+}
@@ -0,0 +1,21 @@
+//! Provides the hook to refuse inbound connections under resource pressure.
+
+/// Consulted by the [`Swarm`](crate::Swarm) before accepting an inbound connection, e.g. to refuse
+/// connections early while the process is short on memory or file descriptors.
+///
+/// Unlike limits on the number of connections, a [`ResourceManager`] can take system-level
+/// resources into account. Inbound connections it refuses are reported as
+/// [`ListenError::InsufficientResources`](crate::ListenError::InsufficientResources), before any
+/// [`NetworkBehaviour`](crate::NetworkBehaviour) is consulted.
+///
+/// See [`Config::with_resource_manager`](crate::Config::with_resource_manager).
+pub trait ResourceManager: Send {
+    /// Whether there are sufficient resources to accept a new inbound connection.
+    fn can_accept_inbound(&self) -> bool;
+}
+
+impl<F: Fn() -> bool + Send> ResourceManager for F {
+    fn can_accept_inbound(&self) -> bool {
+        self()
+    }
+}
@@ -1,11 +1,14 @@
 use futures::{AsyncRead, AsyncWrite};
+use instant::Instant;
 use libp2p_core::muxing::SubstreamBox;
 use libp2p_core::Negotiated;
 use std::{
     io::{IoSlice, IoSliceMut},
     pin::Pin,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 /// Counter for the number of active streams on a connection.
@@ -26,17 +29,176 @@ impl ActiveStreamCounter {
     }
 }
 
+/// Handle to the number of bytes transferred over the streams of a single connection, see
+/// [`Swarm::connection_byte_counters`](crate::Swarm::connection_byte_counters).
+///
+/// Only the payload of the streams is counted, i.e. neither the protocol negotiation nor the
+/// framing of the muxer and the security protocol.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionByteCounters(Arc<ByteCounters>);
+
+#[derive(Debug, Default)]
+struct ByteCounters {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl ConnectionByteCounters {
+    /// The number of bytes read from the streams of the connection so far.
+    pub fn bytes_in(&self) -> u64 {
+        self.0.bytes_in.load(Ordering::Relaxed)
+    }
+
+    /// The number of bytes written to the streams of the connection so far.
+    pub fn bytes_out(&self) -> u64 {
+        self.0.bytes_out.load(Ordering::Relaxed)
+    }
+
+    fn add_in(&self, n: usize) {
+        self.0.bytes_in.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn add_out(&self, n: usize) {
+        self.0.bytes_out.fetch_add(n as u64, Ordering::Relaxed);
+    }
+}
+
+/// Traffic accounting of the streams of a connection, shared between the connection task and the
+/// [`Pool`](crate::connection::pool::Pool).
+#[derive(Debug)]
+pub(crate) struct StreamTraffic {
+    /// The bytes transferred over the streams, if enabled via
+    /// [`Config::with_connection_counters`](crate::Config::with_connection_counters).
+    bytes: Option<ConnectionByteCounters>,
+    /// The time of the last successful read from any of the streams, if enabled via
+    /// [`Config::with_notify_any_read_window`](crate::Config::with_notify_any_read_window).
+    last_read: Option<LastRead>,
+}
+
+impl StreamTraffic {
+    pub(crate) fn new(count_bytes: bool, track_reads: bool) -> Self {
+        Self {
+            bytes: count_bytes.then(ConnectionByteCounters::default),
+            last_read: track_reads.then(LastRead::new),
+        }
+    }
+
+    pub(crate) fn connection_counters(&self) -> Option<ConnectionByteCounters> {
+        self.bytes.clone()
+    }
+
+    /// Whether nothing was read from the streams within the last `window`, counting from the
+    /// creation of the connection.
+    ///
+    /// Always `false` unless reads are tracked.
+    pub(crate) fn is_stale(&self, window: Duration) -> bool {
+        self.last_read
+            .as_ref()
+            .is_some_and(|last_read| last_read.elapsed() > window)
+    }
+
+    /// Returns a recorder for the traffic of a newly opened stream.
+    pub(crate) fn recorder(self: &Arc<Self>) -> StreamTrafficRecorder {
+        StreamTrafficRecorder {
+            traffic: self.clone(),
+            bytes: self.bytes.clone(),
+        }
+    }
+}
+
+/// The time of the last read of a connection, stored as the milliseconds since the connection
+/// was created so it can be updated atomically.
+#[derive(Debug)]
+struct LastRead {
+    created: Instant,
+    millis: AtomicU64,
+}
+
+impl LastRead {
+    fn new() -> Self {
+        Self {
+            created: Instant::now(),
+            millis: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self) {
+        let millis = self.created.elapsed().as_millis() as u64;
+        self.millis.fetch_max(millis, Ordering::Relaxed);
+    }
+
+    fn elapsed(&self) -> Duration {
+        let last_read = Duration::from_millis(self.millis.load(Ordering::Relaxed));
+        self.created.elapsed().saturating_sub(last_read)
+    }
+}
+
+/// Records the traffic of a stream in its connection's [`StreamTraffic`].
+#[derive(Debug)]
+pub(crate) struct StreamTrafficRecorder {
+    traffic: Arc<StreamTraffic>,
+    /// Copy of [`StreamTraffic::bytes`], sparing the stream an indirection on every read and
+    /// write.
+    bytes: Option<ConnectionByteCounters>,
+}
+
+impl StreamTrafficRecorder {
+    fn record_read(&self, n: usize) {
+        if let Some(bytes) = &self.bytes {
+            bytes.add_in(n);
+        }
+        if let Some(last_read) = &self.traffic.last_read {
+            if n > 0 {
+                last_read.record();
+            }
+        }
+    }
+}
+
+/// Keeps an inbound stream accounted for against the limit of its protocol until dropped, see
+/// [`SubstreamProtocol::with_max_inbound_streams`](crate::handler::SubstreamProtocol::with_max_inbound_streams).
+#[derive(Debug)]
+pub(crate) struct InboundStreamLimitGuard(Arc<AtomicUsize>);
+
+impl InboundStreamLimitGuard {
+    /// Accounts for a new inbound stream, unless `max` streams of its protocol are open already.
+    pub(crate) fn try_new(active: Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        active
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()?;
+
+        Some(Self(active))
+    }
+}
+
+impl Drop for InboundStreamLimitGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 #[derive(Debug)]
 pub struct Stream {
     stream: Negotiated<SubstreamBox>,
     counter: Option<ActiveStreamCounter>,
+    traffic: StreamTrafficRecorder,
+    _limit_guard: Option<InboundStreamLimitGuard>,
 }
 
 impl Stream {
-    pub(crate) fn new(stream: Negotiated<SubstreamBox>, counter: ActiveStreamCounter) -> Self {
+    pub(crate) fn new(
+        stream: Negotiated<SubstreamBox>,
+        counter: ActiveStreamCounter,
+        traffic: StreamTrafficRecorder,
+        limit_guard: Option<InboundStreamLimitGuard>,
+    ) -> Self {
         Self {
             stream,
             counter: Some(counter),
+            traffic,
+            _limit_guard: limit_guard,
         }
     }
 
@@ -59,7 +221,13 @@ impl AsyncRead for Stream {
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<std::io::Result<usize>> {
-        Pin::new(&mut self.get_mut().stream).poll_read(cx, buf)
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.stream).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.traffic.record_read(n);
+        }
+
+        poll
     }
 
     fn poll_read_vectored(
@@ -67,7 +235,13 @@ impl AsyncRead for Stream {
         cx: &mut Context<'_>,
         bufs: &mut [IoSliceMut<'_>],
     ) -> Poll<std::io::Result<usize>> {
-        Pin::new(&mut self.get_mut().stream).poll_read_vectored(cx, bufs)
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.stream).poll_read_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.traffic.record_read(n);
+        }
+
+        poll
     }
 }
 
@@ -77,7 +251,13 @@ impl AsyncWrite for Stream {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<std::io::Result<usize>> {
-        Pin::new(&mut self.get_mut().stream).poll_write(cx, buf)
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.stream).poll_write(cx, buf);
+        if let (Poll::Ready(Ok(n)), Some(bytes)) = (&poll, &this.traffic.bytes) {
+            bytes.add_out(*n);
+        }
+
+        poll
     }
 
     fn poll_write_vectored(
@@ -85,7 +265,13 @@ impl AsyncWrite for Stream {
         cx: &mut Context<'_>,
         bufs: &[IoSlice<'_>],
     ) -> Poll<std::io::Result<usize>> {
-        Pin::new(&mut self.get_mut().stream).poll_write_vectored(cx, bufs)
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.stream).poll_write_vectored(cx, bufs);
+        if let (Poll::Ready(Ok(n)), Some(bytes)) = (&poll, &this.traffic.bytes) {
+            bytes.add_out(*n);
+        }
+
+        poll
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
@@ -19,12 +19,14 @@
 // DEALINGS IN THE SOFTWARE.
 
 use crate::behaviour::{
-    ConnectionClosed, ConnectionEstablished, DialFailure, ExpiredListenAddr, ExternalAddrExpired,
-    FromSwarm, ListenerClosed, ListenerError, NewExternalAddrCandidate, NewListenAddr, NewListener,
+    ConnectionCloseCompleted, ConnectionClosed, ConnectionEstablished, DialAttemptFailed,
+    DialFailure, DirectConnectionUpgraded, ExpiredListenAddr, ExternalAddrConfirmed,
+    ExternalAddrExpired, FromSwarm, ListenProtocolsChanged, ListenerClosed, ListenerError,
+    NewExternalAddrCandidate, NewListenAddr, NewListener, UpgradeToDirect,
 };
 use crate::{
-    ConnectionDenied, ConnectionHandler, ConnectionId, NetworkBehaviour, THandler, THandlerInEvent,
-    THandlerOutEvent, ToSwarm,
+    ConnectionDenied, ConnectionHandler, ConnectionId, ListenDenied, ListenProtocol,
+    NetworkBehaviour, StreamProtocol, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
 };
 use libp2p_core::{multiaddr::Multiaddr, transport::ListenerId, ConnectedPoint, Endpoint};
 use libp2p_identity::PeerId;
@@ -145,6 +147,7 @@ where
     pub(crate) on_connection_closed: Vec<(PeerId, ConnectionId, ConnectedPoint, usize)>,
     pub(crate) on_connection_handler_event: Vec<(PeerId, ConnectionId, THandlerOutEvent<TInner>)>,
     pub(crate) on_dial_failure: Vec<Option<PeerId>>,
+    pub(crate) on_dial_attempt_failed: Vec<(Option<PeerId>, ConnectionId, Multiaddr)>,
     pub(crate) on_new_listener: Vec<ListenerId>,
     pub(crate) on_new_listen_addr: Vec<(ListenerId, Multiaddr)>,
     pub(crate) on_new_external_addr: Vec<Multiaddr>,
@@ -152,6 +155,11 @@ where
     pub(crate) on_expired_external_addr: Vec<Multiaddr>,
     pub(crate) on_listener_error: Vec<ListenerId>,
     pub(crate) on_listener_closed: Vec<(ListenerId, bool)>,
+    pub(crate) on_connection_close_completed: Vec<(PeerId, ConnectionId, bool)>,
+    pub(crate) on_external_addr_confirmed: Vec<Multiaddr>,
+    pub(crate) on_listen_protocols_changed: Vec<Vec<ListenProtocol>>,
+    pub(crate) on_direct_connection_upgraded: Vec<(PeerId, ConnectionId, ConnectionId)>,
+    pub(crate) on_upgrade_to_direct: Vec<(PeerId, ConnectionId)>,
     pub(crate) poll: usize,
 }
 
@@ -171,6 +179,7 @@ where
             on_connection_closed: Vec::new(),
             on_connection_handler_event: Vec::new(),
             on_dial_failure: Vec::new(),
+            on_dial_attempt_failed: Vec::new(),
             on_new_listener: Vec::new(),
             on_new_listen_addr: Vec::new(),
             on_new_external_addr: Vec::new(),
@@ -178,6 +187,11 @@ where
             on_expired_external_addr: Vec::new(),
             on_listener_error: Vec::new(),
             on_listener_closed: Vec::new(),
+            on_connection_close_completed: Vec::new(),
+            on_external_addr_confirmed: Vec::new(),
+            on_listen_protocols_changed: Vec::new(),
+            on_direct_connection_upgraded: Vec::new(),
+            on_upgrade_to_direct: Vec::new(),
             poll: 0,
         }
     }
@@ -192,11 +206,17 @@ where
         self.on_connection_closed = Vec::new();
         self.on_connection_handler_event = Vec::new();
         self.on_dial_failure = Vec::new();
+        self.on_dial_attempt_failed = Vec::new();
         self.on_new_listen_addr = Vec::new();
         self.on_new_external_addr = Vec::new();
         self.on_expired_listen_addr = Vec::new();
         self.on_listener_error = Vec::new();
         self.on_listener_closed = Vec::new();
+        self.on_connection_close_completed = Vec::new();
+        self.on_external_addr_confirmed = Vec::new();
+        self.on_listen_protocols_changed = Vec::new();
+        self.on_direct_connection_upgraded = Vec::new();
+        self.on_upgrade_to_direct = Vec::new();
         self.poll = 0;
     }
 
@@ -377,6 +397,22 @@ where
             .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)
     }
 
+    fn handle_pending_listen(
+        &mut self,
+        listener_id: ListenerId,
+        addr: &Multiaddr,
+    ) -> Result<(), ListenDenied> {
+        self.inner.handle_pending_listen(listener_id, addr)
+    }
+
+    fn sort_addresses(&self, peer: &PeerId, addrs: &mut Vec<Multiaddr>) {
+        self.inner.sort_addresses(peer, addrs)
+    }
+
+    fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        self.inner.supported_protocols()
+    }
+
     fn handle_established_inbound_connection(
         &mut self,
         connection_id: ConnectionId,
@@ -446,9 +482,35 @@ where
             FromSwarm::ConnectionClosed(connection_closed) => {
                 self.on_connection_closed(connection_closed)
             }
+            FromSwarm::ConnectionCloseCompleted(ConnectionCloseCompleted {
+                peer_id,
+                connection_id,
+                graceful,
+            }) => {
+                assert!(
+                    self.on_connection_closed
+                        .last()
+                        .is_some_and(
+                            |(peer, conn_id, ..)| (peer, conn_id) == (&peer_id, &connection_id)
+                        ),
+                    "`FromSwarm::ConnectionCloseCompleted` directly follows the \
+                     `FromSwarm::ConnectionClosed` of the same connection."
+                );
+                self.on_connection_close_completed
+                    .push((peer_id, connection_id, graceful));
+            }
             FromSwarm::DialFailure(DialFailure { peer_id, .. }) => {
                 self.on_dial_failure.push(peer_id);
             }
+            FromSwarm::DialAttemptFailed(DialAttemptFailed {
+                peer_id,
+                connection_id,
+                address,
+                ..
+            }) => {
+                self.on_dial_attempt_failed
+                    .push((peer_id, connection_id, address.clone()));
+            }
             FromSwarm::NewListener(NewListener { listener_id }) => {
                 self.on_new_listener.push(listener_id);
             }
@@ -462,6 +524,9 @@ where
             FromSwarm::NewExternalAddrCandidate(NewExternalAddrCandidate { addr }) => {
                 self.on_new_external_addr.push(addr.clone());
             }
+            FromSwarm::ExternalAddrConfirmed(ExternalAddrConfirmed { addr }) => {
+                self.on_external_addr_confirmed.push(addr.clone());
+            }
             FromSwarm::ExternalAddrExpired(ExternalAddrExpired { addr }) => {
                 self.on_expired_external_addr.push(addr.clone());
             }
@@ -474,6 +539,28 @@ where
             }) => {
                 self.on_listener_closed.push((listener_id, reason.is_ok()));
             }
+            FromSwarm::ListenProtocolsChanged(ListenProtocolsChanged { protocols }) => {
+                self.on_listen_protocols_changed.push(protocols.to_vec());
+            }
+            FromSwarm::DirectConnectionUpgraded(DirectConnectionUpgraded {
+                peer_id,
+                relayed_connection_id,
+                direct_connection_id,
+                ..
+            }) => {
+                self.on_direct_connection_upgraded.push((
+                    peer_id,
+                    relayed_connection_id,
+                    direct_connection_id,
+                ));
+            }
+            FromSwarm::UpgradeToDirect(UpgradeToDirect {
+                peer_id,
+                connection_id,
+                ..
+            }) => {
+                self.on_upgrade_to_direct.push((peer_id, connection_id));
+            }
             _ => {}
         }
     }
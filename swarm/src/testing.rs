@@ -0,0 +1,505 @@
+//! A deterministic harness for testing [`NetworkBehaviour`]s, enabled via the `testing` feature.
+//!
+//! [`TestSwarm`]s communicate over the memory transport and are polled in place rather than on an
+//! executor. Their timers run on the [`VirtualClock`] of a shared [`TestRuntime`]: whenever all
+//! swarms are idle, [`TestSwarm::wait_for_event`] advances the clock straight to the next timer,
+//! so tests never wait in real time.
+//!
+//! ```
+//! # use libp2p_swarm::testing::{connect, TestRuntime, TestSwarm};
+//! # use libp2p_swarm::{dummy, SwarmEvent};
+//! # use std::time::Duration;
+//! let runtime = TestRuntime::new();
+//! let mut a = TestSwarm::with_config(
+//!     &runtime,
+//!     |_| dummy::Behaviour,
+//!     |config| config.with_idle_connection_timeout(Duration::from_secs(30)),
+//! );
+//! let mut b = TestSwarm::with_config(
+//!     &runtime,
+//!     |_| dummy::Behaviour,
+//!     |config| config.with_idle_connection_timeout(Duration::from_secs(60)),
+//! );
+//!
+//! connect(&mut a, &mut b);
+//! b.run_in_background();
+//!
+//! a.wait_for_event(
+//!     |event| matches!(event, SwarmEvent::ConnectionClosed { .. }).then_some(()),
+//!     Duration::from_secs(60),
+//! )
+//! .unwrap();
+//! assert_eq!(runtime.clock().elapsed(), Duration::from_secs(30));
+//! ```
+
+use crate::clock::{Clock, Timer};
+use crate::dial_opts::{DialOpts, PeerCondition};
+use crate::{Config, NetworkBehaviour, Swarm, SwarmEvent};
+use futures::future::LocalBoxFuture;
+use futures::task::ArcWake;
+use futures::{FutureExt, StreamExt};
+use instant::Instant;
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::transport::MemoryTransport;
+use libp2p_core::upgrade::Version;
+use libp2p_core::{Multiaddr, Transport};
+use libp2p_identity::{Keypair, PeerId};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// How often the swarms and tasks are polled at most while waiting for them to become idle,
+/// guarding against busy loops.
+const MAX_ROUNDS: usize = 10_000;
+
+/// A [`Clock`] that only advances when told to.
+///
+/// Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    state: Arc<Mutex<ClockState>>,
+}
+
+#[derive(Debug)]
+struct ClockState {
+    start: Instant,
+    elapsed: Duration,
+    /// The wakers of the pending timers, by deadline and a unique ID.
+    timers: BTreeMap<(Duration, u64), Option<Waker>>,
+    next_timer_id: u64,
+}
+
+impl VirtualClock {
+    /// Creates a new [`VirtualClock`], starting at the current point in time.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ClockState {
+                start: Instant::now(),
+                elapsed: Duration::ZERO,
+                timers: BTreeMap::new(),
+                next_timer_id: 0,
+            })),
+        }
+    }
+
+    /// Returns the time passed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().unwrap().elapsed
+    }
+
+    /// Advances the clock by the given duration, waking the timers firing in the meantime.
+    pub fn advance(&self, duration: Duration) {
+        let elapsed = self.elapsed();
+        self.advance_to(elapsed.saturating_add(duration));
+    }
+
+    fn advance_to(&self, elapsed: Duration) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.elapsed = state.elapsed.max(elapsed);
+            let elapsed = state.elapsed;
+            state
+                .timers
+                .range_mut(..=(elapsed, u64::MAX))
+                .filter_map(|(_, waker)| waker.take())
+                .collect::<Vec<_>>()
+        };
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns the deadline of the next timer that did not fire yet.
+    fn next_deadline(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        state
+            .timers
+            .keys()
+            .map(|(deadline, _)| *deadline)
+            .find(|deadline| *deadline > state.elapsed)
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        let state = self.state.lock().unwrap();
+        state.start + state.elapsed
+    }
+
+    fn timer(&self, duration: Duration) -> Timer {
+        let key = {
+            let mut state = self.state.lock().unwrap();
+            let key = (state.elapsed.saturating_add(duration), state.next_timer_id);
+            state.next_timer_id += 1;
+            state.timers.insert(key, None);
+            key
+        };
+
+        Timer::new(VirtualTimer {
+            state: self.state.clone(),
+            key,
+        })
+    }
+}
+
+/// A timer of a [`VirtualClock`], registered with the clock until it fired or is dropped.
+struct VirtualTimer {
+    state: Arc<Mutex<ClockState>>,
+    key: (Duration, u64),
+}
+
+impl Future for VirtualTimer {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if state.elapsed >= self.key.0 {
+            state.timers.remove(&self.key);
+            return Poll::Ready(());
+        }
+        if let Some(waker) = state.timers.get_mut(&self.key) {
+            *waker = Some(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for VirtualTimer {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.timers.remove(&self.key);
+        }
+    }
+}
+
+/// Drives [`TestSwarm`]s and background tasks, with all timers of the swarms running on a shared
+/// [`VirtualClock`].
+///
+/// Clones share the same clock and tasks.
+#[derive(Clone, Default)]
+pub struct TestRuntime {
+    clock: VirtualClock,
+    tasks: Rc<RefCell<Vec<LocalBoxFuture<'static, ()>>>>,
+    woken: Arc<WokenFlag>,
+}
+
+impl TestRuntime {
+    /// Creates a new [`TestRuntime`] with a fresh [`VirtualClock`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the [`VirtualClock`] of the runtime, e.g. to configure the timers of a
+    /// [`NetworkBehaviour`] to use it.
+    pub fn clock(&self) -> VirtualClock {
+        self.clock.clone()
+    }
+
+    /// Runs the given future in the background, polling it whenever the runtime drives a
+    /// [`TestSwarm`].
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        self.tasks.borrow_mut().push(future.boxed_local());
+    }
+
+    /// Polls the given swarms and all background tasks until none of them makes progress.
+    fn run_until_idle(&self, swarms: &mut [&mut dyn Drive]) {
+        let waker = futures::task::waker(self.woken.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        for _ in 0..MAX_ROUNDS {
+            self.woken.0.store(false, Ordering::SeqCst);
+
+            let mut progress = false;
+            for swarm in swarms.iter_mut() {
+                progress |= swarm.poll_events(&mut cx);
+            }
+            progress |= self.poll_tasks(&mut cx);
+
+            if !progress && !self.woken.0.load(Ordering::SeqCst) {
+                return;
+            }
+        }
+
+        panic!("Swarms did not become idle within {MAX_ROUNDS} rounds");
+    }
+
+    /// Polls all background tasks once, returning whether any of them completed.
+    fn poll_tasks(&self, cx: &mut Context<'_>) -> bool {
+        // Tasks may spawn further tasks while being polled.
+        let mut tasks = std::mem::take(&mut *self.tasks.borrow_mut());
+        let num_tasks = tasks.len();
+        tasks.retain_mut(|task| task.poll_unpin(cx).is_pending());
+        let completed = tasks.len() < num_tasks;
+
+        let mut spawned = self.tasks.borrow_mut();
+        tasks.append(&mut spawned);
+        *spawned = tasks;
+
+        completed
+    }
+}
+
+impl fmt::Debug for TestRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestRuntime")
+            .field("clock", &self.clock)
+            .field("tasks", &self.tasks.borrow().len())
+            .finish()
+    }
+}
+
+/// Records whether any of the polled futures was woken, i.e. may be able to make progress.
+#[derive(Debug, Default)]
+struct WokenFlag(AtomicBool);
+
+impl ArcWake for WokenFlag {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A [`Swarm`] on the memory transport, driven by a [`TestRuntime`].
+///
+/// Dereferences to the underlying [`Swarm`]. Events are buffered until consumed via
+/// [`TestSwarm::wait_for_event`] or [`TestSwarm::drive_until_idle`].
+pub struct TestSwarm<B: NetworkBehaviour> {
+    swarm: Swarm<B>,
+    runtime: TestRuntime,
+    events: VecDeque<SwarmEvent<B::ToSwarm>>,
+}
+
+impl<B> TestSwarm<B>
+where
+    B: NetworkBehaviour,
+    B::ToSwarm: fmt::Debug,
+{
+    /// Creates a new [`TestSwarm`] with a random identity.
+    ///
+    /// Like in `libp2p-swarm-test`, connections are kept alive for 5 seconds once idle.
+    pub fn new(runtime: &TestRuntime, behaviour_fn: impl FnOnce(Keypair) -> B) -> Self {
+        Self::with_config(runtime, behaviour_fn, |config| config)
+    }
+
+    /// Creates a new [`TestSwarm`] with a random identity and the [`Config`] returned by
+    /// `config_fn`.
+    pub fn with_config(
+        runtime: &TestRuntime,
+        behaviour_fn: impl FnOnce(Keypair) -> B,
+        config_fn: impl FnOnce(Config) -> Config,
+    ) -> Self {
+        let identity = Keypair::generate_ed25519();
+        let peer_id = PeerId::from(identity.public());
+
+        let transport = MemoryTransport::default()
+            .upgrade(Version::V1)
+            .authenticate(libp2p_plaintext::Config::new(&identity))
+            .multiplex(libp2p_yamux::Config::default())
+            .boxed();
+        let config = config_fn(
+            Config::without_executor()
+                .with_idle_connection_timeout(Duration::from_secs(5))
+                .with_clock(runtime.clock()),
+        );
+
+        Self {
+            swarm: Swarm::new(transport, behaviour_fn(identity), peer_id, config),
+            runtime: runtime.clone(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Listens on a new memory address, returning it once the [`Swarm`] reported it.
+    pub fn listen_on_memory(&mut self) -> Multiaddr {
+        let listener_id = self
+            .swarm
+            .listen_on(Protocol::Memory(0).into())
+            .expect("memory transport to support listening");
+        self.runtime
+            .clone()
+            .run_until_idle(&mut [&mut *self as &mut dyn Drive]);
+
+        let position = self
+            .events
+            .iter()
+            .position(|event| {
+                matches!(event, SwarmEvent::NewListenAddr { listener_id: id, .. } if *id == listener_id)
+            })
+            .expect("memory listener to report its address");
+        match self.events.remove(position) {
+            Some(SwarmEvent::NewListenAddr { address, .. }) => address,
+            _ => unreachable!("found above"),
+        }
+    }
+
+    /// Drives the swarm, along with the background tasks of the runtime, until it is idle, i.e.
+    /// waiting for time to pass, returning all events emitted so far.
+    pub fn drive_until_idle(&mut self) -> Vec<SwarmEvent<B::ToSwarm>> {
+        self.runtime
+            .clone()
+            .run_until_idle(&mut [&mut *self as &mut dyn Drive]);
+
+        self.events.drain(..).collect()
+    }
+
+    /// Drives the swarm until `matcher` returns `Some` for one of its events, discarding the
+    /// events before it.
+    ///
+    /// Whenever the swarm and the background tasks are idle, the [`VirtualClock`] is advanced to
+    /// the next timer. Fails once the clock advanced by `timeout` without a matching event.
+    pub fn wait_for_event<T>(
+        &mut self,
+        mut matcher: impl FnMut(SwarmEvent<B::ToSwarm>) -> Option<T>,
+        timeout: Duration,
+    ) -> Result<T, Elapsed> {
+        let runtime = self.runtime.clone();
+        let deadline = runtime.clock.elapsed().saturating_add(timeout);
+
+        loop {
+            while let Some(event) = self.events.pop_front() {
+                if let Some(value) = matcher(event) {
+                    return Ok(value);
+                }
+            }
+
+            runtime.run_until_idle(&mut [&mut *self as &mut dyn Drive]);
+            if !self.events.is_empty() {
+                continue;
+            }
+
+            match runtime.clock.next_deadline() {
+                Some(next) if next <= deadline => runtime.clock.advance_to(next),
+                _ => {
+                    runtime.clock.advance_to(deadline);
+                    return Err(Elapsed(timeout));
+                }
+            }
+        }
+    }
+
+    /// Moves the swarm into the background, driving it along with any other [`TestSwarm`] of the
+    /// runtime and discarding its events.
+    pub fn run_in_background(self)
+    where
+        B: 'static,
+    {
+        let Self {
+            mut swarm, runtime, ..
+        } = self;
+
+        runtime.spawn(async move {
+            loop {
+                let event = swarm.select_next_some().await;
+                tracing::debug!(peer=%swarm.local_peer_id(), ?event, "Background swarm event");
+            }
+        });
+    }
+}
+
+impl<B: NetworkBehaviour> Deref for TestSwarm<B> {
+    type Target = Swarm<B>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.swarm
+    }
+}
+
+impl<B: NetworkBehaviour> DerefMut for TestSwarm<B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.swarm
+    }
+}
+
+impl<B: NetworkBehaviour> fmt::Debug for TestSwarm<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestSwarm")
+            .field("peer_id", self.swarm.local_peer_id())
+            .field("buffered_events", &self.events.len())
+            .finish()
+    }
+}
+
+/// A [`TestSwarm`] whose events can be collected while driving several swarms at once.
+trait Drive {
+    /// Polls the swarm until it is pending, buffering its events. Returns whether it emitted any.
+    fn poll_events(&mut self, cx: &mut Context<'_>) -> bool;
+}
+
+impl<B> Drive for TestSwarm<B>
+where
+    B: NetworkBehaviour,
+    B::ToSwarm: fmt::Debug,
+{
+    fn poll_events(&mut self, cx: &mut Context<'_>) -> bool {
+        let mut progress = false;
+        while let Poll::Ready(Some(event)) = self.swarm.poll_next_unpin(cx) {
+            tracing::debug!(peer=%self.swarm.local_peer_id(), ?event, "Swarm event");
+            self.events.push_back(event);
+            progress = true;
+        }
+
+        progress
+    }
+}
+
+/// Connects `a` to `b` via a new memory address of `b`, returning once both reported the
+/// connection as established.
+///
+/// Events emitted while connecting are discarded.
+pub fn connect<A, B>(a: &mut TestSwarm<A>, b: &mut TestSwarm<B>)
+where
+    A: NetworkBehaviour,
+    A::ToSwarm: fmt::Debug,
+    B: NetworkBehaviour,
+    B::ToSwarm: fmt::Debug,
+{
+    let address = b.listen_on_memory();
+    let a_id = *a.local_peer_id();
+    let b_id = *b.local_peer_id();
+    a.dial(
+        DialOpts::peer_id(b_id)
+            .addresses(vec![address])
+            .condition(PeerCondition::Always)
+            .build(),
+    )
+    .expect("dial to start");
+
+    a.runtime
+        .clone()
+        .run_until_idle(&mut [&mut *a as &mut dyn Drive, &mut *b]);
+
+    let a_connected = a.events.drain(..).any(
+        |event| matches!(event, SwarmEvent::ConnectionEstablished { peer_id, .. } if peer_id == b_id),
+    );
+    let b_connected = b.events.drain(..).any(
+        |event| matches!(event, SwarmEvent::ConnectionEstablished { peer_id, .. } if peer_id == a_id),
+    );
+    assert!(a_connected && b_connected, "Swarms failed to connect");
+}
+
+/// The virtual time passed without a matching event, see [`TestSwarm::wait_for_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed(Duration);
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No matching event within {:?}", self.0)
+    }
+}
+
+impl std::error::Error for Elapsed {}
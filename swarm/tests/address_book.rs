@@ -0,0 +1,24 @@
+use libp2p_swarm::behaviour::address_book::AddressBook;
+use libp2p_swarm::dial_opts::DialOpts;
+use libp2p_swarm::{dummy, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+
+#[async_std::test]
+async fn dial_by_peer_id_uses_stored_addresses() {
+    let mut listener = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let (listen_addr, _) = listener.listen().await;
+    let listener_id = *listener.local_peer_id();
+    async_std::task::spawn(listener.loop_on_next());
+
+    let mut dialer =
+        Swarm::new_ephemeral(|_| AddressBook::from_iter([(listener_id, vec![listen_addr])]));
+    dialer.dial(DialOpts::peer_id(listener_id).build()).unwrap();
+
+    let connected = dialer
+        .wait(|e| match e {
+            SwarmEvent::ConnectionEstablished { peer_id, .. } => Some(peer_id),
+            _ => None,
+        })
+        .await;
+    assert_eq!(connected, listener_id);
+}
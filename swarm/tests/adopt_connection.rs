@@ -0,0 +1,128 @@
+use futures::io::Cursor;
+use futures::{future, StreamExt};
+use libp2p_core::transport::{dummy::DummyTransport, ListenerId, MemoryTransport};
+use libp2p_core::{ConnectedPoint, Endpoint, Transport};
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_plaintext as plaintext;
+use libp2p_swarm::{dummy, Config, ConnectionId, ListenError, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use libp2p_yamux as yamux;
+
+#[async_std::test]
+async fn adopted_stream_is_upgraded_and_established_as_inbound_connection() {
+    let mut keypairs = Vec::new();
+    let mut new_swarm = || {
+        Swarm::new_ephemeral(|keypair| {
+            keypairs.push(keypair);
+            dummy::Behaviour
+        })
+    };
+    let mut dialer = new_swarm();
+    let mut listener = new_swarm();
+    let (dialer_keypair, listener_keypair) = (keypairs.remove(0), keypairs.remove(0));
+
+    let mut transport = MemoryTransport::default().boxed();
+    transport
+        .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
+        .unwrap();
+    let addr = transport
+        .select_next_some()
+        .await
+        .into_new_address()
+        .unwrap();
+    // The memory transport only reports the incoming connection once the dial is polled.
+    let dial = transport.dial(addr.clone()).unwrap();
+    let (dialer_stream, incoming) = future::join(dial, transport.select_next_some()).await;
+    let (accept, send_back_addr) = incoming.into_incoming().unwrap();
+    let (listener_stream, dialer_stream) = (accept.await.unwrap(), dialer_stream.unwrap());
+
+    let dialer_connection = dialer.adopt_connection(
+        dialer_stream,
+        Endpoint::Dialer,
+        plaintext::Config::new(&dialer_keypair),
+        yamux::Config::default(),
+        send_back_addr.clone(),
+        addr.clone(),
+    );
+    let listener_connection = listener.adopt_connection(
+        listener_stream,
+        Endpoint::Listener,
+        plaintext::Config::new(&listener_keypair),
+        yamux::Config::default(),
+        addr,
+        send_back_addr,
+    );
+
+    let (
+        (dialer_remote, dialer_connection_id, dialer_endpoint),
+        (listener_remote, listener_connection_id, listener_endpoint),
+    ) = future::join(
+        wait_established(&mut dialer),
+        wait_established(&mut listener),
+    )
+    .await;
+
+    assert_eq!(dialer_remote, *listener.local_peer_id());
+    assert_eq!(dialer_connection_id, dialer_connection);
+    assert!(dialer_endpoint.is_listener());
+    assert_eq!(listener_remote, *dialer.local_peer_id());
+    assert_eq!(listener_connection_id, listener_connection);
+    assert!(listener_endpoint.is_listener());
+}
+
+#[async_std::test]
+async fn adopted_connections_count_towards_inbound_rate_limit() {
+    let keypair = Keypair::generate_ed25519();
+    let mut swarm = Swarm::new(
+        DummyTransport::new().boxed(),
+        dummy::Behaviour,
+        keypair.public().to_peer_id(),
+        Config::with_executor(|f| {
+            async_std::task::spawn(f);
+        })
+        .with_inbound_connection_rate_limit(1),
+    );
+    let local_addr: libp2p_core::Multiaddr = "/memory/1".parse().unwrap();
+    let send_back_addr: libp2p_core::Multiaddr = "/memory/2".parse().unwrap();
+
+    let mut adopt = || {
+        swarm.adopt_connection(
+            Cursor::new(Vec::new()),
+            Endpoint::Listener,
+            plaintext::Config::new(&keypair),
+            yamux::Config::default(),
+            local_addr.clone(),
+            send_back_addr.clone(),
+        )
+    };
+    adopt();
+    let refused = adopt();
+
+    let connection_id = swarm
+        .wait(|e| match e {
+            SwarmEvent::IncomingConnectionError {
+                connection_id,
+                error: ListenError::RateLimited,
+                ..
+            } => Some(connection_id),
+            _ => None,
+        })
+        .await;
+    assert_eq!(connection_id, refused);
+}
+
+async fn wait_established(
+    swarm: &mut Swarm<dummy::Behaviour>,
+) -> (PeerId, ConnectionId, ConnectedPoint) {
+    swarm
+        .wait(|e| match e {
+            SwarmEvent::ConnectionEstablished {
+                peer_id,
+                connection_id,
+                endpoint,
+                ..
+            } => Some((peer_id, connection_id, endpoint)),
+            _ => None,
+        })
+        .await
+}
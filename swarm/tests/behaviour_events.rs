@@ -0,0 +1,29 @@
+use futures::StreamExt;
+use libp2p_core::multiaddr::Protocol;
+use libp2p_ping as ping;
+use libp2p_swarm::Swarm;
+use libp2p_swarm_test::SwarmExt;
+use std::time::Duration;
+
+#[async_std::test]
+async fn behaviour_events_only_yields_behaviour_events() {
+    let cfg = ping::Config::new().with_interval(Duration::from_millis(10));
+    let mut listener = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+    let mut dialer = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+
+    listener.listen().with_memory_addr_external().await;
+    dialer.connect(&mut listener).await;
+    let listener_peer_id = *listener.local_peer_id();
+    async_std::task::spawn(listener.loop_on_next());
+
+    // The listen address is reported while the stream below is polled, yet not yielded by it.
+    dialer.listen_on(Protocol::Memory(0).into()).unwrap();
+    let events = dialer
+        .behaviour_events()
+        .take(3)
+        .collect::<Vec<ping::Event>>()
+        .await;
+
+    assert!(events.iter().all(|e| e.peer == listener_peer_id));
+    assert_eq!(dialer.listeners().count(), 1);
+}
@@ -0,0 +1,256 @@
+use futures::future::BoxFuture;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
+use libp2p_core::transport::MemoryTransport;
+use libp2p_core::upgrade::{ReadyUpgrade, Version};
+use libp2p_core::{multiaddr::Protocol, Endpoint, Multiaddr, Transport};
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_swarm::handler::{ConnectionEvent, FullyNegotiatedInbound, FullyNegotiatedOutbound};
+use libp2p_swarm::{
+    Config, ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, StreamProtocol, SubstreamProtocol, Swarm, SwarmEvent, THandler,
+    THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::io;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use void::Void;
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/payload");
+const PAYLOAD: usize = 100_000;
+
+#[async_std::test]
+async fn counters_match_transferred_payload() {
+    let mut dialer = new_swarm(true, true);
+    let mut listener = new_swarm(false, true);
+
+    listen_on_memory(&mut listener).await;
+    dialer.connect(&mut listener).await;
+
+    let ([(dialer_connection, sent)], [(listener_connection, received)]): (
+        [(ConnectionId, usize); 1],
+        [(ConnectionId, usize); 1],
+    ) = libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+    assert_eq!(sent, PAYLOAD);
+    assert_eq!(received, PAYLOAD);
+
+    let dialer_counters = dialer.connection_byte_counters(dialer_connection).unwrap();
+    let listener_counters = listener
+        .connection_byte_counters(listener_connection)
+        .unwrap();
+
+    // Neither protocol negotiation nor framing are counted, only the payload.
+    assert_eq!(dialer_counters.bytes_out(), PAYLOAD as u64);
+    assert_eq!(dialer_counters.bytes_in(), 0);
+    assert_eq!(listener_counters.bytes_in(), PAYLOAD as u64);
+    assert_eq!(listener_counters.bytes_out(), 0);
+}
+
+#[async_std::test]
+async fn counters_are_unavailable_unless_enabled() {
+    let mut dialer = new_swarm(true, false);
+    let mut listener = new_swarm(false, false);
+
+    listen_on_memory(&mut listener).await;
+    dialer.connect(&mut listener).await;
+
+    let ([(connection_id, _)], [_]): ([(ConnectionId, usize); 1], [(ConnectionId, usize); 1]) =
+        libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+
+    assert!(dialer.connection_byte_counters(connection_id).is_none());
+}
+
+fn new_swarm(send_payload: bool, count_bytes: bool) -> Swarm<Behaviour> {
+    let identity = Keypair::generate_ed25519();
+    let peer_id = identity.public().to_peer_id();
+
+    let transport = MemoryTransport::default()
+        .upgrade(Version::V1)
+        .authenticate(libp2p_plaintext::Config::new(&identity))
+        .multiplex(libp2p_yamux::Config::default())
+        .boxed();
+
+    Swarm::new(
+        transport,
+        Behaviour::new(send_payload),
+        peer_id,
+        Config::with_executor(|f| {
+            async_std::task::spawn(f);
+        })
+        .with_connection_counters(count_bytes)
+        .with_idle_connection_timeout(Duration::from_secs(5)),
+    )
+}
+
+/// Listens on a new memory address and adds it as external address.
+async fn listen_on_memory(swarm: &mut Swarm<Behaviour>) {
+    swarm.listen_on(Protocol::Memory(0).into()).unwrap();
+    let address = swarm
+        .wait(|event| match event {
+            SwarmEvent::NewListenAddr { address, .. } => Some(address),
+            _ => None,
+        })
+        .await;
+    swarm.add_external_address(address);
+}
+
+/// Sends [`PAYLOAD`] bytes over a single stream on each outbound connection, if enabled, and
+/// reports the number of bytes written or read for each stream.
+struct Behaviour {
+    send_payload: bool,
+    events: VecDeque<(ConnectionId, usize)>,
+}
+
+impl Behaviour {
+    fn new(send_payload: bool) -> Self {
+        Self {
+            send_payload,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = (ConnectionId, usize);
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(false))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.send_payload))
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.events.push_back((connection_id, event));
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(ToSwarm::GenerateEvent(event));
+        }
+
+        Poll::Pending
+    }
+}
+
+struct Handler {
+    send_payload: bool,
+    transfer: Option<BoxFuture<'static, io::Result<usize>>>,
+}
+
+impl Handler {
+    fn new(send_payload: bool) -> Self {
+        Self {
+            send_payload,
+            transfer: None,
+        }
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = Void;
+    type ToBehaviour = usize;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if std::mem::take(&mut self.send_payload) {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ()),
+            });
+        }
+
+        if let Some(transfer) = self.transfer.as_mut() {
+            if let Poll::Ready(result) = transfer.poll_unpin(cx) {
+                self.transfer = None;
+
+                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(result.unwrap()));
+            }
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol: mut stream,
+                ..
+            }) => {
+                self.transfer = Some(
+                    async move {
+                        let mut payload = Vec::new();
+                        stream.read_to_end(&mut payload).await?;
+
+                        Ok(payload.len())
+                    }
+                    .boxed(),
+                );
+            }
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol: mut stream,
+                ..
+            }) => {
+                self.transfer = Some(
+                    async move {
+                        stream.write_all(&vec![0; PAYLOAD]).await?;
+                        stream.close().await?;
+
+                        Ok(PAYLOAD)
+                    }
+                    .boxed(),
+                );
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::{
+    dial_opts::DialOpts, dummy, ConnectionDenied, ConnectionId, ConnectionPayload, DialError,
+    FromSwarm, NetworkBehaviour, Swarm, SwarmEvent, THandler, THandlerInEvent, THandlerOutEvent,
+    ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+#[derive(NetworkBehaviour)]
+#[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+struct Behaviour {
+    first: Payload,
+    dummy: dummy::Behaviour,
+    second: Payload,
+}
+
+fn new_swarm() -> Swarm<Behaviour> {
+    Swarm::new_ephemeral(|_| Behaviour {
+        first: Payload::new("first"),
+        dummy: dummy::Behaviour,
+        second: Payload::new("second"),
+    })
+}
+
+#[async_std::test]
+async fn payloads_of_pending_connections_arrive_in_established_callbacks() {
+    let mut dialer = new_swarm();
+    let mut listener = new_swarm();
+    listener.listen().with_memory_addr_external().await;
+
+    dialer.connect(&mut listener).await;
+
+    for swarm in [&dialer, &listener] {
+        assert_eq!(swarm.behaviour().first.received, vec!["first"]);
+        assert_eq!(swarm.behaviour().second.received, vec!["second"]);
+    }
+}
+
+#[async_std::test]
+async fn payloads_are_dropped_when_the_handshake_fails() {
+    let mut dialer = new_swarm();
+    let mut listener = new_swarm();
+    let (addr, _) = listener.listen().with_memory_addr_external().await;
+    async_std::task::spawn(listener.loop_on_next());
+
+    dialer
+        .dial(
+            DialOpts::peer_id(PeerId::random())
+                .addresses(vec![addr])
+                .build(),
+        )
+        .unwrap();
+    let error = dialer
+        .wait(|e| match e {
+            SwarmEvent::OutgoingConnectionError { error, .. } => Some(error),
+            _ => None,
+        })
+        .await;
+
+    assert!(matches!(error, DialError::WrongPeerId { .. }));
+    let behaviour = dialer.behaviour();
+    assert!(behaviour.first.received.is_empty());
+    assert_eq!(Arc::strong_count(&behaviour.first.alive), 1);
+    assert_eq!(Arc::strong_count(&behaviour.second.alive), 1);
+}
+
+/// Returns a payload naming the behaviour in the pending connection callbacks and records the
+/// payloads handed back once connections are established.
+struct Payload {
+    name: &'static str,
+    /// Cloned into every payload, tracking whether payloads are still alive.
+    alive: Arc<()>,
+    received: Vec<&'static str>,
+}
+
+struct Tag {
+    name: &'static str,
+    connection_id: ConnectionId,
+    _alive: Arc<()>,
+}
+
+impl Payload {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            alive: Arc::new(()),
+            received: Vec::new(),
+        }
+    }
+
+    fn payload(&self, connection_id: ConnectionId) -> ConnectionPayload {
+        Box::new(Tag {
+            name: self.name,
+            connection_id,
+            _alive: self.alive.clone(),
+        })
+    }
+
+    fn receive(&mut self, connection_id: ConnectionId, payload: Option<ConnectionPayload>) {
+        let tag = payload.unwrap().downcast::<Tag>().unwrap();
+        assert_eq!(tag.connection_id, connection_id);
+        self.received.push(tag.name);
+    }
+}
+
+impl NetworkBehaviour for Payload {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = ();
+
+    fn handle_pending_inbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<Option<ConnectionPayload>, ConnectionDenied> {
+        Ok(Some(self.payload(connection_id)))
+    }
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        unreachable!("The swarm calls the callback with payload")
+    }
+
+    fn handle_established_inbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+        payload: Option<ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.receive(connection_id, payload);
+
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_pending_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        _: Option<PeerId>,
+        _: &[Multiaddr],
+        _: Endpoint,
+    ) -> Result<(Vec<Multiaddr>, Option<ConnectionPayload>), ConnectionDenied> {
+        Ok((vec![], Some(self.payload(connection_id))))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        unreachable!("The swarm calls the callback with payload")
+    }
+
+    fn handle_established_outbound_connection_with_payload(
+        &mut self,
+        connection_id: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+        payload: Option<ConnectionPayload>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.receive(connection_id, payload);
+
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<(), THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
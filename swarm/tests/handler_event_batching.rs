@@ -0,0 +1,152 @@
+use libp2p_core::upgrade::DeniedUpgrade;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::handler::ConnectionEvent;
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, SubstreamProtocol, Swarm, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+use void::Void;
+
+/// Well above the size of a single batch.
+const NUM_EVENTS: u32 = 1000;
+
+#[async_std::test]
+async fn events_keep_their_order_across_batches() {
+    let mut dialer = Swarm::new_ephemeral(|_| Echo::default());
+    let mut listener = Swarm::new_ephemeral(|_| Echo::default());
+
+    listener.listen().with_memory_addr_external().await;
+    dialer.connect(&mut listener).await;
+    async_std::task::spawn(listener.loop_on_next());
+
+    dialer.behaviour_mut().to_send = (0..NUM_EVENTS).collect();
+
+    let mut echoed = Vec::new();
+    while echoed.len() < NUM_EVENTS as usize {
+        echoed.push(dialer.next_behaviour_event().await);
+    }
+
+    assert_eq!(echoed, (0..NUM_EVENTS).collect::<Vec<_>>());
+}
+
+/// Sends the numbers in `to_send` to the handler of the first established connection in one burst
+/// and reports each number the handler sends back.
+#[derive(Default)]
+struct Echo {
+    to_send: VecDeque<u32>,
+    connection: Option<(PeerId, ConnectionId)>,
+    echoed: VecDeque<u32>,
+}
+
+impl NetworkBehaviour for Echo {
+    type ConnectionHandler = EchoHandler;
+    type ToSwarm = u32;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(EchoHandler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(EchoHandler::default())
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ConnectionEstablished(e) = event {
+            self.connection.get_or_insert((e.peer_id, e.connection_id));
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.echoed.push_back(event);
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(n) = self.echoed.pop_front() {
+            return Poll::Ready(ToSwarm::GenerateEvent(n));
+        }
+
+        if let Some((peer_id, connection)) = self.connection {
+            if let Some(n) = self.to_send.pop_front() {
+                return Poll::Ready(ToSwarm::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::One(connection),
+                    event: n,
+                });
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+#[derive(Default)]
+struct EchoHandler {
+    received: VecDeque<u32>,
+}
+
+impl ConnectionHandler for EchoHandler {
+    type FromBehaviour = u32;
+    type ToBehaviour = u32;
+    type InboundProtocol = DeniedUpgrade;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = Void;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(DeniedUpgrade, ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if let Some(n) = self.received.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(n));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        self.received.push_back(event);
+    }
+
+    fn on_connection_event(
+        &mut self,
+        _: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+    }
+}
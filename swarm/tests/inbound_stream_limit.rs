@@ -0,0 +1,202 @@
+use futures::AsyncRead;
+use libp2p_core::upgrade::ReadyUpgrade;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::handler::{ConnectionEvent, FullyNegotiatedInbound, FullyNegotiatedOutbound};
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, Stream, StreamProtocol, SubstreamProtocol, Swarm, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use void::Void;
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/limited");
+const MAX_INBOUND_STREAMS: usize = 10;
+
+#[async_std::test]
+async fn inbound_streams_beyond_protocol_limit_are_reset() {
+    let mut dialer = Swarm::new_ephemeral(|_| Behaviour::new(MAX_INBOUND_STREAMS + 1));
+    let mut listener = Swarm::new_ephemeral(|_| Behaviour::new(0));
+
+    listener.listen().with_memory_addr_external().await;
+    dialer.connect(&mut listener).await;
+
+    let (dialer_events, listener_events): ([Event; 1], [Event; MAX_INBOUND_STREAMS]) =
+        libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+
+    assert_eq!(dialer_events, [Event::Reset]);
+    assert!(listener_events.iter().all(|e| *e == Event::Accepted));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    /// An inbound stream was accepted.
+    Accepted,
+    /// An outbound stream was reset by the remote.
+    Reset,
+}
+
+/// Opens the given number of streams on each outbound connection and holds all streams open.
+struct Behaviour {
+    streams_to_open: usize,
+    events: VecDeque<Event>,
+}
+
+impl Behaviour {
+    fn new(streams_to_open: usize) -> Self {
+        Self {
+            streams_to_open,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(0))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.streams_to_open))
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.events.push_back(event);
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(ToSwarm::GenerateEvent(event));
+        }
+
+        Poll::Pending
+    }
+}
+
+struct Handler {
+    streams_to_open: usize,
+    streams: Vec<Stream>,
+    events: VecDeque<Event>,
+}
+
+impl Handler {
+    fn new(streams_to_open: usize) -> Self {
+        Self {
+            streams_to_open,
+            streams: Vec::new(),
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = Void;
+    type ToBehaviour = Event;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ())
+            .with_max_inbound_streams(PROTOCOL, MAX_INBOUND_STREAMS)
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event));
+        }
+
+        if self.streams_to_open > 0 {
+            self.streams_to_open -= 1;
+
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ()),
+            });
+        }
+
+        // Neither side ever writes, thus a read only completes once the stream is reset.
+        let mut buf = [0; 1];
+        if let Some(i) = self
+            .streams
+            .iter_mut()
+            .position(|stream| Pin::new(stream).poll_read(cx, &mut buf).is_ready())
+        {
+            self.streams.swap_remove(i);
+
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Event::Reset));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol: stream,
+                ..
+            }) => {
+                self.streams.push(stream);
+                self.events.push_back(Event::Accepted);
+            }
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol: stream,
+                ..
+            }) => {
+                self.streams.push(stream);
+            }
+            // The remote may reset the stream before confirming the protocol.
+            ConnectionEvent::DialUpgradeError(_) => {
+                self.events.push_back(Event::Reset);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_ping as ping;
+use libp2p_swarm::handler::logging::LoggingHandler;
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, Swarm, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+#[async_std::test]
+async fn logs_stream_events_of_wrapped_handler() {
+    let logs = Logs::default();
+    let writer = logs.clone();
+    tracing_subscriber::fmt()
+        .with_env_filter("libp2p_swarm::handler::logging=debug")
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .init();
+
+    let mut dialer = Swarm::new_ephemeral(|_| Behaviour(ping::Behaviour::default()));
+    let mut listener = Swarm::new_ephemeral(|_| Behaviour(ping::Behaviour::default()));
+
+    listener.listen().with_memory_addr_external().await;
+    dialer.connect(&mut listener).await;
+
+    let ([dialer_ping], [listener_ping]): ([ping::Event; 1], [ping::Event; 1]) =
+        libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+    assert!(dialer_ping.result.is_ok());
+    assert!(listener_ping.result.is_ok());
+
+    let logs = logs.contents();
+    assert!(logs.contains("Requesting outbound stream"), "{logs}");
+    assert!(logs.contains(ping::PROTOCOL_NAME.as_ref()), "{logs}");
+    assert!(logs.contains("Negotiated outbound stream"), "{logs}");
+    assert!(logs.contains("Negotiated inbound stream"), "{logs}");
+}
+
+/// Wraps the handlers of [`ping::Behaviour`] in a [`LoggingHandler`].
+struct Behaviour(ping::Behaviour);
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = LoggingHandler<THandler<ping::Behaviour>>;
+    type ToSwarm = ping::Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.0
+            .handle_established_inbound_connection(connection_id, peer, local_addr, remote_addr)
+            .map(LoggingHandler::new)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.0
+            .handle_established_outbound_connection(connection_id, peer, addr, role_override)
+            .map(LoggingHandler::new)
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        self.0.on_swarm_event(event)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.0
+            .on_connection_handler_event(peer_id, connection_id, event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        self.0.poll(cx)
+    }
+}
+
+/// Captures the output of the subscriber.
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Logs {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl io::Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use futures::future::{ready, Ready};
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::transport::MemoryTransport;
+use libp2p_core::upgrade::{ReadyUpgrade, Version};
+use libp2p_core::{Endpoint, Multiaddr, Transport, UpgradeInfo};
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_swarm::handler::{ConnectionEvent, FullyNegotiatedOutbound};
+use libp2p_swarm::{
+    Config, ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, Stream, StreamProtocol, SubstreamProtocol, Swarm, SwarmEvent,
+    THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use void::Void;
+
+const SUPPORTED: StreamProtocol = StreamProtocol::new("/supported");
+
+#[async_std::test]
+async fn inbound_negotiation_is_aborted_beyond_max_negotiated_protocols() {
+    let proposed = vec![
+        StreamProtocol::new("/unsupported/1"),
+        StreamProtocol::new("/unsupported/2"),
+        SUPPORTED,
+    ];
+
+    assert_eq!(negotiate(proposed.clone(), 1).await, None);
+    assert_eq!(negotiate(proposed, 2).await, Some(SUPPORTED));
+}
+
+/// Opens a stream proposing the given protocols to a listener that supports [`SUPPORTED`] and
+/// rejects at most `max_negotiated_protocols` of them, returning the negotiated protocol.
+async fn negotiate(
+    proposed: Vec<StreamProtocol>,
+    max_negotiated_protocols: usize,
+) -> Option<StreamProtocol> {
+    let mut dialer = Swarm::new_ephemeral(|_| Behaviour::new(proposed));
+    let mut listener = new_swarm(
+        Config::with_async_std_executor().with_max_negotiated_protocols(max_negotiated_protocols),
+    );
+
+    listen_on_memory(&mut listener).await;
+    dialer.connect(&mut listener).await;
+
+    dialer.behaviour_mut().open_stream = true;
+
+    let ([negotiated], []): ([Option<StreamProtocol>; 1], [Option<StreamProtocol>; 0]) =
+        libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+
+    negotiated
+}
+
+fn new_swarm(config: Config) -> Swarm<Behaviour> {
+    let identity = Keypair::generate_ed25519();
+    let peer_id = identity.public().to_peer_id();
+
+    let transport = MemoryTransport::default()
+        .upgrade(Version::V1)
+        .authenticate(libp2p_plaintext::Config::new(&identity))
+        .multiplex(libp2p_yamux::Config::default())
+        .boxed();
+
+    Swarm::new(
+        transport,
+        Behaviour::new(Vec::new()),
+        peer_id,
+        config.with_idle_connection_timeout(Duration::from_secs(5)),
+    )
+}
+
+/// Listens on a new memory address and adds it as external address.
+async fn listen_on_memory(swarm: &mut Swarm<Behaviour>) {
+    swarm.listen_on(Protocol::Memory(0).into()).unwrap();
+    let address = swarm
+        .wait(|event| match event {
+            SwarmEvent::NewListenAddr { address, .. } => Some(address),
+            _ => None,
+        })
+        .await;
+    swarm.add_external_address(address);
+}
+
+/// Supports [`SUPPORTED`] on inbound streams and, once `open_stream` is set, opens a stream
+/// proposing the given protocols on the first established connection.
+///
+/// Reports the protocol negotiated on the outbound stream, if any.
+struct Behaviour {
+    proposed: Vec<StreamProtocol>,
+    open_stream: bool,
+    connection: Option<(PeerId, ConnectionId)>,
+    events: VecDeque<ToSwarm<Option<StreamProtocol>, ()>>,
+}
+
+impl Behaviour {
+    fn new(proposed: Vec<StreamProtocol>) -> Self {
+        Self {
+            proposed,
+            open_stream: false,
+            connection: None,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Option<StreamProtocol>;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.proposed.clone()))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.proposed.clone()))
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ConnectionEstablished(e) = event {
+            self.connection.get_or_insert((e.peer_id, e.connection_id));
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.events.push_back(ToSwarm::GenerateEvent(event));
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(event);
+        }
+
+        if let Some((peer_id, connection)) = self.connection {
+            if std::mem::take(&mut self.open_stream) {
+                return Poll::Ready(ToSwarm::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::One(connection),
+                    event: (),
+                });
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+struct Handler {
+    proposed: Vec<StreamProtocol>,
+    open_stream: bool,
+    events: VecDeque<Option<StreamProtocol>>,
+}
+
+impl Handler {
+    fn new(proposed: Vec<StreamProtocol>) -> Self {
+        Self {
+            proposed,
+            open_stream: false,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = ();
+    type ToBehaviour = Option<StreamProtocol>;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ProposeUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(SUPPORTED), ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event));
+        }
+
+        if std::mem::take(&mut self.open_stream) {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ProposeUpgrade(self.proposed.clone()), ()),
+            });
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, _: Self::FromBehaviour) {
+        self.open_stream = true;
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol, ..
+            }) => self.events.push_back(Some(protocol)),
+            ConnectionEvent::DialUpgradeError(_) => self.events.push_back(None),
+            _ => {}
+        }
+    }
+}
+
+/// Proposes the given protocols in turn, resolving to the negotiated one.
+struct ProposeUpgrade(Vec<StreamProtocol>);
+
+impl UpgradeInfo for ProposeUpgrade {
+    type Info = StreamProtocol;
+    type InfoIter = std::vec::IntoIter<StreamProtocol>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        self.0.clone().into_iter()
+    }
+}
+
+impl libp2p_core::upgrade::OutboundUpgrade<Stream> for ProposeUpgrade {
+    type Output = StreamProtocol;
+    type Error = Void;
+    type Future = Ready<Result<Self::Output, Self::Error>>;
+
+    fn upgrade_outbound(self, _: Stream, info: Self::Info) -> Self::Future {
+        ready(Ok(info))
+    }
+}
@@ -0,0 +1,182 @@
+use libp2p_core::upgrade::ReadyUpgrade;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::handler::ConnectionEvent;
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, StreamProtocol, SubstreamProtocol, Swarm, THandler,
+    THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+
+#[async_std::test]
+async fn reports_connection_without_common_protocol() {
+    let mut dialer = Swarm::new_ephemeral(|_| Behaviour::new(StreamProtocol::new("/a/1.0.0")));
+    let mut listener = Swarm::new_ephemeral(|_| Behaviour::new(StreamProtocol::new("/b/1.0.0")));
+
+    listener.listen().with_memory_addr_external().await;
+    dialer.connect(&mut listener).await;
+
+    dialer.behaviour_mut().open_stream = true;
+
+    let ([dialer_tried], [listener_tried]): ([Vec<String>; 1], [Vec<String>; 1]) =
+        libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+
+    assert_eq!(dialer_tried, vec!["/a/1.0.0".to_owned()]);
+    assert_eq!(listener_tried, vec!["/b/1.0.0".to_owned()]);
+}
+
+/// Supports a single protocol and, once `open_stream` is set, opens a stream with it on the first
+/// established connection. The connection is no longer kept alive once the stream failed to
+/// negotiate, giving the remote time to fail its side of the stream before the connection closes.
+///
+/// Reports the protocols tried on connections that failed to negotiate any protocol.
+struct Behaviour {
+    protocol: StreamProtocol,
+    open_stream: bool,
+    connection: Option<(PeerId, ConnectionId)>,
+    events: VecDeque<ToSwarm<Vec<String>, ()>>,
+}
+
+impl Behaviour {
+    fn new(protocol: StreamProtocol) -> Self {
+        Self {
+            protocol,
+            open_stream: false,
+            connection: None,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Vec<String>;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.protocol.clone()))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.protocol.clone()))
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        match event {
+            FromSwarm::ConnectionEstablished(e) => {
+                self.connection.get_or_insert((e.peer_id, e.connection_id));
+            }
+            FromSwarm::NegotiationFailed(e) => {
+                self.events
+                    .push_back(ToSwarm::GenerateEvent(e.protocols_tried.to_vec()));
+            }
+            _ => {}
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        _: THandlerOutEvent<Self>,
+    ) {
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(event);
+        }
+
+        if let Some((peer_id, connection)) = self.connection {
+            if std::mem::take(&mut self.open_stream) {
+                return Poll::Ready(ToSwarm::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::One(connection),
+                    event: (),
+                });
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+struct Handler {
+    protocol: StreamProtocol,
+    open_stream: bool,
+    keep_alive: bool,
+}
+
+impl Handler {
+    fn new(protocol: StreamProtocol) -> Self {
+        Self {
+            protocol,
+            open_stream: false,
+            keep_alive: true,
+        }
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = ();
+    type ToBehaviour = ();
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(self.protocol.clone()), ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        self.keep_alive
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if std::mem::take(&mut self.open_stream) {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ReadyUpgrade::new(self.protocol.clone()), ()),
+            });
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, _: Self::FromBehaviour) {
+        self.open_stream = true;
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        if let ConnectionEvent::DialUpgradeError(_) = event {
+            self.keep_alive = false;
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use futures::future::{self, Either};
+use libp2p_core::multiaddr_ext::TransportKind;
+use libp2p_swarm::dial_opts::{DialOpts, PeerCondition};
+use libp2p_swarm::{dummy, DirectionCounts, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::BTreeMap;
+
+#[async_std::test]
+async fn counts_connections_by_transport() {
+    let mut dialer = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut listener = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    let (memory_addr, tcp_addr) = listener.listen().await;
+    let listener_peer_id = *listener.local_peer_id();
+
+    for addr in [memory_addr, tcp_addr] {
+        dialer
+            .dial(
+                DialOpts::peer_id(listener_peer_id)
+                    .addresses(vec![addr])
+                    .condition(PeerCondition::Always)
+                    .build(),
+            )
+            .unwrap();
+    }
+
+    let pending = dialer.network_info().pending_by_transport().clone();
+    assert_eq!(counts(&pending, TransportKind::Memory), (0, 1));
+    assert_eq!(counts(&pending, TransportKind::Tcp), (0, 1));
+
+    let mut num_established = (0, 0);
+    while num_established != (2, 2) {
+        match future::select(dialer.next_swarm_event(), listener.next_swarm_event()).await {
+            Either::Left((SwarmEvent::ConnectionEstablished { .. }, _)) => num_established.0 += 1,
+            Either::Right((SwarmEvent::ConnectionEstablished { .. }, _)) => num_established.1 += 1,
+            _ => {}
+        }
+    }
+
+    let dialer_info = dialer.network_info();
+    assert!(dialer_info.pending_by_transport().is_empty());
+    let established = dialer_info.connections_by_transport();
+    assert_eq!(established.len(), 2);
+    assert_eq!(counts(established, TransportKind::Memory), (0, 1));
+    assert_eq!(counts(established, TransportKind::Tcp), (0, 1));
+
+    let listener_info = listener.network_info();
+    let established = listener_info.connections_by_transport();
+    assert_eq!(established.len(), 2);
+    assert_eq!(counts(established, TransportKind::Memory), (1, 0));
+    assert_eq!(counts(established, TransportKind::Tcp), (1, 0));
+}
+
+/// Returns the number of incoming and outgoing connections of the given transport.
+fn counts(counts: &BTreeMap<TransportKind, DirectionCounts>, kind: TransportKind) -> (u32, u32) {
+    let counts = counts.get(&kind).copied().unwrap_or_default();
+
+    (counts.num_incoming(), counts.num_outgoing())
+}
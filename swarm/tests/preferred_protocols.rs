@@ -0,0 +1,205 @@
+use futures::future;
+use libp2p_core::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo};
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::handler::{ConnectionEvent, FullyNegotiatedInbound, FullyNegotiatedOutbound};
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, Stream, StreamProtocol, SubstreamProtocol, Swarm, THandler,
+    THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+use void::Void;
+
+const V1: StreamProtocol = StreamProtocol::new("/myproto/1.0");
+const V2: StreamProtocol = StreamProtocol::new("/myproto/2.0");
+
+#[async_std::test]
+async fn negotiates_preferred_protocol() {
+    let mut dialer = Swarm::new_ephemeral(|_| Behaviour::default());
+    let mut listener = Swarm::new_ephemeral(|_| Behaviour::default());
+
+    listener.listen().with_memory_addr_external().await;
+    dialer.connect(&mut listener).await;
+
+    dialer.behaviour_mut().open_stream = Some(vec![]);
+    let ([dialer_negotiated], [listener_negotiated]): ([StreamProtocol; 1], [StreamProtocol; 1]) =
+        libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+    assert_eq!(dialer_negotiated, V1);
+    assert_eq!(listener_negotiated, V1);
+
+    dialer.behaviour_mut().open_stream = Some(vec![V2]);
+    let ([dialer_negotiated], [listener_negotiated]): ([StreamProtocol; 1], [StreamProtocol; 1]) =
+        libp2p_swarm_test::drive(&mut dialer, &mut listener).await;
+    assert_eq!(dialer_negotiated, V2);
+    assert_eq!(listener_negotiated, V2);
+}
+
+/// Once `open_stream` is set, opens a stream on the first established connection, proposing the
+/// given protocols first.
+///
+/// Reports the protocol negotiated on each stream.
+#[derive(Default)]
+struct Behaviour {
+    open_stream: Option<Vec<StreamProtocol>>,
+    connection: Option<(PeerId, ConnectionId)>,
+    events: VecDeque<StreamProtocol>,
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = StreamProtocol;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::default())
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ConnectionEstablished(e) = event {
+            self.connection.get_or_insert((e.peer_id, e.connection_id));
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        protocol: THandlerOutEvent<Self>,
+    ) {
+        self.events.push_back(protocol);
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(protocol) = self.events.pop_front() {
+            return Poll::Ready(ToSwarm::GenerateEvent(protocol));
+        }
+
+        if let Some((peer_id, connection)) = self.connection {
+            if let Some(preferred) = self.open_stream.take() {
+                return Poll::Ready(ToSwarm::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::One(connection),
+                    event: preferred,
+                });
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+#[derive(Default)]
+struct Handler {
+    open_stream: Option<Vec<StreamProtocol>>,
+    negotiated: VecDeque<StreamProtocol>,
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = Vec<StreamProtocol>;
+    type ToBehaviour = StreamProtocol;
+    type InboundProtocol = Upgrade;
+    type OutboundProtocol = Upgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(Upgrade, ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if let Some(preferred) = self.open_stream.take() {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(Upgrade, ()).with_preferred_protocols(preferred),
+            });
+        }
+
+        if let Some(protocol) = self.negotiated.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(protocol));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, preferred: Self::FromBehaviour) {
+        self.open_stream = Some(preferred);
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol, ..
+            }) => self.negotiated.push_back(protocol),
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol, ..
+            }) => self.negotiated.push_back(protocol),
+            _ => {}
+        }
+    }
+}
+
+/// Supports [`V1`] and [`V2`], in this order, outputting the negotiated protocol.
+struct Upgrade;
+
+impl UpgradeInfo for Upgrade {
+    type Info = StreamProtocol;
+    type InfoIter = [StreamProtocol; 2];
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        [V1, V2]
+    }
+}
+
+impl InboundUpgrade<Stream> for Upgrade {
+    type Output = StreamProtocol;
+    type Error = Void;
+    type Future = future::Ready<Result<Self::Output, Self::Error>>;
+
+    fn upgrade_inbound(self, _: Stream, info: Self::Info) -> Self::Future {
+        future::ready(Ok(info))
+    }
+}
+
+impl OutboundUpgrade<Stream> for Upgrade {
+    type Output = StreamProtocol;
+    type Error = Void;
+    type Future = future::Ready<Result<Self::Output, Self::Error>>;
+
+    fn upgrade_outbound(self, _: Stream, info: Self::Info) -> Self::Future {
+        future::ready(Ok(info))
+    }
+}
@@ -0,0 +1,80 @@
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_ping as ping;
+use libp2p_swarm::{
+    dummy, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, StreamProtocol, Swarm,
+    THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::task::{Context, Poll};
+
+const LISTED_PROTOCOL: StreamProtocol = StreamProtocol::new("/listed/1.0.0");
+
+#[derive(NetworkBehaviour)]
+#[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+struct Behaviour {
+    ping: ping::Behaviour,
+    dummy: dummy::Behaviour,
+    listed: Listed,
+}
+
+#[async_std::test]
+async fn lists_protocols_of_all_behaviours_before_any_connection() {
+    let swarm = Swarm::new_ephemeral(|_| Behaviour {
+        ping: ping::Behaviour::default(),
+        dummy: dummy::Behaviour,
+        listed: Listed,
+    });
+
+    assert_eq!(
+        swarm.supported_protocols(),
+        vec![ping::PROTOCOL_NAME, LISTED_PROTOCOL]
+    );
+}
+
+/// Lists [`LISTED_PROTOCOL`] and, again, the ping protocol, which is only reported once.
+struct Listed;
+
+impl NetworkBehaviour for Listed {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = ();
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn supported_protocols(&self) -> Vec<StreamProtocol> {
+        vec![LISTED_PROTOCOL, ping::PROTOCOL_NAME]
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<(), THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
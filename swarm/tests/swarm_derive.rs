@@ -609,6 +609,345 @@ fn custom_out_event_no_type_parameters() {
     require_net_behaviour::<Behaviour<()>>();
 }
 
+#[test]
+fn event_accessors() {
+    #[allow(dead_code)]
+    #[derive(NetworkBehaviour)]
+    #[behaviour(event_accessors, prelude = "libp2p_swarm::derive_prelude")]
+    struct Foo {
+        ping: ping::Behaviour,
+        dummy: dummy::Behaviour,
+    }
+
+    let event = FooEvent::Ping(ping::Event {
+        peer: libp2p_identity::PeerId::random(),
+        connection: libp2p_swarm::ConnectionId::new_unchecked(0),
+        result: Ok(std::time::Duration::from_secs(1)),
+        max_failures_reached: false,
+    });
+
+    assert!(event.as_ping().is_some());
+    assert!(event.as_dummy().is_none());
+    assert!(matches!(
+        event.into_ping(),
+        Ok(ping::Event { result: Ok(rtt), .. }) if rtt == std::time::Duration::from_secs(1)
+    ));
+}
+
+#[test]
+fn event_accessors_same_behaviour_type() {
+    #[allow(dead_code)]
+    #[derive(NetworkBehaviour)]
+    #[behaviour(event_accessors, prelude = "libp2p_swarm::derive_prelude")]
+    struct Foo {
+        ping1: ping::Behaviour,
+        ping2: ping::Behaviour,
+        identify: identify::Behaviour,
+    }
+
+    let event = FooEvent::Ping2(ping::Event {
+        peer: libp2p_identity::PeerId::random(),
+        connection: libp2p_swarm::ConnectionId::new_unchecked(0),
+        result: Ok(std::time::Duration::from_secs(1)),
+        max_failures_reached: false,
+    });
+
+    assert!(event.as_ping1().is_none());
+    assert!(event.as_ping2().is_some());
+    assert!(event.as_identify().is_none());
+    let event = event.into_identify().unwrap_err();
+    let event = event.into_ping1().unwrap_err();
+    assert!(event.into_ping2().is_ok());
+}
+
+#[test]
+fn event_accessors_with_generics() {
+    #[allow(dead_code)]
+    #[derive(NetworkBehaviour)]
+    #[behaviour(event_accessors, prelude = "libp2p_swarm::derive_prelude")]
+    struct Foo<A> {
+        a: A,
+        ping: ping::Behaviour,
+    }
+
+    let event = FooEvent::<dummy::Behaviour>::Ping(ping::Event {
+        peer: libp2p_identity::PeerId::random(),
+        connection: libp2p_swarm::ConnectionId::new_unchecked(0),
+        result: Ok(std::time::Duration::from_secs(1)),
+        max_failures_reached: false,
+    });
+
+    assert!(event.as_a().is_none());
+    assert!(event.as_ping().is_some());
+}
+
+#[async_std::test]
+async fn pending_listen_denial_suppresses_address() {
+    use futures::future::poll_fn;
+    use libp2p_core::{multiaddr::Protocol, transport::ListenerId};
+    use libp2p_swarm::{ListenDenied, Swarm};
+    use libp2p_swarm_test::SwarmExt;
+    use std::task::{Context, Poll};
+
+    /// Records reported listen addresses, denying loopback addresses if `deny_loopback` is set.
+    #[derive(Default)]
+    struct ListenFilter {
+        deny_loopback: bool,
+        denied: Vec<Multiaddr>,
+        reported: Vec<Multiaddr>,
+    }
+
+    impl NetworkBehaviour for ListenFilter {
+        type ConnectionHandler = dummy::ConnectionHandler;
+        type ToSwarm = void::Void;
+
+        fn handle_established_inbound_connection(
+            &mut self,
+            _: libp2p_swarm::ConnectionId,
+            _: libp2p_identity::PeerId,
+            _: &Multiaddr,
+            _: &Multiaddr,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn handle_established_outbound_connection(
+            &mut self,
+            _: libp2p_swarm::ConnectionId,
+            _: libp2p_identity::PeerId,
+            _: &Multiaddr,
+            _: Endpoint,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn handle_pending_listen(
+            &mut self,
+            _: ListenerId,
+            addr: &Multiaddr,
+        ) -> Result<(), ListenDenied> {
+            if self.deny_loopback && is_loopback(addr) {
+                self.denied.push(addr.clone());
+                return Err(ListenDenied::new("loopback address"));
+            }
+
+            Ok(())
+        }
+
+        fn on_swarm_event(&mut self, event: FromSwarm) {
+            if let FromSwarm::NewListenAddr(e) = event {
+                self.reported.push(e.addr.clone());
+            }
+        }
+
+        fn on_connection_handler_event(
+            &mut self,
+            _: libp2p_identity::PeerId,
+            _: libp2p_swarm::ConnectionId,
+            _: THandlerOutEvent<Self>,
+        ) {
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<libp2p_swarm::ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+            Poll::Pending
+        }
+    }
+
+    #[derive(NetworkBehaviour)]
+    #[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+    struct Foo {
+        filter: ListenFilter,
+        sibling: ListenFilter,
+    }
+
+    fn is_loopback(addr: &Multiaddr) -> bool {
+        matches!(addr.iter().next(), Some(Protocol::Ip4(ip)) if ip.is_loopback())
+    }
+
+    let mut swarm = Swarm::new_ephemeral(|_| Foo {
+        filter: ListenFilter {
+            deny_loopback: true,
+            ..Default::default()
+        },
+        sibling: ListenFilter::default(),
+    });
+    swarm
+        .listen_on("/ip4/0.0.0.0/tcp/0".parse().unwrap())
+        .unwrap();
+
+    poll_fn(|cx| {
+        while let Poll::Ready(Some(_)) = swarm.poll_next_unpin(cx) {}
+
+        if swarm.behaviour().filter.denied.is_empty() {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(())
+    })
+    .await;
+
+    assert!(swarm.behaviour().filter.denied.iter().all(is_loopback));
+    assert!(!swarm.listeners().any(is_loopback));
+    assert!(!swarm.behaviour().filter.reported.iter().any(is_loopback));
+    assert!(!swarm.behaviour().sibling.reported.iter().any(is_loopback));
+}
+
+#[derive(NetworkBehaviour)]
+#[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+struct ChattyAndQuiet {
+    chatty: Emitter,
+    quiet: Emitter,
+}
+
+impl Default for ChattyAndQuiet {
+    fn default() -> Self {
+        Self {
+            chatty: Emitter {
+                remaining: usize::MAX,
+            },
+            quiet: Emitter { remaining: 1 },
+        }
+    }
+}
+
+/// Polls the swarm for its next two events, returning whether one of them is the event of
+/// `quiet`.
+async fn quiet_event_within_two_polls(swarm: &mut libp2p_swarm::Swarm<ChattyAndQuiet>) -> bool {
+    let mut quiet_event_emitted = false;
+    for _ in 0..2 {
+        if let SwarmEvent::Behaviour(ChattyAndQuietEvent::Quiet(())) =
+            swarm.select_next_some().await
+        {
+            quiet_event_emitted = true;
+        }
+    }
+
+    quiet_event_emitted
+}
+
+#[async_std::test]
+async fn round_robin_poll_order_does_not_starve_later_behaviours() {
+    use libp2p_swarm::Swarm;
+    use libp2p_swarm_test::SwarmExt;
+
+    let mut swarm = Swarm::new_ephemeral(|_| ChattyAndQuiet::default());
+
+    assert!(quiet_event_within_two_polls(&mut swarm).await);
+}
+
+#[async_std::test]
+async fn round_robin_poll_order_is_kept_per_instance() {
+    use libp2p_swarm::Swarm;
+    use libp2p_swarm_test::SwarmExt;
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| ChattyAndQuiet::default());
+    let mut swarm2 = Swarm::new_ephemeral(|_| ChattyAndQuiet::default());
+
+    // Polling the swarms alternately must not make either of them always start with `chatty`.
+    let mut quiet_event_emitted = [false, false];
+    for _ in 0..2 {
+        for (swarm, emitted) in [&mut swarm1, &mut swarm2]
+            .into_iter()
+            .zip(&mut quiet_event_emitted)
+        {
+            if let SwarmEvent::Behaviour(ChattyAndQuietEvent::Quiet(())) =
+                swarm.select_next_some().await
+            {
+                *emitted = true;
+            }
+        }
+    }
+
+    assert_eq!(quiet_event_emitted, [true, true]);
+}
+
+#[test]
+fn sequential_poll_order_polls_in_field_order() {
+    use futures::task::noop_waker_ref;
+    use libp2p_swarm::ToSwarm;
+    use std::task::{Context, Poll};
+
+    #[derive(NetworkBehaviour)]
+    #[behaviour(poll_order = "sequential", prelude = "libp2p_swarm::derive_prelude")]
+    struct Foo {
+        chatty: Emitter,
+        quiet: Emitter,
+    }
+
+    let mut behaviour = Foo {
+        chatty: Emitter { remaining: 10 },
+        quiet: Emitter { remaining: 1 },
+    };
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    for _ in 0..10 {
+        assert!(matches!(
+            behaviour.poll(&mut cx),
+            Poll::Ready(ToSwarm::GenerateEvent(FooEvent::Chatty(())))
+        ));
+    }
+    assert!(matches!(
+        behaviour.poll(&mut cx),
+        Poll::Ready(ToSwarm::GenerateEvent(FooEvent::Quiet(())))
+    ));
+    assert!(behaviour.poll(&mut cx).is_pending());
+}
+
+/// Emits an event on each call to `poll` until `remaining` reaches zero.
+struct Emitter {
+    remaining: usize,
+}
+
+impl NetworkBehaviour for Emitter {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = ();
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: libp2p_swarm::ConnectionId,
+        _: libp2p_identity::PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: libp2p_swarm::ConnectionId,
+        _: libp2p_identity::PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: libp2p_identity::PeerId,
+        _: libp2p_swarm::ConnectionId,
+        _: THandlerOutEvent<Self>,
+    ) {
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<libp2p_swarm::ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if self.remaining == 0 {
+            return std::task::Poll::Pending;
+        }
+        self.remaining -= 1;
+
+        std::task::Poll::Ready(libp2p_swarm::ToSwarm::GenerateEvent(()))
+    }
+}
+
 #[test]
 fn ui() {
     let t = trybuild::TestCases::new();
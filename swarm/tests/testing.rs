@@ -0,0 +1,47 @@
+use libp2p_swarm::testing::{connect, TestRuntime, TestSwarm};
+use libp2p_swarm::{dummy, SwarmEvent};
+use std::time::Duration;
+
+#[test]
+fn closes_idle_connection_after_timeout() {
+    let runtime = TestRuntime::new();
+    let mut dialer = TestSwarm::with_config(
+        &runtime,
+        |_| dummy::Behaviour,
+        |config| config.with_idle_connection_timeout(Duration::from_secs(10)),
+    );
+    let mut listener = TestSwarm::with_config(
+        &runtime,
+        |_| dummy::Behaviour,
+        |config| config.with_idle_connection_timeout(Duration::from_secs(60)),
+    );
+
+    connect(&mut dialer, &mut listener);
+    listener.run_in_background();
+
+    dialer
+        .wait_for_event(
+            |event| matches!(event, SwarmEvent::ConnectionClosed { .. }).then_some(()),
+            Duration::from_secs(30),
+        )
+        .unwrap();
+    assert_eq!(runtime.clock().elapsed(), Duration::from_secs(10));
+}
+
+#[test]
+fn waiting_times_out_in_virtual_time() {
+    let runtime = TestRuntime::new();
+    let mut a = TestSwarm::new(&runtime, |_| dummy::Behaviour);
+    let mut b = TestSwarm::new(&runtime, |_| dummy::Behaviour);
+
+    connect(&mut a, &mut b);
+    assert!(a.drive_until_idle().is_empty());
+    assert_eq!(runtime.clock().elapsed(), Duration::ZERO);
+
+    let result = a.wait_for_event(
+        |event| matches!(event, SwarmEvent::IncomingConnection { .. }).then_some(()),
+        Duration::from_secs(3600),
+    );
+    assert!(result.is_err());
+    assert_eq!(runtime.clock().elapsed(), Duration::from_secs(3600));
+}
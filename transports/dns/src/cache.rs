@@ -0,0 +1,206 @@
+use crate::{ResolveError, Resolver};
+use async_trait::async_trait;
+use hickory_resolver::lookup::{Ipv4Lookup, Ipv6Lookup, TxtLookup};
+use hickory_resolver::lookup_ip::LookupIp;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Configuration of the cache of a [`CachingResolver`].
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    max_entries: usize,
+    min_ttl: Duration,
+    max_ttl: Duration,
+}
+
+impl CacheConfig {
+    /// Creates a new configuration with the following defaults:
+    ///
+    ///   * At most `256` lookups are cached. See [`CacheConfig::with_max_entries`].
+    ///   * Lookups are cached as long as their records are valid, but at most for one day.
+    ///     See [`CacheConfig::with_min_ttl`] and [`CacheConfig::with_max_ttl`].
+    pub fn new() -> Self {
+        Self {
+            max_entries: 256,
+            min_ttl: Duration::ZERO,
+            max_ttl: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// Sets the maximum number of cached lookups. Once reached, the lookup expiring first is
+    /// evicted.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Sets the minimum duration a lookup is cached, even if its records expire earlier.
+    pub fn with_min_ttl(mut self, ttl: Duration) -> Self {
+        self.min_ttl = ttl;
+        self
+    }
+
+    /// Sets the maximum duration a lookup is cached, even if its records are valid for longer.
+    pub fn with_max_ttl(mut self, ttl: Duration) -> Self {
+        self.max_ttl = ttl;
+        self
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`Resolver`] caching the successful lookups of an inner resolver for the TTL of their
+/// records, clamped to the bounds of its [`CacheConfig`].
+///
+/// Clones share the cache. Use it via [`Transport::with_dns_cache`](crate::Transport::with_dns_cache).
+#[derive(Debug, Clone)]
+pub struct CachingResolver<R> {
+    inner: R,
+    config: CacheConfig,
+    cache: Arc<Mutex<HashMap<(RecordKind, String), (Instant, Lookup)>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum RecordKind {
+    Ip,
+    Ipv4,
+    Ipv6,
+    Txt,
+}
+
+#[derive(Debug, Clone)]
+enum Lookup {
+    Ip(LookupIp),
+    Ipv4(Ipv4Lookup),
+    Ipv6(Ipv6Lookup),
+    Txt(TxtLookup),
+}
+
+impl<R> CachingResolver<R> {
+    /// Creates a new [`CachingResolver`], caching the lookups of the given resolver.
+    pub fn new(inner: R, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            config,
+            cache: Default::default(),
+        }
+    }
+
+    /// Returns the inner resolver.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    fn get(&self, kind: RecordKind, name: &str) -> Option<Lookup> {
+        let mut cache = self.cache.lock();
+        let key = (kind, name.to_owned());
+        match cache.get(&key) {
+            Some((expires_at, lookup)) if *expires_at > Instant::now() => Some(lookup.clone()),
+            Some(_) => {
+                cache.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, kind: RecordKind, name: String, lookup: Lookup, valid_until: Instant) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let ttl = valid_until.saturating_duration_since(now).clamp(
+            self.config.min_ttl,
+            self.config.max_ttl.max(self.config.min_ttl),
+        );
+        if ttl.is_zero() {
+            return;
+        }
+
+        let mut cache = self.cache.lock();
+        cache.retain(|_, (expires_at, _)| *expires_at > now);
+        if cache.len() >= self.config.max_entries && !cache.contains_key(&(kind, name.clone())) {
+            let first_expiring = cache
+                .iter()
+                .min_by_key(|(_, (expires_at, _))| *expires_at)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = first_expiring {
+                cache.remove(&key);
+            }
+        }
+        cache.insert((kind, name), (now + ttl, lookup));
+    }
+}
+
+#[async_trait]
+impl<R> Resolver for CachingResolver<R>
+where
+    R: Resolver + Send + Sync,
+{
+    async fn lookup_ip(&self, name: String) -> Result<LookupIp, ResolveError> {
+        if let Some(Lookup::Ip(lookup)) = self.get(RecordKind::Ip, &name) {
+            return Ok(lookup);
+        }
+        let lookup = self.inner.lookup_ip(name.clone()).await?;
+        let valid_until = lookup.valid_until();
+        self.insert(
+            RecordKind::Ip,
+            name,
+            Lookup::Ip(lookup.clone()),
+            valid_until,
+        );
+        Ok(lookup)
+    }
+
+    async fn ipv4_lookup(&self, name: String) -> Result<Ipv4Lookup, ResolveError> {
+        if let Some(Lookup::Ipv4(lookup)) = self.get(RecordKind::Ipv4, &name) {
+            return Ok(lookup);
+        }
+        let lookup = self.inner.ipv4_lookup(name.clone()).await?;
+        let valid_until = lookup.valid_until();
+        self.insert(
+            RecordKind::Ipv4,
+            name,
+            Lookup::Ipv4(lookup.clone()),
+            valid_until,
+        );
+        Ok(lookup)
+    }
+
+    async fn ipv6_lookup(&self, name: String) -> Result<Ipv6Lookup, ResolveError> {
+        if let Some(Lookup::Ipv6(lookup)) = self.get(RecordKind::Ipv6, &name) {
+            return Ok(lookup);
+        }
+        let lookup = self.inner.ipv6_lookup(name.clone()).await?;
+        let valid_until = lookup.valid_until();
+        self.insert(
+            RecordKind::Ipv6,
+            name,
+            Lookup::Ipv6(lookup.clone()),
+            valid_until,
+        );
+        Ok(lookup)
+    }
+
+    async fn txt_lookup(&self, name: String) -> Result<TxtLookup, ResolveError> {
+        if let Some(Lookup::Txt(lookup)) = self.get(RecordKind::Txt, &name) {
+            return Ok(lookup);
+        }
+        let lookup = self.inner.txt_lookup(name.clone()).await?;
+        let valid_until = lookup.valid_until();
+        self.insert(
+            RecordKind::Txt,
+            name,
+            Lookup::Txt(lookup.clone()),
+            valid_until,
+        );
+        Ok(lookup)
+    }
+}
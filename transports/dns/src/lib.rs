@@ -72,6 +72,9 @@ pub mod async_std {
     /// using `async-std` for all async I/O.
     pub type Transport<T> = crate::Transport<T, AsyncStdResolver>;
 
+    /// The DNS resolver of a [`Transport`].
+    pub type Resolver = AsyncStdResolver;
+
     impl<T> Transport<T> {
         /// Creates a new [`Transport`] from the OS's DNS configuration and defaults.
         pub async fn system(inner: T) -> Result<Transport<T>, io::Error> {
@@ -83,6 +86,7 @@ pub mod async_std {
         pub async fn custom(inner: T, cfg: ResolverConfig, opts: ResolverOpts) -> Transport<T> {
             Transport {
                 inner: Arc::new(Mutex::new(inner)),
+                dial_concurrency_factor: crate::DEFAULT_DIAL_CONCURRENCY_FACTOR,
                 resolver: async_std_resolver::resolver(cfg, opts).await,
             }
         }
@@ -92,6 +96,7 @@ pub mod async_std {
         pub fn system2(inner: T) -> Result<Transport<T>, io::Error> {
             Ok(Transport {
                 inner: Arc::new(Mutex::new(inner)),
+                dial_concurrency_factor: crate::DEFAULT_DIAL_CONCURRENCY_FACTOR,
                 resolver: async_std_resolver::resolver_from_system_conf()
                     .now_or_never()
                     .expect(
@@ -105,6 +110,7 @@ pub mod async_std {
         pub fn custom2(inner: T, cfg: ResolverConfig, opts: ResolverOpts) -> Transport<T> {
             Transport {
                 inner: Arc::new(Mutex::new(inner)),
+                dial_concurrency_factor: crate::DEFAULT_DIAL_CONCURRENCY_FACTOR,
                 resolver: async_std_resolver::resolver(cfg, opts)
                     .now_or_never()
                     .expect("async_std_resolver::resolver did not resolve immediately"),
@@ -123,6 +129,9 @@ pub mod tokio {
     /// using `tokio` for all async I/O.
     pub type Transport<T> = crate::Transport<T, TokioAsyncResolver>;
 
+    /// The DNS resolver of a [`Transport`].
+    pub type Resolver = TokioAsyncResolver;
+
     impl<T> Transport<T> {
         /// Creates a new [`Transport`] from the OS's DNS configuration and defaults.
         pub fn system(inner: T) -> Result<Transport<T>, std::io::Error> {
@@ -139,6 +148,7 @@ pub mod tokio {
         ) -> Transport<T> {
             Transport {
                 inner: Arc::new(Mutex::new(inner)),
+                dial_concurrency_factor: crate::DEFAULT_DIAL_CONCURRENCY_FACTOR,
                 resolver: TokioAsyncResolver::tokio(cfg, opts),
             }
         }
@@ -146,7 +156,7 @@ pub mod tokio {
 }
 
 use async_trait::async_trait;
-use futures::{future::BoxFuture, prelude::*};
+use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
 use libp2p_core::{
     connection::Endpoint,
     multiaddr::{Multiaddr, Protocol},
@@ -156,6 +166,7 @@ use parking_lot::Mutex;
 use smallvec::SmallVec;
 use std::io;
 use std::net::{Ipv4Addr, Ipv6Addr};
+use std::num::NonZeroU8;
 use std::{
     error, fmt, iter,
     ops::DerefMut,
@@ -165,6 +176,9 @@ use std::{
     task::{Context, Poll},
 };
 
+mod cache;
+
+pub use cache::{CacheConfig, CachingResolver};
 pub use hickory_resolver::config::{ResolverConfig, ResolverOpts};
 pub use hickory_resolver::error::{ResolveError, ResolveErrorKind};
 use hickory_resolver::lookup::{Ipv4Lookup, Ipv6Lookup, TxtLookup};
@@ -178,6 +192,9 @@ const DNSADDR_PREFIX: &str = "_dnsaddr.";
 /// The maximum number of dialing attempts to resolved addresses.
 const MAX_DIAL_ATTEMPTS: usize = 16;
 
+/// The number of resolved addresses dialed concurrently by default.
+const DEFAULT_DIAL_CONCURRENCY_FACTOR: NonZeroU8 = NonZeroU8::MIN;
+
 /// The maximum number of DNS lookups when dialing.
 ///
 /// This limit is primarily a safeguard against too many, possibly
@@ -196,10 +213,53 @@ const MAX_TXT_RECORDS: usize = 16;
 pub struct Transport<T, R> {
     /// The underlying transport.
     inner: Arc<Mutex<T>>,
+    /// The number of resolved addresses dialed concurrently.
+    dial_concurrency_factor: NonZeroU8,
     /// The DNS resolver used when dialing addresses with DNS components.
     resolver: R,
 }
 
+impl<T, R> Transport<T, R> {
+    /// Creates a [`Transport`] using an existing resolver, e.g. one shared with another
+    /// [`Transport`] to share its cache.
+    pub fn with_resolver(inner: T, resolver: R) -> Self {
+        Transport {
+            inner: Arc::new(Mutex::new(inner)),
+            dial_concurrency_factor: DEFAULT_DIAL_CONCURRENCY_FACTOR,
+            resolver,
+        }
+    }
+
+    /// Returns the resolver used when dialing addresses with DNS components.
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    /// Sets the number of addresses resolved from a single dialed address, e.g. the records of
+    /// a `/dnsaddr`, that are dialed concurrently. The first successful dial wins and the
+    /// remaining ones are dropped.
+    ///
+    /// Defaults to 1, i.e. the resolved addresses are dialed one after the other.
+    pub fn with_dial_concurrency_factor(mut self, factor: NonZeroU8) -> Self {
+        self.dial_concurrency_factor = factor;
+        self
+    }
+
+    /// Caches the successful lookups of the resolver for the TTL of their records, clamped to
+    /// the bounds of the given [`CacheConfig`], so that repeated dials of the same name don't
+    /// query the resolver again.
+    ///
+    /// The resolvers of [`tokio::Transport`] and [`async_std::Transport`] already cache lookups
+    /// as configured via [`ResolverOpts`]. This cache applies to any resolver, e.g. a custom one.
+    pub fn with_dns_cache(self, config: CacheConfig) -> Transport<T, CachingResolver<R>> {
+        Transport {
+            inner: self.inner,
+            dial_concurrency_factor: self.dial_concurrency_factor,
+            resolver: CachingResolver::new(self.resolver, config),
+        }
+    }
+}
+
 impl<T, R> libp2p_core::Transport for Transport<T, R>
 where
     T: libp2p_core::Transport + Send + Unpin + 'static,
@@ -231,14 +291,22 @@ where
     }
 
     fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
-        self.do_dial(addr, Endpoint::Dialer)
+        self.do_dial(addr, Endpoint::Dialer, None)
     }
 
     fn dial_as_listener(
         &mut self,
         addr: Multiaddr,
     ) -> Result<Self::Dial, TransportError<Self::Error>> {
-        self.do_dial(addr, Endpoint::Listener)
+        self.do_dial(addr, Endpoint::Listener, None)
+    }
+
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.do_dial(addr, Endpoint::Dialer, Some(port))
     }
 
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
@@ -269,12 +337,14 @@ where
         &mut self,
         addr: Multiaddr,
         role_override: Endpoint,
+        port: Option<u16>,
     ) -> Result<
         <Self as libp2p_core::Transport>::Dial,
         TransportError<<Self as libp2p_core::Transport>::Error>,
     > {
         let resolver = self.resolver.clone();
         let inner = self.inner.clone();
+        let dial_concurrency_factor = usize::from(self.dial_concurrency_factor.get());
 
         // Asynchronously resolve all DNS names in the address before proceeding
         // with dialing on the underlying transport.
@@ -282,6 +352,7 @@ where
             let mut last_err = None;
             let mut dns_lookups = 0;
             let mut dial_attempts = 0;
+            let mut dials = FuturesUnordered::new();
             // We optimise for the common case of a single DNS component
             // in the address that is resolved with a single lookup.
             let mut unresolved = SmallVec::<[Multiaddr; 1]>::new();
@@ -289,8 +360,25 @@ where
 
             // Resolve (i.e. replace) all DNS protocol components, initiating
             // dialing attempts as soon as there is another fully resolved
-            // address.
-            while let Some(addr) = unresolved.pop() {
+            // address and fewer than `dial_concurrency_factor` dials in progress.
+            loop {
+                let addr = match unresolved.pop() {
+                    Some(addr) if dials.len() < dial_concurrency_factor => addr,
+                    next => {
+                        unresolved.extend(next);
+                        match dials.next().await {
+                            Some(Ok(out)) => return Ok(out),
+                            Some(Err(err)) => {
+                                tracing::debug!("Dial error: {:?}.", err);
+                                last_err = Some(err);
+                                continue;
+                            }
+                            // Neither dials in progress nor addresses left.
+                            None => break,
+                        }
+                    }
+                };
+
                 if let Some((i, name)) = addr.iter().enumerate().find(|(_, p)| {
                     matches!(
                         p,
@@ -310,11 +398,12 @@ where
                     dns_lookups += 1;
                     match resolve(&name, &resolver).await {
                         Err(e) => {
-                            if unresolved.is_empty() {
+                            if unresolved.is_empty() && dials.is_empty() {
                                 return Err(e);
                             }
-                            // If there are still unresolved addresses, there is
-                            // a chance of success, but we track the last error.
+                            // If there are still unresolved addresses or dials in
+                            // progress, there is a chance of success, but we track
+                            // the last error.
                             last_err = Some(e);
                         }
                         Ok(Resolved::One(ip)) => {
@@ -352,44 +441,37 @@ where
                             }
                         }
                     }
+                } else if dial_attempts == MAX_DIAL_ATTEMPTS {
+                    tracing::debug!("Aborting dialing after {} attempts.", MAX_DIAL_ATTEMPTS);
+                    // Only wait for the dials in progress.
+                    unresolved.clear();
                 } else {
                     // We have a fully resolved address, so try to dial it.
                     tracing::debug!(address=%addr, "Dialing address");
 
                     let transport = inner.clone();
-                    let dial = match role_override {
-                        Endpoint::Dialer => transport.lock().dial(addr),
-                        Endpoint::Listener => transport.lock().dial_as_listener(addr),
+                    let dial = match (role_override, port) {
+                        (Endpoint::Dialer, None) => transport.lock().dial(addr),
+                        (Endpoint::Dialer, Some(port)) => {
+                            transport.lock().dial_from_port(addr, port)
+                        }
+                        (Endpoint::Listener, _) => transport.lock().dial_as_listener(addr),
                     };
-                    let result = match dial {
+                    match dial {
                         Ok(out) => {
                             // We only count attempts that the inner transport
                             // actually accepted, i.e. for which it produced
                             // a dialing future.
                             dial_attempts += 1;
-                            out.await.map_err(Error::Transport)
+                            dials.push(out.map_err(Error::Transport));
                         }
                         Err(TransportError::MultiaddrNotSupported(a)) => {
-                            Err(Error::MultiaddrNotSupported(a))
+                            tracing::debug!(address=%a, "Resolved address not supported");
+                            last_err = Some(Error::MultiaddrNotSupported(a));
                         }
-                        Err(TransportError::Other(err)) => Err(Error::Transport(err)),
-                    };
-
-                    match result {
-                        Ok(out) => return Ok(out),
-                        Err(err) => {
+                        Err(TransportError::Other(err)) => {
                             tracing::debug!("Dial error: {:?}.", err);
-                            if unresolved.is_empty() {
-                                return Err(err);
-                            }
-                            if dial_attempts == MAX_DIAL_ATTEMPTS {
-                                tracing::debug!(
-                                    "Aborting dialing after {} attempts.",
-                                    MAX_DIAL_ATTEMPTS
-                                );
-                                return Err(err);
-                            }
-                            last_err = Some(err);
+                            last_err = Some(Error::Transport(err));
                         }
                     }
                 }
@@ -400,7 +482,9 @@ where
             // for the given address to begin with (i.e. DNS lookups succeeded but
             // produced no records relevant for the given `addr`).
             Err(last_err.unwrap_or_else(|| {
-                Error::ResolveError(ResolveErrorKind::Message("No matching records found.").into())
+                Error::ResolveError(
+                    ResolveErrorKind::Msg(format!("No records found matching {addr}")).into(),
+                )
             }))
         }
         .boxed()
@@ -627,8 +711,17 @@ where
 #[cfg(all(test, any(feature = "tokio", feature = "async-std")))]
 mod tests {
     use super::*;
+    use hickory_resolver::lookup::Lookup;
+    use hickory_resolver::proto::op::Query;
+    use hickory_resolver::proto::rr::{
+        rdata::{A, TXT},
+        Name, RData, Record, RecordType,
+    };
     use libp2p_core::Transport;
     use libp2p_identity::PeerId;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
 
     #[test]
     fn basic_resolve() {
@@ -786,4 +879,256 @@ mod tests {
             rt.block_on(run(tokio::Transport::custom(CustomTransport, config, opts)));
         }
     }
+
+    #[test]
+    fn dnsaddr_dials_each_record() {
+        let records = (1..=3)
+            .map(|i| {
+                format!("/ip4/10.0.0.{i}/tcp/4001/p2p/{}", PeerId::random())
+                    .parse::<Multiaddr>()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let inner = RecordingTransport::default();
+        let mut transport =
+            super::Transport::with_resolver(inner.clone(), TxtResolver::new(&records));
+
+        let result = futures::executor::block_on(
+            transport
+                .dial("/dnsaddr/bootstrap.example.com".parse().unwrap())
+                .unwrap(),
+        );
+
+        assert!(matches!(result, Err(Error::Transport(_))));
+        let dialed = inner.dialed.lock().iter().cloned().collect::<HashSet<_>>();
+        assert_eq!(dialed, records.into_iter().collect::<HashSet<_>>());
+    }
+
+    #[test]
+    fn dnsaddr_dials_records_concurrently() {
+        let records = (1..=3)
+            .map(|i| {
+                format!("/ip4/10.0.0.{i}/tcp/4001/p2p/{}", PeerId::random())
+                    .parse::<Multiaddr>()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let inner = RecordingTransport {
+            pending: true,
+            ..Default::default()
+        };
+        let mut transport =
+            super::Transport::with_resolver(inner.clone(), TxtResolver::new(&records))
+                .with_dial_concurrency_factor(NonZeroU8::new(2).unwrap());
+
+        let dial = transport
+            .dial("/dnsaddr/bootstrap.example.com".parse().unwrap())
+            .unwrap();
+
+        assert!(dial.now_or_never().is_none());
+        assert_eq!(inner.dialed.lock().len(), 2);
+    }
+
+    #[test]
+    fn dnsaddr_without_matching_records_fails() {
+        let records = [format!("/ip4/10.0.0.1/tcp/4001/p2p/{}", PeerId::random())
+            .parse::<Multiaddr>()
+            .unwrap()];
+        let inner = RecordingTransport::default();
+        let mut transport =
+            super::Transport::with_resolver(inner.clone(), TxtResolver::new(&records));
+
+        let result = futures::executor::block_on(
+            transport
+                .dial(
+                    format!("/dnsaddr/bootstrap.example.com/p2p/{}", PeerId::random())
+                        .parse()
+                        .unwrap(),
+                )
+                .unwrap(),
+        );
+
+        assert!(matches!(result, Err(Error::ResolveError(_))));
+        assert!(inner.dialed.lock().is_empty());
+    }
+
+    #[test]
+    fn cached_lookup_is_reused_within_ttl() {
+        let resolver = Ipv4Resolver::new(Duration::from_secs(60));
+        let inner = RecordingTransport::default();
+        let mut transport = super::Transport::with_resolver(inner.clone(), resolver.clone())
+            .with_dns_cache(CacheConfig::default());
+
+        let addr: Multiaddr = "/dns4/example.com/tcp/4001".parse().unwrap();
+        for _ in 0..2 {
+            let _ = futures::executor::block_on(transport.dial(addr.clone()).unwrap());
+        }
+
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 1);
+        let resolved: Multiaddr = "/ip4/10.0.0.1/tcp/4001".parse().unwrap();
+        assert_eq!(*inner.dialed.lock(), vec![resolved.clone(), resolved]);
+    }
+
+    #[test]
+    fn lookup_is_queried_again_beyond_max_ttl() {
+        let resolver = Ipv4Resolver::new(Duration::from_secs(60));
+        let mut transport =
+            super::Transport::with_resolver(RecordingTransport::default(), resolver.clone())
+                .with_dns_cache(CacheConfig::default().with_max_ttl(Duration::ZERO));
+
+        let addr: Multiaddr = "/dns4/example.com/tcp/4001".parse().unwrap();
+        for _ in 0..2 {
+            let _ = futures::executor::block_on(transport.dial(addr.clone()).unwrap());
+        }
+
+        assert_eq!(resolver.lookups.load(Ordering::SeqCst), 2);
+    }
+
+    /// A [`Resolver`] answering all IPv4 lookups with `10.0.0.1`, valid for the given TTL, and
+    /// counting them.
+    #[derive(Clone)]
+    struct Ipv4Resolver {
+        ttl: Duration,
+        lookups: Arc<AtomicUsize>,
+    }
+
+    impl Ipv4Resolver {
+        fn new(ttl: Duration) -> Self {
+            Self {
+                ttl,
+                lookups: Default::default(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Resolver for Ipv4Resolver {
+        async fn lookup_ip(&self, name: String) -> Result<LookupIp, ResolveError> {
+            unimplemented!("Unexpected IP lookup of {name}")
+        }
+
+        async fn ipv4_lookup(&self, name: String) -> Result<Ipv4Lookup, ResolveError> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            let name = Name::from_ascii(name).unwrap();
+            let record = Record::from_rdata(
+                name.clone(),
+                self.ttl.as_secs() as u32,
+                RData::A(A(Ipv4Addr::new(10, 0, 0, 1))),
+            );
+
+            Ok(Lookup::new_with_deadline(
+                Query::query(name, RecordType::A),
+                vec![record].into(),
+                Instant::now() + self.ttl,
+            )
+            .into())
+        }
+
+        async fn ipv6_lookup(&self, name: String) -> Result<Ipv6Lookup, ResolveError> {
+            unimplemented!("Unexpected IPv6 lookup of {name}")
+        }
+
+        async fn txt_lookup(&self, name: String) -> Result<TxtLookup, ResolveError> {
+            unimplemented!("Unexpected TXT lookup of {name}")
+        }
+    }
+
+    /// A [`Resolver`] answering all TXT lookups with `dnsaddr` records of the given addresses.
+    #[derive(Clone)]
+    struct TxtResolver {
+        records: Vec<String>,
+    }
+
+    impl TxtResolver {
+        fn new(addrs: &[Multiaddr]) -> Self {
+            Self {
+                records: addrs.iter().map(|a| format!("dnsaddr={a}")).collect(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Resolver for TxtResolver {
+        async fn lookup_ip(&self, name: String) -> Result<LookupIp, ResolveError> {
+            unimplemented!("Unexpected IP lookup of {name}")
+        }
+
+        async fn ipv4_lookup(&self, name: String) -> Result<Ipv4Lookup, ResolveError> {
+            unimplemented!("Unexpected IPv4 lookup of {name}")
+        }
+
+        async fn ipv6_lookup(&self, name: String) -> Result<Ipv6Lookup, ResolveError> {
+            unimplemented!("Unexpected IPv6 lookup of {name}")
+        }
+
+        async fn txt_lookup(&self, name: String) -> Result<TxtLookup, ResolveError> {
+            let name = Name::from_ascii(name).unwrap();
+            let records = self
+                .records
+                .iter()
+                .map(|r| {
+                    Record::from_rdata(name.clone(), 60, RData::TXT(TXT::new(vec![r.clone()])))
+                })
+                .collect::<Vec<_>>();
+
+            Ok(
+                Lookup::new_with_max_ttl(Query::query(name, RecordType::TXT), records.into())
+                    .into(),
+            )
+        }
+    }
+
+    /// A [`Transport`] recording the addresses it dials. Its dials fail, unless `pending` is set,
+    /// in which case they never complete.
+    #[derive(Clone, Default)]
+    struct RecordingTransport {
+        dialed: Arc<Mutex<Vec<Multiaddr>>>,
+        pending: bool,
+    }
+
+    impl Transport for RecordingTransport {
+        type Output = ();
+        type Error = io::Error;
+        type ListenerUpgrade = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+        type Dial = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+        fn listen_on(
+            &mut self,
+            _: ListenerId,
+            _: Multiaddr,
+        ) -> Result<(), TransportError<Self::Error>> {
+            unreachable!()
+        }
+
+        fn remove_listener(&mut self, _: ListenerId) -> bool {
+            false
+        }
+
+        fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.dialed.lock().push(addr);
+            if self.pending {
+                return Ok(future::pending().boxed());
+            }
+
+            Ok(future::ready(Err(io::ErrorKind::ConnectionRefused.into())).boxed())
+        }
+
+        fn dial_as_listener(
+            &mut self,
+            addr: Multiaddr,
+        ) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.dial(addr)
+        }
+
+        fn address_translation(&self, _: &Multiaddr, _: &Multiaddr) -> Option<Multiaddr> {
+            None
+        }
+
+        fn poll(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+            unreachable!()
+        }
+    }
 }
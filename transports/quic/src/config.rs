@@ -18,6 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use libp2p_identity::PeerId;
 use quinn::{MtuDiscoveryConfig, VarInt};
 use std::{sync::Arc, time::Duration};
 
@@ -57,6 +58,31 @@ pub struct Config {
     /// As client the version is chosen based on the remote's address.
     pub support_draft_29: bool,
 
+    /// Whether to set `SO_REUSEPORT` on the UDP sockets of listeners, allowing other transports
+    /// to bind to the same UDP port, e.g. `libp2p_webrtc::tokio::Transport` configured via
+    /// `with_reuse_port`.
+    ///
+    /// Ignored on platforms without `SO_REUSEPORT`. Disabled by default.
+    pub reuse_port: bool,
+
+    /// Whether to set `IPV6_V6ONLY` on the UDP sockets of IPv6 listeners.
+    ///
+    /// If disabled, listening on `/ip6/::` accepts IPv4 connections via IPv4-mapped IPv6
+    /// addresses as well, regardless of the default of the operating system. Enabled by default.
+    pub ipv6_only: bool,
+
+    /// `SO_SNDBUF` to set on the UDP sockets, or `None` to keep the default of the operating
+    /// system. See [`Config::with_socket_buffer_sizes`].
+    pub send_buffer_size: Option<usize>,
+
+    /// `SO_RCVBUF` to set on the UDP sockets, or `None` to keep the default of the operating
+    /// system. See [`Config::with_socket_buffer_sizes`].
+    pub recv_buffer_size: Option<usize>,
+
+    /// Whether to resume sessions with 0-RTT when dialing a peer again, see
+    /// [`Config::with_0rtt`]. Disabled by default.
+    pub zero_rtt: bool,
+
     /// TLS client config for the inner [`quinn::ClientConfig`].
     client_tls_config: Arc<rustls::ClientConfig>,
     /// TLS server config for the inner [`quinn::ServerConfig`].
@@ -77,6 +103,11 @@ impl Config {
             client_tls_config,
             server_tls_config,
             support_draft_29: false,
+            reuse_port: false,
+            ipv6_only: true,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+            zero_rtt: false,
             handshake_timeout: Duration::from_secs(5),
             max_idle_timeout: 10 * 1000,
             max_concurrent_stream_limit: 256,
@@ -95,6 +126,71 @@ impl Config {
         self.mtu_discovery_config = None;
         self
     }
+
+    /// Sets the period of inactivity after which a keep-alive packet is sent, see
+    /// [`Config::keep_alive_interval`].
+    ///
+    /// Keep-alive packets are sent by the transport itself, thus keep NAT mappings alive even if
+    /// no application-level protocol, e.g. ping, is active on the connection.
+    pub fn with_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = interval;
+        self
+    }
+
+    /// Sets the sizes of the send and receive buffers of the UDP sockets of listeners and dialers,
+    /// see [`Config::send_buffer_size`] and [`Config::recv_buffer_size`].
+    ///
+    /// All connections of an endpoint share its socket, thus larger buffers than the defaults of
+    /// the operating system can prevent dropped datagrams on links with a high bandwidth-delay
+    /// product. The operating system may adjust the sizes, e.g. Linux doubles them and caps them at
+    /// `net.core.wmem_max` and `net.core.rmem_max` respectively.
+    pub fn with_socket_buffer_sizes(mut self, send: usize, recv: usize) -> Self {
+        self.send_buffer_size = Some(send);
+        self.recv_buffer_size = Some(recv);
+        self
+    }
+
+    /// Enables or disables 0-RTT session resumption, see [`Config::zero_rtt`].
+    ///
+    /// If enabled, the session tickets issued by remote peers are stored per [`PeerId`]. Dialing
+    /// an address containing the [`PeerId`] of a peer with a stored session ticket resumes that
+    /// session and yields the [`Connection`](crate::Connection) right away, allowing streams to be
+    /// opened before the handshake completed, see [`Connection::is_0rtt`](crate::Connection::is_0rtt).
+    /// As a listener, early data of resuming peers is accepted.
+    ///
+    /// Early data is not protected against replay attacks, thus only enable this if the protocols
+    /// run on the connections tolerate it. Streams opened before the handshake completed fail if
+    /// the remote rejects the early data.
+    pub fn with_0rtt(mut self, enabled: bool) -> Self {
+        self.zero_rtt = enabled;
+        self
+    }
+}
+
+/// Settings for dialing with 0-RTT, see [`Config::with_0rtt`].
+#[derive(Debug, Clone)]
+pub(crate) struct ZeroRttConfig {
+    keypair: libp2p_identity::Keypair,
+    transport: Arc<quinn::TransportConfig>,
+    /// Session tickets issued by remote peers, keyed by the server name, i.e. their [`PeerId`].
+    resumption: rustls::client::Resumption,
+}
+
+impl ZeroRttConfig {
+    /// Creates a [`quinn::ClientConfig`] for dialing the given peer, resuming its stored session
+    /// if any.
+    ///
+    /// Unlike the default client config, the remote is required to authenticate as `peer_id`,
+    /// thus session tickets are only ever stored for the [`PeerId`] that issued them.
+    pub(crate) fn client_config(&self, peer_id: PeerId) -> quinn::ClientConfig {
+        let mut tls_config = libp2p_tls::make_client_config(&self.keypair, Some(peer_id)).unwrap();
+        tls_config.enable_early_data = true;
+        tls_config.resumption = self.resumption.clone();
+
+        let mut client_config = quinn::ClientConfig::new(Arc::new(tls_config));
+        client_config.transport_config(Arc::clone(&self.transport));
+        client_config
+    }
 }
 
 /// Represents the inner configuration for [`quinn`].
@@ -103,6 +199,8 @@ pub(crate) struct QuinnConfig {
     pub(crate) client_config: quinn::ClientConfig,
     pub(crate) server_config: quinn::ServerConfig,
     pub(crate) endpoint_config: quinn::EndpointConfig,
+    /// Settings for dialing with 0-RTT, if enabled.
+    pub(crate) zero_rtt: Option<ZeroRttConfig>,
 }
 
 impl From<Config> for QuinnConfig {
@@ -116,6 +214,11 @@ impl From<Config> for QuinnConfig {
             max_connection_data,
             max_stream_data,
             support_draft_29,
+            reuse_port: _,
+            ipv6_only: _,
+            send_buffer_size: _,
+            recv_buffer_size: _,
+            zero_rtt,
             handshake_timeout: _,
             keypair,
             mtu_discovery_config,
@@ -134,6 +237,14 @@ impl From<Config> for QuinnConfig {
         transport.mtu_discovery_config(mtu_discovery_config);
         let transport = Arc::new(transport);
 
+        let server_tls_config = if zero_rtt {
+            let mut server_tls_config = (*server_tls_config).clone();
+            // `quinn` requires either no or unlimited early data.
+            server_tls_config.max_early_data_size = u32::MAX;
+            Arc::new(server_tls_config)
+        } else {
+            server_tls_config
+        };
         let mut server_config = quinn::ServerConfig::with_crypto(server_tls_config);
         server_config.transport = Arc::clone(&transport);
         // Disables connection migration.
@@ -141,6 +252,12 @@ impl From<Config> for QuinnConfig {
         // on connections in the `Connection`.
         server_config.migration(false);
 
+        let zero_rtt = zero_rtt.then(|| ZeroRttConfig {
+            keypair: keypair.clone(),
+            transport: Arc::clone(&transport),
+            resumption: rustls::client::Resumption::in_memory_sessions(256),
+        });
+
         let mut client_config = quinn::ClientConfig::new(client_tls_config);
         client_config.transport_config(transport);
 
@@ -160,6 +277,23 @@ impl From<Config> for QuinnConfig {
             client_config,
             server_config,
             endpoint_config,
+            zero_rtt,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_alive_interval_is_applied_to_quinn() {
+        let keypair = libp2p_identity::Keypair::generate_ed25519();
+        let config = Config::new(&keypair).with_keep_alive_interval(Duration::from_secs(7));
+
+        let quinn_config = QuinnConfig::from(config);
+
+        assert!(format!("{:?}", quinn_config.server_config.transport)
+            .contains("keep_alive_interval: Some(7s)"));
+    }
+}
@@ -47,6 +47,8 @@ pub struct Connection {
     >,
     /// Future to wait for the connection to be closed.
     closing: Option<BoxFuture<'static, quinn::ConnectionError>>,
+    /// Whether the connection was yielded before its handshake completed.
+    is_0rtt: bool,
 }
 
 impl Connection {
@@ -54,14 +56,22 @@ impl Connection {
     ///
     /// This function assumes that the [`quinn::Connection`] is completely fresh and none of
     /// its methods has ever been called. Failure to comply might lead to logic errors and panics.
-    fn new(connection: quinn::Connection) -> Self {
+    pub(crate) fn new(connection: quinn::Connection, is_0rtt: bool) -> Self {
         Self {
             connection,
             incoming: None,
             outgoing: None,
             closing: None,
+            is_0rtt,
         }
     }
+
+    /// Whether the connection resumed a previous session with 0-RTT, i.e. it was established
+    /// before the handshake completed and data sent on it early is not protected against replay
+    /// attacks. See [`Config::with_0rtt`](crate::Config::with_0rtt).
+    pub fn is_0rtt(&self) -> bool {
+        self.is_0rtt
+    }
 }
 
 impl StreamMuxer for Connection {
@@ -76,7 +76,7 @@ impl Future for Connecting {
         };
 
         let peer_id = Self::remote_peer_id(&connection);
-        let muxer = Connection::new(connection);
+        let muxer = Connection::new(connection, false);
         Poll::Ready(Ok((peer_id, muxer)))
     }
 }
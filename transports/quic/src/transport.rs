@@ -37,7 +37,7 @@ use libp2p_core::{
     Transport,
 };
 use libp2p_identity::PeerId;
-use socket2::{Domain, Socket, Type};
+use socket2::{Domain, SockRef, Socket, Type};
 use std::collections::hash_map::{DefaultHasher, Entry};
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
@@ -69,6 +69,14 @@ pub struct GenTransport<P: Provider> {
     handshake_timeout: Duration,
     /// Whether draft-29 is supported for dialing and listening.
     support_draft_29: bool,
+    /// Whether `SO_REUSEPORT` is set on the sockets of listeners.
+    reuse_port: bool,
+    /// Whether `IPV6_V6ONLY` is set on the sockets of IPv6 listeners.
+    ipv6_only: bool,
+    /// `SO_SNDBUF` to set on the sockets of listeners and dialers.
+    send_buffer_size: Option<usize>,
+    /// `SO_RCVBUF` to set on the sockets of listeners and dialers.
+    recv_buffer_size: Option<usize>,
     /// Streams of active [`Listener`]s.
     listeners: SelectAll<Listener<P>>,
     /// Dialer for each socket family if no matching listener exists.
@@ -84,6 +92,10 @@ impl<P: Provider> GenTransport<P> {
     pub fn new(config: Config) -> Self {
         let handshake_timeout = config.handshake_timeout;
         let support_draft_29 = config.support_draft_29;
+        let reuse_port = config.reuse_port;
+        let ipv6_only = config.ipv6_only;
+        let send_buffer_size = config.send_buffer_size;
+        let recv_buffer_size = config.recv_buffer_size;
         let quinn_config = config.into();
         Self {
             listeners: SelectAll::new(),
@@ -92,6 +104,10 @@ impl<P: Provider> GenTransport<P> {
             dialer: HashMap::new(),
             waker: None,
             support_draft_29,
+            reuse_port,
+            ipv6_only,
+            send_buffer_size,
+            recv_buffer_size,
             hole_punch_attempts: Default::default(),
         }
     }
@@ -146,6 +162,100 @@ impl<P: Provider> GenTransport<P> {
         Ok((socket_addr, version, peer_id))
     }
 
+    /// Dials the given address from the endpoint of the listener bound to the given local `port`,
+    /// if any, or otherwise from any eligible listener or a dedicated dialer endpoint.
+    fn do_dial(
+        &mut self,
+        addr: Multiaddr,
+        port: Option<u16>,
+    ) -> Result<<Self as Transport>::Dial, TransportError<<Self as Transport>::Error>> {
+        let (socket_addr, version, peer_id) = self.remote_multiaddr_to_socketaddr(addr, true)?;
+
+        let listener_endpoint = match port {
+            Some(port) => {
+                let listener = self.listeners.iter().find(|l| {
+                    !l.is_closed
+                        && l.socket_addr().port() == port
+                        && SocketFamily::is_same(&l.socket_addr().ip(), &socket_addr.ip())
+                });
+                match listener {
+                    Some(l) => Some(l.endpoint.clone()),
+                    None => {
+                        return Err(TransportError::Other(Error::Io(io::Error::new(
+                            io::ErrorKind::Unsupported,
+                            format!("no listener bound to port {port} to dial from"),
+                        ))))
+                    }
+                }
+            }
+            None => self
+                .eligible_listener(&socket_addr)
+                .map(|l| l.endpoint.clone()),
+        };
+        let endpoint = match listener_endpoint {
+            None => {
+                // No listener. Get or create an explicit dialer.
+                let socket_family = socket_addr.ip().into();
+                let dialer = match self.dialer.entry(socket_family) {
+                    Entry::Occupied(occupied) => occupied.get().clone(),
+                    Entry::Vacant(vacant) => {
+                        if let Some(waker) = self.waker.take() {
+                            waker.wake();
+                        }
+                        let listen_socket_addr = match socket_family {
+                            SocketFamily::Ipv4 => SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0),
+                            SocketFamily::Ipv6 => SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0),
+                        };
+                        let socket = UdpSocket::bind(listen_socket_addr).map_err(Error::from)?;
+                        set_buffer_sizes(
+                            SockRef::from(&socket),
+                            self.send_buffer_size,
+                            self.recv_buffer_size,
+                        )
+                        .map_err(Error::from)?;
+                        let endpoint_config = self.quinn_config.endpoint_config.clone();
+                        let endpoint = Self::new_endpoint(endpoint_config, None, socket)?;
+
+                        vacant.insert(endpoint.clone());
+                        endpoint
+                    }
+                };
+                dialer
+            }
+            Some(endpoint) => endpoint,
+        };
+        let handshake_timeout = self.handshake_timeout;
+        // Session tickets are stored per peer, thus 0-RTT requires the peer to be known.
+        let zero_rtt_peer = peer_id.filter(|_| self.quinn_config.zero_rtt.is_some());
+        let (mut client_config, server_name) = match (&self.quinn_config.zero_rtt, zero_rtt_peer) {
+            // The peer ID is used as server name, under which rustls stores the session ticket.
+            (Some(zero_rtt), Some(peer_id)) => {
+                (zero_rtt.client_config(peer_id), peer_id.to_string())
+            }
+            // This `"l"` seems necessary because an empty string is an invalid domain
+            // name. While we don't use domain names, the underlying rustls library
+            // is based upon the assumption that we do.
+            _ => (self.quinn_config.client_config.clone(), "l".to_owned()),
+        };
+        if version == ProtocolVersion::Draft29 {
+            client_config.version(0xff00_001d);
+        }
+        Ok(Box::pin(async move {
+            let connecting = endpoint
+                .connect_with(client_config, socket_addr, &server_name)
+                .map_err(ConnectError)?;
+            let Some(peer_id) = zero_rtt_peer else {
+                return Connecting::new(connecting, handshake_timeout).await;
+            };
+            match connecting.into_0rtt() {
+                // The remote authenticated as `peer_id` in the session being resumed.
+                Ok((connection, _)) => Ok((peer_id, Connection::new(connection, true))),
+                // No session ticket of the peer is stored.
+                Err(connecting) => Connecting::new(connecting, handshake_timeout).await,
+            }
+        }))
+    }
+
     /// Pick any listener to use for dialing.
     fn eligible_listener(&mut self, socket_addr: &SocketAddr) -> Option<&mut Listener<P>> {
         let mut listeners: Vec<_> = self
@@ -188,8 +298,17 @@ impl<P: Provider> GenTransport<P> {
             Some(socket2::Protocol::UDP),
         )?;
         if socket_addr.is_ipv6() {
-            socket.set_only_v6(true)?;
+            socket.set_only_v6(self.ipv6_only)?;
+        }
+        #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+        if self.reuse_port {
+            socket.set_reuse_port(true)?;
         }
+        set_buffer_sizes(
+            SockRef::from(&socket),
+            self.send_buffer_size,
+            self.recv_buffer_size,
+        )?;
 
         socket.bind(&socket_addr.into())?;
 
@@ -257,49 +376,17 @@ impl<P: Provider> Transport for GenTransport<P> {
     }
 
     fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
-        let (socket_addr, version, _peer_id) = self.remote_multiaddr_to_socketaddr(addr, true)?;
-
-        let endpoint = match self.eligible_listener(&socket_addr) {
-            None => {
-                // No listener. Get or create an explicit dialer.
-                let socket_family = socket_addr.ip().into();
-                let dialer = match self.dialer.entry(socket_family) {
-                    Entry::Occupied(occupied) => occupied.get().clone(),
-                    Entry::Vacant(vacant) => {
-                        if let Some(waker) = self.waker.take() {
-                            waker.wake();
-                        }
-                        let listen_socket_addr = match socket_family {
-                            SocketFamily::Ipv4 => SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0),
-                            SocketFamily::Ipv6 => SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0),
-                        };
-                        let socket =
-                            UdpSocket::bind(listen_socket_addr).map_err(Self::Error::from)?;
-                        let endpoint_config = self.quinn_config.endpoint_config.clone();
-                        let endpoint = Self::new_endpoint(endpoint_config, None, socket)?;
+        self.do_dial(addr, None)
+    }
 
-                        vacant.insert(endpoint.clone());
-                        endpoint
-                    }
-                };
-                dialer
-            }
-            Some(listener) => listener.endpoint.clone(),
-        };
-        let handshake_timeout = self.handshake_timeout;
-        let mut client_config = self.quinn_config.client_config.clone();
-        if version == ProtocolVersion::Draft29 {
-            client_config.version(0xff00_001d);
-        }
-        Ok(Box::pin(async move {
-            // This `"l"` seems necessary because an empty string is an invalid domain
-            // name. While we don't use domain names, the underlying rustls library
-            // is based upon the assumption that we do.
-            let connecting = endpoint
-                .connect_with(client_config, socket_addr, "l")
-                .map_err(ConnectError)?;
-            Connecting::new(connecting, handshake_timeout).await
-        }))
+    /// Dials from the endpoint of the listener bound to the given `port`. If there is no such
+    /// listener, the endpoint is chosen as for [`Transport::dial`].
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.do_dial(addr, Some(port))
     }
 
     fn dial_as_listener(
@@ -675,6 +762,21 @@ fn ip_to_listenaddr(
     Some(socketaddr_to_multiaddr(&socket_addr, version))
 }
 
+/// Sets `SO_SNDBUF` and `SO_RCVBUF` on the given socket, if configured.
+fn set_buffer_sizes(
+    socket: SockRef<'_>,
+    send: Option<usize>,
+    recv: Option<usize>,
+) -> io::Result<()> {
+    if let Some(size) = send {
+        socket.set_send_buffer_size(size)?;
+    }
+    if let Some(size) = recv {
+        socket.set_recv_buffer_size(size)?;
+    }
+    Ok(())
+}
+
 /// Tries to turn a QUIC multiaddress into a UDP [`SocketAddr`]. Returns None if the format
 /// of the multiaddr is wrong.
 fn multiaddr_to_socketaddr(
@@ -927,6 +1029,22 @@ mod tests {
         assert!(!transport.dialer.contains_key(&SocketFamily::Ipv4));
     }
 
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_dial_from_port_without_listener() {
+        let keypair = libp2p_identity::Keypair::generate_ed25519();
+        let config = Config::new(&keypair);
+        let mut transport = crate::tokio::Transport::new(config);
+
+        match transport.dial_from_port("/ip4/123.45.67.8/udp/1234/quic-v1".parse().unwrap(), 4001) {
+            Err(TransportError::Other(Error::Io(e))) => {
+                assert_eq!(e.kind(), io::ErrorKind::Unsupported)
+            }
+            _ => panic!("Expect dialing from a port without a listener on it to fail."),
+        }
+        assert!(transport.dialer.is_empty());
+    }
+
     #[cfg(feature = "tokio")]
     #[tokio::test]
     async fn test_listens_ipv4_ipv6_separately() {
@@ -951,4 +1069,47 @@ mod tests {
             )
             .unwrap();
     }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_ipv6_only_sets_socket_option() {
+        let keypair = libp2p_identity::Keypair::generate_ed25519();
+        let unspecified = SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0);
+
+        for ipv6_only in [true, false] {
+            let mut config = Config::new(&keypair);
+            config.ipv6_only = ipv6_only;
+            let transport = crate::tokio::Transport::new(config);
+
+            let socket = transport.create_socket(unspecified).unwrap();
+
+            assert_eq!(
+                socket2::SockRef::from(&socket).only_v6().unwrap(),
+                ipv6_only
+            );
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn socket_buffer_sizes_are_set() {
+        const SEND: usize = 1024 * 1024;
+        const RECV: usize = 1024 * 1024;
+
+        let keypair = libp2p_identity::Keypair::generate_ed25519();
+        let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+        let bind = |config: Config| {
+            let socket = crate::tokio::Transport::new(config)
+                .create_socket(addr)
+                .unwrap();
+            socket2::Socket::from(socket)
+        };
+        let default = bind(Config::new(&keypair));
+        let socket = bind(Config::new(&keypair).with_socket_buffer_sizes(SEND, RECV));
+
+        // The operating system may round the sizes or cap them at a system-wide maximum, e.g.
+        // `net.core.wmem_max` on Linux, thus only compare them with the defaults.
+        assert!(socket.send_buffer_size().unwrap() > default.send_buffer_size().unwrap());
+        assert!(socket.recv_buffer_size().unwrap() > default.recv_buffer_size().unwrap());
+    }
 }
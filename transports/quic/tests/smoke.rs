@@ -352,6 +352,54 @@ async fn draft_29_support() {
     }
 }
 
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn redial_resumes_session_with_0rtt() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let a_keypair = generate_tls_keypair();
+    let a_peer_id = a_keypair.public().to_peer_id();
+    let mut a_transport =
+        quic::tokio::Transport::new(quic::Config::new(&a_keypair).with_0rtt(true)).boxed();
+    let mut b_transport =
+        quic::tokio::Transport::new(quic::Config::new(&generate_tls_keypair()).with_0rtt(true))
+            .boxed();
+
+    a_transport
+        .listen_on(
+            ListenerId::next(),
+            "/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap(),
+        )
+        .unwrap();
+    let a_addr = match a_transport.next().await {
+        Some(TransportEvent::NewAddress { listen_addr, .. }) => {
+            listen_addr.with(Protocol::P2p(a_peer_id))
+        }
+        e => panic!("{e:?}"),
+    };
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Some(event) = a_transport.next().await {
+            if let Some((upgrade, _)) = event.into_incoming() {
+                connections.push(upgrade.await.unwrap());
+            }
+        }
+    });
+
+    let (peer_id, first) = b_transport.dial(a_addr.clone()).unwrap().await.unwrap();
+    assert_eq!(peer_id, a_peer_id);
+    assert!(!first.is_0rtt());
+
+    // Give the session ticket sent after the handshake time to arrive.
+    Delay::new(Duration::from_millis(200)).await;
+
+    let (peer_id, second) = b_transport.dial(a_addr).unwrap().await.unwrap();
+    assert_eq!(peer_id, a_peer_id);
+    assert!(second.is_0rtt());
+}
+
 #[cfg(feature = "async-std")]
 #[async_std::test]
 async fn backpressure() {
@@ -25,10 +25,14 @@
 //! This crate provides a [`async_io::Transport`] and [`tokio::Transport`], depending on
 //! the enabled features, which implement the [`libp2p_core::Transport`] trait for use as a
 //! transport with `libp2p-core` or `libp2p-swarm`.
+//!
+//! Outgoing connections can be tunneled through a SOCKS5 proxy by wrapping the transport in a
+//! [`socks5::Transport`].
 
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 mod provider;
+pub mod socks5;
 
 #[cfg(feature = "async-io")]
 pub use provider::async_io;
@@ -67,6 +71,10 @@ pub struct Config {
     backlog: u32,
     /// Whether port reuse should be enabled.
     enable_port_reuse: bool,
+    /// `SO_SNDBUF` to set for opened sockets, or `None` to keep default.
+    send_buffer_size: Option<usize>,
+    /// `SO_RCVBUF` to set for opened sockets, or `None` to keep default.
+    recv_buffer_size: Option<usize>,
 }
 
 type Port = u16;
@@ -145,6 +153,22 @@ impl PortReuse {
 
         None
     }
+
+    /// Returns the unspecified local socket address with the given `port` to bind a dial socket
+    /// to, matching the IP protocol version of `remote_ip`.
+    ///
+    /// Returns `None` if port reuse is disabled, as the port may then be in use by another socket.
+    fn local_dial_addr_with_port(&self, remote_ip: &IpAddr, port: Port) -> Option<SocketAddr> {
+        match self {
+            PortReuse::Disabled => None,
+            PortReuse::Enabled { .. } if remote_ip.is_ipv4() => {
+                Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port))
+            }
+            PortReuse::Enabled { .. } => {
+                Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port))
+            }
+        }
+    }
 }
 
 impl Config {
@@ -158,12 +182,16 @@ impl Config {
     ///     See [`Config::ttl`].
     ///   * The size of the listen backlog for new listening sockets is `1024`.
     ///     See [`Config::listen_backlog`].
+    ///   * No custom `SO_SNDBUF` and `SO_RCVBUF` are set. The defaults of the OS apply.
+    ///     See [`Config::socket_buffer_sizes`].
     pub fn new() -> Self {
         Self {
             ttl: None,
             nodelay: None,
             backlog: 1024,
             enable_port_reuse: false,
+            send_buffer_size: None,
+            recv_buffer_size: None,
         }
     }
 
@@ -179,12 +207,27 @@ impl Config {
         self
     }
 
-    /// Configures the listen backlog for new listen sockets.
+    /// Configures the listen backlog for new listen sockets, i.e. the number of connections the
+    /// OS queues until they are accepted.
+    ///
+    /// Raise it on servers facing bursts of inbound connections, for which the OS would otherwise
+    /// drop connection attempts. The OS may cap the backlog, e.g. at `net.core.somaxconn` on Linux.
     pub fn listen_backlog(mut self, backlog: u32) -> Self {
         self.backlog = backlog;
         self
     }
 
+    /// Configures the `SO_SNDBUF` and `SO_RCVBUF` options for new sockets.
+    ///
+    /// Larger buffers than the defaults of the OS can improve the throughput of connections with a
+    /// high bandwidth-delay product. The OS may adjust the sizes, e.g. Linux doubles them and caps
+    /// them at `net.core.wmem_max` and `net.core.rmem_max` respectively.
+    pub fn socket_buffer_sizes(mut self, send: usize, recv: usize) -> Self {
+        self.send_buffer_size = Some(send);
+        self.recv_buffer_size = Some(recv);
+        self
+    }
+
     /// Configures port reuse for local sockets, which implies
     /// reuse of listening ports for outgoing connections to
     /// enhance NAT traversal capabilities.
@@ -357,6 +400,12 @@ where
         if let Some(nodelay) = self.config.nodelay {
             socket.set_nodelay(nodelay)?;
         }
+        if let Some(size) = self.config.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.config.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
         socket.set_reuse_address(true)?;
         #[cfg(unix)]
         if let PortReuse::Enabled { .. } = &self.port_reuse {
@@ -365,16 +414,87 @@ where
         Ok(socket)
     }
 
+    /// Creates a non-blocking listen socket bound to the given address, with the configured
+    /// listen backlog.
+    fn bind_listener(&self, socket_addr: SocketAddr) -> io::Result<TcpListener> {
+        let socket = self.create_socket(socket_addr)?;
+        socket.bind(&socket_addr.into())?;
+        socket.listen(self.config.backlog as _)?;
+        socket.set_nonblocking(true)?;
+        Ok(socket.into())
+    }
+
+    /// Dials the given address, binding the dial socket to the given local `port` or, if `None`,
+    /// to the port of a listening socket if port reuse is enabled.
+    fn do_dial(
+        &mut self,
+        addr: Multiaddr,
+        port: Option<Port>,
+    ) -> Result<
+        Pin<Box<dyn Future<Output = io::Result<T::Stream>> + Send>>,
+        TransportError<io::Error>,
+    >
+    where
+        T: 'static,
+    {
+        let socket_addr = if let Ok(socket_addr) = multiaddr_to_socketaddr(addr.clone()) {
+            if socket_addr.port() == 0 || socket_addr.ip().is_unspecified() {
+                return Err(TransportError::MultiaddrNotSupported(addr));
+            }
+            socket_addr
+        } else {
+            return Err(TransportError::MultiaddrNotSupported(addr));
+        };
+        tracing::debug!(address=%socket_addr, "dialing address");
+
+        let socket = self
+            .create_socket(socket_addr)
+            .map_err(TransportError::Other)?;
+
+        let local_addr = match port {
+            Some(port) => Some(
+                self.port_reuse
+                    .local_dial_addr_with_port(&socket_addr.ip(), port)
+                    .ok_or_else(|| {
+                        TransportError::Other(io::Error::new(
+                            io::ErrorKind::Unsupported,
+                            "dialing from a given port requires port reuse",
+                        ))
+                    })?,
+            ),
+            None => self.port_reuse.local_dial_addr(&socket_addr.ip()),
+        };
+        if let Some(addr) = local_addr {
+            tracing::trace!(address=%addr, "Binding dial socket to local address");
+            socket.bind(&addr.into()).map_err(TransportError::Other)?;
+        }
+
+        socket
+            .set_nonblocking(true)
+            .map_err(TransportError::Other)?;
+
+        Ok(async move {
+            // [`Transport::dial`] should do no work unless the returned [`Future`] is polled. Thus
+            // do the `connect` call within the [`Future`].
+            match socket.connect(&socket_addr.into()) {
+                Ok(()) => {}
+                Err(err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                Err(err) => return Err(err),
+            };
+
+            let stream = T::new_stream(socket.into()).await?;
+            Ok(stream)
+        }
+        .boxed())
+    }
+
     fn do_listen(
         &mut self,
         id: ListenerId,
         socket_addr: SocketAddr,
     ) -> io::Result<ListenStream<T>> {
-        let socket = self.create_socket(socket_addr)?;
-        socket.bind(&socket_addr.into())?;
-        socket.listen(self.config.backlog as _)?;
-        socket.set_nonblocking(true)?;
-        let listener: TcpListener = socket.into();
+        let listener = self.bind_listener(socket_addr)?;
         let local_addr = listener.local_addr()?;
 
         if local_addr.ip().is_unspecified() {
@@ -457,43 +577,7 @@ where
     }
 
     fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
-        let socket_addr = if let Ok(socket_addr) = multiaddr_to_socketaddr(addr.clone()) {
-            if socket_addr.port() == 0 || socket_addr.ip().is_unspecified() {
-                return Err(TransportError::MultiaddrNotSupported(addr));
-            }
-            socket_addr
-        } else {
-            return Err(TransportError::MultiaddrNotSupported(addr));
-        };
-        tracing::debug!(address=%socket_addr, "dialing address");
-
-        let socket = self
-            .create_socket(socket_addr)
-            .map_err(TransportError::Other)?;
-
-        if let Some(addr) = self.port_reuse.local_dial_addr(&socket_addr.ip()) {
-            tracing::trace!(address=%addr, "Binding dial socket to listen socket address");
-            socket.bind(&addr.into()).map_err(TransportError::Other)?;
-        }
-
-        socket
-            .set_nonblocking(true)
-            .map_err(TransportError::Other)?;
-
-        Ok(async move {
-            // [`Transport::dial`] should do no work unless the returned [`Future`] is polled. Thus
-            // do the `connect` call within the [`Future`].
-            match socket.connect(&socket_addr.into()) {
-                Ok(()) => {}
-                Err(err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
-                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
-                Err(err) => return Err(err),
-            };
-
-            let stream = T::new_stream(socket.into()).await?;
-            Ok(stream)
-        }
-        .boxed())
+        self.do_dial(addr, None)
     }
 
     fn dial_as_listener(
@@ -503,6 +587,17 @@ where
         self.dial(addr)
     }
 
+    /// Binds the dial socket to the given `port` if port reuse is enabled, see
+    /// [`Config::port_reuse`]. Otherwise an ephemeral port is used, as for
+    /// [`libp2p_core::Transport::dial`].
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.do_dial(addr, Some(port))
+    }
+
     /// When port reuse is disabled and hence ephemeral local ports are
     /// used for outgoing connections, the returned address is the
     /// `observed` address with the port replaced by the port of the
@@ -1146,6 +1241,51 @@ mod tests {
         test("/ip6/::1/tcp/0".parse().unwrap());
     }
 
+    #[cfg(all(unix, feature = "tokio"))]
+    #[test]
+    fn dial_from_port_reuses_port() {
+        let rt = ::tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            // Pick a free port, which is released again once the listener is dropped.
+            let port = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port();
+            let mut tcp = tokio::Transport::new(Config::new().port_reuse(true));
+
+            let mut streams = Vec::new();
+            for _ in 0..2 {
+                let listener = ::tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                    .await
+                    .unwrap();
+                let addr = listener.local_addr().unwrap();
+                let dial = tcp
+                    .dial_from_port(ip_to_multiaddr(addr.ip(), addr.port()), port)
+                    .unwrap();
+                let (stream, (_, remote_addr)) = futures::future::try_join(dial, listener.accept())
+                    .await
+                    .unwrap();
+                assert_eq!(remote_addr.port(), port);
+                streams.push(stream);
+            }
+        });
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn dial_from_port_requires_port_reuse() {
+        let mut tcp = tokio::Transport::new(Config::new());
+
+        match tcp.dial_from_port("/ip4/127.0.0.1/tcp/1".parse().unwrap(), 4001) {
+            Err(TransportError::Other(e)) => assert_eq!(e.kind(), io::ErrorKind::Unsupported),
+            _ => panic!("Expect dialing from a port to fail without port reuse."),
+        }
+    }
+
     #[test]
     fn port_reuse_listening() {
         let _ = tracing_subscriber::fmt()
@@ -1311,6 +1451,53 @@ mod tests {
             .is_none());
     }
 
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn socket_buffer_sizes_are_set() {
+        const SEND: usize = 1024 * 1024;
+        const RECV: usize = 1024 * 1024;
+
+        let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+        let bind = |config: Config| {
+            let socket = tokio::Transport::new(config).create_socket(addr).unwrap();
+            socket.bind(&addr.into()).unwrap();
+            socket
+        };
+        let default = bind(Config::new());
+        let socket = bind(Config::new().socket_buffer_sizes(SEND, RECV));
+
+        // The OS may round the sizes or cap them at a system-wide maximum, e.g.
+        // `net.core.wmem_max` on Linux, thus only compare them with the defaults.
+        assert!(socket.send_buffer_size().unwrap() > default.send_buffer_size().unwrap());
+        assert!(socket.recv_buffer_size().unwrap() > default.recv_buffer_size().unwrap());
+    }
+
+    #[cfg(all(target_os = "linux", feature = "tokio"))]
+    #[test]
+    fn listen_backlog_is_set() {
+        const BACKLOG: u32 = 7;
+
+        let transport = tokio::Transport::new(Config::new().listen_backlog(BACKLOG));
+        let listener = transport
+            .bind_listener(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0))
+            .unwrap();
+
+        // For listen sockets, Linux reports the backlog as `tcpi_sacked` of `TCP_INFO`.
+        let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::tcp_info>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(
+                std::os::fd::AsRawFd::as_raw_fd(&listener),
+                libc::IPPROTO_TCP,
+                libc::TCP_INFO,
+                &mut info as *mut _ as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        assert_eq!(ret, 0);
+        assert_eq!(info.tcpi_sacked, BACKLOG);
+    }
+
     #[test]
     fn test_remove_listener() {
         let _ = tracing_subscriber::fmt()
@@ -0,0 +1,685 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Dialing through a SOCKS5 proxy.
+//!
+//! The [`Transport`] of this module wraps a TCP transport, tunneling all outgoing connections
+//! through a SOCKS5 proxy ([RFC 1928](https://www.rfc-editor.org/rfc/rfc1928)), optionally
+//! authenticating with a username and password
+//! ([RFC 1929](https://www.rfc-editor.org/rfc/rfc1929)). Listening is left to the wrapped
+//! transport.
+//!
+//! ```no_run
+//! # #[cfg(feature = "tokio")]
+//! # fn main() {
+//! use libp2p_tcp::{socks5, tokio};
+//!
+//! let proxy = socks5::Config::new("127.0.0.1:1080".parse().unwrap())
+//!     .with_credentials("user", "password");
+//! let transport = socks5::Transport::new(tokio::Transport::default(), proxy);
+//! # }
+//! # #[cfg(not(feature = "tokio"))]
+//! # fn main() {}
+//! ```
+
+use futures::{
+    future::BoxFuture,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    prelude::*,
+};
+use libp2p_core::{
+    multiaddr::{Multiaddr, Protocol},
+    transport::{ListenerId, TransportError, TransportEvent},
+};
+use std::{
+    fmt, io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+const VERSION: u8 = 0x05;
+const METHOD_NO_AUTHENTICATION: u8 = 0x00;
+const METHOD_USERNAME_PASSWORD: u8 = 0x02;
+const METHOD_NO_ACCEPTABLE: u8 = 0xff;
+const USERNAME_PASSWORD_VERSION: u8 = 0x01;
+const COMMAND_CONNECT: u8 = 0x01;
+const ADDRESS_TYPE_IPV4: u8 = 0x01;
+const ADDRESS_TYPE_DOMAIN: u8 = 0x03;
+const ADDRESS_TYPE_IPV6: u8 = 0x04;
+const REPLY_SUCCEEDED: u8 = 0x00;
+
+/// The configuration of the SOCKS5 proxy to dial through.
+#[derive(Clone, Debug)]
+pub struct Config {
+    proxy: SocketAddr,
+    credentials: Option<Credentials>,
+}
+
+impl Config {
+    /// Creates a configuration for the proxy listening on the given address, which does not
+    /// require authentication.
+    pub fn new(proxy: SocketAddr) -> Self {
+        Self {
+            proxy,
+            credentials: None,
+        }
+    }
+
+    /// Authenticate with the given username and password, in case the proxy requires it.
+    ///
+    /// Both must be between 1 and 255 bytes long, otherwise dialing fails.
+    pub fn with_credentials(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.credentials = Some(Credentials {
+            username: username.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    /// The address of the proxy.
+    pub fn proxy(&self) -> SocketAddr {
+        self.proxy
+    }
+}
+
+impl From<SocketAddr> for Config {
+    fn from(proxy: SocketAddr) -> Self {
+        Config::new(proxy)
+    }
+}
+
+#[derive(Clone)]
+struct Credentials {
+    username: String,
+    password: String,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`libp2p_core::Transport`] dialing through a SOCKS5 proxy.
+///
+/// Listening, i.e. [`libp2p_core::Transport::listen_on`], is delegated to the wrapped transport,
+/// which is also used to connect to the proxy.
+#[derive(Debug)]
+pub struct Transport<T> {
+    inner: T,
+    config: Config,
+}
+
+impl<T> Transport<T> {
+    /// Wraps the given TCP transport, dialing through the proxy described by `config`.
+    pub fn new(inner: T, config: Config) -> Self {
+        Self { inner, config }
+    }
+}
+
+impl<T> libp2p_core::Transport for Transport<T>
+where
+    T: libp2p_core::Transport<Error = io::Error> + Unpin,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Dial: Send + 'static,
+{
+    type Output = T::Output;
+    type Error = io::Error;
+    type Dial = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+    type ListenerUpgrade = T::ListenerUpgrade;
+
+    fn listen_on(
+        &mut self,
+        id: ListenerId,
+        addr: Multiaddr,
+    ) -> Result<(), TransportError<Self::Error>> {
+        self.inner.listen_on(id, addr)
+    }
+
+    fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.inner.remove_listener(id)
+    }
+
+    fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.do_dial(addr, None)
+    }
+
+    fn dial_as_listener(
+        &mut self,
+        addr: Multiaddr,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.dial(addr)
+    }
+
+    /// Binds the connection to the proxy to the given `port`, see
+    /// [`libp2p_core::Transport::dial_from_port`]. The port of the connection from the proxy to the
+    /// destination is up to the proxy.
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.do_dial(addr, Some(port))
+    }
+
+    fn address_translation(&self, listen: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.inner.address_translation(listen, observed)
+    }
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+        Pin::new(&mut self.inner).poll(cx)
+    }
+}
+
+impl<T> Transport<T>
+where
+    T: libp2p_core::Transport<Error = io::Error> + Unpin,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Dial: Send + 'static,
+{
+    fn do_dial(
+        &mut self,
+        addr: Multiaddr,
+        port: Option<u16>,
+    ) -> Result<<Self as libp2p_core::Transport>::Dial, TransportError<io::Error>> {
+        let Some(target) = Target::from_multiaddr(&addr) else {
+            return Err(TransportError::MultiaddrNotSupported(addr));
+        };
+        tracing::debug!(address=%addr, proxy=%self.config.proxy, "dialing address through SOCKS5 proxy");
+
+        let proxy = Multiaddr::empty()
+            .with(self.config.proxy.ip().into())
+            .with(Protocol::Tcp(self.config.proxy.port()));
+        let dial = match port {
+            Some(port) => self.inner.dial_from_port(proxy, port),
+            None => self.inner.dial(proxy),
+        }
+        .map_err(|e| match e {
+            TransportError::MultiaddrNotSupported(_) => TransportError::MultiaddrNotSupported(addr),
+            TransportError::Other(e) => TransportError::Other(e),
+        })?;
+        let credentials = self.config.credentials.clone();
+
+        Ok(async move {
+            let mut stream = dial.await?;
+            handshake(&mut stream, &target, credentials.as_ref()).await?;
+            Ok(stream)
+        }
+        .boxed())
+    }
+}
+
+/// The destination of a connection, as requested from the proxy.
+#[derive(Debug, PartialEq)]
+enum Target {
+    Ip(SocketAddr),
+    Domain(String, u16),
+}
+
+impl Target {
+    /// Extracts the target from an `/ip4`, `/ip6` or `/dns*` address, followed by `/tcp` and an
+    /// optional `/p2p`.
+    ///
+    /// Domain names are resolved by the proxy.
+    fn from_multiaddr(addr: &Multiaddr) -> Option<Self> {
+        let mut iter = addr.iter();
+        let host = iter.next()?;
+        let Protocol::Tcp(port) = iter.next()? else {
+            return None;
+        };
+        match iter.next() {
+            None | Some(Protocol::P2p(_)) if iter.next().is_none() => {}
+            _ => return None,
+        }
+        if port == 0 {
+            return None;
+        }
+
+        let target = match host {
+            Protocol::Ip4(ip) => Target::Ip(SocketAddr::new(ip.into(), port)),
+            Protocol::Ip6(ip) => Target::Ip(SocketAddr::new(ip.into(), port)),
+            Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => {
+                Target::Domain(name.into_owned(), port)
+            }
+            _ => return None,
+        };
+        if matches!(target, Target::Ip(addr) if addr.ip().is_unspecified()) {
+            return None;
+        }
+
+        Some(target)
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        let port = match self {
+            Target::Ip(addr) => {
+                match addr.ip() {
+                    IpAddr::V4(ip) => {
+                        buf.push(ADDRESS_TYPE_IPV4);
+                        buf.extend_from_slice(&ip.octets());
+                    }
+                    IpAddr::V6(ip) => {
+                        buf.push(ADDRESS_TYPE_IPV6);
+                        buf.extend_from_slice(&ip.octets());
+                    }
+                }
+                addr.port()
+            }
+            Target::Domain(name, port) => {
+                buf.push(ADDRESS_TYPE_DOMAIN);
+                push_with_len(buf, name.as_bytes(), "domain name")?;
+                *port
+            }
+        };
+        buf.extend_from_slice(&port.to_be_bytes());
+
+        Ok(())
+    }
+}
+
+/// Negotiates a connection to `target` on a stream to the proxy.
+async fn handshake<S>(
+    stream: &mut S,
+    target: &Target,
+    credentials: Option<&Credentials>,
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let greeting: &[u8] = if credentials.is_some() {
+        &[
+            VERSION,
+            2,
+            METHOD_NO_AUTHENTICATION,
+            METHOD_USERNAME_PASSWORD,
+        ]
+    } else {
+        &[VERSION, 1, METHOD_NO_AUTHENTICATION]
+    };
+    stream.write_all(greeting).await?;
+    stream.flush().await?;
+
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await?;
+    check_version(choice[0])?;
+    match (choice[1], credentials) {
+        (METHOD_NO_AUTHENTICATION, _) => {}
+        (METHOD_USERNAME_PASSWORD, Some(credentials)) => authenticate(stream, credentials).await?,
+        (METHOD_NO_ACCEPTABLE, _) => {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "SOCKS5 proxy accepts none of the offered authentication methods",
+            ))
+        }
+        (method, _) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("SOCKS5 proxy selected an unexpected authentication method {method:#04x}"),
+            ))
+        }
+    }
+
+    let mut request = vec![VERSION, COMMAND_CONNECT, 0x00];
+    target.encode(&mut request)?;
+    stream.write_all(&request).await?;
+    stream.flush().await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    check_version(reply[0])?;
+    if reply[1] != REPLY_SUCCEEDED {
+        return Err(reply_error(reply[1]));
+    }
+    // Skip the address the proxy bound for the connection.
+    let bound_addr_len = match reply[3] {
+        ADDRESS_TYPE_IPV4 => 4,
+        ADDRESS_TYPE_IPV6 => 16,
+        ADDRESS_TYPE_DOMAIN => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            len[0] as usize
+        }
+        address_type => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("SOCKS5 proxy replied with unknown address type {address_type:#04x}"),
+            ))
+        }
+    };
+    let mut bound_addr = vec![0u8; bound_addr_len + 2];
+    stream.read_exact(&mut bound_addr).await?;
+
+    Ok(())
+}
+
+/// Performs the username/password authentication of RFC 1929.
+async fn authenticate<S>(stream: &mut S, credentials: &Credentials) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut request = vec![USERNAME_PASSWORD_VERSION];
+    push_with_len(&mut request, credentials.username.as_bytes(), "username")?;
+    push_with_len(&mut request, credentials.password.as_bytes(), "password")?;
+    stream.write_all(&request).await?;
+    stream.flush().await?;
+
+    let mut response = [0u8; 2];
+    stream.read_exact(&mut response).await?;
+    if response[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "SOCKS5 proxy rejected the credentials",
+        ));
+    }
+
+    Ok(())
+}
+
+fn push_with_len(buf: &mut Vec<u8>, bytes: &[u8], what: &str) -> io::Result<()> {
+    let len = u8::try_from(bytes.len())
+        .ok()
+        .filter(|len| *len > 0)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("SOCKS5 {what} must be between 1 and 255 bytes long"),
+            )
+        })?;
+    buf.push(len);
+    buf.extend_from_slice(bytes);
+
+    Ok(())
+}
+
+fn check_version(version: u8) -> io::Result<()> {
+    if version != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unexpected SOCKS version {version:#04x}"),
+        ));
+    }
+
+    Ok(())
+}
+
+fn reply_error(reply: u8) -> io::Error {
+    let (kind, reason) = match reply {
+        0x01 => (io::ErrorKind::Other, "general SOCKS server failure"),
+        0x02 => (
+            io::ErrorKind::PermissionDenied,
+            "connection not allowed by ruleset",
+        ),
+        0x03 => (io::ErrorKind::Other, "network unreachable"),
+        0x04 => (io::ErrorKind::Other, "host unreachable"),
+        0x05 => (io::ErrorKind::ConnectionRefused, "connection refused"),
+        0x06 => (io::ErrorKind::TimedOut, "TTL expired"),
+        0x07 => (io::ErrorKind::Unsupported, "command not supported"),
+        0x08 => (io::ErrorKind::Unsupported, "address type not supported"),
+        _ => (io::ErrorKind::Other, "unknown failure"),
+    };
+
+    io::Error::new(kind, format!("SOCKS5 proxy failed to connect: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "tokio")]
+    use futures::channel::oneshot;
+    #[cfg(feature = "tokio")]
+    use libp2p_core::Transport as _;
+    #[cfg(feature = "tokio")]
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+    #[test]
+    fn multiaddr_to_target() {
+        assert_eq!(
+            Target::from_multiaddr(&"/ip4/1.2.3.4/tcp/10".parse().unwrap()),
+            Some(Target::Ip("1.2.3.4:10".parse().unwrap()))
+        );
+        assert_eq!(
+            Target::from_multiaddr(&"/ip6/::1/tcp/10".parse().unwrap()),
+            Some(Target::Ip("[::1]:10".parse().unwrap()))
+        );
+        assert_eq!(
+            Target::from_multiaddr(
+                &"/dns4/example.com/tcp/10/p2p/12D3KooWGQmdpzHXCqLno4mMxWXKNFQHASBeF99gTm2JR8Vu5Bdc"
+                    .parse()
+                    .unwrap()
+            ),
+            Some(Target::Domain("example.com".to_owned(), 10))
+        );
+        assert_eq!(
+            Target::from_multiaddr(&"/ip4/0.0.0.0/tcp/10".parse().unwrap()),
+            None
+        );
+        assert_eq!(
+            Target::from_multiaddr(&"/ip4/1.2.3.4/tcp/0".parse().unwrap()),
+            None
+        );
+        assert_eq!(
+            Target::from_multiaddr(&"/ip4/1.2.3.4/udp/10/quic-v1".parse().unwrap()),
+            None
+        );
+        assert_eq!(
+            Target::from_multiaddr(&"/ip4/1.2.3.4/tcp/10/ws".parse().unwrap()),
+            None
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn dial_through_proxy() {
+        let (proxy, requested) = spawn_proxy(None).await;
+        let (target, listener) = spawn_target().await;
+
+        let mut transport = Transport::new(crate::tokio::Transport::default(), Config::new(proxy));
+        let mut stream = transport
+            .dial(
+                format!("/ip4/127.0.0.1/tcp/{}", target.port())
+                    .parse()
+                    .unwrap(),
+            )
+            .unwrap()
+            .await
+            .unwrap();
+
+        assert_eq!(requested.await.unwrap(), Target::Ip(target));
+        stream.write_all(b"hello").await.unwrap();
+        assert_eq!(listener.await.unwrap(), *b"hello");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn dial_through_proxy_with_credentials() {
+        let (proxy, requested) = spawn_proxy(Some(("user", "secret"))).await;
+        let (target, listener) = spawn_target().await;
+
+        let mut transport = Transport::new(
+            crate::tokio::Transport::default(),
+            Config::new(proxy).with_credentials("user", "secret"),
+        );
+        let mut stream = transport
+            .dial(
+                format!("/dns/localhost/tcp/{}", target.port())
+                    .parse()
+                    .unwrap(),
+            )
+            .unwrap()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            requested.await.unwrap(),
+            Target::Domain("localhost".to_owned(), target.port())
+        );
+        stream.write_all(b"hello").await.unwrap();
+        assert_eq!(listener.await.unwrap(), *b"hello");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn dial_fails_with_wrong_credentials() {
+        let (proxy, _) = spawn_proxy(Some(("user", "secret"))).await;
+
+        let mut transport = Transport::new(
+            crate::tokio::Transport::default(),
+            Config::new(proxy).with_credentials("user", "wrong"),
+        );
+        let error = transport
+            .dial("/ip4/127.0.0.1/tcp/1".parse().unwrap())
+            .unwrap()
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn dial_from_port_is_forwarded_to_proxy_connection() {
+        let mut transport = Transport::new(
+            crate::tokio::Transport::default(),
+            Config::new("127.0.0.1:1080".parse().unwrap()),
+        );
+
+        match transport.dial_from_port("/ip4/1.2.3.4/tcp/10".parse().unwrap(), 4001) {
+            Err(TransportError::Other(e)) => assert_eq!(e.kind(), io::ErrorKind::Unsupported),
+            _ => panic!("Expect the wrapped transport without port reuse to refuse the port."),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Spawns a listener reading 5 bytes from the first incoming connection.
+    async fn spawn_target() -> (SocketAddr, oneshot::Receiver<[u8; 5]>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 5];
+            stream.read_exact(&mut buf).await.unwrap();
+            let _ = tx.send(buf);
+        });
+
+        (addr, rx)
+    }
+
+    #[cfg(feature = "tokio")]
+    /// Spawns a minimal SOCKS5 proxy, serving a single `CONNECT` request and reporting the
+    /// requested target.
+    async fn spawn_proxy(
+        credentials: Option<(&'static str, &'static str)>,
+    ) -> (SocketAddr, oneshot::Receiver<Target>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut header = [0u8; 2];
+            stream.read_exact(&mut header).await.unwrap();
+            assert_eq!(header[0], VERSION);
+            let mut methods = vec![0u8; header[1] as usize];
+            stream.read_exact(&mut methods).await.unwrap();
+
+            if let Some((username, password)) = credentials {
+                assert!(methods.contains(&METHOD_USERNAME_PASSWORD));
+                stream
+                    .write_all(&[VERSION, METHOD_USERNAME_PASSWORD])
+                    .await
+                    .unwrap();
+                assert_eq!(stream.read_u8().await.unwrap(), USERNAME_PASSWORD_VERSION);
+                let received_username = read_with_len(&mut stream).await;
+                let received_password = read_with_len(&mut stream).await;
+                if received_username != username || received_password != password {
+                    stream
+                        .write_all(&[USERNAME_PASSWORD_VERSION, 0x01])
+                        .await
+                        .unwrap();
+                    return;
+                }
+                stream
+                    .write_all(&[USERNAME_PASSWORD_VERSION, 0x00])
+                    .await
+                    .unwrap();
+            } else {
+                assert!(methods.contains(&METHOD_NO_AUTHENTICATION));
+                stream
+                    .write_all(&[VERSION, METHOD_NO_AUTHENTICATION])
+                    .await
+                    .unwrap();
+            }
+
+            let mut request = [0u8; 4];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[..3], [VERSION, COMMAND_CONNECT, 0x00]);
+            let target = match request[3] {
+                ADDRESS_TYPE_IPV4 => {
+                    let mut ip = [0u8; 4];
+                    stream.read_exact(&mut ip).await.unwrap();
+                    let port = stream.read_u16().await.unwrap();
+                    Target::Ip(SocketAddr::new(ip.into(), port))
+                }
+                ADDRESS_TYPE_DOMAIN => {
+                    let name = read_with_len(&mut stream).await;
+                    let port = stream.read_u16().await.unwrap();
+                    Target::Domain(name, port)
+                }
+                t => panic!("Unexpected address type {t}"),
+            };
+            let mut upstream = match &target {
+                Target::Ip(addr) => tokio::net::TcpStream::connect(addr).await.unwrap(),
+                Target::Domain(name, port) => {
+                    tokio::net::TcpStream::connect((name.as_str(), *port))
+                        .await
+                        .unwrap()
+                }
+            };
+            let bound = upstream.local_addr().unwrap();
+            let mut reply = vec![VERSION, REPLY_SUCCEEDED, 0x00];
+            Target::Ip(bound).encode(&mut reply).unwrap();
+            stream.write_all(&reply).await.unwrap();
+            let _ = tx.send(target);
+
+            let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+        });
+
+        (addr, rx)
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn read_with_len(stream: &mut tokio::net::TcpStream) -> String {
+        let len = stream.read_u8().await.unwrap();
+        let mut buf = vec![0u8; len as usize];
+        stream.read_exact(&mut buf).await.unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+}
@@ -26,7 +26,12 @@
 //!
 //! # Usage
 //!
-//! The `UdsConfig` transport supports multiaddresses of the form `/unix//tmp/foo`.
+//! The `UdsConfig` transport supports multiaddresses of the form `/unix/%2Ftmp%2Ffoo`, i.e. with
+//! an absolute path, percent-encoded as a single multiaddr component.
+//!
+//! A socket file left behind at the path, e.g. by a crashed process, is removed when listening on
+//! it, unless a listener still accepts connections on it. The socket file is removed once the
+//! listener is closed.
 //!
 //! The `UdsConfig` structs implements the `Transport` trait of the `core` library. See the
 //! documentation of `core` and of libp2p in general to learn how to use the `Transport` trait.
@@ -50,9 +55,13 @@ use libp2p_core::{
     Transport,
 };
 use std::collections::VecDeque;
+use std::os::unix::fs::FileTypeExt;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use std::{io, path::PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
 pub type Listener<T> = BoxStream<
     'static,
@@ -96,47 +105,51 @@ macro_rules! codegen {
                 addr: Multiaddr,
             ) -> Result<(), TransportError<Self::Error>> {
                 if let Ok(path) = multiaddr_to_path(&addr) {
-                    #[allow(clippy::redundant_closure_call)]
-                    let listener = $build_listener(path)
-                        .map_err(Err)
-                        .map_ok(move |listener| {
-                            stream::once({
+                    let listener = async move {
+                        remove_stale_socket(&path)?;
+                        #[allow(clippy::redundant_closure_call)]
+                        let listener = $build_listener(path.clone()).await?;
+                        Ok::<_, io::Error>((listener, SocketFile(path)))
+                    }
+                    .map_err(Err)
+                    .map_ok(move |(listener, socket_file)| {
+                        stream::once({
+                            let addr = addr.clone();
+                            async move {
+                                tracing::debug!(address=%addr, "Now listening on address");
+                                Ok(TransportEvent::NewAddress {
+                                    listener_id: id,
+                                    listen_addr: addr,
+                                })
+                            }
+                        })
+                        .chain(stream::unfold(
+                            (listener, socket_file),
+                            move |(listener, socket_file)| {
                                 let addr = addr.clone();
                                 async move {
-                                    tracing::debug!(address=%addr, "Now listening on address");
-                                    Ok(TransportEvent::NewAddress {
-                                        listener_id: id,
-                                        listen_addr: addr,
-                                    })
-                                }
-                            })
-                            .chain(stream::unfold(
-                                listener,
-                                move |listener| {
-                                    let addr = addr.clone();
-                                    async move {
-                                        let event = match listener.accept().await {
-                                            Ok((stream, _)) => {
-                                                tracing::debug!(address=%addr, "incoming connection on address");
-                                                TransportEvent::Incoming {
-                                                    upgrade: future::ok(stream),
-                                                    local_addr: addr.clone(),
-                                                    send_back_addr: addr.clone(),
-                                                    listener_id: id,
-                                                }
-                                            }
-                                            Err(error) => TransportEvent::ListenerError {
+                                    let event = match listener.accept().await {
+                                        Ok((stream, _)) => {
+                                            tracing::debug!(address=%addr, "incoming connection on address");
+                                            TransportEvent::Incoming {
+                                                upgrade: future::ok(<$unix_stream>::from(stream)),
+                                                local_addr: addr.clone(),
+                                                send_back_addr: addr.clone(),
                                                 listener_id: id,
-                                                error,
-                                            },
-                                        };
-                                        Some((Ok(event), listener))
-                                    }
-                                },
-                            ))
-                        })
-                        .try_flatten_stream()
-                        .boxed();
+                                            }
+                                        }
+                                        Err(error) => TransportEvent::ListenerError {
+                                            listener_id: id,
+                                            error,
+                                        },
+                                    };
+                                    Some((Ok(event), (listener, socket_file)))
+                                }
+                            },
+                        ))
+                    })
+                    .try_flatten_stream()
+                    .boxed();
                     self.listeners.push_back((id, listener));
                     Ok(())
                 } else {
@@ -224,14 +237,113 @@ codegen!(
     |addr| async move { async_std::os::unix::net::UnixListener::bind(&addr).await },
     async_std::os::unix::net::UnixStream,
 );
+
+/// The [`async_std::os::unix::net::UnixStream`] yielded by [`UdsConfig`].
+#[cfg(feature = "async-std")]
+pub type UnixStream = async_std::os::unix::net::UnixStream;
 #[cfg(feature = "tokio")]
 codegen!(
     "tokio",
     TokioUdsConfig,
     |addr| async move { tokio::net::UnixListener::bind(&addr) },
-    tokio::net::UnixStream,
+    TokioUnixStream,
 );
 
+/// A [`tokio::net::UnixStream`] that implements [`AsyncRead`] and [`AsyncWrite`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TokioUnixStream(pub tokio::net::UnixStream);
+
+#[cfg(feature = "tokio")]
+impl TokioUnixStream {
+    async fn connect(path: &Path) -> io::Result<Self> {
+        tokio::net::UnixStream::connect(path).await.map(Self)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl From<tokio::net::UnixStream> for TokioUnixStream {
+    fn from(stream: tokio::net::UnixStream) -> Self {
+        Self(stream)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl From<TokioUnixStream> for tokio::net::UnixStream {
+    fn from(stream: TokioUnixStream) -> Self {
+        stream.0
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncRead for TokioUnixStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        let mut read_buf = tokio::io::ReadBuf::new(buf);
+        futures::ready!(tokio::io::AsyncRead::poll_read(
+            Pin::new(&mut self.0),
+            cx,
+            &mut read_buf
+        ))?;
+        Poll::Ready(Ok(read_buf.filled().len()))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncWrite for TokioUnixStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), io::Error>> {
+        tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.0), cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), io::Error>> {
+        tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.0), cx)
+    }
+}
+
+/// The socket file of a listener, removed once the listener is dropped.
+struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.0) {
+            tracing::debug!(path=%self.0.display(), "Failed to remove socket file: {error}");
+        }
+    }
+}
+
+/// Removes the socket file at `path` if no listener accepts connections on it anymore, e.g.
+/// because the process listening on it crashed.
+///
+/// Other files, as well as sockets of live listeners, are left untouched, making the subsequent
+/// bind fail.
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {}
+        Ok(_) => return Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    }
+
+    match std::os::unix::net::UnixStream::connect(path) {
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+            tracing::debug!(path=%path.display(), "Removing stale socket file");
+            std::fs::remove_file(path)
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Turns a `Multiaddr` containing a single `Unix` component into a path.
 ///
 /// Also returns an error if the path is not absolute, as we don't want to dial/listen on relative
@@ -260,7 +372,7 @@ mod tests {
     use futures::{channel::oneshot, prelude::*};
     use libp2p_core::{
         multiaddr::{Multiaddr, Protocol},
-        transport::ListenerId,
+        transport::{ListenerId, TransportEvent},
         Transport,
     };
     use std::{borrow::Cow, path::Path};
@@ -281,6 +393,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn percent_encoded_path_conversion() {
+        assert_eq!(
+            multiaddr_to_path(&"/unix/%2Ftmp%2Fnode.sock".parse().unwrap()),
+            Ok(Path::new("/tmp/node.sock").to_owned())
+        );
+        assert_eq!(
+            multiaddr_to_path(&"/unix/%2Ftmp%2Fa%20b%25c.sock".parse().unwrap()),
+            Ok(Path::new("/tmp/a b%c.sock").to_owned())
+        );
+
+        let addr = Multiaddr::from(Protocol::Unix("/tmp/a b%c/node.sock".into()));
+        assert_eq!(addr.to_string().parse::<Multiaddr>().unwrap(), addr);
+    }
+
     #[test]
     fn communicating_between_dialer_and_listener() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -324,17 +451,83 @@ mod tests {
     }
 
     #[test]
-    #[ignore] // TODO: for the moment unix addresses fail to parse
+    fn removes_socket_file_when_listener_closes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let socket = temp_dir.path().join("socket");
+        let addr = Multiaddr::from(Protocol::Unix(Cow::Owned(
+            socket.to_string_lossy().into_owned(),
+        )));
+
+        async_std::task::block_on(async move {
+            let mut transport = UdsConfig::new().boxed();
+            let listener_id = ListenerId::next();
+            transport.listen_on(listener_id, addr).unwrap();
+            transport
+                .select_next_some()
+                .await
+                .into_new_address()
+                .expect("listen address");
+            assert!(socket.exists());
+
+            assert!(transport.remove_listener(listener_id));
+            match transport.select_next_some().await {
+                TransportEvent::ListenerClosed {
+                    listener_id: id, ..
+                } => {
+                    assert_eq!(id, listener_id)
+                }
+                e => panic!("Unexpected event: {e:?}"),
+            }
+            assert!(!socket.exists());
+        });
+    }
+
+    #[test]
+    fn replaces_stale_socket_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let socket = temp_dir.path().join("socket");
+        let addr = Multiaddr::from(Protocol::Unix(Cow::Owned(
+            socket.to_string_lossy().into_owned(),
+        )));
+
+        // Leave a socket file behind without anyone listening on it.
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        assert!(socket.exists());
+
+        async_std::task::block_on(async move {
+            let mut transport = UdsConfig::new().boxed();
+            transport
+                .listen_on(ListenerId::next(), addr.clone())
+                .unwrap();
+            transport
+                .select_next_some()
+                .await
+                .into_new_address()
+                .expect("listen address");
+
+            // The socket of a live listener is not replaced.
+            let mut other = UdsConfig::new().boxed();
+            other.listen_on(ListenerId::next(), addr).unwrap();
+            assert!(matches!(
+                other.select_next_some().await,
+                TransportEvent::ListenerClosed { reason: Err(_), .. }
+            ));
+        });
+    }
+
+    #[test]
     fn larger_addr_denied() {
         let mut uds = UdsConfig::new();
 
-        let addr = "/unix//foo/bar".parse::<Multiaddr>().unwrap();
+        let addr = "/unix/%2Ffoo%2Fbar/tcp/1".parse::<Multiaddr>().unwrap();
         assert!(uds.listen_on(ListenerId::next(), addr).is_err());
     }
 
     #[test]
-    #[ignore] // TODO: for the moment unix addresses fail to parse
     fn relative_addr_denied() {
-        assert!("/unix/./foo/bar".parse::<Multiaddr>().is_err());
+        let mut uds = UdsConfig::new();
+
+        let addr = "/unix/.%2Ffoo%2Fbar".parse::<Multiaddr>().unwrap();
+        assert!(uds.listen_on(ListenerId::next(), addr).is_err());
     }
 }
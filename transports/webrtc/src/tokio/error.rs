@@ -18,6 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use crate::tokio::fingerprint::Fingerprint;
 use libp2p_identity::PeerId;
 use thiserror::Error;
 
@@ -34,6 +35,15 @@ pub enum Error {
     // Authentication errors.
     #[error("invalid peer ID (expected {expected}, got {got})")]
     InvalidPeerID { expected: PeerId, got: PeerId },
+    #[error(
+        "certificate hash mismatch (expected {}, got {})",
+        .expected.to_sdp_format(),
+        .actual.to_sdp_format()
+    )]
+    CertHashMismatch {
+        expected: Fingerprint,
+        actual: Fingerprint,
+    },
 
     #[error("no active listeners, can not dial without a previous listen")]
     NoListeners,
@@ -76,6 +76,10 @@ impl Fingerprint {
         self.0.algorithm()
     }
 
+    pub(crate) fn from_inner(fp: libp2p_webrtc_utils::Fingerprint) -> Self {
+        Self(fp)
+    }
+
     pub(crate) fn into_inner(self) -> libp2p_webrtc_utils::Fingerprint {
         self.0
     }
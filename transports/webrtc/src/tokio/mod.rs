@@ -23,6 +23,7 @@ mod connection;
 mod error;
 mod fingerprint;
 mod req_res_chan;
+mod reuse_port;
 mod sdp;
 mod stream;
 mod transport;
@@ -0,0 +1,99 @@
+//! Sharing the UDP port of a WebRTC listener with a QUIC listener via `SO_REUSEPORT`.
+//!
+//! `SO_REUSEPORT` alone spreads incoming datagrams over the sockets bound to a port by the
+//! address of the remote, i.e. regardless of the protocol. On Linux, a classic BPF program is
+//! attached to the sockets of the port, routing STUN and DTLS datagrams to the WebRTC socket and
+//! all other datagrams, i.e. QUIC, to the other socket (see
+//! [RFC 7983](https://www.rfc-editor.org/rfc/rfc7983#section-7) and
+//! [RFC 9443](https://www.rfc-editor.org/rfc/rfc9443#section-3)).
+
+use socket2::{Domain, Socket, Type};
+use std::{io, net::SocketAddr};
+
+/// Binds a UDP socket with `SO_REUSEPORT` to the given address.
+pub(crate) fn bind(addr: SocketAddr) -> io::Result<std::net::UdpSocket> {
+    // Sockets sharing a port are indexed in the order they were bound. Probe whether another
+    // socket is bound to the port already, as the index of the WebRTC socket depends on it.
+    let is_first = addr.port() == 0 || new_socket(addr)?.bind(&addr.into()).is_ok();
+
+    let socket = new_socket(addr)?;
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    socket.set_reuse_port(true)?;
+    socket.bind(&addr.into())?;
+
+    #[cfg(target_os = "linux")]
+    if addr.port() != 0 {
+        attach_demux_program(&socket, if is_first { 0 } else { 1 })?;
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = is_first;
+
+    Ok(socket.into())
+}
+
+fn new_socket(addr: SocketAddr) -> io::Result<Socket> {
+    let socket = Socket::new(
+        Domain::for_address(addr),
+        Type::DGRAM,
+        Some(socket2::Protocol::UDP),
+    )?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+
+    Ok(socket)
+}
+
+/// Attaches a program to the sockets sharing the port of `socket`, selecting the socket at
+/// `webrtc_index` for STUN and DTLS datagrams and the other one for all remaining datagrams.
+#[cfg(target_os = "linux")]
+fn attach_demux_program(socket: &Socket, webrtc_index: u32) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    const BPF_LD_B_ABS: u32 = libc::BPF_LD | libc::BPF_B | libc::BPF_ABS;
+    const BPF_JMP_JGT_K: u32 = libc::BPF_JMP | libc::BPF_JGT | libc::BPF_K;
+    const BPF_JMP_JGE_K: u32 = libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K;
+    const BPF_RET_K: u32 = libc::BPF_RET | libc::BPF_K;
+
+    let other_index = 1 - webrtc_index;
+    let instruction = |code: u32, jt, k| libc::sock_filter {
+        code: code as u16,
+        jt,
+        jf: 0,
+        k,
+    };
+    // The data of the program starts at the UDP payload.
+    let mut instructions = [
+        // Load the first byte of the payload.
+        instruction(BPF_LD_B_ABS, 0, 0),
+        // 64..=255: QUIC.
+        instruction(BPF_JMP_JGT_K, 3, 63),
+        // 20..=63: DTLS.
+        instruction(BPF_JMP_JGE_K, 1, 20),
+        // 4..=19: neither STUN nor DTLS, 0..=3: STUN.
+        instruction(BPF_JMP_JGT_K, 1, 3),
+        instruction(BPF_RET_K, 0, webrtc_index),
+        instruction(BPF_RET_K, 0, other_index),
+    ];
+    let program = libc::sock_fprog {
+        len: instructions.len() as u16,
+        filter: instructions.as_mut_ptr(),
+    };
+
+    // SAFETY: `program` points to `instructions`, which outlive the call. The kernel copies the
+    // instructions before returning.
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_ATTACH_REUSEPORT_CBPF,
+            &program as *const libc::sock_fprog as *const libc::c_void,
+            std::mem::size_of::<libc::sock_fprog>() as libc::socklen_t,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
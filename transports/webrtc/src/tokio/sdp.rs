@@ -18,8 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-pub(crate) use libp2p_webrtc_utils::sdp::random_ufrag;
-use libp2p_webrtc_utils::sdp::render_description;
+use libp2p_webrtc_utils::sdp::{random_ufrag, render_description};
 use libp2p_webrtc_utils::Fingerprint;
 use std::net::SocketAddr;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
@@ -38,6 +37,41 @@ pub(crate) fn answer(
     .unwrap()
 }
 
+/// Prefix of the ufrag, according to the spec.
+const UFRAG_PREFIX: &str = "libp2p+webrtc+v1/";
+
+/// Number of hex characters of the certhash hint at the start of the random part of the ufrag.
+const CERTHASH_HINT_LEN: usize = 16;
+
+/// Generates a random ufrag whose random part starts with a hint of the `/certhash` of the
+/// dialed address.
+///
+/// This allows a listener with several certificates to present the one that was dialed, see
+/// [`ufrag_matches_certhash`]. To any other listener, the ufrag is random.
+pub(crate) fn ufrag_for_certhash(server_fingerprint: Fingerprint) -> String {
+    let ufrag = random_ufrag();
+    let hint = certhash_hint(server_fingerprint);
+
+    format!(
+        "{UFRAG_PREFIX}{hint}{}",
+        &ufrag[UFRAG_PREFIX.len() + hint.len()..]
+    )
+}
+
+/// Whether the given ufrag was generated by [`ufrag_for_certhash`] for the given fingerprint.
+pub(crate) fn ufrag_matches_certhash(ufrag: &str, fingerprint: Fingerprint) -> bool {
+    ufrag
+        .strip_prefix(UFRAG_PREFIX)
+        .is_some_and(|random| random.starts_with(&certhash_hint(fingerprint)))
+}
+
+fn certhash_hint(fingerprint: Fingerprint) -> String {
+    let mut hint = hex::encode(fingerprint.to_multihash().digest());
+    hint.truncate(CERTHASH_HINT_LEN);
+
+    hint
+}
+
 /// Creates the SDP offer used by the server.
 ///
 /// Certificate verification is disabled which is why we hardcode a dummy fingerprint here.
@@ -28,6 +28,7 @@ use libp2p_identity as identity;
 use libp2p_identity::PeerId;
 use webrtc::peer_connection::configuration::RTCConfiguration;
 
+use std::collections::VecDeque;
 use std::net::IpAddr;
 use std::{
     io,
@@ -41,16 +42,25 @@ use crate::tokio::{
     connection::Connection,
     error::Error,
     fingerprint::Fingerprint,
+    sdp,
     udp_mux::{UDPMuxEvent, UDPMuxNewAddr},
     upgrade,
 };
 
 /// A WebRTC transport with direct p2p communication (without a STUN server).
+///
+/// # Certificate rotation
+///
+/// Further certificates can be added via [`Transport::add_certificate`], e.g. to keep dials using
+/// the `/certhash` of the previous certificate working during a rollover. Each listen address is
+/// reported once per certificate, with the `/certhash` of that certificate.
 pub struct Transport {
     /// The config which holds this peer's keys and certificate.
     config: Config,
     /// All the active listeners.
     listeners: SelectAll<ListenStream>,
+    /// Whether to set `SO_REUSEPORT` on the sockets of listeners.
+    reuse_port: bool,
 }
 
 impl Transport {
@@ -70,6 +80,64 @@ impl Transport {
         Self {
             config: Config::new(id_keys, certificate),
             listeners: SelectAll::new(),
+            reuse_port: false,
+        }
+    }
+
+    /// Whether to set `SO_REUSEPORT` on the UDP sockets of listeners, allowing a QUIC listener to
+    /// bind to the same UDP port, e.g. a `libp2p_quic::tokio::Transport` with
+    /// `libp2p_quic::Config::reuse_port` enabled.
+    ///
+    /// On Linux, incoming STUN and DTLS datagrams are routed to the WebRTC listener and all other
+    /// datagrams to the QUIC listener, regardless of which of the two listens first. On other
+    /// platforms the datagrams are not routed by protocol, which makes sharing a port unreliable.
+    /// At most one other listener may share the port.
+    ///
+    /// Disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use libp2p_identity as identity;
+    /// use rand::thread_rng;
+    /// use libp2p_webrtc::tokio::{Transport, Certificate};
+    ///
+    /// let id_keys = identity::Keypair::generate_ed25519();
+    /// let transport = Transport::new(id_keys, Certificate::generate(&mut thread_rng()).unwrap())
+    ///     .with_reuse_port(true);
+    /// ```
+    pub fn with_reuse_port(mut self, reuse_port: bool) -> Self {
+        self.reuse_port = reuse_port;
+        self
+    }
+
+    /// Adds a certificate to present to remotes, next to the ones already added.
+    ///
+    /// All listen addresses are reported again with the `/certhash` of the new certificate. When
+    /// accepting a connection, the certificate matching the `/certhash` of the address dialed by
+    /// the remote is presented. Remotes that do not reveal the dialed `/certhash`, e.g. browsers,
+    /// and remotes dialed by this transport are presented the most recently added certificate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use libp2p_identity as identity;
+    /// use rand::thread_rng;
+    /// use libp2p_webrtc::tokio::{Transport, Certificate};
+    ///
+    /// let id_keys = identity::Keypair::generate_ed25519();
+    /// let mut transport =
+    ///     Transport::new(id_keys, Certificate::generate(&mut thread_rng()).unwrap());
+    ///
+    /// transport.add_certificate(Certificate::generate(&mut thread_rng()).unwrap());
+    /// ```
+    pub fn add_certificate(&mut self, certificate: Certificate) {
+        if !self.config.add_certificate(certificate.clone()) {
+            return;
+        }
+
+        for listener in self.listeners.iter_mut() {
+            listener.add_certificate(certificate.clone());
         }
     }
 }
@@ -87,7 +155,7 @@ impl libp2p_core::Transport for Transport {
     ) -> Result<(), TransportError<Self::Error>> {
         let socket_addr =
             parse_webrtc_listen_addr(&addr).ok_or(TransportError::MultiaddrNotSupported(addr))?;
-        let udp_mux = UDPMuxNewAddr::listen_on(socket_addr)
+        let udp_mux = UDPMuxNewAddr::listen_on(socket_addr, self.reuse_port)
             .map_err(|io| TransportError::Other(Error::Io(io)))?;
 
         self.listeners.push(
@@ -125,8 +193,8 @@ impl libp2p_core::Transport for Transport {
             return Err(TransportError::MultiaddrNotSupported(addr));
         }
 
-        let config = self.config.clone();
-        let client_fingerprint = self.config.fingerprint;
+        let (rtc_config, client_fingerprint) = self.config.rtc_configuration(None);
+        let id_keys = self.config.id_keys.clone();
         let udp_mux = self
             .listeners
             .iter()
@@ -138,11 +206,11 @@ impl libp2p_core::Transport for Transport {
         Ok(async move {
             let (peer_id, connection) = upgrade::outbound(
                 sock_addr,
-                config.inner,
+                rtc_config,
                 udp_mux,
                 client_fingerprint.into_inner(),
                 server_fingerprint,
-                config.id_keys,
+                id_keys,
             )
             .await?;
 
@@ -196,11 +264,15 @@ struct ListenStream {
     /// `None` if the socket is only listening on a single interface.
     if_watcher: Option<IfWatcher>,
 
-    /// Pending event to reported.
-    pending_event: Option<<Self as Stream>::Item>,
+    /// The IP addresses the listener is reachable at.
+    ips: Vec<IpAddr>,
+
+    /// Pending events to reported.
+    pending_events: VecDeque<<Self as Stream>::Item>,
 
-    /// The stream must be awaken after it has been closed to deliver the last event.
-    close_listener_waker: Option<Waker>,
+    /// The stream must be awaken after it has been closed or a certificate was added to deliver
+    /// the pending events.
+    waker: Option<Waker>,
 }
 
 impl ListenStream {
@@ -208,30 +280,69 @@ impl ListenStream {
     fn new(listener_id: ListenerId, config: Config, udp_mux: UDPMuxNewAddr) -> io::Result<Self> {
         let listen_addr = udp_mux.listen_addr();
 
-        let if_watcher;
-        let pending_event;
-        if listen_addr.ip().is_unspecified() {
-            if_watcher = Some(IfWatcher::new()?);
-            pending_event = None;
+        let if_watcher = if listen_addr.ip().is_unspecified() {
+            Some(IfWatcher::new()?)
         } else {
-            if_watcher = None;
-            let ma = socketaddr_to_multiaddr(&listen_addr, Some(config.fingerprint));
-            pending_event = Some(TransportEvent::NewAddress {
-                listener_id,
-                listen_addr: ma,
-            })
-        }
+            None
+        };
 
-        Ok(ListenStream {
+        let mut listener = ListenStream {
             listener_id,
             listen_addr,
             config,
             udp_mux,
             report_closed: None,
             if_watcher,
-            pending_event,
-            close_listener_waker: None,
-        })
+            ips: Vec::new(),
+            pending_events: VecDeque::new(),
+            waker: None,
+        };
+        if !listen_addr.ip().is_unspecified() {
+            listener.add_ip(listen_addr.ip());
+        }
+
+        Ok(listener)
+    }
+
+    /// Reports the listen addresses of the given IP address, one per certificate.
+    fn add_ip(&mut self, ip: IpAddr) {
+        self.ips.push(ip);
+        for listen_addr in self.listen_multiaddresses(ip) {
+            self.pending_events.push_back(TransportEvent::NewAddress {
+                listener_id: self.listener_id,
+                listen_addr,
+            });
+        }
+    }
+
+    /// Reports the listen addresses of the given IP address as expired.
+    fn remove_ip(&mut self, ip: IpAddr) {
+        self.ips.retain(|i| *i != ip);
+        for listen_addr in self.listen_multiaddresses(ip) {
+            self.pending_events
+                .push_back(TransportEvent::AddressExpired {
+                    listener_id: self.listener_id,
+                    listen_addr,
+                });
+        }
+    }
+
+    /// Adds a certificate, reporting the listen addresses with its `/certhash`.
+    fn add_certificate(&mut self, certificate: Certificate) {
+        let fingerprint = certificate.fingerprint();
+        self.config.add_certificate(certificate);
+
+        for ip in &self.ips {
+            let socket_addr = SocketAddr::new(*ip, self.listen_addr.port());
+            self.pending_events.push_back(TransportEvent::NewAddress {
+                listener_id: self.listener_id,
+                listen_addr: socketaddr_to_multiaddr(&socket_addr, Some(fingerprint)),
+            });
+        }
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
     }
 
     /// Report the listener as closed in a [`TransportEvent::ListenerClosed`] and
@@ -249,14 +360,15 @@ impl ListenStream {
                     }));
 
                 // Wake the stream to deliver the last event.
-                if let Some(waker) = self.close_listener_waker.take() {
+                if let Some(waker) = self.waker.take() {
                     waker.wake();
                 }
             }
         }
     }
 
-    fn poll_if_watcher(&mut self, cx: &mut Context<'_>) -> Poll<<Self as Stream>::Item> {
+    /// Polls the [`IfWatcher`], returning [`Poll::Ready`] once events are pending.
+    fn poll_if_watcher(&mut self, cx: &mut Context<'_>) -> Poll<()> {
         let Some(if_watcher) = self.if_watcher.as_mut() else {
             return Poll::Pending;
         };
@@ -268,10 +380,8 @@ impl ListenStream {
                     if self.listen_addr.is_ipv4() == ip.is_ipv4()
                         || self.listen_addr.is_ipv6() == ip.is_ipv6()
                     {
-                        return Poll::Ready(TransportEvent::NewAddress {
-                            listener_id: self.listener_id,
-                            listen_addr: self.listen_multiaddress(ip),
-                        });
+                        self.add_ip(ip);
+                        return Poll::Ready(());
                     }
                 }
                 Ok(IfEvent::Down(inet)) => {
@@ -279,17 +389,17 @@ impl ListenStream {
                     if self.listen_addr.is_ipv4() == ip.is_ipv4()
                         || self.listen_addr.is_ipv6() == ip.is_ipv6()
                     {
-                        return Poll::Ready(TransportEvent::AddressExpired {
-                            listener_id: self.listener_id,
-                            listen_addr: self.listen_multiaddress(ip),
-                        });
+                        self.remove_ip(ip);
+                        return Poll::Ready(());
                     }
                 }
                 Err(err) => {
-                    return Poll::Ready(TransportEvent::ListenerError {
-                        listener_id: self.listener_id,
-                        error: Error::Io(err),
-                    });
+                    self.pending_events
+                        .push_back(TransportEvent::ListenerError {
+                            listener_id: self.listener_id,
+                            error: Error::Io(err),
+                        });
+                    return Poll::Ready(());
                 }
             }
         }
@@ -297,11 +407,15 @@ impl ListenStream {
         Poll::Pending
     }
 
-    /// Constructs a [`Multiaddr`] for the given IP address that represents our listen address.
-    fn listen_multiaddress(&self, ip: IpAddr) -> Multiaddr {
+    /// Constructs the [`Multiaddr`]s for the given IP address that represent our listen address,
+    /// one per certificate.
+    fn listen_multiaddresses(&self, ip: IpAddr) -> Vec<Multiaddr> {
         let socket_addr = SocketAddr::new(ip, self.listen_addr.port());
 
-        socketaddr_to_multiaddr(&socket_addr, Some(self.config.fingerprint))
+        self.config
+            .fingerprints()
+            .map(|fingerprint| socketaddr_to_multiaddr(&socket_addr, Some(fingerprint)))
+            .collect()
     }
 }
 
@@ -310,7 +424,7 @@ impl Stream for ListenStream {
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         loop {
-            if let Some(event) = self.pending_event.take() {
+            if let Some(event) = self.pending_events.pop_front() {
                 return Poll::Ready(Some(event));
             }
 
@@ -321,22 +435,23 @@ impl Stream for ListenStream {
                 return Poll::Ready(closed.take());
             }
 
-            if let Poll::Ready(event) = self.poll_if_watcher(cx) {
-                return Poll::Ready(Some(event));
+            if self.poll_if_watcher(cx).is_ready() {
+                continue;
             }
 
             // Poll UDP muxer for new addresses or incoming data for streams.
             match self.udp_mux.poll(cx) {
                 Poll::Ready(UDPMuxEvent::NewAddr(new_addr)) => {
-                    let local_addr =
-                        socketaddr_to_multiaddr(&self.listen_addr, Some(self.config.fingerprint));
+                    let (rtc_config, fingerprint) =
+                        self.config.rtc_configuration(Some(&new_addr.ufrag));
+                    let local_addr = socketaddr_to_multiaddr(&self.listen_addr, Some(fingerprint));
                     let send_back_addr = socketaddr_to_multiaddr(&new_addr.addr, None);
 
                     let upgrade = upgrade::inbound(
                         new_addr.addr,
-                        self.config.inner.clone(),
+                        rtc_config,
                         self.udp_mux.udp_mux_handle(),
-                        self.config.fingerprint.into_inner(),
+                        fingerprint.into_inner(),
                         new_addr.ufrag,
                         self.config.id_keys.clone(),
                     )
@@ -356,18 +471,18 @@ impl Stream for ListenStream {
                 Poll::Pending => {}
             }
 
-            self.close_listener_waker = Some(cx.waker().clone());
+            self.waker = Some(cx.waker().clone());
 
             return Poll::Pending;
         }
     }
 }
 
-/// A config which holds peer's keys and a x509Cert used to authenticate WebRTC communications.
+/// A config which holds peer's keys and the x509Certs used to authenticate WebRTC communications.
 #[derive(Clone)]
 struct Config {
-    inner: RTCConfiguration,
-    fingerprint: Fingerprint,
+    /// The certificates along with their fingerprints, the most recently added one last.
+    certificates: Vec<(Certificate, Fingerprint)>,
     id_keys: identity::Keypair,
 }
 
@@ -378,12 +493,46 @@ impl Config {
 
         Self {
             id_keys,
-            inner: RTCConfiguration {
-                certificates: vec![certificate.to_rtc_certificate()],
-                ..RTCConfiguration::default()
-            },
-            fingerprint,
+            certificates: vec![(certificate, fingerprint)],
+        }
+    }
+
+    /// Adds the given certificate, returning `false` if it was already added.
+    fn add_certificate(&mut self, certificate: Certificate) -> bool {
+        let fingerprint = certificate.fingerprint();
+        if self.fingerprints().any(|f| f == fingerprint) {
+            return false;
         }
+
+        self.certificates.push((certificate, fingerprint));
+        true
+    }
+
+    fn fingerprints(&self) -> impl Iterator<Item = Fingerprint> + '_ {
+        self.certificates
+            .iter()
+            .map(|(_, fingerprint)| *fingerprint)
+    }
+
+    /// Returns the [`RTCConfiguration`] presenting the certificate whose `/certhash` the remote
+    /// dialed according to its `remote_ufrag`, or else the most recently added certificate, along
+    /// with the fingerprint of the certificate.
+    fn rtc_configuration(&self, remote_ufrag: Option<&str>) -> (RTCConfiguration, Fingerprint) {
+        let (certificate, fingerprint) = remote_ufrag
+            .and_then(|ufrag| {
+                self.certificates.iter().find(|(_, fingerprint)| {
+                    sdp::ufrag_matches_certhash(ufrag, fingerprint.into_inner())
+                })
+            })
+            .or(self.certificates.last())
+            .expect("at least one certificate");
+
+        let rtc_config = RTCConfiguration {
+            certificates: vec![certificate.to_rtc_certificate()],
+            ..RTCConfiguration::default()
+        };
+
+        (rtc_config, *fingerprint)
     }
 }
 
@@ -432,6 +581,7 @@ mod tests {
     use super::*;
     use futures::future::poll_fn;
     use libp2p_core::Transport as _;
+    use libp2p_webrtc_utils::sdp::random_ufrag;
     use rand::thread_rng;
     use std::net::Ipv6Addr;
 
@@ -491,6 +641,29 @@ mod tests {
         assert!(maybe_addr.is_none())
     }
 
+    #[test]
+    fn presents_certificate_of_dialed_certhash() {
+        let old_certificate = Certificate::generate(&mut thread_rng()).unwrap();
+        let new_certificate = Certificate::generate(&mut thread_rng()).unwrap();
+        let mut config = Config::new(
+            identity::Keypair::generate_ed25519(),
+            old_certificate.clone(),
+        );
+        assert!(config.add_certificate(new_certificate.clone()));
+        assert!(!config.add_certificate(new_certificate.clone()));
+
+        let old_ufrag = sdp::ufrag_for_certhash(old_certificate.fingerprint().into_inner());
+        let (_, fingerprint) = config.rtc_configuration(Some(&old_ufrag));
+        assert_eq!(fingerprint, old_certificate.fingerprint());
+
+        let new_ufrag = sdp::ufrag_for_certhash(new_certificate.fingerprint().into_inner());
+        let (_, fingerprint) = config.rtc_configuration(Some(&new_ufrag));
+        assert_eq!(fingerprint, new_certificate.fingerprint());
+
+        let (_, fingerprint) = config.rtc_configuration(Some(&random_ufrag()));
+        assert_eq!(fingerprint, new_certificate.fingerprint());
+    }
+
     #[tokio::test]
     async fn close_listener() {
         let id_keys = identity::Keypair::generate_ed25519();
@@ -43,7 +43,7 @@ use std::{
     task::{Context, Poll},
 };
 
-use crate::tokio::req_res_chan;
+use crate::tokio::{req_res_chan, reuse_port};
 
 const RECEIVE_MTU: usize = 8192;
 
@@ -100,8 +100,12 @@ pub(crate) struct UDPMuxNewAddr {
 }
 
 impl UDPMuxNewAddr {
-    pub(crate) fn listen_on(addr: SocketAddr) -> Result<Self, io::Error> {
-        let std_sock = std::net::UdpSocket::bind(addr)?;
+    pub(crate) fn listen_on(addr: SocketAddr, reuse_port: bool) -> Result<Self, io::Error> {
+        let std_sock = if reuse_port {
+            reuse_port::bind(addr)?
+        } else {
+            std::net::UdpSocket::bind(addr)?
+        };
         std_sock.set_nonblocking(true)?;
 
         let tokio_socket = UdpSocket::from_std(std_sock)?;
@@ -37,7 +37,7 @@ use webrtc::ice::udp_network::UDPNetwork;
 use webrtc::peer_connection::configuration::RTCConfiguration;
 use webrtc::peer_connection::RTCPeerConnection;
 
-use crate::tokio::sdp::random_ufrag;
+use crate::tokio::sdp::ufrag_for_certhash;
 use crate::tokio::{error::Error, sdp, stream::Stream, Connection};
 
 /// Creates a new outbound WebRTC connection.
@@ -51,7 +51,8 @@ pub(crate) async fn outbound(
 ) -> Result<(PeerId, Connection), Error> {
     tracing::debug!(address=%addr, "new outbound connection to address");
 
-    let (peer_connection, ufrag) = new_outbound_connection(addr, config, udp_mux).await?;
+    let (peer_connection, ufrag) =
+        new_outbound_connection(addr, config, udp_mux, server_fingerprint).await?;
 
     let offer = peer_connection.create_offer(None).await?;
     tracing::debug!(offer=%offer.sdp, "created SDP offer for outbound connection");
@@ -62,6 +63,17 @@ pub(crate) async fn outbound(
     peer_connection.set_remote_description(answer).await?; // This will start the gathering of ICE candidates.
 
     let data_channel = create_substream_for_noise_handshake(&peer_connection).await?;
+
+    // The fingerprint is verified here rather than by the DTLS layer (see
+    // `new_outbound_connection`), to report a mismatch instead of a failed Noise handshake.
+    let actual_fingerprint = get_remote_fingerprint(&peer_connection).await;
+    if actual_fingerprint != server_fingerprint {
+        return Err(Error::CertHashMismatch {
+            expected: crate::tokio::Fingerprint::from_inner(server_fingerprint),
+            actual: crate::tokio::Fingerprint::from_inner(actual_fingerprint),
+        });
+    }
+
     let peer_id = noise::outbound(
         id_keys,
         data_channel,
@@ -111,9 +123,13 @@ async fn new_outbound_connection(
     addr: SocketAddr,
     config: RTCConfiguration,
     udp_mux: Arc<dyn UDPMux + Send + Sync>,
+    server_fingerprint: Fingerprint,
 ) -> Result<(RTCPeerConnection, String), Error> {
-    let ufrag = random_ufrag();
-    let se = setting_engine(udp_mux, &ufrag, addr);
+    let ufrag = ufrag_for_certhash(server_fingerprint);
+    let mut se = setting_engine(udp_mux, &ufrag, addr);
+    // The fingerprint of the remote certificate is checked against the `/certhash` of the dialed
+    // address once DTLS completed, see `outbound`.
+    se.disable_certificate_fingerprint_verification(true);
 
     let connection = APIBuilder::new()
         .with_setting_engine(se)
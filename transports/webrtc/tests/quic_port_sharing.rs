@@ -0,0 +1,126 @@
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::muxing::StreamMuxerBox;
+use libp2p_core::transport::{Boxed, ListenerId, TransportEvent};
+use libp2p_core::{Multiaddr, Transport};
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_webrtc as webrtc;
+use rand::thread_rng;
+use std::net::UdpSocket;
+
+#[tokio::test]
+async fn quic_and_webrtc_share_port_quic_first() {
+    quic_and_webrtc_share_port(true).await
+}
+
+#[tokio::test]
+async fn quic_and_webrtc_share_port_webrtc_first() {
+    quic_and_webrtc_share_port(false).await
+}
+
+async fn quic_and_webrtc_share_port(quic_first: bool) {
+    let port = UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let quic_addr: Multiaddr = format!("/ip4/127.0.0.1/udp/{port}/quic-v1")
+        .parse()
+        .unwrap();
+    let webrtc_addr: Multiaddr = format!("/ip4/127.0.0.1/udp/{port}/webrtc-direct")
+        .parse()
+        .unwrap();
+
+    let server_keypair = Keypair::generate_ed25519();
+    let server_peer_id = server_keypair.public().to_peer_id();
+    let mut server = quic_transport(&server_keypair, true)
+        .or_transport(webrtc_transport(&server_keypair, true))
+        .map(|either, _| either.into_inner())
+        .boxed();
+
+    let listen_addrs = if quic_first {
+        [quic_addr.clone(), webrtc_addr]
+    } else {
+        [webrtc_addr, quic_addr.clone()]
+    };
+    for addr in listen_addrs {
+        server.listen_on(ListenerId::next(), addr).unwrap();
+    }
+    let mut server_webrtc_addr = None;
+    for _ in 0..2 {
+        match server.next().await {
+            Some(TransportEvent::NewAddress { listen_addr, .. }) => {
+                if listen_addr.iter().any(|p| p == Protocol::WebRTCDirect) {
+                    server_webrtc_addr = Some(listen_addr);
+                }
+            }
+            e => panic!("Unexpected event: {e:?}"),
+        }
+    }
+    let server_webrtc_addr = server_webrtc_addr.unwrap();
+
+    let (connected_tx, mut connected_rx) = mpsc::channel(2);
+    tokio::spawn(async move {
+        loop {
+            if let TransportEvent::Incoming { upgrade, .. } = server.select_next_some().await {
+                let mut connected_tx = connected_tx.clone();
+                tokio::spawn(async move {
+                    let (peer_id, _connection) = upgrade.await.unwrap();
+                    connected_tx.send(peer_id).await.unwrap();
+                });
+            }
+        }
+    });
+
+    let quic_keypair = Keypair::generate_ed25519();
+    let mut quic_client = quic_transport(&quic_keypair, false);
+    let dial = quic_client.dial(quic_addr).unwrap();
+    tokio::spawn(quic_client.collect::<Vec<_>>());
+    let (peer_id, _quic_connection) = dial.await.unwrap();
+    assert_eq!(peer_id, server_peer_id);
+
+    let webrtc_keypair = Keypair::generate_ed25519();
+    let mut webrtc_client = webrtc_transport(&webrtc_keypair, false);
+    webrtc_client
+        .listen_on(
+            ListenerId::next(),
+            "/ip4/127.0.0.1/udp/0/webrtc-direct".parse().unwrap(),
+        )
+        .unwrap();
+    let dial = webrtc_client.dial(server_webrtc_addr).unwrap();
+    tokio::spawn(webrtc_client.collect::<Vec<_>>());
+    let (peer_id, _webrtc_connection) = dial.await.unwrap();
+    assert_eq!(peer_id, server_peer_id);
+
+    let mut connected = vec![
+        connected_rx.next().await.unwrap(),
+        connected_rx.next().await.unwrap(),
+    ];
+    connected.sort();
+    let mut expected = vec![
+        quic_keypair.public().to_peer_id(),
+        webrtc_keypair.public().to_peer_id(),
+    ];
+    expected.sort();
+    assert_eq!(connected, expected);
+}
+
+fn quic_transport(keypair: &Keypair, reuse_port: bool) -> Boxed<(PeerId, StreamMuxerBox)> {
+    let mut config = libp2p_quic::Config::new(keypair);
+    config.reuse_port = reuse_port;
+
+    libp2p_quic::tokio::Transport::new(config)
+        .map(|(p, c), _| (p, StreamMuxerBox::new(c)))
+        .boxed()
+}
+
+fn webrtc_transport(keypair: &Keypair, reuse_port: bool) -> Boxed<(PeerId, StreamMuxerBox)> {
+    webrtc::tokio::Transport::new(
+        keypair.clone(),
+        webrtc::tokio::Certificate::generate(&mut thread_rng()).unwrap(),
+    )
+    .with_reuse_port(reuse_port)
+    .map(|(p, c), _| (p, StreamMuxerBox::new(c)))
+    .boxed()
+}
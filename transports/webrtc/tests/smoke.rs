@@ -22,6 +22,7 @@ use futures::channel::mpsc;
 use futures::future::{BoxFuture, Either};
 use futures::stream::StreamExt;
 use futures::{future, ready, AsyncReadExt, AsyncWriteExt, FutureExt, SinkExt};
+use libp2p_core::multiaddr::Protocol;
 use libp2p_core::muxing::{StreamMuxerBox, StreamMuxerExt};
 use libp2p_core::transport::{Boxed, ListenerId, TransportEvent};
 use libp2p_core::{Multiaddr, Transport};
@@ -53,6 +54,117 @@ async fn smoke() {
     assert_eq!(b_connected, a_peer_id);
 }
 
+#[tokio::test]
+async fn dial_with_wrong_certhash_reports_mismatch() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let (_, mut a_transport) = create_transport();
+    let (_, mut b_transport) = create_transport();
+
+    let a_addr = start_listening(&mut a_transport, "/ip4/127.0.0.1/udp/0/webrtc-direct").await;
+    start_listening(&mut b_transport, "/ip4/127.0.0.1/udp/0/webrtc-direct").await;
+
+    let mut addr = a_addr.clone();
+    let Some(Protocol::Certhash(a_certhash)) = addr.pop() else {
+        panic!("Listen address without `/certhash`: {a_addr}");
+    };
+    let wrong_fingerprint = webrtc::tokio::Certificate::generate(&mut thread_rng())
+        .unwrap()
+        .fingerprint();
+    let addr = addr.with(Protocol::Certhash(wrong_fingerprint.to_multihash()));
+
+    let dial = b_transport.dial(addr).unwrap();
+    let drive_transports = async {
+        loop {
+            match future::select(a_transport.next(), b_transport.next()).await {
+                Either::Left((Some(TransportEvent::Incoming { upgrade, .. }), _)) => {
+                    tokio::spawn(upgrade);
+                }
+                Either::Left(_) | Either::Right(_) => {}
+            }
+        }
+    };
+    let error = match future::select(dial, Box::pin(drive_transports)).await {
+        Either::Left((result, _)) => result.unwrap_err(),
+        Either::Right(_) => unreachable!("transports are driven forever"),
+    };
+
+    let error = error
+        .into_inner()
+        .unwrap()
+        .downcast::<webrtc::tokio::Error>()
+        .unwrap();
+    match *error {
+        webrtc::tokio::Error::CertHashMismatch { expected, actual } => {
+            assert_eq!(expected, wrong_fingerprint);
+            assert_eq!(actual.to_multihash(), a_certhash);
+        }
+        e => panic!("Unexpected error: {e}"),
+    }
+}
+
+#[tokio::test]
+async fn dial_with_old_certhash_after_adding_certificate() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let a_keypair = generate_tls_keypair();
+    let a_peer_id = a_keypair.public().to_peer_id();
+    let old_certificate = webrtc::tokio::Certificate::generate(&mut thread_rng()).unwrap();
+    let new_certificate = webrtc::tokio::Certificate::generate(&mut thread_rng()).unwrap();
+    let mut a_transport = webrtc::tokio::Transport::new(a_keypair, old_certificate.clone());
+
+    a_transport
+        .listen_on(
+            ListenerId::next(),
+            "/ip4/127.0.0.1/udp/0/webrtc-direct".parse().unwrap(),
+        )
+        .unwrap();
+    let old_addr = next_new_address(&mut a_transport).await;
+    a_transport.add_certificate(new_certificate.clone());
+    let new_addr = next_new_address(&mut a_transport).await;
+
+    assert_eq!(
+        old_addr.iter().last(),
+        Some(Protocol::Certhash(
+            old_certificate.fingerprint().to_multihash()
+        ))
+    );
+    assert_eq!(
+        new_addr.iter().last(),
+        Some(Protocol::Certhash(
+            new_certificate.fingerprint().to_multihash()
+        ))
+    );
+
+    let mut a_transport = a_transport
+        .map(|(p, c), _| (p, StreamMuxerBox::new(c)))
+        .boxed();
+
+    // Dial each address from a different transport, as a second connection between the same two
+    // UDP sockets is not supported.
+    for addr in [old_addr, new_addr] {
+        let (b_peer_id, mut b_transport) = create_transport();
+        start_listening(&mut b_transport, "/ip4/127.0.0.1/udp/0/webrtc-direct").await;
+
+        let ((a_connected, _, _), (b_connected, _)) =
+            connect(&mut a_transport, &mut b_transport, addr).await;
+
+        assert_eq!(a_connected, b_peer_id);
+        assert_eq!(b_connected, a_peer_id);
+    }
+}
+
+async fn next_new_address(transport: &mut webrtc::tokio::Transport) -> Multiaddr {
+    match future::poll_fn(|cx| Pin::new(&mut *transport).poll(cx)).await {
+        TransportEvent::NewAddress { listen_addr, .. } => listen_addr,
+        e => panic!("{e:?}"),
+    }
+}
+
 // Note: This test should likely be ported to the muxer compliance test suite.
 #[test]
 fn concurrent_connections_and_streams_tokio() {
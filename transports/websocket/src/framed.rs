@@ -23,7 +23,6 @@ use either::Either;
 use futures::{future::BoxFuture, prelude::*, ready, stream::BoxStream};
 use futures_rustls::{client, rustls, server};
 use libp2p_core::{
-    connection::Endpoint,
     multiaddr::{Multiaddr, Protocol},
     transport::{ListenerId, TransportError, TransportEvent},
     Transport,
@@ -150,14 +149,22 @@ where
     }
 
     fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
-        self.do_dial(addr, Endpoint::Dialer)
+        self.do_dial(addr, Dial::Dialer)
     }
 
     fn dial_as_listener(
         &mut self,
         addr: Multiaddr,
     ) -> Result<Self::Dial, TransportError<Self::Error>> {
-        self.do_dial(addr, Endpoint::Listener)
+        self.do_dial(addr, Dial::Listener)
+    }
+
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.do_dial(addr, Dial::FromPort(port))
     }
 
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
@@ -263,7 +270,7 @@ where
     fn do_dial(
         &mut self,
         addr: Multiaddr,
-        role_override: Endpoint,
+        dial: Dial,
     ) -> Result<<Self as Transport>::Dial, TransportError<<Self as Transport>::Error>> {
         let mut addr = match parse_ws_dial_addr(addr) {
             Ok(addr) => addr,
@@ -282,9 +289,7 @@ where
 
         let future = async move {
             loop {
-                match Self::dial_once(transport.clone(), addr, tls_config.clone(), role_override)
-                    .await
-                {
+                match Self::dial_once(transport.clone(), addr, tls_config.clone(), dial).await {
                     Ok(Either::Left(redirect)) => {
                         if remaining_redirects == 0 {
                             tracing::debug!(%max_redirects, "Too many redirects");
@@ -307,13 +312,14 @@ where
         transport: Arc<Mutex<T>>,
         addr: WsAddress,
         tls_config: tls::Config,
-        role_override: Endpoint,
+        dial: Dial,
     ) -> Result<Either<String, Connection<T::Output>>, Error<T::Error>> {
         tracing::trace!(address=?addr, "Dialing websocket address");
 
-        let dial = match role_override {
-            Endpoint::Dialer => transport.lock().dial(addr.tcp_addr),
-            Endpoint::Listener => transport.lock().dial_as_listener(addr.tcp_addr),
+        let dial = match dial {
+            Dial::Dialer => transport.lock().dial(addr.tcp_addr),
+            Dial::FromPort(port) => transport.lock().dial_from_port(addr.tcp_addr, port),
+            Dial::Listener => transport.lock().dial_as_listener(addr.tcp_addr),
         }
         .map_err(|e| match e {
             TransportError::MultiaddrNotSupported(a) => Error::InvalidMultiaddr(a),
@@ -457,6 +463,15 @@ where
     }
 }
 
+/// How the underlying transport is dialed, see [`Transport::dial`],
+/// [`Transport::dial_from_port`] and [`Transport::dial_as_listener`].
+#[derive(Debug, Clone, Copy)]
+enum Dial {
+    Dialer,
+    FromPort(u16),
+    Listener,
+}
+
 #[derive(Debug)]
 struct WsAddress {
     host_port: String,
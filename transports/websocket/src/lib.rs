@@ -213,6 +213,14 @@ where
         self.transport.dial_as_listener(addr)
     }
 
+    fn dial_from_port(
+        &mut self,
+        addr: Multiaddr,
+        port: u16,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.transport.dial_from_port(addr, port)
+    }
+
     fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
         self.transport.address_translation(server, observed)
     }
@@ -308,6 +316,52 @@ mod tests {
         futures::executor::block_on(connect(a))
     }
 
+    #[test]
+    fn dial_from_port_binds_tcp_socket() {
+        futures::executor::block_on(async {
+            // Pick a free port, which is released again once the listener is dropped.
+            let port = std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port();
+
+            let mut ws_config = new_ws_config().boxed();
+            ws_config
+                .listen_on(
+                    ListenerId::next(),
+                    "/ip4/127.0.0.1/tcp/0/ws".parse().unwrap(),
+                )
+                .expect("listener");
+            let addr = ws_config
+                .next()
+                .await
+                .expect("no error")
+                .into_new_address()
+                .expect("listen address");
+
+            let inbound = async move {
+                let (conn, send_back_addr) = ws_config
+                    .select_next_some()
+                    .map(|ev| ev.into_incoming())
+                    .await
+                    .unwrap();
+                conn.await.map(|_| send_back_addr)
+            };
+
+            let outbound = WsConfig::new(tcp::async_io::Transport::new(
+                tcp::Config::default().port_reuse(true),
+            ))
+            .boxed()
+            .dial_from_port(addr, port)
+            .unwrap();
+
+            let (a, b) = futures::join!(inbound, outbound);
+            b.unwrap();
+            assert_eq!(a.unwrap().iter().nth(1), Some(Protocol::Tcp(port)));
+        })
+    }
+
     fn new_ws_config() -> WsConfig<tcp::async_io::Transport> {
         WsConfig::new(tcp::async_io::Transport::new(tcp::Config::default()))
     }